@@ -0,0 +1,135 @@
+use regex::Regex;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Generate the type stub of the package, vl_convert/__init__.pyi, from vl_convert.pyi.in.
+///
+/// The Literal types of the Vega-Lite versions, themes, and renderers that the module accepts
+/// are filled in from the Rust definitions and the vendored vega-themes module, so that the
+/// stub matches the features of the build. The stub is shipped in the wheel with the locales
+/// (see the include setting of pyproject.toml)
+fn main() {
+    let manifest_dir = PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap());
+    let rs_dir = manifest_dir.join("../vl-convert-rs");
+    let import_map_path = rs_dir.join("src/module_loader/import_map.rs");
+    let converter_path = rs_dir.join("src/converter.rs");
+    let template_path = manifest_dir.join("vl_convert.pyi.in");
+    for path in [&import_map_path, &converter_path, &template_path] {
+        println!("cargo:rerun-if-changed={}", path.display());
+    }
+
+    let import_map = read(&import_map_path);
+    let vl_versions = vl_version_spellings(&import_map);
+    let themes = theme_names(&rs_dir, &import_map);
+    let renderers = renderer_names(&read(&converter_path));
+
+    let stub = read(&template_path)
+        .replace("\r\n", "\n")
+        .replace("@VEGA_THEMES@", &literal("VegaThemes", themes))
+        .replace("@VL_VERSION@", &literal("VlVersion", vl_versions))
+        .replace("@RENDERER@", &literal("Renderer", renderers));
+
+    let package_dir = manifest_dir.join("vl_convert");
+    fs::create_dir_all(&package_dir).unwrap();
+    write_if_changed(&package_dir.join("__init__.pyi"), &stub);
+    write_if_changed(&package_dir.join("py.typed"), "");
+}
+
+fn read(path: &Path) -> String {
+    fs::read_to_string(path).unwrap_or_else(|err| panic!("Failed to read {path:?}: {err}"))
+}
+
+/// Write a file unless it already has `contents`, so that unchanged stubs keep their mtime
+fn write_if_changed(path: &Path, contents: &str) {
+    if fs::read_to_string(path).ok().as_deref() != Some(contents) {
+        fs::write(path, contents).unwrap_or_else(|err| panic!("Failed to write {path:?}: {err}"));
+    }
+}
+
+/// Spellings of the Vega-Lite versions in VL_VERSIONS, as accepted by VlVersion::from_str.
+/// Versions that VL_VERSIONS gates behind the vl_legacy feature are only included when the
+/// feature is enabled
+fn vl_version_spellings(import_map: &str) -> Vec<String> {
+    let legacy = std::env::var_os("CARGO_FEATURE_VL_LEGACY").is_some();
+    let list_re = Regex::new(r"(?s)pub const VL_VERSIONS: &\[VlVersion\] = &\[(.*?)\];").unwrap();
+    let entry_re =
+        Regex::new(r#"(#\[cfg\(feature = "vl_legacy"\)\]\s*)?VlVersion::(v[0-9_]+)"#).unwrap();
+    let list = &list_re
+        .captures(import_map)
+        .expect("Failed to find VL_VERSIONS in import_map.rs")[1];
+
+    let mut spellings = Vec::new();
+    for caps in entry_re.captures_iter(list) {
+        if caps.get(1).is_some() && !legacy {
+            continue;
+        }
+        let variant = &caps[2];
+        let arm_re = Regex::new(&format!(r#"((?:"[^"]+"\s*\|?\s*)+)=> Self::{variant},"#)).unwrap();
+        let arm = arm_re
+            .captures(import_map)
+            .unwrap_or_else(|| panic!("Failed to find the spellings of {variant}"));
+        spellings.extend(
+            arm[1]
+                .split('|')
+                .map(|s| s.trim().trim_matches('"').to_string()),
+        );
+    }
+    spellings
+}
+
+/// Names of the themes that the vendored vega-themes module exports, sorted like
+/// get_theme_names sorts them
+fn theme_names(rs_dir: &Path, import_map: &str) -> Vec<String> {
+    let vendor_dir = rs_dir.join("vendor/cdn.skypack.dev");
+    let path_re = Regex::new(r#"pub const VEGA_THEMES_PATH: &str =\s*"([^"]+)""#).unwrap();
+    let pin_path = &path_re
+        .captures(import_map)
+        .expect("Failed to find VEGA_THEMES_PATH in import_map.rs")[1];
+    // The pinned module re-exports the optimized module, which defines the themes
+    let pin = read(&vendor_dir.join(pin_path.trim_start_matches('/')));
+    let export_re = Regex::new(r"export \* from '(/-/[^']+)'").unwrap();
+    let module_path = &export_re
+        .captures(&pin)
+        .expect("Failed to find the vega-themes module")[1];
+    let module_file = vendor_dir.join(module_path.trim_start_matches('/'));
+    println!("cargo:rerun-if-changed={}", module_file.display());
+    let module = read(&module_file);
+
+    let exports_re = Regex::new(r"export\{([^}]*)\}").unwrap();
+    let exports = &exports_re
+        .captures_iter(&module)
+        .last()
+        .expect("Failed to find the exports of vega-themes")[1];
+    let mut names: Vec<String> = exports
+        .split(',')
+        .filter_map(|export| export.split(" as ").nth(1))
+        .map(|name| name.trim().to_string())
+        .filter(|name| name != "version" && name != "default")
+        .collect();
+    names.sort();
+    names
+}
+
+/// Names of the renderers of HTML output, as displayed by Renderer
+fn renderer_names(converter: &str) -> Vec<String> {
+    let re = Regex::new(r#"Renderer::\w+ => "(\w+)","#).unwrap();
+    let mut names: Vec<String> = re
+        .captures_iter(converter)
+        .map(|caps| caps[1].to_string())
+        .collect();
+    names.sort();
+    names.dedup();
+    names
+}
+
+/// Type alias definition of a Literal type, formatted like black formats the stub
+fn literal(alias: &str, values: Vec<String>) -> String {
+    let quoted: Vec<String> = values.iter().map(|v| format!("\"{v}\"")).collect();
+    let line = format!("Literal[{}]", quoted.join(", "));
+    if format!("    {alias}: TypeAlias = {line}").len() <= 88 {
+        line
+    } else {
+        let items: String = quoted.iter().map(|v| format!("        {v},\n")).collect();
+        format!("Literal[\n{items}    ]")
+    }
+}
@@ -1,20 +1,56 @@
 #![allow(clippy::too_many_arguments)]
 
-use pyo3::exceptions::PyValueError;
+use pyo3::exceptions::{PyFileNotFoundError, PyValueError};
 use pyo3::prelude::*;
-use pyo3::types::{PyBytes, PyDict};
+use pyo3::types::{PyBytes, PyDict, PyList, PyTuple};
 use pythonize::{depythonize, pythonize};
 use std::borrow::Cow;
+use std::collections::HashMap;
+use std::path::PathBuf;
 use std::str::FromStr;
-use std::sync::Mutex;
-use vl_convert_rs::converter::{FormatLocale, Renderer, TimeFormatLocale, VgOpts, VlOpts};
-use vl_convert_rs::html::bundle_vega_snippet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+use vl_convert_rs::anonymize::AnonymizeOptions;
+use vl_convert_rs::anyhow::anyhow;
+use vl_convert_rs::background::BackgroundColor;
+use vl_convert_rs::converter::{
+    ExportFormat, ExportRequest, FormatLocale, HtmlOpts, ImageFileOpts, InteractionStep, JpegOpts,
+    JpegResult, PdfOpts, PngOpts, PngResult, PpiMode, Renderer, ShortenOptions, SvgOpts,
+    SvgPostProcessor, SvgResult, TimeFormatLocale, VgOpts, VlOpts, DEFAULT_SHORTEN_RESPONSE_FIELD,
+    DEFAULT_SHORTEN_TIMEOUT,
+};
+use vl_convert_rs::direction::Direction;
+use vl_convert_rs::facets::FacetOpts;
+use vl_convert_rs::html::{
+    bundle_vega_snippet_with_language, get_script_tags as get_script_tags_rs,
+    get_vega_bundle as get_vega_bundle_rs, SnippetLanguage,
+};
+use vl_convert_rs::image_rendering::ImageRendering;
+use vl_convert_rs::log_level::LogLevel;
 use vl_convert_rs::module_loader::import_map::{
     VlVersion, VEGA_EMBED_VERSION, VEGA_THEMES_VERSION, VEGA_VERSION, VL_VERSIONS,
 };
-use vl_convert_rs::module_loader::{FORMATE_LOCALE_MAP, TIME_FORMATE_LOCALE_MAP};
+use vl_convert_rs::module_loader::{set_locale_dir, FORMATE_LOCALE_MAP, TIME_FORMATE_LOCALE_MAP};
+use vl_convert_rs::render_warnings::RenderWarning;
+use vl_convert_rs::request_hash::request_hash;
+use vl_convert_rs::resize::ResizeMode;
+use vl_convert_rs::resource_stats::get_last_resource_stats as get_last_resource_stats_rs;
+use vl_convert_rs::row_limit::RowLimitStrategy;
 use vl_convert_rs::serde_json;
-use vl_convert_rs::text::register_font_directory as register_font_directory_rs;
+use vl_convert_rs::spec_limits::{
+    get_spec_limits, parse_json_spec as parse_json_spec_rs, set_spec_limits as set_spec_limits_rs,
+    SpecLimits, DEFAULT_MAX_JSON_DEPTH, DEFAULT_MAX_SPEC_BYTES, DEFAULT_MAX_VIEWS,
+};
+use vl_convert_rs::suggestions::{
+    format_locale_names, time_format_locale_names, unknown_name_message,
+};
+use vl_convert_rs::suppress::Component;
+use vl_convert_rs::svg_diff::SvgCompareOptions;
+use vl_convert_rs::text::{
+    get_emoji_font as get_emoji_font_rs, measure_text_detailed,
+    register_font_directory as register_font_directory_rs, set_emoji_font as set_emoji_font_rs,
+};
 use vl_convert_rs::VlConverter as VlConverterRs;
 
 #[macro_use]
@@ -29,6 +65,30 @@ lazy_static! {
             .unwrap();
 }
 
+static COLLECT_RESOURCE_STATS: AtomicBool = AtomicBool::new(false);
+static COLLECT_DATASET_SUMMARY: AtomicBool = AtomicBool::new(false);
+static LOGGER_INSTALLED: AtomicBool = AtomicBool::new(false);
+
+/// Run a function with the shared converter.
+///
+/// The GIL is released while waiting for the converter and while the conversion runs, so that
+/// log messages from the converter's worker thread can be forwarded to Python's logging module
+/// without deadlocking
+fn with_converter<T, F>(f: F) -> T
+where
+    T: Send,
+    F: FnOnce(&mut VlConverterRs) -> T + Send,
+{
+    Python::with_gil(|py| {
+        py.allow_threads(|| {
+            let mut converter = VL_CONVERTER
+                .lock()
+                .expect("Failed to acquire lock on Vega-Lite converter");
+            f(&mut converter)
+        })
+    })
+}
+
 /// Convert a Vega-Lite spec to a Vega spec using a particular
 /// version of the Vega-Lite JavaScript library.
 ///
@@ -36,44 +96,95 @@ lazy_static! {
 ///     vl_spec (str | dict): Vega-Lite JSON specification string or dict
 ///     vl_version (str | None): Vega-Lite library version string (e.g. 'v5.15')
 ///         (default to latest)
-///     config (dict | None): Chart configuration object to apply during conversion
+///     config (dict | str | None): Chart configuration object to apply during conversion,
+///         or a JSON string or the path of a JSON file holding it (e.g. "~/config.json")
 ///     theme (str | None): Named theme (e.g. "dark") to apply during conversion
 ///     show_warnings (bool | None): Whether to print Vega-Lite compilation warnings (default false)
+///     preserve_int64 (bool | None): Whether to pass integers in inline data that JavaScript
+///         numbers can't represent exactly (beyond +/-2^53) through the conversion as strings,
+///         so that they're returned unchanged (default false)
+///     font (str | None): Font to use for all text: axis, legend, and header labels and
+///         titles, chart titles, and text marks. Config takes precedence
+///     font_size_scale (float | None): Factor to scale the default font sizes of all text by
+///     text_color (str | None): Color to use for all text
+///     config_overrides (dict | None): Config values keyed by dotted paths (e.g.
+///         "axis.labelFontSize"), which are set in order over config and theme
+///     direction (str | None): Writing direction to lay the chart out for, "ltr" or "rtl".
+///         "rtl" mirrors the layout for right-to-left locales: y axes and row headers are
+///         drawn on the right, legends on the left, and titles are anchored at the end.
+///         Orients and anchors set in the spec or config take precedence (default "ltr")
 /// Returns:
 ///     dict: Vega JSON specification dict
 #[pyfunction]
-#[pyo3(signature = (vl_spec, vl_version=None, config=None, theme=None, show_warnings=None))]
+#[pyo3(signature = (vl_spec, vl_version=None, config=None, theme=None, show_warnings=None, preserve_int64=None, font=None, font_size_scale=None, text_color=None, config_overrides=None, direction=None))]
 fn vegalite_to_vega(
     vl_spec: PyObject,
     vl_version: Option<&str>,
     config: Option<PyObject>,
     theme: Option<String>,
     show_warnings: Option<bool>,
+    preserve_int64: Option<bool>,
+    font: Option<String>,
+    font_size_scale: Option<f32>,
+    text_color: Option<String>,
+    config_overrides: Option<PyObject>,
+    direction: Option<String>,
 ) -> PyResult<PyObject> {
+    let theme = parse_option_theme(theme)?;
     let vl_spec = parse_json_spec(vl_spec)?;
-    let config = config.and_then(|c| parse_json_spec(c).ok());
+    let config = parse_option_config(config)?;
+    let config_overrides = parse_option_config_overrides(config_overrides)?;
+    let direction = parse_option_direction(direction)?;
 
-    let vl_version = if let Some(vl_version) = vl_version {
-        VlVersion::from_str(vl_version)?
-    } else {
-        Default::default()
-    };
+    let vl_version = parse_vl_version(vl_version)?;
 
-    let mut converter = VL_CONVERTER
-        .lock()
-        .expect("Failed to acquire lock on Vega-Lite converter");
-    let vega_spec = match PYTHON_RUNTIME.block_on(converter.vegalite_to_vega(
-        vl_spec,
-        VlOpts {
-            vl_version,
-            config,
-            theme,
-            show_warnings: show_warnings.unwrap_or(false),
-            allowed_base_urls: None,
-            format_locale: None,
-            time_format_locale: None,
-        },
-    )) {
+    let vega_spec = match with_converter(|converter| {
+        PYTHON_RUNTIME.block_on(converter.vegalite_to_vega(
+            vl_spec,
+            VlOpts {
+                vl_version,
+                config,
+                theme,
+                show_warnings: show_warnings.unwrap_or(false),
+                allowed_base_urls: None,
+                format_locale: None,
+                time_format_locale: None,
+                collect_resource_stats: collect_resource_stats(),
+                collect_dataset_summary: collect_dataset_summary(),
+                fetch_retries: None,
+                fetch_retry_backoff_ms: None,
+                interaction_script: None,
+                preserve_int64: preserve_int64.unwrap_or(false),
+                base_url: None,
+                allow_local_data: false,
+                lenient: false,
+                font,
+                font_size_scale,
+                text_color,
+                config_overrides,
+                direction,
+                generic_font_mapping: None,
+                container_width: None,
+                container_height: None,
+                strict_sizing: false,
+                width: None,
+                height: None,
+                resize_mode: ResizeMode::default(),
+                min_size: None,
+                max_rows: None,
+                row_limit_strategy: RowLimitStrategy::default(),
+                annotate_truncation: false,
+                log_level: None,
+                warning_filters: None,
+                suppress_components: None,
+                browser_shims: true,
+                simplify_topojson: None,
+                pre_aggregate: false,
+                lenient_dates: false,
+                svg_post_processor: None,
+            },
+        ))
+    }) {
         Ok(vega_spec) => vega_spec,
         Err(err) => {
             return Err(PyValueError::new_err(format!(
@@ -97,32 +208,185 @@ fn vegalite_to_vega(
 ///                                      data requests. Default allows any base URL
 ///     format_locale (str | dict): d3-format locale name or dictionary
 ///     time_format_locale (str | dict): d3-time-format locale name or dictionary
+///     theme (str | None): Named theme (e.g. "dark") to apply during conversion
+///     config (dict | str | None): Chart configuration object to apply during conversion,
+///         or a JSON string or the path of a JSON file holding it (e.g. "~/config.json")
+///     fetch_retries (int | None): Number of times to retry data and image requests that fail
+///         with a connection error or 5xx response (default 0)
+///     fetch_retry_backoff_ms (int | None): Delay before the first retry in milliseconds,
+///         doubled for each subsequent retry (default 200)
+///     text_length (bool | None): Whether to add textLength attributes to text elements so that
+///         applications which substitute fonts reproduce the measured layout (default false)
+///     svg_native_tooltips (bool | None): Whether to write the tooltip content of marks as
+///         <title> elements, which browsers display as native tooltips on hover (default false)
+///     svg_minify (bool | None): Whether to write the smallest SVG that draws the same image,
+///         without comments, accessibility metadata, class names, or attributes set to their
+///         default values (default false)
+///     svg_precision (int | None): Number of decimals that numbers are rounded to when
+///         svg_minify is enabled (default 6)
+///     interactions (list of dict | None): Interaction steps to apply before rendering. Each
+///         step is either {"signal": name, "value": value} or {"event": {"type": type, "x": x,
+///         "y": y, ...}} with x and y in view coordinates
+///     lenient (bool | None): Leave out images that fail to load instead of failing the
+///         conversion. Skipped images are listed in the "warnings" of the return_info dict
+///         (default false)
+///     generic_font_mapping (dict | None): Fonts to render generic font families with,
+///         keyed by "sans-serif", "serif", "monospace", or "cursive" (e.g.
+///         {"sans-serif": "Inter"})
+///     container_width (float | None): Width of the container that charts sized to their
+///         container (e.g. with "width": "container") are rendered in (default 600)
+///     container_height (float | None): Height of the container that charts sized to their
+///         container are rendered in (default 400)
+///     strict_sizing (bool | None): Fail the conversion of charts sized to their container,
+///         instead of rendering them with container_width and container_height (default false)
+///     return_info (bool | None): Return a dict with the SVG image string under "data", along
+///         with its "width", "height", "view_box", the "request_hash" of the spec and
+///         options, and the render "warnings" of the conversion (default false)
+///     force_iterations (int | None): Run force transforms as static simulations of this
+///         many ticks before the chart is rendered, so that their layout is reproducible.
+///         Force transforms that set "static" or "iterations" keep them (default None)
+///     random_seed (int | None): Seed for the random numbers of the render, which are used
+///         by the random() expression function and by transforms like sample (default None)
+///     width (float | None): Width to render the chart at, replacing the width of the spec
+///         before its axes and legends are laid out (default None)
+///     height (float | None): Height to render the chart at, replacing the height of the spec
+///         (default None)
+///     resize_mode (str | None): How the chart is laid out in width and height. "fit" makes
+///         them the size of the whole chart, including its axes and legends, "pad" makes them
+///         the size of the plot area, and "none" keeps the autosize of the spec (default "fit")
+///     min_size (tuple of int | None): Smallest (width, height) to render the chart at.
+///         Charts that resolve to a size below 1 pixel, like those with empty data, are
+///         enlarged to it instead of failing the conversion (default None)
+///     max_rows (int | None): Largest number of rows that a dataset of the chart may have once
+///         it's loaded, including inline data and data fetched from urls (default None)
+///     row_limit_strategy (str | None): How datasets with more than max_rows rows are
+///         handled. "error" fails the conversion, "head" keeps the first rows, and "sample"
+///         keeps a random sample of the rows, drawn with a seed of 0 or with the seed given
+///         as "sample:SEED" (default "error")
+///     annotate_truncation (bool | None): Add a subtitle that notes the truncation to charts
+///         with datasets that were truncated to max_rows rows (default false)
+///     log_level (str | None): Level of the messages that Vega logs while the chart is
+///         rendered. One of "none", "error", "warn", "info", or "debug". Warnings at that
+///         level are listed in the "warnings" of the return_info dict (default None, which logs
+///         nothing)
+///     warning_filters (list of str | None): Warnings that contain one of these strings, or
+///         that match it as a regular expression when it's written as "/pattern/", are left
+///         out of the render warnings and counted in a summary warning instead (default None)
+///     suppress_components (list of str | None): Components of the chart that are left out of
+///         the image, for applications that draw them themselves. Any of "title", "legend",
+///         "axis-x", "axis-y", or "grid". The chart is laid out without them (default None)
+///     browser_shims (bool | None): Define the browser globals that expressions copied from
+///         web pages read: window, navigator.language (the name of format_locale, or
+///         "en-US"), screen.width and screen.height (the size of the chart), and
+///         devicePixelRatio (the scale of PNG and JPEG images, or 1). Expressions that read
+///         other browser APIs fail with an error that names them (default true)
+///     simplify_topojson (float | None): Simplify the arcs of TopoJSON datasets as they're
+///         parsed, dropping the vertices that are within this tolerance of the simplified
+///         lines, in the units of the topology's coordinates (degrees for unprojected
+///         topologies). Borders that features share stay shared. The reduction in the number
+///         of vertices is listed in the "warnings" of the return_info dict (default None)
+///     svg_post_processor (Callable[[str], str] | None): Function that the generated SVG is
+///         passed through before it's returned or rasterized. It must return valid SVG, and
+///         exceptions that it raises fail the conversion (default None)
 /// Returns:
-///     str: SVG image string
+///     str | dict: SVG image string, or a dict with the image and its size when return_info
+///         is true
 #[pyfunction]
-#[pyo3(signature = (vg_spec, allowed_base_urls=None, format_locale=None, time_format_locale=None))]
+#[pyo3(signature = (vg_spec, allowed_base_urls=None, format_locale=None, time_format_locale=None, theme=None, config=None, fetch_retries=None, fetch_retry_backoff_ms=None, text_length=None, svg_native_tooltips=None, svg_minify=None, svg_precision=None, interactions=None, lenient=None, generic_font_mapping=None, container_width=None, container_height=None, strict_sizing=None, return_info=None, force_iterations=None, random_seed=None, width=None, height=None, resize_mode=None, min_size=None, max_rows=None, row_limit_strategy=None, annotate_truncation=None, log_level=None, warning_filters=None, suppress_components=None, browser_shims=None, simplify_topojson=None, svg_post_processor=None))]
 fn vega_to_svg(
     vg_spec: PyObject,
     allowed_base_urls: Option<Vec<String>>,
     format_locale: Option<PyObject>,
     time_format_locale: Option<PyObject>,
-) -> PyResult<String> {
+    theme: Option<String>,
+    config: Option<PyObject>,
+    fetch_retries: Option<u32>,
+    fetch_retry_backoff_ms: Option<u64>,
+    text_length: Option<bool>,
+    svg_native_tooltips: Option<bool>,
+    svg_minify: Option<bool>,
+    svg_precision: Option<u8>,
+    interactions: Option<PyObject>,
+    lenient: Option<bool>,
+    generic_font_mapping: Option<HashMap<String, String>>,
+    container_width: Option<f32>,
+    container_height: Option<f32>,
+    strict_sizing: Option<bool>,
+    return_info: Option<bool>,
+    force_iterations: Option<u32>,
+    random_seed: Option<u32>,
+    width: Option<f32>,
+    height: Option<f32>,
+    resize_mode: Option<String>,
+    min_size: Option<(u32, u32)>,
+    max_rows: Option<u64>,
+    row_limit_strategy: Option<String>,
+    annotate_truncation: Option<bool>,
+    log_level: Option<String>,
+    warning_filters: Option<Vec<String>>,
+    suppress_components: Option<Vec<String>>,
+    browser_shims: Option<bool>,
+    simplify_topojson: Option<f64>,
+    svg_post_processor: Option<PyObject>,
+) -> PyResult<PyObject> {
+    let svg_post_processor = parse_svg_post_processor(svg_post_processor)?;
+    let theme = parse_option_theme(theme)?;
+    let resize_mode = parse_resize_mode(resize_mode)?;
+    let row_limit_strategy = parse_row_limit_strategy(row_limit_strategy)?;
+    let log_level = parse_log_level(log_level)?;
+    let suppress_components = parse_suppress_components(suppress_components)?;
     let vg_spec = parse_json_spec(vg_spec)?;
+    let config = parse_option_config(config)?;
     let format_locale = parse_option_format_locale(format_locale)?;
     let time_format_locale = parse_option_time_format_locale(time_format_locale)?;
+    let interactions = parse_option_interactions(interactions)?;
 
-    let mut converter = VL_CONVERTER
-        .lock()
-        .expect("Failed to acquire lock on Vega-Lite converter");
-
-    let svg = match PYTHON_RUNTIME.block_on(converter.vega_to_svg(
-        vg_spec,
-        VgOpts {
-            allowed_base_urls,
-            format_locale,
-            time_format_locale,
-        },
-    )) {
+    let svg = match with_converter(|converter| {
+        PYTHON_RUNTIME.block_on(converter.vega_to_svg_info_with_opts(
+            vg_spec,
+            VgOpts {
+                theme,
+                config,
+                allowed_base_urls,
+                format_locale,
+                time_format_locale,
+                collect_resource_stats: collect_resource_stats(),
+                collect_dataset_summary: collect_dataset_summary(),
+                fetch_retries,
+                fetch_retry_backoff_ms,
+                interaction_script: interactions,
+                preserve_int64: false,
+                base_url: None,
+                allow_local_data: false,
+                lenient: lenient.unwrap_or(false),
+                generic_font_mapping,
+                container_width,
+                container_height,
+                strict_sizing: strict_sizing.unwrap_or(false),
+                width,
+                height,
+                resize_mode,
+                min_size,
+                max_rows,
+                row_limit_strategy,
+                annotate_truncation: annotate_truncation.unwrap_or(false),
+                log_level,
+                warning_filters,
+                suppress_components,
+                browser_shims: browser_shims.unwrap_or(true),
+                simplify_topojson,
+                force_iterations,
+                random_seed,
+                svg_post_processor,
+            },
+            SvgOpts {
+                text_length: text_length.unwrap_or(false),
+                native_tooltips: svg_native_tooltips.unwrap_or(false),
+                minify: svg_minify.unwrap_or(false),
+                precision: svg_precision,
+            },
+        ))
+    }) {
         Ok(vega_spec) => vega_spec,
         Err(err) => {
             return Err(PyValueError::new_err(format!(
@@ -131,7 +395,7 @@ fn vega_to_svg(
             )))
         }
     };
-    Ok(svg)
+    Python::with_gil(|py| svg_result_object(py, svg, return_info.unwrap_or(false)))
 }
 
 /// Convert a Vega spec to a Vega Scenegraph
@@ -142,32 +406,109 @@ fn vega_to_svg(
 ///                                      data requests. Default allows any base URL
 ///     format_locale (str | dict): d3-format locale name or dictionary
 ///     time_format_locale (str | dict): d3-time-format locale name or dictionary
+///     theme (str | None): Named theme (e.g. "dark") to apply during conversion
+///     config (dict | str | None): Chart configuration object to apply during conversion,
+///         or a JSON string or the path of a JSON file holding it (e.g. "~/config.json")
+///     fetch_retries (int | None): Number of times to retry data and image requests that fail
+///         with a connection error or 5xx response (default 0)
+///     fetch_retry_backoff_ms (int | None): Delay before the first retry in milliseconds,
+///         doubled for each subsequent retry (default 200)
+///     interactions (list of dict | None): Interaction steps to apply before rendering. Each
+///         step is either {"signal": name, "value": value} or {"event": {"type": type, "x": x,
+///         "y": y, ...}} with x and y in view coordinates
+///     generic_font_mapping (dict | None): Fonts to render generic font families with,
+///         keyed by "sans-serif", "serif", "monospace", or "cursive" (e.g.
+///         {"sans-serif": "Inter"})
+///     container_width (float | None): Width of the container that charts sized to their
+///         container (e.g. with "width": "container") are rendered in (default 600)
+///     container_height (float | None): Height of the container that charts sized to their
+///         container are rendered in (default 400)
+///     strict_sizing (bool | None): Fail the conversion of charts sized to their container,
+///         instead of rendering them with container_width and container_height (default false)
+///     force_iterations (int | None): Run force transforms as static simulations of this
+///         many ticks before the chart is rendered, so that their layout is reproducible.
+///         Force transforms that set "static" or "iterations" keep them (default None)
+///     random_seed (int | None): Seed for the random numbers of the render, which are used
+///         by the random() expression function and by transforms like sample (default None)
+///     width (float | None): Width to render the chart at, replacing the width of the spec
+///         before its axes and legends are laid out (default None)
+///     height (float | None): Height to render the chart at, replacing the height of the spec
+///         (default None)
+///     resize_mode (str | None): How the chart is laid out in width and height. "fit" makes
+///         them the size of the whole chart, including its axes and legends, "pad" makes them
+///         the size of the plot area, and "none" keeps the autosize of the spec (default "fit")
 /// Returns:
 ///     dict: scenegraph
 #[pyfunction]
-#[pyo3(signature = (vg_spec, allowed_base_urls=None, format_locale=None, time_format_locale=None))]
+#[pyo3(signature = (vg_spec, allowed_base_urls=None, format_locale=None, time_format_locale=None, theme=None, config=None, fetch_retries=None, fetch_retry_backoff_ms=None, interactions=None, generic_font_mapping=None, container_width=None, container_height=None, strict_sizing=None, force_iterations=None, random_seed=None, width=None, height=None, resize_mode=None))]
 fn vega_to_scenegraph(
     vg_spec: PyObject,
     allowed_base_urls: Option<Vec<String>>,
     format_locale: Option<PyObject>,
     time_format_locale: Option<PyObject>,
+    theme: Option<String>,
+    config: Option<PyObject>,
+    fetch_retries: Option<u32>,
+    fetch_retry_backoff_ms: Option<u64>,
+    interactions: Option<PyObject>,
+    generic_font_mapping: Option<HashMap<String, String>>,
+    container_width: Option<f32>,
+    container_height: Option<f32>,
+    strict_sizing: Option<bool>,
+    force_iterations: Option<u32>,
+    random_seed: Option<u32>,
+    width: Option<f32>,
+    height: Option<f32>,
+    resize_mode: Option<String>,
 ) -> PyResult<PyObject> {
+    let theme = parse_option_theme(theme)?;
+    let resize_mode = parse_resize_mode(resize_mode)?;
     let vg_spec = parse_json_spec(vg_spec)?;
+    let config = parse_option_config(config)?;
     let format_locale = parse_option_format_locale(format_locale)?;
     let time_format_locale = parse_option_time_format_locale(time_format_locale)?;
+    let interactions = parse_option_interactions(interactions)?;
 
-    let mut converter = VL_CONVERTER
-        .lock()
-        .expect("Failed to acquire lock on Vega-Lite converter");
-
-    let sg = match PYTHON_RUNTIME.block_on(converter.vega_to_scenegraph(
-        vg_spec,
-        VgOpts {
-            allowed_base_urls,
-            format_locale,
-            time_format_locale,
-        },
-    )) {
+    let sg = match with_converter(|converter| {
+        PYTHON_RUNTIME.block_on(converter.vega_to_scenegraph(
+            vg_spec,
+            VgOpts {
+                theme,
+                config,
+                allowed_base_urls,
+                format_locale,
+                time_format_locale,
+                collect_resource_stats: collect_resource_stats(),
+                collect_dataset_summary: collect_dataset_summary(),
+                fetch_retries,
+                fetch_retry_backoff_ms,
+                interaction_script: interactions,
+                preserve_int64: false,
+                base_url: None,
+                allow_local_data: false,
+                lenient: false,
+                generic_font_mapping,
+                container_width,
+                container_height,
+                strict_sizing: strict_sizing.unwrap_or(false),
+                width,
+                height,
+                resize_mode,
+                min_size: None,
+                max_rows: None,
+                row_limit_strategy: RowLimitStrategy::default(),
+                annotate_truncation: false,
+                log_level: None,
+                warning_filters: None,
+                suppress_components: None,
+                browser_shims: true,
+                simplify_topojson: None,
+                force_iterations,
+                random_seed,
+                svg_post_processor: None,
+            },
+        ))
+    }) {
         Ok(vega_spec) => vega_spec,
         Err(err) => {
             return Err(PyValueError::new_err(format!(
@@ -183,6 +524,135 @@ fn vega_to_scenegraph(
     })
 }
 
+/// Count the groups, items, and scenegraph nodes of a rendered Vega chart, without
+/// generating SVG. For harnesses that check that charts render what they should
+///
+/// Args:
+///     vg_spec (str | dict): Vega JSON specification string or dict
+///     allowed_base_urls (list of str): List of allowed base URLs for external
+///                                      data requests. Default allows any base URL
+///     format_locale (str | dict): d3-format locale name or dictionary
+///     time_format_locale (str | dict): d3-time-format locale name or dictionary
+///     theme (str | None): Named theme (e.g. "dark") to apply during conversion
+///     config (dict | str | None): Chart configuration object to apply during conversion,
+///         or a JSON string or the path of a JSON file holding it (e.g. "~/config.json")
+///     fetch_retries (int | None): Number of times to retry data and image requests that fail
+///         with a connection error or 5xx response (default 0)
+///     fetch_retry_backoff_ms (int | None): Delay before the first retry in milliseconds,
+///         doubled for each subsequent retry (default 200)
+///     interactions (list of dict | None): Interaction steps to apply before rendering. Each
+///         step is either {"signal": name, "value": value} or {"event": {"type": type, "x": x,
+///         "y": y, ...}} with x and y in view coordinates
+///     generic_font_mapping (dict | None): Fonts to render generic font families with,
+///         keyed by "sans-serif", "serif", "monospace", or "cursive" (e.g.
+///         {"sans-serif": "Inter"})
+///     container_width (float | None): Width of the container that charts sized to their
+///         container (e.g. with "width": "container") are rendered in (default 600)
+///     container_height (float | None): Height of the container that charts sized to their
+///         container are rendered in (default 400)
+///     strict_sizing (bool | None): Fail the conversion of charts sized to their container,
+///         instead of rendering them with container_width and container_height (default false)
+///     force_iterations (int | None): Run force transforms as static simulations of this
+///         many ticks before the chart is rendered, so that their layout is reproducible.
+///         Force transforms that set "static" or "iterations" keep them (default None)
+///     random_seed (int | None): Seed for the random numbers of the render, which are used
+///         by the random() expression function and by transforms like sample (default None)
+///     width (float | None): Width to render the chart at, replacing the width of the spec
+///         before its axes and legends are laid out (default None)
+///     height (float | None): Height to render the chart at, replacing the height of the spec
+///         (default None)
+///     resize_mode (str | None): How the chart is laid out in width and height. "fit" makes
+///         them the size of the whole chart, including its axes and legends, "pad" makes them
+///         the size of the plot area, and "none" keeps the autosize of the spec (default "fit")
+/// Returns:
+///     dict: Render stats with group_count, item_counts_by_marktype (a dict of the number
+///         of items of each mark type, like "rect"), text_item_count, and
+///         total_scenegraph_nodes keys
+#[pyfunction]
+#[pyo3(signature = (vg_spec, allowed_base_urls=None, format_locale=None, time_format_locale=None, theme=None, config=None, fetch_retries=None, fetch_retry_backoff_ms=None, interactions=None, generic_font_mapping=None, container_width=None, container_height=None, strict_sizing=None, force_iterations=None, random_seed=None, width=None, height=None, resize_mode=None))]
+fn vega_get_render_stats(
+    vg_spec: PyObject,
+    allowed_base_urls: Option<Vec<String>>,
+    format_locale: Option<PyObject>,
+    time_format_locale: Option<PyObject>,
+    theme: Option<String>,
+    config: Option<PyObject>,
+    fetch_retries: Option<u32>,
+    fetch_retry_backoff_ms: Option<u64>,
+    interactions: Option<PyObject>,
+    generic_font_mapping: Option<HashMap<String, String>>,
+    container_width: Option<f32>,
+    container_height: Option<f32>,
+    strict_sizing: Option<bool>,
+    force_iterations: Option<u32>,
+    random_seed: Option<u32>,
+    width: Option<f32>,
+    height: Option<f32>,
+    resize_mode: Option<String>,
+) -> PyResult<PyObject> {
+    let theme = parse_option_theme(theme)?;
+    let resize_mode = parse_resize_mode(resize_mode)?;
+    let vg_spec = parse_json_spec(vg_spec)?;
+    let config = parse_option_config(config)?;
+    let format_locale = parse_option_format_locale(format_locale)?;
+    let time_format_locale = parse_option_time_format_locale(time_format_locale)?;
+    let interactions = parse_option_interactions(interactions)?;
+
+    let stats = match with_converter(|converter| {
+        PYTHON_RUNTIME.block_on(converter.vega_get_render_stats(
+            vg_spec,
+            VgOpts {
+                theme,
+                config,
+                allowed_base_urls,
+                format_locale,
+                time_format_locale,
+                collect_resource_stats: collect_resource_stats(),
+                collect_dataset_summary: collect_dataset_summary(),
+                fetch_retries,
+                fetch_retry_backoff_ms,
+                interaction_script: interactions,
+                preserve_int64: false,
+                base_url: None,
+                allow_local_data: false,
+                lenient: false,
+                generic_font_mapping,
+                container_width,
+                container_height,
+                strict_sizing: strict_sizing.unwrap_or(false),
+                width,
+                height,
+                resize_mode,
+                min_size: None,
+                max_rows: None,
+                row_limit_strategy: RowLimitStrategy::default(),
+                annotate_truncation: false,
+                log_level: None,
+                warning_filters: None,
+                suppress_components: None,
+                browser_shims: true,
+                simplify_topojson: None,
+                force_iterations,
+                random_seed,
+                svg_post_processor: None,
+            },
+        ))
+    }) {
+        Ok(stats) => stats,
+        Err(err) => {
+            return Err(PyValueError::new_err(format!(
+                "Vega render stats failed:\n{}",
+                err
+            )))
+        }
+    };
+    Python::with_gil(|py| -> PyResult<PyObject> {
+        pythonize(py, &stats)
+            .map_err(|err| PyValueError::new_err(err.to_string()))
+            .map(|obj| obj.into())
+    })
+}
+
 /// Convert a Vega-Lite spec to an SVG image string using a
 /// particular version of the Vega-Lite JavaScript library.
 ///
@@ -190,18 +660,110 @@ fn vega_to_scenegraph(
 ///     vl_spec (str | dict): Vega-Lite JSON specification string or dict
 ///     vl_version (str | None): Vega-Lite library version string (e.g. 'v5.15')
 ///         (default to latest)
-///     config (dict | None): Chart configuration object to apply during conversion
+///     config (dict | str | None): Chart configuration object to apply during conversion,
+///         or a JSON string or the path of a JSON file holding it (e.g. "~/config.json")
 ///     theme (str | None): Named theme (e.g. "dark") to apply during conversion
 ///     show_warnings (bool | None): Whether to print Vega-Lite compilation warnings (default false)
 ///     allowed_base_urls (list of str): List of allowed base URLs for external
 ///                                      data requests. Default allows any base URL
 ///     format_locale (str | dict): d3-format locale name or dictionary
 ///     time_format_locale (str | dict): d3-time-format locale name or dictionary
+///     fetch_retries (int | None): Number of times to retry data and image requests that fail
+///         with a connection error or 5xx response (default 0)
+///     fetch_retry_backoff_ms (int | None): Delay before the first retry in milliseconds,
+///         doubled for each subsequent retry (default 200)
+///     text_length (bool | None): Whether to add textLength attributes to text elements so that
+///         applications which substitute fonts reproduce the measured layout (default false)
+///     svg_native_tooltips (bool | None): Whether to write the tooltip content of marks as
+///         <title> elements, which browsers display as native tooltips on hover (default false)
+///     svg_minify (bool | None): Whether to write the smallest SVG that draws the same image,
+///         without comments, accessibility metadata, class names, or attributes set to their
+///         default values (default false)
+///     svg_precision (int | None): Number of decimals that numbers are rounded to when
+///         svg_minify is enabled (default 6)
+///     interactions (list of dict | None): Interaction steps to apply before rendering. Each
+///         step is either {"signal": name, "value": value} or {"event": {"type": type, "x": x,
+///         "y": y, ...}} with x and y in view coordinates
+///     lenient (bool | None): Leave out images that fail to load instead of failing the
+///         conversion. Skipped images are listed in the "warnings" of the return_info dict
+///         (default false)
+///     font (str | None): Font to use for all text: axis, legend, and header labels and
+///         titles, chart titles, and text marks. Config takes precedence
+///     font_size_scale (float | None): Factor to scale the default font sizes of all text by
+///     text_color (str | None): Color to use for all text
+///     config_overrides (dict | None): Config values keyed by dotted paths (e.g.
+///         "axis.labelFontSize"), which are set in order over config and theme
+///     direction (str | None): Writing direction to lay the chart out for, "ltr" or "rtl".
+///         "rtl" mirrors the layout for right-to-left locales: y axes and row headers are
+///         drawn on the right, legends on the left, and titles are anchored at the end.
+///         Orients and anchors set in the spec or config take precedence (default "ltr")
+///     generic_font_mapping (dict | None): Fonts to render generic font families with,
+///         keyed by "sans-serif", "serif", "monospace", or "cursive" (e.g.
+///         {"sans-serif": "Inter"})
+///     container_width (float | None): Width of the container that charts sized to their
+///         container (e.g. with "width": "container") are rendered in (default 600)
+///     container_height (float | None): Height of the container that charts sized to their
+///         container are rendered in (default 400)
+///     strict_sizing (bool | None): Fail the conversion of charts sized to their container,
+///         instead of rendering them with container_width and container_height (default false)
+///     themes (list of str | None): Named themes to render the chart with, once per theme
+///         ("default" renders it without a theme). Data loaded from urls is fetched once and
+///         shared across the themes. When given, a dict of SVG image strings keyed by theme is
+///         returned and theme is ignored
+///     return_info (bool | None): Return a dict with the SVG image string under "data", along
+///         with its "width", "height", "view_box", the "request_hash" of the spec and
+///         options, and the render "warnings" of the conversion (default false)
+///     width (float | None): Width to render the chart at, replacing the width of the spec
+///         before its axes and legends are laid out (default None)
+///     height (float | None): Height to render the chart at, replacing the height of the spec
+///         (default None)
+///     resize_mode (str | None): How the chart is laid out in width and height. "fit" makes
+///         them the size of the whole chart, including its axes and legends, "pad" makes them
+///         the size of the plot area, and "none" keeps the autosize of the spec (default "fit")
+///     min_size (tuple of int | None): Smallest (width, height) to render the chart at.
+///         Charts that resolve to a size below 1 pixel, like those with empty data, are
+///         enlarged to it instead of failing the conversion (default None)
+///     max_rows (int | None): Largest number of rows that a dataset of the chart may have once
+///         it's loaded, including inline data and data fetched from urls (default None)
+///     row_limit_strategy (str | None): How datasets with more than max_rows rows are
+///         handled. "error" fails the conversion, "head" keeps the first rows, and "sample"
+///         keeps a random sample of the rows, drawn with a seed of 0 or with the seed given
+///         as "sample:SEED" (default "error")
+///     annotate_truncation (bool | None): Add a subtitle that notes the truncation to charts
+///         with datasets that were truncated to max_rows rows (default false)
+///     log_level (str | None): Level of the messages that Vega logs while the chart is
+///         rendered. One of "none", "error", "warn", "info", or "debug". Warnings at that
+///         level are listed in the "warnings" of the return_info dict (default None, which logs
+///         nothing)
+///     warning_filters (list of str | None): Warnings that contain one of these strings, or
+///         that match it as a regular expression when it's written as "/pattern/", are left
+///         out of the render warnings and counted in a summary warning instead (default None)
+///     suppress_components (list of str | None): Components of the chart that are left out of
+///         the image, for applications that draw them themselves. Any of "title", "legend",
+///         "axis-x", "axis-y", or "grid". The chart is laid out without them (default None)
+///     browser_shims (bool | None): Define the browser globals that expressions copied from
+///         web pages read: window, navigator.language (the name of format_locale, or
+///         "en-US"), screen.width and screen.height (the size of the chart), and
+///         devicePixelRatio (the scale of PNG and JPEG images, or 1). Expressions that read
+///         other browser APIs fail with an error that names them (default true)
+///     simplify_topojson (float | None): Simplify the arcs of TopoJSON datasets as they're
+///         parsed, dropping the vertices that are within this tolerance of the simplified
+///         lines, in the units of the topology's coordinates (degrees for unprojected
+///         topologies). Borders that features share stay shared. The reduction in the number
+///         of vertices is listed in the "warnings" of the return_info dict (default None)
+///     lenient_dates (bool | None): Rewrite the dates of the temporal fields of the spec's
+///         inline data that aren't in the ISO 8601 format, like "03/27/2024 5:00 PM", to ISO
+///         8601 before the chart is rendered. The rewritten fields are listed in
+///         the "warnings" of the return_info dict (default false)
+///     svg_post_processor (Callable[[str], str] | None): Function that the generated SVG is
+///         passed through before it's returned or rasterized. It must return valid SVG, and
+///         exceptions that it raises fail the conversion (default None)
 /// Returns:
-///     str: SVG image string
+///     str | dict: SVG image string, SVG image strings keyed by theme, or a dict with the image
+///         and its size when return_info is true
 #[pyfunction]
 #[pyo3(
-    signature = (vl_spec, vl_version=None, config=None, theme=None, show_warnings=None, allowed_base_urls=None, format_locale=None, time_format_locale=None)
+    signature = (vl_spec, vl_version=None, config=None, theme=None, show_warnings=None, allowed_base_urls=None, format_locale=None, time_format_locale=None, fetch_retries=None, fetch_retry_backoff_ms=None, text_length=None, svg_native_tooltips=None, svg_minify=None, svg_precision=None, interactions=None, lenient=None, font=None, font_size_scale=None, text_color=None, config_overrides=None, direction=None, generic_font_mapping=None, container_width=None, container_height=None, strict_sizing=None, themes=None, return_info=None, width=None, height=None, resize_mode=None, min_size=None, max_rows=None, row_limit_strategy=None, annotate_truncation=None, log_level=None, warning_filters=None, suppress_components=None, browser_shims=None, simplify_topojson=None, lenient_dates=None, svg_post_processor=None)
 )]
 fn vegalite_to_svg(
     vl_spec: PyObject,
@@ -212,34 +774,133 @@ fn vegalite_to_svg(
     allowed_base_urls: Option<Vec<String>>,
     format_locale: Option<PyObject>,
     time_format_locale: Option<PyObject>,
-) -> PyResult<String> {
+    fetch_retries: Option<u32>,
+    fetch_retry_backoff_ms: Option<u64>,
+    text_length: Option<bool>,
+    svg_native_tooltips: Option<bool>,
+    svg_minify: Option<bool>,
+    svg_precision: Option<u8>,
+    interactions: Option<PyObject>,
+    lenient: Option<bool>,
+    font: Option<String>,
+    font_size_scale: Option<f32>,
+    text_color: Option<String>,
+    config_overrides: Option<PyObject>,
+    direction: Option<String>,
+    generic_font_mapping: Option<HashMap<String, String>>,
+    container_width: Option<f32>,
+    container_height: Option<f32>,
+    strict_sizing: Option<bool>,
+    themes: Option<Vec<String>>,
+    return_info: Option<bool>,
+    width: Option<f32>,
+    height: Option<f32>,
+    resize_mode: Option<String>,
+    min_size: Option<(u32, u32)>,
+    max_rows: Option<u64>,
+    row_limit_strategy: Option<String>,
+    annotate_truncation: Option<bool>,
+    log_level: Option<String>,
+    warning_filters: Option<Vec<String>>,
+    suppress_components: Option<Vec<String>>,
+    browser_shims: Option<bool>,
+    simplify_topojson: Option<f64>,
+    lenient_dates: Option<bool>,
+    svg_post_processor: Option<PyObject>,
+) -> PyResult<PyObject> {
+    let svg_post_processor = parse_svg_post_processor(svg_post_processor)?;
+    let theme = parse_option_theme(theme)?;
+    let resize_mode = parse_resize_mode(resize_mode)?;
+    let row_limit_strategy = parse_row_limit_strategy(row_limit_strategy)?;
+    let log_level = parse_log_level(log_level)?;
+    let suppress_components = parse_suppress_components(suppress_components)?;
+    let themes = parse_option_themes(themes)?;
+    if themes.is_some() && return_info.unwrap_or(false) {
+        return Err(PyValueError::new_err(
+            "return_info can't be combined with themes",
+        ));
+    }
     let vl_spec = parse_json_spec(vl_spec)?;
-    let config = config.and_then(|c| parse_json_spec(c).ok());
+    let config = parse_option_config(config)?;
+    let config_overrides = parse_option_config_overrides(config_overrides)?;
+    let direction = parse_option_direction(direction)?;
     let format_locale = parse_option_format_locale(format_locale)?;
     let time_format_locale = parse_option_time_format_locale(time_format_locale)?;
+    let interactions = parse_option_interactions(interactions)?;
 
-    let vl_version = if let Some(vl_version) = vl_version {
-        VlVersion::from_str(vl_version)?
-    } else {
-        Default::default()
+    let vl_version = parse_vl_version(vl_version)?;
+
+    let vl_opts = VlOpts {
+        vl_version,
+        config,
+        theme,
+        show_warnings: show_warnings.unwrap_or(false),
+        allowed_base_urls,
+        format_locale,
+        time_format_locale,
+        collect_resource_stats: collect_resource_stats(),
+        collect_dataset_summary: collect_dataset_summary(),
+        fetch_retries,
+        fetch_retry_backoff_ms,
+        interaction_script: interactions,
+        preserve_int64: false,
+        base_url: None,
+        allow_local_data: false,
+        lenient: lenient.unwrap_or(false),
+        font,
+        font_size_scale,
+        text_color,
+        config_overrides,
+        direction,
+        generic_font_mapping,
+        container_width,
+        container_height,
+        strict_sizing: strict_sizing.unwrap_or(false),
+        width,
+        height,
+        resize_mode,
+        min_size,
+        max_rows,
+        row_limit_strategy,
+        annotate_truncation: annotate_truncation.unwrap_or(false),
+        log_level,
+        warning_filters,
+        suppress_components,
+        browser_shims: browser_shims.unwrap_or(true),
+        simplify_topojson,
+        pre_aggregate: false,
+        lenient_dates: lenient_dates.unwrap_or(false),
+        svg_post_processor,
+    };
+    let svg_opts = SvgOpts {
+        text_length: text_length.unwrap_or(false),
+        native_tooltips: svg_native_tooltips.unwrap_or(false),
+        minify: svg_minify.unwrap_or(false),
+        precision: svg_precision,
     };
 
-    let mut converter = VL_CONVERTER
-        .lock()
-        .expect("Failed to acquire lock on Vega-Lite converter");
+    if let Some(themes) = themes {
+        let themes: Vec<&str> = themes.iter().map(String::as_str).collect();
+        let svgs = match with_converter(|converter| {
+            PYTHON_RUNTIME.block_on(
+                converter.vegalite_to_svg_themed_with_opts(vl_spec, vl_opts, &themes, svg_opts),
+            )
+        }) {
+            Ok(svgs) => svgs,
+            Err(err) => {
+                return Err(PyValueError::new_err(format!(
+                    "Vega-Lite to SVG conversion failed:\n{}",
+                    err
+                )))
+            }
+        };
+        return Ok(Python::with_gil(|py| svgs.into_py(py)));
+    }
 
-    let svg = match PYTHON_RUNTIME.block_on(converter.vegalite_to_svg(
-        vl_spec,
-        VlOpts {
-            vl_version,
-            config,
-            theme,
-            show_warnings: show_warnings.unwrap_or(false),
-            allowed_base_urls,
-            format_locale,
-            time_format_locale,
-        },
-    )) {
+    let svg = match with_converter(|converter| {
+        PYTHON_RUNTIME
+            .block_on(converter.vegalite_to_svg_info_with_opts(vl_spec, vl_opts, svg_opts))
+    }) {
         Ok(vega_spec) => vega_spec,
         Err(err) => {
             return Err(PyValueError::new_err(format!(
@@ -248,7 +909,7 @@ fn vegalite_to_svg(
             )))
         }
     };
-    Ok(svg)
+    Python::with_gil(|py| svg_result_object(py, svg, return_info.unwrap_or(false)))
 }
 
 /// Convert a Vega-Lite spec to a Vega Scenegraph using a
@@ -258,18 +919,52 @@ fn vegalite_to_svg(
 ///     vl_spec (str | dict): Vega-Lite JSON specification string or dict
 ///     vl_version (str | None): Vega-Lite library version string (e.g. 'v5.15')
 ///         (default to latest)
-///     config (dict | None): Chart configuration object to apply during conversion
+///     config (dict | str | None): Chart configuration object to apply during conversion,
+///         or a JSON string or the path of a JSON file holding it (e.g. "~/config.json")
 ///     theme (str | None): Named theme (e.g. "dark") to apply during conversion
 ///     show_warnings (bool | None): Whether to print Vega-Lite compilation warnings (default false)
 ///     allowed_base_urls (list of str): List of allowed base URLs for external
 ///                                      data requests. Default allows any base URL
 ///     format_locale (str | dict): d3-format locale name or dictionary
 ///     time_format_locale (str | dict): d3-time-format locale name or dictionary
+///     fetch_retries (int | None): Number of times to retry data and image requests that fail
+///         with a connection error or 5xx response (default 0)
+///     fetch_retry_backoff_ms (int | None): Delay before the first retry in milliseconds,
+///         doubled for each subsequent retry (default 200)
+///     interactions (list of dict | None): Interaction steps to apply before rendering. Each
+///         step is either {"signal": name, "value": value} or {"event": {"type": type, "x": x,
+///         "y": y, ...}} with x and y in view coordinates
+///     font (str | None): Font to use for all text: axis, legend, and header labels and
+///         titles, chart titles, and text marks. Config takes precedence
+///     font_size_scale (float | None): Factor to scale the default font sizes of all text by
+///     text_color (str | None): Color to use for all text
+///     config_overrides (dict | None): Config values keyed by dotted paths (e.g.
+///         "axis.labelFontSize"), which are set in order over config and theme
+///     direction (str | None): Writing direction to lay the chart out for, "ltr" or "rtl".
+///         "rtl" mirrors the layout for right-to-left locales: y axes and row headers are
+///         drawn on the right, legends on the left, and titles are anchored at the end.
+///         Orients and anchors set in the spec or config take precedence (default "ltr")
+///     generic_font_mapping (dict | None): Fonts to render generic font families with,
+///         keyed by "sans-serif", "serif", "monospace", or "cursive" (e.g.
+///         {"sans-serif": "Inter"})
+///     container_width (float | None): Width of the container that charts sized to their
+///         container (e.g. with "width": "container") are rendered in (default 600)
+///     container_height (float | None): Height of the container that charts sized to their
+///         container are rendered in (default 400)
+///     strict_sizing (bool | None): Fail the conversion of charts sized to their container,
+///         instead of rendering them with container_width and container_height (default false)
+///     width (float | None): Width to render the chart at, replacing the width of the spec
+///         before its axes and legends are laid out (default None)
+///     height (float | None): Height to render the chart at, replacing the height of the spec
+///         (default None)
+///     resize_mode (str | None): How the chart is laid out in width and height. "fit" makes
+///         them the size of the whole chart, including its axes and legends, "pad" makes them
+///         the size of the plot area, and "none" keeps the autosize of the spec (default "fit")
 /// Returns:
 ///     str: SVG image string
 #[pyfunction]
 #[pyo3(
-    signature = (vl_spec, vl_version=None, config=None, theme=None, show_warnings=None, allowed_base_urls=None, format_locale=None, time_format_locale=None)
+    signature = (vl_spec, vl_version=None, config=None, theme=None, show_warnings=None, allowed_base_urls=None, format_locale=None, time_format_locale=None, fetch_retries=None, fetch_retry_backoff_ms=None, interactions=None, font=None, font_size_scale=None, text_color=None, config_overrides=None, direction=None, generic_font_mapping=None, container_width=None, container_height=None, strict_sizing=None, width=None, height=None, resize_mode=None)
 )]
 fn vegalite_to_scenegraph(
     vl_spec: PyObject,
@@ -280,34 +975,81 @@ fn vegalite_to_scenegraph(
     allowed_base_urls: Option<Vec<String>>,
     format_locale: Option<PyObject>,
     time_format_locale: Option<PyObject>,
+    fetch_retries: Option<u32>,
+    fetch_retry_backoff_ms: Option<u64>,
+    interactions: Option<PyObject>,
+    font: Option<String>,
+    font_size_scale: Option<f32>,
+    text_color: Option<String>,
+    config_overrides: Option<PyObject>,
+    direction: Option<String>,
+    generic_font_mapping: Option<HashMap<String, String>>,
+    container_width: Option<f32>,
+    container_height: Option<f32>,
+    strict_sizing: Option<bool>,
+    width: Option<f32>,
+    height: Option<f32>,
+    resize_mode: Option<String>,
 ) -> PyResult<PyObject> {
+    let theme = parse_option_theme(theme)?;
+    let resize_mode = parse_resize_mode(resize_mode)?;
     let vl_spec = parse_json_spec(vl_spec)?;
-    let config = config.and_then(|c| parse_json_spec(c).ok());
+    let config = parse_option_config(config)?;
+    let config_overrides = parse_option_config_overrides(config_overrides)?;
+    let direction = parse_option_direction(direction)?;
     let format_locale = parse_option_format_locale(format_locale)?;
     let time_format_locale = parse_option_time_format_locale(time_format_locale)?;
+    let interactions = parse_option_interactions(interactions)?;
 
-    let vl_version = if let Some(vl_version) = vl_version {
-        VlVersion::from_str(vl_version)?
-    } else {
-        Default::default()
-    };
-
-    let mut converter = VL_CONVERTER
-        .lock()
-        .expect("Failed to acquire lock on Vega-Lite converter");
+    let vl_version = parse_vl_version(vl_version)?;
 
-    let sg = match PYTHON_RUNTIME.block_on(converter.vegalite_to_scenegraph(
-        vl_spec,
-        VlOpts {
-            vl_version,
-            config,
-            theme,
-            show_warnings: show_warnings.unwrap_or(false),
-            allowed_base_urls,
-            format_locale,
-            time_format_locale,
-        },
-    )) {
+    let sg = match with_converter(|converter| {
+        PYTHON_RUNTIME.block_on(converter.vegalite_to_scenegraph(
+            vl_spec,
+            VlOpts {
+                vl_version,
+                config,
+                theme,
+                show_warnings: show_warnings.unwrap_or(false),
+                allowed_base_urls,
+                format_locale,
+                time_format_locale,
+                collect_resource_stats: collect_resource_stats(),
+                collect_dataset_summary: collect_dataset_summary(),
+                fetch_retries,
+                fetch_retry_backoff_ms,
+                interaction_script: interactions,
+                preserve_int64: false,
+                base_url: None,
+                allow_local_data: false,
+                lenient: false,
+                font,
+                font_size_scale,
+                text_color,
+                config_overrides,
+                direction,
+                generic_font_mapping,
+                container_width,
+                container_height,
+                strict_sizing: strict_sizing.unwrap_or(false),
+                width,
+                height,
+                resize_mode,
+                min_size: None,
+                max_rows: None,
+                row_limit_strategy: RowLimitStrategy::default(),
+                annotate_truncation: false,
+                log_level: None,
+                warning_filters: None,
+                suppress_components: None,
+                browser_shims: true,
+                simplify_topojson: None,
+                pre_aggregate: false,
+                lenient_dates: false,
+                svg_post_processor: None,
+            },
+        ))
+    }) {
         Ok(vega_spec) => vega_spec,
         Err(err) => {
             return Err(PyValueError::new_err(format!(
@@ -323,321 +1065,713 @@ fn vegalite_to_scenegraph(
     })
 }
 
-/// Convert a Vega spec to PNG image data.
+/// Get the final Vega spec that a Vega-Lite spec is rendered from.
+///
+/// The Vega-Lite spec is compiled with the theme and config, and the format locales are
+/// added to the spec's config.locale. Rendering the result with vega_to_png and
+/// default options produces the same image as vegalite_to_png with these options.
 ///
 /// Args:
-///     vg_spec (str | dict): Vega JSON specification string or dict
-///     scale (float): Image scale factor (default 1.0)
-///     ppi (float): Pixels per inch (default 72)
-///     allowed_base_urls (list of str): List of allowed base URLs for external
-///                                      data requests. Default allows any base URL
+///     vl_spec (str | dict): Vega-Lite JSON specification string or dict
+///     vl_version (str | None): Vega-Lite library version string (e.g. 'v5.15')
+///         (default to latest)
+///     config (dict | str | None): Chart configuration object to apply during conversion,
+///         or a JSON string or the path of a JSON file holding it (e.g. "~/config.json")
+///     theme (str | None): Named theme (e.g. "dark") to apply during conversion
+///     show_warnings (bool | None): Whether to print Vega-Lite compilation warnings (default false)
 ///     format_locale (str | dict): d3-format locale name or dictionary
 ///     time_format_locale (str | dict): d3-time-format locale name or dictionary
+///     preserve_int64 (bool | None): Whether to pass integers in inline data that JavaScript
+///         numbers can't represent exactly (beyond +/-2^53) through the conversion as strings,
+///         so that they're returned unchanged (default false)
+///     font (str | None): Font to use for all text: axis, legend, and header labels and
+///         titles, chart titles, and text marks. Config takes precedence
+///     font_size_scale (float | None): Factor to scale the default font sizes of all text by
+///     text_color (str | None): Color to use for all text
+///     config_overrides (dict | None): Config values keyed by dotted paths (e.g.
+///         "axis.labelFontSize"), which are set in order over config and theme
+///     direction (str | None): Writing direction to lay the chart out for, "ltr" or "rtl".
+///         "rtl" mirrors the layout for right-to-left locales: y axes and row headers are
+///         drawn on the right, legends on the left, and titles are anchored at the end.
+///         Orients and anchors set in the spec or config take precedence (default "ltr")
 /// Returns:
-///     bytes: PNG image data
+///     dict: Vega JSON specification dict
 #[pyfunction]
 #[pyo3(
-    signature = (vg_spec, scale=None, ppi=None, allowed_base_urls=None, format_locale=None, time_format_locale=None)
+    signature = (vl_spec, vl_version=None, config=None, theme=None, show_warnings=None, format_locale=None, time_format_locale=None, preserve_int64=None, font=None, font_size_scale=None, text_color=None, config_overrides=None, direction=None)
 )]
-fn vega_to_png(
-    vg_spec: PyObject,
-    scale: Option<f32>,
-    ppi: Option<f32>,
-    allowed_base_urls: Option<Vec<String>>,
+fn get_final_vega_spec(
+    vl_spec: PyObject,
+    vl_version: Option<&str>,
+    config: Option<PyObject>,
+    theme: Option<String>,
+    show_warnings: Option<bool>,
     format_locale: Option<PyObject>,
     time_format_locale: Option<PyObject>,
+    preserve_int64: Option<bool>,
+    font: Option<String>,
+    font_size_scale: Option<f32>,
+    text_color: Option<String>,
+    config_overrides: Option<PyObject>,
+    direction: Option<String>,
 ) -> PyResult<PyObject> {
-    let vg_spec = parse_json_spec(vg_spec)?;
+    let theme = parse_option_theme(theme)?;
+    let vl_spec = parse_json_spec(vl_spec)?;
+    let config = parse_option_config(config)?;
+    let config_overrides = parse_option_config_overrides(config_overrides)?;
+    let direction = parse_option_direction(direction)?;
     let format_locale = parse_option_format_locale(format_locale)?;
     let time_format_locale = parse_option_time_format_locale(time_format_locale)?;
 
-    let mut converter = VL_CONVERTER
-        .lock()
-        .expect("Failed to acquire lock on Vega-Lite converter");
+    let vl_version = parse_vl_version(vl_version)?;
 
-    let png_data = match PYTHON_RUNTIME.block_on(converter.vega_to_png(
-        vg_spec,
-        VgOpts {
-            allowed_base_urls,
-            format_locale,
-            time_format_locale,
-        },
-        scale,
-        ppi,
-    )) {
+    let vega_spec = match with_converter(|converter| {
+        PYTHON_RUNTIME.block_on(converter.get_final_vega_spec(
+            vl_spec,
+            VlOpts {
+                vl_version,
+                config,
+                theme,
+                show_warnings: show_warnings.unwrap_or(false),
+                allowed_base_urls: None,
+                format_locale,
+                time_format_locale,
+                collect_resource_stats: collect_resource_stats(),
+                collect_dataset_summary: collect_dataset_summary(),
+                fetch_retries: None,
+                fetch_retry_backoff_ms: None,
+                interaction_script: None,
+                preserve_int64: preserve_int64.unwrap_or(false),
+                base_url: None,
+                allow_local_data: false,
+                lenient: false,
+                font,
+                font_size_scale,
+                text_color,
+                config_overrides,
+                direction,
+                generic_font_mapping: None,
+                container_width: None,
+                container_height: None,
+                strict_sizing: false,
+                width: None,
+                height: None,
+                resize_mode: ResizeMode::default(),
+                min_size: None,
+                max_rows: None,
+                row_limit_strategy: RowLimitStrategy::default(),
+                annotate_truncation: false,
+                log_level: None,
+                warning_filters: None,
+                suppress_components: None,
+                browser_shims: true,
+                simplify_topojson: None,
+                pre_aggregate: false,
+                lenient_dates: false,
+                svg_post_processor: None,
+            },
+        ))
+    }) {
         Ok(vega_spec) => vega_spec,
         Err(err) => {
             return Err(PyValueError::new_err(format!(
-                "Vega to PNG conversion failed:\n{}",
+                "Vega-Lite to final Vega spec conversion failed:\n{}",
                 err
             )))
         }
     };
-
-    Ok(Python::with_gil(|py| -> PyObject {
-        PyBytes::new_bound(py, png_data.as_slice()).into()
-    }))
+    Python::with_gil(|py| -> PyResult<PyObject> {
+        pythonize(py, &vega_spec)
+            .map_err(|err| PyValueError::new_err(err.to_string()))
+            .map(|obj| obj.into())
+    })
 }
 
-/// Convert a Vega-Lite spec to PNG image data using a particular
-/// version of the Vega-Lite JavaScript library.
+/// Get the final Vega spec that a Vega spec is rendered from.
+///
+/// The theme and config are merged into the spec's config, and the format locales are
+/// added to the spec's config.locale. Rendering the result with vega_to_png and
+/// default options produces the same image as vega_to_png with these options.
 ///
 /// Args:
-///     vl_spec (str | dict): Vega-Lite JSON specification string or dict
-///     vl_version (str): Vega-Lite library version string (e.g. 'v5.15')
-///         (default to latest)
-///     scale (float): Image scale factor (default 1.0)
-///     ppi (float): Pixels per inch (default 72)
-///     config (dict | None): Chart configuration object to apply during conversion
-///     theme (str | None): Named theme (e.g. "dark") to apply during conversion
-///     show_warnings (bool | None): Whether to print Vega-Lite compilation warnings (default false)
-///     allowed_base_urls (list of str): List of allowed base URLs for external
-///                                      data requests. Default allows any base URL
+///     vg_spec (str | dict): Vega JSON specification string or dict
 ///     format_locale (str | dict): d3-format locale name or dictionary
 ///     time_format_locale (str | dict): d3-time-format locale name or dictionary
+///     theme (str | None): Named theme (e.g. "dark") to apply during conversion
+///     config (dict | str | None): Chart configuration object to apply during conversion,
+///         or a JSON string or the path of a JSON file holding it (e.g. "~/config.json")
+///     preserve_int64 (bool | None): Whether to pass integers in inline data that JavaScript
+///         numbers can't represent exactly (beyond +/-2^53) through the conversion as strings,
+///         so that they're returned unchanged (default false)
 /// Returns:
-///     bytes: PNG image data
+///     dict: Vega JSON specification dict
 #[pyfunction]
-#[pyo3(
-    signature = (vl_spec, vl_version=None, scale=None, ppi=None, config=None, theme=None, show_warnings=None, allowed_base_urls=None, format_locale=None, time_format_locale=None)
-)]
-fn vegalite_to_png(
-    vl_spec: PyObject,
-    vl_version: Option<&str>,
-    scale: Option<f32>,
-    ppi: Option<f32>,
-    config: Option<PyObject>,
-    theme: Option<String>,
-    show_warnings: Option<bool>,
-    allowed_base_urls: Option<Vec<String>>,
+#[pyo3(signature = (vg_spec, format_locale=None, time_format_locale=None, theme=None, config=None, preserve_int64=None))]
+fn get_final_vega_spec_vg(
+    vg_spec: PyObject,
     format_locale: Option<PyObject>,
     time_format_locale: Option<PyObject>,
+    theme: Option<String>,
+    config: Option<PyObject>,
+    preserve_int64: Option<bool>,
 ) -> PyResult<PyObject> {
-    let vl_version = if let Some(vl_version) = vl_version {
-        VlVersion::from_str(vl_version)?
-    } else {
-        Default::default()
-    };
-    let vl_spec = parse_json_spec(vl_spec)?;
-    let config = config.and_then(|c| parse_json_spec(c).ok());
+    let theme = parse_option_theme(theme)?;
+    let vg_spec = parse_json_spec(vg_spec)?;
+    let config = parse_option_config(config)?;
     let format_locale = parse_option_format_locale(format_locale)?;
     let time_format_locale = parse_option_time_format_locale(time_format_locale)?;
 
-    let mut converter = VL_CONVERTER
-        .lock()
-        .expect("Failed to acquire lock on Vega-Lite converter");
-
-    let png_data = match PYTHON_RUNTIME.block_on(converter.vegalite_to_png(
-        vl_spec,
-        VlOpts {
-            vl_version,
-            config,
-            theme,
-            show_warnings: show_warnings.unwrap_or(false),
-            allowed_base_urls,
-            format_locale,
-            time_format_locale,
-        },
-        scale,
-        ppi,
-    )) {
+    let vega_spec = match with_converter(|converter| {
+        PYTHON_RUNTIME.block_on(converter.get_final_vega_spec_vg(
+            vg_spec,
+            VgOpts {
+                theme,
+                config,
+                allowed_base_urls: None,
+                format_locale,
+                time_format_locale,
+                collect_resource_stats: collect_resource_stats(),
+                collect_dataset_summary: collect_dataset_summary(),
+                fetch_retries: None,
+                fetch_retry_backoff_ms: None,
+                interaction_script: None,
+                preserve_int64: preserve_int64.unwrap_or(false),
+                base_url: None,
+                allow_local_data: false,
+                lenient: false,
+                generic_font_mapping: None,
+                container_width: None,
+                container_height: None,
+                strict_sizing: false,
+                width: None,
+                height: None,
+                resize_mode: ResizeMode::default(),
+                min_size: None,
+                max_rows: None,
+                row_limit_strategy: RowLimitStrategy::default(),
+                annotate_truncation: false,
+                log_level: None,
+                warning_filters: None,
+                suppress_components: None,
+                browser_shims: true,
+                simplify_topojson: None,
+                force_iterations: None,
+                random_seed: None,
+                svg_post_processor: None,
+            },
+        ))
+    }) {
         Ok(vega_spec) => vega_spec,
         Err(err) => {
             return Err(PyValueError::new_err(format!(
-                "Vega-Lite to PNG conversion failed:\n{}",
+                "Vega to final Vega spec conversion failed:\n{}",
                 err
             )))
         }
     };
-
-    Ok(Python::with_gil(|py| -> PyObject {
-        PyBytes::new_bound(py, png_data.as_slice()).into()
-    }))
+    Python::with_gil(|py| -> PyResult<PyObject> {
+        pythonize(py, &vega_spec)
+            .map_err(|err| PyValueError::new_err(err.to_string()))
+            .map(|obj| obj.into())
+    })
 }
 
-/// Convert a Vega spec to JPEG image data.
+/// Freeze a Vega-Lite spec into a self-contained spec that renders identically without
+/// network access.
+///
+/// Data loaded from URLs is inlined as values, the theme and config are merged into the
+/// spec's config, $schema is pinned to the exact Vega-Lite version used, and
+/// usermeta.vlConvert records the vl-convert version and the time the spec was frozen.
 ///
 /// Args:
-///     vg_spec (str | dict): Vega JSON specification string or dict
-///     scale (float): Image scale factor (default 1.0)
-///     quality (int): JPEG Quality between 0 (worst) and 100 (best). Default 90
+///     vl_spec (str | dict): Vega-Lite JSON specification string or dict
+///     vl_version (str | None): Vega-Lite library version string (e.g. 'v5.15')
+///         (default to latest)
+///     config (dict | str | None): Chart configuration object to merge into the spec, or a
+///         JSON string or the path of a JSON file holding it
+///     theme (str | None): Named theme (e.g. "dark") to merge into the spec
 ///     allowed_base_urls (list of str): List of allowed base URLs for external
 ///                                      data requests. Default allows any base URL
-///     format_locale (str | dict): d3-format locale name or dictionary
-///     time_format_locale (str | dict): d3-time-format locale name or dictionary
+///     fetch_retries (int | None): Number of times to retry data requests that fail
+///         with a connection error or 5xx response (default 0)
+///     fetch_retry_backoff_ms (int | None): Delay before the first retry in milliseconds,
+///         doubled for each subsequent retry (default 200)
+///     preserve_int64 (bool | None): Whether to pass integers in inline data that JavaScript
+///         numbers can't represent exactly (beyond +/-2^53) through the conversion as strings,
+///         so that they're returned unchanged (default false)
+///     font (str | None): Font to use for all text: axis, legend, and header labels and
+///         titles, chart titles, and text marks. Config takes precedence
+///     font_size_scale (float | None): Factor to scale the default font sizes of all text by
+///     text_color (str | None): Color to use for all text
+///     config_overrides (dict | None): Config values keyed by dotted paths (e.g.
+///         "axis.labelFontSize"), which are set in order over config and theme
+///     direction (str | None): Writing direction to lay the chart out for, "ltr" or "rtl".
+///         "rtl" mirrors the layout for right-to-left locales: y axes and row headers are
+///         drawn on the right, legends on the left, and titles are anchored at the end.
+///         Orients and anchors set in the spec or config take precedence (default "ltr")
 /// Returns:
-///     bytes: JPEG image data
+///     dict: Frozen Vega-Lite JSON specification dict
 #[pyfunction]
 #[pyo3(
-    signature = (vg_spec, scale=None, quality=None, allowed_base_urls=None, format_locale=None, time_format_locale=None)
+    signature = (vl_spec, vl_version=None, config=None, theme=None, allowed_base_urls=None, fetch_retries=None, fetch_retry_backoff_ms=None, preserve_int64=None, font=None, font_size_scale=None, text_color=None, config_overrides=None, direction=None)
 )]
-fn vega_to_jpeg(
-    vg_spec: PyObject,
-    scale: Option<f32>,
-    quality: Option<u8>,
+fn vegalite_freeze(
+    vl_spec: PyObject,
+    vl_version: Option<&str>,
+    config: Option<PyObject>,
+    theme: Option<String>,
     allowed_base_urls: Option<Vec<String>>,
-    format_locale: Option<PyObject>,
-    time_format_locale: Option<PyObject>,
+    fetch_retries: Option<u32>,
+    fetch_retry_backoff_ms: Option<u64>,
+    preserve_int64: Option<bool>,
+    font: Option<String>,
+    font_size_scale: Option<f32>,
+    text_color: Option<String>,
+    config_overrides: Option<PyObject>,
+    direction: Option<String>,
 ) -> PyResult<PyObject> {
-    let vg_spec = parse_json_spec(vg_spec)?;
-    let format_locale = parse_option_format_locale(format_locale)?;
-    let time_format_locale = parse_option_time_format_locale(time_format_locale)?;
+    let theme = parse_option_theme(theme)?;
+    let vl_spec = parse_json_spec(vl_spec)?;
+    let config = parse_option_config(config)?;
+    let config_overrides = parse_option_config_overrides(config_overrides)?;
+    let direction = parse_option_direction(direction)?;
 
-    let mut converter = VL_CONVERTER
-        .lock()
-        .expect("Failed to acquire lock on Vega-Lite converter");
+    let vl_version = parse_vl_version(vl_version)?;
 
-    let jpeg_data = match PYTHON_RUNTIME.block_on(converter.vega_to_jpeg(
-        vg_spec,
-        VgOpts {
-            allowed_base_urls,
-            format_locale,
-            time_format_locale,
-        },
-        scale,
-        quality,
-    )) {
-        Ok(vega_spec) => vega_spec,
+    let frozen = match with_converter(|converter| {
+        PYTHON_RUNTIME.block_on(converter.vegalite_freeze(
+            vl_spec,
+            VlOpts {
+                vl_version,
+                config,
+                theme,
+                show_warnings: false,
+                allowed_base_urls,
+                format_locale: None,
+                time_format_locale: None,
+                collect_resource_stats: collect_resource_stats(),
+                collect_dataset_summary: collect_dataset_summary(),
+                fetch_retries,
+                fetch_retry_backoff_ms,
+                interaction_script: None,
+                preserve_int64: preserve_int64.unwrap_or(false),
+                base_url: None,
+                allow_local_data: false,
+                lenient: false,
+                font,
+                font_size_scale,
+                text_color,
+                config_overrides,
+                direction,
+                generic_font_mapping: None,
+                container_width: None,
+                container_height: None,
+                strict_sizing: false,
+                width: None,
+                height: None,
+                resize_mode: ResizeMode::default(),
+                min_size: None,
+                max_rows: None,
+                row_limit_strategy: RowLimitStrategy::default(),
+                annotate_truncation: false,
+                log_level: None,
+                warning_filters: None,
+                suppress_components: None,
+                browser_shims: true,
+                simplify_topojson: None,
+                pre_aggregate: false,
+                lenient_dates: false,
+                svg_post_processor: None,
+            },
+        ))
+    }) {
+        Ok(frozen) => frozen,
         Err(err) => {
             return Err(PyValueError::new_err(format!(
-                "Vega to JPEG conversion failed:\n{}",
+                "Vega-Lite freeze failed:\n{}",
                 err
             )))
         }
     };
-
-    Ok(Python::with_gil(|py| -> PyObject {
-        PyBytes::new_bound(py, jpeg_data.as_slice()).into()
-    }))
+    Python::with_gil(|py| -> PyResult<PyObject> {
+        pythonize(py, &frozen)
+            .map_err(|err| PyValueError::new_err(err.to_string()))
+            .map(|obj| obj.into())
+    })
 }
 
-/// Convert a Vega-Lite spec to JPEG image data using a particular
-/// version of the Vega-Lite JavaScript library.
+/// Extract the visible text items from a Vega-Lite chart, with absolute positions and roles.
 ///
 /// Args:
 ///     vl_spec (str | dict): Vega-Lite JSON specification string or dict
-///     vl_version (str): Vega-Lite library version string (e.g. 'v5.15')
+///     vl_version (str | None): Vega-Lite library version string (e.g. 'v5.15')
 ///         (default to latest)
-///     scale (float): Image scale factor (default 1.0)
-///     quality (int): JPEG Quality between 0 (worst) and 100 (best). Default 90
-///     config (dict | None): Chart configuration object to apply during conversion
+///     config (dict | str | None): Chart configuration object to apply during conversion,
+///         or a JSON string or the path of a JSON file holding it (e.g. "~/config.json")
 ///     theme (str | None): Named theme (e.g. "dark") to apply during conversion
 ///     show_warnings (bool | None): Whether to print Vega-Lite compilation warnings (default false)
 ///     allowed_base_urls (list of str): List of allowed base URLs for external
 ///                                      data requests. Default allows any base URL
 ///     format_locale (str | dict): d3-format locale name or dictionary
 ///     time_format_locale (str | dict): d3-time-format locale name or dictionary
+///     fetch_retries (int | None): Number of times to retry data and image requests that fail
+///         with a connection error or 5xx response (default 0)
+///     fetch_retry_backoff_ms (int | None): Delay before the first retry in milliseconds,
+///         doubled for each subsequent retry (default 200)
+///     interactions (list of dict | None): Interaction steps to apply before rendering. Each
+///         step is either {"signal": name, "value": value} or {"event": {"type": type, "x": x,
+///         "y": y, ...}} with x and y in view coordinates
+///     font (str | None): Font to use for all text: axis, legend, and header labels and
+///         titles, chart titles, and text marks. Config takes precedence
+///     font_size_scale (float | None): Factor to scale the default font sizes of all text by
+///     text_color (str | None): Color to use for all text
+///     config_overrides (dict | None): Config values keyed by dotted paths (e.g.
+///         "axis.labelFontSize"), which are set in order over config and theme
+///     direction (str | None): Writing direction to lay the chart out for, "ltr" or "rtl".
+///         "rtl" mirrors the layout for right-to-left locales: y axes and row headers are
+///         drawn on the right, legends on the left, and titles are anchored at the end.
+///         Orients and anchors set in the spec or config take precedence (default "ltr")
+///     generic_font_mapping (dict | None): Fonts to render generic font families with,
+///         keyed by "sans-serif", "serif", "monospace", or "cursive" (e.g.
+///         {"sans-serif": "Inter"})
+///     container_width (float | None): Width of the container that charts sized to their
+///         container (e.g. with "width": "container") are rendered in (default 600)
+///     container_height (float | None): Height of the container that charts sized to their
+///         container are rendered in (default 400)
+///     strict_sizing (bool | None): Fail the conversion of charts sized to their container,
+///         instead of rendering them with container_width and container_height (default false)
+///     width (float | None): Width to render the chart at, replacing the width of the spec
+///         before its axes and legends are laid out (default None)
+///     height (float | None): Height to render the chart at, replacing the height of the spec
+///         (default None)
+///     resize_mode (str | None): How the chart is laid out in width and height. "fit" makes
+///         them the size of the whole chart, including its axes and legends, "pad" makes them
+///         the size of the plot area, and "none" keeps the autosize of the spec (default "fit")
 /// Returns:
-///     bytes: JPEG image data
+///     list of dict: Text items with text, x, y, role, font, and size keys. role is one of
+///         "axis-label", "axis-title", "legend-label", "legend-title", "title", "subtitle",
+///         or "mark"
 #[pyfunction]
 #[pyo3(
-    signature = (vl_spec, vl_version=None, scale=None, quality=None, config=None, theme=None, show_warnings=None, allowed_base_urls=None, format_locale=None, time_format_locale=None)
+    signature = (vl_spec, vl_version=None, config=None, theme=None, show_warnings=None, allowed_base_urls=None, format_locale=None, time_format_locale=None, fetch_retries=None, fetch_retry_backoff_ms=None, interactions=None, font=None, font_size_scale=None, text_color=None, config_overrides=None, direction=None, generic_font_mapping=None, container_width=None, container_height=None, strict_sizing=None, width=None, height=None, resize_mode=None)
 )]
-fn vegalite_to_jpeg(
+fn vegalite_get_text_items(
     vl_spec: PyObject,
     vl_version: Option<&str>,
-    scale: Option<f32>,
-    quality: Option<u8>,
     config: Option<PyObject>,
     theme: Option<String>,
     show_warnings: Option<bool>,
     allowed_base_urls: Option<Vec<String>>,
     format_locale: Option<PyObject>,
     time_format_locale: Option<PyObject>,
+    fetch_retries: Option<u32>,
+    fetch_retry_backoff_ms: Option<u64>,
+    interactions: Option<PyObject>,
+    font: Option<String>,
+    font_size_scale: Option<f32>,
+    text_color: Option<String>,
+    config_overrides: Option<PyObject>,
+    direction: Option<String>,
+    generic_font_mapping: Option<HashMap<String, String>>,
+    container_width: Option<f32>,
+    container_height: Option<f32>,
+    strict_sizing: Option<bool>,
+    width: Option<f32>,
+    height: Option<f32>,
+    resize_mode: Option<String>,
 ) -> PyResult<PyObject> {
-    let vl_version = if let Some(vl_version) = vl_version {
-        VlVersion::from_str(vl_version)?
-    } else {
-        Default::default()
-    };
+    let theme = parse_option_theme(theme)?;
+    let resize_mode = parse_resize_mode(resize_mode)?;
     let vl_spec = parse_json_spec(vl_spec)?;
-    let config = config.and_then(|c| parse_json_spec(c).ok());
+    let config = parse_option_config(config)?;
+    let config_overrides = parse_option_config_overrides(config_overrides)?;
+    let direction = parse_option_direction(direction)?;
     let format_locale = parse_option_format_locale(format_locale)?;
     let time_format_locale = parse_option_time_format_locale(time_format_locale)?;
+    let interactions = parse_option_interactions(interactions)?;
 
-    let mut converter = VL_CONVERTER
-        .lock()
-        .expect("Failed to acquire lock on Vega-Lite converter");
+    let vl_version = parse_vl_version(vl_version)?;
 
-    let jpeg_data = match PYTHON_RUNTIME.block_on(converter.vegalite_to_jpeg(
-        vl_spec,
-        VlOpts {
-            vl_version,
-            config,
-            theme,
-            show_warnings: show_warnings.unwrap_or(false),
-            allowed_base_urls,
-            format_locale,
-            time_format_locale,
-        },
-        scale,
-        quality,
-    )) {
-        Ok(vega_spec) => vega_spec,
+    let text_items = match with_converter(|converter| {
+        PYTHON_RUNTIME.block_on(converter.vegalite_get_text_items(
+            vl_spec,
+            VlOpts {
+                vl_version,
+                config,
+                theme,
+                show_warnings: show_warnings.unwrap_or(false),
+                allowed_base_urls,
+                format_locale,
+                time_format_locale,
+                collect_resource_stats: collect_resource_stats(),
+                collect_dataset_summary: collect_dataset_summary(),
+                fetch_retries,
+                fetch_retry_backoff_ms,
+                interaction_script: interactions,
+                preserve_int64: false,
+                base_url: None,
+                allow_local_data: false,
+                lenient: false,
+                font,
+                font_size_scale,
+                text_color,
+                config_overrides,
+                direction,
+                generic_font_mapping,
+                container_width,
+                container_height,
+                strict_sizing: strict_sizing.unwrap_or(false),
+                width,
+                height,
+                resize_mode,
+                min_size: None,
+                max_rows: None,
+                row_limit_strategy: RowLimitStrategy::default(),
+                annotate_truncation: false,
+                log_level: None,
+                warning_filters: None,
+                suppress_components: None,
+                browser_shims: true,
+                simplify_topojson: None,
+                pre_aggregate: false,
+                lenient_dates: false,
+                svg_post_processor: None,
+            },
+        ))
+    }) {
+        Ok(text_items) => text_items,
         Err(err) => {
             return Err(PyValueError::new_err(format!(
-                "Vega-Lite to JPEG conversion failed:\n{}",
+                "Vega-Lite text item extraction failed:\n{}",
                 err
             )))
         }
     };
-
-    Ok(Python::with_gil(|py| -> PyObject {
-        PyBytes::new_bound(py, jpeg_data.as_slice()).into()
-    }))
+    Python::with_gil(|py| -> PyResult<PyObject> {
+        pythonize(py, &text_items)
+            .map_err(|err| PyValueError::new_err(err.to_string()))
+            .map(|obj| obj.into())
+    })
 }
 
-/// Convert a Vega spec to PDF format
+/// Convert a Vega spec to PNG image data.
 ///
 /// Args:
 ///     vg_spec (str | dict): Vega JSON specification string or dict
 ///     scale (float): Image scale factor (default 1.0)
+///     ppi (float): Pixels per inch (default 72)
 ///     allowed_base_urls (list of str): List of allowed base URLs for external
 ///                                      data requests. Default allows any base URL
 ///     format_locale (str | dict): d3-format locale name or dictionary
 ///     time_format_locale (str | dict): d3-time-format locale name or dictionary
+///     interlaced (bool): Whether to write an interlaced (Adam7) PNG for progressive
+///                        display (default false)
+///     theme (str | None): Named theme (e.g. "dark") to apply during conversion
+///     config (dict | str | None): Chart configuration object to apply during conversion,
+///         or a JSON string or the path of a JSON file holding it (e.g. "~/config.json")
+///     fetch_retries (int | None): Number of times to retry data and image requests that fail
+///         with a connection error or 5xx response (default 0)
+///     fetch_retry_backoff_ms (int | None): Delay before the first retry in milliseconds,
+///         doubled for each subsequent retry (default 200)
+///     interactions (list of dict | None): Interaction steps to apply before rendering. Each
+///         step is either {"signal": name, "value": value} or {"event": {"type": type, "x": x,
+///         "y": y, ...}} with x and y in view coordinates
+///     lenient (bool | None): Leave out images that fail to load instead of failing the
+///         conversion. Skipped images are listed in the "warnings" of the return_info dict
+///         (default false)
+///     generic_font_mapping (dict | None): Fonts to render generic font families with,
+///         keyed by "sans-serif", "serif", "monospace", or "cursive" (e.g.
+///         {"sans-serif": "Inter"})
+///     container_width (float | None): Width of the container that charts sized to their
+///         container (e.g. with "width": "container") are rendered in (default 600)
+///     container_height (float | None): Height of the container that charts sized to their
+///         container are rendered in (default 400)
+///     strict_sizing (bool | None): Fail the conversion of charts sized to their container,
+///         instead of rendering them with container_width and container_height (default false)
+///     return_info (bool | None): Return a dict with the PNG image data under "data", along
+///         with its "width_px", "height_px", "css_width", "css_height", "scale", "ppi", the
+///         "request_hash" of the spec and options, and the render "warnings" of the conversion
+///         (default false)
+///     antialias (bool | None): Whether to antialias the edges of shapes. When false, shapes
+///         are drawn with crisp edges on whole pixels, so that adjacent rects (e.g. heatmap
+///         cells) don't show seams. Text is antialiased either way (default true)
+///     ppi_mode (str | None): How ppi affects the image. "resize-pixels" multiplies the
+///         number of pixels by ppi / 72, and "metadata-only" leaves the number of pixels to
+///         scale and only writes ppi to the PNG metadata (default "resize-pixels")
+///     image_rendering (str | None): How embedded raster images are resampled when they're
+///         scaled. One of "auto", "smooth", or "pixelated", which draws each pixel of an
+///         image as a sharp square. Images with an image-rendering attribute of their own
+///         keep it (default "auto")
+///     flatten_background (str | None): CSS color to composite the image over so that it
+///         has no transparent pixels, e.g. "white" or "#f0f0f0". By default, transparent
+///         parts of the chart are left transparent (default None)
+///     force_iterations (int | None): Run force transforms as static simulations of this
+///         many ticks before the chart is rendered, so that their layout is reproducible.
+///         Force transforms that set "static" or "iterations" keep them (default None)
+///     random_seed (int | None): Seed for the random numbers of the render, which are used
+///         by the random() expression function and by transforms like sample (default None)
+///     width (float | None): Width to render the chart at, replacing the width of the spec
+///         before its axes and legends are laid out (default None)
+///     height (float | None): Height to render the chart at, replacing the height of the spec
+///         (default None)
+///     resize_mode (str | None): How the chart is laid out in width and height. "fit" makes
+///         them the size of the whole chart, including its axes and legends, "pad" makes them
+///         the size of the plot area, and "none" keeps the autosize of the spec (default "fit")
+///     min_size (tuple of int | None): Smallest (width, height) to render the chart at.
+///         Charts that resolve to a size below 1 pixel, like those with empty data, are
+///         enlarged to it instead of failing the conversion (default None)
+///     max_rows (int | None): Largest number of rows that a dataset of the chart may have once
+///         it's loaded, including inline data and data fetched from urls (default None)
+///     row_limit_strategy (str | None): How datasets with more than max_rows rows are
+///         handled. "error" fails the conversion, "head" keeps the first rows, and "sample"
+///         keeps a random sample of the rows, drawn with a seed of 0 or with the seed given
+///         as "sample:SEED" (default "error")
+///     annotate_truncation (bool | None): Add a subtitle that notes the truncation to charts
+///         with datasets that were truncated to max_rows rows (default false)
+///     log_level (str | None): Level of the messages that Vega logs while the chart is
+///         rendered. One of "none", "error", "warn", "info", or "debug". Warnings at that
+///         level are listed in the "warnings" of the return_info dict (default None, which logs
+///         nothing)
+///     warning_filters (list of str | None): Warnings that contain one of these strings, or
+///         that match it as a regular expression when it's written as "/pattern/", are left
+///         out of the render warnings and counted in a summary warning instead (default None)
+///     suppress_components (list of str | None): Components of the chart that are left out of
+///         the image, for applications that draw them themselves. Any of "title", "legend",
+///         "axis-x", "axis-y", or "grid". The chart is laid out without them (default None)
+///     browser_shims (bool | None): Define the browser globals that expressions copied from
+///         web pages read: window, navigator.language (the name of format_locale, or
+///         "en-US"), screen.width and screen.height (the size of the chart), and
+///         devicePixelRatio (the scale of PNG and JPEG images, or 1). Expressions that read
+///         other browser APIs fail with an error that names them (default true)
+///     simplify_topojson (float | None): Simplify the arcs of TopoJSON datasets as they're
+///         parsed, dropping the vertices that are within this tolerance of the simplified
+///         lines, in the units of the topology's coordinates (degrees for unprojected
+///         topologies). Borders that features share stay shared. The reduction in the number
+///         of vertices is listed in the "warnings" of the return_info dict (default None)
+///     svg_post_processor (Callable[[str], str] | None): Function that the generated SVG is
+///         passed through before it's returned or rasterized. It must return valid SVG, and
+///         exceptions that it raises fail the conversion (default None)
 /// Returns:
-///     bytes: PDF file bytes
+///     bytes | dict: PNG image data, or a dict with the image data and its size when
+///         return_info is true
 #[pyfunction]
-#[pyo3(signature = (vg_spec, scale=None, allowed_base_urls=None, format_locale=None, time_format_locale=None))]
-fn vega_to_pdf(
+#[pyo3(
+    signature = (vg_spec, scale=None, ppi=None, allowed_base_urls=None, format_locale=None, time_format_locale=None, interlaced=None, theme=None, config=None, fetch_retries=None, fetch_retry_backoff_ms=None, interactions=None, lenient=None, generic_font_mapping=None, container_width=None, container_height=None, strict_sizing=None, return_info=None, antialias=None, ppi_mode=None, image_rendering=None, flatten_background=None, force_iterations=None, random_seed=None, width=None, height=None, resize_mode=None, min_size=None, max_rows=None, row_limit_strategy=None, annotate_truncation=None, log_level=None, warning_filters=None, suppress_components=None, browser_shims=None, simplify_topojson=None, svg_post_processor=None)
+)]
+fn vega_to_png(
     vg_spec: PyObject,
     scale: Option<f32>,
+    ppi: Option<f32>,
     allowed_base_urls: Option<Vec<String>>,
     format_locale: Option<PyObject>,
     time_format_locale: Option<PyObject>,
+    interlaced: Option<bool>,
+    theme: Option<String>,
+    config: Option<PyObject>,
+    fetch_retries: Option<u32>,
+    fetch_retry_backoff_ms: Option<u64>,
+    interactions: Option<PyObject>,
+    lenient: Option<bool>,
+    generic_font_mapping: Option<HashMap<String, String>>,
+    container_width: Option<f32>,
+    container_height: Option<f32>,
+    strict_sizing: Option<bool>,
+    return_info: Option<bool>,
+    antialias: Option<bool>,
+    ppi_mode: Option<String>,
+    image_rendering: Option<String>,
+    flatten_background: Option<String>,
+    force_iterations: Option<u32>,
+    random_seed: Option<u32>,
+    width: Option<f32>,
+    height: Option<f32>,
+    resize_mode: Option<String>,
+    min_size: Option<(u32, u32)>,
+    max_rows: Option<u64>,
+    row_limit_strategy: Option<String>,
+    annotate_truncation: Option<bool>,
+    log_level: Option<String>,
+    warning_filters: Option<Vec<String>>,
+    suppress_components: Option<Vec<String>>,
+    browser_shims: Option<bool>,
+    simplify_topojson: Option<f64>,
+    svg_post_processor: Option<PyObject>,
 ) -> PyResult<PyObject> {
-    warn_if_scale_not_one_for_pdf(scale)?;
+    let svg_post_processor = parse_svg_post_processor(svg_post_processor)?;
+    let theme = parse_option_theme(theme)?;
+    let resize_mode = parse_resize_mode(resize_mode)?;
+    let row_limit_strategy = parse_row_limit_strategy(row_limit_strategy)?;
+    let log_level = parse_log_level(log_level)?;
+    let suppress_components = parse_suppress_components(suppress_components)?;
     let vg_spec = parse_json_spec(vg_spec)?;
+    let config = parse_option_config(config)?;
     let format_locale = parse_option_format_locale(format_locale)?;
     let time_format_locale = parse_option_time_format_locale(time_format_locale)?;
+    let interactions = parse_option_interactions(interactions)?;
 
-    let mut converter = VL_CONVERTER
-        .lock()
-        .expect("Failed to acquire lock on Vega-Lite converter");
-
-    let pdf_bytes = match PYTHON_RUNTIME.block_on(converter.vega_to_pdf(
-        vg_spec,
-        VgOpts {
-            allowed_base_urls,
-            format_locale,
-            time_format_locale,
-        },
-    )) {
+    let png = match with_converter(|converter| {
+        PYTHON_RUNTIME.block_on(converter.vega_to_png_info_with_opts(
+            vg_spec,
+            VgOpts {
+                theme,
+                config,
+                allowed_base_urls,
+                format_locale,
+                time_format_locale,
+                collect_resource_stats: collect_resource_stats(),
+                collect_dataset_summary: collect_dataset_summary(),
+                fetch_retries,
+                fetch_retry_backoff_ms,
+                interaction_script: interactions,
+                preserve_int64: false,
+                base_url: None,
+                allow_local_data: false,
+                lenient: lenient.unwrap_or(false),
+                generic_font_mapping,
+                container_width,
+                container_height,
+                strict_sizing: strict_sizing.unwrap_or(false),
+                width,
+                height,
+                resize_mode,
+                min_size,
+                max_rows,
+                row_limit_strategy,
+                annotate_truncation: annotate_truncation.unwrap_or(false),
+                log_level,
+                warning_filters,
+                suppress_components,
+                browser_shims: browser_shims.unwrap_or(true),
+                simplify_topojson,
+                force_iterations,
+                random_seed,
+                svg_post_processor,
+            },
+            scale,
+            ppi,
+            PngOpts {
+                interlaced: interlaced.unwrap_or(false),
+                antialias: antialias.unwrap_or(true),
+                ppi_mode: parse_ppi_mode(ppi_mode)?,
+                image_rendering: parse_image_rendering(image_rendering)?,
+                flatten_background: parse_flatten_background(flatten_background)?,
+                ..Default::default()
+            },
+        ))
+    }) {
         Ok(vega_spec) => vega_spec,
         Err(err) => {
             return Err(PyValueError::new_err(format!(
-                "Vega to PDF conversion failed:\n{}",
+                "Vega to PNG conversion failed:\n{}",
                 err
             )))
         }
     };
-    Ok(Python::with_gil(|py| -> PyObject {
-        PyObject::from(PyBytes::new_bound(py, pdf_bytes.as_slice()))
-    }))
+
+    Python::with_gil(|py| png_result_object(py, png, return_info.unwrap_or(false)))
 }
 
-/// Convert a Vega-Lite spec to PDF image data using a particular
+/// Convert a Vega-Lite spec to PNG image data using a particular
 /// version of the Vega-Lite JavaScript library.
 ///
 /// Args:
@@ -645,55 +1779,1733 @@ fn vega_to_pdf(
 ///     vl_version (str): Vega-Lite library version string (e.g. 'v5.15')
 ///         (default to latest)
 ///     scale (float): Image scale factor (default 1.0)
-///     config (dict | None): Chart configuration object to apply during conversion
+///     ppi (float): Pixels per inch (default 72)
+///     config (dict | str | None): Chart configuration object to apply during conversion,
+///         or a JSON string or the path of a JSON file holding it (e.g. "~/config.json")
 ///     theme (str | None): Named theme (e.g. "dark") to apply during conversion
+///     show_warnings (bool | None): Whether to print Vega-Lite compilation warnings (default false)
 ///     allowed_base_urls (list of str): List of allowed base URLs for external
 ///                                      data requests. Default allows any base URL
 ///     format_locale (str | dict): d3-format locale name or dictionary
 ///     time_format_locale (str | dict): d3-time-format locale name or dictionary
+///     interlaced (bool): Whether to write an interlaced (Adam7) PNG for progressive
+///                        display (default false)
+///     fetch_retries (int | None): Number of times to retry data and image requests that fail
+///         with a connection error or 5xx response (default 0)
+///     fetch_retry_backoff_ms (int | None): Delay before the first retry in milliseconds,
+///         doubled for each subsequent retry (default 200)
+///     interactions (list of dict | None): Interaction steps to apply before rendering. Each
+///         step is either {"signal": name, "value": value} or {"event": {"type": type, "x": x,
+///         "y": y, ...}} with x and y in view coordinates
+///     lenient (bool | None): Leave out images that fail to load instead of failing the
+///         conversion. Skipped images are listed in the "warnings" of the return_info dict
+///         (default false)
+///     font (str | None): Font to use for all text: axis, legend, and header labels and
+///         titles, chart titles, and text marks. Config takes precedence
+///     font_size_scale (float | None): Factor to scale the default font sizes of all text by
+///     text_color (str | None): Color to use for all text
+///     config_overrides (dict | None): Config values keyed by dotted paths (e.g.
+///         "axis.labelFontSize"), which are set in order over config and theme
+///     direction (str | None): Writing direction to lay the chart out for, "ltr" or "rtl".
+///         "rtl" mirrors the layout for right-to-left locales: y axes and row headers are
+///         drawn on the right, legends on the left, and titles are anchored at the end.
+///         Orients and anchors set in the spec or config take precedence (default "ltr")
+///     generic_font_mapping (dict | None): Fonts to render generic font families with,
+///         keyed by "sans-serif", "serif", "monospace", or "cursive" (e.g.
+///         {"sans-serif": "Inter"})
+///     container_width (float | None): Width of the container that charts sized to their
+///         container (e.g. with "width": "container") are rendered in (default 600)
+///     container_height (float | None): Height of the container that charts sized to their
+///         container are rendered in (default 400)
+///     strict_sizing (bool | None): Fail the conversion of charts sized to their container,
+///         instead of rendering them with container_width and container_height (default false)
+///     themes (list of str | None): Named themes to render the chart with, once per theme
+///         ("default" renders it without a theme). Data loaded from urls is fetched once and
+///         shared across the themes. When given, a dict of PNG image data keyed by theme is
+///         returned and theme is ignored
+///     return_info (bool | None): Return a dict with the PNG image data under "data", along
+///         with its "width_px", "height_px", "css_width", "css_height", "scale", "ppi", the
+///         "request_hash" of the spec and options, and the render "warnings" of the conversion
+///         (default false)
+///     antialias (bool | None): Whether to antialias the edges of shapes. When false, shapes
+///         are drawn with crisp edges on whole pixels, so that adjacent rects (e.g. heatmap
+///         cells) don't show seams. Text is antialiased either way (default true)
+///     ppi_mode (str | None): How ppi affects the image. "resize-pixels" multiplies the
+///         number of pixels by ppi / 72, and "metadata-only" leaves the number of pixels to
+///         scale and only writes ppi to the PNG metadata (default "resize-pixels")
+///     image_rendering (str | None): How embedded raster images are resampled when they're
+///         scaled. One of "auto", "smooth", or "pixelated", which draws each pixel of an
+///         image as a sharp square. Images with an image-rendering attribute of their own
+///         keep it (default "auto")
+///     flatten_background (str | None): CSS color to composite the image over so that it
+///         has no transparent pixels, e.g. "white" or "#f0f0f0". By default, transparent
+///         parts of the chart are left transparent (default None)
+///     width (float | None): Width to render the chart at, replacing the width of the spec
+///         before its axes and legends are laid out (default None)
+///     height (float | None): Height to render the chart at, replacing the height of the spec
+///         (default None)
+///     resize_mode (str | None): How the chart is laid out in width and height. "fit" makes
+///         them the size of the whole chart, including its axes and legends, "pad" makes them
+///         the size of the plot area, and "none" keeps the autosize of the spec (default "fit")
+///     min_size (tuple of int | None): Smallest (width, height) to render the chart at.
+///         Charts that resolve to a size below 1 pixel, like those with empty data, are
+///         enlarged to it instead of failing the conversion (default None)
+///     max_rows (int | None): Largest number of rows that a dataset of the chart may have once
+///         it's loaded, including inline data and data fetched from urls (default None)
+///     row_limit_strategy (str | None): How datasets with more than max_rows rows are
+///         handled. "error" fails the conversion, "head" keeps the first rows, and "sample"
+///         keeps a random sample of the rows, drawn with a seed of 0 or with the seed given
+///         as "sample:SEED" (default "error")
+///     annotate_truncation (bool | None): Add a subtitle that notes the truncation to charts
+///         with datasets that were truncated to max_rows rows (default false)
+///     log_level (str | None): Level of the messages that Vega logs while the chart is
+///         rendered. One of "none", "error", "warn", "info", or "debug". Warnings at that
+///         level are listed in the "warnings" of the return_info dict (default None, which logs
+///         nothing)
+///     warning_filters (list of str | None): Warnings that contain one of these strings, or
+///         that match it as a regular expression when it's written as "/pattern/", are left
+///         out of the render warnings and counted in a summary warning instead (default None)
+///     suppress_components (list of str | None): Components of the chart that are left out of
+///         the image, for applications that draw them themselves. Any of "title", "legend",
+///         "axis-x", "axis-y", or "grid". The chart is laid out without them (default None)
+///     browser_shims (bool | None): Define the browser globals that expressions copied from
+///         web pages read: window, navigator.language (the name of format_locale, or
+///         "en-US"), screen.width and screen.height (the size of the chart), and
+///         devicePixelRatio (the scale of PNG and JPEG images, or 1). Expressions that read
+///         other browser APIs fail with an error that names them (default true)
+///     simplify_topojson (float | None): Simplify the arcs of TopoJSON datasets as they're
+///         parsed, dropping the vertices that are within this tolerance of the simplified
+///         lines, in the units of the topology's coordinates (degrees for unprojected
+///         topologies). Borders that features share stay shared. The reduction in the number
+///         of vertices is listed in the "warnings" of the return_info dict (default None)
+///     lenient_dates (bool | None): Rewrite the dates of the temporal fields of the spec's
+///         inline data that aren't in the ISO 8601 format, like "03/27/2024 5:00 PM", to ISO
+///         8601 before the chart is rendered. The rewritten fields are listed in
+///         the "warnings" of the return_info dict (default false)
+///     svg_post_processor (Callable[[str], str] | None): Function that the generated SVG is
+///         passed through before it's returned or rasterized. It must return valid SVG, and
+///         exceptions that it raises fail the conversion (default None)
 /// Returns:
-///     bytes: PDF image data
+///     bytes | dict: PNG image data, PNG image data keyed by theme, or a dict with the image
+///         data and its size when return_info is true
 #[pyfunction]
 #[pyo3(
-    signature = (vl_spec, vl_version=None, scale=None, config=None, theme=None, allowed_base_urls=None, format_locale=None, time_format_locale=None)
+    signature = (vl_spec, vl_version=None, scale=None, ppi=None, config=None, theme=None, show_warnings=None, allowed_base_urls=None, format_locale=None, time_format_locale=None, interlaced=None, fetch_retries=None, fetch_retry_backoff_ms=None, interactions=None, lenient=None, font=None, font_size_scale=None, text_color=None, config_overrides=None, direction=None, generic_font_mapping=None, container_width=None, container_height=None, strict_sizing=None, themes=None, return_info=None, antialias=None, ppi_mode=None, image_rendering=None, flatten_background=None, width=None, height=None, resize_mode=None, min_size=None, max_rows=None, row_limit_strategy=None, annotate_truncation=None, log_level=None, warning_filters=None, suppress_components=None, browser_shims=None, simplify_topojson=None, lenient_dates=None, svg_post_processor=None)
 )]
-fn vegalite_to_pdf(
+fn vegalite_to_png(
     vl_spec: PyObject,
     vl_version: Option<&str>,
     scale: Option<f32>,
+    ppi: Option<f32>,
     config: Option<PyObject>,
     theme: Option<String>,
+    show_warnings: Option<bool>,
     allowed_base_urls: Option<Vec<String>>,
     format_locale: Option<PyObject>,
     time_format_locale: Option<PyObject>,
+    interlaced: Option<bool>,
+    fetch_retries: Option<u32>,
+    fetch_retry_backoff_ms: Option<u64>,
+    interactions: Option<PyObject>,
+    lenient: Option<bool>,
+    font: Option<String>,
+    font_size_scale: Option<f32>,
+    text_color: Option<String>,
+    config_overrides: Option<PyObject>,
+    direction: Option<String>,
+    generic_font_mapping: Option<HashMap<String, String>>,
+    container_width: Option<f32>,
+    container_height: Option<f32>,
+    strict_sizing: Option<bool>,
+    themes: Option<Vec<String>>,
+    return_info: Option<bool>,
+    antialias: Option<bool>,
+    ppi_mode: Option<String>,
+    image_rendering: Option<String>,
+    flatten_background: Option<String>,
+    width: Option<f32>,
+    height: Option<f32>,
+    resize_mode: Option<String>,
+    min_size: Option<(u32, u32)>,
+    max_rows: Option<u64>,
+    row_limit_strategy: Option<String>,
+    annotate_truncation: Option<bool>,
+    log_level: Option<String>,
+    warning_filters: Option<Vec<String>>,
+    suppress_components: Option<Vec<String>>,
+    browser_shims: Option<bool>,
+    simplify_topojson: Option<f64>,
+    lenient_dates: Option<bool>,
+    svg_post_processor: Option<PyObject>,
 ) -> PyResult<PyObject> {
-    warn_if_scale_not_one_for_pdf(scale)?;
-    let vl_version = if let Some(vl_version) = vl_version {
-        VlVersion::from_str(vl_version)?
-    } else {
-        Default::default()
+    let svg_post_processor = parse_svg_post_processor(svg_post_processor)?;
+    let theme = parse_option_theme(theme)?;
+    let resize_mode = parse_resize_mode(resize_mode)?;
+    let row_limit_strategy = parse_row_limit_strategy(row_limit_strategy)?;
+    let log_level = parse_log_level(log_level)?;
+    let suppress_components = parse_suppress_components(suppress_components)?;
+    let themes = parse_option_themes(themes)?;
+    if themes.is_some() && return_info.unwrap_or(false) {
+        return Err(PyValueError::new_err(
+            "return_info can't be combined with themes",
+        ));
+    }
+    let vl_version = parse_vl_version(vl_version)?;
+    let vl_spec = parse_json_spec(vl_spec)?;
+    let config = parse_option_config(config)?;
+    let config_overrides = parse_option_config_overrides(config_overrides)?;
+    let direction = parse_option_direction(direction)?;
+    let format_locale = parse_option_format_locale(format_locale)?;
+    let time_format_locale = parse_option_time_format_locale(time_format_locale)?;
+    let interactions = parse_option_interactions(interactions)?;
+
+    let vl_opts = VlOpts {
+        vl_version,
+        config,
+        theme,
+        show_warnings: show_warnings.unwrap_or(false),
+        allowed_base_urls,
+        format_locale,
+        time_format_locale,
+        collect_resource_stats: collect_resource_stats(),
+        collect_dataset_summary: collect_dataset_summary(),
+        fetch_retries,
+        fetch_retry_backoff_ms,
+        interaction_script: interactions,
+        preserve_int64: false,
+        base_url: None,
+        allow_local_data: false,
+        lenient: lenient.unwrap_or(false),
+        font,
+        font_size_scale,
+        text_color,
+        config_overrides,
+        direction,
+        generic_font_mapping,
+        container_width,
+        container_height,
+        strict_sizing: strict_sizing.unwrap_or(false),
+        width,
+        height,
+        resize_mode,
+        min_size,
+        max_rows,
+        row_limit_strategy,
+        annotate_truncation: annotate_truncation.unwrap_or(false),
+        log_level,
+        warning_filters,
+        suppress_components,
+        browser_shims: browser_shims.unwrap_or(true),
+        simplify_topojson,
+        pre_aggregate: false,
+        lenient_dates: lenient_dates.unwrap_or(false),
+        svg_post_processor,
+    };
+    let png_opts = PngOpts {
+        interlaced: interlaced.unwrap_or(false),
+        antialias: antialias.unwrap_or(true),
+        ppi_mode: parse_ppi_mode(ppi_mode)?,
+        image_rendering: parse_image_rendering(image_rendering)?,
+        flatten_background: parse_flatten_background(flatten_background)?,
+        ..Default::default()
+    };
+
+    if let Some(themes) = themes {
+        let themes: Vec<&str> = themes.iter().map(String::as_str).collect();
+        let pngs =
+            match with_converter(|converter| {
+                PYTHON_RUNTIME.block_on(converter.vegalite_to_png_themed_with_opts(
+                    vl_spec, vl_opts, &themes, scale, ppi, png_opts,
+                ))
+            }) {
+                Ok(pngs) => pngs,
+                Err(err) => {
+                    return Err(PyValueError::new_err(format!(
+                        "Vega-Lite to PNG conversion failed:\n{}",
+                        err
+                    )))
+                }
+            };
+        return Python::with_gil(|py| -> PyResult<PyObject> {
+            let dict = PyDict::new_bound(py);
+            for (theme, png_data) in &pngs {
+                dict.set_item(theme, PyBytes::new_bound(py, png_data))?;
+            }
+            Ok(dict.into())
+        });
+    }
+
+    let png = match with_converter(|converter| {
+        PYTHON_RUNTIME.block_on(
+            converter.vegalite_to_png_info_with_opts(vl_spec, vl_opts, scale, ppi, png_opts),
+        )
+    }) {
+        Ok(vega_spec) => vega_spec,
+        Err(err) => {
+            return Err(PyValueError::new_err(format!(
+                "Vega-Lite to PNG conversion failed:\n{}",
+                err
+            )))
+        }
+    };
+
+    Python::with_gil(|py| png_result_object(py, png, return_info.unwrap_or(false)))
+}
+
+/// Convert a Vega-Lite spec to a Vega spec and an SVG image string in a single pass,
+/// using a particular version of the Vega-Lite JavaScript library.
+///
+/// The returned Vega spec is the exact spec that was rendered to produce the SVG image.
+///
+/// Args:
+///     vl_spec (str | dict): Vega-Lite JSON specification string or dict
+///     vl_version (str | None): Vega-Lite library version string (e.g. 'v5.15')
+///         (default to latest)
+///     config (dict | str | None): Chart configuration object to apply during conversion,
+///         or a JSON string or the path of a JSON file holding it (e.g. "~/config.json")
+///     theme (str | None): Named theme (e.g. "dark") to apply during conversion
+///     show_warnings (bool | None): Whether to print Vega-Lite compilation warnings (default false)
+///     allowed_base_urls (list of str): List of allowed base URLs for external
+///                                      data requests. Default allows any base URL
+///     format_locale (str | dict): d3-format locale name or dictionary
+///     time_format_locale (str | dict): d3-time-format locale name or dictionary
+///     fetch_retries (int | None): Number of times to retry data and image requests that fail
+///         with a connection error or 5xx response (default 0)
+///     fetch_retry_backoff_ms (int | None): Delay before the first retry in milliseconds,
+///         doubled for each subsequent retry (default 200)
+///     interactions (list of dict | None): Interaction steps to apply before rendering. Each
+///         step is either {"signal": name, "value": value} or {"event": {"type": type, "x": x,
+///         "y": y, ...}} with x and y in view coordinates
+///     lenient (bool | None): Leave out images that fail to load instead of failing the
+///         conversion (default false)
+///     font (str | None): Font to use for all text: axis, legend, and header labels and
+///         titles, chart titles, and text marks. Config takes precedence
+///     font_size_scale (float | None): Factor to scale the default font sizes of all text by
+///     text_color (str | None): Color to use for all text
+///     config_overrides (dict | None): Config values keyed by dotted paths (e.g.
+///         "axis.labelFontSize"), which are set in order over config and theme
+///     direction (str | None): Writing direction to lay the chart out for, "ltr" or "rtl".
+///         "rtl" mirrors the layout for right-to-left locales: y axes and row headers are
+///         drawn on the right, legends on the left, and titles are anchored at the end.
+///         Orients and anchors set in the spec or config take precedence (default "ltr")
+///     generic_font_mapping (dict | None): Fonts to render generic font families with,
+///         keyed by "sans-serif", "serif", "monospace", or "cursive" (e.g.
+///         {"sans-serif": "Inter"})
+///     container_width (float | None): Width of the container that charts sized to their
+///         container (e.g. with "width": "container") are rendered in (default 600)
+///     container_height (float | None): Height of the container that charts sized to their
+///         container are rendered in (default 400)
+///     strict_sizing (bool | None): Fail the conversion of charts sized to their container,
+///         instead of rendering them with container_width and container_height (default false)
+///     width (float | None): Width to render the chart at, replacing the width of the spec
+///         before its axes and legends are laid out (default None)
+///     height (float | None): Height to render the chart at, replacing the height of the spec
+///         (default None)
+///     resize_mode (str | None): How the chart is laid out in width and height. "fit" makes
+///         them the size of the whole chart, including its axes and legends, "pad" makes them
+///         the size of the plot area, and "none" keeps the autosize of the spec (default "fit")
+///     min_size (tuple of int | None): Smallest (width, height) to render the chart at.
+///         Charts that resolve to a size below 1 pixel, like those with empty data, are
+///         enlarged to it instead of failing the conversion (default None)
+///     max_rows (int | None): Largest number of rows that a dataset of the chart may have once
+///         it's loaded, including inline data and data fetched from urls (default None)
+///     row_limit_strategy (str | None): How datasets with more than max_rows rows are
+///         handled. "error" fails the conversion, "head" keeps the first rows, and "sample"
+///         keeps a random sample of the rows, drawn with a seed of 0 or with the seed given
+///         as "sample:SEED" (default "error")
+///     annotate_truncation (bool | None): Add a subtitle that notes the truncation to charts
+///         with datasets that were truncated to max_rows rows (default false)
+///     log_level (str | None): Level of the messages that Vega logs while the chart is
+///         rendered. One of "none", "error", "warn", "info", or "debug". Warnings at that
+///         level are logged (default None, which logs nothing)
+///     warning_filters (list of str | None): Warnings that contain one of these strings, or
+///         that match it as a regular expression when it's written as "/pattern/", are left
+///         out of the render warnings and counted in a summary warning instead (default None)
+///     suppress_components (list of str | None): Components of the chart that are left out of
+///         the image, for applications that draw them themselves. Any of "title", "legend",
+///         "axis-x", "axis-y", or "grid". The chart is laid out without them (default None)
+///     browser_shims (bool | None): Define the browser globals that expressions copied from
+///         web pages read: window, navigator.language (the name of format_locale, or
+///         "en-US"), screen.width and screen.height (the size of the chart), and
+///         devicePixelRatio (the scale of PNG and JPEG images, or 1). Expressions that read
+///         other browser APIs fail with an error that names them (default true)
+///     simplify_topojson (float | None): Simplify the arcs of TopoJSON datasets as they're
+///         parsed, dropping the vertices that are within this tolerance of the simplified
+///         lines, in the units of the topology's coordinates (degrees for unprojected
+///         topologies). Borders that features share stay shared (default None)
+///     lenient_dates (bool | None): Rewrite the dates of the temporal fields of the spec's
+///         inline data that aren't in the ISO 8601 format, like "03/27/2024 5:00 PM", to ISO
+///         8601 before the chart is rendered (default false)
+/// Returns:
+///     tuple[dict, str]: Vega JSON specification dict and SVG image string
+#[pyfunction]
+#[pyo3(
+    signature = (vl_spec, vl_version=None, config=None, theme=None, show_warnings=None, allowed_base_urls=None, format_locale=None, time_format_locale=None, fetch_retries=None, fetch_retry_backoff_ms=None, interactions=None, lenient=None, font=None, font_size_scale=None, text_color=None, config_overrides=None, direction=None, generic_font_mapping=None, container_width=None, container_height=None, strict_sizing=None, width=None, height=None, resize_mode=None, min_size=None, max_rows=None, row_limit_strategy=None, annotate_truncation=None, log_level=None, warning_filters=None, suppress_components=None, browser_shims=None, simplify_topojson=None, lenient_dates=None)
+)]
+fn vegalite_to_vega_and_svg(
+    vl_spec: PyObject,
+    vl_version: Option<&str>,
+    config: Option<PyObject>,
+    theme: Option<String>,
+    show_warnings: Option<bool>,
+    allowed_base_urls: Option<Vec<String>>,
+    format_locale: Option<PyObject>,
+    time_format_locale: Option<PyObject>,
+    fetch_retries: Option<u32>,
+    fetch_retry_backoff_ms: Option<u64>,
+    interactions: Option<PyObject>,
+    lenient: Option<bool>,
+    font: Option<String>,
+    font_size_scale: Option<f32>,
+    text_color: Option<String>,
+    config_overrides: Option<PyObject>,
+    direction: Option<String>,
+    generic_font_mapping: Option<HashMap<String, String>>,
+    container_width: Option<f32>,
+    container_height: Option<f32>,
+    strict_sizing: Option<bool>,
+    width: Option<f32>,
+    height: Option<f32>,
+    resize_mode: Option<String>,
+    min_size: Option<(u32, u32)>,
+    max_rows: Option<u64>,
+    row_limit_strategy: Option<String>,
+    annotate_truncation: Option<bool>,
+    log_level: Option<String>,
+    warning_filters: Option<Vec<String>>,
+    suppress_components: Option<Vec<String>>,
+    browser_shims: Option<bool>,
+    simplify_topojson: Option<f64>,
+    lenient_dates: Option<bool>,
+) -> PyResult<PyObject> {
+    let theme = parse_option_theme(theme)?;
+    let resize_mode = parse_resize_mode(resize_mode)?;
+    let row_limit_strategy = parse_row_limit_strategy(row_limit_strategy)?;
+    let log_level = parse_log_level(log_level)?;
+    let suppress_components = parse_suppress_components(suppress_components)?;
+    let vl_spec = parse_json_spec(vl_spec)?;
+    let config = parse_option_config(config)?;
+    let config_overrides = parse_option_config_overrides(config_overrides)?;
+    let direction = parse_option_direction(direction)?;
+    let format_locale = parse_option_format_locale(format_locale)?;
+    let time_format_locale = parse_option_time_format_locale(time_format_locale)?;
+    let interactions = parse_option_interactions(interactions)?;
+
+    let vl_version = parse_vl_version(vl_version)?;
+
+    let (vega_spec, svg) = match with_converter(|converter| {
+        PYTHON_RUNTIME.block_on(converter.vegalite_to_vega_and_svg(
+            vl_spec,
+            VlOpts {
+                vl_version,
+                config,
+                theme,
+                show_warnings: show_warnings.unwrap_or(false),
+                allowed_base_urls,
+                format_locale,
+                time_format_locale,
+                collect_resource_stats: collect_resource_stats(),
+                collect_dataset_summary: collect_dataset_summary(),
+                fetch_retries,
+                fetch_retry_backoff_ms,
+                interaction_script: interactions,
+                preserve_int64: false,
+                base_url: None,
+                allow_local_data: false,
+                lenient: lenient.unwrap_or(false),
+                font,
+                font_size_scale,
+                text_color,
+                config_overrides,
+                direction,
+                generic_font_mapping,
+                container_width,
+                container_height,
+                strict_sizing: strict_sizing.unwrap_or(false),
+                width,
+                height,
+                resize_mode,
+                min_size,
+                max_rows,
+                row_limit_strategy,
+                annotate_truncation: annotate_truncation.unwrap_or(false),
+                log_level,
+                warning_filters,
+                suppress_components,
+                browser_shims: browser_shims.unwrap_or(true),
+                simplify_topojson,
+                pre_aggregate: false,
+                lenient_dates: lenient_dates.unwrap_or(false),
+                svg_post_processor: None,
+            },
+        ))
+    }) {
+        Ok(result) => result,
+        Err(err) => {
+            return Err(PyValueError::new_err(format!(
+                "Vega-Lite to Vega and SVG conversion failed:\n{}",
+                err
+            )))
+        }
     };
+    Python::with_gil(|py| -> PyResult<PyObject> {
+        let vega_spec =
+            pythonize(py, &vega_spec).map_err(|err| PyValueError::new_err(err.to_string()))?;
+        Ok((vega_spec, svg).into_py(py))
+    })
+}
+
+/// Convert a Vega-Lite spec to a Vega spec and PNG image data in a single pass,
+/// using a particular version of the Vega-Lite JavaScript library.
+///
+/// The returned Vega spec is the exact spec that was rendered to produce the PNG image.
+///
+/// Args:
+///     vl_spec (str | dict): Vega-Lite JSON specification string or dict
+///     vl_version (str): Vega-Lite library version string (e.g. 'v5.15')
+///         (default to latest)
+///     scale (float): Image scale factor (default 1.0)
+///     ppi (float): Pixels per inch (default 72)
+///     config (dict | str | None): Chart configuration object to apply during conversion,
+///         or a JSON string or the path of a JSON file holding it (e.g. "~/config.json")
+///     theme (str | None): Named theme (e.g. "dark") to apply during conversion
+///     show_warnings (bool | None): Whether to print Vega-Lite compilation warnings (default false)
+///     allowed_base_urls (list of str): List of allowed base URLs for external
+///                                      data requests. Default allows any base URL
+///     format_locale (str | dict): d3-format locale name or dictionary
+///     time_format_locale (str | dict): d3-time-format locale name or dictionary
+///     fetch_retries (int | None): Number of times to retry data and image requests that fail
+///         with a connection error or 5xx response (default 0)
+///     fetch_retry_backoff_ms (int | None): Delay before the first retry in milliseconds,
+///         doubled for each subsequent retry (default 200)
+///     interactions (list of dict | None): Interaction steps to apply before rendering. Each
+///         step is either {"signal": name, "value": value} or {"event": {"type": type, "x": x,
+///         "y": y, ...}} with x and y in view coordinates
+///     lenient (bool | None): Leave out images that fail to load instead of failing the
+///         conversion (default false)
+///     font (str | None): Font to use for all text: axis, legend, and header labels and
+///         titles, chart titles, and text marks. Config takes precedence
+///     font_size_scale (float | None): Factor to scale the default font sizes of all text by
+///     text_color (str | None): Color to use for all text
+///     config_overrides (dict | None): Config values keyed by dotted paths (e.g.
+///         "axis.labelFontSize"), which are set in order over config and theme
+///     direction (str | None): Writing direction to lay the chart out for, "ltr" or "rtl".
+///         "rtl" mirrors the layout for right-to-left locales: y axes and row headers are
+///         drawn on the right, legends on the left, and titles are anchored at the end.
+///         Orients and anchors set in the spec or config take precedence (default "ltr")
+///     generic_font_mapping (dict | None): Fonts to render generic font families with,
+///         keyed by "sans-serif", "serif", "monospace", or "cursive" (e.g.
+///         {"sans-serif": "Inter"})
+///     container_width (float | None): Width of the container that charts sized to their
+///         container (e.g. with "width": "container") are rendered in (default 600)
+///     container_height (float | None): Height of the container that charts sized to their
+///         container are rendered in (default 400)
+///     strict_sizing (bool | None): Fail the conversion of charts sized to their container,
+///         instead of rendering them with container_width and container_height (default false)
+///     width (float | None): Width to render the chart at, replacing the width of the spec
+///         before its axes and legends are laid out (default None)
+///     height (float | None): Height to render the chart at, replacing the height of the spec
+///         (default None)
+///     resize_mode (str | None): How the chart is laid out in width and height. "fit" makes
+///         them the size of the whole chart, including its axes and legends, "pad" makes them
+///         the size of the plot area, and "none" keeps the autosize of the spec (default "fit")
+///     min_size (tuple of int | None): Smallest (width, height) to render the chart at.
+///         Charts that resolve to a size below 1 pixel, like those with empty data, are
+///         enlarged to it instead of failing the conversion (default None)
+///     max_rows (int | None): Largest number of rows that a dataset of the chart may have once
+///         it's loaded, including inline data and data fetched from urls (default None)
+///     row_limit_strategy (str | None): How datasets with more than max_rows rows are
+///         handled. "error" fails the conversion, "head" keeps the first rows, and "sample"
+///         keeps a random sample of the rows, drawn with a seed of 0 or with the seed given
+///         as "sample:SEED" (default "error")
+///     annotate_truncation (bool | None): Add a subtitle that notes the truncation to charts
+///         with datasets that were truncated to max_rows rows (default false)
+///     log_level (str | None): Level of the messages that Vega logs while the chart is
+///         rendered. One of "none", "error", "warn", "info", or "debug". Warnings at that
+///         level are logged (default None, which logs nothing)
+///     warning_filters (list of str | None): Warnings that contain one of these strings, or
+///         that match it as a regular expression when it's written as "/pattern/", are left
+///         out of the render warnings and counted in a summary warning instead (default None)
+///     suppress_components (list of str | None): Components of the chart that are left out of
+///         the image, for applications that draw them themselves. Any of "title", "legend",
+///         "axis-x", "axis-y", or "grid". The chart is laid out without them (default None)
+///     browser_shims (bool | None): Define the browser globals that expressions copied from
+///         web pages read: window, navigator.language (the name of format_locale, or
+///         "en-US"), screen.width and screen.height (the size of the chart), and
+///         devicePixelRatio (the scale of PNG and JPEG images, or 1). Expressions that read
+///         other browser APIs fail with an error that names them (default true)
+///     simplify_topojson (float | None): Simplify the arcs of TopoJSON datasets as they're
+///         parsed, dropping the vertices that are within this tolerance of the simplified
+///         lines, in the units of the topology's coordinates (degrees for unprojected
+///         topologies). Borders that features share stay shared (default None)
+///     lenient_dates (bool | None): Rewrite the dates of the temporal fields of the spec's
+///         inline data that aren't in the ISO 8601 format, like "03/27/2024 5:00 PM", to ISO
+///         8601 before the chart is rendered (default false)
+/// Returns:
+///     tuple[dict, bytes]: Vega JSON specification dict and PNG image data
+#[pyfunction]
+#[pyo3(
+    signature = (vl_spec, vl_version=None, scale=None, ppi=None, config=None, theme=None, show_warnings=None, allowed_base_urls=None, format_locale=None, time_format_locale=None, fetch_retries=None, fetch_retry_backoff_ms=None, interactions=None, lenient=None, font=None, font_size_scale=None, text_color=None, config_overrides=None, direction=None, generic_font_mapping=None, container_width=None, container_height=None, strict_sizing=None, width=None, height=None, resize_mode=None, min_size=None, max_rows=None, row_limit_strategy=None, annotate_truncation=None, log_level=None, warning_filters=None, suppress_components=None, browser_shims=None, simplify_topojson=None, lenient_dates=None)
+)]
+fn vegalite_to_vega_and_png(
+    vl_spec: PyObject,
+    vl_version: Option<&str>,
+    scale: Option<f32>,
+    ppi: Option<f32>,
+    config: Option<PyObject>,
+    theme: Option<String>,
+    show_warnings: Option<bool>,
+    allowed_base_urls: Option<Vec<String>>,
+    format_locale: Option<PyObject>,
+    time_format_locale: Option<PyObject>,
+    fetch_retries: Option<u32>,
+    fetch_retry_backoff_ms: Option<u64>,
+    interactions: Option<PyObject>,
+    lenient: Option<bool>,
+    font: Option<String>,
+    font_size_scale: Option<f32>,
+    text_color: Option<String>,
+    config_overrides: Option<PyObject>,
+    direction: Option<String>,
+    generic_font_mapping: Option<HashMap<String, String>>,
+    container_width: Option<f32>,
+    container_height: Option<f32>,
+    strict_sizing: Option<bool>,
+    width: Option<f32>,
+    height: Option<f32>,
+    resize_mode: Option<String>,
+    min_size: Option<(u32, u32)>,
+    max_rows: Option<u64>,
+    row_limit_strategy: Option<String>,
+    annotate_truncation: Option<bool>,
+    log_level: Option<String>,
+    warning_filters: Option<Vec<String>>,
+    suppress_components: Option<Vec<String>>,
+    browser_shims: Option<bool>,
+    simplify_topojson: Option<f64>,
+    lenient_dates: Option<bool>,
+) -> PyResult<PyObject> {
+    let theme = parse_option_theme(theme)?;
+    let resize_mode = parse_resize_mode(resize_mode)?;
+    let row_limit_strategy = parse_row_limit_strategy(row_limit_strategy)?;
+    let log_level = parse_log_level(log_level)?;
+    let suppress_components = parse_suppress_components(suppress_components)?;
+    let vl_version = parse_vl_version(vl_version)?;
     let vl_spec = parse_json_spec(vl_spec)?;
-    let config = config.and_then(|c| parse_json_spec(c).ok());
+    let config = parse_option_config(config)?;
+    let config_overrides = parse_option_config_overrides(config_overrides)?;
+    let direction = parse_option_direction(direction)?;
     let format_locale = parse_option_format_locale(format_locale)?;
     let time_format_locale = parse_option_time_format_locale(time_format_locale)?;
+    let interactions = parse_option_interactions(interactions)?;
 
-    let mut converter = VL_CONVERTER
-        .lock()
-        .expect("Failed to acquire lock on Vega-Lite converter");
+    let (vega_spec, png_data) = match with_converter(|converter| {
+        PYTHON_RUNTIME.block_on(converter.vegalite_to_vega_and_png(
+            vl_spec,
+            VlOpts {
+                vl_version,
+                config,
+                theme,
+                show_warnings: show_warnings.unwrap_or(false),
+                allowed_base_urls,
+                format_locale,
+                time_format_locale,
+                collect_resource_stats: collect_resource_stats(),
+                collect_dataset_summary: collect_dataset_summary(),
+                fetch_retries,
+                fetch_retry_backoff_ms,
+                interaction_script: interactions,
+                preserve_int64: false,
+                base_url: None,
+                allow_local_data: false,
+                lenient: lenient.unwrap_or(false),
+                font,
+                font_size_scale,
+                text_color,
+                config_overrides,
+                direction,
+                generic_font_mapping,
+                container_width,
+                container_height,
+                strict_sizing: strict_sizing.unwrap_or(false),
+                width,
+                height,
+                resize_mode,
+                min_size,
+                max_rows,
+                row_limit_strategy,
+                annotate_truncation: annotate_truncation.unwrap_or(false),
+                log_level,
+                warning_filters,
+                suppress_components,
+                browser_shims: browser_shims.unwrap_or(true),
+                simplify_topojson,
+                pre_aggregate: false,
+                lenient_dates: lenient_dates.unwrap_or(false),
+                svg_post_processor: None,
+            },
+            scale,
+            ppi,
+        ))
+    }) {
+        Ok(result) => result,
+        Err(err) => {
+            return Err(PyValueError::new_err(format!(
+                "Vega-Lite to Vega and PNG conversion failed:\n{}",
+                err
+            )))
+        }
+    };
 
-    let pdf_data = match PYTHON_RUNTIME.block_on(converter.vegalite_to_pdf(
-        vl_spec,
-        VlOpts {
-            vl_version,
-            config,
-            theme,
-            show_warnings: false,
-            allowed_base_urls,
-            format_locale,
-            time_format_locale,
-        },
-    )) {
+    Python::with_gil(|py| -> PyResult<PyObject> {
+        let vega_spec =
+            pythonize(py, &vega_spec).map_err(|err| PyValueError::new_err(err.to_string()))?;
+        let png_data = PyBytes::new_bound(py, png_data.as_slice());
+        Ok((vega_spec, png_data).into_py(py))
+    })
+}
+
+/// Convert each cell of a faceted Vega-Lite spec to its own PNG image.
+///
+/// The chart is rendered once and each image is cropped from it, so the cells share their
+/// scales and formatting, and cells of the same size have images of the same size. Only the
+/// plot area of each cell is included, unless include_guides is set.
+///
+/// Args:
+///     vl_spec (str | dict): Vega-Lite JSON specification string or dict
+///     vl_version (str): Vega-Lite library version string (e.g. 'v5.15')
+///         (default to latest)
+///     scale (float): Image scale factor (default 1.0)
+///     ppi (float): Pixels per inch (default 72)
+///     config (dict | str | None): Chart configuration object to apply during conversion,
+///         or a JSON string or the path of a JSON file holding it (e.g. "~/config.json")
+///     theme (str | None): Named theme (e.g. "dark") to apply during conversion
+///     show_warnings (bool | None): Whether to print Vega-Lite compilation warnings (default false)
+///     allowed_base_urls (list of str): List of allowed base URLs for external
+///                                      data requests. Default allows any base URL
+///     format_locale (str | dict): d3-format locale name or dictionary
+///     time_format_locale (str | dict): d3-time-format locale name or dictionary
+///     fetch_retries (int | None): Number of times to retry data and image requests that fail
+///         with a connection error or 5xx response (default 0)
+///     fetch_retry_backoff_ms (int | None): Delay before the first retry in milliseconds,
+///         doubled for each subsequent retry (default 200)
+///     interactions (list of dict | None): Interaction steps to apply before rendering. Each
+///         step is either {"signal": name, "value": value} or {"event": {"type": type, "x": x,
+///         "y": y, ...}} with x and y in view coordinates
+///     lenient (bool | None): Leave out images that fail to load instead of failing the
+///         conversion (default false)
+///     font (str | None): Font to use for all text: axis, legend, and header labels and
+///         titles, chart titles, and text marks. Config takes precedence
+///     font_size_scale (float | None): Factor to scale the default font sizes of all text by
+///     text_color (str | None): Color to use for all text
+///     config_overrides (dict | None): Config values keyed by dotted paths (e.g.
+///         "axis.labelFontSize"), which are set in order over config and theme
+///     direction (str | None): Writing direction to lay the chart out for, "ltr" or "rtl".
+///         "rtl" mirrors the layout for right-to-left locales: y axes and row headers are
+///         drawn on the right, legends on the left, and titles are anchored at the end.
+///         Orients and anchors set in the spec or config take precedence (default "ltr")
+///     generic_font_mapping (dict | None): Fonts to render generic font families with,
+///         keyed by "sans-serif", "serif", "monospace", or "cursive" (e.g.
+///         {"sans-serif": "Inter"})
+///     container_width (float | None): Width of the container that charts sized to their
+///         container (e.g. with "width": "container") are rendered in (default 600)
+///     container_height (float | None): Height of the container that charts sized to their
+///         container are rendered in (default 400)
+///     strict_sizing (bool | None): Fail the conversion of charts sized to their container,
+///         instead of rendering them with container_width and container_height (default false)
+///     width (float | None): Width to render the chart at, replacing the width of the spec
+///         before its axes and legends are laid out (default None)
+///     height (float | None): Height to render the chart at, replacing the height of the spec
+///         (default None)
+///     resize_mode (str | None): How the chart is laid out in width and height. "fit" makes
+///         them the size of the whole chart, including its axes and legends, "pad" makes them
+///         the size of the plot area, and "none" keeps the autosize of the spec (default "fit")
+///     min_size (tuple of int | None): Smallest (width, height) to render the chart at.
+///         Charts that resolve to a size below 1 pixel, like those with empty data, are
+///         enlarged to it instead of failing the conversion (default None)
+///     max_rows (int | None): Largest number of rows that a dataset of the chart may have once
+///         it's loaded, including inline data and data fetched from urls (default None)
+///     row_limit_strategy (str | None): How datasets with more than max_rows rows are
+///         handled. "error" fails the conversion, "head" keeps the first rows, and "sample"
+///         keeps a random sample of the rows, drawn with a seed of 0 or with the seed given
+///         as "sample:SEED" (default "error")
+///     annotate_truncation (bool | None): Add a subtitle that notes the truncation to charts
+///         with datasets that were truncated to max_rows rows (default false)
+///     log_level (str | None): Level of the messages that Vega logs while the chart is
+///         rendered. One of "none", "error", "warn", "info", or "debug". Warnings at that
+///         level are logged (default None, which logs nothing)
+///     warning_filters (list of str | None): Warnings that contain one of these strings, or
+///         that match it as a regular expression when it's written as "/pattern/", are left
+///         out of the render warnings and counted in a summary warning instead (default None)
+///     suppress_components (list of str | None): Components of the chart that are left out of
+///         the image, for applications that draw them themselves. Any of "title", "legend",
+///         "axis-x", "axis-y", or "grid". The chart is laid out without them (default None)
+///     browser_shims (bool | None): Define the browser globals that expressions copied from
+///         web pages read: window, navigator.language (the name of format_locale, or
+///         "en-US"), screen.width and screen.height (the size of the chart), and
+///         devicePixelRatio (the scale of PNG and JPEG images, or 1). Expressions that read
+///         other browser APIs fail with an error that names them (default true)
+///     simplify_topojson (float | None): Simplify the arcs of TopoJSON datasets as they're
+///         parsed, dropping the vertices that are within this tolerance of the simplified
+///         lines, in the units of the topology's coordinates (degrees for unprojected
+///         topologies). Borders that features share stay shared (default None)
+///     lenient_dates (bool | None): Rewrite the dates of the temporal fields of the spec's
+///         inline data that aren't in the ISO 8601 format, like "03/27/2024 5:00 PM", to ISO
+///         8601 before the chart is rendered (default false)
+///     include_guides (bool | None): Include the headers and shared axes next to each cell
+///         in its image, along with the plot area of the cell (default false)
+/// Returns:
+///     list of dict: dict per cell, in the order of the cells, with the cell's "row_value"
+///         and "column_value" (the values of the row and column fields, or None when the
+///         chart has no row or column facet) and its PNG image data under "data". The cells
+///         of wrapped facets have the value of the facet field as their "column_value"
+#[pyfunction]
+#[pyo3(
+    signature = (vl_spec, vl_version=None, scale=None, ppi=None, config=None, theme=None, show_warnings=None, allowed_base_urls=None, format_locale=None, time_format_locale=None, fetch_retries=None, fetch_retry_backoff_ms=None, interactions=None, lenient=None, font=None, font_size_scale=None, text_color=None, config_overrides=None, direction=None, generic_font_mapping=None, container_width=None, container_height=None, strict_sizing=None, width=None, height=None, resize_mode=None, min_size=None, max_rows=None, row_limit_strategy=None, annotate_truncation=None, log_level=None, warning_filters=None, suppress_components=None, browser_shims=None, simplify_topojson=None, lenient_dates=None, include_guides=None)
+)]
+fn vegalite_facets_to_pngs(
+    vl_spec: PyObject,
+    vl_version: Option<&str>,
+    scale: Option<f32>,
+    ppi: Option<f32>,
+    config: Option<PyObject>,
+    theme: Option<String>,
+    show_warnings: Option<bool>,
+    allowed_base_urls: Option<Vec<String>>,
+    format_locale: Option<PyObject>,
+    time_format_locale: Option<PyObject>,
+    fetch_retries: Option<u32>,
+    fetch_retry_backoff_ms: Option<u64>,
+    interactions: Option<PyObject>,
+    lenient: Option<bool>,
+    font: Option<String>,
+    font_size_scale: Option<f32>,
+    text_color: Option<String>,
+    config_overrides: Option<PyObject>,
+    direction: Option<String>,
+    generic_font_mapping: Option<HashMap<String, String>>,
+    container_width: Option<f32>,
+    container_height: Option<f32>,
+    strict_sizing: Option<bool>,
+    width: Option<f32>,
+    height: Option<f32>,
+    resize_mode: Option<String>,
+    min_size: Option<(u32, u32)>,
+    max_rows: Option<u64>,
+    row_limit_strategy: Option<String>,
+    annotate_truncation: Option<bool>,
+    log_level: Option<String>,
+    warning_filters: Option<Vec<String>>,
+    suppress_components: Option<Vec<String>>,
+    browser_shims: Option<bool>,
+    simplify_topojson: Option<f64>,
+    lenient_dates: Option<bool>,
+    include_guides: Option<bool>,
+) -> PyResult<PyObject> {
+    let theme = parse_option_theme(theme)?;
+    let resize_mode = parse_resize_mode(resize_mode)?;
+    let row_limit_strategy = parse_row_limit_strategy(row_limit_strategy)?;
+    let log_level = parse_log_level(log_level)?;
+    let suppress_components = parse_suppress_components(suppress_components)?;
+    let vl_version = parse_vl_version(vl_version)?;
+    let vl_spec = parse_json_spec(vl_spec)?;
+    let config = parse_option_config(config)?;
+    let config_overrides = parse_option_config_overrides(config_overrides)?;
+    let direction = parse_option_direction(direction)?;
+    let format_locale = parse_option_format_locale(format_locale)?;
+    let time_format_locale = parse_option_time_format_locale(time_format_locale)?;
+    let interactions = parse_option_interactions(interactions)?;
+
+    let images = match with_converter(|converter| {
+        PYTHON_RUNTIME.block_on(converter.vegalite_facets_to_pngs_with_opts(
+            vl_spec,
+            VlOpts {
+                vl_version,
+                config,
+                theme,
+                show_warnings: show_warnings.unwrap_or(false),
+                allowed_base_urls,
+                format_locale,
+                time_format_locale,
+                collect_resource_stats: collect_resource_stats(),
+                collect_dataset_summary: collect_dataset_summary(),
+                fetch_retries,
+                fetch_retry_backoff_ms,
+                interaction_script: interactions,
+                preserve_int64: false,
+                base_url: None,
+                allow_local_data: false,
+                lenient: lenient.unwrap_or(false),
+                font,
+                font_size_scale,
+                text_color,
+                config_overrides,
+                direction,
+                generic_font_mapping,
+                container_width,
+                container_height,
+                strict_sizing: strict_sizing.unwrap_or(false),
+                width,
+                height,
+                resize_mode,
+                min_size,
+                max_rows,
+                row_limit_strategy,
+                annotate_truncation: annotate_truncation.unwrap_or(false),
+                log_level,
+                warning_filters,
+                suppress_components,
+                browser_shims: browser_shims.unwrap_or(true),
+                simplify_topojson,
+                pre_aggregate: false,
+                lenient_dates: lenient_dates.unwrap_or(false),
+                svg_post_processor: None,
+            },
+            scale,
+            ppi,
+            FacetOpts {
+                include_guides: include_guides.unwrap_or(false),
+            },
+        ))
+    }) {
+        Ok(images) => images,
+        Err(err) => {
+            return Err(PyValueError::new_err(format!(
+                "Vega-Lite facets to PNG conversion failed:\n{}",
+                err
+            )))
+        }
+    };
+
+    Python::with_gil(|py| -> PyResult<PyObject> {
+        let list = PyList::empty_bound(py);
+        for image in &images {
+            let dict = PyDict::new_bound(py);
+            for (key, value) in [
+                ("row_value", &image.row_value),
+                ("column_value", &image.column_value),
+            ] {
+                let value =
+                    pythonize(py, value).map_err(|err| PyValueError::new_err(err.to_string()))?;
+                dict.set_item(key, value)?;
+            }
+            dict.set_item("data", PyBytes::new_bound(py, &image.data))?;
+            list.append(dict)?;
+        }
+        Ok(list.into())
+    })
+}
+
+/// Convert a Vega spec to JPEG image data.
+///
+/// Args:
+///     vg_spec (str | dict): Vega JSON specification string or dict
+///     scale (float): Image scale factor (default 1.0)
+///     quality (int): JPEG Quality between 1 (worst) and 100 (best). Default 90
+///     jpeg_background (str | None): CSS color to composite the image over, since JPEG
+///         images can't be transparent, e.g. "#f0f0f0". The chart's own background, which
+///         Vega-Lite charts have by default, covers it (default "white")
+///     allowed_base_urls (list of str): List of allowed base URLs for external
+///                                      data requests. Default allows any base URL
+///     format_locale (str | dict): d3-format locale name or dictionary
+///     time_format_locale (str | dict): d3-time-format locale name or dictionary
+///     theme (str | None): Named theme (e.g. "dark") to apply during conversion
+///     config (dict | str | None): Chart configuration object to apply during conversion,
+///         or a JSON string or the path of a JSON file holding it (e.g. "~/config.json")
+///     fetch_retries (int | None): Number of times to retry data and image requests that fail
+///         with a connection error or 5xx response (default 0)
+///     fetch_retry_backoff_ms (int | None): Delay before the first retry in milliseconds,
+///         doubled for each subsequent retry (default 200)
+///     interactions (list of dict | None): Interaction steps to apply before rendering. Each
+///         step is either {"signal": name, "value": value} or {"event": {"type": type, "x": x,
+///         "y": y, ...}} with x and y in view coordinates
+///     lenient (bool | None): Leave out images that fail to load instead of failing the
+///         conversion. Skipped images are listed in the "warnings" of the return_info dict
+///         (default false)
+///     generic_font_mapping (dict | None): Fonts to render generic font families with,
+///         keyed by "sans-serif", "serif", "monospace", or "cursive" (e.g.
+///         {"sans-serif": "Inter"})
+///     container_width (float | None): Width of the container that charts sized to their
+///         container (e.g. with "width": "container") are rendered in (default 600)
+///     container_height (float | None): Height of the container that charts sized to their
+///         container are rendered in (default 400)
+///     strict_sizing (bool | None): Fail the conversion of charts sized to their container,
+///         instead of rendering them with container_width and container_height (default false)
+///     return_info (bool | None): Return a dict with the JPEG image data under "data", along
+///         with its "width_px", "height_px", "css_width", "css_height", "scale", "quality",
+///         the "request_hash" of the spec and options, and the render "warnings" of the
+///         conversion (default false)
+///     force_iterations (int | None): Run force transforms as static simulations of this
+///         many ticks before the chart is rendered, so that their layout is reproducible.
+///         Force transforms that set "static" or "iterations" keep them (default None)
+///     random_seed (int | None): Seed for the random numbers of the render, which are used
+///         by the random() expression function and by transforms like sample (default None)
+///     width (float | None): Width to render the chart at, replacing the width of the spec
+///         before its axes and legends are laid out (default None)
+///     height (float | None): Height to render the chart at, replacing the height of the spec
+///         (default None)
+///     resize_mode (str | None): How the chart is laid out in width and height. "fit" makes
+///         them the size of the whole chart, including its axes and legends, "pad" makes them
+///         the size of the plot area, and "none" keeps the autosize of the spec (default "fit")
+///     min_size (tuple of int | None): Smallest (width, height) to render the chart at.
+///         Charts that resolve to a size below 1 pixel, like those with empty data, are
+///         enlarged to it instead of failing the conversion (default None)
+///     max_rows (int | None): Largest number of rows that a dataset of the chart may have once
+///         it's loaded, including inline data and data fetched from urls (default None)
+///     row_limit_strategy (str | None): How datasets with more than max_rows rows are
+///         handled. "error" fails the conversion, "head" keeps the first rows, and "sample"
+///         keeps a random sample of the rows, drawn with a seed of 0 or with the seed given
+///         as "sample:SEED" (default "error")
+///     annotate_truncation (bool | None): Add a subtitle that notes the truncation to charts
+///         with datasets that were truncated to max_rows rows (default false)
+///     log_level (str | None): Level of the messages that Vega logs while the chart is
+///         rendered. One of "none", "error", "warn", "info", or "debug". Warnings at that
+///         level are listed in the "warnings" of the return_info dict (default None, which logs
+///         nothing)
+///     warning_filters (list of str | None): Warnings that contain one of these strings, or
+///         that match it as a regular expression when it's written as "/pattern/", are left
+///         out of the render warnings and counted in a summary warning instead (default None)
+///     suppress_components (list of str | None): Components of the chart that are left out of
+///         the image, for applications that draw them themselves. Any of "title", "legend",
+///         "axis-x", "axis-y", or "grid". The chart is laid out without them (default None)
+///     browser_shims (bool | None): Define the browser globals that expressions copied from
+///         web pages read: window, navigator.language (the name of format_locale, or
+///         "en-US"), screen.width and screen.height (the size of the chart), and
+///         devicePixelRatio (the scale of PNG and JPEG images, or 1). Expressions that read
+///         other browser APIs fail with an error that names them (default true)
+///     simplify_topojson (float | None): Simplify the arcs of TopoJSON datasets as they're
+///         parsed, dropping the vertices that are within this tolerance of the simplified
+///         lines, in the units of the topology's coordinates (degrees for unprojected
+///         topologies). Borders that features share stay shared. The reduction in the number
+///         of vertices is listed in the "warnings" of the return_info dict (default None)
+///     svg_post_processor (Callable[[str], str] | None): Function that the generated SVG is
+///         passed through before it's returned or rasterized. It must return valid SVG, and
+///         exceptions that it raises fail the conversion (default None)
+/// Returns:
+///     bytes | dict: JPEG image data, or a dict with the image data and its size when
+///         return_info is true
+#[pyfunction]
+#[pyo3(
+    signature = (vg_spec, scale=None, quality=None, jpeg_background=None, allowed_base_urls=None, format_locale=None, time_format_locale=None, theme=None, config=None, fetch_retries=None, fetch_retry_backoff_ms=None, interactions=None, lenient=None, generic_font_mapping=None, container_width=None, container_height=None, strict_sizing=None, return_info=None, force_iterations=None, random_seed=None, width=None, height=None, resize_mode=None, min_size=None, max_rows=None, row_limit_strategy=None, annotate_truncation=None, log_level=None, warning_filters=None, suppress_components=None, browser_shims=None, simplify_topojson=None, svg_post_processor=None)
+)]
+fn vega_to_jpeg(
+    vg_spec: PyObject,
+    scale: Option<f32>,
+    quality: Option<u8>,
+    jpeg_background: Option<String>,
+    allowed_base_urls: Option<Vec<String>>,
+    format_locale: Option<PyObject>,
+    time_format_locale: Option<PyObject>,
+    theme: Option<String>,
+    config: Option<PyObject>,
+    fetch_retries: Option<u32>,
+    fetch_retry_backoff_ms: Option<u64>,
+    interactions: Option<PyObject>,
+    lenient: Option<bool>,
+    generic_font_mapping: Option<HashMap<String, String>>,
+    container_width: Option<f32>,
+    container_height: Option<f32>,
+    strict_sizing: Option<bool>,
+    return_info: Option<bool>,
+    force_iterations: Option<u32>,
+    random_seed: Option<u32>,
+    width: Option<f32>,
+    height: Option<f32>,
+    resize_mode: Option<String>,
+    min_size: Option<(u32, u32)>,
+    max_rows: Option<u64>,
+    row_limit_strategy: Option<String>,
+    annotate_truncation: Option<bool>,
+    log_level: Option<String>,
+    warning_filters: Option<Vec<String>>,
+    suppress_components: Option<Vec<String>>,
+    browser_shims: Option<bool>,
+    simplify_topojson: Option<f64>,
+    svg_post_processor: Option<PyObject>,
+) -> PyResult<PyObject> {
+    let svg_post_processor = parse_svg_post_processor(svg_post_processor)?;
+    let theme = parse_option_theme(theme)?;
+    let resize_mode = parse_resize_mode(resize_mode)?;
+    let row_limit_strategy = parse_row_limit_strategy(row_limit_strategy)?;
+    let log_level = parse_log_level(log_level)?;
+    let suppress_components = parse_suppress_components(suppress_components)?;
+    let vg_spec = parse_json_spec(vg_spec)?;
+    let config = parse_option_config(config)?;
+    let format_locale = parse_option_format_locale(format_locale)?;
+    let time_format_locale = parse_option_time_format_locale(time_format_locale)?;
+    let interactions = parse_option_interactions(interactions)?;
+    let jpeg_opts = parse_jpeg_opts(jpeg_background)?;
+
+    let jpeg = match with_converter(|converter| {
+        PYTHON_RUNTIME.block_on(converter.vega_to_jpeg_info_with_opts(
+            vg_spec,
+            VgOpts {
+                theme,
+                config,
+                allowed_base_urls,
+                format_locale,
+                time_format_locale,
+                collect_resource_stats: collect_resource_stats(),
+                collect_dataset_summary: collect_dataset_summary(),
+                fetch_retries,
+                fetch_retry_backoff_ms,
+                interaction_script: interactions,
+                preserve_int64: false,
+                base_url: None,
+                allow_local_data: false,
+                lenient: lenient.unwrap_or(false),
+                generic_font_mapping,
+                container_width,
+                container_height,
+                strict_sizing: strict_sizing.unwrap_or(false),
+                width,
+                height,
+                resize_mode,
+                min_size,
+                max_rows,
+                row_limit_strategy,
+                annotate_truncation: annotate_truncation.unwrap_or(false),
+                log_level,
+                warning_filters,
+                suppress_components,
+                browser_shims: browser_shims.unwrap_or(true),
+                simplify_topojson,
+                force_iterations,
+                random_seed,
+                svg_post_processor,
+            },
+            scale,
+            quality,
+            jpeg_opts,
+        ))
+    }) {
+        Ok(vega_spec) => vega_spec,
+        Err(err) => {
+            return Err(PyValueError::new_err(format!(
+                "Vega to JPEG conversion failed:\n{}",
+                err
+            )))
+        }
+    };
+
+    Python::with_gil(|py| jpeg_result_object(py, jpeg, return_info.unwrap_or(false)))
+}
+
+/// Convert a Vega-Lite spec to JPEG image data using a particular
+/// version of the Vega-Lite JavaScript library.
+///
+/// Args:
+///     vl_spec (str | dict): Vega-Lite JSON specification string or dict
+///     vl_version (str): Vega-Lite library version string (e.g. 'v5.15')
+///         (default to latest)
+///     scale (float): Image scale factor (default 1.0)
+///     quality (int): JPEG Quality between 1 (worst) and 100 (best). Default 90
+///     jpeg_background (str | None): CSS color to composite the image over, since JPEG
+///         images can't be transparent, e.g. "#f0f0f0". The chart's own background, which
+///         Vega-Lite charts have by default, covers it (default "white")
+///     config (dict | str | None): Chart configuration object to apply during conversion,
+///         or a JSON string or the path of a JSON file holding it (e.g. "~/config.json")
+///     theme (str | None): Named theme (e.g. "dark") to apply during conversion
+///     show_warnings (bool | None): Whether to print Vega-Lite compilation warnings (default false)
+///     allowed_base_urls (list of str): List of allowed base URLs for external
+///                                      data requests. Default allows any base URL
+///     format_locale (str | dict): d3-format locale name or dictionary
+///     time_format_locale (str | dict): d3-time-format locale name or dictionary
+///     fetch_retries (int | None): Number of times to retry data and image requests that fail
+///         with a connection error or 5xx response (default 0)
+///     fetch_retry_backoff_ms (int | None): Delay before the first retry in milliseconds,
+///         doubled for each subsequent retry (default 200)
+///     interactions (list of dict | None): Interaction steps to apply before rendering. Each
+///         step is either {"signal": name, "value": value} or {"event": {"type": type, "x": x,
+///         "y": y, ...}} with x and y in view coordinates
+///     lenient (bool | None): Leave out images that fail to load instead of failing the
+///         conversion. Skipped images are listed in the "warnings" of the return_info dict
+///         (default false)
+///     font (str | None): Font to use for all text: axis, legend, and header labels and
+///         titles, chart titles, and text marks. Config takes precedence
+///     font_size_scale (float | None): Factor to scale the default font sizes of all text by
+///     text_color (str | None): Color to use for all text
+///     config_overrides (dict | None): Config values keyed by dotted paths (e.g.
+///         "axis.labelFontSize"), which are set in order over config and theme
+///     direction (str | None): Writing direction to lay the chart out for, "ltr" or "rtl".
+///         "rtl" mirrors the layout for right-to-left locales: y axes and row headers are
+///         drawn on the right, legends on the left, and titles are anchored at the end.
+///         Orients and anchors set in the spec or config take precedence (default "ltr")
+///     generic_font_mapping (dict | None): Fonts to render generic font families with,
+///         keyed by "sans-serif", "serif", "monospace", or "cursive" (e.g.
+///         {"sans-serif": "Inter"})
+///     container_width (float | None): Width of the container that charts sized to their
+///         container (e.g. with "width": "container") are rendered in (default 600)
+///     container_height (float | None): Height of the container that charts sized to their
+///         container are rendered in (default 400)
+///     strict_sizing (bool | None): Fail the conversion of charts sized to their container,
+///         instead of rendering them with container_width and container_height (default false)
+///     return_info (bool | None): Return a dict with the JPEG image data under "data", along
+///         with its "width_px", "height_px", "css_width", "css_height", "scale", "quality",
+///         the "request_hash" of the spec and options, and the render "warnings" of the
+///         conversion (default false)
+///     width (float | None): Width to render the chart at, replacing the width of the spec
+///         before its axes and legends are laid out (default None)
+///     height (float | None): Height to render the chart at, replacing the height of the spec
+///         (default None)
+///     resize_mode (str | None): How the chart is laid out in width and height. "fit" makes
+///         them the size of the whole chart, including its axes and legends, "pad" makes them
+///         the size of the plot area, and "none" keeps the autosize of the spec (default "fit")
+///     min_size (tuple of int | None): Smallest (width, height) to render the chart at.
+///         Charts that resolve to a size below 1 pixel, like those with empty data, are
+///         enlarged to it instead of failing the conversion (default None)
+///     max_rows (int | None): Largest number of rows that a dataset of the chart may have once
+///         it's loaded, including inline data and data fetched from urls (default None)
+///     row_limit_strategy (str | None): How datasets with more than max_rows rows are
+///         handled. "error" fails the conversion, "head" keeps the first rows, and "sample"
+///         keeps a random sample of the rows, drawn with a seed of 0 or with the seed given
+///         as "sample:SEED" (default "error")
+///     annotate_truncation (bool | None): Add a subtitle that notes the truncation to charts
+///         with datasets that were truncated to max_rows rows (default false)
+///     log_level (str | None): Level of the messages that Vega logs while the chart is
+///         rendered. One of "none", "error", "warn", "info", or "debug". Warnings at that
+///         level are listed in the "warnings" of the return_info dict (default None, which logs
+///         nothing)
+///     warning_filters (list of str | None): Warnings that contain one of these strings, or
+///         that match it as a regular expression when it's written as "/pattern/", are left
+///         out of the render warnings and counted in a summary warning instead (default None)
+///     suppress_components (list of str | None): Components of the chart that are left out of
+///         the image, for applications that draw them themselves. Any of "title", "legend",
+///         "axis-x", "axis-y", or "grid". The chart is laid out without them (default None)
+///     browser_shims (bool | None): Define the browser globals that expressions copied from
+///         web pages read: window, navigator.language (the name of format_locale, or
+///         "en-US"), screen.width and screen.height (the size of the chart), and
+///         devicePixelRatio (the scale of PNG and JPEG images, or 1). Expressions that read
+///         other browser APIs fail with an error that names them (default true)
+///     simplify_topojson (float | None): Simplify the arcs of TopoJSON datasets as they're
+///         parsed, dropping the vertices that are within this tolerance of the simplified
+///         lines, in the units of the topology's coordinates (degrees for unprojected
+///         topologies). Borders that features share stay shared. The reduction in the number
+///         of vertices is listed in the "warnings" of the return_info dict (default None)
+///     lenient_dates (bool | None): Rewrite the dates of the temporal fields of the spec's
+///         inline data that aren't in the ISO 8601 format, like "03/27/2024 5:00 PM", to ISO
+///         8601 before the chart is rendered. The rewritten fields are listed in
+///         the "warnings" of the return_info dict (default false)
+///     svg_post_processor (Callable[[str], str] | None): Function that the generated SVG is
+///         passed through before it's returned or rasterized. It must return valid SVG, and
+///         exceptions that it raises fail the conversion (default None)
+/// Returns:
+///     bytes | dict: JPEG image data, or a dict with the image data and its size when
+///         return_info is true
+#[pyfunction]
+#[pyo3(
+    signature = (vl_spec, vl_version=None, scale=None, quality=None, jpeg_background=None, config=None, theme=None, show_warnings=None, allowed_base_urls=None, format_locale=None, time_format_locale=None, fetch_retries=None, fetch_retry_backoff_ms=None, interactions=None, lenient=None, font=None, font_size_scale=None, text_color=None, config_overrides=None, direction=None, generic_font_mapping=None, container_width=None, container_height=None, strict_sizing=None, return_info=None, width=None, height=None, resize_mode=None, min_size=None, max_rows=None, row_limit_strategy=None, annotate_truncation=None, log_level=None, warning_filters=None, suppress_components=None, browser_shims=None, simplify_topojson=None, lenient_dates=None, svg_post_processor=None)
+)]
+fn vegalite_to_jpeg(
+    vl_spec: PyObject,
+    vl_version: Option<&str>,
+    scale: Option<f32>,
+    quality: Option<u8>,
+    jpeg_background: Option<String>,
+    config: Option<PyObject>,
+    theme: Option<String>,
+    show_warnings: Option<bool>,
+    allowed_base_urls: Option<Vec<String>>,
+    format_locale: Option<PyObject>,
+    time_format_locale: Option<PyObject>,
+    fetch_retries: Option<u32>,
+    fetch_retry_backoff_ms: Option<u64>,
+    interactions: Option<PyObject>,
+    lenient: Option<bool>,
+    font: Option<String>,
+    font_size_scale: Option<f32>,
+    text_color: Option<String>,
+    config_overrides: Option<PyObject>,
+    direction: Option<String>,
+    generic_font_mapping: Option<HashMap<String, String>>,
+    container_width: Option<f32>,
+    container_height: Option<f32>,
+    strict_sizing: Option<bool>,
+    return_info: Option<bool>,
+    width: Option<f32>,
+    height: Option<f32>,
+    resize_mode: Option<String>,
+    min_size: Option<(u32, u32)>,
+    max_rows: Option<u64>,
+    row_limit_strategy: Option<String>,
+    annotate_truncation: Option<bool>,
+    log_level: Option<String>,
+    warning_filters: Option<Vec<String>>,
+    suppress_components: Option<Vec<String>>,
+    browser_shims: Option<bool>,
+    simplify_topojson: Option<f64>,
+    lenient_dates: Option<bool>,
+    svg_post_processor: Option<PyObject>,
+) -> PyResult<PyObject> {
+    let svg_post_processor = parse_svg_post_processor(svg_post_processor)?;
+    let theme = parse_option_theme(theme)?;
+    let resize_mode = parse_resize_mode(resize_mode)?;
+    let row_limit_strategy = parse_row_limit_strategy(row_limit_strategy)?;
+    let log_level = parse_log_level(log_level)?;
+    let suppress_components = parse_suppress_components(suppress_components)?;
+    let vl_version = parse_vl_version(vl_version)?;
+    let vl_spec = parse_json_spec(vl_spec)?;
+    let config = parse_option_config(config)?;
+    let config_overrides = parse_option_config_overrides(config_overrides)?;
+    let direction = parse_option_direction(direction)?;
+    let format_locale = parse_option_format_locale(format_locale)?;
+    let time_format_locale = parse_option_time_format_locale(time_format_locale)?;
+    let interactions = parse_option_interactions(interactions)?;
+    let jpeg_opts = parse_jpeg_opts(jpeg_background)?;
+
+    let jpeg = match with_converter(|converter| {
+        PYTHON_RUNTIME.block_on(converter.vegalite_to_jpeg_info_with_opts(
+            vl_spec,
+            VlOpts {
+                vl_version,
+                config,
+                theme,
+                show_warnings: show_warnings.unwrap_or(false),
+                allowed_base_urls,
+                format_locale,
+                time_format_locale,
+                collect_resource_stats: collect_resource_stats(),
+                collect_dataset_summary: collect_dataset_summary(),
+                fetch_retries,
+                fetch_retry_backoff_ms,
+                interaction_script: interactions,
+                preserve_int64: false,
+                base_url: None,
+                allow_local_data: false,
+                lenient: lenient.unwrap_or(false),
+                font,
+                font_size_scale,
+                text_color,
+                config_overrides,
+                direction,
+                generic_font_mapping,
+                container_width,
+                container_height,
+                strict_sizing: strict_sizing.unwrap_or(false),
+                width,
+                height,
+                resize_mode,
+                min_size,
+                max_rows,
+                row_limit_strategy,
+                annotate_truncation: annotate_truncation.unwrap_or(false),
+                log_level,
+                warning_filters,
+                suppress_components,
+                browser_shims: browser_shims.unwrap_or(true),
+                simplify_topojson,
+                pre_aggregate: false,
+                lenient_dates: lenient_dates.unwrap_or(false),
+                svg_post_processor,
+            },
+            scale,
+            quality,
+            jpeg_opts,
+        ))
+    }) {
+        Ok(vega_spec) => vega_spec,
+        Err(err) => {
+            return Err(PyValueError::new_err(format!(
+                "Vega-Lite to JPEG conversion failed:\n{}",
+                err
+            )))
+        }
+    };
+
+    Python::with_gil(|py| jpeg_result_object(py, jpeg, return_info.unwrap_or(false)))
+}
+
+/// Convert a Vega spec to PDF format
+///
+/// Args:
+///     vg_spec (str | dict): Vega JSON specification string or dict
+///     scale (float): Image scale factor (default 1.0)
+///     allowed_base_urls (list of str): List of allowed base URLs for external
+///                                      data requests. Default allows any base URL
+///     format_locale (str | dict): d3-format locale name or dictionary
+///     time_format_locale (str | dict): d3-time-format locale name or dictionary
+///     theme (str | None): Named theme (e.g. "dark") to apply during conversion
+///     config (dict | str | None): Chart configuration object to apply during conversion,
+///         or a JSON string or the path of a JSON file holding it (e.g. "~/config.json")
+///     fetch_retries (int | None): Number of times to retry data and image requests that fail
+///         with a connection error or 5xx response (default 0)
+///     fetch_retry_backoff_ms (int | None): Delay before the first retry in milliseconds,
+///         doubled for each subsequent retry (default 200)
+///     interactions (list of dict | None): Interaction steps to apply before rendering. Each
+///         step is either {"signal": name, "value": value} or {"event": {"type": type, "x": x,
+///         "y": y, ...}} with x and y in view coordinates
+///     lenient (bool | None): Leave out images that fail to load instead of failing the
+///         conversion (default false)
+///     generic_font_mapping (dict | None): Fonts to render generic font families with,
+///         keyed by "sans-serif", "serif", "monospace", or "cursive" (e.g.
+///         {"sans-serif": "Inter"})
+///     container_width (float | None): Width of the container that charts sized to their
+///         container (e.g. with "width": "container") are rendered in (default 600)
+///     container_height (float | None): Height of the container that charts sized to their
+///         container are rendered in (default 400)
+///     strict_sizing (bool | None): Fail the conversion of charts sized to their container,
+///         instead of rendering them with container_width and container_height (default false)
+///     pdfa (bool | None): Write a PDF/A-2b document for archiving, with XMP metadata, an
+///         sRGB output intent, and a document ID (default false)
+///     force_iterations (int | None): Run force transforms as static simulations of this
+///         many ticks before the chart is rendered, so that their layout is reproducible.
+///         Force transforms that set "static" or "iterations" keep them (default None)
+///     random_seed (int | None): Seed for the random numbers of the render, which are used
+///         by the random() expression function and by transforms like sample (default None)
+///     source_dpi (float | None): Pixels per inch of the image, which sets the size of the
+///         PDF page in points. The default of 96 matches CSS pixels, so that a chart 960
+///         pixels wide is written to a page 720 points wide (default 96)
+///     width (float | None): Width to render the chart at, replacing the width of the spec
+///         before its axes and legends are laid out (default None)
+///     height (float | None): Height to render the chart at, replacing the height of the spec
+///         (default None)
+///     resize_mode (str | None): How the chart is laid out in width and height. "fit" makes
+///         them the size of the whole chart, including its axes and legends, "pad" makes them
+///         the size of the plot area, and "none" keeps the autosize of the spec (default "fit")
+///     min_size (tuple of int | None): Smallest (width, height) to render the chart at.
+///         Charts that resolve to a size below 1 pixel, like those with empty data, are
+///         enlarged to it instead of failing the conversion (default None)
+///     max_rows (int | None): Largest number of rows that a dataset of the chart may have once
+///         it's loaded, including inline data and data fetched from urls (default None)
+///     row_limit_strategy (str | None): How datasets with more than max_rows rows are
+///         handled. "error" fails the conversion, "head" keeps the first rows, and "sample"
+///         keeps a random sample of the rows, drawn with a seed of 0 or with the seed given
+///         as "sample:SEED" (default "error")
+///     annotate_truncation (bool | None): Add a subtitle that notes the truncation to charts
+///         with datasets that were truncated to max_rows rows (default false)
+///     log_level (str | None): Level of the messages that Vega logs while the chart is
+///         rendered. One of "none", "error", "warn", "info", or "debug". Warnings at that
+///         level are logged (default None, which logs nothing)
+///     warning_filters (list of str | None): Warnings that contain one of these strings, or
+///         that match it as a regular expression when it's written as "/pattern/", are left
+///         out of the render warnings and counted in a summary warning instead (default None)
+///     suppress_components (list of str | None): Components of the chart that are left out of
+///         the image, for applications that draw them themselves. Any of "title", "legend",
+///         "axis-x", "axis-y", or "grid". The chart is laid out without them (default None)
+///     browser_shims (bool | None): Define the browser globals that expressions copied from
+///         web pages read: window, navigator.language (the name of format_locale, or
+///         "en-US"), screen.width and screen.height (the size of the chart), and
+///         devicePixelRatio (the scale of PNG and JPEG images, or 1). Expressions that read
+///         other browser APIs fail with an error that names them (default true)
+///     simplify_topojson (float | None): Simplify the arcs of TopoJSON datasets as they're
+///         parsed, dropping the vertices that are within this tolerance of the simplified
+///         lines, in the units of the topology's coordinates (degrees for unprojected
+///         topologies). Borders that features share stay shared (default None)
+///     svg_post_processor (Callable[[str], str] | None): Function that the generated SVG is
+///         passed through before it's returned or rasterized. It must return valid SVG, and
+///         exceptions that it raises fail the conversion (default None)
+/// Returns:
+///     bytes: PDF file bytes
+#[pyfunction]
+#[pyo3(signature = (vg_spec, scale=None, allowed_base_urls=None, format_locale=None, time_format_locale=None, theme=None, config=None, fetch_retries=None, fetch_retry_backoff_ms=None, interactions=None, lenient=None, generic_font_mapping=None, container_width=None, container_height=None, strict_sizing=None, pdfa=None, force_iterations=None, random_seed=None, source_dpi=None, width=None, height=None, resize_mode=None, min_size=None, max_rows=None, row_limit_strategy=None, annotate_truncation=None, log_level=None, warning_filters=None, suppress_components=None, browser_shims=None, simplify_topojson=None, svg_post_processor=None))]
+fn vega_to_pdf(
+    vg_spec: PyObject,
+    scale: Option<f32>,
+    allowed_base_urls: Option<Vec<String>>,
+    format_locale: Option<PyObject>,
+    time_format_locale: Option<PyObject>,
+    theme: Option<String>,
+    config: Option<PyObject>,
+    fetch_retries: Option<u32>,
+    fetch_retry_backoff_ms: Option<u64>,
+    interactions: Option<PyObject>,
+    lenient: Option<bool>,
+    generic_font_mapping: Option<HashMap<String, String>>,
+    container_width: Option<f32>,
+    container_height: Option<f32>,
+    strict_sizing: Option<bool>,
+    pdfa: Option<bool>,
+    force_iterations: Option<u32>,
+    random_seed: Option<u32>,
+    source_dpi: Option<f32>,
+    width: Option<f32>,
+    height: Option<f32>,
+    resize_mode: Option<String>,
+    min_size: Option<(u32, u32)>,
+    max_rows: Option<u64>,
+    row_limit_strategy: Option<String>,
+    annotate_truncation: Option<bool>,
+    log_level: Option<String>,
+    warning_filters: Option<Vec<String>>,
+    suppress_components: Option<Vec<String>>,
+    browser_shims: Option<bool>,
+    simplify_topojson: Option<f64>,
+    svg_post_processor: Option<PyObject>,
+) -> PyResult<PyObject> {
+    let svg_post_processor = parse_svg_post_processor(svg_post_processor)?;
+    let theme = parse_option_theme(theme)?;
+    let resize_mode = parse_resize_mode(resize_mode)?;
+    let row_limit_strategy = parse_row_limit_strategy(row_limit_strategy)?;
+    let log_level = parse_log_level(log_level)?;
+    let suppress_components = parse_suppress_components(suppress_components)?;
+    warn_if_scale_not_one_for_pdf(scale)?;
+    let vg_spec = parse_json_spec(vg_spec)?;
+    let config = parse_option_config(config)?;
+    let format_locale = parse_option_format_locale(format_locale)?;
+    let time_format_locale = parse_option_time_format_locale(time_format_locale)?;
+    let interactions = parse_option_interactions(interactions)?;
+
+    let pdf_bytes = match with_converter(|converter| {
+        PYTHON_RUNTIME.block_on(converter.vega_to_pdf_with_opts(
+            vg_spec,
+            VgOpts {
+                theme,
+                config,
+                allowed_base_urls,
+                format_locale,
+                time_format_locale,
+                collect_resource_stats: collect_resource_stats(),
+                collect_dataset_summary: collect_dataset_summary(),
+                fetch_retries,
+                fetch_retry_backoff_ms,
+                interaction_script: interactions,
+                preserve_int64: false,
+                base_url: None,
+                allow_local_data: false,
+                lenient: lenient.unwrap_or(false),
+                generic_font_mapping,
+                container_width,
+                container_height,
+                strict_sizing: strict_sizing.unwrap_or(false),
+                width,
+                height,
+                resize_mode,
+                min_size,
+                max_rows,
+                row_limit_strategy,
+                annotate_truncation: annotate_truncation.unwrap_or(false),
+                log_level,
+                warning_filters,
+                suppress_components,
+                browser_shims: browser_shims.unwrap_or(true),
+                simplify_topojson,
+                force_iterations,
+                random_seed,
+                svg_post_processor,
+            },
+            PdfOpts {
+                pdfa: pdfa.unwrap_or(false),
+                source_dpi: source_dpi.unwrap_or(96.0),
+            },
+        ))
+    }) {
+        Ok(vega_spec) => vega_spec,
+        Err(err) => {
+            return Err(PyValueError::new_err(format!(
+                "Vega to PDF conversion failed:\n{}",
+                err
+            )))
+        }
+    };
+    Ok(Python::with_gil(|py| -> PyObject {
+        PyObject::from(PyBytes::new_bound(py, pdf_bytes.as_slice()))
+    }))
+}
+
+/// Convert a Vega-Lite spec to PDF image data using a particular
+/// version of the Vega-Lite JavaScript library.
+///
+/// Args:
+///     vl_spec (str | dict): Vega-Lite JSON specification string or dict
+///     vl_version (str): Vega-Lite library version string (e.g. 'v5.15')
+///         (default to latest)
+///     scale (float): Image scale factor (default 1.0)
+///     config (dict | str | None): Chart configuration object to apply during conversion,
+///         or a JSON string or the path of a JSON file holding it (e.g. "~/config.json")
+///     theme (str | None): Named theme (e.g. "dark") to apply during conversion
+///     show_warnings (bool | None): Whether to print Vega-Lite compilation warnings (default false)
+///     allowed_base_urls (list of str): List of allowed base URLs for external
+///                                      data requests. Default allows any base URL
+///     format_locale (str | dict): d3-format locale name or dictionary
+///     time_format_locale (str | dict): d3-time-format locale name or dictionary
+///     fetch_retries (int | None): Number of times to retry data and image requests that fail
+///         with a connection error or 5xx response (default 0)
+///     fetch_retry_backoff_ms (int | None): Delay before the first retry in milliseconds,
+///         doubled for each subsequent retry (default 200)
+///     interactions (list of dict | None): Interaction steps to apply before rendering. Each
+///         step is either {"signal": name, "value": value} or {"event": {"type": type, "x": x,
+///         "y": y, ...}} with x and y in view coordinates
+///     lenient (bool | None): Leave out images that fail to load instead of failing the
+///         conversion (default false)
+///     font (str | None): Font to use for all text: axis, legend, and header labels and
+///         titles, chart titles, and text marks. Config takes precedence
+///     font_size_scale (float | None): Factor to scale the default font sizes of all text by
+///     text_color (str | None): Color to use for all text
+///     config_overrides (dict | None): Config values keyed by dotted paths (e.g.
+///         "axis.labelFontSize"), which are set in order over config and theme
+///     direction (str | None): Writing direction to lay the chart out for, "ltr" or "rtl".
+///         "rtl" mirrors the layout for right-to-left locales: y axes and row headers are
+///         drawn on the right, legends on the left, and titles are anchored at the end.
+///         Orients and anchors set in the spec or config take precedence (default "ltr")
+///     generic_font_mapping (dict | None): Fonts to render generic font families with,
+///         keyed by "sans-serif", "serif", "monospace", or "cursive" (e.g.
+///         {"sans-serif": "Inter"})
+///     container_width (float | None): Width of the container that charts sized to their
+///         container (e.g. with "width": "container") are rendered in (default 600)
+///     container_height (float | None): Height of the container that charts sized to their
+///         container are rendered in (default 400)
+///     strict_sizing (bool | None): Fail the conversion of charts sized to their container,
+///         instead of rendering them with container_width and container_height (default false)
+///     pdfa (bool | None): Write a PDF/A-2b document for archiving, with XMP metadata, an
+///         sRGB output intent, and a document ID (default false)
+///     source_dpi (float | None): Pixels per inch of the image, which sets the size of the
+///         PDF page in points. The default of 96 matches CSS pixels, so that a chart 960
+///         pixels wide is written to a page 720 points wide (default 96)
+///     width (float | None): Width to render the chart at, replacing the width of the spec
+///         before its axes and legends are laid out (default None)
+///     height (float | None): Height to render the chart at, replacing the height of the spec
+///         (default None)
+///     resize_mode (str | None): How the chart is laid out in width and height. "fit" makes
+///         them the size of the whole chart, including its axes and legends, "pad" makes them
+///         the size of the plot area, and "none" keeps the autosize of the spec (default "fit")
+///     min_size (tuple of int | None): Smallest (width, height) to render the chart at.
+///         Charts that resolve to a size below 1 pixel, like those with empty data, are
+///         enlarged to it instead of failing the conversion (default None)
+///     max_rows (int | None): Largest number of rows that a dataset of the chart may have once
+///         it's loaded, including inline data and data fetched from urls (default None)
+///     row_limit_strategy (str | None): How datasets with more than max_rows rows are
+///         handled. "error" fails the conversion, "head" keeps the first rows, and "sample"
+///         keeps a random sample of the rows, drawn with a seed of 0 or with the seed given
+///         as "sample:SEED" (default "error")
+///     annotate_truncation (bool | None): Add a subtitle that notes the truncation to charts
+///         with datasets that were truncated to max_rows rows (default false)
+///     log_level (str | None): Level of the messages that Vega logs while the chart is
+///         rendered. One of "none", "error", "warn", "info", or "debug". Warnings at that
+///         level are logged (default None, which logs nothing)
+///     warning_filters (list of str | None): Warnings that contain one of these strings, or
+///         that match it as a regular expression when it's written as "/pattern/", are left
+///         out of the render warnings and counted in a summary warning instead (default None)
+///     suppress_components (list of str | None): Components of the chart that are left out of
+///         the image, for applications that draw them themselves. Any of "title", "legend",
+///         "axis-x", "axis-y", or "grid". The chart is laid out without them (default None)
+///     browser_shims (bool | None): Define the browser globals that expressions copied from
+///         web pages read: window, navigator.language (the name of format_locale, or
+///         "en-US"), screen.width and screen.height (the size of the chart), and
+///         devicePixelRatio (the scale of PNG and JPEG images, or 1). Expressions that read
+///         other browser APIs fail with an error that names them (default true)
+///     simplify_topojson (float | None): Simplify the arcs of TopoJSON datasets as they're
+///         parsed, dropping the vertices that are within this tolerance of the simplified
+///         lines, in the units of the topology's coordinates (degrees for unprojected
+///         topologies). Borders that features share stay shared (default None)
+///     lenient_dates (bool | None): Rewrite the dates of the temporal fields of the spec's
+///         inline data that aren't in the ISO 8601 format, like "03/27/2024 5:00 PM", to ISO
+///         8601 before the chart is rendered (default false)
+///     svg_post_processor (Callable[[str], str] | None): Function that the generated SVG is
+///         passed through before it's returned or rasterized. It must return valid SVG, and
+///         exceptions that it raises fail the conversion (default None)
+/// Returns:
+///     bytes: PDF image data
+#[pyfunction]
+#[pyo3(
+    signature = (vl_spec, vl_version=None, scale=None, config=None, theme=None, show_warnings=None, allowed_base_urls=None, format_locale=None, time_format_locale=None, fetch_retries=None, fetch_retry_backoff_ms=None, interactions=None, lenient=None, font=None, font_size_scale=None, text_color=None, config_overrides=None, direction=None, generic_font_mapping=None, container_width=None, container_height=None, strict_sizing=None, pdfa=None, source_dpi=None, width=None, height=None, resize_mode=None, min_size=None, max_rows=None, row_limit_strategy=None, annotate_truncation=None, log_level=None, warning_filters=None, suppress_components=None, browser_shims=None, simplify_topojson=None, lenient_dates=None, svg_post_processor=None)
+)]
+fn vegalite_to_pdf(
+    vl_spec: PyObject,
+    vl_version: Option<&str>,
+    scale: Option<f32>,
+    config: Option<PyObject>,
+    theme: Option<String>,
+    show_warnings: Option<bool>,
+    allowed_base_urls: Option<Vec<String>>,
+    format_locale: Option<PyObject>,
+    time_format_locale: Option<PyObject>,
+    fetch_retries: Option<u32>,
+    fetch_retry_backoff_ms: Option<u64>,
+    interactions: Option<PyObject>,
+    lenient: Option<bool>,
+    font: Option<String>,
+    font_size_scale: Option<f32>,
+    text_color: Option<String>,
+    config_overrides: Option<PyObject>,
+    direction: Option<String>,
+    generic_font_mapping: Option<HashMap<String, String>>,
+    container_width: Option<f32>,
+    container_height: Option<f32>,
+    strict_sizing: Option<bool>,
+    pdfa: Option<bool>,
+    source_dpi: Option<f32>,
+    width: Option<f32>,
+    height: Option<f32>,
+    resize_mode: Option<String>,
+    min_size: Option<(u32, u32)>,
+    max_rows: Option<u64>,
+    row_limit_strategy: Option<String>,
+    annotate_truncation: Option<bool>,
+    log_level: Option<String>,
+    warning_filters: Option<Vec<String>>,
+    suppress_components: Option<Vec<String>>,
+    browser_shims: Option<bool>,
+    simplify_topojson: Option<f64>,
+    lenient_dates: Option<bool>,
+    svg_post_processor: Option<PyObject>,
+) -> PyResult<PyObject> {
+    let svg_post_processor = parse_svg_post_processor(svg_post_processor)?;
+    let theme = parse_option_theme(theme)?;
+    let resize_mode = parse_resize_mode(resize_mode)?;
+    let row_limit_strategy = parse_row_limit_strategy(row_limit_strategy)?;
+    let log_level = parse_log_level(log_level)?;
+    let suppress_components = parse_suppress_components(suppress_components)?;
+    warn_if_scale_not_one_for_pdf(scale)?;
+    let vl_version = parse_vl_version(vl_version)?;
+    let vl_spec = parse_json_spec(vl_spec)?;
+    let config = parse_option_config(config)?;
+    let config_overrides = parse_option_config_overrides(config_overrides)?;
+    let direction = parse_option_direction(direction)?;
+    let format_locale = parse_option_format_locale(format_locale)?;
+    let time_format_locale = parse_option_time_format_locale(time_format_locale)?;
+    let interactions = parse_option_interactions(interactions)?;
+
+    let pdf_data = match with_converter(|converter| {
+        PYTHON_RUNTIME.block_on(converter.vegalite_to_pdf_with_opts(
+            vl_spec,
+            VlOpts {
+                vl_version,
+                config,
+                theme,
+                show_warnings: show_warnings.unwrap_or(false),
+                allowed_base_urls,
+                format_locale,
+                time_format_locale,
+                collect_resource_stats: collect_resource_stats(),
+                collect_dataset_summary: collect_dataset_summary(),
+                fetch_retries,
+                fetch_retry_backoff_ms,
+                interaction_script: interactions,
+                preserve_int64: false,
+                base_url: None,
+                allow_local_data: false,
+                lenient: lenient.unwrap_or(false),
+                font,
+                font_size_scale,
+                text_color,
+                config_overrides,
+                direction,
+                generic_font_mapping,
+                container_width,
+                container_height,
+                strict_sizing: strict_sizing.unwrap_or(false),
+                width,
+                height,
+                resize_mode,
+                min_size,
+                max_rows,
+                row_limit_strategy,
+                annotate_truncation: annotate_truncation.unwrap_or(false),
+                log_level,
+                warning_filters,
+                suppress_components,
+                browser_shims: browser_shims.unwrap_or(true),
+                simplify_topojson,
+                pre_aggregate: false,
+                lenient_dates: lenient_dates.unwrap_or(false),
+                svg_post_processor,
+            },
+            PdfOpts {
+                pdfa: pdfa.unwrap_or(false),
+                source_dpi: source_dpi.unwrap_or(96.0),
+            },
+        ))
+    }) {
         Ok(vega_spec) => vega_spec,
         Err(err) => {
             return Err(PyValueError::new_err(format!(
@@ -708,21 +3520,640 @@ fn vegalite_to_pdf(
     }))
 }
 
+/// Convert a Vega-Lite spec to several image formats at once. The chart is compiled and
+/// rendered to SVG a single time, and the requested PNG, JPEG, and PDF outputs are derived
+/// from that SVG.
+///
+/// Args:
+///     vl_spec (str | dict): Vega-Lite JSON specification string or dict
+///     formats (list of str): Formats to produce. Any of "svg", "png", "jpeg", and "pdf"
+///     vl_version (str): Vega-Lite library version string (e.g. 'v5.15')
+///         (default to latest)
+///     scale (float): Image scale factor for PNG and JPEG output (default 1.0)
+///     ppi (float): Pixels per inch for PNG output (default 72)
+///     quality (int): JPEG Quality between 1 (worst) and 100 (best). Default 90
+///     jpeg_background (str | None): CSS color to composite the JPEG image over, since JPEG
+///         images can't be transparent, e.g. "#f0f0f0". The chart's own background, which
+///         Vega-Lite charts have by default, covers it (default "white")
+///     flatten_background (str | None): CSS color to composite the PNG image over so that it
+///         has no transparent pixels, e.g. "white" or "#f0f0f0". By default, transparent
+///         parts of the chart are left transparent (default None)
+///     config (dict | str | None): Chart configuration object to apply during conversion,
+///         or a JSON string or the path of a JSON file holding it (e.g. "~/config.json")
+///     theme (str | None): Named theme (e.g. "dark") to apply during conversion
+///     show_warnings (bool | None): Whether to print Vega-Lite compilation warnings (default false)
+///     allowed_base_urls (list of str): List of allowed base URLs for external
+///                                      data requests. Default allows any base URL
+///     format_locale (str | dict): d3-format locale name or dictionary
+///     time_format_locale (str | dict): d3-time-format locale name or dictionary
+///     fetch_retries (int | None): Number of times to retry data and image requests that fail
+///         with a connection error or 5xx response (default 0)
+///     fetch_retry_backoff_ms (int | None): Delay before the first retry in milliseconds,
+///         doubled for each subsequent retry (default 200)
+///     interactions (list of dict | None): Interaction steps to apply before rendering. Each
+///         step is either {"signal": name, "value": value} or {"event": {"type": type, "x": x,
+///         "y": y, ...}} with x and y in view coordinates
+///     lenient (bool | None): Leave out images that fail to load instead of failing the
+///         conversion. Skipped images are listed in the "warnings" of the result
+///         (default false)
+///     font (str | None): Font to use for all text: axis, legend, and header labels and
+///         titles, chart titles, and text marks. Config takes precedence
+///     font_size_scale (float | None): Factor to scale the default font sizes of all text by
+///     text_color (str | None): Color to use for all text
+///     config_overrides (dict | None): Config values keyed by dotted paths (e.g.
+///         "axis.labelFontSize"), which are set in order over config and theme
+///     direction (str | None): Writing direction to lay the chart out for, "ltr" or "rtl".
+///         "rtl" mirrors the layout for right-to-left locales: y axes and row headers are
+///         drawn on the right, legends on the left, and titles are anchored at the end.
+///         Orients and anchors set in the spec or config take precedence (default "ltr")
+///     generic_font_mapping (dict | None): Fonts to render generic font families with,
+///         keyed by "sans-serif", "serif", "monospace", or "cursive" (e.g.
+///         {"sans-serif": "Inter"})
+///     container_width (float | None): Width of the container that charts sized to their
+///         container (e.g. with "width": "container") are rendered in (default 600)
+///     container_height (float | None): Height of the container that charts sized to their
+///         container are rendered in (default 400)
+///     strict_sizing (bool | None): Fail the conversion of charts sized to their container,
+///         instead of rendering them with container_width and container_height (default false)
+///     width (float | None): Width to render the chart at, replacing the width of the spec
+///         before its axes and legends are laid out (default None)
+///     height (float | None): Height to render the chart at, replacing the height of the spec
+///         (default None)
+///     resize_mode (str | None): How the chart is laid out in width and height. "fit" makes
+///         them the size of the whole chart, including its axes and legends, "pad" makes them
+///         the size of the plot area, and "none" keeps the autosize of the spec (default "fit")
+///     min_size (tuple of int | None): Smallest (width, height) to render the chart at.
+///         Charts that resolve to a size below 1 pixel, like those with empty data, are
+///         enlarged to it instead of failing the conversion (default None)
+///     max_rows (int | None): Largest number of rows that a dataset of the chart may have once
+///         it's loaded, including inline data and data fetched from urls (default None)
+///     row_limit_strategy (str | None): How datasets with more than max_rows rows are
+///         handled. "error" fails the conversion, "head" keeps the first rows, and "sample"
+///         keeps a random sample of the rows, drawn with a seed of 0 or with the seed given
+///         as "sample:SEED" (default "error")
+///     annotate_truncation (bool | None): Add a subtitle that notes the truncation to charts
+///         with datasets that were truncated to max_rows rows (default false)
+///     log_level (str | None): Level of the messages that Vega logs while the chart is
+///         rendered. One of "none", "error", "warn", "info", or "debug". Warnings at that
+///         level are listed in the "warnings" of the result (default None, which logs
+///         nothing)
+///     warning_filters (list of str | None): Warnings that contain one of these strings, or
+///         that match it as a regular expression when it's written as "/pattern/", are left
+///         out of the render warnings and counted in a summary warning instead (default None)
+///     suppress_components (list of str | None): Components of the chart that are left out of
+///         the image, for applications that draw them themselves. Any of "title", "legend",
+///         "axis-x", "axis-y", or "grid". The chart is laid out without them (default None)
+///     browser_shims (bool | None): Define the browser globals that expressions copied from
+///         web pages read: window, navigator.language (the name of format_locale, or
+///         "en-US"), screen.width and screen.height (the size of the chart), and
+///         devicePixelRatio (the scale of PNG and JPEG images, or 1). Expressions that read
+///         other browser APIs fail with an error that names them (default true)
+///     simplify_topojson (float | None): Simplify the arcs of TopoJSON datasets as they're
+///         parsed, dropping the vertices that are within this tolerance of the simplified
+///         lines, in the units of the topology's coordinates (degrees for unprojected
+///         topologies). Borders that features share stay shared. The reduction in the number
+///         of vertices is listed in the "warnings" of the result (default None)
+///     lenient_dates (bool | None): Rewrite the dates of the temporal fields of the spec's
+///         inline data that aren't in the ISO 8601 format, like "03/27/2024 5:00 PM", to ISO
+///         8601 before the chart is rendered. The rewritten fields are listed in
+///         the "warnings" of the result (default false)
+/// Returns:
+///     dict: Image data bytes keyed by format, along with the render "warnings" of the
+///         conversion
+#[pyfunction]
+#[pyo3(
+    signature = (vl_spec, formats, vl_version=None, scale=None, ppi=None, quality=None, jpeg_background=None, flatten_background=None, config=None, theme=None, show_warnings=None, allowed_base_urls=None, format_locale=None, time_format_locale=None, fetch_retries=None, fetch_retry_backoff_ms=None, interactions=None, lenient=None, font=None, font_size_scale=None, text_color=None, config_overrides=None, direction=None, generic_font_mapping=None, container_width=None, container_height=None, strict_sizing=None, width=None, height=None, resize_mode=None, min_size=None, max_rows=None, row_limit_strategy=None, annotate_truncation=None, log_level=None, warning_filters=None, suppress_components=None, browser_shims=None, simplify_topojson=None, lenient_dates=None)
+)]
+fn vegalite_export(
+    vl_spec: PyObject,
+    formats: Vec<String>,
+    vl_version: Option<&str>,
+    scale: Option<f32>,
+    ppi: Option<f32>,
+    quality: Option<u8>,
+    jpeg_background: Option<String>,
+    flatten_background: Option<String>,
+    config: Option<PyObject>,
+    theme: Option<String>,
+    show_warnings: Option<bool>,
+    allowed_base_urls: Option<Vec<String>>,
+    format_locale: Option<PyObject>,
+    time_format_locale: Option<PyObject>,
+    fetch_retries: Option<u32>,
+    fetch_retry_backoff_ms: Option<u64>,
+    interactions: Option<PyObject>,
+    lenient: Option<bool>,
+    font: Option<String>,
+    font_size_scale: Option<f32>,
+    text_color: Option<String>,
+    config_overrides: Option<PyObject>,
+    direction: Option<String>,
+    generic_font_mapping: Option<HashMap<String, String>>,
+    container_width: Option<f32>,
+    container_height: Option<f32>,
+    strict_sizing: Option<bool>,
+    width: Option<f32>,
+    height: Option<f32>,
+    resize_mode: Option<String>,
+    min_size: Option<(u32, u32)>,
+    max_rows: Option<u64>,
+    row_limit_strategy: Option<String>,
+    annotate_truncation: Option<bool>,
+    log_level: Option<String>,
+    warning_filters: Option<Vec<String>>,
+    suppress_components: Option<Vec<String>>,
+    browser_shims: Option<bool>,
+    simplify_topojson: Option<f64>,
+    lenient_dates: Option<bool>,
+) -> PyResult<PyObject> {
+    let theme = parse_option_theme(theme)?;
+    let resize_mode = parse_resize_mode(resize_mode)?;
+    let row_limit_strategy = parse_row_limit_strategy(row_limit_strategy)?;
+    let log_level = parse_log_level(log_level)?;
+    let suppress_components = parse_suppress_components(suppress_components)?;
+    let vl_version = parse_vl_version(vl_version)?;
+    let formats = formats
+        .iter()
+        .map(|format| ExportFormat::from_str(format))
+        .collect::<Result<Vec<_>, _>>()?;
+    let vl_spec = parse_json_spec(vl_spec)?;
+    let config = parse_option_config(config)?;
+    let config_overrides = parse_option_config_overrides(config_overrides)?;
+    let direction = parse_option_direction(direction)?;
+    let format_locale = parse_option_format_locale(format_locale)?;
+    let time_format_locale = parse_option_time_format_locale(time_format_locale)?;
+    let interactions = parse_option_interactions(interactions)?;
+
+    let mut request = ExportRequest::from_formats(&formats);
+    if let Some(png) = &mut request.png {
+        png.scale = scale;
+        png.ppi = ppi;
+        png.png_opts.flatten_background = parse_flatten_background(flatten_background)?;
+    }
+    if let Some(jpeg) = &mut request.jpeg {
+        jpeg.scale = scale;
+        jpeg.quality = quality;
+        jpeg.jpeg_opts = parse_jpeg_opts(jpeg_background)?;
+    }
+
+    let result = match with_converter(|converter| {
+        PYTHON_RUNTIME.block_on(converter.vegalite_export(
+            vl_spec,
+            VlOpts {
+                vl_version,
+                config,
+                theme,
+                show_warnings: show_warnings.unwrap_or(false),
+                allowed_base_urls,
+                format_locale,
+                time_format_locale,
+                collect_resource_stats: collect_resource_stats(),
+                collect_dataset_summary: collect_dataset_summary(),
+                fetch_retries,
+                fetch_retry_backoff_ms,
+                interaction_script: interactions,
+                preserve_int64: false,
+                base_url: None,
+                allow_local_data: false,
+                lenient: lenient.unwrap_or(false),
+                font,
+                font_size_scale,
+                text_color,
+                config_overrides,
+                direction,
+                generic_font_mapping,
+                container_width,
+                container_height,
+                strict_sizing: strict_sizing.unwrap_or(false),
+                width,
+                height,
+                resize_mode,
+                min_size,
+                max_rows,
+                row_limit_strategy,
+                annotate_truncation: annotate_truncation.unwrap_or(false),
+                log_level,
+                warning_filters,
+                suppress_components,
+                browser_shims: browser_shims.unwrap_or(true),
+                simplify_topojson,
+                pre_aggregate: false,
+                lenient_dates: lenient_dates.unwrap_or(false),
+                svg_post_processor: None,
+            },
+            request,
+        ))
+    }) {
+        Ok(result) => result,
+        Err(err) => {
+            return Err(PyValueError::new_err(format!(
+                "Vega-Lite export failed:\n{}",
+                err
+            )))
+        }
+    };
+
+    Python::with_gil(|py| -> PyResult<PyObject> {
+        let dict = PyDict::new_bound(py);
+        if let Some(svg) = &result.svg {
+            dict.set_item("svg", PyBytes::new_bound(py, svg.as_bytes()))?;
+        }
+        for (format, data) in [
+            (ExportFormat::Png, &result.png),
+            (ExportFormat::Jpeg, &result.jpeg),
+            (ExportFormat::Pdf, &result.pdf),
+        ] {
+            if let Some(data) = data {
+                dict.set_item(format.extension(), PyBytes::new_bound(py, data))?;
+            }
+        }
+        dict.set_item("warnings", render_warnings_object(py, &result.warnings)?)?;
+        Ok(dict.into())
+    })
+}
+
+/// Options of convert_to_files jobs, which may also be passed to convert_to_files as keyword
+/// arguments to apply them to every job
+const FILE_JOB_OPTIONS: &[&str] = &[
+    "format",
+    "vl_version",
+    "scale",
+    "ppi",
+    "quality",
+    "jpeg_background",
+    "flatten_background",
+    "config",
+    "theme",
+    "show_warnings",
+    "allowed_base_urls",
+    "format_locale",
+    "time_format_locale",
+    "fetch_retries",
+    "fetch_retry_backoff_ms",
+    "interactions",
+    "lenient",
+    "font",
+    "font_size_scale",
+    "text_color",
+    "config_overrides",
+    "direction",
+    "generic_font_mapping",
+    "container_width",
+    "container_height",
+    "strict_sizing",
+    "width",
+    "height",
+    "resize_mode",
+    "min_size",
+    "max_rows",
+    "row_limit_strategy",
+    "annotate_truncation",
+    "log_level",
+    "warning_filters",
+    "suppress_components",
+    "browser_shims",
+    "simplify_topojson",
+    "lenient_dates",
+    "pdfa",
+    "source_dpi",
+];
+
+/// Convert a batch of Vega-Lite specs to image files. Each image is written to its file as
+/// soon as it's converted, so that a large batch never holds all of its images in memory
+///
+/// Args:
+///     jobs (list of dict): Jobs to convert, in order. Each job has a spec key, holding a
+///         Vega-Lite JSON specification string or dict, and an output key, holding the path
+///         of the file to write. Jobs may also set any of the options below, which take
+///         precedence over the keyword arguments
+///     format (str): Image format of the files. One of "svg", "png", "jpeg", or "pdf"
+///         (default "png")
+///     **kwargs: Options that apply to every job: vl_version, scale, ppi, quality,
+///         jpeg_background, flatten_background, config, theme, show_warnings,
+///         allowed_base_urls, format_locale, time_format_locale, fetch_retries,
+///         fetch_retry_backoff_ms, interactions, lenient, font, font_size_scale,
+///         text_color, config_overrides, direction, generic_font_mapping, container_width,
+///         container_height, strict_sizing, width, height, resize_mode, min_size, max_rows,
+///         row_limit_strategy, annotate_truncation, log_level, warning_filters,
+///         suppress_components, browser_shims, simplify_topojson, lenient_dates, pdfa, and
+///         source_dpi, as documented for vegalite_export and vegalite_to_pdf
+/// Returns:
+///     list of dict: dict per job, in the order of the jobs, with the job's output and an ok
+///         key. Jobs that failed also have an error key with the error message. Jobs that
+///         were parsed have a request_hash key, with a hash of the job's spec and options
+///         that's stable for a given version of vl-convert. When a job's if_none_match_hash
+///         key equals its request hash, the job isn't converted and its unchanged key is True
+#[pyfunction]
+#[pyo3(signature = (jobs, format=None, **kwargs))]
+fn convert_to_files<'py>(
+    py: Python<'py>,
+    jobs: Vec<Bound<'py, PyDict>>,
+    format: Option<&str>,
+    kwargs: Option<&Bound<'py, PyDict>>,
+) -> PyResult<PyObject> {
+    let format = ExportFormat::from_str(format.unwrap_or("png"))?;
+    if let Some(kwargs) = kwargs {
+        for key in kwargs.keys() {
+            let key = key.extract::<String>()?;
+            if !FILE_JOB_OPTIONS.contains(&key.as_str()) {
+                return Err(PyValueError::new_err(format!(
+                    "Unsupported convert_to_files option: {key}"
+                )));
+            }
+        }
+    }
+
+    let results = PyList::empty_bound(py);
+    for job in jobs {
+        let output = job.get_item("output")?;
+        let mut request_hash = None;
+        let mut unchanged = false;
+        let result = parse_file_job(py, &job, kwargs, format).and_then(|job| {
+            request_hash = Some(job.request_hash.clone());
+            if job.if_none_match_hash.as_ref() == Some(&job.request_hash) {
+                unchanged = true;
+                return Ok(());
+            }
+            with_converter(|converter| {
+                PYTHON_RUNTIME.block_on(converter.vegalite_to_file(
+                    job.vl_spec,
+                    job.vl_opts,
+                    job.format,
+                    job.file_opts,
+                    &job.path,
+                ))
+            })
+            .map_err(|err| PyValueError::new_err(err.to_string()))
+        });
+
+        let dict = PyDict::new_bound(py);
+        dict.set_item("output", output)?;
+        dict.set_item("ok", result.is_ok())?;
+        dict.set_item("unchanged", unchanged)?;
+        if let Some(request_hash) = request_hash {
+            dict.set_item("request_hash", request_hash)?;
+        }
+        if let Err(err) = result {
+            dict.set_item("error", err.value_bound(py).to_string())?;
+        }
+        results.append(dict)?;
+    }
+    Ok(results.into())
+}
+
+/// A convert_to_files job, parsed from its dict
+struct FileJob {
+    path: PathBuf,
+    vl_spec: serde_json::Value,
+    vl_opts: VlOpts,
+    format: ExportFormat,
+    file_opts: ImageFileOpts,
+    request_hash: String,
+    if_none_match_hash: Option<String>,
+}
+
+/// Parse a convert_to_files job, with the shared options as defaults
+fn parse_file_job(
+    py: Python,
+    job: &Bound<PyDict>,
+    shared: Option<&Bound<PyDict>>,
+    format: ExportFormat,
+) -> PyResult<FileJob> {
+    let options = PyDict::new_bound(py);
+    if let Some(shared) = shared {
+        options.update(shared.as_mapping())?;
+    }
+    for (key, value) in job.iter() {
+        let key = key.extract::<String>()?;
+        if !FILE_JOB_OPTIONS.contains(&key.as_str())
+            && !["spec", "output", "if_none_match_hash"].contains(&key.as_str())
+        {
+            return Err(PyValueError::new_err(format!(
+                "Unsupported convert_to_files option: {key}"
+            )));
+        }
+        options.set_item(key, value)?;
+    }
+
+    let Some(path) = job_option::<PathBuf>(&options, "output")? else {
+        return Err(PyValueError::new_err("Job has no output path"));
+    };
+    let Some(vl_spec) = job_option::<PyObject>(&options, "spec")? else {
+        return Err(PyValueError::new_err("Job has no spec"));
+    };
+    let vl_spec = parse_json_spec(vl_spec)?;
+    let format = match job_option::<String>(&options, "format")? {
+        Some(format) => ExportFormat::from_str(&format)?,
+        None => format,
+    };
+    let vl_version = parse_vl_version(job_option::<String>(&options, "vl_version")?.as_deref())?;
+    let config = parse_option_config(job_option(&options, "config")?)?;
+    let request_hash = file_job_request_hash(&options, &vl_spec, format, config.as_ref())?;
+
+    let vl_opts = VlOpts {
+        vl_version,
+        config,
+        theme: parse_option_theme(job_option(&options, "theme")?)?,
+        show_warnings: job_option(&options, "show_warnings")?.unwrap_or(false),
+        allowed_base_urls: job_option(&options, "allowed_base_urls")?,
+        format_locale: parse_option_format_locale(job_option(&options, "format_locale")?)?,
+        time_format_locale: parse_option_time_format_locale(job_option(
+            &options,
+            "time_format_locale",
+        )?)?,
+        collect_resource_stats: collect_resource_stats(),
+        collect_dataset_summary: collect_dataset_summary(),
+        fetch_retries: job_option(&options, "fetch_retries")?,
+        fetch_retry_backoff_ms: job_option(&options, "fetch_retry_backoff_ms")?,
+        interaction_script: parse_option_interactions(job_option(&options, "interactions")?)?,
+        preserve_int64: false,
+        base_url: None,
+        allow_local_data: false,
+        lenient: job_option(&options, "lenient")?.unwrap_or(false),
+        font: job_option(&options, "font")?,
+        font_size_scale: job_option(&options, "font_size_scale")?,
+        text_color: job_option(&options, "text_color")?,
+        config_overrides: parse_option_config_overrides(job_option(&options, "config_overrides")?)?,
+        direction: parse_option_direction(job_option(&options, "direction")?)?,
+        generic_font_mapping: job_option(&options, "generic_font_mapping")?,
+        container_width: job_option(&options, "container_width")?,
+        container_height: job_option(&options, "container_height")?,
+        strict_sizing: job_option(&options, "strict_sizing")?.unwrap_or(false),
+        width: job_option(&options, "width")?,
+        height: job_option(&options, "height")?,
+        resize_mode: parse_resize_mode(job_option(&options, "resize_mode")?)?,
+        min_size: job_option(&options, "min_size")?,
+        max_rows: job_option(&options, "max_rows")?,
+        row_limit_strategy: parse_row_limit_strategy(job_option(&options, "row_limit_strategy")?)?,
+        annotate_truncation: job_option(&options, "annotate_truncation")?.unwrap_or(false),
+        log_level: parse_log_level(job_option(&options, "log_level")?)?,
+        warning_filters: job_option(&options, "warning_filters")?,
+        suppress_components: parse_suppress_components(job_option(
+            &options,
+            "suppress_components",
+        )?)?,
+        browser_shims: job_option(&options, "browser_shims")?.unwrap_or(true),
+        simplify_topojson: job_option(&options, "simplify_topojson")?,
+        pre_aggregate: false,
+        lenient_dates: job_option(&options, "lenient_dates")?.unwrap_or(false),
+        svg_post_processor: None,
+    };
+    let file_opts = ImageFileOpts {
+        scale: job_option(&options, "scale")?,
+        ppi: job_option(&options, "ppi")?,
+        quality: job_option(&options, "quality")?,
+        png_opts: PngOpts {
+            flatten_background: parse_flatten_background(job_option(
+                &options,
+                "flatten_background",
+            )?)?,
+            ..Default::default()
+        },
+        jpeg_opts: parse_jpeg_opts(job_option(&options, "jpeg_background")?)?,
+        pdf_opts: PdfOpts {
+            pdfa: job_option(&options, "pdfa")?.unwrap_or(false),
+            source_dpi: job_option(&options, "source_dpi")?.unwrap_or(96.0),
+        },
+    };
+    Ok(FileJob {
+        path,
+        vl_spec,
+        vl_opts,
+        format,
+        file_opts,
+        request_hash,
+        if_none_match_hash: job_option(&options, "if_none_match_hash")?,
+    })
+}
+
+/// Request hash of a convert_to_files job, from its spec and the options that are set, with
+/// the effective format and the parsed config. The output path doesn't change the hash
+fn file_job_request_hash(
+    options: &Bound<PyDict>,
+    vl_spec: &serde_json::Value,
+    format: ExportFormat,
+    config: Option<&serde_json::Value>,
+) -> PyResult<String> {
+    let mut hashed_options = serde_json::Map::new();
+    for (key, value) in options.iter() {
+        let key = key.extract::<String>()?;
+        if value.is_none() || ["spec", "output", "if_none_match_hash"].contains(&key.as_str()) {
+            continue;
+        }
+        let value = depythonize::<serde_json::Value>(&value)
+            .map_err(|err| PyValueError::new_err(format!("Failed to hash option {key}: {err}")))?;
+        hashed_options.insert(key, value);
+    }
+    hashed_options.insert("format".to_string(), format.to_string().into());
+    if let Some(config) = config {
+        hashed_options.insert("config".to_string(), config.clone());
+    }
+    Ok(request_hash(vl_spec, &hashed_options.into()))
+}
+
+/// Get an option of a convert_to_files job. Options set to None are treated as unset
+fn job_option<'py, T: FromPyObject<'py>>(
+    options: &Bound<'py, PyDict>,
+    key: &str,
+) -> PyResult<Option<T>> {
+    match options.get_item(key)? {
+        Some(value) if !value.is_none() => value
+            .extract()
+            .map(Some)
+            .map_err(|err| PyValueError::new_err(format!("Invalid {key} option: {err}"))),
+        _ => Ok(None),
+    }
+}
+
+/// Build the options of the URL shortening service from the shorten_* arguments of the URL
+/// conversion functions
+fn shorten_options(
+    endpoint: String,
+    auth_header: Option<String>,
+    response_field: Option<String>,
+    timeout: Option<f64>,
+    fallback: Option<bool>,
+    fullscreen: bool,
+) -> PyResult<ShortenOptions> {
+    let timeout = match timeout {
+        Some(timeout) => Duration::try_from_secs_f64(timeout).map_err(|_| {
+            PyValueError::new_err(format!(
+                "Invalid shorten_timeout: {timeout}. Expected a non-negative number of seconds"
+            ))
+        })?,
+        None => DEFAULT_SHORTEN_TIMEOUT,
+    };
+    Ok(ShortenOptions {
+        endpoint,
+        auth_header,
+        fullscreen,
+        response_field: response_field
+            .unwrap_or_else(|| DEFAULT_SHORTEN_RESPONSE_FIELD.to_string()),
+        timeout,
+        fallback: fallback.unwrap_or(false),
+    })
+}
+
 /// Convert a Vega-Lite spec to a URL that opens the chart in the Vega editor
 ///
 /// Args:
 ///     vl_spec (str | dict): Vega-Lite JSON specification string or dict
 ///     fullscreen (bool): Whether to open the chart in full screen in the editor
+///     shorten_endpoint (str | None): URL shortening service to POST the editor URL to, as
+///         {"url": "<editor URL>"}, returning the short URL from its JSON response instead.
+///         URLs aren't sent anywhere unless this is given
+///     shorten_auth_header (str | None): Value of the Authorization header of the request to
+///         the URL shortening service
+///     shorten_response_field (str | None): Field of the URL shortening service's JSON
+///         response that holds the short URL (default "short_url")
+///     shorten_timeout (float | None): Timeout of the request to the URL shortening service,
+///         in seconds (default 10)
+///     shorten_fallback (bool | None): Return the unshortened URL, and log a warning, if the
+///         URL can't be shortened (default false)
 /// Returns:
 ///     str: URL string
 #[pyfunction]
-#[pyo3(signature = (vl_spec, fullscreen=None))]
-fn vegalite_to_url(vl_spec: PyObject, fullscreen: Option<bool>) -> PyResult<String> {
+#[pyo3(signature = (
+    vl_spec,
+    fullscreen=None,
+    shorten_endpoint=None,
+    shorten_auth_header=None,
+    shorten_response_field=None,
+    shorten_timeout=None,
+    shorten_fallback=None
+))]
+fn vegalite_to_url(
+    vl_spec: PyObject,
+    fullscreen: Option<bool>,
+    shorten_endpoint: Option<String>,
+    shorten_auth_header: Option<String>,
+    shorten_response_field: Option<String>,
+    shorten_timeout: Option<f64>,
+    shorten_fallback: Option<bool>,
+) -> PyResult<String> {
     let vl_spec = parse_json_spec(vl_spec)?;
-    Ok(vl_convert_rs::converter::vegalite_to_url(
-        &vl_spec,
-        fullscreen.unwrap_or(false),
-    )?)
+    let fullscreen = fullscreen.unwrap_or(false);
+    let Some(endpoint) = shorten_endpoint else {
+        return Ok(vl_convert_rs::converter::vegalite_to_url(
+            &vl_spec, fullscreen,
+        )?);
+    };
+    let opts = shorten_options(
+        endpoint,
+        shorten_auth_header,
+        shorten_response_field,
+        shorten_timeout,
+        shorten_fallback,
+        fullscreen,
+    )?;
+    Python::with_gil(|py| {
+        py.allow_threads(|| {
+            PYTHON_RUNTIME.block_on(vl_convert_rs::converter::vegalite_to_short_url(
+                &vl_spec, &opts,
+            ))
+        })
+    })
+    .map_err(|err| PyValueError::new_err(format!("Failed to shorten URL:\n{}", err)))
 }
 
 /// Convert a Vega spec to a URL that opens the chart in the Vega editor
@@ -730,16 +4161,57 @@ fn vegalite_to_url(vl_spec: PyObject, fullscreen: Option<bool>) -> PyResult<Stri
 /// Args:
 ///     vg_spec (str | dict): Vega JSON specification string or dict
 ///     fullscreen (bool): Whether to open the chart in full screen in the editor
+///     shorten_endpoint (str | None): URL shortening service to POST the editor URL to, as
+///         {"url": "<editor URL>"}, returning the short URL from its JSON response instead.
+///         URLs aren't sent anywhere unless this is given
+///     shorten_auth_header (str | None): Value of the Authorization header of the request to
+///         the URL shortening service
+///     shorten_response_field (str | None): Field of the URL shortening service's JSON
+///         response that holds the short URL (default "short_url")
+///     shorten_timeout (float | None): Timeout of the request to the URL shortening service,
+///         in seconds (default 10)
+///     shorten_fallback (bool | None): Return the unshortened URL, and log a warning, if the
+///         URL can't be shortened (default false)
 /// Returns:
 ///     str: URL string
 #[pyfunction]
-#[pyo3(signature = (vg_spec, fullscreen=None))]
-fn vega_to_url(vg_spec: PyObject, fullscreen: Option<bool>) -> PyResult<String> {
+#[pyo3(signature = (
+    vg_spec,
+    fullscreen=None,
+    shorten_endpoint=None,
+    shorten_auth_header=None,
+    shorten_response_field=None,
+    shorten_timeout=None,
+    shorten_fallback=None
+))]
+fn vega_to_url(
+    vg_spec: PyObject,
+    fullscreen: Option<bool>,
+    shorten_endpoint: Option<String>,
+    shorten_auth_header: Option<String>,
+    shorten_response_field: Option<String>,
+    shorten_timeout: Option<f64>,
+    shorten_fallback: Option<bool>,
+) -> PyResult<String> {
     let vg_spec = parse_json_spec(vg_spec)?;
-    Ok(vl_convert_rs::converter::vega_to_url(
-        &vg_spec,
-        fullscreen.unwrap_or(false),
-    )?)
+    let fullscreen = fullscreen.unwrap_or(false);
+    let Some(endpoint) = shorten_endpoint else {
+        return Ok(vl_convert_rs::converter::vega_to_url(&vg_spec, fullscreen)?);
+    };
+    let opts = shorten_options(
+        endpoint,
+        shorten_auth_header,
+        shorten_response_field,
+        shorten_timeout,
+        shorten_fallback,
+        fullscreen,
+    )?;
+    Python::with_gil(|py| {
+        py.allow_threads(|| {
+            PYTHON_RUNTIME.block_on(vl_convert_rs::converter::vega_to_short_url(&vg_spec, &opts))
+        })
+    })
+    .map_err(|err| PyValueError::new_err(format!("Failed to shorten URL:\n{}", err)))
 }
 
 /// Convert a Vega-Lite spec to self-contained HTML document using a particular
@@ -751,17 +4223,43 @@ fn vega_to_url(vg_spec: PyObject, fullscreen: Option<bool>) -> PyResult<String>
 ///         (default to latest)
 ///     bundle (bool): If True, bundle all dependencies in HTML file
 ///         If False (default), HTML file will load dependencies from only CDN
-///     config (dict | None): Chart configuration object to apply during conversion
+///     config (dict | str | None): Chart configuration object to apply during conversion,
+///         or a JSON string or the path of a JSON file holding it (e.g. "~/config.json")
 ///     theme (str | None): Named theme (e.g. "dark") to apply during conversion
 ///     format_locale (str | dict): d3-format locale name or dictionary
 ///     time_format_locale (str | dict): d3-time-format locale name or dictionary
 ///     renderer (str): Vega renderer. One of 'svg' (default), 'canvas',
 ///         or 'hybrid' (where text is svg and other marks are canvas)
+///     font (str | None): Font to use for all text: axis, legend, and header labels and
+///         titles, chart titles, and text marks. Config takes precedence
+///     font_size_scale (float | None): Factor to scale the default font sizes of all text by
+///     text_color (str | None): Color to use for all text
+///     config_overrides (dict | None): Config values keyed by dotted paths (e.g.
+///         "axis.labelFontSize"), which are set in order over config and theme
+///     direction (str | None): Writing direction to lay the chart out for, "ltr" or "rtl".
+///         "rtl" mirrors the layout for right-to-left locales: y axes and row headers are
+///         drawn on the right, legends on the left, and titles are anchored at the end.
+///         Orients and anchors set in the spec or config take precedence (default "ltr")
+///     show_warnings (bool | None): Whether to print Vega-Lite compilation warnings
+///         (default false)
+///     static_fallback (bool | None): If True, embed a PNG rendering of the chart that is
+///         shown until the chart is rendered and in viewers that don't run scripts
+///     static_fallback_scale (float | None): Scale of the fallback image (default 2)
+///     allowed_base_urls (list of str | None): Base URLs that the spec may load data and
+///         images from. Specs with other URLs, including relative ones, fail to export, and
+///         the document gets a Content-Security-Policy that limits the page to the origins of
+///         the base URLs. Default allows any URL
+///     warn_only (bool | None): Log a warning for URLs that allowed_base_urls doesn't allow,
+///         instead of failing the export. The Content-Security-Policy is still added
+///         (default false)
+///     template (str | None): HTML template to write the document with instead of the default,
+///         with {{ scripts }}, {{ element_id }}, {{ spec }}, and {{ embed_options }}
+///         placeholders, and optionally {{ title }}, {{ fallback }}, and {{ noscript }}
 /// Returns:
 ///     string: HTML document
 #[pyfunction]
 #[pyo3(
-    signature = (vl_spec, vl_version=None, bundle=None, config=None, theme=None, format_locale=None, time_format_locale=None, renderer=None)
+    signature = (vl_spec, vl_version=None, bundle=None, config=None, theme=None, format_locale=None, time_format_locale=None, renderer=None, font=None, font_size_scale=None, text_color=None, config_overrides=None, direction=None, show_warnings=None, static_fallback=None, static_fallback_scale=None, allowed_base_urls=None, warn_only=None, template=None)
 )]
 fn vegalite_to_html(
     vl_spec: PyObject,
@@ -772,35 +4270,194 @@ fn vegalite_to_html(
     format_locale: Option<PyObject>,
     time_format_locale: Option<PyObject>,
     renderer: Option<String>,
+    font: Option<String>,
+    font_size_scale: Option<f32>,
+    text_color: Option<String>,
+    config_overrides: Option<PyObject>,
+    direction: Option<String>,
+    show_warnings: Option<bool>,
+    static_fallback: Option<bool>,
+    static_fallback_scale: Option<f32>,
+    allowed_base_urls: Option<Vec<String>>,
+    warn_only: Option<bool>,
+    template: Option<String>,
 ) -> PyResult<String> {
-    let vl_version = if let Some(vl_version) = vl_version {
-        VlVersion::from_str(vl_version)?
-    } else {
-        Default::default()
-    };
+    let theme = parse_option_theme(theme)?;
+    let vl_version = parse_vl_version(vl_version)?;
     let vl_spec = parse_json_spec(vl_spec)?;
-    let config = config.and_then(|c| parse_json_spec(c).ok());
+    let config = parse_option_config(config)?;
+    let config_overrides = parse_option_config_overrides(config_overrides)?;
+    let direction = parse_option_direction(direction)?;
     let format_locale = parse_option_format_locale(format_locale)?;
     let time_format_locale = parse_option_time_format_locale(time_format_locale)?;
-    let renderer = renderer.unwrap_or_else(|| "svg".to_string());
-    let mut converter = VL_CONVERTER
-        .lock()
-        .expect("Failed to acquire lock on Vega-Lite converter");
+    let renderer = parse_renderer(renderer)?;
+    Ok(with_converter(|converter| {
+        PYTHON_RUNTIME.block_on(converter.vegalite_to_html_with_opts(
+            vl_spec,
+            VlOpts {
+                vl_version,
+                config,
+                theme,
+                show_warnings: show_warnings.unwrap_or(false),
+                allowed_base_urls,
+                format_locale,
+                time_format_locale,
+                collect_resource_stats: collect_resource_stats(),
+                collect_dataset_summary: collect_dataset_summary(),
+                fetch_retries: None,
+                fetch_retry_backoff_ms: None,
+                interaction_script: None,
+                preserve_int64: false,
+                base_url: None,
+                allow_local_data: false,
+                lenient: false,
+                font,
+                font_size_scale,
+                text_color,
+                config_overrides,
+                direction,
+                generic_font_mapping: None,
+                container_width: None,
+                container_height: None,
+                strict_sizing: false,
+                width: None,
+                height: None,
+                resize_mode: ResizeMode::default(),
+                min_size: None,
+                max_rows: None,
+                row_limit_strategy: RowLimitStrategy::default(),
+                annotate_truncation: false,
+                log_level: None,
+                warning_filters: None,
+                suppress_components: None,
+                browser_shims: true,
+                simplify_topojson: None,
+                pre_aggregate: false,
+                lenient_dates: false,
+                svg_post_processor: None,
+            },
+            bundle.unwrap_or(false),
+            renderer,
+            HtmlOpts {
+                static_fallback: static_fallback.unwrap_or(false),
+                static_fallback_scale,
+                warn_only: warn_only.unwrap_or(false),
+                template,
+            },
+        ))
+    })?)
+}
 
-    Ok(PYTHON_RUNTIME.block_on(converter.vegalite_to_html(
-        vl_spec,
-        VlOpts {
-            vl_version,
-            config,
-            theme,
-            show_warnings: false,
-            allowed_base_urls: None,
-            format_locale,
-            time_format_locale,
-        },
-        bundle.unwrap_or(false),
-        Renderer::from_str(&renderer)?,
-    ))?)
+/// Convert several Vega-Lite specs to a single HTML report, with one section per chart,
+/// using a particular version of the Vega-Lite JavaScript library.
+///
+/// Args:
+///     specs (list of tuple): List of (title, vl_spec) tuples, where title (str | None) is
+///         the heading of the chart's section and vl_spec (str | dict) is a Vega-Lite JSON
+///         specification string or dict
+///     vl_version (str): Vega-Lite library version string (e.g. 'v5.15')
+///         (default to latest)
+///     bundle (bool): If True, bundle all dependencies in HTML file
+///         If False (default), HTML file will load dependencies from only CDN
+///     config (dict | str | None): Chart configuration object to apply during conversion,
+///         or a JSON string or the path of a JSON file holding it (e.g. "~/config.json")
+///     theme (str | None): Named theme (e.g. "dark") to apply during conversion
+///     format_locale (str | dict): d3-format locale name or dictionary
+///     time_format_locale (str | dict): d3-time-format locale name or dictionary
+///     renderer (str): Vega renderer. One of 'svg' (default), 'canvas',
+///         or 'hybrid' (where text is svg and other marks are canvas)
+///     font (str | None): Font to use for all text: axis, legend, and header labels and
+///         titles, chart titles, and text marks. Config takes precedence
+///     font_size_scale (float | None): Factor to scale the default font sizes of all text by
+///     text_color (str | None): Color to use for all text
+///     config_overrides (dict | None): Config values keyed by dotted paths (e.g.
+///         "axis.labelFontSize"), which are set in order over config and theme
+///     direction (str | None): Writing direction to lay the chart out for, "ltr" or "rtl".
+///         "rtl" mirrors the layout for right-to-left locales: y axes and row headers are
+///         drawn on the right, legends on the left, and titles are anchored at the end.
+///         Orients and anchors set in the spec or config take precedence (default "ltr")
+/// Returns:
+///     string: HTML document
+#[pyfunction]
+#[pyo3(
+    signature = (specs, vl_version=None, bundle=None, config=None, theme=None, format_locale=None, time_format_locale=None, renderer=None, font=None, font_size_scale=None, text_color=None, config_overrides=None, direction=None)
+)]
+fn vegalite_to_html_report(
+    specs: Vec<(Option<String>, PyObject)>,
+    vl_version: Option<&str>,
+    bundle: Option<bool>,
+    config: Option<PyObject>,
+    theme: Option<String>,
+    format_locale: Option<PyObject>,
+    time_format_locale: Option<PyObject>,
+    renderer: Option<String>,
+    font: Option<String>,
+    font_size_scale: Option<f32>,
+    text_color: Option<String>,
+    config_overrides: Option<PyObject>,
+    direction: Option<String>,
+) -> PyResult<String> {
+    let theme = parse_option_theme(theme)?;
+    let vl_version = parse_vl_version(vl_version)?;
+    let specs = specs
+        .into_iter()
+        .map(|(title, vl_spec)| Ok((title, parse_json_spec(vl_spec)?)))
+        .collect::<PyResult<Vec<_>>>()?;
+    let config = parse_option_config(config)?;
+    let config_overrides = parse_option_config_overrides(config_overrides)?;
+    let direction = parse_option_direction(direction)?;
+    let format_locale = parse_option_format_locale(format_locale)?;
+    let time_format_locale = parse_option_time_format_locale(time_format_locale)?;
+    let renderer = parse_renderer(renderer)?;
+    Ok(with_converter(|converter| {
+        PYTHON_RUNTIME.block_on(converter.vegalite_to_html_report(
+            specs,
+            VlOpts {
+                vl_version,
+                config,
+                theme,
+                show_warnings: false,
+                allowed_base_urls: None,
+                format_locale,
+                time_format_locale,
+                collect_resource_stats: collect_resource_stats(),
+                collect_dataset_summary: collect_dataset_summary(),
+                fetch_retries: None,
+                fetch_retry_backoff_ms: None,
+                interaction_script: None,
+                preserve_int64: false,
+                base_url: None,
+                allow_local_data: false,
+                lenient: false,
+                font,
+                font_size_scale,
+                text_color,
+                config_overrides,
+                direction,
+                generic_font_mapping: None,
+                container_width: None,
+                container_height: None,
+                strict_sizing: false,
+                width: None,
+                height: None,
+                resize_mode: ResizeMode::default(),
+                min_size: None,
+                max_rows: None,
+                row_limit_strategy: RowLimitStrategy::default(),
+                annotate_truncation: false,
+                log_level: None,
+                warning_filters: None,
+                suppress_components: None,
+                browser_shims: true,
+                simplify_topojson: None,
+                pre_aggregate: false,
+                lenient_dates: false,
+                svg_post_processor: None,
+            },
+            bundle.unwrap_or(false),
+            renderer,
+        ))
+    })?)
 }
 
 /// Convert a Vega spec to a self-contained HTML document
@@ -813,123 +4470,760 @@ fn vegalite_to_html(
 ///     time_format_locale (str | dict): d3-time-format locale name or dictionary
 ///     renderer (str): Vega renderer. One of 'svg' (default), 'canvas',
 ///         or 'hybrid' (where text is svg and other marks are canvas)
+///     theme (str | None): Named theme (e.g. "dark") to apply during conversion
+///     config (dict | str | None): Chart configuration object to apply during conversion,
+///         or a JSON string or the path of a JSON file holding it (e.g. "~/config.json")
+///     static_fallback (bool | None): If True, embed a PNG rendering of the chart that is
+///         shown until the chart is rendered and in viewers that don't run scripts
+///     static_fallback_scale (float | None): Scale of the fallback image (default 2)
+///     allowed_base_urls (list of str | None): Base URLs that the spec may load data and
+///         images from. Specs with other URLs, including relative ones, fail to export, and
+///         the document gets a Content-Security-Policy that limits the page to the origins of
+///         the base URLs. Default allows any URL
+///     warn_only (bool | None): Log a warning for URLs that allowed_base_urls doesn't allow,
+///         instead of failing the export. The Content-Security-Policy is still added
+///         (default false)
+///     template (str | None): HTML template to write the document with instead of the default,
+///         with {{ scripts }}, {{ element_id }}, {{ spec }}, and {{ embed_options }}
+///         placeholders, and optionally {{ title }}, {{ fallback }}, and {{ noscript }}
 /// Returns:
 ///     string: HTML document
 #[pyfunction]
-#[pyo3(signature = (vg_spec, bundle=None, format_locale=None, time_format_locale=None, renderer=None))]
+#[pyo3(signature = (vg_spec, bundle=None, format_locale=None, time_format_locale=None, renderer=None, theme=None, config=None, static_fallback=None, static_fallback_scale=None, allowed_base_urls=None, warn_only=None, template=None))]
 fn vega_to_html(
     vg_spec: PyObject,
     bundle: Option<bool>,
     format_locale: Option<PyObject>,
     time_format_locale: Option<PyObject>,
     renderer: Option<String>,
+    theme: Option<String>,
+    config: Option<PyObject>,
+    static_fallback: Option<bool>,
+    static_fallback_scale: Option<f32>,
+    allowed_base_urls: Option<Vec<String>>,
+    warn_only: Option<bool>,
+    template: Option<String>,
 ) -> PyResult<String> {
+    let theme = parse_option_theme(theme)?;
     let vg_spec = parse_json_spec(vg_spec)?;
+    let config = parse_option_config(config)?;
     let format_locale = parse_option_format_locale(format_locale)?;
     let time_format_locale = parse_option_time_format_locale(time_format_locale)?;
-    let renderer = renderer.unwrap_or_else(|| "svg".to_string());
-    let mut converter = VL_CONVERTER
-        .lock()
-        .expect("Failed to acquire lock on Vega-Lite converter");
-    Ok(PYTHON_RUNTIME.block_on(converter.vega_to_html(
-        vg_spec,
-        VgOpts {
-            allowed_base_urls: None,
-            format_locale,
-            time_format_locale,
+    let renderer = parse_renderer(renderer)?;
+    Ok(with_converter(|converter| {
+        PYTHON_RUNTIME.block_on(converter.vega_to_html_with_opts(
+            vg_spec,
+            VgOpts {
+                theme,
+                config,
+                allowed_base_urls,
+                format_locale,
+                time_format_locale,
+                collect_resource_stats: collect_resource_stats(),
+                collect_dataset_summary: collect_dataset_summary(),
+                fetch_retries: None,
+                fetch_retry_backoff_ms: None,
+                interaction_script: None,
+                preserve_int64: false,
+                base_url: None,
+                allow_local_data: false,
+                lenient: false,
+                generic_font_mapping: None,
+                container_width: None,
+                container_height: None,
+                strict_sizing: false,
+                width: None,
+                height: None,
+                resize_mode: ResizeMode::default(),
+                min_size: None,
+                max_rows: None,
+                row_limit_strategy: RowLimitStrategy::default(),
+                annotate_truncation: false,
+                log_level: None,
+                warning_filters: None,
+                suppress_components: None,
+                browser_shims: true,
+                simplify_topojson: None,
+                force_iterations: None,
+                random_seed: None,
+                svg_post_processor: None,
+            },
+            bundle.unwrap_or(false),
+            renderer,
+            HtmlOpts {
+                static_fallback: static_fallback.unwrap_or(false),
+                static_fallback_scale,
+                warn_only: warn_only.unwrap_or(false),
+                template,
+            },
+        ))
+    })?)
+}
+
+/// Check a Vega spec for references to undefined scales, signals, and datasets, scale domains
+/// with fields missing from inline data, unused datasets, and duplicate signal names
+///
+/// Args:
+///     vg_spec (str | dict): Vega JSON specification string or dict
+/// Returns:
+///     list of dict: dicts with severity ("error" or "warning"), code, message, and path (JSON
+///         pointer to the part of the spec with the issue) keys
+#[pyfunction]
+#[pyo3(signature = (vg_spec))]
+fn lint_vega(vg_spec: PyObject) -> PyResult<PyObject> {
+    let vg_spec = parse_json_spec(vg_spec)?;
+    let issues = vl_convert_rs::lint::lint_vega(&vg_spec);
+    Python::with_gil(|py| -> PyResult<PyObject> {
+        pythonize(py, &issues)
+            .map_err(|err| PyValueError::new_err(err.to_string()))
+            .map(|obj| obj.into())
+    })
+}
+
+/// Find the interactive features of a Vega-Lite spec, like selections, hover highlights,
+/// inputs bound to params, tooltips, and datasets meant to be streamed in, which have no
+/// effect or are frozen in static SVG, PNG, JPEG, and PDF images
+///
+/// Args:
+///     vl_spec (str | dict): Vega-Lite JSON specification string or dict
+///     vl_version (str | None): Vega-Lite library version string (e.g. 'v5.15')
+///         (default to latest)
+///     config (dict | str | None): Chart configuration object, or a JSON string or the path
+///         of a JSON file holding it, to apply before checking for tooltips that it turns on
+///     theme (str | None): Named theme (e.g. "dark") to apply before checking for tooltips
+///         that it turns on
+/// Returns:
+///     list of dict: dicts with feature ("selection", "hover", "bind", "tooltip", or
+///         "streaming-data"), severity ("degraded" or "unsupported"), message, and spec_path
+///         (JSON pointer to the part of the spec with the feature) keys
+#[pyfunction]
+#[pyo3(signature = (vl_spec, vl_version=None, config=None, theme=None))]
+fn analyze_static_export(
+    vl_spec: PyObject,
+    vl_version: Option<&str>,
+    config: Option<PyObject>,
+    theme: Option<String>,
+) -> PyResult<PyObject> {
+    let theme = parse_option_theme(theme)?;
+    let vl_spec = parse_json_spec(vl_spec)?;
+    let config = parse_option_config(config)?;
+    let vl_version = parse_vl_version(vl_version)?;
+
+    let findings = match with_converter(|converter| {
+        PYTHON_RUNTIME.block_on(converter.analyze_static_export(
+            vl_spec,
+            VlOpts {
+                vl_version,
+                config,
+                theme,
+                show_warnings: false,
+                allowed_base_urls: None,
+                format_locale: None,
+                time_format_locale: None,
+                collect_resource_stats: false,
+                collect_dataset_summary: false,
+                fetch_retries: None,
+                fetch_retry_backoff_ms: None,
+                interaction_script: None,
+                preserve_int64: false,
+                base_url: None,
+                allow_local_data: false,
+                lenient: false,
+                font: None,
+                font_size_scale: None,
+                text_color: None,
+                config_overrides: None,
+                direction: None,
+                generic_font_mapping: None,
+                container_width: None,
+                container_height: None,
+                strict_sizing: false,
+                width: None,
+                height: None,
+                resize_mode: ResizeMode::default(),
+                min_size: None,
+                max_rows: None,
+                row_limit_strategy: RowLimitStrategy::default(),
+                annotate_truncation: false,
+                log_level: None,
+                warning_filters: None,
+                suppress_components: None,
+                browser_shims: true,
+                simplify_topojson: None,
+                pre_aggregate: false,
+                lenient_dates: false,
+                svg_post_processor: None,
+            },
+        ))
+    }) {
+        Ok(findings) => findings,
+        Err(err) => {
+            return Err(PyValueError::new_err(format!(
+                "Static export analysis failed:\n{}",
+                err
+            )))
+        }
+    };
+    Python::with_gil(|py| -> PyResult<PyObject> {
+        pythonize(py, &findings)
+            .map_err(|err| PyValueError::new_err(err.to_string()))
+            .map(|obj| obj.into())
+    })
+}
+
+/// Anonymize the inline data of a Vega or Vega-Lite spec, so that it can be shared in a bug
+/// report
+///
+/// Args:
+///     spec (str | dict): Vega or Vega-Lite JSON specification string or dict
+///     preserve_types (bool | None): Whether strings holding numbers or dates are perturbed
+///         and shifted like numbers and dates, rather than hashed (default true)
+///     preserve_cardinality (bool | None): Whether equal strings are replaced with equal
+///         hashes (default true)
+///     salt (str | None): Salt of the hashes and seed of the perturbation. When None, a
+///         random salt is used
+/// Returns:
+///     dict: The spec with anonymized inline data
+#[pyfunction]
+#[pyo3(signature = (spec, preserve_types=None, preserve_cardinality=None, salt=None))]
+fn anonymize_spec(
+    spec: PyObject,
+    preserve_types: Option<bool>,
+    preserve_cardinality: Option<bool>,
+    salt: Option<String>,
+) -> PyResult<PyObject> {
+    let spec = parse_json_spec(spec)?;
+    let defaults = AnonymizeOptions::default();
+    let anonymized = vl_convert_rs::anonymize::anonymize_spec(
+        &spec,
+        &AnonymizeOptions {
+            preserve_types: preserve_types.unwrap_or(defaults.preserve_types),
+            preserve_cardinality: preserve_cardinality.unwrap_or(defaults.preserve_cardinality),
+            salt,
+        },
+    );
+    Python::with_gil(|py| -> PyResult<PyObject> {
+        pythonize(py, &anonymized)
+            .map_err(|err| PyValueError::new_err(err.to_string()))
+            .map(|obj| obj.into())
+    })
+}
+
+/// Compare the structure of two SVG image strings, element by element
+///
+/// Args:
+///     svg_a (str): First SVG image string
+///     svg_b (str): Second SVG image string
+///     tolerance (float | None): Largest difference between two numbers in numeric attribute
+///         values, like coordinates, sizes, path data, and transforms, for them to be
+///         considered equal. Other attributes, like colors and ids, are compared exactly
+///         (default 0.0)
+///     ignore_ids (bool | None): Whether to ignore the values of id attributes, such as the
+///         clip path and gradient ids that Vega generates (default true)
+/// Returns:
+///     dict: dict with equal (bool) and differences keys. differences is a list of dicts with
+///         path, kind ("tag", "attribute", "text", "added", or "removed"), a, and b keys
+#[pyfunction]
+#[pyo3(signature = (svg_a, svg_b, tolerance=None, ignore_ids=None))]
+fn compare_svgs(
+    svg_a: &str,
+    svg_b: &str,
+    tolerance: Option<f64>,
+    ignore_ids: Option<bool>,
+) -> PyResult<PyObject> {
+    let opts = SvgCompareOptions {
+        tolerance: tolerance.unwrap_or(0.0),
+        ignore_ids: ignore_ids.unwrap_or(true),
+    };
+    let diff = vl_convert_rs::svg_diff::compare_svgs(svg_a, svg_b, &opts)?;
+    Python::with_gil(|py| -> PyResult<PyObject> {
+        pythonize(py, &diff)
+            .map_err(|err| PyValueError::new_err(err.to_string()))
+            .map(|obj| obj.into())
+    })
+}
+
+/// Convert an SVG image string to PNG image data
+///
+/// Args:
+///     svg (str): SVG image string
+///     scale (float): Image scale factor (default 1.0)
+///     ppi (float): Pixels per inch (default 72)
+///     interlaced (bool): Whether to write an interlaced (Adam7) PNG for progressive
+///                        display (default false)
+///     antialias (bool | None): Whether to antialias the edges of shapes. When false, shapes
+///         are drawn with crisp edges on whole pixels, so that adjacent rects (e.g. heatmap
+///         cells) don't show seams. Text is antialiased either way (default true)
+///     ppi_mode (str | None): How ppi affects the image. "resize-pixels" multiplies the
+///         number of pixels by ppi / 72, and "metadata-only" leaves the number of pixels to
+///         scale and only writes ppi to the PNG metadata (default "resize-pixels")
+///     image_rendering (str | None): How embedded raster images are resampled when they're
+///         scaled. One of "auto", "smooth", or "pixelated", which draws each pixel of an
+///         image as a sharp square. Images with an image-rendering attribute of their own
+///         keep it (default "auto")
+///     flatten_background (str | None): CSS color to composite the image over so that it
+///         has no transparent pixels, e.g. "white" or "#f0f0f0". By default, transparent
+///         parts of the chart are left transparent (default None)
+/// Returns:
+///     bytes: PNG image data
+#[pyfunction]
+#[pyo3(signature = (svg, scale=None, ppi=None, interlaced=None, antialias=None, ppi_mode=None, image_rendering=None, flatten_background=None))]
+fn svg_to_png(
+    svg: &str,
+    scale: Option<f32>,
+    ppi: Option<f32>,
+    interlaced: Option<bool>,
+    antialias: Option<bool>,
+    ppi_mode: Option<String>,
+    image_rendering: Option<String>,
+    flatten_background: Option<String>,
+) -> PyResult<PyObject> {
+    let png_data = vl_convert_rs::converter::svg_to_png_with_opts(
+        svg,
+        scale.unwrap_or(1.0),
+        ppi,
+        &PngOpts {
+            interlaced: interlaced.unwrap_or(false),
+            antialias: antialias.unwrap_or(true),
+            ppi_mode: parse_ppi_mode(ppi_mode)?,
+            image_rendering: parse_image_rendering(image_rendering)?,
+            flatten_background: parse_flatten_background(flatten_background)?,
+            ..Default::default()
         },
-        bundle.unwrap_or(false),
-        Renderer::from_str(&renderer)?,
-    ))?)
+    )?;
+    Ok(Python::with_gil(|py| -> PyObject {
+        PyBytes::new_bound(py, png_data.as_slice()).into()
+    }))
+}
+
+/// Convert an SVG image string to JPEG image data
+///
+/// Args:
+///     svg (str): SVG image string
+///     scale (float): Image scale factor (default 1.0)
+///     quality (int): JPEG Quality between 1 (worst) and 100 (best). Default 90
+///     jpeg_background (str | None): CSS color to composite the image over, since JPEG
+///         images can't be transparent, e.g. "#f0f0f0" (default "white")
+/// Returns:
+///     bytes: JPEG image data
+#[pyfunction]
+#[pyo3(signature = (svg, scale=None, quality=None, jpeg_background=None))]
+fn svg_to_jpeg(
+    svg: &str,
+    scale: Option<f32>,
+    quality: Option<u8>,
+    jpeg_background: Option<String>,
+) -> PyResult<PyObject> {
+    let jpeg_data = vl_convert_rs::converter::svg_to_jpeg_with_opts(
+        svg,
+        scale.unwrap_or(1.0),
+        quality,
+        &parse_jpeg_opts(jpeg_background)?,
+    )?;
+    Ok(Python::with_gil(|py| -> PyObject {
+        PyBytes::new_bound(py, jpeg_data.as_slice()).into()
+    }))
+}
+
+/// Convert an SVG image string to PDF document data
+///
+/// Args:
+///     svg (str): SVG image string
+///     scale (float): Image scale factor (default 1.0)
+///     pdfa (bool | None): Write a PDF/A-2b document for archiving, with XMP metadata, an
+///         sRGB output intent, and a document ID (default false)
+///     source_dpi (float | None): Pixels per inch of the image, which sets the size of the
+///         PDF page in points. The default of 96 matches CSS pixels, so that a chart 960
+///         pixels wide is written to a page 720 points wide (default 96)
+/// Returns:
+///     bytes: PDF document data
+#[pyfunction]
+#[pyo3(signature = (svg, scale=None, pdfa=None, source_dpi=None))]
+fn svg_to_pdf(
+    svg: &str,
+    scale: Option<f32>,
+    pdfa: Option<bool>,
+    source_dpi: Option<f32>,
+) -> PyResult<PyObject> {
+    warn_if_scale_not_one_for_pdf(scale)?;
+    let pdf_opts = PdfOpts {
+        pdfa: pdfa.unwrap_or(false),
+        source_dpi: source_dpi.unwrap_or(96.0),
+    };
+    // Always pass 1.0 as scale
+    let pdf_data = vl_convert_rs::converter::svg_to_pdf_with_opts(svg, &pdf_opts)?;
+    Ok(Python::with_gil(|py| -> PyObject {
+        PyBytes::new_bound(py, pdf_data.as_slice()).into()
+    }))
+}
+
+/// The SVG image string of a conversion, or a dict with the image and its size when
+/// `return_info` is true
+fn svg_result_object(py: Python, svg: SvgResult, return_info: bool) -> PyResult<PyObject> {
+    if !return_info {
+        return Ok(svg.data.into_py(py));
+    }
+    let dict = PyDict::new_bound(py);
+    dict.set_item("data", svg.data)?;
+    dict.set_item("width", svg.width)?;
+    dict.set_item("height", svg.height)?;
+    dict.set_item("view_box", svg.view_box.map(|view_box| view_box.to_vec()))?;
+    dict.set_item("request_hash", svg.request_hash)?;
+    dict.set_item("warnings", render_warnings_object(py, &svg.warnings)?)?;
+    Ok(dict.into())
+}
+
+/// The PNG image data of a conversion, or a dict with the image data and its size when
+/// `return_info` is true
+fn png_result_object(py: Python, png: PngResult, return_info: bool) -> PyResult<PyObject> {
+    let data = PyBytes::new_bound(py, png.data.as_slice());
+    if !return_info {
+        return Ok(data.into());
+    }
+    let dict = PyDict::new_bound(py);
+    dict.set_item("data", data)?;
+    dict.set_item("width_px", png.width_px)?;
+    dict.set_item("height_px", png.height_px)?;
+    dict.set_item("css_width", png.css_width)?;
+    dict.set_item("css_height", png.css_height)?;
+    dict.set_item("scale", png.scale)?;
+    dict.set_item("ppi", png.ppi)?;
+    dict.set_item("request_hash", png.request_hash)?;
+    dict.set_item("warnings", render_warnings_object(py, &png.warnings)?)?;
+    Ok(dict.into())
+}
+
+/// The JPEG image data of a conversion, or a dict with the image data and its size when
+/// `return_info` is true
+fn jpeg_result_object(py: Python, jpeg: JpegResult, return_info: bool) -> PyResult<PyObject> {
+    let data = PyBytes::new_bound(py, jpeg.data.as_slice());
+    if !return_info {
+        return Ok(data.into());
+    }
+    let dict = PyDict::new_bound(py);
+    dict.set_item("data", data)?;
+    dict.set_item("width_px", jpeg.width_px)?;
+    dict.set_item("height_px", jpeg.height_px)?;
+    dict.set_item("css_width", jpeg.css_width)?;
+    dict.set_item("css_height", jpeg.css_height)?;
+    dict.set_item("scale", jpeg.scale)?;
+    dict.set_item("quality", jpeg.quality)?;
+    dict.set_item("request_hash", jpeg.request_hash)?;
+    dict.set_item("warnings", render_warnings_object(py, &jpeg.warnings)?)?;
+    Ok(dict.into())
+}
+
+/// A list of dicts with the "mark" and "message" of each render warning
+fn render_warnings_object(py: Python, warnings: &[RenderWarning]) -> PyResult<PyObject> {
+    pythonize(py, warnings)
+        .map_err(|err| PyValueError::new_err(err.to_string()))
+        .map(|obj| obj.into())
+}
+
+/// Helper function to read whether conversions should collect resource statistics
+fn collect_resource_stats() -> bool {
+    COLLECT_RESOURCE_STATS.load(Ordering::Relaxed)
+}
+
+fn collect_dataset_summary() -> bool {
+    COLLECT_DATASET_SUMMARY.load(Ordering::Relaxed)
+}
+
+/// Helper function to parse an input Python string or dict as a serde_json::Value
+fn parse_json_spec(vl_spec: PyObject) -> PyResult<serde_json::Value> {
+    Python::with_gil(|py| -> PyResult<serde_json::Value> {
+        if let Ok(vl_spec) = vl_spec.extract::<Cow<str>>(py) {
+            match parse_json_spec_rs(vl_spec.as_ref()) {
+                Ok(vl_spec) => Ok(vl_spec),
+                Err(err) => Err(PyValueError::new_err(format!(
+                    "Failed to parse vl_spec string as JSON: {}",
+                    err
+                ))),
+            }
+        } else if let Ok(vl_spec) = vl_spec.downcast_bound::<PyDict>(py) {
+            check_py_json_depth(vl_spec.as_any())?;
+            match depythonize(vl_spec.as_any()) {
+                Ok(vl_spec) => Ok(vl_spec),
+                Err(err) => Err(PyValueError::new_err(format!(
+                    "Failed to parse vl_spec dict as JSON: {}",
+                    err
+                ))),
+            }
+        } else {
+            Err(PyValueError::new_err("vl_spec must be a string or dict"))
+        }
+    })
+}
+
+/// Parse the config argument of the conversion functions. Strings that hold a JSON object are
+/// parsed as config, and other strings are the path of a JSON file to read it from, as with
+/// the --config option of the CLI
+fn parse_option_config(config: Option<PyObject>) -> PyResult<Option<serde_json::Value>> {
+    let Some(config) = config else {
+        return Ok(None);
+    };
+    let config_str = Python::with_gil(|py| -> PyResult<Option<String>> {
+        if let Ok(config_str) = config.extract::<String>(py) {
+            Ok(Some(config_str))
+        } else if config.downcast_bound::<PyDict>(py).is_ok() {
+            Ok(None)
+        } else {
+            Err(PyValueError::new_err(
+                "config must be a dict, a JSON string, or the path of a JSON file",
+            ))
+        }
+    })?;
+    let Some(config_str) = config_str else {
+        return parse_json_spec(config).map(Some);
+    };
+    if config_str.trim_start().starts_with('{') {
+        return parse_json_spec_rs(&config_str).map(Some).map_err(|err| {
+            PyValueError::new_err(format!("Failed to parse config string as JSON: {err}"))
+        });
+    }
+
+    let path = shellexpand::tilde(config_str.trim()).to_string();
+    let file_str = match std::fs::read_to_string(&path) {
+        Ok(file_str) => file_str,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            return Err(PyFileNotFoundError::new_err(format!(
+                "Config file not found: {path}"
+            )))
+        }
+        Err(err) => {
+            return Err(PyValueError::new_err(format!(
+                "Failed to read config file {path}: {err}"
+            )))
+        }
+    };
+    parse_json_spec_rs(&file_str)
+        .map(Some)
+        .map_err(|err| PyValueError::new_err(format!("Invalid JSON in config file {path}: {err}")))
+}
+
+/// Check the nesting depth of a Python dict or list before it's converted to JSON.
+///
+/// The check walks the object with an explicit stack so that deeply nested input can't
+/// overflow the stack before the max_json_depth limit is applied
+fn check_py_json_depth(obj: &Bound<PyAny>) -> PyResult<()> {
+    let limits = get_spec_limits()
+        .map_err(|err| PyValueError::new_err(format!("Failed to get spec limits: {}", err)))?;
+    let mut stack: Vec<(Bound<PyAny>, usize)> = vec![(obj.clone(), 0)];
+    while let Some((obj, depth)) = stack.pop() {
+        let children: Vec<Bound<PyAny>> = if let Ok(dict) = obj.downcast::<PyDict>() {
+            dict.values().into_iter().collect()
+        } else if let Ok(list) = obj.downcast::<PyList>() {
+            list.iter().collect()
+        } else if let Ok(tuple) = obj.downcast::<PyTuple>() {
+            tuple.iter().collect()
+        } else {
+            continue;
+        };
+        let depth = depth + 1;
+        limits
+            .check_json_depth(depth)
+            .map_err(|err| PyValueError::new_err(err.to_string()))?;
+        stack.extend(children.into_iter().map(|child| (child, depth)));
+    }
+    Ok(())
+}
+
+/// Parse the ppi_mode argument of the PNG conversion functions
+fn parse_ppi_mode(ppi_mode: Option<String>) -> PyResult<PpiMode> {
+    match ppi_mode {
+        None => Ok(PpiMode::default()),
+        Some(ppi_mode) => {
+            PpiMode::from_str(&ppi_mode).map_err(|err| PyValueError::new_err(err.to_string()))
+        }
+    }
+}
+
+/// Parse the image_rendering argument of the PNG conversion functions
+fn parse_image_rendering(image_rendering: Option<String>) -> PyResult<ImageRendering> {
+    match image_rendering {
+        None => Ok(ImageRendering::default()),
+        Some(image_rendering) => ImageRendering::from_str(&image_rendering)
+            .map_err(|err| PyValueError::new_err(err.to_string())),
+    }
+}
+
+/// Parse the jpeg_background argument of the JPEG conversion functions
+fn parse_jpeg_opts(jpeg_background: Option<String>) -> PyResult<JpegOpts> {
+    let background = match jpeg_background {
+        None => BackgroundColor::default(),
+        Some(background) => parse_background_color(&background)?,
+    };
+    Ok(JpegOpts { background })
+}
+
+/// Parse the flatten_background argument of the PNG conversion functions
+fn parse_flatten_background(
+    flatten_background: Option<String>,
+) -> PyResult<Option<BackgroundColor>> {
+    flatten_background
+        .as_deref()
+        .map(parse_background_color)
+        .transpose()
+}
+
+fn parse_background_color(background: &str) -> PyResult<BackgroundColor> {
+    BackgroundColor::from_str(background).map_err(|err| PyValueError::new_err(err.to_string()))
+}
+
+/// Parse the resize_mode argument of the conversion functions that take a width and height
+fn parse_resize_mode(resize_mode: Option<String>) -> PyResult<ResizeMode> {
+    match resize_mode {
+        None => Ok(ResizeMode::default()),
+        Some(resize_mode) => {
+            ResizeMode::from_str(&resize_mode).map_err(|err| PyValueError::new_err(err.to_string()))
+        }
+    }
+}
+
+fn parse_row_limit_strategy(row_limit_strategy: Option<String>) -> PyResult<RowLimitStrategy> {
+    match row_limit_strategy {
+        None => Ok(RowLimitStrategy::default()),
+        Some(row_limit_strategy) => RowLimitStrategy::from_str(&row_limit_strategy)
+            .map_err(|err| PyValueError::new_err(err.to_string())),
+    }
+}
+
+fn parse_log_level(log_level: Option<String>) -> PyResult<Option<LogLevel>> {
+    log_level
+        .map(|log_level| LogLevel::from_str(&log_level))
+        .transpose()
+        .map_err(|err| PyValueError::new_err(err.to_string()))
+}
+
+fn parse_suppress_components(
+    suppress_components: Option<Vec<String>>,
+) -> PyResult<Option<Vec<Component>>> {
+    suppress_components
+        .map(|components| {
+            components
+                .iter()
+                .map(|component| Component::from_str(component))
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .transpose()
+        .map_err(|err| PyValueError::new_err(err.to_string()))
+}
+
+/// Wrap the Python callable that post-processes the SVG of a conversion. Conversions run with
+/// the GIL released, so it's re-acquired around each call
+fn parse_svg_post_processor(
+    svg_post_processor: Option<PyObject>,
+) -> PyResult<Option<SvgPostProcessor>> {
+    let Some(svg_post_processor) = svg_post_processor else {
+        return Ok(None);
+    };
+    Python::with_gil(|py| {
+        if !svg_post_processor.bind(py).is_callable() {
+            return Err(PyValueError::new_err(
+                "svg_post_processor must be a callable that takes and returns an SVG string",
+            ));
+        }
+        Ok(())
+    })?;
+    Ok(Some(SvgPostProcessor::new(move |svg: String| {
+        Python::with_gil(|py| {
+            svg_post_processor
+                .call1(py, (svg,))
+                .and_then(|svg| svg.extract::<String>(py))
+                .map_err(|err| anyhow!("{err}"))
+        })
+    })))
+}
+
+/// Parse the vl_version argument of the Vega-Lite conversion functions, listing the bundled
+/// versions when it isn't one of them
+fn parse_vl_version(vl_version: Option<&str>) -> PyResult<VlVersion> {
+    let Some(vl_version) = vl_version else {
+        return Ok(Default::default());
+    };
+    VlVersion::from_str(vl_version).map_err(|err| {
+        // Versions that are left out of this build are reported with how to get them
+        if err.to_string().contains("does not include") {
+            return PyValueError::new_err(err.to_string());
+        }
+        let versions: Vec<_> = VL_VERSIONS.iter().map(|v| v.to_semver()).collect();
+        PyValueError::new_err(format!(
+            "Unsupported Vega-Lite version: {vl_version}. Valid versions are: {}",
+            versions.join(", ")
+        ))
+    })
 }
 
-/// Convert an SVG image string to PNG image data
-///
-/// Args:
-///     svg (str): SVG image string
-///     scale (float): Image scale factor (default 1.0)
-///     ppi (float): Pixels per inch (default 72)
-/// Returns:
-///     bytes: PNG image data
-#[pyfunction]
-#[pyo3(signature = (svg, scale=None, ppi=None))]
-fn svg_to_png(svg: &str, scale: Option<f32>, ppi: Option<f32>) -> PyResult<PyObject> {
-    let png_data = vl_convert_rs::converter::svg_to_png(svg, scale.unwrap_or(1.0), ppi)?;
-    Ok(Python::with_gil(|py| -> PyObject {
-        PyBytes::new_bound(py, png_data.as_slice()).into()
-    }))
+/// Parse the renderer argument of the HTML conversion functions
+fn parse_renderer(renderer: Option<String>) -> PyResult<Renderer> {
+    let Some(renderer) = renderer else {
+        return Ok(Renderer::Svg);
+    };
+    Renderer::from_str(&renderer).map_err(|_| {
+        PyValueError::new_err(format!(
+            "Unsupported renderer: {renderer}. Valid renderers are: svg, canvas, hybrid"
+        ))
+    })
 }
 
-/// Convert an SVG image string to JPEG image data
-///
-/// Args:
-///     svg (str): SVG image string
-///     scale (float): Image scale factor (default 1.0)
-///     quality (int): JPEG Quality between 0 (worst) and 100 (best). Default 90
-/// Returns:
-///     bytes: JPEG image data
-#[pyfunction]
-#[pyo3(signature = (svg, scale=None, quality=None))]
-fn svg_to_jpeg(svg: &str, scale: Option<f32>, quality: Option<u8>) -> PyResult<PyObject> {
-    let jpeg_data = vl_convert_rs::converter::svg_to_jpeg(svg, scale.unwrap_or(1.0), quality)?;
-    Ok(Python::with_gil(|py| -> PyObject {
-        PyBytes::new_bound(py, jpeg_data.as_slice()).into()
-    }))
+/// Names of the built-in themes, which are read from the converter on first use
+fn theme_names() -> PyResult<&'static [String]> {
+    static THEME_NAMES: OnceLock<Vec<String>> = OnceLock::new();
+    if let Some(names) = THEME_NAMES.get() {
+        return Ok(names);
+    }
+    let names = with_converter(|converter| PYTHON_RUNTIME.block_on(converter.get_theme_names()))
+        .map_err(|err| {
+            PyValueError::new_err(format!("get_theme_names request failed:\n{}", err))
+        })?;
+    Ok(THEME_NAMES.get_or_init(|| names))
 }
 
-/// Convert an SVG image string to PDF document data
-///
-/// Args:
-///     svg (str): SVG image string
-///     scale (float): Image scale factor (default 1.0)
-/// Returns:
-///     bytes: PDF document data
-#[pyfunction]
-#[pyo3(signature = (svg, scale=None))]
-fn svg_to_pdf(svg: &str, scale: Option<f32>) -> PyResult<PyObject> {
-    warn_if_scale_not_one_for_pdf(scale)?;
-    let pdf_data = vl_convert_rs::converter::svg_to_pdf(svg)?; // Always pass 1.0 as scale
-    Ok(Python::with_gil(|py| -> PyObject {
-        PyBytes::new_bound(py, pdf_data.as_slice()).into()
-    }))
+/// Check the theme argument of the conversion functions against the built-in themes
+fn parse_option_theme(theme: Option<String>) -> PyResult<Option<String>> {
+    if let Some(theme) = &theme {
+        let names = theme_names()?;
+        if !names.contains(theme) {
+            return Err(PyValueError::new_err(format!(
+                "{} Valid themes are: {}",
+                unknown_name_message("theme", theme, &names),
+                names.join(", ")
+            )));
+        }
+    }
+    Ok(theme)
 }
 
-/// Helper function to parse an input Python string or dict as a serde_json::Value
-fn parse_json_spec(vl_spec: PyObject) -> PyResult<serde_json::Value> {
-    Python::with_gil(|py| -> PyResult<serde_json::Value> {
-        if let Ok(vl_spec) = vl_spec.extract::<Cow<str>>(py) {
-            match serde_json::from_str::<serde_json::Value>(vl_spec.as_ref()) {
-                Ok(vl_spec) => Ok(vl_spec),
-                Err(err) => Err(PyValueError::new_err(format!(
-                    "Failed to parse vl_spec string as JSON: {}",
-                    err
-                ))),
-            }
-        } else if let Ok(vl_spec) = vl_spec.downcast_bound::<PyDict>(py) {
-            match depythonize(vl_spec.as_any()) {
-                Ok(vl_spec) => Ok(vl_spec),
-                Err(err) => Err(PyValueError::new_err(format!(
-                    "Failed to parse vl_spec dict as JSON: {}",
-                    err
-                ))),
-            }
-        } else {
-            Err(PyValueError::new_err("vl_spec must be a string or dict"))
+/// Check the themes argument of the conversion functions, which may also hold "default"
+fn parse_option_themes(themes: Option<Vec<String>>) -> PyResult<Option<Vec<String>>> {
+    if let Some(themes) = &themes {
+        for theme in themes.iter().filter(|theme| *theme != "default") {
+            parse_option_theme(Some(theme.clone()))?;
         }
-    })
+    }
+    Ok(themes)
+}
+
+fn parse_option_direction(direction: Option<String>) -> PyResult<Option<Direction>> {
+    direction
+        .map(|direction| {
+            Direction::from_str(&direction).map_err(|err| PyValueError::new_err(err.to_string()))
+        })
+        .transpose()
 }
 
 /// Helper function to parse an input Python string or dict as a FormatLocale
 fn parse_format_locale(v: PyObject) -> PyResult<FormatLocale> {
     Python::with_gil(|py| -> PyResult<FormatLocale> {
         if let Ok(name) = v.extract::<Cow<str>>(py) {
-            let format_locale = FormatLocale::Name(name.as_ref().to_string());
-            if format_locale.as_object().is_err() {
-                Err(PyValueError::new_err(
-                    format!("Invalid format_locale name: {name}\nSee https://github.com/d3/d3-format/tree/main/locale for available names")
-                ))
+            let names = format_locale_names();
+            if names.iter().any(|locale| locale == name.as_ref()) {
+                Ok(FormatLocale::Name(name.as_ref().to_string()))
             } else {
-                Ok(format_locale)
+                Err(PyValueError::new_err(format!(
+                    "{}\nSee https://github.com/d3/d3-format/tree/main/locale for available names",
+                    unknown_name_message("format locale", &name, &names)
+                )))
             }
         } else if let Ok(obj) = v.downcast_bound::<PyDict>(py) {
             match depythonize(obj.as_any()) {
@@ -946,6 +5240,48 @@ fn parse_format_locale(v: PyObject) -> PyResult<FormatLocale> {
         }
     })
 }
+fn parse_option_interactions(v: Option<PyObject>) -> PyResult<Option<Vec<InteractionStep>>> {
+    let Some(v) = v else {
+        return Ok(None);
+    };
+    Python::with_gil(|py| match depythonize(v.bind(py)) {
+        Ok(interactions) => Ok(Some(interactions)),
+        Err(err) => Err(PyValueError::new_err(format!(
+            "Failed to parse interactions: {}",
+            err
+        ))),
+    })
+}
+
+/// Helper function to parse a dict of config values keyed by dotted paths, keeping the order
+/// of its keys
+fn parse_option_config_overrides(
+    v: Option<PyObject>,
+) -> PyResult<Option<Vec<(String, serde_json::Value)>>> {
+    let Some(v) = v else {
+        return Ok(None);
+    };
+    Python::with_gil(|py| {
+        let Ok(overrides) = v.downcast_bound::<PyDict>(py) else {
+            return Err(PyValueError::new_err("config_overrides must be a dict"));
+        };
+        overrides
+            .iter()
+            .map(|(path, value)| {
+                let path = path.extract::<String>()?;
+                match depythonize(&value) {
+                    Ok(value) => Ok((path, value)),
+                    Err(err) => Err(PyValueError::new_err(format!(
+                        "Failed to parse config override {}: {}",
+                        path, err
+                    ))),
+                }
+            })
+            .collect::<PyResult<Vec<_>>>()
+            .map(Some)
+    })
+}
+
 fn parse_option_format_locale(v: Option<PyObject>) -> PyResult<Option<FormatLocale>> {
     match v {
         None => Ok(None),
@@ -957,13 +5293,14 @@ fn parse_option_format_locale(v: Option<PyObject>) -> PyResult<Option<FormatLoca
 fn parse_time_format_locale(v: PyObject) -> PyResult<TimeFormatLocale> {
     Python::with_gil(|py| -> PyResult<TimeFormatLocale> {
         if let Ok(name) = v.extract::<Cow<str>>(py) {
-            let time_format_locale = TimeFormatLocale::Name(name.as_ref().to_string());
-            if time_format_locale.as_object().is_err() {
-                Err(PyValueError::new_err(
-                    format!("Invalid time_format_locale name: {name}\nSee https://github.com/d3/d3-time-format/tree/main/locale for available names")
-                ))
+            let names = time_format_locale_names();
+            if names.iter().any(|locale| locale == name.as_ref()) {
+                Ok(TimeFormatLocale::Name(name.as_ref().to_string()))
             } else {
-                Ok(time_format_locale)
+                Err(PyValueError::new_err(format!(
+                    "{}\nSee https://github.com/d3/d3-time-format/tree/main/locale for available names",
+                    unknown_name_message("time format locale", &name, &names)
+                )))
             }
         } else if let Ok(obj) = v.downcast_bound::<PyDict>(py) {
             match depythonize(obj.as_any()) {
@@ -1004,6 +5341,206 @@ fn register_font_directory(font_dir: &str) -> PyResult<()> {
     Ok(())
 }
 
+/// Set the font family that emoji in chart text are drawn with in subsequent conversions,
+/// when the font of the text doesn't have them
+///
+/// Args:
+///     family (str | None): Font family, which must be installed or in a directory registered
+///         with register_font_directory. When None, the first installed of the common color
+///         emoji fonts is used, like Noto Color Emoji and Apple Color Emoji
+///
+/// Returns:
+///     None
+#[pyfunction]
+#[pyo3(signature = (family=None))]
+fn set_emoji_font(family: Option<String>) -> PyResult<()> {
+    set_emoji_font_rs(family)
+        .map_err(|err| PyValueError::new_err(format!("Failed to set emoji font: {}", err)))
+}
+
+/// Get the font family that emoji in chart text are drawn with
+///
+/// Returns:
+///     str | None: Font family, or None if no emoji font is installed or registered
+#[pyfunction]
+#[pyo3(signature = ())]
+fn get_emoji_font() -> PyResult<Option<String>> {
+    get_emoji_font_rs()
+        .map_err(|err| PyValueError::new_err(format!("Failed to get emoji font: {}", err)))
+}
+
+/// Measure text glyph by glyph with the same fonts that charts are rendered with
+///
+/// Args:
+///     text (str): Text to measure
+///     font (str | None): Font family. Defaults to the default sans-serif font
+///     size (float): Font size in pixels. Defaults to 11, the default font size of labels
+///     weight (str | int | None): Font weight, like "bold" or 700
+///
+/// Returns:
+///     dict: dict with the width of the text and a glyphs list of dicts with cluster (byte
+///         offset in the UTF-8 text), x_advance, x_offset, y_offset, glyph_id, and
+///         font_family keys. The advances sum to the width
+#[pyfunction]
+#[pyo3(signature = (text, font=None, size=11.0, weight=None))]
+fn measure_text(
+    text: &str,
+    font: Option<&str>,
+    size: f64,
+    weight: Option<PyObject>,
+) -> PyResult<PyObject> {
+    let weight = weight
+        .map(|weight| Python::with_gil(|py| weight.bind(py).str().map(|weight| weight.to_string())))
+        .transpose()?;
+    let metrics = measure_text_detailed(text, font, size, weight.as_deref())
+        .map_err(|err| PyValueError::new_err(format!("Failed to measure text: {}", err)))?;
+    Python::with_gil(|py| -> PyResult<PyObject> {
+        pythonize(py, &metrics)
+            .map_err(|err| PyValueError::new_err(err.to_string()))
+            .map(|obj| obj.into())
+    })
+}
+
+/// Set the limits applied to input specifications in subsequent conversions
+///
+/// Args:
+///     max_spec_bytes (int | None): Maximum size, in bytes, of serialized specifications.
+///         Defaults to 256MB
+///     max_json_depth (int | None): Maximum nesting depth of arrays and objects in
+///         specifications. Defaults to 1000
+///     max_views (int | None): Maximum number of views that a Vega-Lite specification may
+///         expand to through repeat, facet, concat, and layer. Facets are counted by the
+///         distinct values of their fields in inline data. Defaults to 500
+///
+/// Returns:
+///     None
+#[pyfunction]
+#[pyo3(signature = (max_spec_bytes=None, max_json_depth=None, max_views=None))]
+fn set_spec_limits(
+    max_spec_bytes: Option<usize>,
+    max_json_depth: Option<usize>,
+    max_views: Option<usize>,
+) -> PyResult<()> {
+    set_spec_limits_rs(SpecLimits {
+        max_spec_bytes: max_spec_bytes.unwrap_or(DEFAULT_MAX_SPEC_BYTES),
+        max_json_depth: max_json_depth.unwrap_or(DEFAULT_MAX_JSON_DEPTH),
+        max_views: max_views.unwrap_or(DEFAULT_MAX_VIEWS),
+    })
+    .map_err(|err| PyValueError::new_err(format!("Failed to set spec limits: {}", err)))?;
+    Ok(())
+}
+
+/// Forward log messages from vl-convert to Python's logging module
+///
+/// Messages are emitted through the "vl_convert" logger, including Vega-Lite compilation
+/// warnings and messages from the JavaScript runtime.
+///
+/// Args:
+///     level (str): Minimum level of messages to forward. One of "off", "error", "warn",
+///         "info", "debug", or "trace". Defaults to "warn"
+///
+/// Returns:
+///     None
+#[pyfunction]
+#[pyo3(signature = (level="warn"))]
+fn enable_logging(py: Python<'_>, level: &str) -> PyResult<()> {
+    let level = log::LevelFilter::from_str(level)
+        .map_err(|_| PyValueError::new_err(format!("Invalid log level: {}", level)))?;
+
+    // The logger can only be installed once, so later calls only change the level
+    if !LOGGER_INSTALLED.swap(true, Ordering::Relaxed) {
+        let logger = pyo3_log::Logger::new(py, pyo3_log::Caching::Loggers)?;
+        if let Err(err) = logger.filter(log::LevelFilter::Trace).install() {
+            LOGGER_INSTALLED.store(false, Ordering::Relaxed);
+            return Err(PyValueError::new_err(format!(
+                "Failed to enable logging: {}",
+                err
+            )));
+        }
+    }
+    log::set_max_level(level);
+    Ok(())
+}
+
+/// Enable or disable the collection of memory statistics for subsequent conversions
+///
+/// Args:
+///     enabled (bool): Whether to collect memory statistics. Defaults to True
+///
+/// Returns:
+///     None
+#[pyfunction]
+#[pyo3(signature = (enabled=true))]
+fn set_collect_resource_stats(enabled: bool) {
+    COLLECT_RESOURCE_STATS.store(enabled, Ordering::Relaxed);
+}
+
+/// Enable or disable the collection of dataset summaries for subsequent conversions
+///
+/// Args:
+///     enabled (bool): Whether to collect dataset summaries. Defaults to True
+///
+/// Returns:
+///     None
+#[pyfunction]
+#[pyo3(signature = (enabled=true))]
+fn set_collect_dataset_summary(enabled: bool) {
+    COLLECT_DATASET_SUMMARY.store(enabled, Ordering::Relaxed);
+}
+
+/// Set whether the EXIF orientation of JPEG images is ignored when rendering charts to PNG,
+/// JPEG, and PDF. By default, JPEG images are rotated and flipped according to their EXIF
+/// orientation, as browsers display them
+///
+/// Args:
+///     ignore (bool): Whether to draw the pixels of JPEG images as they're stored.
+///         Defaults to True
+///
+/// Returns:
+///     None
+#[pyfunction]
+#[pyo3(signature = (ignore=true))]
+fn set_ignore_exif_orientation(ignore: bool) {
+    vl_convert_rs::image_loading::set_ignore_exif_orientation(ignore);
+}
+
+/// Get a summary of the datasets of the chart rendered by the most recent conversion performed
+/// while dataset summary collection was enabled with set_collect_dataset_summary
+///
+/// Returns:
+///     dict | None: dict with a datasets key, holding a list of dicts with name, rows, fields,
+///         and feeds_mark keys, and an any_marks_rendered key. None if no summary has been
+///         collected
+#[pyfunction]
+#[pyo3(signature = ())]
+fn get_last_dataset_summary() -> PyResult<PyObject> {
+    let summary = with_converter(|converter| converter.get_last_dataset_summary());
+    Python::with_gil(|py| -> PyResult<PyObject> {
+        pythonize(py, &summary)
+            .map_err(|err| PyValueError::new_err(err.to_string()))
+            .map(|obj| obj.into())
+    })
+}
+
+/// Get memory statistics for the most recent conversion performed while resource statistics
+/// collection was enabled with set_collect_resource_stats
+///
+/// Returns:
+///     dict | None: dict with v8_heap_used_bytes, v8_heap_total_bytes, pixmap_bytes, svg_bytes,
+///         and peak_rss_delta_bytes keys, or None if no statistics have been collected
+#[pyfunction]
+#[pyo3(signature = ())]
+fn get_last_resource_stats() -> PyResult<PyObject> {
+    let resource_stats = get_last_resource_stats_rs().map_err(|err| {
+        PyValueError::new_err(format!("Failed to get resource statistics: {}", err))
+    })?;
+    Python::with_gil(|py| -> PyResult<PyObject> {
+        pythonize(py, &resource_stats)
+            .map_err(|err| PyValueError::new_err(err.to_string()))
+            .map(|obj| obj.into())
+    })
+}
+
 /// Get the named local timezone that Vega uses to perform timezone calculations
 ///
 /// Returns:
@@ -1012,32 +5549,28 @@ fn register_font_directory(font_dir: &str) -> PyResult<()> {
 #[pyfunction]
 #[pyo3(signature = ())]
 fn get_local_tz() -> PyResult<Option<String>> {
-    let mut converter = VL_CONVERTER
-        .lock()
-        .expect("Failed to acquire lock on Vega-Lite converter");
-    let local_tz = match PYTHON_RUNTIME.block_on(converter.get_local_tz()) {
-        Ok(local_tz) => local_tz,
-        Err(err) => {
-            return Err(PyValueError::new_err(format!(
-                "get_local_tz request failed:\n{}",
-                err
-            )))
-        }
-    };
+    let local_tz =
+        match with_converter(|converter| PYTHON_RUNTIME.block_on(converter.get_local_tz())) {
+            Ok(local_tz) => local_tz,
+            Err(err) => {
+                return Err(PyValueError::new_err(format!(
+                    "get_local_tz request failed:\n{}",
+                    err
+                )))
+            }
+        };
     Ok(local_tz)
 }
 
 /// Get the config dict for each built-in theme
 ///
 /// Returns:
-///     dict: dict from theme name to config object
+///     dict: dict from theme name to config object, sorted by theme name. The keys of each
+///         config are sorted too
 #[pyfunction]
 #[pyo3(signature = ())]
 fn get_themes() -> PyResult<PyObject> {
-    let mut converter = VL_CONVERTER
-        .lock()
-        .expect("Failed to acquire lock on Vega-Lite converter");
-    let themes = match PYTHON_RUNTIME.block_on(converter.get_themes()) {
+    let themes = match with_converter(|converter| PYTHON_RUNTIME.block_on(converter.get_themes())) {
         Ok(themes) => themes,
         Err(err) => {
             return Err(PyValueError::new_err(format!(
@@ -1053,6 +5586,84 @@ fn get_themes() -> PyResult<PyObject> {
     })
 }
 
+/// Get the names of the built-in themes, without their configs
+///
+/// Returns:
+///     list: Sorted theme names
+#[pyfunction]
+#[pyo3(signature = ())]
+fn get_theme_names() -> PyResult<Vec<String>> {
+    Ok(theme_names()?.to_vec())
+}
+
+/// Get the provenance of the built-in themes
+///
+/// Returns:
+///     dict: dict with the vega_themes_version that the themes come from, and the sorted
+///         theme names under themes
+#[pyfunction]
+#[pyo3(signature = ())]
+fn get_themes_meta() -> PyResult<PyObject> {
+    let meta = serde_json::json!({
+        "vega_themes_version": VEGA_THEMES_VERSION,
+        "themes": theme_names()?,
+    });
+    Python::with_gil(|py| -> PyResult<PyObject> {
+        pythonize(py, &meta)
+            .map_err(|err| PyValueError::new_err(err.to_string()))
+            .map(|obj| obj.into())
+    })
+}
+
+/// Check that the converter's JavaScript runtime is responsive, without rendering a chart
+///
+/// Returns:
+///     dict: dict with vega_version, vl_default_version, worker_uptime (seconds),
+///         worker_restarts, last_conversion_at (seconds since the Unix epoch, or None
+///         before the first conversion), conversions_completed, conversions_failed, and
+///         vl_module_evaluations keys
+#[pyfunction]
+#[pyo3(signature = ())]
+fn health_check() -> PyResult<PyObject> {
+    let report = match with_converter(|converter| PYTHON_RUNTIME.block_on(converter.health_check()))
+    {
+        Ok(report) => report,
+        Err(err) => {
+            return Err(PyValueError::new_err(format!(
+                "Health check failed:\n{}",
+                err
+            )))
+        }
+    };
+    Python::with_gil(|py| -> PyResult<PyObject> {
+        pythonize(py, &report)
+            .map_err(|err| PyValueError::new_err(err.to_string()))
+            .map(|obj| obj.into())
+    })
+}
+
+/// Load and evaluate Vega-Lite versions ahead of time, so that the first conversion that uses
+/// each version doesn't pay for its evaluation. Versions that were evaluated already are
+/// skipped
+///
+/// Args:
+///     versions (list of str): Vega-Lite versions (e.g. ["5.8", "5.21"])
+///
+/// Returns:
+///     None
+#[pyfunction]
+#[pyo3(signature = (versions))]
+fn warm_versions(versions: Vec<String>) -> PyResult<()> {
+    let vl_versions = versions
+        .iter()
+        .map(|version| parse_vl_version(Some(version)))
+        .collect::<PyResult<Vec<_>>>()?;
+    with_converter(|converter| PYTHON_RUNTIME.block_on(converter.warm_versions(&vl_versions)))
+        .map_err(|err| {
+            PyValueError::new_err(format!("Failed to warm Vega-Lite versions:\n{}", err))
+        })
+}
+
 /// Get the d3-format locale dict for a named locale
 ///
 /// See https://github.com/d3/d3-format/tree/main/locale for available names
@@ -1066,17 +5677,17 @@ fn get_themes() -> PyResult<PyObject> {
 #[pyo3(signature = (name))]
 fn get_format_locale(name: &str) -> PyResult<PyObject> {
     match FORMATE_LOCALE_MAP.get(name) {
-        None => {
-            Err(PyValueError::new_err(format!(
-                "Invalid format locale name: {name}\nSee https://github.com/d3/d3-format/tree/main/locale for available names"
-            )))
-        }
+        None => Err(PyValueError::new_err(format!(
+            "{}\nSee https://github.com/d3/d3-format/tree/main/locale for available names",
+            unknown_name_message("format locale", name, &format_locale_names())
+        ))),
         Some(locale) => {
-            let locale: serde_json::Value = serde_json::from_str(locale).expect(
-                "Failed to parse internal format locale as JSON"
-            );
+            let locale: serde_json::Value = serde_json::from_str(locale)
+                .expect("Failed to parse internal format locale as JSON");
             Python::with_gil(|py| -> PyResult<PyObject> {
-                pythonize(py, &locale).map_err(|err| PyValueError::new_err(err.to_string())).map(|obj| obj.into())
+                pythonize(py, &locale)
+                    .map_err(|err| PyValueError::new_err(err.to_string()))
+                    .map(|obj| obj.into())
             })
         }
     }
@@ -1095,17 +5706,17 @@ fn get_format_locale(name: &str) -> PyResult<PyObject> {
 #[pyo3(signature = (name))]
 fn get_time_format_locale(name: &str) -> PyResult<PyObject> {
     match TIME_FORMATE_LOCALE_MAP.get(name) {
-        None => {
-            Err(PyValueError::new_err(format!(
-                "Invalid time format locale name: {name}\nSee https://github.com/d3/d3-time-format/tree/main/locale for available names"
-            )))
-        }
+        None => Err(PyValueError::new_err(format!(
+            "{}\nSee https://github.com/d3/d3-time-format/tree/main/locale for available names",
+            unknown_name_message("time format locale", name, &time_format_locale_names())
+        ))),
         Some(locale) => {
-            let locale: serde_json::Value = serde_json::from_str(locale).expect(
-                "Failed to parse internal time format locale as JSON"
-            );
+            let locale: serde_json::Value = serde_json::from_str(locale)
+                .expect("Failed to parse internal time format locale as JSON");
             Python::with_gil(|py| -> PyResult<PyObject> {
-                pythonize(py, &locale).map_err(|err| PyValueError::new_err(err.to_string())).map(|obj| obj.into())
+                pythonize(py, &locale)
+                    .map_err(|err| PyValueError::new_err(err.to_string()))
+                    .map(|obj| obj.into())
             })
         }
     }
@@ -1126,27 +5737,101 @@ fn get_time_format_locale(name: &str) -> PyResult<PyObject> {
 ///     snippet (str): An ES6 JavaScript snippet which includes no imports
 ///     vl_version (str): Vega-Lite library version string (e.g. 'v5.15')
 ///         (default to latest)
+///     language (str | None): Language of the snippet, either "js" or "ts" (default "js").
+///         TypeScript snippets are transpiled to JavaScript
 /// Returns:
 ///     str: Bundled snippet with all dependencies
 #[pyfunction]
-#[pyo3(signature = (snippet=None, vl_version=None))]
-fn javascript_bundle(snippet: Option<String>, vl_version: Option<&str>) -> PyResult<String> {
-    let vl_version = if let Some(vl_version) = vl_version {
-        VlVersion::from_str(vl_version)?
+#[pyo3(signature = (snippet=None, vl_version=None, language=None))]
+fn javascript_bundle(
+    snippet: Option<String>,
+    vl_version: Option<&str>,
+    language: Option<&str>,
+) -> PyResult<String> {
+    let vl_version = parse_vl_version(vl_version)?;
+    let language = if let Some(language) = language {
+        SnippetLanguage::from_str(language)?
     } else {
         Default::default()
     };
 
     if let Some(snippet) = &snippet {
-        Ok(PYTHON_RUNTIME.block_on(bundle_vega_snippet(snippet, vl_version))?)
+        Ok(Python::with_gil(|py| {
+            py.allow_threads(|| {
+                PYTHON_RUNTIME.block_on(bundle_vega_snippet_with_language(
+                    snippet, vl_version, language,
+                ))
+            })
+        })?)
     } else {
-        let mut converter = VL_CONVERTER
-            .lock()
-            .expect("Failed to acquire lock on Vega-Lite converter");
-        Ok(PYTHON_RUNTIME.block_on(converter.get_vegaembed_bundle(vl_version))?)
+        Ok(with_converter(|converter| {
+            PYTHON_RUNTIME.block_on(converter.get_vegaembed_bundle(vl_version))
+        })?)
     }
 }
 
+/// Create a JavaScript bundle of the vendored Vega and Vega-Lite libraries, without
+/// vega-embed. The bundle assigns `vega` and `vegaLite` to the global window object
+///
+/// Args:
+///     vl_version (str | None): Vega-Lite library version string (e.g. 'v5.15')
+///         (default to latest)
+/// Returns:
+///     str: Bundled Vega and Vega-Lite libraries
+#[pyfunction]
+#[pyo3(signature = (vl_version=None))]
+fn get_vega_bundle(vl_version: Option<&str>) -> PyResult<String> {
+    let vl_version = parse_vl_version(vl_version)?;
+    Ok(Python::with_gil(|py| {
+        py.allow_threads(|| PYTHON_RUNTIME.block_on(get_vega_bundle_rs(vl_version)))
+    })?)
+}
+
+/// Create a JavaScript bundle of the vendored vega-embed, Vega, Vega-Lite, and
+/// lodash.debounce libraries, as inlined by the HTML conversion functions. The bundle assigns
+/// `vegaEmbed`, `vega`, `vegaLite`, and `lodashDebounce` to the global window object
+///
+/// Args:
+///     vl_version (str | None): Vega-Lite library version string (e.g. 'v5.15')
+///         (default to latest)
+/// Returns:
+///     str: Bundled vega-embed, Vega, Vega-Lite, and lodash.debounce libraries
+#[pyfunction]
+#[pyo3(signature = (vl_version=None))]
+fn get_vegaembed_bundle(vl_version: Option<&str>) -> PyResult<String> {
+    let vl_version = parse_vl_version(vl_version)?;
+    Ok(with_converter(|converter| {
+        PYTHON_RUNTIME.block_on(converter.get_vegaembed_bundle(vl_version))
+    })?)
+}
+
+/// Get the script tags that load the vendored Vega libraries into a custom HTML template
+///
+/// Args:
+///     vl_version (str | None): Vega-Lite library version string (e.g. 'v5.15')
+///         (default to latest)
+///     bundle (bool | None): Inline the vega-embed bundle, rather than loading the libraries
+///         from a CDN at the exact versions that are vendored (default false)
+/// Returns:
+///     dict: dict with a head key, holding the tags to place in the head element, and a body
+///         key, holding the tags to place at the end of the body element
+#[pyfunction]
+#[pyo3(signature = (vl_version=None, bundle=None))]
+fn get_script_tags(vl_version: Option<&str>, bundle: Option<bool>) -> PyResult<PyObject> {
+    let vl_version = parse_vl_version(vl_version)?;
+    let scripts = Python::with_gil(|py| {
+        py.allow_threads(|| {
+            PYTHON_RUNTIME.block_on(get_script_tags_rs(vl_version, bundle.unwrap_or(false)))
+        })
+    })?;
+    Python::with_gil(|py| -> PyResult<PyObject> {
+        let dict = PyDict::new_bound(py);
+        dict.set_item("head", scripts.head)?;
+        dict.set_item("body", scripts.body)?;
+        Ok(dict.into())
+    })
+}
+
 /// Get the bundled version of Vega
 ///
 /// Returns:
@@ -1190,33 +5875,78 @@ fn get_vegalite_versions() -> Vec<String> {
         .collect()
 }
 
+/// Directory of the locales of the vl_convert package, which ships them as package data
+/// rather than compiling them into the extension module
+fn package_locale_dir(py: Python) -> Option<PathBuf> {
+    // The package is partially initialized while its extension module is imported
+    let modules = py.import_bound("sys").ok()?.getattr("modules").ok()?;
+    let package = modules.get_item("vl_convert").ok()?;
+    let package_dirs: Vec<PathBuf> = package.getattr("__path__").ok()?.extract().ok()?;
+    Some(package_dirs.into_iter().next()?.join("locales"))
+}
+
 /// Convert Vega-Lite specifications to other formats
 #[pymodule]
-fn vl_convert(_py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
+fn vl_convert(py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
+    if let Some(locale_dir) = package_locale_dir(py) {
+        set_locale_dir(locale_dir);
+    }
     m.add_function(wrap_pyfunction!(vegalite_to_vega, m)?)?;
     m.add_function(wrap_pyfunction!(vegalite_to_svg, m)?)?;
     m.add_function(wrap_pyfunction!(vegalite_to_scenegraph, m)?)?;
+    m.add_function(wrap_pyfunction!(vegalite_get_text_items, m)?)?;
+    m.add_function(wrap_pyfunction!(get_final_vega_spec, m)?)?;
+    m.add_function(wrap_pyfunction!(get_final_vega_spec_vg, m)?)?;
+    m.add_function(wrap_pyfunction!(vegalite_freeze, m)?)?;
     m.add_function(wrap_pyfunction!(vegalite_to_png, m)?)?;
     m.add_function(wrap_pyfunction!(vegalite_to_jpeg, m)?)?;
     m.add_function(wrap_pyfunction!(vegalite_to_pdf, m)?)?;
+    m.add_function(wrap_pyfunction!(vegalite_export, m)?)?;
+    m.add_function(wrap_pyfunction!(convert_to_files, m)?)?;
     m.add_function(wrap_pyfunction!(vegalite_to_url, m)?)?;
     m.add_function(wrap_pyfunction!(vegalite_to_html, m)?)?;
+    m.add_function(wrap_pyfunction!(vegalite_to_html_report, m)?)?;
+    m.add_function(wrap_pyfunction!(vegalite_to_vega_and_svg, m)?)?;
+    m.add_function(wrap_pyfunction!(vegalite_to_vega_and_png, m)?)?;
+    m.add_function(wrap_pyfunction!(vegalite_facets_to_pngs, m)?)?;
     m.add_function(wrap_pyfunction!(vega_to_svg, m)?)?;
     m.add_function(wrap_pyfunction!(vega_to_scenegraph, m)?)?;
+    m.add_function(wrap_pyfunction!(vega_get_render_stats, m)?)?;
     m.add_function(wrap_pyfunction!(vega_to_png, m)?)?;
     m.add_function(wrap_pyfunction!(vega_to_jpeg, m)?)?;
     m.add_function(wrap_pyfunction!(vega_to_pdf, m)?)?;
     m.add_function(wrap_pyfunction!(vega_to_url, m)?)?;
     m.add_function(wrap_pyfunction!(vega_to_html, m)?)?;
+    m.add_function(wrap_pyfunction!(lint_vega, m)?)?;
+    m.add_function(wrap_pyfunction!(analyze_static_export, m)?)?;
+    m.add_function(wrap_pyfunction!(anonymize_spec, m)?)?;
+    m.add_function(wrap_pyfunction!(compare_svgs, m)?)?;
     m.add_function(wrap_pyfunction!(svg_to_png, m)?)?;
     m.add_function(wrap_pyfunction!(svg_to_jpeg, m)?)?;
     m.add_function(wrap_pyfunction!(svg_to_pdf, m)?)?;
     m.add_function(wrap_pyfunction!(register_font_directory, m)?)?;
+    m.add_function(wrap_pyfunction!(set_emoji_font, m)?)?;
+    m.add_function(wrap_pyfunction!(get_emoji_font, m)?)?;
+    m.add_function(wrap_pyfunction!(measure_text, m)?)?;
+    m.add_function(wrap_pyfunction!(set_spec_limits, m)?)?;
+    m.add_function(wrap_pyfunction!(set_collect_resource_stats, m)?)?;
+    m.add_function(wrap_pyfunction!(enable_logging, m)?)?;
+    m.add_function(wrap_pyfunction!(get_last_resource_stats, m)?)?;
+    m.add_function(wrap_pyfunction!(set_collect_dataset_summary, m)?)?;
+    m.add_function(wrap_pyfunction!(get_last_dataset_summary, m)?)?;
+    m.add_function(wrap_pyfunction!(set_ignore_exif_orientation, m)?)?;
     m.add_function(wrap_pyfunction!(get_local_tz, m)?)?;
     m.add_function(wrap_pyfunction!(get_themes, m)?)?;
+    m.add_function(wrap_pyfunction!(get_theme_names, m)?)?;
+    m.add_function(wrap_pyfunction!(get_themes_meta, m)?)?;
+    m.add_function(wrap_pyfunction!(health_check, m)?)?;
+    m.add_function(wrap_pyfunction!(warm_versions, m)?)?;
     m.add_function(wrap_pyfunction!(get_format_locale, m)?)?;
     m.add_function(wrap_pyfunction!(get_time_format_locale, m)?)?;
     m.add_function(wrap_pyfunction!(javascript_bundle, m)?)?;
+    m.add_function(wrap_pyfunction!(get_vega_bundle, m)?)?;
+    m.add_function(wrap_pyfunction!(get_vegaembed_bundle, m)?)?;
+    m.add_function(wrap_pyfunction!(get_script_tags, m)?)?;
     m.add_function(wrap_pyfunction!(get_vega_version, m)?)?;
     m.add_function(wrap_pyfunction!(get_vega_themes_version, m)?)?;
     m.add_function(wrap_pyfunction!(get_vega_embed_version, m)?)?;
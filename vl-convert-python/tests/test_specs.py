@@ -1,4 +1,8 @@
+import ast
+import base64
+import inspect
 import json
+import logging
 from pathlib import Path
 import vl_convert as vlc
 import pytest
@@ -7,8 +11,11 @@ from skimage.io import imread
 from skimage.metrics import structural_similarity as ssim
 import os
 import math
+import re
 import ctypes
 import sys
+import threading
+from http.server import BaseHTTPRequestHandler, ThreadingHTTPServer
 import pypdfium2.raw as pdfium_c
 from tempfile import NamedTemporaryFile
 import PIL.Image
@@ -162,6 +169,129 @@ def test_vegalite_to_html_bundle(name, vl_version):
     assert '"theme":"dark"' in html
 
 
+def test_library_bundles():
+    bundle = vlc.get_vega_bundle(vl_version="5.20")
+    assert vlc.get_vega_version() in bundle
+    assert "5.20.1" in bundle
+    assert vlc.get_vega_embed_version() not in bundle
+
+    bundle = vlc.get_vegaembed_bundle(vl_version="5.20")
+    assert vlc.get_vega_embed_version() in bundle
+    assert "window.lodashDebounce" in bundle
+
+
+def test_script_tags():
+    scripts = vlc.get_script_tags(vl_version="5.20")
+    assert f"cdn.jsdelivr.net/npm/vega@{vlc.get_vega_version()}" in scripts["head"]
+    assert "cdn.jsdelivr.net/npm/vega-lite@5.20.1" in scripts["head"]
+    assert "lodash.debounce@4.0.8" in scripts["body"]
+
+    scripts = vlc.get_script_tags(vl_version="5.20", bundle=True)
+    assert vlc.get_vega_embed_version() in scripts["head"]
+    assert scripts["body"] == ""
+
+
+def test_vegalite_to_html_show_warnings(capfd):
+    vl_spec = {
+        "data": {"values": [{"a": 1}]},
+        "mark": "bar",
+        "encoding": {
+            "x": {"field": "a", "type": "quantitative"},
+            "shape": {"field": "a", "type": "nominal"},
+        },
+    }
+    html = vlc.vegalite_to_html(vl_spec)
+    assert html.startswith("<!DOCTYPE html>")
+    assert "shape dropped" not in capfd.readouterr().err
+
+    html = vlc.vegalite_to_html(vl_spec, show_warnings=True)
+    assert html.startswith("<!DOCTYPE html>")
+    assert "shape dropped" in capfd.readouterr().err
+
+
+@pytest.mark.parametrize("scale", [None, 1.0])
+def test_html_static_fallback(scale):
+    vl_spec = load_vl_spec("circle_binned")
+    vg_spec = vlc.vegalite_to_vega(vl_spec)
+    expected_png = vlc.vegalite_to_png(vl_spec, scale=scale or 2.0)
+    expected_size = PIL.Image.open(BytesIO(expected_png)).size
+    for html in [
+        vlc.vegalite_to_html(
+            vl_spec, static_fallback=True, static_fallback_scale=scale
+        ),
+        vlc.vega_to_html(vg_spec, static_fallback=True, static_fallback_scale=scale),
+    ]:
+        assert "<noscript>" in html
+        data_uris = re.findall(r'src="data:image/png;base64,([^"]+)"', html)
+        # The image is the initial content of the chart div and is repeated in noscript
+        assert len(data_uris) == 2
+        png = base64.b64decode(data_uris[0])
+        img = PIL.Image.open(BytesIO(png))
+        assert img.format == "PNG"
+        assert img.size == expected_size
+
+    html = vlc.vegalite_to_html(vl_spec)
+    assert "<noscript>" not in html
+    assert "data:image/png" not in html
+
+
+def test_html_template():
+    vl_spec = {
+        "data": {"values": [{"a": "</script><script>alert(1)</script>"}]},
+        "mark": "text",
+        "encoding": {"text": {"field": "a"}},
+    }
+    template = (
+        "<head>{{ scripts }}<title>{{ title }}</title></head>"
+        '<div id="{{ element_id }}"></div>'
+        '<script>vegaEmbed("#{{ element_id }}", {{ spec }}, {{ embed_options }});</script>'
+        "<footer>{{ footer }}</footer>"
+    )
+    for html in [
+        vlc.vegalite_to_html(vl_spec, template=template),
+        vlc.vega_to_html(vlc.vegalite_to_vega(vl_spec), template=template),
+    ]:
+        assert html.startswith('<head><script src="https://cdn.jsdelivr.net/npm/vega@5">')
+        assert "<title>Chart</title>" in html
+        assert 'vegaEmbed("#vega-chart", {' in html
+        assert html.endswith("<footer>{{ footer }}</footer>")
+        # The spec can't end the script element that it's written in
+        assert html.count("</script>") == 4
+        assert "\\u003c/script\\u003e\\u003cscript\\u003ealert(1)" in html
+
+    with pytest.raises(RuntimeError, match="missing required placeholders"):
+        vlc.vegalite_to_html(vl_spec, template="<div>{{ spec }}</div>")
+
+
+def test_html_allowed_base_urls():
+    vl_spec = {
+        "data": {"url": "https://data.example.com/cars.json"},
+        "mark": "image",
+        "encoding": {"url": {"value": "https://cdn.example.com/logo.png"}},
+    }
+    vg_spec = vlc.vegalite_to_vega(vl_spec)
+    for to_html, spec in [(vlc.vegalite_to_html, vl_spec), (vlc.vega_to_html, vg_spec)]:
+        with pytest.raises(RuntimeError, match="https://cdn.example.com/logo.png"):
+            to_html(spec, allowed_base_urls=["https://data.example.com/"])
+
+        html = to_html(
+            spec, allowed_base_urls=["https://data.example.com/"], warn_only=True
+        )
+        assert "connect-src https://data.example.com;" in html
+
+        html = to_html(
+            spec,
+            allowed_base_urls=["https://data.example.com/", "https://cdn.example.com/"],
+        )
+        assert (
+            '<meta http-equiv="Content-Security-Policy" content="'
+            "connect-src https://data.example.com https://cdn.example.com; "
+            'img-src https://data.example.com https://cdn.example.com data:">'
+        ) in html
+
+        assert "Content-Security-Policy" not in to_html(spec)
+
+
 @pytest.mark.parametrize("name", ["circle_binned", "stacked_bar_h"])
 @pytest.mark.parametrize("as_dict", [False, True])
 def test_svg(name, as_dict):
@@ -265,6 +395,28 @@ def test_jpeg(name, scale, as_dict):
     assert jpeg[:10] == jpeg_prefix
 
 
+@pytest.mark.parametrize(
+    "kwargs,message",
+    [
+        (dict(scale=0), "Image scale must be a finite number"),
+        (dict(scale=float("nan")), "Image scale must be a finite number"),
+        (dict(ppi=-72), "Image ppi must be a finite number"),
+        (dict(scale=1e-9), "produces an image of 0x0 pixels"),
+    ],
+)
+def test_invalid_png_size(kwargs, message):
+    svg = vlc.vegalite_to_svg(load_vl_spec("circle_binned"), vl_version="v5_8")
+    with pytest.raises(RuntimeError, match=message):
+        vlc.svg_to_png(svg, **kwargs)
+
+
+@pytest.mark.parametrize("quality", [0, 101])
+def test_invalid_jpeg_quality(quality):
+    svg = vlc.vegalite_to_svg(load_vl_spec("circle_binned"), vl_version="v5_8")
+    with pytest.raises(RuntimeError, match="must be between 1 and 100"):
+        vlc.svg_to_jpeg(svg, quality=quality)
+
+
 @pytest.mark.skipif(
     sys.platform.startswith("win"), reason="PDF tests not supported on windows"
 )
@@ -277,6 +429,7 @@ def test_jpeg(name, scale, as_dict):
         ("maptile_background", 0.97),
         ("no_text_in_font_metrics", 0.94),
         ("lookup_urls", 0.99),
+        ("text_halo", 0.97),
     ],
 )
 @pytest.mark.parametrize("as_dict", [False])
@@ -289,15 +442,16 @@ def test_pdf(name, tol, as_dict):
 
     expected_png = vlc.vegalite_to_png(vl_spec, vl_version=vl_version)
 
-    # Convert to vega first
+    # Convert to vega first. Pages are measured at 72 dpi, so that pdfium renders them at
+    # the size of the PNG image
     vg_spec = vlc.vegalite_to_vega(vl_spec, vl_version=vl_version)
-    pdf = vlc.vega_to_pdf(vg_spec)
+    pdf = vlc.vega_to_pdf(vg_spec, source_dpi=72)
     png = pdf_to_png(pdf)
     # Lower tolerance because pdfium does its own text rendering, which won't be pixel identical to resvg
     check_png(png, expected_png, tol=tol)
 
     # Convert directly to image
-    pdf = vlc.vegalite_to_pdf(vl_spec, vl_version=vl_version)
+    pdf = vlc.vegalite_to_pdf(vl_spec, vl_version=vl_version, source_dpi=72)
     png = pdf_to_png(pdf)
     check_png(png, expected_png, tol=tol)
 
@@ -338,6 +492,33 @@ def test_locale():
     check_png(png, expected_png)
 
 
+def test_package_locales():
+    # Locales are read from the locales directory of the package, rather than compiled
+    # into the extension module
+    package_locale_dir = Path(vlc.__file__).parent / "locales"
+    for kind, get_locale in [
+        ("format", vlc.get_format_locale),
+        ("time-format", vlc.get_time_format_locale),
+    ]:
+        locale_path = package_locale_dir / kind / "it-IT.json"
+        assert get_locale("it-IT") == json.loads(locale_path.read_text(encoding="utf8"))
+
+    with pytest.raises(ValueError, match="Unknown format locale 'xx-XX'"):
+        vlc.get_format_locale("xx-XX")
+
+
+def test_legacy_vl_versions():
+    # Vega-Lite versions older than 5.14 are only included in builds with the vl_legacy
+    # feature
+    if "5.8" in vlc.get_vegalite_versions():
+        vlc.vegalite_to_vega({"mark": "point"}, vl_version="5.8")
+    else:
+        message = "This build does not include v5.8; install vl-convert-python-full"
+        with pytest.raises(ValueError, match=message):
+            vlc.vegalite_to_vega({"mark": "point"}, vl_version="5.8")
+    assert "5.14" in vlc.get_vegalite_versions()
+
+
 def test_gh_78():
     vl_version = "v5_8"
     name = "lookup_urls"
@@ -351,6 +532,1362 @@ def test_gh_78():
     check_png(png, expected_png)
 
 
+def test_enable_logging(caplog):
+    vlc.enable_logging("warn")
+    vl_spec = {
+        "data": {"values": [{"a": 1}]},
+        "mark": "bar",
+        "encoding": {
+            "x": {"field": "a", "type": "quantitative"},
+            "shape": {"field": "a", "type": "nominal"},
+        },
+    }
+    with caplog.at_level(logging.WARNING, logger="vl_convert"):
+        vlc.vegalite_to_vega(vl_spec)
+
+    messages = [r.getMessage() for r in caplog.records if r.name == "vl_convert"]
+    assert 'shape dropped as it is incompatible with "bar".' in messages
+
+
+def test_svg_native_tooltips():
+    vl_spec = {
+        "data": {"values": [{"a": "A", "b": 28}, {"a": "B", "b": 55}]},
+        "mark": "bar",
+        "encoding": {
+            "x": {"field": "a", "type": "nominal"},
+            "y": {"field": "b", "type": "quantitative"},
+            "tooltip": [
+                {"field": "a", "type": "nominal"},
+                {"field": "b", "type": "quantitative"},
+            ],
+        },
+    }
+    svg = vlc.vegalite_to_svg(vl_spec, svg_native_tooltips=True)
+    assert re.findall(r"<title>([^<]*)</title>", svg) == ["a: A\nb: 28", "a: B\nb: 55"]
+    assert "<title>" not in vlc.vegalite_to_svg(vl_spec)
+
+
+def test_svg_minify():
+    vl_spec = load_vl_spec("stacked_bar_h")
+    svg = vlc.vegalite_to_svg(vl_spec)
+    minified = vlc.vegalite_to_svg(vl_spec, svg_minify=True, svg_precision=2)
+    assert len(minified) < len(svg)
+    assert "aria-" not in minified
+    assert re.search(r"\d\.\d{3}", minified) is None
+
+
+def test_vegalite_to_html_report():
+    specs = [
+        ("Binned <circles>", load_vl_spec("circle_binned")),
+        (None, load_vl_spec("stacked_bar_h")),
+    ]
+    html = vlc.vegalite_to_html_report(specs)
+    assert html.startswith("<!DOCTYPE html>")
+    assert html.count("cdn.jsdelivr.net/npm/vega-embed@6") == 1
+    assert html.count("<h2>") == 1
+    assert "<h2>Binned &lt;circles&gt;</h2>" in html
+    assert 'id="vega-chart-0"' in html
+    assert 'id="vega-chart-1"' in html
+
+
+def test_preserve_int64():
+    ids = [2**53 - 1, 2**53, 2**53 + 1, -(2**53 + 1)]
+    vl_spec = {
+        "data": {"values": [{"id": i, "b": b} for b, i in enumerate(ids)]},
+        "mark": "point",
+        "encoding": {"y": {"field": "b", "type": "quantitative"}},
+    }
+    vg_spec = vlc.vegalite_to_vega(vl_spec, preserve_int64=True)
+    values = next(d["values"] for d in vg_spec["data"] if "values" in d)
+    assert [v["id"] for v in values] == ids
+
+    frozen = vlc.vegalite_freeze(vl_spec, preserve_int64=True)
+    assert frozen["data"] == vl_spec["data"]
+
+
+def test_dataset_summary():
+    vl_spec = {
+        "data": {"values": [{"a": "A", "b": 28}, {"a": "B", "b": 55}]},
+        "transform": [{"filter": "datum.b > 100"}],
+        "mark": "bar",
+        "encoding": {
+            "x": {"field": "a", "type": "nominal"},
+            "y": {"field": "b", "type": "quantitative"},
+        },
+    }
+    vlc.set_collect_dataset_summary(True)
+    try:
+        vlc.vegalite_to_svg(vl_spec)
+        summary = vlc.get_last_dataset_summary()
+    finally:
+        vlc.set_collect_dataset_summary(False)
+
+    assert summary["any_marks_rendered"] is False
+    assert summary["datasets"] == [
+        {"name": "source_0", "rows": 2, "fields": ["a", "b"], "feeds_mark": False},
+        {"name": "data_0", "rows": 0, "fields": [], "feeds_mark": True},
+    ]
+
+
+@pytest.mark.parametrize(
+    "ppi_mode,size",
+    [(None, (155, 96)), ("resize-pixels", (155, 96)), ("metadata-only", (37, 23))],
+)
+def test_ppi_mode(ppi_mode, size):
+    svg = (
+        '<svg xmlns="http://www.w3.org/2000/svg" width="37" height="23">'
+        '<rect width="37" height="23" fill="red"/></svg>'
+    )
+    png = vlc.svg_to_png(svg, ppi=300, ppi_mode=ppi_mode)
+    img = PIL.Image.open(BytesIO(png))
+    assert img.size == size
+    assert img.info["dpi"] == pytest.approx((300, 300), abs=0.01)
+
+    with pytest.raises(ValueError, match="Unsupported ppi mode"):
+        vlc.svg_to_png(svg, ppi=300, ppi_mode="resize")
+
+
+CHECKERBOARD_PNG = (
+    "iVBORw0KGgoAAAANSUhEUgAAAAQAAAAECAIAAAAmkwkpAAAAFklEQVR42mNgYGD4//8/lESwIA"
+    "C7DABt4hfpR7+BjAAAAABJRU5ErkJggg=="
+)
+
+
+@pytest.mark.parametrize(
+    "image_rendering,attr,pixelated",
+    [
+        (None, "", False),
+        ("smooth", "", False),
+        ("pixelated", "", True),
+        ("smooth", 'image-rendering="pixelated"', True),
+    ],
+)
+def test_image_rendering(image_rendering, attr, pixelated):
+    # A 4x4 checkerboard drawn at 64x64
+    svg = (
+        '<svg xmlns="http://www.w3.org/2000/svg" width="64" height="64">'
+        f'<image width="64" height="64" {attr} '
+        f'href="data:image/png;base64,{CHECKERBOARD_PNG}"/></svg>'
+    )
+    png = vlc.svg_to_png(svg, image_rendering=image_rendering)
+    img = PIL.Image.open(BytesIO(png)).convert("L")
+    expected = [
+        0 if (x // 16 + y // 16) % 2 == 0 else 255 for y in range(64) for x in range(64)
+    ]
+    assert (list(img.getdata()) == expected) == pixelated
+
+    with pytest.raises(ValueError, match="Unsupported image rendering"):
+        vlc.svg_to_png(svg, image_rendering="nearest")
+
+
+def test_jpeg_png_background():
+    vl_spec = {
+        "data": {"values": [{"a": "A", "b": 28}, {"a": "B", "b": 55}]},
+        "mark": "bar",
+        "background": None,
+        "encoding": {
+            "x": {"field": "a", "type": "nominal"},
+            "y": {"field": "b", "type": "quantitative"},
+        },
+    }
+
+    def corner(image_data, mode="RGB"):
+        return PIL.Image.open(BytesIO(image_data)).convert(mode).getpixel((0, 0))
+
+    # The transparent corner of the chart is composited over white by default
+    jpeg = vlc.vegalite_to_jpeg(vl_spec, quality=100)
+    assert corner(jpeg) == pytest.approx((255, 255, 255), abs=3)
+    jpeg = vlc.vegalite_to_jpeg(vl_spec, quality=100, jpeg_background="#ff0000")
+    assert corner(jpeg) == pytest.approx((255, 0, 0), abs=3)
+
+    # The chart's own background covers the JPEG background
+    blue_spec = {**vl_spec, "background": "blue"}
+    jpeg = vlc.vegalite_to_jpeg(blue_spec, quality=100, jpeg_background="#ff0000")
+    assert corner(jpeg) == pytest.approx((0, 0, 255), abs=3)
+
+    png = vlc.vegalite_to_png(vl_spec)
+    assert corner(png, "RGBA")[3] == 0
+    png = vlc.vegalite_to_png(vl_spec, flatten_background="rgb(0, 128, 0)")
+    assert corner(png, "RGBA") == (0, 128, 0, 255)
+
+    with pytest.raises(ValueError, match="Invalid background color"):
+        vlc.vegalite_to_jpeg(vl_spec, jpeg_background="whiteish")
+
+
+def test_force_iterations_random_seed():
+    # Nodes start at random positions, and are laid out by a force simulation
+    vg_spec = {
+        "$schema": "https://vega.github.io/schema/vega/v5.json",
+        "width": 200,
+        "height": 200,
+        "data": [
+            {
+                "name": "nodes",
+                "values": [{"id": i} for i in range(12)],
+                "transform": [
+                    {"type": "formula", "as": "x0", "expr": "random() * width"},
+                    {"type": "formula", "as": "y0", "expr": "random() * height"},
+                ],
+            }
+        ],
+        "marks": [
+            {
+                "type": "symbol",
+                "from": {"data": "nodes"},
+                "encode": {"enter": {"x": {"field": "x0"}, "y": {"field": "y0"}}},
+                "transform": [
+                    {
+                        "type": "force",
+                        "forces": [
+                            {"force": "center", "x": 100, "y": 100},
+                            {"force": "collide", "radius": 8},
+                            {"force": "nbody", "strength": -20},
+                        ],
+                    }
+                ],
+            }
+        ],
+    }
+    svg = vlc.vega_to_svg(vg_spec, force_iterations=300, random_seed=42)
+    assert vlc.vega_to_svg(vg_spec, force_iterations=300, random_seed=42) == svg
+    assert vlc.vega_to_svg(vg_spec, force_iterations=300, random_seed=7) != svg
+
+
+def test_pdfa():
+    vl_version = "v5_8"
+    vl_spec = load_vl_spec("circle_binned")
+    expected_png = vlc.vegalite_to_png(vl_spec, vl_version=vl_version)
+
+    pdf = vlc.vegalite_to_pdf(
+        vl_spec, vl_version=vl_version, pdfa=True, source_dpi=72
+    )
+    assert b"<pdfaid:part>2</pdfaid:part>" in pdf
+    assert b"<pdfaid:conformance>B</pdfaid:conformance>" in pdf
+    assert b"/S /GTS_PDFA1" in pdf
+    assert b"/ID [" in pdf
+    check_png(pdf_to_png(pdf), expected_png, tol=0.97)
+
+    # Regular PDFs don't claim PDF/A conformance
+    assert b"pdfaid" not in vlc.vegalite_to_pdf(vl_spec, vl_version=vl_version)
+
+
+@pytest.mark.parametrize("pdfa", [False, True])
+def test_pdf_source_dpi(pdfa):
+    svg = (
+        '<svg xmlns="http://www.w3.org/2000/svg" width="960" height="480">'
+        '<rect x="10" y="10" width="100" height="50" fill="steelblue"/></svg>'
+    )
+    # Pixels are 1/96 of an inch by default, like CSS pixels
+    assert b"/MediaBox [0 0 720 360]" in vlc.svg_to_pdf(svg, pdfa=pdfa)
+    pdf = vlc.svg_to_pdf(svg, pdfa=pdfa, source_dpi=72)
+    assert b"/MediaBox [0 0 960 480]" in pdf
+
+
+def test_resize_mode():
+    vl_spec = {
+        "data": {
+            "values": [
+                {"x": i, "y": i * i, "category": f"A category with a long label {i}"}
+                for i in range(12)
+            ]
+        },
+        "mark": "point",
+        "width": 400,
+        "height": 200,
+        "encoding": {
+            "x": {"field": "x", "type": "quantitative"},
+            "y": {"field": "y", "type": "quantitative"},
+            "color": {"field": "category", "type": "nominal"},
+        },
+    }
+    # The axes and legend are laid out inside of the width
+    info = vlc.vegalite_to_svg(vl_spec, width=300, height=150, return_info=True)
+    assert (info["width"], info["height"]) == (300, 150)
+
+    # The plot area is 300px wide, with the axes and legend around it
+    info = vlc.vegalite_to_svg(vl_spec, width=300, resize_mode="pad", return_info=True)
+    assert info["width"] > 300
+
+    with pytest.raises(ValueError, match="Unsupported resize mode: stretch"):
+        vlc.vegalite_to_svg(vl_spec, width=300, resize_mode="stretch")
+
+
+def test_zero_size_chart():
+    vg_spec = {
+        "width": 0,
+        "height": 100,
+        "padding": 0,
+        "autosize": "none",
+        "marks": [],
+    }
+    for func in [vlc.vega_to_svg, vlc.vega_to_png, vlc.vega_to_jpeg, vlc.vega_to_pdf]:
+        with pytest.raises(ValueError, match="Chart resolved to 0×100"):
+            func(vg_spec)
+
+    # With min_size, the chart is enlarged instead
+    info = vlc.vega_to_svg(vg_spec, min_size=(40, 30), return_info=True)
+    assert (info["width"], info["height"]) == (40, 100)
+    info = vlc.vega_to_png(vg_spec, min_size=(40, 30), return_info=True)
+    assert (info["width_px"], info["height_px"]) == (40, 100)
+    assert vlc.vega_to_pdf(vg_spec, min_size=(40, 30)).startswith(b"%PDF")
+
+
+def test_row_limit():
+    # Chart of a 10k-row CSV that draws its number of rows and largest row number
+    csv = "i\n" + "".join(f"{i}\n" for i in range(10_000))
+    vl_spec = {
+        "data": {"values": csv, "format": {"type": "csv", "parse": {"i": "number"}}},
+        "transform": [
+            {
+                "aggregate": [
+                    {"op": "count", "as": "n"},
+                    {"op": "max", "field": "i", "as": "m"},
+                ]
+            },
+            {"calculate": "datum.n + ':' + datum.m", "as": "label"},
+        ],
+        "mark": "text",
+        "encoding": {"text": {"field": "label", "type": "nominal"}},
+    }
+    with pytest.raises(ValueError, match="10000 rows, more than max_rows \\(1000\\)"):
+        vlc.vegalite_to_svg(vl_spec, max_rows=1000)
+
+    svg = vlc.vegalite_to_svg(
+        vl_spec, max_rows=1000, row_limit_strategy="head", return_info=True
+    )
+    assert ">1000:999<" in svg["data"]
+    assert [warning["message"] for warning in svg["warnings"]] == [
+        "A dataset with 10000 rows was truncated to 1000 rows (first rows)"
+    ]
+
+    # The sample is drawn from all of the rows, and is the same for each conversion
+    svg = vlc.vegalite_to_svg(vl_spec, max_rows=1000, row_limit_strategy="sample:42")
+    assert ">1000:" in svg and ">1000:999<" not in svg
+    assert svg == vlc.vegalite_to_svg(
+        vl_spec, max_rows=1000, row_limit_strategy="sample:42"
+    )
+
+    svg = vlc.vegalite_to_svg(
+        vl_spec, max_rows=1000, row_limit_strategy="head", annotate_truncation=True
+    )
+    assert "Data truncated to 1000 rows" in svg
+
+    with pytest.raises(ValueError, match="Unsupported row limit strategy: tail"):
+        vlc.vegalite_to_svg(vl_spec, max_rows=1000, row_limit_strategy="tail")
+
+
+def test_log_level_warning_filters():
+    # Vega warns that the extents of the empty dataset are infinite
+    vg_spec = {
+        "width": 100,
+        "height": 100,
+        "data": [
+            {
+                "name": "table",
+                "values": [],
+                "transform": [
+                    {"type": "extent", "field": "v", "signal": "v_extent"},
+                    {"type": "extent", "field": "w", "signal": "w_extent"},
+                ],
+            }
+        ],
+        "marks": [{"type": "rect", "encode": {"enter": {"width": {"value": 100}}}}],
+    }
+
+    svg = vlc.vega_to_svg(vg_spec, log_level="warn", return_info=True)
+    messages = [w["message"] for w in svg["warnings"]]
+    assert messages == [
+        'Infinite extent for field "v": [Infinity, -Infinity]',
+        'Infinite extent for field "w": [Infinity, -Infinity]',
+    ]
+
+    svg = vlc.vega_to_svg(
+        vg_spec, log_level="warn", warning_filters=["Infinite extent"], return_info=True
+    )
+    assert svg["warnings"] == [
+        {"mark": None, "message": 'Suppressed 2 warnings matching "Infinite extent"'}
+    ]
+
+    with pytest.raises(ValueError, match="Unsupported log level: verbose"):
+        vlc.vega_to_svg(vg_spec, log_level="verbose")
+
+
+def test_convert_to_files(tmp_path):
+    vl_spec = load_vl_spec("circle_binned")
+    jobs = [
+        {"spec": vl_spec, "output": str(tmp_path / "chart.png")},
+        {"spec": vl_spec, "output": str(tmp_path / "chart@2x.png"), "scale": 2},
+        {"spec": vl_spec, "output": str(tmp_path / "chart.svg"), "format": "svg"},
+        {"spec": "{not json", "output": str(tmp_path / "invalid.png")},
+        {"spec": vl_spec, "output": str(tmp_path / "zero.png"), "scale": 0},
+        {"output": str(tmp_path / "no_spec.png")},
+    ]
+    results = vlc.convert_to_files(jobs, format="png", ppi=144)
+    assert [result["output"] for result in results] == [job["output"] for job in jobs]
+    oks = [result["ok"] for result in results]
+    assert oks == [True, True, True, False, False, False]
+    assert "Job has no spec" in results[5]["error"]
+
+    # The shared ppi applies to every job, and the scale of a job overrides the default
+    png = (tmp_path / "chart.png").read_bytes()
+    png_2x = (tmp_path / "chart@2x.png").read_bytes()
+    assert png == vlc.vegalite_to_png(vl_spec, ppi=144)
+    assert png_2x == vlc.vegalite_to_png(vl_spec, scale=2, ppi=144)
+    assert (tmp_path / "chart.svg").read_text().startswith("<svg")
+
+    # Failed jobs don't leave files behind
+    for job in jobs[3:]:
+        assert not os.path.exists(job["output"])
+
+    with pytest.raises(ValueError, match="Unsupported convert_to_files option"):
+        vlc.convert_to_files(jobs, scael=2)
+
+
+def test_convert_to_files_request_hash(tmp_path):
+    vl_spec = load_vl_spec("circle_binned")
+    output = str(tmp_path / "chart.png")
+    [result] = vlc.convert_to_files([{"spec": vl_spec, "output": output}])
+    assert result["ok"] and not result["unchanged"]
+    request_hash = result["request_hash"]
+    assert len(request_hash) == 64
+
+    # Key order, the output path, and unset options don't change the hash
+    reordered = json.dumps(dict(reversed(list(json.loads(vl_spec).items()))))
+    jobs = [
+        {"spec": reordered, "output": str(tmp_path / "other.png"), "scale": None},
+        {"spec": vl_spec, "output": output, "scale": 2},
+        {"spec": vl_spec, "output": output, "format": "svg"},
+    ]
+    hashes = [result["request_hash"] for result in vlc.convert_to_files(jobs)]
+    assert hashes[0] == request_hash
+    assert request_hash not in hashes[1:]
+
+    # Jobs whose if_none_match_hash equals their hash aren't converted
+    os.remove(output)
+    job = {"spec": vl_spec, "output": output, "if_none_match_hash": request_hash}
+    [result] = vlc.convert_to_files([job])
+    assert result["ok"] and result["unchanged"]
+    assert not os.path.exists(output)
+
+    [result] = vlc.convert_to_files([{**job, "if_none_match_hash": "stale"}])
+    assert result["ok"] and not result["unchanged"]
+    assert os.path.exists(output)
+
+
+def test_return_info_request_hash():
+    vl_spec = load_vl_spec("circle_binned")
+    png_hash = vlc.vegalite_to_png(vl_spec, scale=2, return_info=True)["request_hash"]
+    assert len(png_hash) == 64
+
+    # Key order doesn't change the hash, but options and the output format do
+    reordered = json.dumps(dict(reversed(list(json.loads(vl_spec).items()))))
+    info = vlc.vegalite_to_png(reordered, scale=2.0, return_info=True)
+    assert info["request_hash"] == png_hash
+    info = vlc.vegalite_to_png(vl_spec, scale=1, return_info=True)
+    assert info["request_hash"] != png_hash
+    info = vlc.vegalite_to_jpeg(vl_spec, scale=2, return_info=True)
+    assert info["request_hash"] not in (png_hash, None)
+    info = vlc.vegalite_to_svg(vl_spec, return_info=True)
+    assert info["request_hash"] not in (png_hash, None)
+
+
+def test_lint_vega():
+    vg_spec = {
+        "data": [{"name": "table", "values": [{"a": 1}]}],
+        "scales": [{"name": "xscale", "domain": {"data": "table", "field": "b"}}],
+        "marks": [
+            {
+                "type": "rect",
+                "from": {"data": "table"},
+                "encode": {"update": {"x": {"scale": "x_scale", "field": "a"}}},
+            }
+        ],
+    }
+    assert vlc.lint_vega(vg_spec) == [
+        {
+            "severity": "error",
+            "code": "missing-field",
+            "message": 'Field "b" is not in the inline rows of dataset "table"',
+            "path": "/scales/0/domain/field",
+        },
+        {
+            "severity": "error",
+            "code": "undefined-scale",
+            "message": 'Scale "x_scale" is not defined',
+            "path": "/marks/0/encode/update/x/scale",
+        },
+    ]
+    assert vlc.lint_vega(json.dumps({"marks": []})) == []
+
+
+def test_analyze_static_export():
+    vl_spec = {
+        "data": {"values": [{"a": "A", "b": 28}, {"a": "B", "b": 55}]},
+        "mark": "bar",
+        "encoding": {
+            "x": {"field": "a", "type": "nominal"},
+            "y": {"field": "b", "type": "quantitative"},
+        },
+    }
+    assert vlc.analyze_static_export(vl_spec) == []
+
+    # Tooltips turned on by the config are found in the compiled spec
+    findings = vlc.analyze_static_export(vl_spec, config={"mark": {"tooltip": True}})
+    assert [(f["feature"], f["spec_path"]) for f in findings] == [("tooltip", "")]
+
+    brush_spec = {
+        **vl_spec,
+        "params": [{"name": "brush", "select": "interval"}],
+        "mark": {"type": "bar", "tooltip": True},
+    }
+    assert vlc.analyze_static_export(json.dumps(brush_spec)) == [
+        {
+            "feature": "selection",
+            "severity": "unsupported",
+            "message": 'Selection "brush" has no effect in static output',
+            "spec_path": "/params/0",
+        },
+        {
+            "feature": "tooltip",
+            "severity": "unsupported",
+            "message": "Tooltips aren't shown in static output. SVG images can include "
+            "them as native tooltips instead",
+            "spec_path": "/mark/tooltip",
+        },
+    ]
+
+
+def test_svg_post_processor():
+    vl_spec = {
+        "data": {"values": [{"a": "A", "b": 28}, {"a": "B", "b": 55}]},
+        "mark": "bar",
+        "encoding": {
+            "x": {"field": "a", "type": "nominal"},
+            "y": {"field": "b", "type": "quantitative"},
+        },
+    }
+
+    def red_corner(svg):
+        corner = '<rect id="corner" width="10" height="10" fill="#ff0000"/>'
+        return svg.replace("</svg>", corner + "</svg>")
+
+    svg = vlc.vegalite_to_svg(vl_spec, svg_post_processor=red_corner)
+    assert '<rect id="corner"' in svg
+
+    # Rasterized formats are rendered from the post-processed SVG
+    png_data = vlc.vegalite_to_png(vl_spec, svg_post_processor=red_corner)
+    img = PIL.Image.open(BytesIO(png_data)).convert("RGBA")
+    assert img.getpixel((0, 0)) == (255, 0, 0, 255)
+
+    def fail(svg):
+        raise RuntimeError("watermark not found")
+
+    with pytest.raises(ValueError, match="watermark not found"):
+        vlc.vegalite_to_jpeg(vl_spec, svg_post_processor=fail)
+
+    with pytest.raises(ValueError, match="svg_post_processor must be a callable"):
+        vlc.vega_to_svg({}, svg_post_processor="not callable")
+
+
+def test_suppress_components():
+    vl_spec = {
+        "title": "Fruit",
+        "data": {"values": [{"a": "A", "b": 28}, {"a": "B", "b": 55}]},
+        "mark": "bar",
+        "encoding": {
+            "x": {"field": "a", "type": "nominal"},
+            "y": {"field": "b", "type": "quantitative"},
+            "color": {"field": "a", "type": "nominal"},
+        },
+    }
+    svg = vlc.vegalite_to_svg(vl_spec)
+    assert "role-legend" in svg
+    assert "role-title" in svg
+
+    svg = vlc.vegalite_to_svg(vl_spec, suppress_components=["legend", "title"])
+    assert "role-legend" not in svg
+    assert "role-title" not in svg
+    assert "role-axis" in svg
+
+    # Vega specs have the components removed before they're rendered
+    vg_spec = vlc.vegalite_to_vega(vl_spec)
+    svg = vlc.vega_to_svg(vg_spec, suppress_components=["axis-x", "axis-y"])
+    assert "role-axis" not in svg
+    assert "role-legend" in svg
+
+    with pytest.raises(ValueError, match="Unsupported component: axes"):
+        vlc.vegalite_to_png(vl_spec, suppress_components=["axes"])
+
+
+def test_browser_shims():
+    vl_spec = {
+        "data": {"values": [{}]},
+        "transform": [{"calculate": "navigator.language", "as": "language"}],
+        "mark": "text",
+        "encoding": {"text": {"field": "language", "type": "nominal"}},
+    }
+    assert "en-US" in vlc.vegalite_to_svg(vl_spec)
+    assert "de-DE" in vlc.vegalite_to_svg(vl_spec, format_locale="de-DE")
+
+    with pytest.raises(ValueError, match='reads the browser API "navigator"'):
+        vlc.vegalite_to_svg(vl_spec, browser_shims=False)
+
+
+def test_simplify_topojson():
+    vl_spec = load_vl_spec("topojson_regions")
+    svg = vlc.vegalite_to_svg(vl_spec)
+    simplified = vlc.vegalite_to_svg(vl_spec, simplify_topojson=1.0, return_info=True)
+    assert simplified["data"].count('aria-roledescription="geoshape"') == 3
+    assert len(simplified["data"]) < len(svg)
+    assert simplified["warnings"] == [
+        {
+            "mark": None,
+            "message": "Simplified the arcs of a TopoJSON dataset from 126 to 18 "
+            "vertices (85.7% fewer)",
+        }
+    ]
+
+    with pytest.raises(ValueError, match="non-negative"):
+        vlc.vegalite_to_svg(vl_spec, simplify_topojson=-1.0)
+
+
+@pytest.mark.parametrize(
+    "dates",
+    [
+        ["03/28/2024 9:15 AM", "03/27/2024 5:00 PM", "04/02/2024 11:30 PM"],
+        ["27.03.2024 17:00", "02.04.2024 23:30", "28.03.2024 09:15"],
+    ],
+)
+def test_lenient_dates(dates):
+    # Draws the extent of the dates that are parsed from the strings
+    extent = (
+        "timeFormat(datum.lo, '%Y-%m-%d %H:%M') + ' to ' + "
+        "timeFormat(datum.hi, '%Y-%m-%d %H:%M')"
+    )
+    vl_spec = {
+        "data": {
+            "values": [{"date": date} for date in dates],
+            "format": {"parse": {"date": "date"}},
+        },
+        "transform": [
+            {
+                "aggregate": [
+                    {"op": "min", "field": "date", "as": "lo"},
+                    {"op": "max", "field": "date", "as": "hi"},
+                ]
+            },
+            {"calculate": extent, "as": "label"},
+        ],
+        "mark": "text",
+        "encoding": {"text": {"field": "label", "type": "nominal"}},
+    }
+    svg = vlc.vegalite_to_svg(vl_spec, lenient_dates=True, return_info=True)
+    assert "2024-03-27 17:00 to 2024-04-02 23:30" in svg["data"]
+    assert svg["warnings"] == [
+        {
+            "mark": None,
+            "message": 'Rewrote the non-ISO 8601 dates of fields "date" to ISO 8601',
+        }
+    ]
+
+
+@pytest.mark.parametrize("n", [1, 7])
+def test_vega_get_render_stats(n):
+    vl_spec = {
+        "data": {"values": [{"a": str(i), "b": i + 1} for i in range(n)]},
+        "mark": "bar",
+        "encoding": {
+            "x": {"field": "a", "type": "nominal"},
+            "y": {"field": "b", "type": "quantitative"},
+        },
+    }
+    vg_spec = vlc.vegalite_to_vega(vl_spec)
+    stats = vlc.vega_get_render_stats(vg_spec)
+    assert stats["item_counts_by_marktype"]["rect"] == n
+    assert stats["group_count"] >= 1
+    assert stats["text_item_count"] > 0
+    assert stats["total_scenegraph_nodes"] > n
+
+    # Renders of the same spec have the same stats
+    assert vlc.vega_get_render_stats(vg_spec) == stats
+
+
+def test_vegalite_facets_to_pngs():
+    vl_spec = {
+        "data": {
+            "values": [
+                {"row": row, "column": column, "x": i, "y": i * 2}
+                for row in ["r1", "r2"]
+                for i, column in enumerate(["c1", "c2", "c3"])
+            ]
+        },
+        "mark": "point",
+        "width": 100,
+        "height": 80,
+        "encoding": {
+            "row": {"field": "row", "type": "nominal"},
+            "column": {"field": "column", "type": "nominal"},
+            "x": {"field": "x", "type": "quantitative"},
+            "y": {"field": "y", "type": "quantitative"},
+        },
+    }
+    images = vlc.vegalite_facets_to_pngs(vl_spec, scale=2)
+    assert [(image["row_value"], image["column_value"]) for image in images] == [
+        (row, column) for row in ["r1", "r2"] for column in ["c1", "c2", "c3"]
+    ]
+
+    # Every cell is cropped to the scaled size of the cell
+    for image in images:
+        assert imread(BytesIO(image["data"])).shape[:2] == (160, 200)
+
+    # With guides, the first cell includes the row header and y axis on its left and the
+    # column header above it
+    images = vlc.vegalite_facets_to_pngs(vl_spec, scale=2, include_guides=True)
+    height, width = imread(BytesIO(images[0]["data"])).shape[:2]
+    assert height > 160 and width > 200
+
+    with pytest.raises(ValueError, match="Spec has no facet cells"):
+        vlc.vegalite_facets_to_pngs({"mark": "point", "data": {"values": []}})
+
+
+def test_anonymize_spec():
+    vl_spec = {
+        "data": {
+            "values": [
+                {"customer": "Acme Corp", "region": "North", "revenue": 1200},
+                {"customer": "Globex", "region": "North", "revenue": 800},
+            ]
+        },
+        "mark": "bar",
+        "encoding": {
+            "x": {"field": "region", "type": "nominal"},
+            "y": {"field": "revenue", "aggregate": "sum", "type": "quantitative"},
+        },
+    }
+    anonymized = vlc.anonymize_spec(vl_spec, salt="bug-report")
+    assert anonymized["encoding"] == vl_spec["encoding"]
+    values = anonymized["data"]["values"]
+    assert "Acme Corp" not in json.dumps(values)
+    assert values[0]["region"] == values[1]["region"] != "North"
+    assert values[0]["revenue"] > values[1]["revenue"]
+    assert vlc.anonymize_spec(vl_spec, salt="bug-report") == anonymized
+
+    # The anonymized spec still converts
+    vlc.vegalite_to_svg(anonymized)
+
+
+def test_compare_svgs():
+    svg_a = '<svg><defs><clipPath id="clip1"/></defs><g clip-path="url(#clip1)"><rect x="5"/><text>Sales</text></g></svg>'
+    svg_b = '<svg><defs><clipPath id="clip9"/></defs><g clip-path="url(#clip9)"><rect x="5.2"/><text>Sales</text></g></svg>'
+    assert vlc.compare_svgs(svg_a, svg_b, tolerance=0.5) == {
+        "equal": True,
+        "differences": [],
+    }
+
+    diff = vlc.compare_svgs(svg_a, svg_b.replace(">Sales<", ">Revenue<"), tolerance=0.5)
+    assert diff == {
+        "equal": False,
+        "differences": [
+            {
+                "path": "/svg[0]/g[0]/text[0]",
+                "kind": "text",
+                "a": "Sales",
+                "b": "Revenue",
+            }
+        ],
+    }
+
+    diff = vlc.compare_svgs(svg_a, svg_b)
+    assert [d["path"] for d in diff["differences"]] == ["/svg[0]/g[0]/rect[0]/@x"]
+
+
+def test_health_check():
+    vlc.vegalite_to_vega({"mark": "point"})
+    report = vlc.health_check()
+    assert report["vega_version"] == "5.30.0"
+    assert report["conversions_completed"] > 0
+    assert report["last_conversion_at"] is not None
+    assert report["worker_uptime"] > 0
+    assert report["worker_restarts"] == 0
+
+
+def test_warm_versions():
+    vlc.warm_versions(["5.8", "v5_21"])
+    evaluations = vlc.health_check()["vl_module_evaluations"]
+
+    # Warm versions aren't evaluated again, by warming or by conversions that use them
+    vlc.warm_versions(["5.8"])
+    vlc.vegalite_to_vega({"mark": "point"}, vl_version="5.8")
+    assert vlc.health_check()["vl_module_evaluations"] == evaluations
+
+    with pytest.raises(ValueError, match="Valid versions are: 5.8"):
+        vlc.warm_versions(["4.17"])
+
+
+def test_lenient():
+    vl_spec = load_vl_spec("missing_local_image")
+    with pytest.raises(ValueError, match="Local file access is not allowed"):
+        vlc.vegalite_to_png(vl_spec)
+
+    png = vlc.vegalite_to_png(vl_spec, lenient=True)
+    assert png[:4] == b"\x89PNG"
+    warnings = vlc.vegalite_to_png(vl_spec, lenient=True, return_info=True)["warnings"]
+    assert len(warnings) == 1
+    assert warnings[0]["mark"] == "layer_1_marks"
+    assert "/vl-convert/missing.png" in warnings[0]["message"]
+
+
+def test_config_overrides():
+    vl_spec = load_vl_spec("circle_binned")
+    vg_spec = vlc.vegalite_to_vega(
+        vl_spec,
+        theme="dark",
+        config={"axis": {"labelFontSize": 12}},
+        config_overrides={
+            "axis.labelFontSize": 14,
+            "range.category": ["#111", "#222"],
+            "background": "#123456",
+        },
+    )
+    assert vg_spec["config"]["axis"]["labelFontSize"] == 14
+    assert vg_spec["config"]["range"]["category"] == ["#111", "#222"]
+    assert vg_spec["background"] == "#123456"
+    assert vg_spec["config"]["axis"]["domainColor"] == "#fff"
+
+
+@pytest.mark.parametrize("config_kind", ["dict", "json", "path"])
+def test_config_forms(config_kind, tmp_path):
+    config = {"background": "#123456"}
+    if config_kind == "json":
+        config = json.dumps(config)
+    elif config_kind == "path":
+        path = tmp_path / "config.json"
+        path.write_text(json.dumps(config))
+        config = str(path)
+
+    vl_spec = load_vl_spec("circle_binned")
+    vg_spec = vlc.vegalite_to_vega(vl_spec, config=config)
+    assert vg_spec["background"] == "#123456"
+    svg = vlc.vegalite_to_svg(vl_spec, config=config)
+    assert 'fill="#123456"' in svg
+
+
+def test_config_file_errors(tmp_path):
+    vl_spec = load_vl_spec("circle_binned")
+    missing = tmp_path / "missing.json"
+    with pytest.raises(FileNotFoundError, match="Config file not found"):
+        vlc.vegalite_to_vega(vl_spec, config=str(missing))
+
+    invalid = tmp_path / "invalid.json"
+    invalid.write_text('{\n  "background": #123456\n}')
+    with pytest.raises(ValueError, match="Invalid JSON in config file .* line 2"):
+        vlc.vegalite_to_vega(vl_spec, config=str(invalid))
+
+
+def test_dataflow_error_location():
+    vl_spec = load_vl_spec("broken_calculate")
+    with pytest.raises(
+        ValueError, match=re.escape("error in transform[2] (calculate) of layer[1]")
+    ):
+        vlc.vegalite_to_svg(vl_spec)
+
+
+def test_text_style_options():
+    vl_spec = load_vl_spec("circle_binned")
+    svg = vlc.vegalite_to_svg(vl_spec, font="Courier New", text_color="#ff0000")
+    assert 'font-family="Courier New"' in svg
+    assert 'font-family="sans-serif"' not in svg
+    assert 'fill="#ff0000"' in svg
+
+    # Config takes precedence over the text style options
+    svg = vlc.vegalite_to_svg(
+        vl_spec, config={"axis": {"labelFont": "Georgia"}}, font="Courier New"
+    )
+    assert 'font-family="Georgia"' in svg
+    assert 'font-family="Courier New"' in svg
+
+
+def test_direction_rtl():
+    vl_spec = {
+        "data": {"values": [{"a": "A", "b": 28}, {"a": "B", "b": 55}]},
+        "mark": "bar",
+        "encoding": {
+            "x": {"field": "a", "type": "nominal"},
+            "y": {"field": "b", "type": "quantitative"},
+        },
+    }
+    vg_spec = vlc.vegalite_to_vega(vl_spec, direction="rtl")
+    y_orients = {axis["orient"] for axis in vg_spec["axes"] if axis["scale"] == "y"}
+    assert y_orients == {"right"}
+
+    svg = vlc.vegalite_to_svg(vl_spec, direction="rtl")
+    assert 'aria-label="Y-axis' in svg
+
+    with pytest.raises(ValueError, match="Unsupported direction"):
+        vlc.vegalite_to_vega(vl_spec, direction="up")
+
+
+def test_max_views():
+    # 4 repeated fields, each faceted into 5 rows
+    vl_spec = {
+        "data": {"values": [{"row": i % 5, "w": i, "x": i} for i in range(10)]},
+        "repeat": ["w", "x", "y", "z"],
+        "spec": {
+            "facet": {"row": {"field": "row"}},
+            "spec": {
+                "mark": "point",
+                "encoding": {
+                    "x": {"field": {"repeat": "repeat"}, "type": "quantitative"}
+                },
+            },
+        },
+    }
+    try:
+        vlc.set_spec_limits(max_views=20)
+        vlc.vegalite_to_vega(vl_spec)
+        vlc.set_spec_limits(max_views=19)
+        with pytest.raises(ValueError, match="exceeds the max_views limit of 19"):
+            vlc.vegalite_to_vega(vl_spec)
+    finally:
+        vlc.set_spec_limits()
+
+
+def test_generic_font_mapping():
+    vl_spec = load_vl_spec("circle_binned")
+    mapping = {"sans-serif": "Caveat"}
+    svg = vlc.vegalite_to_svg(vl_spec, generic_font_mapping=mapping)
+    assert 'font-family="Caveat"' in svg
+    assert 'font-family="sans-serif"' not in svg
+    assert svg == vlc.vegalite_to_svg(vl_spec, font="Caveat")
+
+    png = vlc.vegalite_to_png(vl_spec, generic_font_mapping=mapping)
+    assert png == vlc.vegalite_to_png(vl_spec, font="Caveat")
+    assert png != vlc.vegalite_to_png(vl_spec)
+
+    with pytest.raises(ValueError, match="Unsupported generic font family"):
+        vlc.vegalite_to_svg(vl_spec, generic_font_mapping={"fantasy": "Caveat"})
+
+
+def test_emoji_text():
+    vl_spec = {
+        "data": {"values": [{"label": "\u26a0\ufe0f\U0001f600\U0001f1e9\U0001f1ea"}]},
+        "mark": {"type": "text", "fontSize": 40},
+        "encoding": {"text": {"field": "label"}},
+    }
+    # CI installs a color emoji font on Linux, and Windows and macOS come with one
+    emoji_font = vlc.get_emoji_font()
+    assert emoji_font is not None
+
+    # Emoji that text fonts don't have are drawn with the emoji font, rather than as the
+    # missing glyph box of the sans-serif font
+    metrics = vlc.measure_text("\U0001f600\U0001f680", font="sans-serif", size=40)
+    assert metrics["glyphs"]
+    for glyph in metrics["glyphs"]:
+        assert glyph["font_family"] == emoji_font
+        assert glyph["glyph_id"] != 0
+
+    # The emoji are drawn in color
+    png = vlc.vegalite_to_png(vl_spec)
+    img = PIL.Image.open(BytesIO(png)).convert("RGB")
+    assert any(abs(r - b) > 64 for r, g, b in img.getdata())
+    assert vlc.vegalite_to_pdf(vl_spec).startswith(b"%PDF")
+
+    try:
+        # An emoji font that isn't installed falls back to the default emoji font
+        vlc.set_emoji_font("Not An Emoji Font")
+        assert vlc.get_emoji_font() == emoji_font
+        assert vlc.vegalite_to_png(vl_spec) == png
+    finally:
+        vlc.set_emoji_font()
+
+
+def text_layout_width(text, font):
+    # Width that text marks are laid out with, which is the textLength added to SVG text
+    vg_spec = {
+        "width": 200,
+        "height": 20,
+        "marks": [
+            {
+                "type": "text",
+                "encode": {
+                    "enter": {
+                        "text": {"value": text},
+                        "font": {"value": font},
+                        "fontSize": {"value": 12},
+                    }
+                },
+            }
+        ],
+    }
+    svg = vlc.vega_to_svg(vg_spec, text_length=True)
+    return float(re.search(r'textLength="([\d.]+)"', svg).group(1))
+
+
+def test_measure_text():
+    metrics = vlc.measure_text("Hello,  World", font="Liberation Sans", size=12)
+    assert metrics["width"] > 0
+    assert sum(g["x_advance"] for g in metrics["glyphs"]) == pytest.approx(
+        text_layout_width("Hello,  World", "Liberation Sans"), abs=0.01
+    )
+    # The collapsed whitespace has no glyph, and clusters are byte offsets
+    assert [g["cluster"] for g in metrics["glyphs"]][6:8] == [6, 8]
+    assert {g["font_family"] for g in metrics["glyphs"]} == {"Liberation Sans"}
+    bold = vlc.measure_text(
+        "Hello,  World", font="Liberation Sans", size=12, weight=700
+    )
+    assert bold["width"] > metrics["width"]
+
+    # Matter doesn't have Greek letters, which are drawn with a fallback font
+    metrics = vlc.measure_text("A\u03a9B", font="Matter", size=12)
+    omega = [g for g in metrics["glyphs"] if g["cluster"] == 1]
+    assert len(omega) == 1
+    assert omega[0]["font_family"] != "Matter"
+    assert omega[0]["glyph_id"] != 0
+    assert sum(g["x_advance"] for g in metrics["glyphs"]) == pytest.approx(
+        text_layout_width("A\u03a9B", "Matter"), abs=0.01
+    )
+
+    assert vlc.measure_text("  ") == {"width": 0.0, "glyphs": []}
+
+
+def test_themes():
+    requests = []
+
+    class DataHandler(BaseHTTPRequestHandler):
+        def do_GET(self):
+            requests.append(self.path)
+            body = json.dumps([{"a": "A", "b": 28}, {"a": "B", "b": 55}]).encode()
+            self.send_response(200)
+            self.send_header("Content-Type", "application/json")
+            self.send_header("Content-Length", str(len(body)))
+            self.end_headers()
+            self.wfile.write(body)
+
+        def log_message(self, *args):
+            pass
+
+    server = ThreadingHTTPServer(("127.0.0.1", 0), DataHandler)
+    threading.Thread(target=server.serve_forever, daemon=True).start()
+    try:
+        vl_spec = {
+            "data": {"url": f"http://127.0.0.1:{server.server_port}/data.json"},
+            "mark": "bar",
+            "encoding": {
+                "x": {"field": "a", "type": "nominal"},
+                "y": {"field": "b", "type": "quantitative"},
+            },
+        }
+        pngs = vlc.vegalite_to_png(vl_spec, themes=["default", "dark"])
+        assert set(pngs) == {"default", "dark"}
+        assert pngs["default"] != pngs["dark"]
+        assert requests == ["/data.json"]
+
+        svgs = vlc.vegalite_to_svg(vl_spec, themes=["default", "dark"])
+        assert svgs["dark"] == vlc.vegalite_to_svg(vl_spec, theme="dark")
+        assert svgs["default"] == vlc.vegalite_to_svg(vl_spec)
+    finally:
+        server.shutdown()
+
+
+def test_short_url():
+    requests = []
+
+    class ShortenerHandler(BaseHTTPRequestHandler):
+        def do_POST(self):
+            length = int(self.headers["Content-Length"])
+            requests.append(
+                (self.headers["Authorization"], json.loads(self.rfile.read(length)))
+            )
+            status, body = (503, {}) if self.path == "/down" else (200, {"link": "abc"})
+            body = json.dumps(body).encode()
+            self.send_response(status)
+            self.send_header("Content-Type", "application/json")
+            self.send_header("Content-Length", str(len(body)))
+            self.end_headers()
+            self.wfile.write(body)
+
+        def log_message(self, *args):
+            pass
+
+    server = ThreadingHTTPServer(("127.0.0.1", 0), ShortenerHandler)
+    threading.Thread(target=server.serve_forever, daemon=True).start()
+    try:
+        endpoint = f"http://127.0.0.1:{server.server_port}"
+        vl_spec = load_vl_spec("circle_binned")
+        url = vlc.vegalite_to_url(
+            vl_spec,
+            shorten_endpoint=f"{endpoint}/shorten",
+            shorten_auth_header="Bearer secret",
+            shorten_response_field="link",
+        )
+        assert url == "abc"
+        assert requests == [("Bearer secret", {"url": vlc.vegalite_to_url(vl_spec)})]
+
+        with pytest.raises(ValueError, match="503"):
+            vlc.vega_to_url({"marks": []}, shorten_endpoint=f"{endpoint}/down")
+
+        url = vlc.vega_to_url(
+            {"marks": []}, shorten_endpoint=f"{endpoint}/down", shorten_fallback=True
+        )
+        assert url == vlc.vega_to_url({"marks": []})
+    finally:
+        server.shutdown()
+
+
+def test_container_size():
+    vl_spec = {
+        "data": {"values": [{"a": "A", "b": 28}, {"a": "B", "b": 55}]},
+        "mark": "bar",
+        "width": "container",
+        "height": "container",
+        "encoding": {
+            "x": {"field": "a", "type": "nominal"},
+            "y": {"field": "b", "type": "quantitative"},
+        },
+    }
+    svg = vlc.vegalite_to_svg(vl_spec)
+    assert 'width="600"' in svg
+    assert 'height="400"' in svg
+
+    svg = vlc.vegalite_to_svg(vl_spec, container_width=500, container_height=300)
+    assert 'width="500"' in svg
+    assert 'height="300"' in svg
+
+    with pytest.raises(ValueError, match="sized to its container"):
+        vlc.vegalite_to_svg(vl_spec, strict_sizing=True)
+
+
+@pytest.mark.parametrize("scale,ppi", [(1, None), (2, None), (1.5, 144)])
+def test_return_info(scale, ppi):
+    vl_spec = load_vl_spec("circle_binned")
+    info = vlc.vegalite_to_png(vl_spec, scale=scale, ppi=ppi, return_info=True)
+    assert info["data"] == vlc.vegalite_to_png(vl_spec, scale=scale, ppi=ppi)
+    img = PIL.Image.open(BytesIO(info["data"]))
+    assert (info["width_px"], info["height_px"]) == img.size
+    assert info["scale"] == scale
+    assert info["ppi"] == (ppi or 72)
+
+    info = vlc.vegalite_to_jpeg(vl_spec, scale=scale, return_info=True)
+    img = PIL.Image.open(BytesIO(info["data"]))
+    assert (info["width_px"], info["height_px"]) == img.size
+    assert info["quality"] == 90
+
+    svg_info = vlc.vegalite_to_svg(vl_spec, return_info=True)
+    assert svg_info["data"] == vlc.vegalite_to_svg(vl_spec)
+    assert svg_info["view_box"] == [0, 0, svg_info["width"], svg_info["height"]]
+    assert (info["css_width"], info["css_height"]) == (
+        svg_info["width"],
+        svg_info["height"],
+    )
+
+
+# Argument values for the vegalite_* functions, chosen so that a misbound positional
+# argument changes the result or fails
+POSITIONAL_ARG_VALUES = {
+    "vl_spec": {
+        "data": {"values": [{"a": "A", "b": 1200.5}, {"a": "B", "b": 3400.25}]},
+        "mark": "bar",
+        "encoding": {
+            "x": {"field": "a", "type": "nominal"},
+            "y": {"field": "b", "type": "quantitative"},
+        },
+    },
+    "formats": ["svg", "png"],
+    "vl_version": "v5_8",
+    "scale": 1.0,
+    "ppi": 96.0,
+    "quality": 80,
+    "config": {"background": BACKGROUND_COLOR},
+    "theme": "dark",
+    "show_warnings": False,
+    "allowed_base_urls": [],
+    "format_locale": "it-IT",
+    "time_format_locale": "it-IT",
+    "interlaced": False,
+    "fetch_retries": 1,
+    "fetch_retry_backoff_ms": 10,
+    "text_length": True,
+    "svg_native_tooltips": True,
+    "svg_minify": True,
+    "svg_precision": 2,
+    "interactions": [],
+    "preserve_int64": True,
+    "bundle": False,
+    "renderer": "canvas",
+    "fullscreen": True,
+    "shorten_endpoint": None,
+    "shorten_auth_header": None,
+    "shorten_response_field": "link",
+    "shorten_timeout": 5.0,
+    "shorten_fallback": False,
+    "lenient": True,
+    "browser_shims": True,
+    "simplify_topojson": 1.0,
+    "font": "Courier New",
+    "font_size_scale": 1.5,
+    "text_color": "#333333",
+    "config_overrides": {"axis.labelFontSize": 14},
+    "direction": "rtl",
+    "generic_font_mapping": {"sans-serif": "Courier New"},
+    "container_width": 500.0,
+    "container_height": 300.0,
+    "strict_sizing": False,
+    "width": 300.0,
+    "height": 200.0,
+    "resize_mode": "pad",
+    "min_size": (10, 10),
+    "max_rows": 1,
+    "row_limit_strategy": "head",
+    "annotate_truncation": True,
+    "log_level": "warn",
+    "warning_filters": [],
+    "suppress_components": [],
+    "lenient_dates": True,
+    "svg_post_processor": None,
+    "return_info": False,
+    "antialias": False,
+    "ppi_mode": "metadata-only",
+    "image_rendering": "pixelated",
+    "flatten_background": "white",
+    "jpeg_background": "#f0f0f0",
+    "pdfa": True,
+    "source_dpi": 72.0,
+    "themes": ["default", "dark"],
+    "static_fallback": True,
+    "static_fallback_scale": 1.0,
+    "warn_only": False,
+    "template": None,
+}
+
+VEGALITE_FUNCTIONS = [
+    "vegalite_to_vega",
+    "vegalite_to_svg",
+    "vegalite_to_scenegraph",
+    "get_final_vega_spec",
+    "vegalite_freeze",
+    "vegalite_get_text_items",
+    "vegalite_to_png",
+    "vegalite_to_vega_and_svg",
+    "vegalite_to_vega_and_png",
+    "vegalite_to_jpeg",
+    "vegalite_to_pdf",
+    "vegalite_export",
+    "vegalite_to_url",
+    "vegalite_to_html",
+]
+
+
+def parse_stub():
+    # The stub that build.rs generated for the installed module
+    return ast.parse((Path(vlc.__file__).parent / "__init__.pyi").read_text())
+
+
+def stub_parameters(name):
+    func = next(
+        node
+        for node in parse_stub().body
+        if isinstance(node, ast.FunctionDef) and node.name == name
+    )
+    args = func.args
+    params = [arg.arg for arg in args.args]
+    if args.vararg:
+        params.append(args.vararg.arg)
+    params.extend(arg.arg for arg in args.kwonlyargs)
+    if args.kwarg:
+        params.append(args.kwarg.arg)
+    return params
+
+
+def stub_exports():
+    stub = parse_stub()
+    exports = next(
+        node
+        for node in stub.body
+        if isinstance(node, ast.Assign) and node.targets[0].id == "__all__"
+    )
+    return [elt.value for elt in exports.value.elts]
+
+
+def stub_literal(alias):
+    # Type aliases are defined in the TYPE_CHECKING block of the stub
+    for node in ast.walk(parse_stub()):
+        if isinstance(node, ast.AnnAssign) and node.target.id == alias:
+            values = node.value.slice
+            elts = values.elts if isinstance(values, ast.Tuple) else [values]
+            return {elt.value for elt in elts}
+    raise KeyError(alias)
+
+
+@pytest.mark.parametrize("name", VEGALITE_FUNCTIONS)
+def test_vegalite_positional_args(name):
+    func = getattr(vlc, name)
+    params = list(inspect.signature(func).parameters)
+    assert params == stub_parameters(name)
+
+    kwargs = {param: POSITIONAL_ARG_VALUES[param] for param in params}
+    assert func(*kwargs.values()) == func(**kwargs)
+
+
+@pytest.mark.parametrize("name", stub_exports())
+def test_stub_signature(name):
+    func = getattr(vlc, name)
+    assert list(inspect.signature(func).parameters) == stub_parameters(name)
+
+
+def test_stub_literals():
+    assert set(vlc.__all__) - {"__version__"} == set(stub_exports())
+
+    versions = set()
+    for version in vlc.get_vegalite_versions():
+        underscored = version.replace(".", "_")
+        versions.update([version, f"v{version}", underscored, f"v{underscored}"])
+    assert stub_literal("VlVersion") == versions
+    assert stub_literal("VegaThemes") == set(vlc.get_themes())
+    assert stub_literal("Renderer") == {"svg", "canvas", "hybrid"}
+    assert stub_literal("ResizeMode") == {"fit", "pad", "none"}
+
+
+def test_invalid_enum_arguments():
+    vl_spec = load_vl_spec("circle_binned")
+
+    versions = ", ".join(vlc.get_vegalite_versions())
+    with pytest.raises(ValueError, match=f"Valid versions are: {re.escape(versions)}$"):
+        vlc.vegalite_to_vega(vl_spec, vl_version="5.3")
+    with pytest.raises(ValueError, match="Valid themes are: .*dark"):
+        vlc.vegalite_to_vega(vl_spec, theme="drak")
+    with pytest.raises(ValueError, match="Unknown theme 'drak'. Did you mean 'dark'?"):
+        vlc.vegalite_to_svg(vl_spec, themes=["default", "drak"])
+    with pytest.raises(ValueError, match="Valid renderers are: svg, canvas, hybrid"):
+        vlc.vegalite_to_html(vl_spec, renderer="webgl")
+
+
+def test_misspelled_names():
+    vl_spec = load_vl_spec("circle_binned")
+
+    with pytest.raises(ValueError, match="Unknown theme 'drk'. Did you mean 'dark'?"):
+        vlc.vegalite_to_svg(vl_spec, theme="drk")
+    message = "Unknown format locale 'it_IT'. Did you mean 'it-IT'?"
+    with pytest.raises(ValueError, match=message):
+        vlc.vegalite_to_svg(vl_spec, format_locale="it_IT")
+    message = "Unknown time format locale 'fr-FRR'. Did you mean 'fr-FR'?"
+    with pytest.raises(ValueError, match=message):
+        vlc.vegalite_to_svg(vl_spec, time_format_locale="fr-FRR")
+
+    # Valid names pass through
+    svg = vlc.vegalite_to_svg(
+        vl_spec, theme="dark", format_locale="it-IT", time_format_locale="it-IT"
+    )
+    assert svg.startswith("<svg")
+
+
+def test_vegalite_positional_args_misbound():
+    # Arguments passed in the order that preceded show_warnings are rejected
+    with pytest.raises(TypeError):
+        vlc.vegalite_to_pdf(
+            POSITIONAL_ARG_VALUES["vl_spec"], None, 1.0, None, None, ["https://"]
+        )
+
+
 def check_png(png, expected_png, tol=0.994):
     png_img = imread(BytesIO(png))
     expected_png_img = imread(BytesIO(expected_png))
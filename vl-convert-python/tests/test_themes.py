@@ -1,3 +1,4 @@
+import json
 import vl_convert as vlc
 
 
@@ -8,3 +9,21 @@ def test_get_themes_dark_theme_background():
     assert isinstance(dark, dict)
     background = dark["background"]
     assert background == "#333"
+
+
+def test_get_themes_sorted():
+    names = vlc.get_theme_names()
+    assert names == sorted(names)
+    assert "dark" in names
+    assert "default" not in names
+
+    themes = vlc.get_themes()
+    assert list(themes) == names
+    assert list(themes["dark"]) == sorted(themes["dark"])
+    assert json.dumps(vlc.get_themes()) == json.dumps(themes)
+
+
+def test_get_themes_meta():
+    meta = vlc.get_themes_meta()
+    assert meta["vega_themes_version"] == vlc.get_vega_themes_version()
+    assert meta["themes"] == vlc.get_theme_names()
@@ -0,0 +1,133 @@
+fn main() {
+    #[cfg(feature = "snapshot")]
+    snapshot::create_snapshot();
+}
+
+/// Create a V8 snapshot of the Deno runtime with Vega, vega-themes, and the default Vega-Lite
+/// version already evaluated, so that converters don't parse and compile them on startup.
+///
+/// The vendored modules import each other with skypack paths, which extension modules can't
+/// resolve, so the modules that the libraries import are added to an extension with `ext:`
+/// specifiers, and their imports are rewritten to match
+#[cfg(feature = "snapshot")]
+mod snapshot {
+    use deno_runtime::deno_core::{Extension, ExtensionFileSource};
+    use deno_runtime::ops::bootstrap::SnapshotOptions;
+    use regex::Regex;
+    use std::borrow::Cow;
+    use std::collections::{HashSet, VecDeque};
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use std::sync::Arc;
+
+    include!("src/module_loader/vendor_manifest.rs");
+
+    /// Prefix of the specifiers of the vendored modules in the snapshot
+    const SPECIFIER_PREFIX: &str = "ext:vl_convert";
+
+    /// Specifier of the module that evaluates the libraries and keeps them in a global
+    const ENTRY_POINT: &str = "ext:vl_convert/snapshot.js";
+
+    pub fn create_snapshot() {
+        let manifest_dir = PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap());
+        let out_dir = PathBuf::from(std::env::var("OUT_DIR").unwrap());
+        for path in ["vendor", "src/module_loader"] {
+            println!("cargo:rerun-if-changed={path}");
+        }
+
+        let import_map =
+            fs::read_to_string(manifest_dir.join("src/module_loader/import_map.rs")).unwrap();
+        let vega_path = capture(&import_map, r#"pub const VEGA_PATH: &str =\s*"([^"]+)""#);
+        let vega_themes_path = capture(
+            &import_map,
+            r#"pub const VEGA_THEMES_PATH: &str =\s*"([^"]+)""#,
+        );
+        let vl_semver = capture(&import_map, r#"VlVersion::from_str\("([0-9.]+)"\)"#);
+        let vl_path = capture(
+            &import_map,
+            &format!(
+                r#"v{} => "([^"]+)""#,
+                regex::escape(&vl_semver.replace('.', "_"))
+            ),
+        );
+
+        let entry_code = format!(
+            r#"
+import * as vega from "{SPECIFIER_PREFIX}{vega_path}";
+import * as vegaThemes from "{SPECIFIER_PREFIX}{vega_themes_path}";
+import * as vegaLite from "{SPECIFIER_PREFIX}{vl_path}";
+
+globalThis.vlConvertSnapshot = {{
+    vega,
+    vegaThemes,
+    vegaLite: {{ "{vl_semver}": vegaLite }},
+}};
+"#
+        );
+        let mut esm_files = vec![ExtensionFileSource::new_computed(
+            ENTRY_POINT,
+            Arc::from(entry_code),
+        )];
+        for (path, code) in vendored_modules(
+            &manifest_dir.join("vendor/cdn.skypack.dev"),
+            &[&vega_path, &vega_themes_path, &vl_path],
+        ) {
+            let specifier: &'static str =
+                Box::leak(format!("{SPECIFIER_PREFIX}{path}").into_boxed_str());
+            esm_files.push(ExtensionFileSource::new_computed(
+                specifier,
+                Arc::from(code),
+            ));
+        }
+
+        let extension = Extension {
+            name: "vl_convert_snapshot",
+            esm_files: Cow::Owned(esm_files),
+            esm_entry_point: Some(ENTRY_POINT),
+            ..Default::default()
+        };
+        deno_runtime::snapshot::create_runtime_snapshot(
+            out_dir.join("VL_CONVERT_SNAPSHOT.bin"),
+            SnapshotOptions::default(),
+            vec![extension],
+        );
+    }
+
+    /// First group of the first match of `pattern` in `text`
+    fn capture(text: &str, pattern: &str) -> String {
+        let re = Regex::new(pattern).unwrap();
+        match re.captures(text) {
+            Some(caps) => caps[1].to_string(),
+            None => panic!("Failed to find {pattern} in import_map.rs"),
+        }
+    }
+
+    /// Sources of the vendored modules that `entry_paths` import, directly or indirectly,
+    /// keyed by path, with their imports rewritten to extension specifiers. Only these
+    /// modules are added to the extension, as every module of an extension must be used
+    fn vendored_modules(vendor_dir: &Path, entry_paths: &[&str]) -> Vec<(String, String)> {
+        let import_re = Regex::new(r#"(["'])(/(?:-|pin)/[^"']+\.js)(["'])"#).unwrap();
+        let vendored: HashSet<&str> = VENDOR_MANIFEST.iter().map(|(path, _)| *path).collect();
+
+        let mut modules = Vec::new();
+        let mut seen: HashSet<String> = entry_paths.iter().map(|p| p.to_string()).collect();
+        let mut queue: VecDeque<String> = entry_paths.iter().map(|p| p.to_string()).collect();
+        while let Some(path) = queue.pop_front() {
+            if !vendored.contains(path.as_str()) {
+                panic!("Module {path} isn't in the vendor manifest");
+            }
+            let code = fs::read_to_string(vendor_dir.join(path.trim_start_matches('/')))
+                .unwrap_or_else(|err| panic!("Failed to read vendored module {path}: {err}"));
+            for caps in import_re.captures_iter(&code) {
+                if seen.insert(caps[2].to_string()) {
+                    queue.push_back(caps[2].to_string());
+                }
+            }
+            let code = import_re
+                .replace_all(&code, format!("${{1}}{SPECIFIER_PREFIX}${{2}}${{3}}"))
+                .into_owned();
+            modules.push((path, code));
+        }
+        modules
+    }
+}
@@ -0,0 +1,88 @@
+use vl_convert_rs::anyhow::Error;
+use vl_convert_rs::converter::VlOpts;
+use vl_convert_rs::output_format::{OutputFormat, RenderContext};
+use vl_convert_rs::tiny_skia::Pixmap;
+use vl_convert_rs::VlConverter;
+
+/// Output format that writes charts as 24-bit BMP images, composited over a white background
+struct BmpFormat;
+
+impl OutputFormat for BmpFormat {
+    fn id(&self) -> &str {
+        "bmp"
+    }
+
+    fn from_svg(&self, svg: &str, ctx: &RenderContext) -> Result<Vec<u8>, Error> {
+        self.from_pixmap(ctx.rasterize(svg)?, ctx)
+    }
+
+    fn from_pixmap(&self, pixmap: Pixmap, _ctx: &RenderContext) -> Result<Vec<u8>, Error> {
+        let (width, height) = (pixmap.width(), pixmap.height());
+        // Rows of pixels are padded to a multiple of 4 bytes
+        let row_bytes = (width * 3).div_ceil(4) * 4;
+        let image_bytes = row_bytes * height;
+
+        let mut bmp = Vec::with_capacity(54 + image_bytes as usize);
+        // File header
+        bmp.extend_from_slice(b"BM");
+        bmp.extend_from_slice(&(54 + image_bytes).to_le_bytes());
+        bmp.extend_from_slice(&[0; 4]);
+        bmp.extend_from_slice(&54u32.to_le_bytes());
+        // BITMAPINFOHEADER
+        bmp.extend_from_slice(&40u32.to_le_bytes());
+        bmp.extend_from_slice(&(width as i32).to_le_bytes());
+        bmp.extend_from_slice(&(height as i32).to_le_bytes());
+        bmp.extend_from_slice(&1u16.to_le_bytes());
+        bmp.extend_from_slice(&24u16.to_le_bytes());
+        bmp.extend_from_slice(&0u32.to_le_bytes());
+        bmp.extend_from_slice(&image_bytes.to_le_bytes());
+        bmp.extend_from_slice(&[0; 16]);
+
+        // Rows are stored bottom to top, with pixels as BGR
+        for row in pixmap.pixels().chunks(width as usize).rev() {
+            for pixel in row {
+                // Premultiplied colors over white
+                let white = 255 - pixel.alpha();
+                bmp.extend_from_slice(&[
+                    pixel.blue() + white,
+                    pixel.green() + white,
+                    pixel.red() + white,
+                ]);
+            }
+            bmp.resize(bmp.len() + (row_bytes - width * 3) as usize, 0);
+        }
+        Ok(bmp)
+    }
+}
+
+/// Register a BMP output format with a converter and convert a chart to it. Run with
+/// `cargo run --example bmp_output_format`
+#[tokio::main]
+async fn main() {
+    let vl_spec = serde_json::json!({
+        "data": {"values": [{"a": "A", "b": 28}, {"a": "B", "b": 55}, {"a": "C", "b": 43}]},
+        "mark": "bar",
+        "encoding": {
+            "x": {"field": "a", "type": "nominal"},
+            "y": {"field": "b", "type": "quantitative"}
+        }
+    });
+
+    let mut converter = VlConverter::new();
+    converter
+        .register_output_format(Box::new(BmpFormat))
+        .expect("Failed to register the BMP output format");
+
+    let bmp = converter
+        .convert_to(
+            vl_spec,
+            VlOpts::default(),
+            "bmp",
+            Some(serde_json::json!({"scale": 2})),
+        )
+        .await
+        .expect("Failed to convert the chart to BMP");
+
+    std::fs::write("chart.bmp", &bmp).expect("Failed to write chart.bmp");
+    println!("Wrote chart.bmp ({} bytes)", bmp.len());
+}
@@ -0,0 +1,54 @@
+use std::time::{Duration, Instant};
+use vl_convert_rs::converter::VlOpts;
+use vl_convert_rs::VlConverter;
+
+/// Measure how long a histogram of 1M rows takes to convert with and without the
+/// pre_aggregate option. Run with `cargo run --release --example pre_aggregate`
+#[tokio::main]
+async fn main() {
+    let mut seed: u64 = 1;
+    let rows: Vec<_> = (0..1_000_000)
+        .map(|_| {
+            seed = seed * 16807 % 2147483647;
+            serde_json::json!({"v": seed as f64 / 2147483647.0 * 100.0})
+        })
+        .collect();
+    let vl_spec = serde_json::json!({
+        "datasets": {"rows": rows},
+        "data": {"name": "rows"},
+        "mark": "bar",
+        "encoding": {
+            "x": {"field": "v", "bin": {"maxbins": 20}, "type": "quantitative"},
+            "y": {"aggregate": "count", "type": "quantitative"}
+        }
+    });
+
+    // Load Vega and Vega-Lite before timing the conversions
+    let mut converter = VlConverter::new();
+    convert(&mut converter, &vl_spec, true).await;
+
+    let start = Instant::now();
+    convert(&mut converter, &vl_spec, false).await;
+    report("1M rows aggregated in JavaScript", start.elapsed());
+
+    let start = Instant::now();
+    convert(&mut converter, &vl_spec, true).await;
+    report("1M rows pre-aggregated", start.elapsed());
+}
+
+async fn convert(converter: &mut VlConverter, vl_spec: &serde_json::Value, pre_aggregate: bool) {
+    converter
+        .vegalite_to_svg(
+            vl_spec.clone(),
+            VlOpts {
+                pre_aggregate,
+                ..Default::default()
+            },
+        )
+        .await
+        .expect("Failed to convert Vega-Lite spec to SVG");
+}
+
+fn report(label: &str, elapsed: Duration) {
+    println!("{label}: {:.0} ms", elapsed.as_secs_f64() * 1000.0);
+}
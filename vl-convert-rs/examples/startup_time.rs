@@ -0,0 +1,39 @@
+use std::time::Instant;
+use vl_convert_rs::VlConverter;
+
+/// Time the startup of a converter, along with its first conversion, which evaluates Vega and
+/// Vega-Lite unless the runtime was started from a snapshot. Compare the times of builds with
+/// and without the snapshot feature:
+///
+/// cargo run --release --example startup_time
+/// cargo run --release --example startup_time --features snapshot
+#[tokio::main]
+async fn main() {
+    let vl_spec = serde_json::json!({
+        "$schema": "https://vega.github.io/schema/vega-lite/v5.json",
+        "data": {"values": [{"a": "A", "b": 28}, {"a": "B", "b": 55}, {"a": "C", "b": 43}]},
+        "mark": "bar",
+        "encoding": {
+            "x": {"field": "a", "type": "nominal"},
+            "y": {"field": "b", "type": "quantitative"}
+        }
+    });
+
+    let start = Instant::now();
+    let mut converter = VlConverter::new();
+    converter
+        .vegalite_to_svg(vl_spec.clone(), Default::default())
+        .await
+        .expect("Failed to perform Vega-Lite to SVG conversion");
+    let first = start.elapsed();
+
+    let start = Instant::now();
+    converter
+        .vegalite_to_svg(vl_spec, Default::default())
+        .await
+        .expect("Failed to perform Vega-Lite to SVG conversion");
+    let second = start.elapsed();
+
+    println!("Startup and first conversion: {first:.2?}");
+    println!("Second conversion: {second:.2?}");
+}
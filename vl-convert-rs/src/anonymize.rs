@@ -0,0 +1,744 @@
+use regex::Regex;
+use serde_json::{Map, Number, Value};
+use sha2::{Digest, Sha256};
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hasher};
+
+lazy_static! {
+    static ref DATE_RE: Regex = Regex::new(
+        r"^(\d{4})([-/])(\d{2})([-/])(\d{2})((?:[T ]\d{2}:\d{2}(?::\d{2}(?:\.\d+)?)?(?:Z|[+-]\d{2}:?\d{2})?)?)$"
+    )
+    .unwrap();
+    // Numbers with leading zeros, like zip codes, are treated as text
+    static ref NUMBER_RE: Regex =
+        Regex::new(r"^-?(?:0|[1-9]\d*)(?:\.(\d+))?(?:[eE][+-]?\d+)?$").unwrap();
+}
+
+/// Values of "type" properties in GeoJSON and TopoJSON data, which are kept so that
+/// geographic data still draws
+const GEO_TYPES: [&str; 10] = [
+    "Feature",
+    "FeatureCollection",
+    "GeometryCollection",
+    "LineString",
+    "MultiLineString",
+    "MultiPoint",
+    "MultiPolygon",
+    "Point",
+    "Polygon",
+    "Topology",
+];
+
+/// Largest number of days that dates are shifted by
+const MAX_DATE_SHIFT_DAYS: u64 = 730;
+
+/// Options for [`anonymize_spec`]
+#[derive(Debug, Clone)]
+pub struct AnonymizeOptions {
+    /// Whether strings that hold numbers or dates are kept as numbers or dates, which are
+    /// perturbed and shifted like other numbers and dates. When false, they're replaced with
+    /// hashes like other strings, which hides more but breaks quantitative and temporal
+    /// encodings of string fields
+    pub preserve_types: bool,
+    /// Whether equal strings are replaced with equal hashes, so that fields keep their number
+    /// of distinct values and grouping by them still works. When false, every occurrence of a
+    /// string is replaced with a hash of its own
+    pub preserve_cardinality: bool,
+    /// Salt of the hashes, which also seeds how numbers and dates are changed, so that the
+    /// same salt anonymizes a spec the same way. When None, a random salt is used
+    pub salt: Option<String>,
+}
+
+impl Default for AnonymizeOptions {
+    fn default() -> Self {
+        Self {
+            preserve_types: true,
+            preserve_cardinality: true,
+            salt: None,
+        }
+    }
+}
+
+/// Anonymize the inline data of a Vega or Vega-Lite spec, so that the spec can be shared in a
+/// bug report without the data that it holds.
+///
+/// The `values` of `data` and the entries of `datasets` are rewritten, including inline JSON,
+/// CSV, and TSV text. Strings are replaced with salted hashes, numbers are perturbed while
+/// keeping their order and rough magnitude, and dates are all shifted by the same random
+/// number of days. Field names, encodings, transforms, and config are left as they are, so
+/// that the chart usually still reproduces the bug. Values that transforms or encodings
+/// refer to, e.g. in a filter, aren't rewritten to match
+pub fn anonymize_spec(spec: &Value, opts: &AnonymizeOptions) -> Value {
+    let salt = opts.salt.clone().unwrap_or_else(|| {
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u64(0);
+        format!("{:x}", hasher.finish())
+    });
+    let mut anonymizer = Anonymizer::new(opts, salt);
+
+    // Numbers are collected first, so that each one can be placed between its neighbors
+    let mut spec = spec.clone();
+    for_each_inline_dataset(&mut spec, &mut |values, format| {
+        anonymizer.collect_numbers(values, format)
+    });
+    anonymizer.perturb_numbers();
+    for_each_inline_dataset(&mut spec, &mut |values, format| {
+        anonymizer.rewrite_dataset(values, format)
+    });
+    spec
+}
+
+/// Call `f` with the inline values of each dataset of a spec, along with the format of the
+/// dataset when it has one
+fn for_each_inline_dataset(value: &mut Value, f: &mut impl FnMut(&mut Value, Option<&Value>)) {
+    match value {
+        Value::Object(obj) => {
+            for (key, child) in obj.iter_mut() {
+                match (key.as_str(), child) {
+                    ("datasets", Value::Object(datasets)) => {
+                        for values in datasets.values_mut() {
+                            f(values, None);
+                        }
+                    }
+                    ("data", Value::Object(data)) => visit_data(data, f),
+                    ("data", Value::Array(data)) => {
+                        for data in data {
+                            if let Value::Object(data) = data {
+                                visit_data(data, f);
+                            }
+                        }
+                    }
+                    (_, child) => for_each_inline_dataset(child, f),
+                }
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                for_each_inline_dataset(item, f);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn visit_data(data: &mut Map<String, Value>, f: &mut impl FnMut(&mut Value, Option<&Value>)) {
+    let format = data.get("format").cloned();
+    match data.get_mut("values") {
+        // Values that are set by a signal aren't inline data
+        Some(Value::Object(values)) if values.contains_key("signal") => {}
+        Some(values) => f(values, format.as_ref()),
+        None => {}
+    }
+}
+
+/// Delimiter of text values in `format`, or None when they're JSON
+fn text_delimiter(values: &str, format: Option<&Value>) -> Option<String> {
+    let format_type = format.and_then(|format| format.get("type")?.as_str());
+    match format_type {
+        Some("json") | Some("topojson") => None,
+        Some("tsv") => Some("\t".to_string()),
+        Some("dsv") => Some(
+            format
+                .and_then(|format| format.get("delimiter")?.as_str())
+                .unwrap_or(",")
+                .to_string(),
+        ),
+        Some("csv") => Some(",".to_string()),
+        // Datasets of Vega-Lite's datasets property don't have a format
+        _ if serde_json::from_str::<Value>(values).is_ok() => None,
+        _ => Some(",".to_string()),
+    }
+}
+
+/// A number of the data, as it's written
+#[derive(Debug, Clone, Copy)]
+struct NumberInfo {
+    value: f64,
+    /// Whether the number is a whole number, which is kept whole
+    integer: bool,
+    /// Number of digits after the decimal point
+    decimals: usize,
+}
+
+struct Anonymizer<'a> {
+    opts: &'a AnonymizeOptions,
+    salt: String,
+    rng: SplitMix64,
+    /// Numbers of the data, while they're collected
+    numbers: Vec<NumberInfo>,
+    /// Perturbed values of the numbers of the data, keyed by the bits of their value
+    perturbed: HashMap<u64, f64>,
+    /// Hashes of the strings of the data, and the strings that they were made from
+    hashes: HashMap<String, String>,
+    /// Hashes of the strings of the data, keyed by the strings, when cardinality is preserved
+    strings: HashMap<String, String>,
+    /// Number of strings hashed so far, which makes the hashes of repeated strings differ
+    /// when cardinality isn't preserved
+    occurrences: u64,
+    date_shift_days: i64,
+}
+
+impl<'a> Anonymizer<'a> {
+    fn new(opts: &'a AnonymizeOptions, salt: String) -> Self {
+        let seed = Sha256::digest(salt.as_bytes());
+        let mut rng = SplitMix64(u64::from_le_bytes(seed[..8].try_into().unwrap()));
+        let mut date_shift_days =
+            (rng.next() % (2 * MAX_DATE_SHIFT_DAYS + 1)) as i64 - MAX_DATE_SHIFT_DAYS as i64;
+        if date_shift_days == 0 {
+            date_shift_days = 1;
+        }
+        Self {
+            opts,
+            salt,
+            rng,
+            numbers: Vec::new(),
+            perturbed: HashMap::new(),
+            hashes: HashMap::new(),
+            strings: HashMap::new(),
+            occurrences: 0,
+            date_shift_days,
+        }
+    }
+
+    fn collect_numbers(&mut self, values: &Value, format: Option<&Value>) {
+        match values {
+            Value::String(text) => match text_delimiter(text, format) {
+                None => {
+                    if let Ok(values) = serde_json::from_str::<Value>(text) {
+                        self.collect_value_numbers(&values);
+                    }
+                }
+                Some(delimiter) => {
+                    for row in parse_dsv(text, &delimiter).iter().skip(1) {
+                        for cell in row {
+                            self.collect_str_number(cell);
+                        }
+                    }
+                }
+            },
+            values => self.collect_value_numbers(values),
+        }
+    }
+
+    fn collect_value_numbers(&mut self, value: &Value) {
+        match value {
+            Value::Number(number) => {
+                if let Some(value) = number.as_f64() {
+                    let text = number.to_string();
+                    self.numbers.push(NumberInfo {
+                        value,
+                        integer: number.is_i64() || number.is_u64(),
+                        decimals: decimals(&text),
+                    })
+                }
+            }
+            Value::String(text) => self.collect_str_number(text),
+            Value::Array(items) => items
+                .iter()
+                .for_each(|item| self.collect_value_numbers(item)),
+            Value::Object(obj) => obj
+                .values()
+                .for_each(|value| self.collect_value_numbers(value)),
+            _ => {}
+        }
+    }
+
+    fn collect_str_number(&mut self, text: &str) {
+        if let Some(number) = self.str_number(text) {
+            self.numbers.push(number);
+        }
+    }
+
+    /// The number that a string holds, when numbers in strings are preserved
+    fn str_number(&self, text: &str) -> Option<NumberInfo> {
+        if !self.opts.preserve_types {
+            return None;
+        }
+        let captures = NUMBER_RE.captures(text)?;
+        let value = text.parse::<f64>().ok().filter(|value| value.is_finite())?;
+        Some(NumberInfo {
+            value,
+            integer: captures.get(1).is_none() && !text.contains(['e', 'E']),
+            decimals: decimals(text),
+        })
+    }
+
+    /// Choose the perturbed value of each collected number. Numbers are scaled by a random
+    /// factor and moved by a random fraction of the distance to their neighbors, so that
+    /// their order is kept. Whole numbers are rounded, and other numbers are rounded to the
+    /// number of decimals that they were written with, when that keeps their order
+    fn perturb_numbers(&mut self) {
+        let mut numbers = std::mem::take(&mut self.numbers);
+        numbers.sort_by(|a, b| a.value.total_cmp(&b.value));
+        // Merge the entries of the same value, which is whole when every entry is
+        let mut distinct: Vec<NumberInfo> = Vec::new();
+        for number in numbers {
+            match distinct.last_mut() {
+                Some(last) if last.value == number.value => {
+                    last.integer &= number.integer;
+                    last.decimals = last.decimals.max(number.decimals);
+                }
+                _ => distinct.push(number),
+            }
+        }
+
+        let factor = 1.05 + self.rng.next_f64() * 0.2;
+        let mut prev: Option<(f64, f64)> = None;
+        for (i, number) in distinct.iter().enumerate() {
+            let value = number.value;
+            let gap_prev = i.checked_sub(1).map(|j| value - distinct[j].value);
+            let gap_next = distinct.get(i + 1).map(|next| next.value - value);
+            let gap = match (gap_prev, gap_next) {
+                (Some(a), Some(b)) => a.min(b),
+                (Some(gap), None) | (None, Some(gap)) => gap,
+                (None, None) => f64::INFINITY,
+            }
+            .min(value.abs().max(1.0) * 0.25);
+            let scaled = value * factor + (self.rng.next_f64() - 0.5) * 0.8 * gap * factor;
+
+            let mut perturbed = if number.integer {
+                scaled.round()
+            } else {
+                round_decimals(scaled, number.decimals)
+            };
+            if let Some((prev_scaled, prev_perturbed)) = prev {
+                if perturbed <= prev_perturbed {
+                    perturbed = if number.integer {
+                        prev_perturbed.floor() + 1.0
+                    } else {
+                        prev_perturbed + (scaled - prev_scaled)
+                    };
+                }
+            }
+            prev = Some((scaled, perturbed));
+            self.perturbed.insert(value.to_bits(), perturbed);
+        }
+    }
+
+    fn rewrite_dataset(&mut self, values: &mut Value, format: Option<&Value>) {
+        match values {
+            Value::String(text) => match text_delimiter(text, format) {
+                None => {
+                    if let Ok(mut parsed) = serde_json::from_str::<Value>(text) {
+                        self.rewrite_value(&mut parsed, None);
+                        *text = parsed.to_string();
+                    } else {
+                        *text = self.hash(text);
+                    }
+                }
+                Some(delimiter) => {
+                    let mut rows = parse_dsv(text, &delimiter);
+                    for row in rows.iter_mut().skip(1) {
+                        for cell in row.iter_mut() {
+                            *cell = self.rewrite_str(cell);
+                        }
+                    }
+                    let trailing_newline = text.ends_with('\n');
+                    *text = write_dsv(&rows, &delimiter);
+                    if trailing_newline {
+                        text.push('\n');
+                    }
+                }
+            },
+            values => self.rewrite_value(values, None),
+        }
+    }
+
+    fn rewrite_value(&mut self, value: &mut Value, key: Option<&str>) {
+        match value {
+            Value::Number(number) => {
+                if let Some(perturbed) = number
+                    .as_f64()
+                    .and_then(|value| self.perturbed.get(&value.to_bits()))
+                {
+                    let perturbed = *perturbed;
+                    if let Some(number) = to_number(perturbed) {
+                        *value = Value::Number(number);
+                    }
+                }
+            }
+            Value::String(text) => {
+                if key == Some("type") && GEO_TYPES.contains(&text.as_str()) {
+                    return;
+                }
+                *text = self.rewrite_str(text);
+            }
+            Value::Array(items) => {
+                for item in items {
+                    self.rewrite_value(item, None);
+                }
+            }
+            Value::Object(obj) => {
+                for (key, value) in obj.iter_mut() {
+                    self.rewrite_value(value, Some(key));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Anonymize a string, keeping the type of the value that it holds when types are
+    /// preserved
+    fn rewrite_str(&mut self, text: &str) -> String {
+        if text.is_empty() {
+            return String::new();
+        }
+        if let Some(number) = self.str_number(text) {
+            if let Some(perturbed) = self.perturbed.get(&number.value.to_bits()) {
+                return if number.integer {
+                    format!("{}", *perturbed as i64)
+                } else {
+                    format!("{:.*}", number.decimals, perturbed)
+                };
+            }
+        }
+        if self.opts.preserve_types {
+            if let Some(date) = self.shift_date(text) {
+                return date;
+            }
+        }
+        self.hash(text)
+    }
+
+    /// Shift a date string by the date shift of the anonymizer, keeping its format
+    fn shift_date(&self, text: &str) -> Option<String> {
+        let captures = DATE_RE.captures(text)?;
+        let year: i64 = captures[1].parse().ok()?;
+        let month: u32 = captures[3].parse().ok()?;
+        let day: u32 = captures[5].parse().ok()?;
+        if !(1..=12).contains(&month) || day < 1 || day > days_in_month(year, month) {
+            return None;
+        }
+        let (year, month, day) =
+            civil_from_days(days_from_civil(year, month, day) + self.date_shift_days);
+        Some(format!(
+            "{year:04}{}{month:02}{}{day:02}{}",
+            &captures[2], &captures[4], &captures[6]
+        ))
+    }
+
+    /// Salted hash of a string. Equal strings get equal hashes, and different strings get
+    /// different hashes, when cardinality is preserved
+    fn hash(&mut self, text: &str) -> String {
+        if self.opts.preserve_cardinality {
+            if let Some(hash) = self.strings.get(text) {
+                return hash.clone();
+            }
+        }
+        self.occurrences += 1;
+        let mut hasher = Sha256::new();
+        hasher.update(self.salt.as_bytes());
+        hasher.update([0]);
+        hasher.update(text.as_bytes());
+        if !self.opts.preserve_cardinality {
+            hasher.update(self.occurrences.to_le_bytes());
+        }
+        let digest = format!("{:x}", hasher.finalize());
+        // Lengthen the hash until it's unique. The "s" prefix keeps hashes from being read
+        // as numbers
+        let mut len = 8;
+        let hash = loop {
+            let hash = format!("s{}", &digest[..len]);
+            match self.hashes.get(&hash) {
+                Some(other) if other != text && len < digest.len() => len += 2,
+                _ => break hash,
+            }
+        };
+        self.hashes.insert(hash.clone(), text.to_string());
+        if self.opts.preserve_cardinality {
+            self.strings.insert(text.to_string(), hash.clone());
+        }
+        hash
+    }
+}
+
+fn decimals(text: &str) -> usize {
+    match text.split_once('.') {
+        Some((_, fraction)) => fraction
+            .chars()
+            .take_while(|c| c.is_ascii_digit())
+            .count()
+            .min(15),
+        None => 0,
+    }
+}
+
+fn round_decimals(value: f64, decimals: usize) -> f64 {
+    format!("{value:.decimals$}").parse().unwrap_or(value)
+}
+
+fn to_number(value: f64) -> Option<Number> {
+    if value.fract() == 0.0 && value.abs() < 2f64.powi(53) {
+        Some(Number::from(value as i64))
+    } else {
+        Number::from_f64(value)
+    }
+}
+
+/// Split delimiter separated text into rows of cells. Cells may be quoted with double
+/// quotes, which are escaped by doubling them
+fn parse_dsv(text: &str, delimiter: &str) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut cell = String::new();
+    let mut quoted = false;
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        if quoted {
+            if rest.starts_with("\"\"") {
+                cell.push('"');
+                rest = &rest[2..];
+                continue;
+            } else if c == '"' {
+                quoted = false;
+            } else {
+                cell.push(c);
+            }
+        } else if c == '"' && cell.is_empty() {
+            quoted = true;
+        } else if !delimiter.is_empty() && rest.starts_with(delimiter) {
+            row.push(std::mem::take(&mut cell));
+            rest = &rest[delimiter.len()..];
+            continue;
+        } else if c == '\n' || c == '\r' {
+            row.push(std::mem::take(&mut cell));
+            rows.push(std::mem::take(&mut row));
+            if rest.starts_with("\r\n") {
+                rest = &rest[1..];
+            }
+        } else {
+            cell.push(c);
+        }
+        rest = &rest[c.len_utf8()..];
+    }
+    if !cell.is_empty() || !row.is_empty() {
+        row.push(cell);
+        rows.push(row);
+    }
+    rows
+}
+
+fn write_dsv(rows: &[Vec<String>], delimiter: &str) -> String {
+    rows.iter()
+        .map(|row| {
+            row.iter()
+                .map(|cell| {
+                    if cell.contains(delimiter) || cell.contains(['"', '\n', '\r']) {
+                        format!("\"{}\"", cell.replace('"', "\"\""))
+                    } else {
+                        cell.clone()
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(delimiter)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Days since 1970-01-01 of a date in the proleptic Gregorian calendar
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = month as i64;
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+/// Date of a number of days since 1970-01-01, as year, month, and day
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+/// Small seeded random number generator, so that a salt always anonymizes a spec the same way
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+
+    /// Random number in [0, 1)
+    fn next_f64(&mut self) -> f64 {
+        (self.next() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn salted() -> AnonymizeOptions {
+        AnonymizeOptions {
+            salt: Some("salt".to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_anonymize_strings() {
+        let spec = json!({
+            "data": {"values": [
+                {"name": "Alice", "team": "red"},
+                {"name": "Bob", "team": "red"},
+                {"name": "Carol", "team": "blue"}
+            ]},
+            "mark": "bar",
+            "encoding": {"x": {"field": "team", "type": "nominal"}}
+        });
+        let anon = anonymize_spec(&spec, &salted());
+        let values = anon["data"]["values"].as_array().unwrap();
+        for (row, original) in values
+            .iter()
+            .zip(spec["data"]["values"].as_array().unwrap())
+        {
+            assert_ne!(row["name"], original["name"]);
+            assert_ne!(row["team"], original["team"]);
+        }
+        // Groupings survive
+        assert_eq!(values[0]["team"], values[1]["team"]);
+        assert_ne!(values[0]["team"], values[2]["team"]);
+        assert_eq!(anon["encoding"], spec["encoding"]);
+        // The same salt anonymizes the same way
+        assert_eq!(anonymize_spec(&spec, &salted()), anon);
+
+        let anon = anonymize_spec(
+            &spec,
+            &AnonymizeOptions {
+                preserve_cardinality: false,
+                ..salted()
+            },
+        );
+        let values = anon["data"]["values"].as_array().unwrap();
+        assert_ne!(values[0]["team"], values[1]["team"]);
+    }
+
+    #[test]
+    fn test_anonymize_numbers() {
+        let numbers = [3, -10, 7, 7, 1000, 0, 8];
+        let spec = json!({"datasets": {"table": numbers.iter().map(|n| json!({"n": n})).collect::<Vec<_>>()}});
+        let anon = anonymize_spec(&spec, &salted());
+        let perturbed: Vec<i64> = anon["datasets"]["table"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|row| row["n"].as_i64().unwrap())
+            .collect();
+        for i in 0..numbers.len() {
+            for j in 0..numbers.len() {
+                assert_eq!(
+                    numbers[i].cmp(&numbers[j]),
+                    perturbed[i].cmp(&perturbed[j]),
+                    "{perturbed:?}"
+                );
+            }
+        }
+        assert!((900..1400).contains(&perturbed[4]), "{perturbed:?}");
+
+        let anon = anonymize_spec(
+            &json!({"data": {"values": [{"x": 1.25}, {"x": 2.5}]}}),
+            &salted(),
+        );
+        let x = anon["data"]["values"][0]["x"].as_f64().unwrap();
+        assert_ne!(x, 1.25);
+        assert!(x < anon["data"]["values"][1]["x"].as_f64().unwrap());
+    }
+
+    #[test]
+    fn test_anonymize_dates() {
+        let spec = json!({"data": {"values": [
+            {"date": "2024-02-28"},
+            {"date": "2024-03-01T12:30:00Z"},
+            {"date": "2024/13/01"}
+        ]}});
+        let anon = anonymize_spec(&spec, &salted());
+        let values = &anon["data"]["values"];
+        let date = values[0]["date"].as_str().unwrap();
+        let datetime = values[1]["date"].as_str().unwrap();
+        assert!(DATE_RE.is_match(date) && date != "2024-02-28", "{date}");
+        assert!(datetime.ends_with("T12:30:00Z") && datetime != "2024-03-01T12:30:00Z");
+        // Both dates are shifted by the same number of days, over the leap day
+        let days = |text: &str| {
+            days_from_civil(
+                text[..4].parse().unwrap(),
+                text[5..7].parse().unwrap(),
+                text[8..10].parse().unwrap(),
+            )
+        };
+        assert_eq!(days(datetime) - days(date), 2);
+        // Invalid dates are hashed
+        assert!(values[2]["date"].as_str().unwrap().starts_with('s'));
+
+        let anon = anonymize_spec(
+            &spec,
+            &AnonymizeOptions {
+                preserve_types: false,
+                ..salted()
+            },
+        );
+        assert!(anon["data"]["values"][0]["date"]
+            .as_str()
+            .unwrap()
+            .starts_with('s'));
+    }
+
+    #[test]
+    fn test_anonymize_csv() {
+        let spec = json!({
+            "data": [{
+                "name": "table",
+                "values": "city,\"pop, est\"\nParis,2100000\n\"Saint-Denis, FR\",113000\n",
+                "format": {"type": "csv"}
+            }]
+        });
+        let anon = anonymize_spec(&spec, &salted());
+        let csv = anon["data"][0]["values"].as_str().unwrap();
+        let rows = parse_dsv(csv, ",");
+        assert_eq!(rows[0], vec!["city", "pop, est"]);
+        assert_eq!(rows.len(), 3);
+        assert!(!csv.contains("Paris") && !csv.contains("Saint-Denis"));
+        let pop: Vec<i64> = rows[1..]
+            .iter()
+            .map(|row| row[1].parse().unwrap())
+            .collect();
+        assert!(pop[0] > pop[1] && pop[0] != 2100000, "{pop:?}");
+        assert!(csv.ends_with('\n'));
+    }
+
+    #[test]
+    fn test_civil_days() {
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(days_from_civil(2000, 3, 1), 11017);
+        for days in [-800_000, -1, 0, 59, 11016, 19_000] {
+            let (year, month, day) = civil_from_days(days);
+            assert_eq!(days_from_civil(year, month, day), days);
+        }
+    }
+}
@@ -0,0 +1,132 @@
+use crate::anyhow::anyhow;
+use deno_core::error::AnyError;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use tiny_skia::{ColorU8, Pixmap, PremultipliedColorU8};
+
+/// Color that the pixels of a rasterized chart are composited over, so that its transparent
+/// parts take that color rather than being left transparent (PNG) or turning black (JPEG).
+///
+/// Charts with a `background` of their own, which Vega-Lite charts have by default, are
+/// opaque already and aren't changed. Charts that set `"background": null` or
+/// `"background": "transparent"`, and the transparent parts of charts with a translucent
+/// background, take this color
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BackgroundColor {
+    pub red: u8,
+    pub green: u8,
+    pub blue: u8,
+    pub alpha: u8,
+}
+
+impl BackgroundColor {
+    pub const WHITE: Self = Self {
+        red: 255,
+        green: 255,
+        blue: 255,
+        alpha: 255,
+    };
+
+    /// Composite the pixels of a pixmap over the color
+    pub fn flatten(&self, pixmap: &mut Pixmap) {
+        let background =
+            ColorU8::from_rgba(self.red, self.green, self.blue, self.alpha).premultiply();
+        // Part of a premultiplied background channel that shows through a pixel of the
+        // given alpha
+        let through = |value: u8, alpha: u8| -> u8 {
+            ((value as u32 * (255 - alpha as u32) + 127) / 255) as u8
+        };
+        for pixel in pixmap.pixels_mut() {
+            let alpha = pixel.alpha();
+            if alpha == 255 {
+                continue;
+            }
+            if let Some(flattened) = PremultipliedColorU8::from_rgba(
+                pixel.red() + through(background.red(), alpha),
+                pixel.green() + through(background.green(), alpha),
+                pixel.blue() + through(background.blue(), alpha),
+                alpha + through(background.alpha(), alpha),
+            ) {
+                *pixel = flattened;
+            }
+        }
+    }
+}
+
+impl Default for BackgroundColor {
+    fn default() -> Self {
+        Self::WHITE
+    }
+}
+
+impl Display for BackgroundColor {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.red, self.green, self.blue)?;
+        if self.alpha != 255 {
+            write!(f, "{:02x}", self.alpha)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for BackgroundColor {
+    type Err = AnyError;
+
+    /// Parse a CSS color, e.g. "white", "#f0f0f0", "#0000", or "rgb(240, 240, 240)"
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let color = svgtypes::Color::from_str(s.trim())
+            .map_err(|_| anyhow!("Invalid background color: {:?}. Expected a CSS color", s))?;
+        Ok(Self {
+            red: color.red,
+            green: color.green,
+            blue: color.blue,
+            alpha: color.alpha,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_background_color() {
+        assert_eq!(
+            BackgroundColor::from_str("white").unwrap(),
+            BackgroundColor::WHITE
+        );
+        let color = BackgroundColor::from_str(" rgba(255, 0, 128, 0.5) ").unwrap();
+        assert_eq!((color.red, color.green, color.blue), (255, 0, 128));
+        assert_eq!(color.to_string(), "#ff008080");
+        assert_eq!(
+            BackgroundColor::from_str(&color.to_string()).unwrap(),
+            color
+        );
+        assert_eq!(
+            BackgroundColor::from_str("#FFF").unwrap().to_string(),
+            "#ffffff"
+        );
+        assert!(BackgroundColor::from_str("whiteish").is_err());
+    }
+
+    #[test]
+    fn test_flatten() {
+        let mut pixmap = Pixmap::new(3, 1).unwrap();
+        let pixels = pixmap.pixels_mut();
+        // Transparent, half-transparent red, and opaque blue
+        pixels[1] = PremultipliedColorU8::from_rgba(128, 0, 0, 128).unwrap();
+        pixels[2] = PremultipliedColorU8::from_rgba(0, 0, 255, 255).unwrap();
+
+        let background = BackgroundColor::from_str("#00ff00").unwrap();
+        background.flatten(&mut pixmap);
+        let colors: Vec<_> = pixmap
+            .pixels()
+            .iter()
+            .map(|p| (p.red(), p.green(), p.blue(), p.alpha()))
+            .collect();
+        assert_eq!(
+            colors,
+            vec![(0, 255, 0, 255), (128, 127, 0, 255), (0, 0, 255, 255)]
+        );
+    }
+}
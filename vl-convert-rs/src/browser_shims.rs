@@ -0,0 +1,61 @@
+use crate::converter::FormatLocale;
+use serde_json::json;
+
+/// Language of `navigator.language` when the conversion has no named format locale
+pub const DEFAULT_BROWSER_LANGUAGE: &str = "en-US";
+
+/// JavaScript literal for the browser globals that expressions of the conversion can read, as
+/// `{language, devicePixelRatio}`. `null` when `browser_shims` isn't set.
+///
+/// Expressions copied from web pages sometimes read `window`, `navigator.language`,
+/// `screen.width`, or `devicePixelRatio`, which aren't defined in headless conversion. With
+/// the shims, they read:
+///
+/// - `navigator.language` (and `navigator.languages`): the name of the format locale (e.g.
+///   "de-DE"), or [`DEFAULT_BROWSER_LANGUAGE`]
+/// - `devicePixelRatio`: `device_pixel_ratio`, which is the scale of the image for raster
+///   conversions (e.g. 2 for a PNG image at scale 2), and 1 otherwise
+/// - `screen.width` and `screen.height` (and `availWidth` and `availHeight`): the width and
+///   height of the spec, or the container size when they aren't numbers
+/// - `window`: an object with the `navigator`, `screen`, and `devicePixelRatio` above, and
+///   `innerWidth` and `innerHeight` set to the screen size
+///
+/// Only the globals that the expressions of the spec mention are defined
+pub(crate) fn browser_shims_arg(
+    browser_shims: bool,
+    format_locale: Option<&FormatLocale>,
+    device_pixel_ratio: f32,
+) -> String {
+    if !browser_shims {
+        return "null".to_string();
+    }
+    let language = match format_locale {
+        Some(FormatLocale::Name(name)) => name.as_str(),
+        _ => DEFAULT_BROWSER_LANGUAGE,
+    };
+    json!({
+        "language": language,
+        "devicePixelRatio": device_pixel_ratio,
+    })
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_browser_shims_arg() {
+        let parse = |arg: String| serde_json::from_str::<serde_json::Value>(&arg).unwrap();
+        assert_eq!(browser_shims_arg(false, None, 1.0), "null");
+        assert_eq!(
+            parse(browser_shims_arg(true, None, 1.0)),
+            json!({"language": "en-US", "devicePixelRatio": 1.0})
+        );
+        let format_locale = FormatLocale::Name("de-DE".to_string());
+        assert_eq!(
+            parse(browser_shims_arg(true, Some(&format_locale), 2.0)),
+            json!({"language": "de-DE", "devicePixelRatio": 2.0})
+        );
+    }
+}
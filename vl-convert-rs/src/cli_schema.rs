@@ -0,0 +1,14 @@
+use serde_json::Value;
+
+/// Description of the vl-convert command line interface, as printed by
+/// `vl-convert introspect --format json`. Regenerate it with
+/// `vl-convert introspect --format json > vl-convert-rs/src/cli_schema.json` when the
+/// interface changes
+const CLI_SCHEMA: &str = include_str!("cli_schema.json");
+
+/// Get the description of the subcommands and options of the vl-convert command line
+/// interface, with their types, defaults, and help text, for wrappers that build vl-convert
+/// command lines
+pub fn get_cli_schema() -> Value {
+    serde_json::from_str(CLI_SCHEMA).expect("Failed to parse embedded CLI schema")
+}
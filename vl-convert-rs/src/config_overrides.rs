@@ -0,0 +1,86 @@
+use crate::anyhow::bail;
+use crate::text_style::merge_config;
+use deno_core::error::AnyError;
+use serde_json::{Map, Value};
+
+/// Parse a `path=value` config override, like those given with the --set flag of the CLI.
+/// The path is a dotted path into the Vega-Lite config (e.g. `axis.labelFontSize`), and the
+/// value is parsed as JSON, falling back to the value as a string when it isn't valid JSON
+pub fn parse_config_override(arg: &str) -> Result<(String, Value), AnyError> {
+    let Some((path, value)) = arg.split_once('=') else {
+        bail!("Invalid config override {arg:?}, expected path=value")
+    };
+    let path = path.trim();
+    if path.split('.').any(str::is_empty) {
+        bail!("Invalid config override path {path:?}")
+    }
+    let value = serde_json::from_str(value).unwrap_or_else(|_| Value::String(value.to_string()));
+    Ok((path.to_string(), value))
+}
+
+/// Set each override at its path in `config`, in order, creating intermediate objects. Object
+/// values are merged into the object at their path, and other values replace it
+pub(crate) fn apply_config_overrides(config: &mut Value, overrides: &[(String, Value)]) {
+    for (path, value) in overrides {
+        let mut target = &mut *config;
+        for key in path.split('.') {
+            if !target.is_object() {
+                *target = Value::Object(Map::new());
+            }
+            let Value::Object(object) = target else {
+                unreachable!()
+            };
+            target = object.entry(key.to_string()).or_insert(Value::Null);
+        }
+        merge_config(target, value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_config_override() {
+        assert_eq!(
+            parse_config_override("axis.labelFontSize=14").unwrap(),
+            ("axis.labelFontSize".to_string(), json!(14))
+        );
+        assert_eq!(
+            parse_config_override(r##"range.category=["#111","#222"]"##).unwrap(),
+            ("range.category".to_string(), json!(["#111", "#222"]))
+        );
+        // Values that aren't JSON are strings, and may contain '='
+        assert_eq!(
+            parse_config_override("title.font=Roboto Slab").unwrap().1,
+            json!("Roboto Slab")
+        );
+        assert_eq!(parse_config_override("a=b=c").unwrap().1, json!("b=c"));
+        assert!(parse_config_override("axis.labelFontSize").is_err());
+        assert!(parse_config_override("axis..labelFontSize=14").is_err());
+        assert!(parse_config_override("=14").is_err());
+    }
+
+    #[test]
+    fn test_apply_config_overrides() {
+        let mut config = json!({"axis": {"labelColor": "red"}, "background": "white"});
+        apply_config_overrides(
+            &mut config,
+            &[
+                ("axis.labelFontSize".to_string(), json!(14)),
+                ("legend.title.font".to_string(), json!("Roboto")),
+                ("background.fill".to_string(), json!("#eee")),
+                ("axis".to_string(), json!({"labelFontSize": 16})),
+            ],
+        );
+        assert_eq!(
+            config,
+            json!({
+                "axis": {"labelColor": "red", "labelFontSize": 16},
+                "legend": {"title": {"font": "Roboto"}},
+                "background": {"fill": "#eee"}
+            })
+        );
+    }
+}
@@ -0,0 +1,42 @@
+use crate::anyhow::bail;
+use deno_core::error::AnyError;
+use serde::Serialize;
+
+/// Width of the container that charts sized to their container (e.g. with Vega-Lite's
+/// `"width": "container"`) are rendered in, when no container width is given
+pub const DEFAULT_CONTAINER_WIDTH: f32 = 600.0;
+
+/// Height of the container that charts sized to their container are rendered in, when no
+/// container height is given
+pub const DEFAULT_CONTAINER_HEIGHT: f32 = 400.0;
+
+/// Container that charts sized to their container are rendered in. There is no container in
+/// headless conversion, so its size comes from the container_width and container_height
+/// options, or container sizing is an error when `strict` is set
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub(crate) struct ContainerSize {
+    pub width: f32,
+    pub height: f32,
+    pub strict: bool,
+}
+
+impl ContainerSize {
+    pub fn try_new(
+        width: Option<f32>,
+        height: Option<f32>,
+        strict: bool,
+    ) -> Result<Self, AnyError> {
+        let width = width.unwrap_or(DEFAULT_CONTAINER_WIDTH);
+        let height = height.unwrap_or(DEFAULT_CONTAINER_HEIGHT);
+        for (name, value) in [("container_width", width), ("container_height", height)] {
+            if !value.is_finite() || value <= 0.0 {
+                bail!("{name} must be a positive number, got {value}")
+            }
+        }
+        Ok(Self {
+            width,
+            height,
+            strict,
+        })
+    }
+}
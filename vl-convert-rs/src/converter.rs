@@ -6,39 +6,101 @@ use crate::module_loader::{
 use deno_core::op2;
 use deno_runtime::deno_core;
 use deno_runtime::deno_core::anyhow::bail;
-use deno_runtime::deno_core::error::AnyError;
-use deno_runtime::deno_core::{serde_v8, v8};
+use deno_runtime::deno_core::error::{AnyError, JsError};
+use deno_runtime::deno_core::{serde_v8, v8, OpState};
 use deno_runtime::deno_permissions::{Permissions, PermissionsContainer};
 use deno_runtime::worker::MainWorker;
 use deno_runtime::worker::WorkerOptions;
 use std::collections::hash_map::Entry;
 use std::collections::{HashMap, HashSet};
 use std::fmt::{Debug, Display, Formatter};
-use std::io::Cursor;
+use std::io::{Cursor, Write};
 use std::path::Path;
 use std::rc::Rc;
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, OnceLock};
 
 use std::panic;
 use std::str::FromStr;
 use std::thread;
 use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
 
 use crate::anyhow::anyhow;
+use crate::background::BackgroundColor;
+use crate::browser_shims::browser_shims_arg;
+use flate2::write::ZlibEncoder;
 use futures::channel::{mpsc, mpsc::Sender, oneshot};
 use futures_util::{SinkExt, StreamExt};
 use png::{PixelDimensions, Unit};
 use svg2pdf::{ConversionOptions, PageOptions};
 use tiny_skia::{Pixmap, PremultipliedColorU8};
 
-use crate::html::{bundle_vega_snippet, get_vega_or_vegalite_script};
+use crate::config_overrides::apply_config_overrides;
+use crate::container_size::ContainerSize;
+use crate::data_export::ExportedDataset;
+use crate::dataflow_error::DataflowError;
+use crate::dataset_summary::DatasetSummary;
+use crate::direction::{mirror_padding, rtl_config, Direction};
+use crate::facets::{crop_facet_pngs, FacetCells, FacetImage, FacetOpts};
+use crate::force_layout::with_static_force;
+use crate::health::{ConversionCounters, HealthReport, HEALTH_CHECK_TIMEOUT};
+use crate::html::{
+    check_html_template, check_spec_urls, escape_html, get_chart_html, get_chart_script,
+    get_csp_meta, get_vegaembed_bundle,
+};
+use crate::image_loading::{with_fetch_retry, with_lenient_images, FetchRetry};
+use crate::image_rendering::{normalize_image_rendering, ImageRendering};
+use crate::int64::Int64Strings;
+use crate::lenient_dates::with_lenient_dates;
+use crate::log_level::{view_log_arg, LogLevel};
+use crate::output_format::{
+    builtin_output_formats, JpegFormat, OutputFormat, PdfFormat, PngFormat, RenderContext,
+};
+use crate::pdfa::svg_tree_to_pdfa;
+use crate::pre_aggregate::pre_aggregate_spec;
+use crate::render_warnings::{extend_render_warnings, RenderWarning};
+use crate::request_hash::{vega_request_hash, vegalite_request_hash};
+use crate::resize::{with_size_override, ResizeMode};
+use crate::resource_stats::{
+    record_pixmap_stats, set_last_resource_stats, ResourceStats, RssSample,
+};
+use crate::row_limit::{row_limit_arg, RowLimitStrategy};
+use crate::spec_limits::get_spec_limits;
+use crate::static_export::{
+    analyze_static_export, vega_has_tooltips, StaticExportFinding, StaticExportSeverity,
+};
+use crate::stroke_dash::clamp_stroke_dasharrays;
+use crate::suggestions::{format_locale_names, time_format_locale_names, unknown_name_message};
+use crate::suppress::{suppress_vega_components, suppress_vegalite_components, Component};
+use crate::text_style::{merge_config, text_style_config};
+use crate::topojson::{op_simplify_topojson, simplify_topojson_arg};
 use image::codecs::jpeg::JpegEncoder;
 use image::io::Reader as ImageReader;
+use image::{ImageFormat, RgbImage};
+use regex::{Captures, Regex};
 use resvg::render;
 
-use crate::text::{vl_convert_text_runtime, USVG_OPTIONS};
-
-deno_core::extension!(vl_convert_converter_runtime, ops = [op_get_json_arg]);
+use crate::svg_minify::{minify_svg, DEFAULT_SVG_PRECISION};
+use crate::text::{
+    add_svg_text_length, check_generic_font_mapping, has_color_glyphs, vl_convert_text_runtime,
+    USVG_OPTIONS,
+};
+#[cfg(feature = "verify-vendor")]
+use crate::vendor_integrity::ensure_vendor_integrity;
+use crate::LOG_TARGET;
+
+#[rustfmt::skip]
+deno_core::extension!(vl_convert_converter_runtime, ops = [op_get_json_arg, op_log, op_call_expression_fn, op_simplify_topojson]);
+
+/// V8 snapshot of the Deno runtime with Vega, vega-themes, and the default Vega-Lite version
+/// evaluated, which build.rs creates with the snapshot feature
+#[cfg(feature = "snapshot")]
+static RUNTIME_SNAPSHOT: Option<&[u8]> = Some(include_bytes!(concat!(
+    env!("OUT_DIR"),
+    "/VL_CONVERT_SNAPSHOT.bin"
+)));
+#[cfg(not(feature = "snapshot"))]
+static RUNTIME_SNAPSHOT: Option<&[u8]> = None;
 
 lazy_static! {
     pub static ref TOKIO_RUNTIME: tokio::runtime::Runtime =
@@ -46,18 +108,150 @@ lazy_static! {
             .enable_all()
             .build()
             .unwrap();
-    static ref JSON_ARGS: Arc<Mutex<HashMap<i32, String>>> = Arc::new(Mutex::new(HashMap::new()));
+    /// Runtime that drives the health check timeouts of callers that aren't running on a
+    /// tokio runtime
+    static ref TIMER_RUNTIME: tokio::runtime::Runtime =
+        tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .enable_time()
+            .build()
+            .unwrap();
+    static ref JSON_ARGS: Arc<Mutex<HashMap<i32, JsonArg>>> = Arc::new(Mutex::new(HashMap::new()));
     static ref NEXT_ARG_ID: Arc<Mutex<i32>> = Arc::new(Mutex::new(0));
+    static ref SVG_SIZE_ATTR_RE: Regex = Regex::new(r#"\s(width|height|viewBox)="[^"]*""#).unwrap();
 }
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone)]
 pub struct VgOpts {
+    pub theme: Option<String>,
+    pub config: Option<serde_json::Value>,
     pub allowed_base_urls: Option<Vec<String>>,
     pub format_locale: Option<FormatLocale>,
     pub time_format_locale: Option<TimeFormatLocale>,
+    pub collect_resource_stats: bool,
+    pub collect_dataset_summary: bool,
+    pub fetch_retries: Option<u32>,
+    pub fetch_retry_backoff_ms: Option<u64>,
+    pub interaction_script: Option<Vec<InteractionStep>>,
+    pub preserve_int64: bool,
+    pub base_url: Option<String>,
+    pub allow_local_data: bool,
+    pub lenient: bool,
+    /// Fonts that the generic font families (e.g. "sans-serif") are rendered with
+    pub generic_font_mapping: Option<HashMap<String, String>>,
+    /// Width of the container that charts sized to their container are rendered in.
+    /// Defaults to DEFAULT_CONTAINER_WIDTH
+    pub container_width: Option<f32>,
+    /// Height of the container that charts sized to their container are rendered in.
+    /// Defaults to DEFAULT_CONTAINER_HEIGHT
+    pub container_height: Option<f32>,
+    /// Fail the conversion of charts sized to their container, rather than rendering them
+    /// with the container width and height
+    pub strict_sizing: bool,
+    /// Width that the chart is rendered with, instead of the width of the spec
+    pub width: Option<f32>,
+    /// Height that the chart is rendered with, instead of the height of the spec
+    pub height: Option<f32>,
+    /// How the chart is laid out in `width` and `height` when either is set
+    pub resize_mode: ResizeMode,
+    /// Smallest width and height that the chart is rendered with. Charts that resolve to a
+    /// size below 1 pixel (e.g. with empty data) are enlarged to it, rather than failing the
+    /// conversion
+    pub min_size: Option<(u32, u32)>,
+    /// Largest number of rows that a dataset of the chart may have once it's loaded,
+    /// including inline data and data fetched from urls
+    pub max_rows: Option<u64>,
+    /// How datasets with more than `max_rows` rows are handled
+    pub row_limit_strategy: RowLimitStrategy,
+    /// Add a subtitle that notes the truncation to charts with datasets that were truncated
+    /// to `max_rows` rows
+    pub annotate_truncation: bool,
+    /// Level of the messages that the Vega view logs while the chart is rendered. Warnings
+    /// at that level are collected with the render warnings. When None, the view logs
+    /// nothing
+    pub log_level: Option<LogLevel>,
+    /// Filters of the collected warnings, as substrings or `/regex/` patterns. Matching
+    /// warnings are left out and counted in a summary warning instead
+    pub warning_filters: Option<Vec<String>>,
+    /// Components of the chart that are left out of its image, for applications that draw
+    /// them themselves. The chart is laid out without them, so that the plot area takes up
+    /// the space they would have used
+    pub suppress_components: Option<Vec<Component>>,
+    /// Define the browser globals that expressions copied from web pages read, like
+    /// `navigator.language` or `screen.width`, with the values that are documented at
+    /// [`crate::browser_shims::browser_shims_arg`]. Expressions that read other browser
+    /// APIs fail with an error that names them. Defaults to true
+    pub browser_shims: bool,
+    /// Simplify the arcs of TopoJSON datasets as they're parsed, dropping the vertices that
+    /// are within this tolerance of the simplified lines, in the units of the topology's
+    /// coordinates (see [`crate::topojson::simplify_topology`]). Borders that geometries share
+    /// stay shared. The reduction in the number of vertices is reported as a render warning
+    pub simplify_topojson: Option<f64>,
+    /// Run force transforms as static simulations of this many ticks before the chart is
+    /// rendered. Force transforms that set `static` or `iterations` themselves keep them
+    pub force_iterations: Option<u32>,
+    /// Seed for the random numbers of the render, which are used by the random() expression
+    /// function and by transforms like sample. When None, they're seeded by the runtime
+    pub random_seed: Option<u32>,
+    /// Function that the generated SVG is passed through before it's returned or rasterized
+    pub svg_post_processor: Option<SvgPostProcessor>,
+}
+
+impl Default for VgOpts {
+    fn default() -> Self {
+        Self {
+            theme: None,
+            config: None,
+            allowed_base_urls: None,
+            format_locale: None,
+            time_format_locale: None,
+            collect_resource_stats: false,
+            collect_dataset_summary: false,
+            fetch_retries: None,
+            fetch_retry_backoff_ms: None,
+            interaction_script: None,
+            preserve_int64: false,
+            base_url: None,
+            allow_local_data: false,
+            lenient: false,
+            generic_font_mapping: None,
+            container_width: None,
+            container_height: None,
+            strict_sizing: false,
+            width: None,
+            height: None,
+            resize_mode: ResizeMode::default(),
+            min_size: None,
+            max_rows: None,
+            row_limit_strategy: RowLimitStrategy::default(),
+            annotate_truncation: false,
+            log_level: None,
+            warning_filters: None,
+            suppress_components: None,
+            browser_shims: true,
+            simplify_topojson: None,
+            force_iterations: None,
+            random_seed: None,
+            svg_post_processor: None,
+        }
+    }
 }
 
 impl VgOpts {
+    pub(crate) fn container_size(&self) -> Result<ContainerSize, AnyError> {
+        ContainerSize::try_new(
+            self.container_width,
+            self.container_height,
+            self.strict_sizing,
+        )
+    }
+
+    /// Whether the warnings that the view logs or that match the warning filters are
+    /// collected with the render warnings
+    pub(crate) fn collects_view_warnings(&self) -> bool {
+        self.log_level.is_some() || self.warning_filters.is_some()
+    }
+
     pub fn to_embed_opts(&self, renderer: Renderer) -> Result<serde_json::Value, AnyError> {
         let mut opts_map = serde_json::Map::new();
 
@@ -66,6 +260,15 @@ impl VgOpts {
             serde_json::Value::String(renderer.to_string()),
         );
 
+        if let Some(theme) = &self.theme {
+            opts_map.insert(
+                "theme".to_string(),
+                serde_json::Value::String(theme.clone()),
+            );
+        }
+        if let Some(config) = &self.config {
+            opts_map.insert("config".to_string(), config.clone());
+        }
         if let Some(format_locale) = &self.format_locale {
             opts_map.insert("formatLocale".to_string(), format_locale.as_object()?);
         }
@@ -80,6 +283,20 @@ impl VgOpts {
     }
 }
 
+/// A step of an interaction script that's applied to a chart before it's rendered
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(untagged)]
+pub enum InteractionStep {
+    /// Set the value of a named signal, e.g. `{"signal": "brush_x", "value": [0, 50]}`
+    Signal {
+        signal: String,
+        value: serde_json::Value,
+    },
+    /// Dispatch an event at a position in view coordinates, e.g.
+    /// `{"event": {"type": "pointerdown", "x": 10, "y": 20}}`
+    Event { event: serde_json::Value },
+}
+
 #[derive(Debug, Clone)]
 pub enum FormatLocale {
     Name(String),
@@ -91,7 +308,11 @@ impl FormatLocale {
         match self {
             FormatLocale::Name(name) => {
                 let Some(locale_str) = FORMATE_LOCALE_MAP.get(name) else {
-                    return Err(anyhow!("No built-in format locale named {}", name));
+                    return Err(anyhow!(unknown_name_message(
+                        "format locale",
+                        name,
+                        &format_locale_names()
+                    )));
                 };
                 Ok(serde_json::from_str(locale_str)?)
             }
@@ -111,7 +332,11 @@ impl TimeFormatLocale {
         match self {
             TimeFormatLocale::Name(name) => {
                 let Some(locale_str) = TIME_FORMATE_LOCALE_MAP.get(name) else {
-                    return Err(anyhow!("No built-in time format locale named {}", name));
+                    return Err(anyhow!(unknown_name_message(
+                        "time format locale",
+                        name,
+                        &time_format_locale_names()
+                    )));
                 };
                 Ok(serde_json::from_str(locale_str)?)
             }
@@ -151,7 +376,7 @@ impl FromStr for Renderer {
     }
 }
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone)]
 pub struct VlOpts {
     pub config: Option<serde_json::Value>,
     pub theme: Option<String>,
@@ -160,9 +385,179 @@ pub struct VlOpts {
     pub allowed_base_urls: Option<Vec<String>>,
     pub format_locale: Option<FormatLocale>,
     pub time_format_locale: Option<TimeFormatLocale>,
+    pub collect_resource_stats: bool,
+    pub collect_dataset_summary: bool,
+    pub fetch_retries: Option<u32>,
+    pub fetch_retry_backoff_ms: Option<u64>,
+    pub interaction_script: Option<Vec<InteractionStep>>,
+    pub preserve_int64: bool,
+    pub base_url: Option<String>,
+    pub allow_local_data: bool,
+    pub lenient: bool,
+    /// Font of the text of axes, legends, headers, titles, and text marks
+    pub font: Option<String>,
+    /// Factor that the default font sizes of that text are multiplied by
+    pub font_size_scale: Option<f32>,
+    /// Color of that text
+    pub text_color: Option<String>,
+    /// Values to set at dotted paths of the config (e.g. "axis.labelFontSize"), in order,
+    /// over the config and theme
+    pub config_overrides: Option<Vec<(String, serde_json::Value)>>,
+    /// Writing direction to lay charts out for. With Rtl, the layout is mirrored for
+    /// right-to-left locales through config that's applied beneath `config`
+    pub direction: Option<Direction>,
+    /// Fonts that the generic font families (e.g. "sans-serif") are rendered with
+    pub generic_font_mapping: Option<HashMap<String, String>>,
+    /// Width of the container that charts sized to their container are rendered in.
+    /// Defaults to DEFAULT_CONTAINER_WIDTH
+    pub container_width: Option<f32>,
+    /// Height of the container that charts sized to their container are rendered in.
+    /// Defaults to DEFAULT_CONTAINER_HEIGHT
+    pub container_height: Option<f32>,
+    /// Fail the conversion of charts sized to their container, rather than rendering them
+    /// with the container width and height
+    pub strict_sizing: bool,
+    /// Width that the chart is rendered with, instead of the width of the spec
+    pub width: Option<f32>,
+    /// Height that the chart is rendered with, instead of the height of the spec
+    pub height: Option<f32>,
+    /// How the chart is laid out in `width` and `height` when either is set
+    pub resize_mode: ResizeMode,
+    /// Smallest width and height that the chart is rendered with. Charts that resolve to a
+    /// size below 1 pixel (e.g. with empty data) are enlarged to it, rather than failing the
+    /// conversion
+    pub min_size: Option<(u32, u32)>,
+    /// Largest number of rows that a dataset of the chart may have once it's loaded,
+    /// including inline data and data fetched from urls
+    pub max_rows: Option<u64>,
+    /// How datasets with more than `max_rows` rows are handled
+    pub row_limit_strategy: RowLimitStrategy,
+    /// Add a subtitle that notes the truncation to charts with datasets that were truncated
+    /// to `max_rows` rows
+    pub annotate_truncation: bool,
+    /// Level of the messages that the Vega view logs while the chart is rendered. Warnings
+    /// at that level are collected with the render warnings. When None, the view logs
+    /// nothing
+    pub log_level: Option<LogLevel>,
+    /// Filters of the collected warnings, as substrings or `/regex/` patterns. Matching
+    /// warnings are left out and counted in a summary warning instead
+    pub warning_filters: Option<Vec<String>>,
+    /// Components of the chart that are left out of its image, for applications that draw
+    /// them themselves. The chart is laid out without them, so that the plot area takes up
+    /// the space they would have used
+    pub suppress_components: Option<Vec<Component>>,
+    /// Define the browser globals that expressions copied from web pages read, like
+    /// `navigator.language` or `screen.width`, with the values that are documented at
+    /// [`crate::browser_shims::browser_shims_arg`]. Expressions that read other browser
+    /// APIs fail with an error that names them. Defaults to true
+    pub browser_shims: bool,
+    /// Simplify the arcs of TopoJSON datasets as they're parsed, dropping the vertices that
+    /// are within this tolerance of the simplified lines, in the units of the topology's
+    /// coordinates (see [`crate::topojson::simplify_topology`]). Borders that geometries share
+    /// stay shared. The reduction in the number of vertices is reported as a render warning
+    pub simplify_topojson: Option<f64>,
+    /// Bin and aggregate the inline data of histogram-like charts in Rust before they're
+    /// rendered, rather than in JavaScript. Charts that aren't supported are rendered as usual
+    pub pre_aggregate: bool,
+    /// Rewrite the dates of the temporal fields of the spec's inline data that aren't in the
+    /// ISO 8601 format, like `03/27/2024 5:00 PM`, to ISO 8601 before the chart is rendered.
+    /// See [`crate::lenient_dates::LENIENT_DATE_FORMATS`] for the formats that are parsed
+    pub lenient_dates: bool,
+    /// Function that the generated SVG is passed through before it's returned or rasterized
+    pub svg_post_processor: Option<SvgPostProcessor>,
+}
+
+impl Default for VlOpts {
+    fn default() -> Self {
+        Self {
+            config: None,
+            theme: None,
+            vl_version: VlVersion::default(),
+            show_warnings: false,
+            allowed_base_urls: None,
+            format_locale: None,
+            time_format_locale: None,
+            collect_resource_stats: false,
+            collect_dataset_summary: false,
+            fetch_retries: None,
+            fetch_retry_backoff_ms: None,
+            interaction_script: None,
+            preserve_int64: false,
+            base_url: None,
+            allow_local_data: false,
+            lenient: false,
+            font: None,
+            font_size_scale: None,
+            text_color: None,
+            config_overrides: None,
+            direction: None,
+            generic_font_mapping: None,
+            container_width: None,
+            container_height: None,
+            strict_sizing: false,
+            width: None,
+            height: None,
+            resize_mode: ResizeMode::default(),
+            min_size: None,
+            max_rows: None,
+            row_limit_strategy: RowLimitStrategy::default(),
+            annotate_truncation: false,
+            log_level: None,
+            warning_filters: None,
+            suppress_components: None,
+            browser_shims: true,
+            simplify_topojson: None,
+            pre_aggregate: false,
+            lenient_dates: false,
+            svg_post_processor: None,
+        }
+    }
 }
 
 impl VlOpts {
+    pub(crate) fn container_size(&self) -> Result<ContainerSize, AnyError> {
+        ContainerSize::try_new(
+            self.container_width,
+            self.container_height,
+            self.strict_sizing,
+        )
+    }
+
+    /// Whether the warnings that the view logs or that match the warning filters are
+    /// collected with the render warnings
+    pub(crate) fn collects_view_warnings(&self) -> bool {
+        self.log_level.is_some() || self.warning_filters.is_some()
+    }
+
+    /// The config that's passed to Vega-Lite, with the font, font_size_scale, text_color, and
+    /// direction options expanded into it. Properties that are set in `config` take
+    /// precedence, and `config_overrides` are applied last
+    pub fn effective_config(&self) -> Option<serde_json::Value> {
+        let style_config = text_style_config(
+            self.font.as_deref(),
+            self.font_size_scale,
+            self.text_color.as_deref(),
+        );
+        let rtl = self.direction == Some(Direction::Rtl);
+        if style_config.is_none() && self.config_overrides.is_none() && !rtl {
+            return self.config.clone();
+        }
+        let mut config = style_config.unwrap_or_else(|| serde_json::json!({}));
+        if rtl {
+            merge_config(&mut config, &rtl_config());
+        }
+        if let Some(user_config) = &self.config {
+            merge_config(&mut config, user_config);
+        }
+        if rtl {
+            mirror_padding(&mut config);
+        }
+        if let Some(overrides) = &self.config_overrides {
+            apply_config_overrides(&mut config, overrides);
+        }
+        Some(config)
+    }
+
     pub fn to_embed_opts(&self, renderer: Renderer) -> Result<serde_json::Value, AnyError> {
         let mut opts_map = serde_json::Map::new();
 
@@ -178,8 +573,8 @@ impl VlOpts {
             );
         }
 
-        if let Some(config) = &self.config {
-            opts_map.insert("config".to_string(), config.clone());
+        if let Some(config) = self.effective_config() {
+            opts_map.insert("config".to_string(), config);
         }
 
         if let Some(format_locale) = &self.format_locale {
@@ -196,7 +591,28 @@ impl VlOpts {
     }
 }
 
+/// Args whose serialized size exceeds this many bytes are passed to JavaScript as structured
+/// values, which avoids building and then parsing a second copy of a very large JSON string
+const STRUCTURED_JSON_ARG_BYTES: usize = 16 * 1024 * 1024;
+
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(untagged)]
+enum JsonArg {
+    /// Serialized JSON, parsed with JSON.parse on the JavaScript side
+    Text(String),
+    /// Object or array converted directly to JavaScript values by serde_v8
+    Structured(serde_json::Value),
+}
+
 fn set_json_arg(arg: serde_json::Value) -> Result<i32, AnyError> {
+    // Enforce spec limits before the arg reaches the JavaScript runtime
+    let arg_bytes = get_spec_limits()?.check_json_value(&arg)?;
+    let arg = if arg_bytes > STRUCTURED_JSON_ARG_BYTES && (arg.is_object() || arg.is_array()) {
+        JsonArg::Structured(arg)
+    } else {
+        JsonArg::Text(serde_json::to_string(&arg)?)
+    };
+
     // Increment arg id
     let id = match NEXT_ARG_ID.lock() {
         Ok(mut guard) => {
@@ -212,7 +628,7 @@ fn set_json_arg(arg: serde_json::Value) -> Result<i32, AnyError> {
     // Add Arg at id to args
     match JSON_ARGS.lock() {
         Ok(mut guard) => {
-            guard.insert(id, serde_json::to_string(&arg).unwrap());
+            guard.insert(id, arg);
         }
         Err(err) => {
             bail!("Failed to acquire lock: {}", err.to_string())
@@ -222,9 +638,35 @@ fn set_json_arg(arg: serde_json::Value) -> Result<i32, AnyError> {
     Ok(id)
 }
 
+/// JavaScript literal for the data base URL, local file policy, and lenient option that's
+/// passed to vegaToView
+fn data_access_arg(
+    base_url: &Option<String>,
+    allow_local_data: bool,
+    lenient: bool,
+) -> Result<String, AnyError> {
+    Ok(format!(
+        "{{baseURL: {}, allowLocalData: {}, lenient: {}}}",
+        serde_json::to_string(base_url)?,
+        allow_local_data,
+        lenient
+    ))
+}
+
+/// JavaScript literal for the fetch retry policy that's passed to vegaToView
+fn fetch_retry_arg(fetch_retry: Option<FetchRetry>) -> String {
+    match fetch_retry {
+        None => "null".to_string(),
+        Some(fetch_retry) => format!(
+            "{{retries: {}, backoffMs: {}}}",
+            fetch_retry.retries, fetch_retry.backoff_ms
+        ),
+    }
+}
+
 #[op2]
-#[string]
-fn op_get_json_arg(arg_id: i32) -> Result<String, AnyError> {
+#[serde]
+fn op_get_json_arg(arg_id: i32) -> Result<JsonArg, AnyError> {
     match JSON_ARGS.lock() {
         Ok(mut guard) => {
             if let Some(arg) = guard.remove(&arg_id) {
@@ -239,34 +681,177 @@ fn op_get_json_arg(arg_id: i32) -> Result<String, AnyError> {
     }
 }
 
+/// Forward a message from the JavaScript console to the `log` crate
+#[op2(fast)]
+fn op_log(#[string] level: String, #[string] message: String) {
+    let level = match level.as_str() {
+        "error" => log::Level::Error,
+        "warn" => log::Level::Warn,
+        "info" => log::Level::Info,
+        "debug" => log::Level::Debug,
+        _ => log::Level::Trace,
+    };
+    log::log!(target: LOG_TARGET, level, "{message}");
+}
+
+/// Rust function that Vega expressions can call, registered with
+/// [`VlConverter::register_expression_fn`]. It receives the arguments of the call as JSON
+/// values
+pub type ExpressionFn =
+    Box<dyn Fn(&[serde_json::Value]) -> Result<serde_json::Value, AnyError> + Send>;
+
+/// Expression functions registered with a converter, keyed by name
+#[derive(Default)]
+struct ExpressionFns(HashMap<String, ExpressionFn>);
+
+/// Rust function that post-processes the SVG of a conversion, set with the
+/// `svg_post_processor` of [`VgOpts`] and [`VlOpts`]. It receives the generated SVG before
+/// it's returned or rasterized to PNG, JPEG, or PDF, and must return valid SVG. Errors that
+/// it returns abort the conversion
+#[derive(Clone)]
+pub struct SvgPostProcessor(Arc<dyn Fn(String) -> Result<String, AnyError> + Send + Sync>);
+
+impl SvgPostProcessor {
+    pub fn new<F>(f: F) -> Self
+    where
+        F: Fn(String) -> Result<String, AnyError> + Send + Sync + 'static,
+    {
+        Self(Arc::new(f))
+    }
+
+    fn apply(&self, svg: String) -> Result<String, AnyError> {
+        (self.0)(svg).map_err(|err| anyhow!("SVG post-processor failed: {err}"))
+    }
+}
+
+impl Debug for SvgPostProcessor {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SvgPostProcessor")
+    }
+}
+
+/// Apply the SVG post-processor of a conversion's options, if any
+fn post_process_svg(
+    svg: String,
+    svg_post_processor: &Option<SvgPostProcessor>,
+) -> Result<String, AnyError> {
+    match svg_post_processor {
+        Some(svg_post_processor) => svg_post_processor.apply(svg),
+        None => Ok(svg),
+    }
+}
+
+/// Call a registered expression function with its JSON-serialized arguments
+#[op2]
+#[serde]
+fn op_call_expression_fn(
+    state: &mut OpState,
+    #[string] name: String,
+    #[string] args: String,
+) -> Result<serde_json::Value, AnyError> {
+    let args: Vec<serde_json::Value> = serde_json::from_str(&args)?;
+    match state.borrow::<ExpressionFns>().0.get(&name) {
+        Some(f) => f(&args).map_err(|err| anyhow!("Expression function {name} failed: {err}")),
+        None => bail!("Expression function {name} is not registered"),
+    }
+}
+
+/// Whether a name can be called from a Vega expression
+fn is_expression_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
+
 /// Struct that interacts directly with the Deno JavaScript runtime. Not Sendable
 struct InnerVlConverter {
     worker: MainWorker,
     initialized_vl_versions: HashSet<VlVersion>,
+    counters: Arc<ConversionCounters>,
     vega_initialized: bool,
+    /// Render warnings of the command that the worker is performing, which are returned with
+    /// the results of conversions to images
+    render_warnings: Vec<RenderWarning>,
+    /// devicePixelRatio of the browser shims of the command that the worker is performing,
+    /// which is the scale that the chart will be rasterized at, or 1 for vector output
+    device_pixel_ratio: f32,
 }
 
 impl InnerVlConverter {
+    /// Whether the JavaScript runtime was terminated while producing `result`, after which
+    /// it can't be relied on
+    fn was_terminated<T>(&mut self, result: &Result<T, AnyError>) -> bool {
+        self.worker
+            .js_runtime
+            .v8_isolate()
+            .is_execution_terminating()
+            || result.as_ref().is_err_and(|err| {
+                err.chain().any(|cause| {
+                    cause.downcast_ref::<JsError>().is_some_and(|js_error| {
+                        js_error
+                            .exception_message
+                            .ends_with("Error: execution terminated")
+                    })
+                })
+            })
+    }
+
     async fn init_vega(&mut self) -> Result<(), AnyError> {
         if !self.vega_initialized {
             let import_code = format!(
                 r#"
-var vega;
-import('{vega_url}').then((imported) => {{
-    vega = imported;
-}})
+// Runtimes started from a snapshot have Vega and vega-themes evaluated already
+var vega = globalThis.vlConvertSnapshot?.vega;
+if (vega == null) {{
+    import('{vega_url}').then((imported) => {{
+        vega = imported;
+    }})
+}}
 
-var vegaThemes;
-import('{vega_themes_url}').then((imported) => {{
-    vegaThemes = imported;
-}})
+var vegaThemes = globalThis.vlConvertSnapshot?.vegaThemes;
+if (vegaThemes == null) {{
+    import('{vega_themes_url}').then((imported) => {{
+        vegaThemes = imported;
+    }})
+}}
 
 var op_text_width;
 var op_get_json_arg;
+var op_log;
+var op_call_expression_fn;
+var op_simplify_topojson;
 import("ext:core/ops").then((imported) => {{
     op_text_width = imported.op_text_width;
     op_get_json_arg = imported.op_get_json_arg;
+    op_log = imported.op_log;
+    op_call_expression_fn = imported.op_call_expression_fn;
+    op_simplify_topojson = imported.op_simplify_topojson;
 }})
+
+function getJsonArg(argId) {{
+    const arg = op_get_json_arg(argId);
+    return typeof arg === "string" ? JSON.parse(arg) : arg;
+}}
+
+// Make a Rust function registered with the converter callable from Vega expressions
+function registerExpressionFn(name) {{
+    vega.expressionFunction(
+        name,
+        (...args) => op_call_expression_fn(name, JSON.stringify(args))
+    );
+}}
+
+// Seed the random numbers of the next render, or restore the runtime's own when seed is
+// null. The random() expression function calls Math.random, and transforms like sample
+// call the random function of vega-statistics, so both are replaced
+const defaultMathRandom = Math.random;
+function setRandomSeed(seed) {{
+    const random = seed == null ? defaultMathRandom : vega.randomLCG(seed);
+    Math.random = random;
+    vega.setRandom(random);
+}}
 "#,
                 vega_url = vega_url(),
                 vega_themes_url = vega_themes_url(),
@@ -276,9 +861,72 @@ import("ext:core/ops").then((imported) => {{
                 .execute_script("ext:<anon>", import_code.into())?;
 
             let logger_code = r#"""
-class WarningCollector {
-  constructor() {
-    this.warningsLogs = [];
+function formatLogArgs(args) {
+  return args.map((arg) => {
+    if (typeof arg === 'string') return arg;
+    if (arg instanceof Error) return arg.stack ?? arg.message;
+    try {
+      return JSON.stringify(arg) ?? String(arg);
+    } catch (e) {
+      return String(arg);
+    }
+  }).join(' ');
+}
+
+// Route the JavaScript console through the Rust log crate so that nothing is written to
+// stdout. The original stderr warning function is kept to print warnings on request.
+var printWarning = console.warn.bind(console);
+for (const [method, level] of [
+  ['error', 'error'], ['warn', 'warn'], ['info', 'info'], ['log', 'info'],
+  ['debug', 'debug'], ['trace', 'trace'],
+]) {
+  console[method] = (...args) => op_log(level, formatLogArgs(args));
+}
+
+// Log level of the views of the conversion that's running and the filters of the warnings
+// that it collects, as {level, filters}, or null. Set by the conversion script
+var viewLog = null;
+
+// Number of warnings of the conversion that's running that matched a warning filter
+var suppressedWarnings = 0;
+
+function compileViewLog(arg) {
+  if (arg == null) return null;
+  const filters = arg.filters.map((filter) => {
+    if (filter.regex != null) {
+      const regex = new RegExp(filter.regex);
+      return {label: `/${filter.regex}/`, test: (message) => regex.test(message)};
+    }
+    return {
+      label: JSON.stringify(filter.substring),
+      test: (message) => message.includes(filter.substring),
+    };
+  });
+  return {level: arg.level, filters};
+}
+
+// Count warnings that match a filter, which are left out of the warnings of the conversion
+function isSuppressedWarning(message) {
+  if (viewLog?.filters.some((filter) => filter.test(message))) {
+    suppressedWarnings++;
+    return true;
+  }
+  return false;
+}
+
+// Warning that stands in for the suppressed warnings of the conversion, if there were any
+function suppressedWarningsSummary() {
+  if (suppressedWarnings === 0) return [];
+  const filters = viewLog.filters.map((filter) => filter.label).join(', ');
+  const plural = suppressedWarnings === 1 ? '' : 's';
+  return [{mark: null, message: `Suppressed ${suppressedWarnings} warning${plural} matching ${filters}`}];
+}
+
+class VegaLiteLogger {
+  // Warnings are always logged, and are also printed to stderr when showWarnings is true.
+  // Warnings that match a warning filter are neither logged nor printed
+  constructor(showWarnings) {
+    this.showWarnings = showWarnings;
   }
 
   level(lvl) {
@@ -286,13 +934,19 @@ class WarningCollector {
     return this;
   }
 
-  error(msg) {
-    console.error(msg);
+  error(...args) {
+    console.error(...args);
     return this;
   }
 
-  warn(msg) {
-    this.warningsLogs.push(msg);
+  warn(...args) {
+    if (isSuppressedWarning(formatLogArgs(args))) {
+      return this;
+    }
+    console.warn(...args);
+    if (this.showWarnings) {
+      printWarning('vl-convert: warning:', ...args);
+    }
     return this;
   }
 
@@ -326,7 +980,7 @@ import('{url}').then((sg) => {{
         // Convert number form to string
         let weight = item.fontWeight == null? null: String(item.fontWeight);
         let size = sg.fontSize(item);
-        let family = sg.fontFamily(item);
+        let family = mapGenericFontFamily(sg.fontFamily(item));
 
         let text_info = JSON.stringify({{
             style, variant, weight, size, family, text
@@ -345,13 +999,148 @@ import('{url}').then((sg) => {{
                 }
             }
 
+            // Register the extended projections from d3-geo-projection (e.g. robinson, winkel3)
+            // with vega, the same way that vega-projection-extended does
+            for path in IMPORT_MAP.keys() {
+                if path.ends_with("d3-geo-projection.js") {
+                    let script_code = format!(
+                        r#"
+var projectionTypes = [
+    'albers', 'albersUsa', 'azimuthalEqualArea', 'azimuthalEquidistant', 'conicConformal',
+    'conicEqualArea', 'conicEquidistant', 'equalEarth', 'equirectangular', 'gnomonic',
+    'identity', 'mercator', 'mollweide', 'naturalEarth1', 'orthographic', 'stereographic',
+    'transverseMercator'
+];
+import('{url}').then((d3GeoProjection) => {{
+    for (const [name, factory] of Object.entries(d3GeoProjection)) {{
+        if (!name.startsWith('geo') || name.endsWith('Raw')) {{
+            continue;
+        }}
+        // Skip helpers (e.g. geoInterrupt, geoStitch) that don't construct a projection
+        let isProjection;
+        try {{
+            const projection = factory();
+            isProjection = typeof projection?.scale === 'function'
+                && typeof projection?.stream === 'function';
+        }} catch (e) {{
+            isProjection = false;
+        }}
+        const type = name.charAt(3).toLowerCase() + name.slice(4);
+        if (isProjection && vega.projection(type) == null) {{
+            vega.projection(type, factory);
+            projectionTypes.push(type);
+        }}
+    }}
+}})
+"#,
+                        url = url_for_path(path)
+                    );
+                    self.worker
+                        .execute_script("ext:<anon>", script_code.into())?;
+                    self.worker.run_event_loop(false).await?;
+                }
+            }
+
             // Create and initialize svg function string
             let function_str = r#"
-function vegaToView(vgSpec, allowedBaseUrls, errors) {
-    let runtime = vega.parse(vgSpec);
-    let baseURL = 'https://vega.github.io/vega-datasets/';
+function applyVegaConfig(vgSpec, config, theme) {
+    // Theme config is overridden by user config, which is overridden by the spec's own config
+    if (theme == null && config == null) {
+        return vgSpec;
+    }
+    let themeConfig = theme == null ? {} : vegaThemes[theme];
+    return {...vgSpec, config: vega.mergeConfig(themeConfig, config ?? {}, vgSpec.config ?? {})};
+}
+
+async function httpWithRetry(http, uri, options, fetchRetry, errors) {
+    // Only idempotent GET requests are retried
+    let method = (options?.method ?? 'GET').toUpperCase();
+    if (fetchRetry == null || method !== 'GET') {
+        return http(uri, options);
+    }
+    let maxAttempts = fetchRetry.retries + 1;
+    for (let attempt = 1; ; attempt++) {
+        try {
+            return await http(uri, options);
+        } catch (e) {
+            // Network failures are TypeErrors. vega-loader reports HTTP failures as
+            // the status code followed by the status text (e.g. "503Service Unavailable").
+            let message = e?.message ?? `${e}`;
+            let status = parseInt(message.slice(0, 3));
+            let retryable = e instanceof TypeError || (status >= 500 && status < 600);
+            if (!retryable || attempt >= maxAttempts) {
+                if (attempt > 1) {
+                    // Fail the conversion rather than rendering without the data
+                    let error = `Failed to fetch ${uri} after ${attempt} attempts: ${message}`;
+                    errors.push(error);
+                    throw new Error(error);
+                }
+                throw e;
+            }
+            let delay = fetchRetry.backoffMs * 2 ** (attempt - 1);
+            delay += Math.random() * delay / 2;
+            await new Promise((resolve) => setTimeout(resolve, delay));
+        }
+    }
+}
+
+// Responses to the GET requests made by loaders, keyed by url, while a chart is converted
+// once per theme. This is only set for the duration of the themed conversion, so that data
+// is fetched once and shared across the themes
+var fetchCache = null;
+
+function cachedHttp(http, uri, options) {
+    let method = (options?.method ?? 'GET').toUpperCase();
+    if (fetchCache == null || method !== 'GET') {
+        return http(uri, options);
+    }
+    let key = `${options?.response ?? 'text'} ${uri}`;
+    if (!fetchCache.has(key)) {
+        let response = http(uri, options);
+        fetchCache.set(key, response);
+        // Failed requests aren't cached, so that they're reported for each theme
+        response.catch(() => fetchCache.delete(key));
+    }
+    return fetchCache.get(key);
+}
+
+function vegaLoader(allowedBaseUrls, fetchRetry, dataAccess, errors) {
+    let baseURL = dataAccess?.baseURL ?? 'https://vega.github.io/vega-datasets/';
     const loader = vega.loader({ mode: 'http', baseURL });
+
+    // Urls that resolve to local files (file:// urls, or relative urls with a file:// base
+    // url) are read with Deno, since there's no file system module for Vega to use. This
+    // covers image hrefs too, which are sanitized by the same loader
+    const originalSanitize = loader.sanitize.bind(loader);
+    loader.sanitize = async (uri, options) => {
+        const result = await originalSanitize(uri, options);
+        if (result.localFile && !dataAccess?.allowLocalData) {
+            const message = `Local file access is not allowed: ${result.href}. `
+                + 'Enable the allow_local_data option (--allow-local-data in the CLI) '
+                + 'to load files from the file system';
+            if (dataAccess?.lenient && options?.context === 'image') {
+                // Vega draws the image without an href, so only the image is left out
+                if (!imageFailures.some((failure) => failure.url === uri)) {
+                    imageFailures.push({url: uri, message});
+                }
+            } else {
+                errors.push(message);
+            }
+            throw new Error(message);
+        }
+        return result;
+    };
+    // Sanitizing strips the file:// prefix, which is restored so that percent-encoded paths
+    // and Windows drive letters are read correctly
+    loader.file = (path) => Deno.readTextFile(new URL(`file://${path}`));
+
     const originalHttp = loader.http.bind(loader);
+    const retryingHttp = (uri, options) => cachedHttp(
+        (uri, options) => httpWithRetry(originalHttp, uri, options, fetchRetry, errors),
+        uri,
+        options,
+    );
+    loader.http = retryingHttp;
 
     if (allowedBaseUrls != null) {
         loader.http = async (uri, options) => {
@@ -364,949 +1153,4290 @@ function vegaToView(vgSpec, allowedBaseUrls, errors) {
                 errors.push(`External data url not allowed: ${uri}`);
                 throw new Error(`External data url not allowed: ${uri}`);
             }
-            return originalHttp(uri, options);
+            return retryingHttp(uri, options);
         };
     }
+    return loader;
+}
 
-    return new vega.View(runtime, {renderer: 'none', loader});
+function checkProjectionTypes(vgSpec) {
+    // Vega's own error for an unknown projection doesn't say which projections exist
+    const visit = (group) => {
+        for (const projection of group.projections ?? []) {
+            const type = projection.type;
+            if (typeof type === 'string' && vega.projection(type) == null) {
+                throw new Error(
+                    `Unrecognized projection type: "${type}". `
+                    + `Available projection types: ${projectionTypes.join(', ')}`
+                );
+            }
+        }
+        for (const mark of group.marks ?? []) {
+            if (mark.type === 'group') {
+                visit(mark);
+            }
+        }
+    };
+    visit(vgSpec);
 }
 
-function vegaToSvg(vgSpec, allowedBaseUrls, formatLocale, timeFormatLocale, errors) {
-    if (formatLocale != null) {
-        vega.formatLocale(formatLocale);
-    }
-    if (timeFormatLocale != null) {
-        vega.timeFormatLocale(timeFormatLocale);
-    }
-    let view = vegaToView(vgSpec, allowedBaseUrls, errors);
-    let svgPromise = view.runAsync().then(() => {
-        try {
-            // Workaround for https://github.com/vega/vega/issues/3481
-            view.signal("geo_interval_init_tick", {});
-        } catch (e) {
-            // No geo_interval_init_tick signal
+// Summary of the datasets and marks of the most recently rendered view, which is recorded
+// after the conversion when the collect_dataset_summary option is enabled
+var lastDatasetSummary = null;
+
+// Images that the most recently rendered view failed to load in lenient mode, and the
+// warnings that are recorded for them once the view is rendered
+var imageFailures = [];
+var lastRenderWarnings = [];
+
+function renderWarnings(view) {
+    // Name the image marks with items that have the urls of the failed images
+    const markNames = new Map();
+    const visit = (mark) => {
+        for (const item of mark.items ?? []) {
+            if (mark.marktype === 'group') {
+                (item.items ?? []).forEach(visit);
+            } else if (mark.marktype === 'image' && !markNames.has(item.url)) {
+                markNames.set(item.url, mark.name ?? null);
+            }
         }
-    }).then(() => {
-        return view.runAsync().then(
-            () => view.toSVG()
-        ).finally(() => {
-            view.finalize();
-            vega.resetDefaultLocale();
-        })
-    });
-    return svgPromise
+    };
+    visit(view.scenegraph().root);
+    return imageFailures.map(({url, message}) => ({mark: markNames.get(url) ?? null, message}));
 }
 
-function cloneScenegraph(obj) {
-    const keys = [
-      'marktype', 'name', 'role', 'interactive', 'clip', 'items', 'zindex',
-      'x', 'y', 'width', 'height', 'align', 'baseline',             // layout
-      'fill', 'fillOpacity', 'opacity', 'blend',                    // fill
-      'x1', 'y1', 'r1', 'r2', 'gradient',                           // gradient
-      'stops', 'offset', 'color',
-      'stroke', 'strokeOpacity', 'strokeWidth', 'strokeCap',        // stroke
-      'strokeJoin',
-      'strokeDash', 'strokeDashOffset',                             // stroke dash
-      'strokeForeground', 'strokeOffset',                           // group
-      'startAngle', 'endAngle', 'innerRadius', 'outerRadius',       // arc
-      'cornerRadius', 'padAngle',                                   // arc, rect
-      'cornerRadiusTopLeft', 'cornerRadiusTopRight',                // rect, group
-      'cornerRadiusBottomLeft', 'cornerRadiusBottomRight',
-      'interpolate', 'tension', 'orient', 'defined',                // area, line
-      'url', 'aspect', 'smooth',                                    // image
-      'path', 'scaleX', 'scaleY',                                   // path
-      'x2', 'y2',                                                   // rule
-      'size', 'shape',                                              // symbol
-      'text', 'angle', 'theta', 'radius', 'dir', 'dx', 'dy',        // text
-      'ellipsis', 'limit', 'lineBreak', 'lineHeight',
-      'font', 'fontSize', 'fontWeight', 'fontStyle', 'fontVariant', // font
-      'description', 'aria', 'ariaRole', 'ariaRoleDescription'      // aria
-    ];
+// Warnings that the most recently rendered view logged at the log level of the conversion
+var viewWarnings = [];
 
-    // Check if the input is an object (including an array) or null
-    if (typeof obj !== 'object' || obj === null) {
-        return obj;
+function collectViewWarnings(view) {
+    // Vega views log nothing unless a log level is set. Warnings are logged through the
+    // console as usual, and are also collected unless they match a warning filter
+    if (viewLog?.level == null) {
+        return;
     }
+    view.logLevel(viewLog.level);
+    const logger = view.logger();
+    const logWarn = logger.warn;
+    logger.warn = function (...args) {
+        if (logger.level() >= vega.Warn) {
+            const message = formatLogArgs(args);
+            if (isSuppressedWarning(message)) {
+                return this;
+            }
+            viewWarnings.push({mark: null, message});
+        }
+        return logWarn.apply(this, args);
+    };
+}
 
-    // Initialize the clone as an array or object based on the input type
-    const clone = Array.isArray(obj) ? [] : {};
+// Warnings of the most recently rendered view that are recorded for the conversion, with the
+// warnings that matched a warning filter replaced by a summary
+function collectRenderWarnings(warnings) {
+    const kept = [...warnings, ...viewWarnings].filter(({message}) => !isSuppressedWarning(message));
+    return [...kept, ...suppressedWarningsSummary()];
+}
 
-    // If the object is an array, iterate over its elements
-    if (Array.isArray(obj)) {
-        for (let i = 0; i < obj.length; i++) {
-            // Apply the function recursively to each element
-            clone.push(cloneScenegraph(obj[i]));
+// Rows of the top-level datasets of the most recently rendered view. These are only kept
+// while collectDatasets is set, for exports with the export_data option
+var collectDatasets = false;
+var lastDatasets = null;
+
+function datasetRows(view, vgSpec) {
+    // Vega stores most dates as timestamps. Timestamps are written as dates in fields that
+    // are parsed as dates or computed by toDate formulas or timeunit transforms, including
+    // the fields that are inherited from source datasets
+    const datasetsByName = new Map((vgSpec.data ?? []).map((data) => [data.name, data]));
+    const dateFields = (data, visited = new Set()) => {
+        const fields = new Set();
+        if (data == null || visited.has(data.name)) {
+            return fields;
         }
-    } else {
-        // If the object is not an array, iterate over its keys
-        for (const key in obj) {
-            // Clone only the properties with specified keys
-            if (key === "shape" && typeof obj[key] === "function") {
-                // Convert path object to SVG path string.
-                // Initialize context. This is needed for obj.shape(obj) to work.
-                obj.shape.context();
-                clone["shape"] = obj.shape(obj) ?? "";
-            } else if (keys.includes(key)) {
-                clone[key] = cloneScenegraph(obj[key]);
+        visited.add(data.name);
+        for (const source of [data.source ?? []].flat()) {
+            dateFields(datasetsByName.get(source), visited).forEach((field) => fields.add(field));
+        }
+        const parse = data.format?.parse;
+        if (parse != null && typeof parse === 'object') {
+            for (const [field, type] of Object.entries(parse)) {
+                if (/^(date|utc)/.test(type)) {
+                    fields.add(field);
+                }
             }
         }
-    }
+        for (const transform of data.transform ?? []) {
+            if (transform.type === 'formula' && /^(toDate|datetime|utcdatetime)\(/.test(transform.expr)) {
+                fields.add(transform.as);
+            } else if (transform.type === 'timeunit') {
+                (transform.as ?? ['unit0', 'unit1']).forEach((field) => fields.add(field));
+            }
+        }
+        return fields;
+    };
+    const toJson = (value, isDate) => {
+        if (value instanceof Date) {
+            return value.toISOString();
+        } else if (isDate && Number.isFinite(value)) {
+            return new Date(value).toISOString();
+        }
+        return value ?? null;
+    };
 
-    return clone;
-}
+    return (vgSpec.data ?? []).map((data) => {
+        const rows = (view.data(data.name) ?? []).filter((row) => row != null && typeof row === 'object');
 
-function vegaToScenegraph(vgSpec, allowedBaseUrls, formatLocale, timeFormatLocale, errors) {
-    if (formatLocale != null) {
-        vega.formatLocale(formatLocale);
-    }
-    if (timeFormatLocale != null) {
-        vega.timeFormatLocale(timeFormatLocale);
-    }
-    let view = vegaToView(vgSpec, allowedBaseUrls, errors);
-    let scenegraphPromise = view.runAsync().then(() => {
-        try {
-            // Workaround for https://github.com/vega/vega/issues/3481
-            view.signal("geo_interval_init_tick", {});
-        } catch (e) {
-            // No geo_interval_init_tick signal
+        // Fields of the first row in their order, then the fields of other rows alphabetically
+        const fields = Object.keys(rows[0] ?? {});
+        const extraFields = new Set();
+        for (const row of rows.slice(1)) {
+            Object.keys(row).filter((field) => !fields.includes(field)).forEach((field) => extraFields.add(field));
         }
-    }).then(() => {
-        return view.runAsync().then(
-            () => {
-                let padding = view.padding();
-                return {
-                    width: Math.max(0, view._viewWidth + padding.left + padding.right),
-                    height: Math.max(0, view._viewHeight + padding.top + padding.bottom),
-                    origin: [
-                        padding.left + view._origin[0],
-                        padding.top + view._origin[1]
-                    ],
-                    scenegraph: cloneScenegraph(view.scenegraph().root)
-                }
-            }
-        ).finally(() => {
-            view.finalize();
-            vega.resetDefaultLocale();
-        })
+        fields.push(...[...extraFields].sort());
+
+        const dates = dateFields(data);
+        return {
+            name: data.name,
+            fields,
+            rows: rows.map((row) => fields.map((field) => toJson(row[field], dates.has(field)))),
+        };
     });
-    return scenegraphPromise
 }
-"#;
-            self.worker.execute_script(
-                "ext:<anon>",
-                deno_core::FastString::from_static(function_str),
-            )?;
-            self.worker.run_event_loop(false).await?;
 
-            self.vega_initialized = true;
+function datasetSummary(view, vgSpec) {
+    // Names of the datasets that marks are drawn from, directly or through a facet
+    const markDatasets = new Set();
+    const visitMarks = (group) => {
+        for (const mark of group.marks ?? []) {
+            const from = mark.from ?? {};
+            if (from.data != null) {
+                markDatasets.add(from.data);
+            }
+            if (from.facet?.data != null) {
+                markDatasets.add(from.facet.data);
+            }
+            if (mark.type === 'group') {
+                visitMarks(mark);
+            }
         }
+    };
+    visitMarks(vgSpec);
+
+    // Top-level datasets of the spec. Vega also registers named marks as datasets, which
+    // are left out
+    const datasets = (vgSpec.data ?? []).map(({name}) => {
+        const rows = view.data(name) ?? [];
+        const fields = new Set();
+        for (const row of rows) {
+            if (row != null && typeof row === 'object') {
+                Object.keys(row).forEach((field) => fields.add(field));
+            }
+        }
+        return {name, rows: rows.length, fields: [...fields], feeds_mark: markDatasets.has(name)};
+    });
 
-        Ok(())
-    }
-
-    async fn init_vl_version(&mut self, vl_version: &VlVersion) -> Result<(), AnyError> {
-        if !self.initialized_vl_versions.contains(vl_version) {
-            // Create and evaluate import string
-            let import_code = format!(
-                r#"
-var {ver_name};
-import('{vl_url}').then((imported) => {{
-    {ver_name} = imported;
-}})
-"#,
-                ver_name = format!("{:?}", vl_version),
-                vl_url = vl_version.to_url()
-            );
-
-            self.worker
-                .execute_script("ext:<anon>", import_code.into())?;
+    // Count the items of data marks, leaving out axes, legends, and titles
+    const countMarkItems = (mark) => {
+        let count = 0;
+        for (const item of mark.items ?? []) {
+            if (mark.marktype === 'group') {
+                for (const child of item.items ?? []) {
+                    count += countMarkItems(child);
+                }
+            } else if (mark.role === 'mark') {
+                count += 1;
+            }
+        }
+        return count;
+    };
+    const anyMarksRendered = countMarkItems(view.scenegraph().root) > 0;
+    return {datasets, any_marks_rendered: anyMarksRendered};
+}
 
-            self.worker.run_event_loop(false).await?;
+// The error of the most recent conversion that failed while Vega parsed the spec or
+// evaluated its dataflow, with the operator that failed and the transform that it runs
+var lastDataflowError = null;
+
+// Vega logs the errors that operators throw instead of rejecting runAsync, without saying
+// which operator threw them. Errors are annotated with the operator that threw them here
+for (const proto of [vega.Operator.prototype, vega.Transform.prototype]) {
+    const evaluate = proto.evaluate;
+    proto.evaluate = function (pulse) {
+        const annotate = (e) => {
+            if (e instanceof Error && e.vlConvertOperator == null) {
+                e.vlConvertOperator = this;
+            }
+            throw e;
+        };
+        try {
+            const result = evaluate.call(this, pulse);
+            return typeof result?.then === 'function' ? result.then(null, annotate) : result;
+        } catch (e) {
+            annotate(e);
+        }
+    };
+}
 
-            // Create and initialize function string
-            let function_code = format!(
-                r#"
-function compileVegaLite_{ver_name}(vlSpec, config, theme, warnings) {{
-    let options = {{}};
+function operatorType(op) {
+    return op.constructor.Definition?.type ?? null;
+}
 
-    // Handle config and theme
-    let usermetaTheme = ((vlSpec.usermeta ?? {{}}).embedOptions ?? {{}}).theme;
-    let namedTheme = theme ?? usermetaTheme;
-    if (namedTheme != null) {{
-        options["config"] = vega.mergeConfig(vegaThemes[namedTheme], config ?? {{}});
-    }} else if (config != null) {{
-        options["config"] = config;
-    }}
+function locateOperator(view, vgSpec, op) {
+    // The transforms of a top-level dataset are evaluated in order by the operators between
+    // the input and output of its pipeline, along with operators that Vega adds
+    for (const [dataIndex, data] of (vgSpec.data ?? []).entries()) {
+        const pipeline = view._runtime.data[data.name];
+        if (pipeline == null) {
+            continue;
+        }
+        const operators = [];
+        for (let node = pipeline.output; node != null && node !== pipeline.input; node = node.source) {
+            operators.unshift(node);
+        }
+        let position = 0;
+        for (const [index, transform] of (data.transform ?? []).entries()) {
+            const type = transform.type.toLowerCase();
+            while (position < operators.length
+                && operatorType(operators[position])?.toLowerCase() !== type) {
+                position++;
+            }
+            if (position === operators.length) {
+                break;
+            }
+            if (operators[position] === op) {
+                return {
+                    spec_path: `data[${dataIndex}].transform[${index}]`,
+                    transform: transform.type,
+                    vega_transform: transform,
+                };
+            }
+            position++;
+        }
+    }
+    return {};
+}
 
-    if (!warnings) {{
-        options["logger"] = new WarningCollector();
-    }}
+function recordDataflowErrors(view, vgSpec, errors) {
+    // Fail the conversion on the first error that an operator throws
+    const logger = view.logger();
+    const logError = logger.error;
+    logger.error = function (...args) {
+        const error = args.find((arg) => arg?.vlConvertOperator != null);
+        if (error != null && lastDataflowError == null) {
+            const op = error.vlConvertOperator;
+            lastDataflowError = {
+                phase: 'dataflow',
+                operator: operatorType(op),
+                message: error.message,
+                ...locateOperator(view, vgSpec, op),
+            };
+            errors.push(error.message);
+        }
+        return logError.apply(this, args);
+    };
+}
 
-    return {ver_name}.compile(vlSpec, options).spec
-}}
+// Fonts that generic font families are rendered with, keyed by the generic family, for the
+// conversion that's running. Set by the conversion script
+var genericFontMapping = null;
 
-function vegaLiteToSvg_{ver_name}(vlSpec, config, theme, warnings, allowedBaseUrls, formatLocale, timeFormatLocale, errors) {{
-    let vgSpec = compileVegaLite_{ver_name}(vlSpec, config, theme, warnings);
-    return vegaToSvg(vgSpec, allowedBaseUrls, formatLocale, timeFormatLocale, errors)
-}}
+function mapGenericFontFamily(family) {
+    if (genericFontMapping == null || family == null) {
+        return family;
+    }
+    return String(family).split(',').map((name) => {
+        const generic = name.trim().replace(/^(["'])(.*)\1$/, '$2').toLowerCase();
+        return Object.hasOwn(genericFontMapping, generic)
+            ? name.replace(name.trim(), genericFontMapping[generic]) : name;
+    }).join(',');
+}
 
-function vegaLiteToScenegraph_{ver_name}(vlSpec, config, theme, warnings, allowedBaseUrls, formatLocale, timeFormatLocale, errors) {{
-    let vgSpec = compileVegaLite_{ver_name}(vlSpec, config, theme, warnings);
-    return vegaToScenegraph(vgSpec, allowedBaseUrls,formatLocale, timeFormatLocale,  errors)
-}}
-"#,
-                ver_name = format!("{:?}", vl_version),
-            );
+function applyGenericFontMapping(scene) {
+    // Text is measured with the mapped fonts, and the fonts of the text items are replaced
+    // so that SVG output and the images rasterized from it use them too
+    if (genericFontMapping == null || scene == null) {
+        return;
+    }
+    if (scene.marktype === 'text') {
+        for (const item of scene.items) {
+            item.font = mapGenericFontFamily(item.font || 'sans-serif');
+        }
+    }
+    for (const child of scene.items ?? []) {
+        applyGenericFontMapping(child);
+    }
+}
 
-            self.worker
-                .execute_script("ext:<anon>", function_code.into())?;
+// Size of the container that charts sized to their container are rendered in, and whether
+// container sizing is an error instead, for the conversion that's running. Set by the
+// conversion script
+var containerSize = null;
+
+function resolveContainerSize(vgSpec) {
+    // Charts sized to their container (e.g. Vega-Lite's "width": "container", including in
+    // concatenated and faceted views) have signals that read containerSize(), which isn't
+    // defined without a container. Those signals read the configured size instead
+    const usesContainer = (signal) => JSON.stringify(signal).includes('containerSize()');
+    const names = [];
+    const find = (group) => {
+        for (const signal of group.signals ?? []) {
+            if (usesContainer(signal)) {
+                names.push(signal.name);
+            }
+        }
+        for (const mark of group.marks ?? []) {
+            if (mark.type === 'group') {
+                find(mark);
+            }
+        }
+    };
+    find(vgSpec);
+    if (names.length === 0 || containerSize == null) {
+        return;
+    }
+    if (containerSize.strict) {
+        throw new Error(
+            `The chart is sized to its container (signals ${names.join(', ')}), which has no `
+            + 'size in headless conversion. Give the chart an explicit width and height, or '
+            + 'disable strict_sizing to render it with container_width and container_height'
+        );
+    }
 
-            self.worker.run_event_loop(false).await?;
+    const size = `[${containerSize.width}, ${containerSize.height}]`;
+    const replace = (value) => {
+        if (typeof value === 'string') {
+            return value.replaceAll('containerSize()', size);
+        } else if (Array.isArray(value)) {
+            return value.map(replace);
+        } else if (value != null && typeof value === 'object') {
+            return Object.fromEntries(Object.entries(value).map(([k, v]) => [k, replace(v)]));
+        }
+        return value;
+    };
+    const visit = (group) => {
+        if (group.signals != null) {
+            group.signals = group.signals.map((s) => usesContainer(s) ? replace(s) : s);
+        }
+        for (const mark of group.marks ?? []) {
+            if (mark.type === 'group') {
+                visit(mark);
+            }
+        }
+    };
+    visit(vgSpec);
+    console.warn(
+        `The chart is sized to its container (signals ${names.join(', ')}), and is rendered `
+        + `with a container size of ${containerSize.width}x${containerSize.height}. Set `
+        + 'container_width and container_height to render it with another size'
+    );
+}
 
-            // Register that this Vega-Lite version has been initialized
-            self.initialized_vl_versions.insert(*vl_version);
+// Browser globals that the expressions of the conversion that's running can read, as
+// {language, devicePixelRatio}, or null when browser shims are disabled. Set by the
+// conversion script
+var browserShims = null;
+
+// Shimmed browser globals, which are defined when browser shims are enabled
+const SHIMMED_BROWSER_GLOBALS = ['window', 'navigator', 'screen', 'devicePixelRatio'];
+
+// Browser globals that expressions copied from web pages read, which aren't available in
+// headless conversion
+const BROWSER_GLOBALS = [
+    ...SHIMMED_BROWSER_GLOBALS, 'document', 'location', 'history', 'localStorage',
+    'sessionStorage', 'fetch', 'XMLHttpRequest', 'performance', 'console', 'alert',
+];
+
+// Keys of a Vega spec whose strings are expressions, like the update of a signal or the expr
+// of a formula transform
+const EXPRESSION_KEYS = new Set(['expr', 'update', 'init', 'signal', 'test', 'filter']);
+
+function expressionStrings(value, key, strings) {
+    // Strings in the inline data of the spec and in its text, like titles, aren't
+    // expressions, even when they mention a browser global
+    if (typeof value === 'string') {
+        if (EXPRESSION_KEYS.has(key)) {
+            strings.push(value);
+        }
+    } else if (Array.isArray(value)) {
+        // e.g. the filter expressions of an event stream
+        value.forEach((item) => expressionStrings(item, key, strings));
+    } else if (value != null && typeof value === 'object' && key !== 'values') {
+        for (const [k, v] of Object.entries(value)) {
+            expressionStrings(v, k, strings);
         }
-        Ok(())
     }
+    return strings;
+}
 
-    pub async fn try_new() -> Result<Self, AnyError> {
-        let module_loader = Rc::new(VlConvertModuleLoader);
-        let options = WorkerOptions {
-            extensions: vec![
-                vl_convert_text_runtime::init_ops(),
-                vl_convert_converter_runtime::init_ops(),
-            ],
-            module_loader: module_loader.clone(),
-            ..Default::default()
-        };
+function withBrowserShims(vgSpec) {
+    // Vega expressions read unknown identifiers as signals, so the browser globals that the
+    // spec's expressions read are defined as top-level signals. The worker's globals are set
+    // too, for Vega's screen() and windowSize() expression functions
+    if (browserShims == null) {
+        delete globalThis.screen;
+        delete globalThis.devicePixelRatio;
+        delete globalThis.innerWidth;
+        delete globalThis.innerHeight;
+        return vgSpec;
+    }
+    const size = (value, containerValue) => typeof value === 'number' ? value : containerValue;
+    const width = size(vgSpec.width, containerSize?.width ?? 600);
+    const height = size(vgSpec.height, containerSize?.height ?? 400);
+    const {language, devicePixelRatio} = browserShims;
+    const navigator = {language, languages: [language]};
+    const screen = {width, height, availWidth: width, availHeight: height};
+    const window = {navigator, screen, devicePixelRatio, innerWidth: width, innerHeight: height};
+    Object.assign(globalThis, {screen, devicePixelRatio, innerWidth: width, innerHeight: height});
+
+    const globals = {window, navigator, screen, devicePixelRatio};
+    const source = expressionStrings(vgSpec, null, []).join('\n');
+    const defined = new Set((vgSpec.signals ?? []).map((signal) => signal.name));
+    const signals = SHIMMED_BROWSER_GLOBALS
+        .filter((name) => !defined.has(name) && new RegExp(`\\b${name}\\b`).test(source))
+        .map((name) => ({name, value: globals[name]}));
+    if (signals.length === 0) {
+        return vgSpec;
+    }
+    return {...vgSpec, signals: [...signals, ...(vgSpec.signals ?? [])]};
+}
 
-        let main_module =
-            deno_core::resolve_path("vl-convert-rs.js", Path::new(env!("CARGO_MANIFEST_DIR")))?;
+function browserGlobalError(error) {
+    // Vega fails to parse expressions that read browser globals that aren't shimmed as
+    // references to undefined signals. Name the browser API instead
+    const name = /^Unrecognized signal name: "(\w+)"$/.exec(error?.message ?? '')?.[1];
+    if (!BROWSER_GLOBALS.includes(name)) {
+        return error;
+    }
+    const hint = SHIMMED_BROWSER_GLOBALS.includes(name)
+        ? ', and browser shims are disabled'
+        : '';
+    return new Error(
+        `An expression reads the browser API "${name}", which isn't available in headless `
+        + `conversion${hint}`
+    );
+}
 
-        let permissions = PermissionsContainer::new(Permissions::allow_all());
+// Row limit of the datasets of the conversion that's running, as {maxRows, strategy, seed,
+// annotate}, or null when datasets aren't limited. Set by the conversion script
+var rowLimit = null;
 
-        let mut worker =
-            MainWorker::bootstrap_from_options(main_module.clone(), permissions, options);
+// Errors of the conversion that's running, which datasets over the row limit are reported to
+var rowLimitErrors = null;
 
-        worker.execute_main_module(&main_module).await?;
-        worker.run_event_loop(false).await?;
+// Warnings for the datasets of the most recently rendered view that were truncated to the
+// row limit
+var rowLimitWarnings = [];
 
-        let this = Self {
-            worker,
-            initialized_vl_versions: Default::default(),
-            vega_initialized: false,
-        };
+// Signal that holds the subtitle note of charts with truncated datasets
+const TRUNCATION_NOTE_SIGNAL = 'vlconvert_truncation_note';
 
-        Ok(this)
+function limitRows(rows) {
+    if (rowLimit == null || !Array.isArray(rows) || rows.length <= rowLimit.maxRows) {
+        return rows;
+    }
+    const {maxRows, strategy, seed} = rowLimit;
+    if (strategy === 'error') {
+        const message = `A dataset has ${rows.length} rows, more than max_rows (${maxRows}). `
+            + 'Set the row limit strategy to head or sample to render the chart with part of '
+            + 'the data';
+        rowLimitErrors?.push(message);
+        throw new Error(message);
+    }
+    let limited;
+    if (strategy === 'head') {
+        limited = rows.slice(0, maxRows);
+    } else {
+        // Reservoir sample of the row indices, which are sorted to keep the rows in order
+        const random = vega.randomLCG(seed);
+        const indices = Array.from({length: maxRows}, (_, i) => i);
+        for (let i = maxRows; i < rows.length; i++) {
+            const j = Math.floor(random() * (i + 1));
+            if (j < maxRows) {
+                indices[j] = i;
+            }
+        }
+        indices.sort((a, b) => a - b);
+        limited = indices.map((i) => rows[i]);
     }
+    const kept = strategy === 'head' ? 'first rows' : 'random sample';
+    rowLimitWarnings.push({
+        mark: null,
+        message: `A dataset with ${rows.length} rows was truncated to ${maxRows} rows (${kept})`,
+    });
+    return limited;
+}
 
-    async fn execute_script_to_json(
-        &mut self,
-        script: &str,
-    ) -> Result<serde_json::Value, AnyError> {
-        let code = script.to_string();
-        let res = self.worker.js_runtime.execute_script("ext:<anon>", code)?;
+// Datasets are limited as they're parsed, which covers inline values as well as data that's
+// loaded from urls
+for (const type of ['json', 'csv', 'tsv', 'dsv', 'topojson']) {
+    const reader = vega.formats(type);
+    const limitedReader = (data, format) => limitRows(reader(data, format));
+    limitedReader.responseType = reader.responseType;
+    vega.formats(type, limitedReader);
+}
 
-        self.worker.run_event_loop(false).await?;
+// Tolerance that the TopoJSON datasets of the conversion that's running are simplified with,
+// or null when they aren't simplified. Set by the conversion script
+var topojsonTolerance = null;
 
-        let scope = &mut self.worker.js_runtime.handle_scope();
-        let local = v8::Local::new(scope, res);
+// Warnings for the TopoJSON datasets of the most recently rendered view that were simplified
+var topojsonWarnings = [];
 
-        // Deserialize a `v8` object into a Rust type using `serde_v8`,
-        // in this case deserialize to a JSON `Value`.
-        let deserialized_value = serde_v8::from_v8::<serde_json::Value>(scope, local);
-        deserialized_value.map_err(|err| {
-            anyhow!(
-                "Failed to deserialize JavaScript value: {}",
-                err.to_string()
-            )
-        })
+function simplifyTopojson(data, format) {
+    // Topologies that are nested in a property of the data are left as they are
+    if (topojsonTolerance == null || format?.property != null) {
+        return data;
     }
+    const text = typeof data === 'string' ? data : JSON.stringify(data);
+    const simplified = op_simplify_topojson(text, topojsonTolerance);
+    if (simplified == null) {
+        return data;
+    }
+    const {topology, vertices, simplifiedVertices} = simplified;
+    const reduction = vertices === 0 ? 0 : 100 * (1 - simplifiedVertices / vertices);
+    topojsonWarnings.push({
+        mark: null,
+        message: `Simplified the arcs of a TopoJSON dataset from ${vertices} to `
+            + `${simplifiedVertices} vertices (${reduction.toFixed(1)}% fewer)`,
+    });
+    return topology;
+}
 
-    async fn execute_script_to_string(&mut self, script: &str) -> Result<String, AnyError> {
-        let code = script.to_string();
-        let res = self.worker.js_runtime.execute_script("ext:<anon>", code)?;
+// TopoJSON datasets are simplified in Rust as they're parsed, before their features are
+// extracted, so that the paths of their features are generated from fewer vertices
+const topojsonReader = vega.formats('topojson');
+const simplifyingTopojsonReader = (data, format) => topojsonReader(
+    simplifyTopojson(data, format),
+    format,
+);
+simplifyingTopojsonReader.responseType = topojsonReader.responseType;
+vega.formats('topojson', simplifyingTopojsonReader);
+
+function withTruncationNote(vgSpec) {
+    // The note is added to the subtitle by a signal, which is set once the data is loaded and
+    // it's known whether any dataset was truncated
+    const title = typeof vgSpec.title === 'string' ? {text: vgSpec.title} : vgSpec.title;
+    const subtitle = title?.subtitle;
+    const current = subtitle == null ? null
+        : subtitle.signal != null ? `(${subtitle.signal})` : JSON.stringify(subtitle);
+    const note = TRUNCATION_NOTE_SIGNAL;
+    return {
+        ...vgSpec,
+        signals: [...(vgSpec.signals ?? []), {name: note, value: ''}],
+        title: {
+            ...(title ?? {text: ''}),
+            subtitle: {
+                signal: current == null ? note : `${note} ? [].concat(${current}, ${note}) : ${current}`,
+            },
+        },
+    };
+}
 
-        self.worker.run_event_loop(false).await?;
+function applyTruncationNote(view) {
+    if (rowLimit?.annotate && rowLimitWarnings.length > 0) {
+        view.signal(TRUNCATION_NOTE_SIGNAL, `Data truncated to ${rowLimit.maxRows} rows`);
+    }
+}
 
-        let scope = &mut self.worker.js_runtime.handle_scope();
-        let local = v8::Local::new(scope, res);
+function vegaToView(vgSpec, allowedBaseUrls, fetchRetry, dataAccess, errors) {
+    lastDatasetSummary = null;
+    lastDatasets = null;
+    imageFailures = [];
+    lastRenderWarnings = [];
+    rowLimitWarnings = [];
+    topojsonWarnings = [];
+    viewWarnings = [];
+    rowLimitErrors = errors;
+    lastDataflowError = null;
+    checkProjectionTypes(vgSpec);
+    resolveContainerSize(vgSpec);
+    vgSpec = withBrowserShims(vgSpec);
+    if (rowLimit?.annotate) {
+        vgSpec = withTruncationNote(vgSpec);
+    }
+    let loader = vegaLoader(allowedBaseUrls, fetchRetry, dataAccess, errors);
+    let view;
+    try {
+        view = new vega.View(vega.parse(vgSpec), {renderer: 'none', loader});
+    } catch (e) {
+        e = browserGlobalError(e);
+        lastDataflowError = {phase: 'parse', operator: null, message: e?.message ?? `${e}`};
+        throw e;
+    }
+    recordDataflowErrors(view, vgSpec, errors);
+    collectViewWarnings(view);
+    return view;
+}
 
-        // Deserialize a `v8` object into a Rust type using `serde_v8`,
-        // in this case deserialize to a JSON `Value`.
-        let deserialized_value = serde_v8::from_v8::<serde_json::Value>(scope, local);
+async function inlineUrlData(spec, loader) {
+    // Replace url data sources anywhere in the spec (including layers, concatenated views,
+    // and lookup transforms) with the text of the data, which is parsed exactly as the
+    // fetched data would be
+    const visit = async (obj) => {
+        if (obj == null || typeof obj !== 'object') {
+            return;
+        }
+        for (const [key, value] of Object.entries(obj)) {
+            if (key === 'data' && typeof value?.url === 'string') {
+                const {url, ...data} = value;
+                // Vega-Lite infers the format of url data from the file extension, but
+                // assumes that inline data is JSON
+                const extension = /(?:\.([^.]+))?$/.exec(url)[1];
+                const defaultType = ['json', 'csv', 'tsv', 'dsv', 'topojson'].includes(extension)
+                    ? extension : 'json';
+                const format = {...value.format, type: value.format?.type ?? defaultType};
+                obj[key] = {...data, values: await loader.load(url), format};
+            } else if (key !== 'values') {
+                await visit(value);
+            }
+        }
+    };
+    await visit(spec);
+}
 
-        let value = match deserialized_value {
-            Ok(value) => {
-                let value = value.as_str();
-                value.unwrap().to_string()
+function pickSceneItem(scene, x, y) {
+    // Find the last drawn interactive item whose bounds contain the point
+    let picked = null;
+    const visit = (mark, groupX, groupY) => {
+        if (mark.interactive === false) {
+            return;
+        }
+        for (const item of mark.items ?? []) {
+            if (item.bounds != null && item.bounds.contains(x - groupX, y - groupY)) {
+                picked = item;
             }
-            Err(err) => bail!("{}", err.to_string()),
-        };
+            if (mark.marktype === 'group') {
+                for (const child of item.items ?? []) {
+                    visit(child, groupX + (item.x ?? 0), groupY + (item.y ?? 0));
+                }
+            }
+        }
+    };
+    visit(scene, 0, 0);
+    return picked;
+}
 
-        Ok(value)
+async function applyInteractions(view, interactions) {
+    if (interactions == null || interactions.length === 0) {
+        return;
     }
 
-    pub async fn vegalite_to_vega(
-        &mut self,
-        vl_spec: &serde_json::Value,
-        vl_opts: VlOpts,
-    ) -> Result<serde_json::Value, AnyError> {
-        self.init_vega().await?;
-        self.init_vl_version(&vl_opts.vl_version).await?;
-        let config = vl_opts.config.clone().unwrap_or(serde_json::Value::Null);
-
-        let spec_arg_id = set_json_arg(vl_spec.clone())?;
-        let config_arg_id = set_json_arg(config)?;
+    // Event coordinates are computed relative to the renderer's canvas, so stand in a
+    // renderer whose canvas is positioned at the origin while interactions are applied
+    const canvas = {getBoundingClientRect: () => ({left: 0, top: 0}), clientLeft: 0, clientTop: 0};
+    const renderer = view._renderer;
+    view._renderer = {
+        canvas: () => canvas,
+        background() { return this; },
+        resize() { return this; },
+        dirty() {},
+        renderAsync: async function () { return this; },
+    };
+    try {
+        for (const step of interactions) {
+            if (step.signal != null) {
+                view.signal(step.signal, step.value);
+            } else if (step.event != null) {
+                const {type, x = 0, y = 0, ...props} = step.event;
+                const padding = view.padding();
+                const origin = view.origin();
+                const item = pickSceneItem(
+                    view.scenegraph().root, x - padding.left - origin[0], y - padding.top - origin[1]
+                );
+                const makeEvent = () => ({
+                    ...props, type, clientX: x, clientY: y, preventDefault() {}, stopPropagation() {},
+                });
+                // Handlers for view and scope events, followed by window and selector events
+                for (const {handler} of view._handler.handlers().filter((h) => h.type === type)) {
+                    handler.call(view, makeEvent(), item);
+                }
+                for (const listener of view._eventListeners.filter((l) => l.type === type)) {
+                    listener.handler(makeEvent());
+                }
+            }
+            await view.runAsync();
+        }
+    } finally {
+        view._renderer = renderer;
+    }
+}
 
-        let theme_arg = match &vl_opts.theme {
-            None => "null".to_string(),
-            Some(s) => format!("'{}'", s),
-        };
+function formatTooltip(tooltip) {
+    // Format tooltip content as plain text, with one "key: value" line per field of
+    // object tooltips
+    const formatValue = (value) => typeof value === 'string' ? value : JSON.stringify(value);
+    if (tooltip == null || typeof tooltip !== 'object') {
+        return tooltip == null ? null : formatValue(tooltip);
+    }
+    const {title, ...fields} = tooltip;
+    const lines = title == null ? [] : [formatValue(title)];
+    for (const [key, value] of Object.entries(fields)) {
+        if (value !== undefined) {
+            lines.push(`${key}: ${formatValue(value)}`);
+        }
+    }
+    return lines.join('\n');
+}
 
-        let allowed_base_urls =
-            serde_json::to_string(&serde_json::Value::from(vl_opts.allowed_base_urls))?;
+class NativeTooltipSVGStringRenderer extends vega.SVGStringRenderer {
+    // Emits each item's tooltip as a <title> element inside of the first element that is
+    // written for the item. The title is written after any text content of the element,
+    // because the markup builder drops text that follows a child element.
+    attr(scene, item, attrs, tag) {
+        this._tooltipItem = item;
+        return super.attr(scene, item, attrs, tag);
+    }
 
-        let code = format!(
-            r#"
-compileVegaLite_{ver_name:?}(
-    JSON.parse(op_get_json_arg({spec_arg_id})),
-    JSON.parse(op_get_json_arg({config_arg_id})),
+    mark(markup, scene) {
+        if (!markup._nativeTooltips) {
+            const {open, text, close} = markup;
+            const titled = new WeakSet();
+            let pendingTitle = null;
+            const writeTitle = () => {
+                if (pendingTitle != null) {
+                    open.call(markup, 'title');
+                    text.call(markup, pendingTitle);
+                    close.call(markup);
+                    pendingTitle = null;
+                }
+            };
+            markup._nativeTooltips = true;
+            markup.open = (...args) => {
+                writeTitle();
+                const item = this._tooltipItem;
+                this._tooltipItem = null;
+                open.apply(markup, args);
+                if (item != null && !titled.has(item)) {
+                    titled.add(item);
+                    const title = formatTooltip(item.tooltip);
+                    pendingTitle = title === '' ? null : title;
+                }
+                return markup;
+            };
+            markup.text = (...args) => {
+                text.apply(markup, args);
+                writeTitle();
+                return markup;
+            };
+            markup.close = (...args) => {
+                writeTitle();
+                return close.apply(markup, args);
+            };
+        }
+        return super.mark(markup, scene);
+    }
+}
+
+async function viewToSvg(view, nativeTooltips) {
+    if (!nativeTooltips) {
+        return view.toSVG();
+    }
+    const svgModule = vega.renderModule('svg');
+    vega.renderModule('svg', {...svgModule, headless: NativeTooltipSVGStringRenderer});
+    try {
+        return await view.toSVG();
+    } finally {
+        vega.renderModule('svg', svgModule);
+    }
+}
+
+function vegaToSvg(vgSpec, allowedBaseUrls, fetchRetry, dataAccess, interactions, formatLocale, timeFormatLocale, nativeTooltips, errors) {
+    if (formatLocale != null) {
+        vega.formatLocale(formatLocale);
+    }
+    if (timeFormatLocale != null) {
+        vega.timeFormatLocale(timeFormatLocale);
+    }
+    let view = vegaToView(vgSpec, allowedBaseUrls, fetchRetry, dataAccess, errors);
+    let svgPromise = view.runAsync().then(() => {
+        try {
+            // Workaround for https://github.com/vega/vega/issues/3481
+            view.signal("geo_interval_init_tick", {});
+        } catch (e) {
+            // No geo_interval_init_tick signal
+        }
+    }).then(() => {
+        applyTruncationNote(view);
+        return applyInteractions(view, interactions);
+    }).then(() => {
+        return view.runAsync().then(async () => {
+            lastDatasetSummary = datasetSummary(view, vgSpec);
+            if (collectDatasets) {
+                lastDatasets = datasetRows(view, vgSpec);
+            }
+            applyGenericFontMapping(view.scenegraph().root);
+            const svg = await viewToSvg(view, nativeTooltips);
+            lastRenderWarnings = collectRenderWarnings(
+                [...rowLimitWarnings, ...topojsonWarnings, ...renderWarnings(view)],
+            );
+            return svg;
+        }).finally(() => {
+            view.finalize();
+            vega.resetDefaultLocale();
+        })
+    });
+    return svgPromise
+}
+
+function cloneScenegraph(obj) {
+    const keys = [
+      'marktype', 'name', 'role', 'interactive', 'clip', 'items', 'zindex',
+      'x', 'y', 'width', 'height', 'align', 'baseline',             // layout
+      'fill', 'fillOpacity', 'opacity', 'blend',                    // fill
+      'x1', 'y1', 'r1', 'r2', 'gradient',                           // gradient
+      'stops', 'offset', 'color',
+      'stroke', 'strokeOpacity', 'strokeWidth', 'strokeCap',        // stroke
+      'strokeJoin',
+      'strokeDash', 'strokeDashOffset',                             // stroke dash
+      'strokeForeground', 'strokeOffset',                           // group
+      'startAngle', 'endAngle', 'innerRadius', 'outerRadius',       // arc
+      'cornerRadius', 'padAngle',                                   // arc, rect
+      'cornerRadiusTopLeft', 'cornerRadiusTopRight',                // rect, group
+      'cornerRadiusBottomLeft', 'cornerRadiusBottomRight',
+      'interpolate', 'tension', 'orient', 'defined',                // area, line
+      'url', 'aspect', 'smooth',                                    // image
+      'path', 'scaleX', 'scaleY',                                   // path
+      'x2', 'y2',                                                   // rule
+      'size', 'shape',                                              // symbol
+      'text', 'angle', 'theta', 'radius', 'dir', 'dx', 'dy',        // text
+      'ellipsis', 'limit', 'lineBreak', 'lineHeight',
+      'font', 'fontSize', 'fontWeight', 'fontStyle', 'fontVariant', // font
+      'description', 'aria', 'ariaRole', 'ariaRoleDescription'      // aria
+    ];
+
+    // Check if the input is an object (including an array) or null
+    if (typeof obj !== 'object' || obj === null) {
+        return obj;
+    }
+
+    // Initialize the clone as an array or object based on the input type
+    const clone = Array.isArray(obj) ? [] : {};
+
+    // If the object is an array, iterate over its elements
+    if (Array.isArray(obj)) {
+        for (let i = 0; i < obj.length; i++) {
+            // Apply the function recursively to each element
+            clone.push(cloneScenegraph(obj[i]));
+        }
+    } else {
+        // If the object is not an array, iterate over its keys
+        for (const key in obj) {
+            // Clone only the properties with specified keys
+            if (key === "shape" && typeof obj[key] === "function") {
+                // Convert path object to SVG path string.
+                // Initialize context. This is needed for obj.shape(obj) to work.
+                obj.shape.context();
+                clone["shape"] = obj.shape(obj) ?? "";
+            } else if (keys.includes(key)) {
+                clone[key] = cloneScenegraph(obj[key]);
+            }
+        }
+    }
+
+    return clone;
+}
+
+function vegaToScenegraph(vgSpec, allowedBaseUrls, fetchRetry, dataAccess, interactions, formatLocale, timeFormatLocale, errors) {
+    return runVegaView(vgSpec, allowedBaseUrls, fetchRetry, dataAccess, interactions, formatLocale, timeFormatLocale, errors, (view) => {
+        applyGenericFontMapping(view.scenegraph().root);
+        let padding = view.padding();
+        return {
+            width: Math.max(0, view._viewWidth + padding.left + padding.right),
+            height: Math.max(0, view._viewHeight + padding.top + padding.bottom),
+            origin: [
+                padding.left + view._origin[0],
+                padding.top + view._origin[1]
+            ],
+            scenegraph: cloneScenegraph(view.scenegraph().root)
+        }
+    });
+}
+
+function vegaToRenderStats(vgSpec, allowedBaseUrls, fetchRetry, dataAccess, interactions, formatLocale, timeFormatLocale, errors) {
+    return runVegaView(vgSpec, allowedBaseUrls, fetchRetry, dataAccess, interactions, formatLocale, timeFormatLocale, errors, (view) => {
+        return renderStats(view.scenegraph().root);
+    });
+}
+
+// Count the group items, the items of each mark type, the text items with text, and the
+// marks and items of a scenegraph, without cloning it
+function renderStats(root) {
+    let stats = {
+        group_count: 0,
+        item_counts_by_marktype: {},
+        text_item_count: 0,
+        total_scenegraph_nodes: 0,
+    };
+    let marks = [root];
+    while (marks.length > 0) {
+        let mark = marks.pop();
+        let items = mark.items ?? [];
+        let counts = stats.item_counts_by_marktype;
+        counts[mark.marktype] = (counts[mark.marktype] ?? 0) + items.length;
+        stats.total_scenegraph_nodes += 1 + items.length;
+        if (mark.marktype === "group") {
+            stats.group_count += items.length;
+            for (const item of items) {
+                marks.push(...(item.items ?? []));
+            }
+        } else if (mark.marktype === "text") {
+            stats.text_item_count += items.filter((item) => item.text != null && `${item.text}` !== "").length;
+        }
+    }
+    return stats;
+}
+
+function vegaToFacetCells(vgSpec, allowedBaseUrls, fetchRetry, dataAccess, interactions, formatLocale, timeFormatLocale, errors) {
+    return runVegaView(vgSpec, allowedBaseUrls, fetchRetry, dataAccess, interactions, formatLocale, timeFormatLocale, errors, async (view) => {
+        applyGenericFontMapping(view.scenegraph().root);
+        let padding = view.padding();
+        let fields = facetFields(vgSpec);
+        return {
+            svg: await viewToSvg(view, false),
+            width: Math.max(0, view._viewWidth + padding.left + padding.right),
+            height: Math.max(0, view._viewHeight + padding.top + padding.bottom),
+            row_field: fields.row,
+            column_field: fields.column,
+            cells: facetCells(view, fields),
+        };
+    });
+}
+
+// Find the row and column fields of a Vega spec compiled from a faceted Vega-Lite spec.
+// Vega-Lite groups the row and column domains of the facet by the row and column fields,
+// and the domain of wrapped facets by the facet field, which is reported as the column
+function facetFields(vgSpec) {
+    const domainField = (name) => {
+        let data = (vgSpec.data ?? []).find((data) => data.name === name);
+        let aggregate = (data?.transform ?? []).find((tx) => tx.type === "aggregate");
+        return aggregate?.groupby?.[0] ?? null;
+    };
+    return {
+        row: domainField("row_domain"),
+        column: domainField("column_domain") ?? domainField("facet_domain"),
+    };
+}
+
+// Find the cells of a view with the facet fields from facetFields, with their facet values
+// and their bounds in the SVG image of the view. The bounds with_guides also include the
+// row and column header and footer groups next to the cell, which hold the headers and the
+// shared axes of the cell
+function facetCells(view, fields) {
+    let rowField = fields.row == null ? null : vega.field(fields.row);
+    let columnField = fields.column == null ? null : vega.field(fields.column);
+    let padding = view.padding();
+    let root = view.scenegraph().root.items[0] ?? {};
+    let originX = padding.left + view._origin[0] + (root.x ?? 0);
+    let originY = padding.top + view._origin[1] + (root.y ?? 0);
+    let cellMark = (root.items ?? []).find(
+        (mark) => mark.marktype === "group" && mark.name === "cell"
+    );
+    let guides = (root.items ?? [])
+        .filter((mark) => mark.marktype === "group" && /^(row|column)_(header|footer)$/.test(mark.name))
+        .flatMap((mark) => mark.items ?? [])
+        .map((item) => item.bounds)
+        .filter((bounds) => bounds != null && !bounds.empty());
+    let cells = (cellMark?.items ?? []).map((item) => new vega.Bounds().set(
+        item.x ?? 0,
+        item.y ?? 0,
+        (item.x ?? 0) + (item.width ?? 0),
+        (item.y ?? 0) + (item.height ?? 0),
+    ));
+    return (cellMark?.items ?? []).map((item, i) => {
+        let bounds = cellGuideBounds(cells[i], cells, guides);
+        return {
+            row_value: rowField == null ? null : rowField(item.datum) ?? null,
+            column_value: columnField == null ? null : columnField(item.datum) ?? null,
+            x: originX + cells[i].x1,
+            y: originY + cells[i].y1,
+            width: cells[i].width(),
+            height: cells[i].height(),
+            with_guides: {
+                x: originX + bounds.x1,
+                y: originY + bounds.y1,
+                width: bounds.width(),
+                height: bounds.height(),
+            },
+        };
+    });
+}
+
+// Bounds of a cell along with the guide groups next to it. A guide group is next to a cell
+// when the center of the group is within the columns of the cell and it's above or below
+// the cell, or within the rows of the cell and it's on its left or right, and no other cell
+// is between them
+function cellGuideBounds(cell, cells, guides) {
+    let bounds = cell.clone();
+    let center = (b) => [(b.x1 + b.x2) / 2, (b.y1 + b.y2) / 2];
+    let within = (v, b, axis) => axis === 0 ? b.x1 <= v && v <= b.x2 : b.y1 <= v && v <= b.y2;
+    for (let guide of guides) {
+        let guideCenter = center(guide);
+        let cellCenter = center(cell);
+        // axis is the direction from the cell to the guide group
+        let axis = within(guideCenter[0], cell, 0) ? 1 : within(guideCenter[1], cell, 1) ? 0 : null;
+        if (axis == null || within(guideCenter[axis], cell, axis)) {
+            continue;
+        }
+        let [low, high] = [guideCenter[axis], cellCenter[axis]].sort((a, b) => a - b);
+        let blocked = cells.some((other) => {
+            let otherCenter = center(other);
+            return other !== cell
+                && low < otherCenter[axis] && otherCenter[axis] < high
+                && within(otherCenter[1 - axis], guide, 1 - axis);
+        });
+        if (!blocked) {
+            bounds.union(guide);
+        }
+    }
+    return bounds;
+}
+
+// Run the dataflow of a Vega view, apply the interactions, and return the result of
+// calling result with the view once it's rendered
+function runVegaView(vgSpec, allowedBaseUrls, fetchRetry, dataAccess, interactions, formatLocale, timeFormatLocale, errors, result) {
+    if (formatLocale != null) {
+        vega.formatLocale(formatLocale);
+    }
+    if (timeFormatLocale != null) {
+        vega.timeFormatLocale(timeFormatLocale);
+    }
+    let view = vegaToView(vgSpec, allowedBaseUrls, fetchRetry, dataAccess, errors);
+    let scenegraphPromise = view.runAsync().then(() => {
+        try {
+            // Workaround for https://github.com/vega/vega/issues/3481
+            view.signal("geo_interval_init_tick", {});
+        } catch (e) {
+            // No geo_interval_init_tick signal
+        }
+    }).then(() => {
+        applyTruncationNote(view);
+        return applyInteractions(view, interactions);
+    }).then(() => {
+        return view.runAsync().then(
+            () => {
+                lastDatasetSummary = datasetSummary(view, vgSpec);
+                lastRenderWarnings = collectRenderWarnings([...rowLimitWarnings, ...topojsonWarnings]);
+                return result(view);
+            }
+        ).finally(() => {
+            view.finalize();
+            vega.resetDefaultLocale();
+        })
+    });
+    return scenegraphPromise
+}
+"#;
+            self.worker.execute_script(
+                "ext:<anon>",
+                deno_core::FastString::from_static(function_str),
+            )?;
+            self.worker.run_event_loop(false).await?;
+
+            self.vega_initialized = true;
+        }
+
+        Ok(())
+    }
+
+    async fn init_vl_version(&mut self, vl_version: &VlVersion) -> Result<(), AnyError> {
+        vl_version.ensure_included()?;
+        if !self.initialized_vl_versions.contains(vl_version) {
+            // Create and evaluate import string
+            let import_code = format!(
+                r#"
+var {ver_name} = globalThis.vlConvertSnapshot?.vegaLite["{vl_semver}"];
+if ({ver_name} == null) {{
+    import('{vl_url}').then((imported) => {{
+        {ver_name} = imported;
+    }})
+}}
+"#,
+                ver_name = format!("{:?}", vl_version),
+                vl_semver = vl_version.to_semver(),
+                vl_url = vl_version.to_url()
+            );
+
+            self.worker
+                .execute_script("ext:<anon>", import_code.into())?;
+
+            self.worker.run_event_loop(false).await?;
+
+            // Create and initialize function string
+            let function_code = format!(
+                r#"
+function compileVegaLite_{ver_name}(vlSpec, config, theme, warnings) {{
+    let options = {{}};
+
+    // Handle config and theme
+    let usermetaTheme = ((vlSpec.usermeta ?? {{}}).embedOptions ?? {{}}).theme;
+    let namedTheme = theme ?? usermetaTheme;
+    if (namedTheme != null) {{
+        options["config"] = vega.mergeConfig(vegaThemes[namedTheme], config ?? {{}});
+    }} else if (config != null) {{
+        options["config"] = config;
+    }}
+
+    options["logger"] = new VegaLiteLogger(warnings);
+
+    return {ver_name}.compile(vlSpec, options).spec
+}}
+
+function vegaLiteToSvg_{ver_name}(vlSpec, config, theme, warnings, allowedBaseUrls, fetchRetry, dataAccess, interactions, formatLocale, timeFormatLocale, nativeTooltips, errors) {{
+    let vgSpec = compileVegaLite_{ver_name}(vlSpec, config, theme, warnings);
+    return vegaToSvg(vgSpec, allowedBaseUrls, fetchRetry, dataAccess, interactions, formatLocale, timeFormatLocale, nativeTooltips, errors)
+}}
+
+function vegaLiteToScenegraph_{ver_name}(vlSpec, config, theme, warnings, allowedBaseUrls, fetchRetry, dataAccess, interactions, formatLocale, timeFormatLocale, errors) {{
+    let vgSpec = compileVegaLite_{ver_name}(vlSpec, config, theme, warnings);
+    return vegaToScenegraph(vgSpec, allowedBaseUrls, fetchRetry, dataAccess, interactions, formatLocale, timeFormatLocale, errors)
+}}
+
+function vegaLiteToFacetCells_{ver_name}(vlSpec, config, theme, warnings, allowedBaseUrls, fetchRetry, dataAccess, interactions, formatLocale, timeFormatLocale, errors) {{
+    let vgSpec = compileVegaLite_{ver_name}(vlSpec, config, theme, warnings);
+    return vegaToFacetCells(vgSpec, allowedBaseUrls, fetchRetry, dataAccess, interactions, formatLocale, timeFormatLocale, errors)
+}}
+
+async function vegaLiteFreeze_{ver_name}(vlSpec, config, theme, allowedBaseUrls, fetchRetry, dataAccess, vlConvertVersion, errors) {{
+    let {{$schema, ...frozen}} = structuredClone(vlSpec);
+    await inlineUrlData(frozen, vegaLoader(allowedBaseUrls, fetchRetry, dataAccess, errors));
+
+    // Merge the theme and config into the spec's config, which takes precedence
+    let usermetaTheme = frozen.usermeta?.embedOptions?.theme;
+    let namedTheme = theme ?? usermetaTheme;
+    let themeConfig = namedTheme == null ? {{}} : vegaThemes[namedTheme];
+    frozen.config = vega.mergeConfig(themeConfig, config ?? {{}}, frozen.config ?? {{}});
+    if (usermetaTheme != null) {{
+        delete frozen.usermeta.embedOptions.theme;
+    }}
+
+    frozen.usermeta = {{
+        ...frozen.usermeta,
+        vlConvert: {{version: vlConvertVersion, timestamp: new Date().toISOString()}},
+    }};
+    return {{
+        $schema: `https://vega.github.io/schema/vega-lite/v${{{ver_name}.version}}.json`,
+        ...frozen,
+    }};
+}}
+
+function vegaLiteToVegaAndSvg_{ver_name}(vlSpec, config, theme, warnings, allowedBaseUrls, fetchRetry, dataAccess, interactions, formatLocale, timeFormatLocale, errors) {{
+    let vgSpec = compileVegaLite_{ver_name}(vlSpec, config, theme, warnings);
+    // Clone before rendering so that the returned spec is exactly the one that was compiled
+    let vgSpecClone = structuredClone(vgSpec);
+    return vegaToSvg(vgSpec, allowedBaseUrls, fetchRetry, dataAccess, interactions, formatLocale, timeFormatLocale, false, errors).then(
+        (svg) => ({{vega: vgSpecClone, svg}})
+    )
+}}
+"#,
+                ver_name = format!("{:?}", vl_version),
+            );
+
+            self.worker
+                .execute_script("ext:<anon>", function_code.into())?;
+
+            self.worker.run_event_loop(false).await?;
+
+            // Register that this Vega-Lite version has been initialized
+            self.initialized_vl_versions.insert(*vl_version);
+            self.counters.record_vl_module_evaluation();
+        }
+        Ok(())
+    }
+
+    /// Evaluate the modules of Vega and of `vl_versions` ahead of the conversions that use
+    /// them. Versions that were evaluated already are skipped
+    async fn warm_versions(&mut self, vl_versions: &[VlVersion]) -> Result<(), AnyError> {
+        self.init_vega().await?;
+        for vl_version in vl_versions {
+            self.init_vl_version(vl_version).await?;
+        }
+        Ok(())
+    }
+
+    async fn register_expression_fn(
+        &mut self,
+        name: String,
+        f: ExpressionFn,
+    ) -> Result<(), AnyError> {
+        if !is_expression_identifier(&name) {
+            bail!("Invalid expression function name: {name}");
+        }
+        self.init_vega().await?;
+        self.worker
+            .js_runtime
+            .op_state()
+            .borrow_mut()
+            .borrow_mut::<ExpressionFns>()
+            .0
+            .insert(name.clone(), f);
+
+        let code = format!("registerExpressionFn({})", serde_json::to_string(&name)?);
+        self.worker.execute_script("ext:<anon>", code.into())?;
+        Ok(())
+    }
+
+    /// Record resource statistics for a conversion that just completed on this worker
+    fn record_resource_stats(
+        &mut self,
+        rss_before: &RssSample,
+        svg_bytes: usize,
+    ) -> Result<(), AnyError> {
+        let mut heap_stats = v8::HeapStatistics::default();
+        self.worker
+            .js_runtime
+            .v8_isolate()
+            .get_heap_statistics(&mut heap_stats);
+        set_last_resource_stats(ResourceStats {
+            v8_heap_used_bytes: heap_stats.used_heap_size() as u64,
+            v8_heap_total_bytes: heap_stats.total_heap_size() as u64,
+            pixmap_bytes: 0,
+            svg_bytes: svg_bytes as u64,
+            peak_rss_delta_bytes: RssSample::now().delta_since(rss_before),
+        })
+    }
+
+    /// Run the script of a conversion that renders a view, with the generic families of its
+    /// text mapped to the fonts of `generic_font_mapping`, charts that are sized to their
+    /// container rendered in `container_size`, its datasets limited by `row_limit` (a
+    /// literal from [`row_limit_arg`]), the browser globals of `browser_shims` (a literal
+    /// from [`browser_shims_arg`]), and its TopoJSON datasets simplified with the tolerance of
+    /// `simplify_topojson` (a literal from [`simplify_topojson_arg`]). When Vega fails to parse
+    /// the spec
+    /// or to evaluate its dataflow, the error is a [`DataflowError`], located in `vl_spec`
+    /// for conversions of Vega-Lite specs
+    #[allow(clippy::too_many_arguments)]
+    async fn execute_view_script(
+        &mut self,
+        code: String,
+        vl_spec: Option<&serde_json::Value>,
+        generic_font_mapping: Option<&HashMap<String, String>>,
+        container_size: ContainerSize,
+        random_seed: Option<u32>,
+        row_limit: String,
+        view_log: String,
+        browser_shims: String,
+        simplify_topojson: String,
+    ) -> Result<(), AnyError> {
+        if let Some(mapping) = generic_font_mapping {
+            check_generic_font_mapping(mapping)?;
+        }
+        let generic_font_mapping = serde_json::to_string(&generic_font_mapping)?;
+        let container_size = serde_json::to_string(&container_size)?;
+        let random_seed = serde_json::to_string(&random_seed)?;
+        let result = async {
+            let code = format!(
+                "lastDataflowError = null;\ngenericFontMapping = {generic_font_mapping};\n\
+                containerSize = {container_size};\nsetRandomSeed({random_seed});\n\
+                rowLimit = {row_limit};\nviewLog = compileViewLog({view_log});\n\
+                browserShims = {browser_shims};\ntopojsonTolerance = {simplify_topojson};\n\
+                suppressedWarnings = 0;\n{code}"
+            );
+            self.worker.execute_script("ext:<anon>", code.into())?;
+            self.worker.run_event_loop(false).await?;
+            Ok::<_, AnyError>(())
+        }
+        .await;
+        // Compiling Vega-Lite specs outside of a view script doesn't filter warnings
+        self.worker.execute_script(
+            "ext:<anon>",
+            deno_core::FastString::from_static("viewLog = null;"),
+        )?;
+        let Err(err) = result else {
+            return Ok(());
+        };
+
+        let dataflow_error = self.execute_script_to_json("lastDataflowError").await?;
+        if dataflow_error.is_null() {
+            return Err(err);
+        }
+        let mut dataflow_error: DataflowError = serde_json::from_value(dataflow_error)?;
+        if let Some(vl_spec) = vl_spec {
+            dataflow_error.locate_in_vegalite(vl_spec);
+        }
+        Err(dataflow_error.into())
+    }
+
+    /// Record the render warnings of a lenient conversion that just completed on this worker,
+    /// along with the warnings that were collected before the chart was rendered
+    async fn record_render_warnings(
+        &mut self,
+        warnings: Vec<RenderWarning>,
+    ) -> Result<(), AnyError> {
+        let view_warnings = self.execute_script_to_json("lastRenderWarnings").await?;
+        self.render_warnings = serde_json::from_value(view_warnings)?;
+        extend_render_warnings(&mut self.render_warnings, warnings);
+        Ok(())
+    }
+
+    /// Bundle the result of a conversion that just completed on this worker with its render
+    /// warnings and, when `collect_dataset_summary` is set, the summary of its datasets
+    async fn rendered<T>(
+        &mut self,
+        value: T,
+        collect_dataset_summary: bool,
+    ) -> Result<Rendered<T>, AnyError> {
+        let dataset_summary = if collect_dataset_summary {
+            let summary = self.execute_script_to_json("lastDatasetSummary").await?;
+            if summary.is_null() {
+                bail!("No dataset summary was recorded for the conversion")
+            }
+            Some(serde_json::from_value(summary)?)
+        } else {
+            None
+        };
+        Ok(Rendered {
+            value,
+            warnings: std::mem::take(&mut self.render_warnings),
+            dataset_summary,
+        })
+    }
+
+    pub async fn try_new(counters: Arc<ConversionCounters>) -> Result<Self, AnyError> {
+        #[cfg(feature = "verify-vendor")]
+        ensure_vendor_integrity()?;
+
+        let module_loader = Rc::new(VlConvertModuleLoader);
+        let options = WorkerOptions {
+            extensions: vec![
+                vl_convert_text_runtime::init_ops(),
+                vl_convert_converter_runtime::init_ops(),
+            ],
+            module_loader: module_loader.clone(),
+            startup_snapshot: RUNTIME_SNAPSHOT,
+            ..Default::default()
+        };
+
+        let main_module =
+            deno_core::resolve_path("vl-convert-rs.js", Path::new(env!("CARGO_MANIFEST_DIR")))?;
+
+        let permissions = PermissionsContainer::new(Permissions::allow_all());
+
+        let mut worker =
+            MainWorker::bootstrap_from_options(main_module.clone(), permissions, options);
+
+        worker.execute_main_module(&main_module).await?;
+        worker.run_event_loop(false).await?;
+        worker
+            .js_runtime
+            .op_state()
+            .borrow_mut()
+            .put(ExpressionFns::default());
+
+        let this = Self {
+            worker,
+            initialized_vl_versions: Default::default(),
+            counters,
+            vega_initialized: false,
+            render_warnings: Vec::new(),
+            device_pixel_ratio: 1.0,
+        };
+
+        Ok(this)
+    }
+
+    async fn execute_script_to_json(
+        &mut self,
+        script: &str,
+    ) -> Result<serde_json::Value, AnyError> {
+        let code = script.to_string();
+        let res = self.worker.js_runtime.execute_script("ext:<anon>", code)?;
+
+        self.worker.run_event_loop(false).await?;
+
+        let scope = &mut self.worker.js_runtime.handle_scope();
+        let local = v8::Local::new(scope, res);
+
+        // Deserialize a `v8` object into a Rust type using `serde_v8`,
+        // in this case deserialize to a JSON `Value`.
+        let deserialized_value = serde_v8::from_v8::<serde_json::Value>(scope, local);
+        deserialized_value.map_err(|err| {
+            anyhow!(
+                "Failed to deserialize JavaScript value: {}",
+                err.to_string()
+            )
+        })
+    }
+
+    async fn execute_script_to_string(&mut self, script: &str) -> Result<String, AnyError> {
+        let code = script.to_string();
+        let res = self.worker.js_runtime.execute_script("ext:<anon>", code)?;
+
+        self.worker.run_event_loop(false).await?;
+
+        let scope = &mut self.worker.js_runtime.handle_scope();
+        let local = v8::Local::new(scope, res);
+
+        // Deserialize a `v8` object into a Rust type using `serde_v8`,
+        // in this case deserialize to a JSON `Value`.
+        let deserialized_value = serde_v8::from_v8::<serde_json::Value>(scope, local);
+
+        let value = match deserialized_value {
+            Ok(value) => {
+                let value = value.as_str();
+                value.unwrap().to_string()
+            }
+            Err(err) => bail!("{}", err.to_string()),
+        };
+
+        Ok(value)
+    }
+
+    pub async fn vegalite_to_vega(
+        &mut self,
+        vl_spec: &serde_json::Value,
+        vl_opts: VlOpts,
+    ) -> Result<serde_json::Value, AnyError> {
+        let resized =
+            with_size_override(vl_spec, vl_opts.width, vl_opts.height, vl_opts.resize_mode)?;
+        let vl_spec = resized.as_ref();
+        let suppressed =
+            suppress_vegalite_components(vl_spec, vl_opts.suppress_components.as_deref());
+        let vl_spec = suppressed.as_ref();
+        let (dated, _) = with_lenient_dates(vl_spec, vl_opts.lenient_dates);
+        let vl_spec = dated.as_ref();
+        get_spec_limits()?.check_view_count(vl_spec)?;
+        self.init_vega().await?;
+        self.init_vl_version(&vl_opts.vl_version).await?;
+        let config = vl_opts.effective_config().unwrap_or_default();
+
+        let (vl_spec, int64_strings) = Int64Strings::encode(vl_spec, vl_opts.preserve_int64);
+        let spec_arg_id = set_json_arg(vl_spec)?;
+        let config_arg_id = set_json_arg(config)?;
+
+        let theme_arg = match &vl_opts.theme {
+            None => "null".to_string(),
+            Some(s) => format!("'{}'", s),
+        };
+
+        let allowed_base_urls =
+            serde_json::to_string(&serde_json::Value::from(vl_opts.allowed_base_urls))?;
+
+        let code = format!(
+            r#"
+compileVegaLite_{ver_name:?}(
+    getJsonArg({spec_arg_id}),
+    getJsonArg({config_arg_id}),
+    {theme_arg},
+    {show_warnings},
+    {allowed_base_urls},
+)
+"#,
+            ver_name = vl_opts.vl_version,
+            spec_arg_id = spec_arg_id,
+            config_arg_id = config_arg_id,
+            theme_arg = theme_arg,
+            show_warnings = vl_opts.show_warnings,
+        );
+
+        let mut value = self.execute_script_to_json(&code).await?;
+        int64_strings.decode(&mut value);
+        Ok(value)
+    }
+
+    /// The pre-aggregated spec that's rendered in place of `vl_spec` when the pre_aggregate
+    /// option is set, or None when the spec is rendered as is
+    async fn pre_aggregated_spec(
+        &mut self,
+        vl_spec: &serde_json::Value,
+        vl_opts: &VlOpts,
+    ) -> Result<Option<serde_json::Value>, AnyError> {
+        if !vl_opts.pre_aggregate {
+            return Ok(None);
+        }
+        let theme_config = match &vl_opts.theme {
+            None => serde_json::Value::Null,
+            Some(theme) => {
+                let theme = serde_json::to_string(theme)?;
+                self.execute_script_to_json(&format!("vegaThemes[{theme}] ?? null"))
+                    .await?
+            }
+        };
+        let config = vl_opts.effective_config().unwrap_or_default();
+        match pre_aggregate_spec(vl_spec, &[&theme_config, &config]) {
+            Ok(spec) => Ok(Some(spec)),
+            Err(reason) => {
+                log::debug!(
+                    target: LOG_TARGET,
+                    "Converting the spec without pre-aggregation: {reason}"
+                );
+                Ok(None)
+            }
+        }
+    }
+
+    pub async fn vegalite_to_svg(
+        &mut self,
+        vl_spec: &serde_json::Value,
+        vl_opts: VlOpts,
+        svg_opts: SvgOpts,
+    ) -> Result<String, AnyError> {
+        let container_size = vl_opts.container_size()?;
+        let browser_shims = browser_shims_arg(
+            vl_opts.browser_shims,
+            vl_opts.format_locale.as_ref(),
+            self.device_pixel_ratio,
+        );
+        let resized =
+            with_size_override(vl_spec, vl_opts.width, vl_opts.height, vl_opts.resize_mode)?;
+        let vl_spec = resized.as_ref();
+        let suppressed =
+            suppress_vegalite_components(vl_spec, vl_opts.suppress_components.as_deref());
+        let vl_spec = suppressed.as_ref();
+        let (dated, date_warnings) = with_lenient_dates(vl_spec, vl_opts.lenient_dates);
+        let vl_spec = dated.as_ref();
+        get_spec_limits()?.check_view_count(vl_spec)?;
+        self.init_vega().await?;
+        self.init_vl_version(&vl_opts.vl_version).await?;
+        let pre_aggregated = self.pre_aggregated_spec(vl_spec, &vl_opts).await?;
+
+        let config = vl_opts.effective_config().unwrap_or_default();
+
+        let format_locale = match vl_opts.format_locale {
+            None => serde_json::Value::Null,
+            Some(fl) => fl.as_object()?,
+        };
+
+        let time_format_locale = match vl_opts.time_format_locale {
+            None => serde_json::Value::Null,
+            Some(fl) => fl.as_object()?,
+        };
+
+        let (encoded_spec, _) = Int64Strings::encode(
+            pre_aggregated.as_ref().unwrap_or(vl_spec),
+            vl_opts.preserve_int64,
+        );
+        let spec_arg_id = set_json_arg(encoded_spec)?;
+        let config_arg_id = set_json_arg(config)?;
+        let format_locale_id = set_json_arg(format_locale)?;
+        let time_format_locale_id = set_json_arg(time_format_locale)?;
+        let interactions_id = set_json_arg(serde_json::to_value(&vl_opts.interaction_script)?)?;
+
+        let theme_arg = match &vl_opts.theme {
+            None => "null".to_string(),
+            Some(s) => format!("'{}'", s),
+        };
+
+        let allowed_base_urls =
+            serde_json::to_string(&serde_json::Value::from(vl_opts.allowed_base_urls))?;
+        let fetch_retry = fetch_retry_arg(FetchRetry::from_opts(
+            vl_opts.fetch_retries,
+            vl_opts.fetch_retry_backoff_ms,
+        ));
+        let data_access =
+            data_access_arg(&vl_opts.base_url, vl_opts.allow_local_data, vl_opts.lenient)?;
+
+        let code = format!(
+            r#"
+var svg;
+var errors = [];
+vegaLiteToSvg_{ver_name:?}(
+    getJsonArg({spec_arg_id}),
+    getJsonArg({config_arg_id}),
     {theme_arg},
     {show_warnings},
     {allowed_base_urls},
-)
+    {fetch_retry},
+    {data_access},
+    getJsonArg({interactions_id}),
+    getJsonArg({format_locale_id}),
+    getJsonArg({time_format_locale_id}),
+    {native_tooltips},
+    errors,
+).then((result) => {{
+    if (errors != null && errors.length > 0) {{
+        throw new Error(`${{errors}}`);
+    }}
+    svg = result;
+}});
+"#,
+            ver_name = vl_opts.vl_version,
+            show_warnings = vl_opts.show_warnings,
+            native_tooltips = svg_opts.native_tooltips,
+        );
+        self.execute_view_script(
+            code,
+            Some(vl_spec),
+            vl_opts.generic_font_mapping.as_ref(),
+            container_size,
+            None,
+            row_limit_arg(
+                vl_opts.max_rows,
+                vl_opts.row_limit_strategy,
+                vl_opts.annotate_truncation,
+            )?,
+            view_log_arg(vl_opts.log_level, vl_opts.warning_filters.as_deref())?,
+            browser_shims,
+            simplify_topojson_arg(vl_opts.simplify_topojson)?,
+        )
+        .await?;
+        if vl_opts.lenient
+            || vl_opts.max_rows.is_some()
+            || vl_opts.collects_view_warnings()
+            || vl_opts.lenient_dates
+            || vl_opts.simplify_topojson.is_some()
+        {
+            self.record_render_warnings(date_warnings).await?;
+        }
+
+        let value = self.execute_script_to_string("svg").await?;
+        check_chart_size(value, vl_opts.min_size)
+    }
+
+    /// Convert a Vega-Lite spec to SVG, keeping the rows of the chart's top-level datasets
+    /// once it's rendered
+    pub async fn vegalite_to_svg_and_datasets(
+        &mut self,
+        vl_spec: &serde_json::Value,
+        vl_opts: VlOpts,
+    ) -> Result<(String, Vec<ExportedDataset>), AnyError> {
+        // collectDatasets is defined once Vega is initialized
+        self.init_vega().await?;
+        self.worker.execute_script(
+            "ext:<anon>",
+            deno_core::FastString::from_static("collectDatasets = true;"),
+        )?;
+        let svg = self
+            .vegalite_to_svg(vl_spec, vl_opts, Default::default())
+            .await;
+        self.worker.execute_script(
+            "ext:<anon>",
+            deno_core::FastString::from_static("collectDatasets = false;"),
+        )?;
+        let svg = svg?;
+
+        let datasets = self.execute_script_to_json("lastDatasets").await?;
+        Ok((svg, serde_json::from_value(datasets)?))
+    }
+
+    /// Convert a Vega-Lite spec to SVG once per theme, where the "default" theme renders the
+    /// chart without a theme. Data that the spec loads from urls is fetched once and shared
+    /// across the themes
+    pub async fn vegalite_to_svg_themed(
+        &mut self,
+        vl_spec: &serde_json::Value,
+        vl_opts: VlOpts,
+        svg_opts: SvgOpts,
+        themes: &[String],
+    ) -> Result<Vec<(String, String)>, AnyError> {
+        // fetchCache is defined once Vega is initialized
+        self.init_vega().await?;
+        self.worker.execute_script(
+            "ext:<anon>",
+            deno_core::FastString::from_static("fetchCache = new Map();"),
+        )?;
+        let mut svgs = Vec::new();
+        let mut error = None;
+        for theme in themes {
+            let theme_opts = VlOpts {
+                theme: (theme != "default").then(|| theme.clone()),
+                ..vl_opts.clone()
+            };
+            match self.vegalite_to_svg(vl_spec, theme_opts, svg_opts).await {
+                Ok(svg) => svgs.push((theme.clone(), svg)),
+                Err(err) => {
+                    error = Some(err);
+                    break;
+                }
+            }
+        }
+        self.worker.execute_script(
+            "ext:<anon>",
+            deno_core::FastString::from_static("fetchCache = null;"),
+        )?;
+        match error {
+            Some(err) => Err(err),
+            None => Ok(svgs),
+        }
+    }
+
+    pub async fn vegalite_to_scenegraph(
+        &mut self,
+        vl_spec: &serde_json::Value,
+        vl_opts: VlOpts,
+    ) -> Result<serde_json::Value, AnyError> {
+        // Scenegraph items don't include their data, so the strings of preserved integers
+        // only appear as the text of marks
+        let (sg, _) = self
+            .run_vegalite_view(vl_spec, vl_opts, "vegaLiteToScenegraph")
+            .await?;
+        Ok(sg)
+    }
+
+    pub async fn vegalite_facet_cells(
+        &mut self,
+        vl_spec: &serde_json::Value,
+        vl_opts: VlOpts,
+    ) -> Result<FacetCells, AnyError> {
+        let (facet_cells, int64_strings) = self
+            .run_vegalite_view(vl_spec, vl_opts, "vegaLiteToFacetCells")
+            .await?;
+        let mut facet_cells: FacetCells = serde_json::from_value(facet_cells)?;
+        for cell in facet_cells.cells.iter_mut() {
+            let values = [
+                (&facet_cells.row_field, &mut cell.row_value),
+                (&facet_cells.column_field, &mut cell.column_value),
+            ];
+            for (field, value) in values {
+                if let (Some(field), Some(value)) = (field, value) {
+                    int64_strings.decode_field_value(field, value);
+                }
+            }
+        }
+        Ok(facet_cells)
+    }
+
+    /// Compile a Vega-Lite spec, render it in a view, and return the result of the
+    /// JavaScript function `js_function` for the Vega-Lite version, which is called like
+    /// vegaLiteToScenegraph, along with the integers that were passed to it as strings
+    async fn run_vegalite_view(
+        &mut self,
+        vl_spec: &serde_json::Value,
+        vl_opts: VlOpts,
+        js_function: &str,
+    ) -> Result<(serde_json::Value, Int64Strings), AnyError> {
+        let container_size = vl_opts.container_size()?;
+        let browser_shims = browser_shims_arg(
+            vl_opts.browser_shims,
+            vl_opts.format_locale.as_ref(),
+            self.device_pixel_ratio,
+        );
+        let resized =
+            with_size_override(vl_spec, vl_opts.width, vl_opts.height, vl_opts.resize_mode)?;
+        let vl_spec = resized.as_ref();
+        let suppressed =
+            suppress_vegalite_components(vl_spec, vl_opts.suppress_components.as_deref());
+        let vl_spec = suppressed.as_ref();
+        let (dated, date_warnings) = with_lenient_dates(vl_spec, vl_opts.lenient_dates);
+        let vl_spec = dated.as_ref();
+        get_spec_limits()?.check_view_count(vl_spec)?;
+        self.init_vega().await?;
+        self.init_vl_version(&vl_opts.vl_version).await?;
+        let pre_aggregated = self.pre_aggregated_spec(vl_spec, &vl_opts).await?;
+
+        let config = vl_opts.effective_config().unwrap_or_default();
+        let format_locale = match vl_opts.format_locale {
+            None => serde_json::Value::Null,
+            Some(fl) => fl.as_object()?,
+        };
+
+        let time_format_locale = match vl_opts.time_format_locale {
+            None => serde_json::Value::Null,
+            Some(fl) => fl.as_object()?,
+        };
+
+        let (encoded_spec, int64_strings) = Int64Strings::encode(
+            pre_aggregated.as_ref().unwrap_or(vl_spec),
+            vl_opts.preserve_int64,
+        );
+        let spec_arg_id = set_json_arg(encoded_spec)?;
+        let config_arg_id = set_json_arg(config)?;
+        let format_locale_id = set_json_arg(format_locale)?;
+        let time_format_locale_id = set_json_arg(time_format_locale)?;
+        let interactions_id = set_json_arg(serde_json::to_value(&vl_opts.interaction_script)?)?;
+
+        let theme_arg = match &vl_opts.theme {
+            None => "null".to_string(),
+            Some(s) => format!("'{}'", s),
+        };
+
+        let allowed_base_urls =
+            serde_json::to_string(&serde_json::Value::from(vl_opts.allowed_base_urls))?;
+        let fetch_retry = fetch_retry_arg(FetchRetry::from_opts(
+            vl_opts.fetch_retries,
+            vl_opts.fetch_retry_backoff_ms,
+        ));
+        let data_access =
+            data_access_arg(&vl_opts.base_url, vl_opts.allow_local_data, vl_opts.lenient)?;
+
+        let code = format!(
+            r#"
+var sg;
+var errors = [];
+{js_function}_{ver_name:?}(
+    getJsonArg({spec_arg_id}),
+    getJsonArg({config_arg_id}),
+    {theme_arg},
+    {show_warnings},
+    {allowed_base_urls},
+    {fetch_retry},
+    {data_access},
+    getJsonArg({interactions_id}),
+    getJsonArg({format_locale_id}),
+    getJsonArg({time_format_locale_id}),
+    errors,
+).then((result) => {{
+    if (errors != null && errors.length > 0) {{
+        throw new Error(`${{errors}}`);
+    }}
+    sg = result;
+}})
+"#,
+            ver_name = vl_opts.vl_version,
+            show_warnings = vl_opts.show_warnings,
+        );
+        self.execute_view_script(
+            code,
+            Some(vl_spec),
+            vl_opts.generic_font_mapping.as_ref(),
+            container_size,
+            None,
+            row_limit_arg(
+                vl_opts.max_rows,
+                vl_opts.row_limit_strategy,
+                vl_opts.annotate_truncation,
+            )?,
+            view_log_arg(vl_opts.log_level, vl_opts.warning_filters.as_deref())?,
+            browser_shims,
+            simplify_topojson_arg(vl_opts.simplify_topojson)?,
+        )
+        .await?;
+        if vl_opts.max_rows.is_some()
+            || vl_opts.collects_view_warnings()
+            || vl_opts.lenient_dates
+            || vl_opts.simplify_topojson.is_some()
+        {
+            self.record_render_warnings(date_warnings).await?;
+        }
+
+        let value = self.execute_script_to_json("sg").await?;
+        Ok((value, int64_strings))
+    }
+
+    pub async fn vegalite_to_vega_and_svg(
+        &mut self,
+        vl_spec: &serde_json::Value,
+        vl_opts: VlOpts,
+    ) -> Result<(serde_json::Value, String), AnyError> {
+        let container_size = vl_opts.container_size()?;
+        let browser_shims = browser_shims_arg(
+            vl_opts.browser_shims,
+            vl_opts.format_locale.as_ref(),
+            self.device_pixel_ratio,
+        );
+        let resized =
+            with_size_override(vl_spec, vl_opts.width, vl_opts.height, vl_opts.resize_mode)?;
+        let vl_spec = resized.as_ref();
+        let suppressed =
+            suppress_vegalite_components(vl_spec, vl_opts.suppress_components.as_deref());
+        let vl_spec = suppressed.as_ref();
+        let (dated, date_warnings) = with_lenient_dates(vl_spec, vl_opts.lenient_dates);
+        let vl_spec = dated.as_ref();
+        get_spec_limits()?.check_view_count(vl_spec)?;
+        self.init_vega().await?;
+        self.init_vl_version(&vl_opts.vl_version).await?;
+        let pre_aggregated = self.pre_aggregated_spec(vl_spec, &vl_opts).await?;
+
+        let config = vl_opts.effective_config().unwrap_or_default();
+
+        let format_locale = match vl_opts.format_locale {
+            None => serde_json::Value::Null,
+            Some(fl) => fl.as_object()?,
+        };
+
+        let time_format_locale = match vl_opts.time_format_locale {
+            None => serde_json::Value::Null,
+            Some(fl) => fl.as_object()?,
+        };
+
+        let (encoded_spec, int64_strings) = Int64Strings::encode(
+            pre_aggregated.as_ref().unwrap_or(vl_spec),
+            vl_opts.preserve_int64,
+        );
+        let spec_arg_id = set_json_arg(encoded_spec)?;
+        let config_arg_id = set_json_arg(config)?;
+        let format_locale_id = set_json_arg(format_locale)?;
+        let time_format_locale_id = set_json_arg(time_format_locale)?;
+        let interactions_id = set_json_arg(serde_json::to_value(&vl_opts.interaction_script)?)?;
+
+        let theme_arg = match &vl_opts.theme {
+            None => "null".to_string(),
+            Some(s) => format!("'{}'", s),
+        };
+
+        let allowed_base_urls =
+            serde_json::to_string(&serde_json::Value::from(vl_opts.allowed_base_urls))?;
+        let fetch_retry = fetch_retry_arg(FetchRetry::from_opts(
+            vl_opts.fetch_retries,
+            vl_opts.fetch_retry_backoff_ms,
+        ));
+        let data_access =
+            data_access_arg(&vl_opts.base_url, vl_opts.allow_local_data, vl_opts.lenient)?;
+
+        let code = format!(
+            r#"
+var vgAndSvg;
+var errors = [];
+vegaLiteToVegaAndSvg_{ver_name:?}(
+    getJsonArg({spec_arg_id}),
+    getJsonArg({config_arg_id}),
+    {theme_arg},
+    {show_warnings},
+    {allowed_base_urls},
+    {fetch_retry},
+    {data_access},
+    getJsonArg({interactions_id}),
+    getJsonArg({format_locale_id}),
+    getJsonArg({time_format_locale_id}),
+    errors,
+).then((result) => {{
+    if (errors != null && errors.length > 0) {{
+        throw new Error(`${{errors}}`);
+    }}
+    vgAndSvg = result;
+}});
+"#,
+            ver_name = vl_opts.vl_version,
+            show_warnings = vl_opts.show_warnings,
+        );
+        self.execute_view_script(
+            code,
+            Some(vl_spec),
+            vl_opts.generic_font_mapping.as_ref(),
+            container_size,
+            None,
+            row_limit_arg(
+                vl_opts.max_rows,
+                vl_opts.row_limit_strategy,
+                vl_opts.annotate_truncation,
+            )?,
+            view_log_arg(vl_opts.log_level, vl_opts.warning_filters.as_deref())?,
+            browser_shims,
+            simplify_topojson_arg(vl_opts.simplify_topojson)?,
+        )
+        .await?;
+        if vl_opts.lenient
+            || vl_opts.max_rows.is_some()
+            || vl_opts.collects_view_warnings()
+            || vl_opts.lenient_dates
+            || vl_opts.simplify_topojson.is_some()
+        {
+            self.record_render_warnings(date_warnings).await?;
+        }
+
+        let mut value = self.execute_script_to_json("vgAndSvg").await?;
+        let mut vg_spec = value
+            .get_mut("vega")
+            .map(serde_json::Value::take)
+            .ok_or_else(|| anyhow!("Missing Vega spec in conversion result"))?;
+        int64_strings.decode(&mut vg_spec);
+        let Some(svg) = value.get("svg").and_then(|svg| svg.as_str()) else {
+            bail!("Missing SVG in conversion result")
+        };
+        let svg = check_chart_size(svg.to_string(), vl_opts.min_size)?;
+        Ok((vg_spec, svg))
+    }
+
+    pub async fn vegalite_freeze(
+        &mut self,
+        vl_spec: &serde_json::Value,
+        vl_opts: VlOpts,
+    ) -> Result<serde_json::Value, AnyError> {
+        get_spec_limits()?.check_view_count(vl_spec)?;
+        self.init_vega().await?;
+        self.init_vl_version(&vl_opts.vl_version).await?;
+
+        let config = vl_opts.effective_config().unwrap_or_default();
+
+        let (vl_spec, int64_strings) = Int64Strings::encode(vl_spec, vl_opts.preserve_int64);
+        let spec_arg_id = set_json_arg(vl_spec)?;
+        let config_arg_id = set_json_arg(config)?;
+
+        let theme_arg = match &vl_opts.theme {
+            None => "null".to_string(),
+            Some(s) => format!("'{}'", s),
+        };
+
+        let allowed_base_urls =
+            serde_json::to_string(&serde_json::Value::from(vl_opts.allowed_base_urls))?;
+        let fetch_retry = fetch_retry_arg(FetchRetry::from_opts(
+            vl_opts.fetch_retries,
+            vl_opts.fetch_retry_backoff_ms,
+        ));
+        let data_access =
+            data_access_arg(&vl_opts.base_url, vl_opts.allow_local_data, vl_opts.lenient)?;
+
+        let code = format!(
+            r#"
+var frozen;
+var errors = [];
+vegaLiteFreeze_{ver_name:?}(
+    getJsonArg({spec_arg_id}),
+    getJsonArg({config_arg_id}),
+    {theme_arg},
+    {allowed_base_urls},
+    {fetch_retry},
+    {data_access},
+    '{vl_convert_version}',
+    errors,
+).then((result) => {{
+    if (errors != null && errors.length > 0) {{
+        throw new Error(`${{errors}}`);
+    }}
+    frozen = result;
+}});
+"#,
+            ver_name = vl_opts.vl_version,
+            vl_convert_version = env!("CARGO_PKG_VERSION"),
+        );
+        self.worker.execute_script("ext:<anon>", code.into())?;
+        self.worker.run_event_loop(false).await?;
+
+        let mut frozen = self.execute_script_to_json("frozen").await?;
+        int64_strings.decode(&mut frozen);
+        Ok(frozen)
+    }
+
+    pub async fn vega_to_svg(
+        &mut self,
+        vg_spec: &serde_json::Value,
+        vg_opts: VgOpts,
+        svg_opts: SvgOpts,
+    ) -> Result<String, AnyError> {
+        let container_size = vg_opts.container_size()?;
+        let browser_shims = browser_shims_arg(
+            vg_opts.browser_shims,
+            vg_opts.format_locale.as_ref(),
+            self.device_pixel_ratio,
+        );
+        self.init_vega().await?;
+        let allowed_base_urls =
+            serde_json::to_string(&serde_json::Value::from(vg_opts.allowed_base_urls))?;
+        let fetch_retry = fetch_retry_arg(FetchRetry::from_opts(
+            vg_opts.fetch_retries,
+            vg_opts.fetch_retry_backoff_ms,
+        ));
+        let data_access =
+            data_access_arg(&vg_opts.base_url, vg_opts.allow_local_data, vg_opts.lenient)?;
+
+        let format_locale = match vg_opts.format_locale {
+            None => serde_json::Value::Null,
+            Some(fl) => fl.as_object()?,
+        };
+
+        let time_format_locale = match vg_opts.time_format_locale {
+            None => serde_json::Value::Null,
+            Some(fl) => fl.as_object()?,
+        };
+
+        let theme_arg = match &vg_opts.theme {
+            None => "null".to_string(),
+            Some(s) => format!("'{}'", s),
+        };
+
+        let vg_spec =
+            with_size_override(vg_spec, vg_opts.width, vg_opts.height, vg_opts.resize_mode)?;
+        let vg_spec = suppress_vega_components(&vg_spec, vg_opts.suppress_components.as_deref());
+        let vg_spec = with_static_force(&vg_spec, vg_opts.force_iterations);
+        let (vg_spec, _) = Int64Strings::encode(&vg_spec, vg_opts.preserve_int64);
+        let arg_id = set_json_arg(vg_spec)?;
+        let config_arg_id = set_json_arg(vg_opts.config.unwrap_or(serde_json::Value::Null))?;
+        let format_locale_id = set_json_arg(format_locale)?;
+        let time_format_locale_id = set_json_arg(time_format_locale)?;
+        let interactions_id = set_json_arg(serde_json::to_value(&vg_opts.interaction_script)?)?;
+
+        let code = format!(
+            r#"
+var svg;
+var errors = [];
+vegaToSvg(
+    applyVegaConfig(getJsonArg({arg_id}), getJsonArg({config_arg_id}), {theme_arg}),
+    {allowed_base_urls},
+    {fetch_retry},
+    {data_access},
+    getJsonArg({interactions_id}),
+    getJsonArg({format_locale_id}),
+    getJsonArg({time_format_locale_id}),
+    {native_tooltips},
+    errors,
+).then((result) => {{
+    if (errors != null && errors.length > 0) {{
+        throw new Error(`${{errors}}`);
+    }}
+    svg = result;
+}})
 "#,
-            ver_name = vl_opts.vl_version,
-            spec_arg_id = spec_arg_id,
-            config_arg_id = config_arg_id,
-            theme_arg = theme_arg,
-            show_warnings = vl_opts.show_warnings,
+            native_tooltips = svg_opts.native_tooltips,
+        );
+        self.execute_view_script(
+            code,
+            None,
+            vg_opts.generic_font_mapping.as_ref(),
+            container_size,
+            vg_opts.random_seed,
+            row_limit_arg(
+                vg_opts.max_rows,
+                vg_opts.row_limit_strategy,
+                vg_opts.annotate_truncation,
+            )?,
+            view_log_arg(vg_opts.log_level, vg_opts.warning_filters.as_deref())?,
+            browser_shims,
+            simplify_topojson_arg(vg_opts.simplify_topojson)?,
+        )
+        .await?;
+        if vg_opts.lenient
+            || vg_opts.max_rows.is_some()
+            || vg_opts.collects_view_warnings()
+            || vg_opts.simplify_topojson.is_some()
+        {
+            self.record_render_warnings(Vec::new()).await?;
+        }
+
+        let value = self.execute_script_to_string("svg").await?;
+        check_chart_size(value, vg_opts.min_size)
+    }
+
+    pub async fn vega_apply_config(
+        &mut self,
+        vg_spec: &serde_json::Value,
+        vg_opts: VgOpts,
+    ) -> Result<serde_json::Value, AnyError> {
+        self.init_vega().await?;
+
+        let theme_arg = match &vg_opts.theme {
+            None => "null".to_string(),
+            Some(s) => format!("'{}'", s),
+        };
+
+        let vg_spec =
+            with_size_override(vg_spec, vg_opts.width, vg_opts.height, vg_opts.resize_mode)?;
+        let vg_spec = suppress_vega_components(&vg_spec, vg_opts.suppress_components.as_deref());
+        let vg_spec = with_static_force(&vg_spec, vg_opts.force_iterations);
+        let (vg_spec, int64_strings) = Int64Strings::encode(&vg_spec, vg_opts.preserve_int64);
+        let arg_id = set_json_arg(vg_spec)?;
+        let config_arg_id = set_json_arg(vg_opts.config.unwrap_or(serde_json::Value::Null))?;
+
+        let code = format!(
+            "applyVegaConfig(getJsonArg({arg_id}), getJsonArg({config_arg_id}), {theme_arg})"
+        );
+        let mut vg_spec = self.execute_script_to_json(&code).await?;
+        int64_strings.decode(&mut vg_spec);
+        Ok(vg_spec)
+    }
+
+    pub async fn vega_to_scenegraph(
+        &mut self,
+        vg_spec: &serde_json::Value,
+        vg_opts: VgOpts,
+    ) -> Result<serde_json::Value, AnyError> {
+        // Scenegraph items don't include their data, so the strings of preserved integers
+        // only appear as the text of marks
+        let (sg, _) = self
+            .run_vega_view(vg_spec, vg_opts, "vegaToScenegraph")
+            .await?;
+        Ok(sg)
+    }
+
+    pub async fn vega_render_stats(
+        &mut self,
+        vg_spec: &serde_json::Value,
+        vg_opts: VgOpts,
+    ) -> Result<RenderStats, AnyError> {
+        let (stats, _) = self
+            .run_vega_view(vg_spec, vg_opts, "vegaToRenderStats")
+            .await?;
+        Ok(serde_json::from_value(stats)?)
+    }
+
+    /// Render a Vega spec in a view, and return the result of the JavaScript function
+    /// `js_function`, which is called like vegaToScenegraph, along with the integers that
+    /// were passed to it as strings
+    async fn run_vega_view(
+        &mut self,
+        vg_spec: &serde_json::Value,
+        vg_opts: VgOpts,
+        js_function: &str,
+    ) -> Result<(serde_json::Value, Int64Strings), AnyError> {
+        let container_size = vg_opts.container_size()?;
+        let browser_shims = browser_shims_arg(
+            vg_opts.browser_shims,
+            vg_opts.format_locale.as_ref(),
+            self.device_pixel_ratio,
+        );
+        self.init_vega().await?;
+        let allowed_base_urls =
+            serde_json::to_string(&serde_json::Value::from(vg_opts.allowed_base_urls))?;
+        let fetch_retry = fetch_retry_arg(FetchRetry::from_opts(
+            vg_opts.fetch_retries,
+            vg_opts.fetch_retry_backoff_ms,
+        ));
+        let data_access =
+            data_access_arg(&vg_opts.base_url, vg_opts.allow_local_data, vg_opts.lenient)?;
+        let format_locale = match vg_opts.format_locale {
+            None => serde_json::Value::Null,
+            Some(fl) => fl.as_object()?,
+        };
+
+        let time_format_locale = match vg_opts.time_format_locale {
+            None => serde_json::Value::Null,
+            Some(fl) => fl.as_object()?,
+        };
+
+        let theme_arg = match &vg_opts.theme {
+            None => "null".to_string(),
+            Some(s) => format!("'{}'", s),
+        };
+
+        let vg_spec =
+            with_size_override(vg_spec, vg_opts.width, vg_opts.height, vg_opts.resize_mode)?;
+        let vg_spec = suppress_vega_components(&vg_spec, vg_opts.suppress_components.as_deref());
+        let vg_spec = with_static_force(&vg_spec, vg_opts.force_iterations);
+        let (vg_spec, int64_strings) = Int64Strings::encode(&vg_spec, vg_opts.preserve_int64);
+        let arg_id = set_json_arg(vg_spec)?;
+        let config_arg_id = set_json_arg(vg_opts.config.unwrap_or(serde_json::Value::Null))?;
+        let format_locale_id = set_json_arg(format_locale)?;
+        let time_format_locale_id = set_json_arg(time_format_locale)?;
+        let interactions_id = set_json_arg(serde_json::to_value(&vg_opts.interaction_script)?)?;
+
+        let code = format!(
+            r#"
+var sg;
+var errors = [];
+{js_function}(
+    applyVegaConfig(getJsonArg({arg_id}), getJsonArg({config_arg_id}), {theme_arg}),
+    {allowed_base_urls},
+    {fetch_retry},
+    {data_access},
+    getJsonArg({interactions_id}),
+    getJsonArg({format_locale_id}),
+    getJsonArg({time_format_locale_id}),
+    errors,
+).then((result) => {{
+    if (errors != null && errors.length > 0) {{
+        throw new Error(`${{errors}}`);
+    }}
+    sg = result;
+}})
+"#
         );
+        self.execute_view_script(
+            code,
+            None,
+            vg_opts.generic_font_mapping.as_ref(),
+            container_size,
+            vg_opts.random_seed,
+            row_limit_arg(
+                vg_opts.max_rows,
+                vg_opts.row_limit_strategy,
+                vg_opts.annotate_truncation,
+            )?,
+            view_log_arg(vg_opts.log_level, vg_opts.warning_filters.as_deref())?,
+            browser_shims,
+            simplify_topojson_arg(vg_opts.simplify_topojson)?,
+        )
+        .await?;
+        if vg_opts.max_rows.is_some()
+            || vg_opts.collects_view_warnings()
+            || vg_opts.simplify_topojson.is_some()
+        {
+            self.record_render_warnings(Vec::new()).await?;
+        }
+
+        let value = self.execute_script_to_json("sg").await?;
+        Ok((value, int64_strings))
+    }
+
+    pub async fn get_local_tz(&mut self) -> Result<Option<String>, AnyError> {
+        let code = "var localTz = Intl.DateTimeFormat().resolvedOptions().timeZone ?? 'undefined';"
+            .to_string();
+        self.worker.execute_script("ext:<anon>", code.into())?;
+        self.worker.run_event_loop(false).await?;
+
+        let value = self.execute_script_to_string("localTz").await?;
+        if value == "undefined" {
+            Ok(None)
+        } else {
+            Ok(Some(value))
+        }
+    }
+
+    pub async fn get_themes(&mut self) -> Result<serde_json::Value, AnyError> {
+        self.init_vega().await?;
+
+        let code = r#"
+var themes = Object.assign({}, vegaThemes);
+delete themes.version
+delete themes.default
+"#
+        .to_string();
+
+        self.worker.execute_script("ext:<anon>", code.into())?;
+        self.worker.run_event_loop(false).await?;
+
+        let value = self.execute_script_to_json("themes").await?;
+        Ok(sort_json_keys(value))
+    }
+
+    pub async fn get_theme_names(&mut self) -> Result<Vec<String>, AnyError> {
+        self.init_vega().await?;
+
+        let code =
+            "Object.keys(vegaThemes).filter((name) => name !== 'version' && name !== 'default')";
+        let value = self.execute_script_to_json(code).await?;
+        let mut names: Vec<String> = serde_json::from_value(value)?;
+        names.sort();
+        Ok(names)
+    }
+
+    /// Evaluate the version of the imported Vega module, which checks that the JavaScript
+    /// runtime is responsive
+    pub async fn vega_version(&mut self) -> Result<String, AnyError> {
+        self.init_vega().await?;
+        match self.execute_script_to_json("vega.version").await? {
+            serde_json::Value::String(version) => Ok(version),
+            value => bail!("Unexpected Vega version: {value}"),
+        }
+    }
+}
+
+/// Result of a command that rendered a chart, along with the render warnings of the conversion
+/// and the summary of the chart's datasets, when it was collected
+pub struct Rendered<T> {
+    pub value: T,
+    pub warnings: Vec<RenderWarning>,
+    pub dataset_summary: Option<DatasetSummary>,
+}
+
+pub enum VlConvertCommand {
+    VlToVg {
+        vl_spec: Arc<serde_json::Value>,
+        vl_opts: Arc<VlOpts>,
+        responder: oneshot::Sender<Result<serde_json::Value, AnyError>>,
+    },
+    VgToSvg {
+        vg_spec: Arc<serde_json::Value>,
+        vg_opts: Arc<VgOpts>,
+        svg_opts: SvgOpts,
+        device_pixel_ratio: f32,
+        responder: oneshot::Sender<Result<Rendered<String>, AnyError>>,
+    },
+    VgToSg {
+        vg_spec: Arc<serde_json::Value>,
+        vg_opts: Arc<VgOpts>,
+        responder: oneshot::Sender<Result<Rendered<serde_json::Value>, AnyError>>,
+    },
+    VgToRenderStats {
+        vg_spec: Arc<serde_json::Value>,
+        vg_opts: Arc<VgOpts>,
+        responder: oneshot::Sender<Result<Rendered<RenderStats>, AnyError>>,
+    },
+    VgApplyConfig {
+        vg_spec: Arc<serde_json::Value>,
+        vg_opts: Arc<VgOpts>,
+        responder: oneshot::Sender<Result<serde_json::Value, AnyError>>,
+    },
+    VlToSvg {
+        vl_spec: Arc<serde_json::Value>,
+        vl_opts: Arc<VlOpts>,
+        svg_opts: SvgOpts,
+        device_pixel_ratio: f32,
+        responder: oneshot::Sender<Result<Rendered<String>, AnyError>>,
+    },
+    VlToSvgAndDatasets {
+        vl_spec: Arc<serde_json::Value>,
+        vl_opts: Arc<VlOpts>,
+        device_pixel_ratio: f32,
+        responder: oneshot::Sender<Result<Rendered<(String, Vec<ExportedDataset>)>, AnyError>>,
+    },
+    VlToSvgThemed {
+        vl_spec: Arc<serde_json::Value>,
+        vl_opts: Arc<VlOpts>,
+        svg_opts: SvgOpts,
+        themes: Vec<String>,
+        device_pixel_ratio: f32,
+        responder: oneshot::Sender<Result<Vec<(String, String)>, AnyError>>,
+    },
+    VlToSg {
+        vl_spec: Arc<serde_json::Value>,
+        vl_opts: Arc<VlOpts>,
+        responder: oneshot::Sender<Result<Rendered<serde_json::Value>, AnyError>>,
+    },
+    VlToFacetCells {
+        vl_spec: Arc<serde_json::Value>,
+        vl_opts: Arc<VlOpts>,
+        device_pixel_ratio: f32,
+        responder: oneshot::Sender<Result<Rendered<FacetCells>, AnyError>>,
+    },
+    VlToVgSvg {
+        vl_spec: Arc<serde_json::Value>,
+        vl_opts: Arc<VlOpts>,
+        device_pixel_ratio: f32,
+        responder: oneshot::Sender<Result<Rendered<(serde_json::Value, String)>, AnyError>>,
+    },
+    VlFreeze {
+        vl_spec: Arc<serde_json::Value>,
+        vl_opts: Arc<VlOpts>,
+        responder: oneshot::Sender<Result<serde_json::Value, AnyError>>,
+    },
+    GetLocalTz {
+        responder: oneshot::Sender<Result<Option<String>, AnyError>>,
+    },
+    GetThemes {
+        responder: oneshot::Sender<Result<serde_json::Value, AnyError>>,
+    },
+    GetThemeNames {
+        responder: oneshot::Sender<Result<Vec<String>, AnyError>>,
+    },
+    RegisterExpressionFn {
+        name: String,
+        f: ExpressionFn,
+        responder: oneshot::Sender<Result<(), AnyError>>,
+    },
+    HealthCheck {
+        responder: oneshot::Sender<Result<String, AnyError>>,
+    },
+    WarmVersions {
+        vl_versions: Vec<VlVersion>,
+        responder: oneshot::Sender<Result<(), AnyError>>,
+    },
+}
+
+/// Struct for performing Vega-Lite to Vega conversions using the Deno v8 Runtime
+///
+/// # Examples
+///
+/// ```
+/// use vl_convert_rs::{VlConverter, VlVersion};
+/// let mut converter = VlConverter::new();
+///
+/// let vl_spec: serde_json::Value = serde_json::from_str(r#"
+/// {
+///   "$schema": "https://vega.github.io/schema/vega-lite/v5.json",
+///   "data": {"url": "data/movies.json"},
+///   "mark": "circle",
+///   "encoding": {
+///     "x": {
+///       "bin": {"maxbins": 10},
+///       "field": "IMDB Rating"
+///     },
+///     "y": {
+///       "bin": {"maxbins": 10},
+///       "field": "Rotten Tomatoes Rating"
+///     },
+///     "size": {"aggregate": "count"}
+///   }
+/// }   "#).unwrap();
+///
+///     let vega_spec = futures::executor::block_on(
+///         converter.vegalite_to_vega(vl_spec, Default::default())
+///     ).expect(
+///         "Failed to perform Vega-Lite to Vega conversion"
+///     );
+///
+///     println!("{}", vega_spec)
+/// ```
+#[derive(Clone)]
+pub struct VlConverter {
+    worker: Arc<Mutex<Worker>>,
+    expression_fns: Arc<Mutex<HashMap<String, SharedExpressionFn>>>,
+    _vegaembed_bundles: HashMap<VlVersion, String>,
+    counters: Arc<ConversionCounters>,
+    warm_vl_versions: Arc<Mutex<Vec<VlVersion>>>,
+    output_formats: Arc<Mutex<HashMap<String, Arc<dyn OutputFormat>>>>,
+    /// Dataset summary of the most recent conversion by this converter that collected one
+    last_dataset_summary: Option<DatasetSummary>,
+}
+
+/// Thread that runs a converter's JavaScript runtime and performs the commands that it's
+/// sent. A converter replaces its worker with a new one when the worker stops
+struct Worker {
+    sender: Sender<VlConvertCommand>,
+    handle: JoinHandle<Result<(), AnyError>>,
+    /// Handle for terminating the worker's JavaScript runtime from other threads, which is set
+    /// once the runtime is initialized
+    isolate: Arc<OnceLock<v8::IsolateHandle>>,
+    /// Number of workers that the converter started before this one
+    generation: u64,
+    started_at: Instant,
+}
+
+impl Worker {
+    /// Start a worker that initializes a JavaScript runtime, registers `expression_fns` with
+    /// it, evaluates the modules of `warm_vl_versions`, and performs the commands that it
+    /// receives. The worker stops when it panics or its JavaScript runtime is terminated
+    fn spawn(
+        generation: u64,
+        counters: Arc<ConversionCounters>,
+        expression_fns: Vec<(String, SharedExpressionFn)>,
+        warm_vl_versions: Vec<VlVersion>,
+    ) -> Self {
+        let (sender, mut receiver) = mpsc::channel::<VlConvertCommand>(32);
+        let isolate = Arc::new(OnceLock::new());
+
+        let worker_isolate = isolate.clone();
+        let handle = thread::spawn(move || {
+            TOKIO_RUNTIME.block_on(async {
+                let mut inner = InnerVlConverter::try_new(counters.clone()).await?;
+                worker_isolate
+                    .set(inner.worker.js_runtime.v8_isolate().thread_safe_handle())
+                    .ok();
+                for (name, f) in expression_fns {
+                    inner
+                        .register_expression_fn(name, shared_expression_fn(f))
+                        .await?;
+                }
+                if !warm_vl_versions.is_empty() {
+                    inner.warm_versions(&warm_vl_versions).await?;
+                }
+                while let Some(cmd) = receiver.next().await {
+                    inner.render_warnings.clear();
+                    inner.device_pixel_ratio = 1.0;
+                    match cmd {
+                        VlConvertCommand::VlToVg {
+                            vl_spec,
+                            vl_opts,
+                            responder,
+                        } => {
+                            let rss_before = vl_opts.collect_resource_stats.then(RssSample::now);
+                            let vega_spec = inner
+                                .vegalite_to_vega(&vl_spec, VlOpts::clone(&vl_opts))
+                                .await
+                                .and_then(|vega_spec| {
+                                    if let Some(rss_before) = &rss_before {
+                                        inner.record_resource_stats(rss_before, 0)?;
+                                    }
+                                    Ok(vega_spec)
+                                });
+                            counters.record(&vega_spec);
+                            respond(&mut inner, responder, vega_spec)?;
+                        }
+                        VlConvertCommand::VgToSvg {
+                            vg_spec,
+                            vg_opts,
+                            svg_opts,
+                            device_pixel_ratio,
+                            responder,
+                        } => {
+                            inner.device_pixel_ratio = device_pixel_ratio;
+                            let collect_dataset_summary = vg_opts.collect_dataset_summary;
+                            let rss_before = vg_opts.collect_resource_stats.then(RssSample::now);
+                            let svg_result = inner
+                                .vega_to_svg(&vg_spec, VgOpts::clone(&vg_opts), svg_opts)
+                                .await
+                                .and_then(|svg| {
+                                    if let Some(rss_before) = &rss_before {
+                                        inner.record_resource_stats(rss_before, svg.len())?;
+                                    }
+                                    Ok(svg)
+                                });
+                            let svg_result = match svg_result {
+                                Ok(svg) => inner.rendered(svg, collect_dataset_summary).await,
+                                Err(err) => Err(err),
+                            };
+                            counters.record(&svg_result);
+                            respond(&mut inner, responder, svg_result)?;
+                        }
+                        VlConvertCommand::VgToSg {
+                            vg_spec,
+                            vg_opts,
+                            responder,
+                        } => {
+                            let collect_dataset_summary = vg_opts.collect_dataset_summary;
+                            let rss_before = vg_opts.collect_resource_stats.then(RssSample::now);
+                            let sg_result = inner
+                                .vega_to_scenegraph(&vg_spec, VgOpts::clone(&vg_opts))
+                                .await
+                                .and_then(|sg| {
+                                    if let Some(rss_before) = &rss_before {
+                                        inner.record_resource_stats(rss_before, 0)?;
+                                    }
+                                    Ok(sg)
+                                });
+                            let sg_result = match sg_result {
+                                Ok(sg) => inner.rendered(sg, collect_dataset_summary).await,
+                                Err(err) => Err(err),
+                            };
+                            counters.record(&sg_result);
+                            respond(&mut inner, responder, sg_result)?;
+                        }
+                        VlConvertCommand::VgToRenderStats {
+                            vg_spec,
+                            vg_opts,
+                            responder,
+                        } => {
+                            let collect_dataset_summary = vg_opts.collect_dataset_summary;
+                            let rss_before = vg_opts.collect_resource_stats.then(RssSample::now);
+                            let stats_result = inner
+                                .vega_render_stats(&vg_spec, VgOpts::clone(&vg_opts))
+                                .await
+                                .and_then(|stats| {
+                                    if let Some(rss_before) = &rss_before {
+                                        inner.record_resource_stats(rss_before, 0)?;
+                                    }
+                                    Ok(stats)
+                                });
+                            let stats_result = match stats_result {
+                                Ok(stats) => inner.rendered(stats, collect_dataset_summary).await,
+                                Err(err) => Err(err),
+                            };
+                            counters.record(&stats_result);
+                            respond(&mut inner, responder, stats_result)?;
+                        }
+                        VlConvertCommand::VgApplyConfig {
+                            vg_spec,
+                            vg_opts,
+                            responder,
+                        } => {
+                            let vg_spec = inner
+                                .vega_apply_config(&vg_spec, VgOpts::clone(&vg_opts))
+                                .await;
+                            counters.record(&vg_spec);
+                            respond(&mut inner, responder, vg_spec)?;
+                        }
+                        VlConvertCommand::VlToSvg {
+                            vl_spec,
+                            vl_opts,
+                            svg_opts,
+                            device_pixel_ratio,
+                            responder,
+                        } => {
+                            inner.device_pixel_ratio = device_pixel_ratio;
+                            let collect_dataset_summary = vl_opts.collect_dataset_summary;
+                            let rss_before = vl_opts.collect_resource_stats.then(RssSample::now);
+                            let svg_result = inner
+                                .vegalite_to_svg(&vl_spec, VlOpts::clone(&vl_opts), svg_opts)
+                                .await
+                                .and_then(|svg| {
+                                    if let Some(rss_before) = &rss_before {
+                                        inner.record_resource_stats(rss_before, svg.len())?;
+                                    }
+                                    Ok(svg)
+                                });
+                            let svg_result = match svg_result {
+                                Ok(svg) => inner.rendered(svg, collect_dataset_summary).await,
+                                Err(err) => Err(err),
+                            };
+                            counters.record(&svg_result);
+                            respond(&mut inner, responder, svg_result)?;
+                        }
+                        VlConvertCommand::VlToSvgThemed {
+                            vl_spec,
+                            vl_opts,
+                            svg_opts,
+                            themes,
+                            device_pixel_ratio,
+                            responder,
+                        } => {
+                            inner.device_pixel_ratio = device_pixel_ratio;
+                            let svgs_result = inner
+                                .vegalite_to_svg_themed(
+                                    &vl_spec,
+                                    VlOpts::clone(&vl_opts),
+                                    svg_opts,
+                                    &themes,
+                                )
+                                .await;
+                            counters.record(&svgs_result);
+                            respond(&mut inner, responder, svgs_result)?;
+                        }
+                        VlConvertCommand::VlToSvgAndDatasets {
+                            vl_spec,
+                            vl_opts,
+                            device_pixel_ratio,
+                            responder,
+                        } => {
+                            inner.device_pixel_ratio = device_pixel_ratio;
+                            let collect_dataset_summary = vl_opts.collect_dataset_summary;
+                            let rss_before = vl_opts.collect_resource_stats.then(RssSample::now);
+                            let result = inner
+                                .vegalite_to_svg_and_datasets(&vl_spec, VlOpts::clone(&vl_opts))
+                                .await
+                                .and_then(|(svg, datasets)| {
+                                    if let Some(rss_before) = &rss_before {
+                                        inner.record_resource_stats(rss_before, svg.len())?;
+                                    }
+                                    Ok((svg, datasets))
+                                });
+                            let result = match result {
+                                Ok(result) => inner.rendered(result, collect_dataset_summary).await,
+                                Err(err) => Err(err),
+                            };
+                            counters.record(&result);
+                            respond(&mut inner, responder, result)?;
+                        }
+                        VlConvertCommand::VlToSg {
+                            vl_spec,
+                            vl_opts,
+                            responder,
+                        } => {
+                            let collect_dataset_summary = vl_opts.collect_dataset_summary;
+                            let rss_before = vl_opts.collect_resource_stats.then(RssSample::now);
+                            let sg_result = inner
+                                .vegalite_to_scenegraph(&vl_spec, VlOpts::clone(&vl_opts))
+                                .await
+                                .and_then(|sg| {
+                                    if let Some(rss_before) = &rss_before {
+                                        inner.record_resource_stats(rss_before, 0)?;
+                                    }
+                                    Ok(sg)
+                                });
+                            let sg_result = match sg_result {
+                                Ok(sg) => inner.rendered(sg, collect_dataset_summary).await,
+                                Err(err) => Err(err),
+                            };
+                            counters.record(&sg_result);
+                            respond(&mut inner, responder, sg_result)?;
+                        }
+                        VlConvertCommand::VlToFacetCells {
+                            vl_spec,
+                            vl_opts,
+                            device_pixel_ratio,
+                            responder,
+                        } => {
+                            inner.device_pixel_ratio = device_pixel_ratio;
+                            let collect_dataset_summary = vl_opts.collect_dataset_summary;
+                            let rss_before = vl_opts.collect_resource_stats.then(RssSample::now);
+                            let result = inner
+                                .vegalite_facet_cells(&vl_spec, VlOpts::clone(&vl_opts))
+                                .await
+                                .and_then(|facet_cells| {
+                                    if let Some(rss_before) = &rss_before {
+                                        inner.record_resource_stats(
+                                            rss_before,
+                                            facet_cells.svg.len(),
+                                        )?;
+                                    }
+                                    Ok(facet_cells)
+                                });
+                            let result = match result {
+                                Ok(result) => inner.rendered(result, collect_dataset_summary).await,
+                                Err(err) => Err(err),
+                            };
+                            counters.record(&result);
+                            respond(&mut inner, responder, result)?;
+                        }
+                        VlConvertCommand::VlToVgSvg {
+                            vl_spec,
+                            vl_opts,
+                            device_pixel_ratio,
+                            responder,
+                        } => {
+                            inner.device_pixel_ratio = device_pixel_ratio;
+                            let collect_dataset_summary = vl_opts.collect_dataset_summary;
+                            let rss_before = vl_opts.collect_resource_stats.then(RssSample::now);
+                            let result = inner
+                                .vegalite_to_vega_and_svg(&vl_spec, VlOpts::clone(&vl_opts))
+                                .await
+                                .and_then(|(vg_spec, svg)| {
+                                    if let Some(rss_before) = &rss_before {
+                                        inner.record_resource_stats(rss_before, svg.len())?;
+                                    }
+                                    Ok((vg_spec, svg))
+                                });
+                            let result = match result {
+                                Ok(result) => inner.rendered(result, collect_dataset_summary).await,
+                                Err(err) => Err(err),
+                            };
+                            counters.record(&result);
+                            respond(&mut inner, responder, result)?;
+                        }
+                        VlConvertCommand::VlFreeze {
+                            vl_spec,
+                            vl_opts,
+                            responder,
+                        } => {
+                            let frozen = inner
+                                .vegalite_freeze(&vl_spec, VlOpts::clone(&vl_opts))
+                                .await;
+                            counters.record(&frozen);
+                            respond(&mut inner, responder, frozen)?;
+                        }
+                        VlConvertCommand::GetLocalTz { responder } => {
+                            let local_tz = inner.get_local_tz().await;
+                            respond(&mut inner, responder, local_tz)?;
+                        }
+                        VlConvertCommand::GetThemes { responder } => {
+                            let themes = inner.get_themes().await;
+                            respond(&mut inner, responder, themes)?;
+                        }
+                        VlConvertCommand::GetThemeNames { responder } => {
+                            let names = inner.get_theme_names().await;
+                            respond(&mut inner, responder, names)?;
+                        }
+                        VlConvertCommand::RegisterExpressionFn { name, f, responder } => {
+                            let result = inner.register_expression_fn(name, f).await;
+                            respond(&mut inner, responder, result)?;
+                        }
+                        VlConvertCommand::HealthCheck { responder } => {
+                            let vega_version = inner.vega_version().await;
+                            respond(&mut inner, responder, vega_version)?;
+                        }
+                        VlConvertCommand::WarmVersions {
+                            vl_versions,
+                            responder,
+                        } => {
+                            let result = inner.warm_versions(&vl_versions).await;
+                            respond(&mut inner, responder, result)?;
+                        }
+                    }
+                }
+                Ok::<(), AnyError>(())
+            })?;
 
-        let value = self.execute_script_to_json(&code).await?;
-        Ok(value)
+            Ok(())
+        });
+
+        Self {
+            sender,
+            handle,
+            isolate,
+            generation,
+            started_at: Instant::now(),
+        }
     }
 
-    pub async fn vegalite_to_svg(
-        &mut self,
-        vl_spec: &serde_json::Value,
-        vl_opts: VlOpts,
-    ) -> Result<String, AnyError> {
-        self.init_vega().await?;
-        self.init_vl_version(&vl_opts.vl_version).await?;
+    /// Why the worker stopped, if its thread has finished
+    fn stop_reason(self) -> Option<String> {
+        if !self.handle.is_finished() {
+            return None;
+        }
+        match self.handle.join() {
+            Ok(Ok(())) => None,
+            Ok(Err(err)) => Some(err.to_string()),
+            Err(_) => Some("the worker panicked".to_string()),
+        }
+    }
+}
 
-        let config = vl_opts.config.clone().unwrap_or(serde_json::Value::Null);
+/// Send the result of a command to the converter that sent it. If the worker's JavaScript
+/// runtime was terminated while performing the command, it can't be relied on, so the result
+/// is dropped instead and an error is returned that stops the worker. The converter then
+/// restarts the worker and sends it the command again
+fn respond<T>(
+    inner: &mut InnerVlConverter,
+    responder: oneshot::Sender<Result<T, AnyError>>,
+    result: Result<T, AnyError>,
+) -> Result<(), AnyError> {
+    if inner.was_terminated(&result) {
+        bail!("The JavaScript runtime was terminated")
+    }
+    responder.send(result).ok();
+    Ok(())
+}
 
-        let format_locale = match vl_opts.format_locale {
-            None => serde_json::Value::Null,
-            Some(fl) => fl.as_object()?,
-        };
+/// Send a command built by `make_cmd` to a worker, and wait for its result. The outer error
+/// is returned when the worker has stopped
+async fn send_command<T>(
+    sender: &mut Sender<VlConvertCommand>,
+    request: &str,
+    make_cmd: &impl Fn(oneshot::Sender<Result<T, AnyError>>) -> VlConvertCommand,
+) -> Result<Result<T, AnyError>, AnyError> {
+    let (resp_tx, resp_rx) = oneshot::channel::<Result<T, AnyError>>();
+
+    // Send request
+    match sender.send(make_cmd(resp_tx)).await {
+        Ok(_) => {
+            // All good
+        }
+        Err(err) => {
+            bail!("Failed to send {request} request: {}", err.to_string())
+        }
+    }
 
-        let time_format_locale = match vl_opts.time_format_locale {
-            None => serde_json::Value::Null,
-            Some(fl) => fl.as_object()?,
-        };
+    // Wait for result
+    match resp_rx.await {
+        Ok(result) => Ok(result),
+        Err(err) => bail!("Failed to retrieve {request} result: {}", err.to_string()),
+    }
+}
 
-        let spec_arg_id = set_json_arg(vl_spec.clone())?;
-        let config_arg_id = set_json_arg(config)?;
-        let format_locale_id = set_json_arg(format_locale)?;
-        let time_format_locale_id = set_json_arg(time_format_locale)?;
+/// Expression function that's shared by the workers of a converter, so that it can be
+/// registered again with a restarted worker
+type SharedExpressionFn = Arc<Mutex<ExpressionFn>>;
 
-        let theme_arg = match &vl_opts.theme {
-            None => "null".to_string(),
-            Some(s) => format!("'{}'", s),
+fn shared_expression_fn(f: SharedExpressionFn) -> ExpressionFn {
+    Box::new(move |args: &[serde_json::Value]| match f.lock() {
+        Ok(f) => (*f)(args),
+        Err(err) => bail!("Failed to acquire lock: {}", err.to_string()),
+    })
+}
+
+impl VlConverter {
+    pub fn new() -> Self {
+        let counters = Arc::new(ConversionCounters::default());
+        let worker = Worker::spawn(0, counters.clone(), Vec::new(), Vec::new());
+
+        Self {
+            worker: Arc::new(Mutex::new(worker)),
+            expression_fns: Default::default(),
+            _vegaembed_bundles: Default::default(),
+            counters,
+            warm_vl_versions: Default::default(),
+            output_formats: Arc::new(Mutex::new(
+                builtin_output_formats()
+                    .into_iter()
+                    .map(|format| (format.id().to_string(), format))
+                    .collect(),
+            )),
+            last_dataset_summary: None,
+        }
+    }
+
+    /// Send a command built by `make_cmd` to the worker, and wait for its result. If the
+    /// worker has stopped, e.g. because its JavaScript runtime was terminated, it's restarted
+    /// and sent the command once more before the error is returned. Conversion commands share
+    /// their spec and options with the caller, so building the command again doesn't copy them
+    async fn request<T>(
+        &self,
+        request: &str,
+        make_cmd: impl Fn(oneshot::Sender<Result<T, AnyError>>) -> VlConvertCommand,
+    ) -> Result<T, AnyError> {
+        let (mut sender, generation) = self.worker_sender()?;
+        let err = match send_command(&mut sender, request, &make_cmd).await {
+            Ok(result) => return result,
+            Err(err) => err,
         };
+        let mut sender = self.restart_worker(generation, &err)?;
+        send_command(&mut sender, request, &make_cmd).await?
+    }
 
-        let allowed_base_urls =
-            serde_json::to_string(&serde_json::Value::from(vl_opts.allowed_base_urls))?;
+    /// Send a command that renders a chart, like [`request`](Self::request), and keep the
+    /// dataset summary of the chart when the command collected one. Returns the result of the
+    /// command along with its render warnings
+    async fn request_rendered<T>(
+        &mut self,
+        request: &str,
+        make_cmd: impl Fn(oneshot::Sender<Result<Rendered<T>, AnyError>>) -> VlConvertCommand,
+    ) -> Result<(T, Vec<RenderWarning>), AnyError> {
+        let rendered = self.request(request, make_cmd).await?;
+        if rendered.dataset_summary.is_some() {
+            self.last_dataset_summary = rendered.dataset_summary;
+        }
+        Ok((rendered.value, rendered.warnings))
+    }
 
-        let code = format!(
-            r#"
-var svg;
-var errors = [];
-vegaLiteToSvg_{ver_name:?}(
-    JSON.parse(op_get_json_arg({spec_arg_id})),
-    JSON.parse(op_get_json_arg({config_arg_id})),
-    {theme_arg},
-    {show_warnings},
-    {allowed_base_urls},
-    JSON.parse(op_get_json_arg({format_locale_id})),
-    JSON.parse(op_get_json_arg({time_format_locale_id})),
-    errors,
-).then((result) => {{
-    if (errors != null && errors.length > 0) {{
-        throw new Error(`${{errors}}`);
-    }}
-    svg = result;
-}});
-"#,
-            ver_name = vl_opts.vl_version,
-            show_warnings = vl_opts.show_warnings,
-        );
-        self.worker.execute_script("ext:<anon>", code.into())?;
-        self.worker.run_event_loop(false).await?;
+    /// Dataset summary of the most recent conversion by this converter that was performed
+    /// with `collect_dataset_summary`, or None if there wasn't one
+    pub fn get_last_dataset_summary(&self) -> Option<DatasetSummary> {
+        self.last_dataset_summary.clone()
+    }
 
-        let value = self.execute_script_to_string("svg").await?;
-        Ok(value)
+    /// Sender for commands to the current worker, along with the worker's generation
+    fn worker_sender(&self) -> Result<(Sender<VlConvertCommand>, u64), AnyError> {
+        match self.worker.lock() {
+            Ok(worker) => Ok((worker.sender.clone(), worker.generation)),
+            Err(err) => bail!("Failed to acquire lock: {}", err.to_string()),
+        }
     }
 
-    pub async fn vegalite_to_scenegraph(
+    /// Replace the worker of `generation`, which stopped with `err`, with a new worker, unless
+    /// a clone of this converter replaced it already. Returns the sender for commands to the
+    /// new worker
+    fn restart_worker(
+        &self,
+        generation: u64,
+        err: &AnyError,
+    ) -> Result<Sender<VlConvertCommand>, AnyError> {
+        let mut worker = match self.worker.lock() {
+            Ok(worker) => worker,
+            Err(err) => bail!("Failed to acquire lock: {}", err.to_string()),
+        };
+        if worker.generation == generation {
+            let expression_fns = match self.expression_fns.lock() {
+                Ok(fns) => fns
+                    .iter()
+                    .map(|(name, f)| (name.clone(), f.clone()))
+                    .collect(),
+                Err(err) => bail!("Failed to acquire lock: {}", err.to_string()),
+            };
+            let warm_vl_versions = match self.warm_vl_versions.lock() {
+                Ok(versions) => versions.clone(),
+                Err(err) => bail!("Failed to acquire lock: {}", err.to_string()),
+            };
+            let stopped = std::mem::replace(
+                &mut *worker,
+                Worker::spawn(
+                    generation + 1,
+                    self.counters.clone(),
+                    expression_fns,
+                    warm_vl_versions,
+                ),
+            );
+            let reason = stopped.stop_reason().unwrap_or_else(|| err.to_string());
+            log::warn!(
+                target: LOG_TARGET,
+                "Restarting the JavaScript worker, which stopped: {reason}"
+            );
+        }
+        Ok(worker.sender.clone())
+    }
+
+    /// Terminate the JavaScript runtime of the current worker, which stops the script that
+    /// it's running, as though the script had exhausted the runtime. For testing that
+    /// converters restart their worker
+    #[doc(hidden)]
+    pub fn terminate_worker(&self) -> Result<(), AnyError> {
+        match self.worker.lock() {
+            Ok(worker) => {
+                if let Some(isolate) = worker.isolate.get() {
+                    isolate.terminate_execution();
+                }
+                Ok(())
+            }
+            Err(err) => bail!("Failed to acquire lock: {}", err.to_string()),
+        }
+    }
+
+    pub async fn vegalite_to_vega(
         &mut self,
-        vl_spec: &serde_json::Value,
+        vl_spec: serde_json::Value,
         vl_opts: VlOpts,
     ) -> Result<serde_json::Value, AnyError> {
-        self.init_vega().await?;
-        self.init_vl_version(&vl_opts.vl_version).await?;
+        let vl_spec = Arc::new(vl_spec);
+        let vl_opts = Arc::new(vl_opts);
+        self.request("conversion", |responder| VlConvertCommand::VlToVg {
+            vl_spec: vl_spec.clone(),
+            vl_opts: vl_opts.clone(),
+            responder,
+        })
+        .await
+    }
 
-        let config = vl_opts.config.clone().unwrap_or(serde_json::Value::Null);
-        let format_locale = match vl_opts.format_locale {
-            None => serde_json::Value::Null,
-            Some(fl) => fl.as_object()?,
-        };
+    /// Find the features of a Vega-Lite spec that are lost or frozen in static images, see
+    /// [`analyze_static_export`](crate::static_export::analyze_static_export). The spec is
+    /// also compiled with `vl_opts`, to find tooltips that its theme or config turn on
+    pub async fn analyze_static_export(
+        &mut self,
+        vl_spec: serde_json::Value,
+        vl_opts: VlOpts,
+    ) -> Result<Vec<StaticExportFinding>, AnyError> {
+        let mut findings = analyze_static_export(&vl_spec);
+        if !findings.iter().any(|finding| finding.feature == "tooltip") {
+            let vg_spec = self.vegalite_to_vega(vl_spec, vl_opts).await?;
+            if vega_has_tooltips(&vg_spec) {
+                findings.push(StaticExportFinding {
+                    feature: "tooltip".to_string(),
+                    severity: StaticExportSeverity::Unsupported,
+                    message: "Tooltips turned on by the theme or config aren't shown in static \
+                        output. SVG images can include them as native tooltips instead"
+                        .to_string(),
+                    spec_path: String::new(),
+                });
+            }
+        }
+        Ok(findings)
+    }
 
-        let time_format_locale = match vl_opts.time_format_locale {
-            None => serde_json::Value::Null,
-            Some(fl) => fl.as_object()?,
-        };
+    pub async fn vega_to_svg(
+        &mut self,
+        vg_spec: serde_json::Value,
+        vg_opts: VgOpts,
+    ) -> Result<String, AnyError> {
+        self.vega_to_svg_with_opts(vg_spec, vg_opts, Default::default())
+            .await
+    }
 
-        let spec_arg_id = set_json_arg(vl_spec.clone())?;
-        let config_arg_id = set_json_arg(config)?;
-        let format_locale_id = set_json_arg(format_locale)?;
-        let time_format_locale_id = set_json_arg(time_format_locale)?;
+    pub async fn vega_to_svg_with_opts(
+        &mut self,
+        vg_spec: serde_json::Value,
+        vg_opts: VgOpts,
+        svg_opts: SvgOpts,
+    ) -> Result<String, AnyError> {
+        let (svg, _) = self
+            .vega_to_svg_with_warnings(vg_spec, vg_opts, svg_opts, 1.0)
+            .await?;
+        Ok(svg)
+    }
 
-        let theme_arg = match &vl_opts.theme {
-            None => "null".to_string(),
-            Some(s) => format!("'{}'", s),
-        };
+    /// Convert a Vega spec to SVG, returning the SVG image along with the render
+    /// warnings of the conversion. `device_pixel_ratio` is the scale that the image will be
+    /// rasterized at, which the browser shims report as devicePixelRatio
+    async fn vega_to_svg_with_warnings(
+        &mut self,
+        vg_spec: serde_json::Value,
+        vg_opts: VgOpts,
+        svg_opts: SvgOpts,
+        device_pixel_ratio: f32,
+    ) -> Result<(String, Vec<RenderWarning>), AnyError> {
+        let vg_spec = Arc::new(vg_spec);
+        let vg_opts = Arc::new(vg_opts);
+        let (svg, warnings) = self
+            .request_rendered("SVG conversion", |responder| VlConvertCommand::VgToSvg {
+                vg_spec: vg_spec.clone(),
+                vg_opts: vg_opts.clone(),
+                svg_opts,
+                device_pixel_ratio,
+                responder,
+            })
+            .await?;
+        let svg = post_process_svg(svg, &vg_opts.svg_post_processor)?;
+        Ok((apply_svg_opts(svg, &svg_opts)?, warnings))
+    }
 
-        let allowed_base_urls =
-            serde_json::to_string(&serde_json::Value::from(vl_opts.allowed_base_urls))?;
+    /// Convert a Vega spec to SVG, returning the SVG image along with its size and viewBox
+    pub async fn vega_to_svg_info(
+        &mut self,
+        vg_spec: serde_json::Value,
+        vg_opts: VgOpts,
+    ) -> Result<SvgResult, AnyError> {
+        self.vega_to_svg_info_with_opts(vg_spec, vg_opts, Default::default())
+            .await
+    }
 
-        let code = format!(
-            r#"
-var sg;
-var errors = [];
-vegaLiteToScenegraph_{ver_name:?}(
-    JSON.parse(op_get_json_arg({spec_arg_id})),
-    JSON.parse(op_get_json_arg({config_arg_id})),
-    {theme_arg},
-    {show_warnings},
-    {allowed_base_urls},
-    JSON.parse(op_get_json_arg({format_locale_id})),
-    JSON.parse(op_get_json_arg({time_format_locale_id})),
-    errors,
-).then((result) => {{
-    if (errors != null && errors.length > 0) {{
-        throw new Error(`${{errors}}`);
-    }}
-    sg = result;
-}})
-"#,
-            ver_name = vl_opts.vl_version,
-            show_warnings = vl_opts.show_warnings,
-        );
-        self.worker.execute_script("ext:<anon>", code.into())?;
-        self.worker.run_event_loop(false).await?;
+    pub async fn vega_to_svg_info_with_opts(
+        &mut self,
+        vg_spec: serde_json::Value,
+        vg_opts: VgOpts,
+        svg_opts: SvgOpts,
+    ) -> Result<SvgResult, AnyError> {
+        let output = serde_json::json!({"format": "svg", "svg_opts": format!("{svg_opts:?}")});
+        let request_hash = vega_request_hash(&vg_spec, &vg_opts, output);
+        let (svg, warnings) = self
+            .vega_to_svg_with_warnings(vg_spec, vg_opts, svg_opts, 1.0)
+            .await?;
+        Ok(SvgResult {
+            request_hash: Some(request_hash),
+            warnings,
+            ..svg_info(svg)?
+        })
+    }
 
-        let value = self.execute_script_to_json("sg").await?;
-        Ok(value)
+    pub async fn vega_to_scenegraph(
+        &mut self,
+        vg_spec: serde_json::Value,
+        vg_opts: VgOpts,
+    ) -> Result<serde_json::Value, AnyError> {
+        let vg_spec = Arc::new(vg_spec);
+        let vg_opts = Arc::new(vg_opts);
+        let (sg, _) = self
+            .request_rendered("Scenegraph conversion", |responder| {
+                VlConvertCommand::VgToSg {
+                    vg_spec: vg_spec.clone(),
+                    vg_opts: vg_opts.clone(),
+                    responder,
+                }
+            })
+            .await?;
+        Ok(sg)
     }
 
-    pub async fn vega_to_svg(
+    /// Count the groups, items, and scenegraph nodes of a rendered Vega chart, without
+    /// generating SVG or copying the scenegraph out of the view
+    pub async fn vega_get_render_stats(
         &mut self,
-        vg_spec: &serde_json::Value,
+        vg_spec: serde_json::Value,
         vg_opts: VgOpts,
+    ) -> Result<RenderStats, AnyError> {
+        let vg_spec = Arc::new(vg_spec);
+        let vg_opts = Arc::new(vg_opts);
+        let (stats, _) = self
+            .request_rendered("render stats", |responder| {
+                VlConvertCommand::VgToRenderStats {
+                    vg_spec: vg_spec.clone(),
+                    vg_opts: vg_opts.clone(),
+                    responder,
+                }
+            })
+            .await?;
+        Ok(stats)
+    }
+
+    pub async fn vegalite_to_svg(
+        &mut self,
+        vl_spec: serde_json::Value,
+        vl_opts: VlOpts,
     ) -> Result<String, AnyError> {
-        self.init_vega().await?;
-        let allowed_base_urls =
-            serde_json::to_string(&serde_json::Value::from(vg_opts.allowed_base_urls))?;
+        self.vegalite_to_svg_with_opts(vl_spec, vl_opts, Default::default())
+            .await
+    }
+
+    pub async fn vegalite_to_svg_with_opts(
+        &mut self,
+        vl_spec: serde_json::Value,
+        vl_opts: VlOpts,
+        svg_opts: SvgOpts,
+    ) -> Result<String, AnyError> {
+        let (svg, _) = self
+            .vegalite_to_svg_with_warnings(vl_spec, vl_opts, svg_opts, 1.0)
+            .await?;
+        Ok(svg)
+    }
+
+    /// Convert a Vega-Lite spec to SVG, returning the SVG image along with the render
+    /// warnings of the conversion. `device_pixel_ratio` is the scale that the image will be
+    /// rasterized at, which the browser shims report as devicePixelRatio
+    async fn vegalite_to_svg_with_warnings(
+        &mut self,
+        vl_spec: serde_json::Value,
+        vl_opts: VlOpts,
+        svg_opts: SvgOpts,
+        device_pixel_ratio: f32,
+    ) -> Result<(String, Vec<RenderWarning>), AnyError> {
+        let vl_spec = Arc::new(vl_spec);
+        let vl_opts = Arc::new(vl_opts);
+        let (svg, warnings) = self
+            .request_rendered("SVG conversion", |responder| VlConvertCommand::VlToSvg {
+                vl_spec: vl_spec.clone(),
+                vl_opts: vl_opts.clone(),
+                svg_opts,
+                device_pixel_ratio,
+                responder,
+            })
+            .await?;
+        let svg = post_process_svg(svg, &vl_opts.svg_post_processor)?;
+        Ok((apply_svg_opts(svg, &svg_opts)?, warnings))
+    }
+
+    /// Convert a Vega-Lite spec to SVG, returning the SVG image along with its size and viewBox
+    pub async fn vegalite_to_svg_info(
+        &mut self,
+        vl_spec: serde_json::Value,
+        vl_opts: VlOpts,
+    ) -> Result<SvgResult, AnyError> {
+        self.vegalite_to_svg_info_with_opts(vl_spec, vl_opts, Default::default())
+            .await
+    }
+
+    pub async fn vegalite_to_svg_info_with_opts(
+        &mut self,
+        vl_spec: serde_json::Value,
+        vl_opts: VlOpts,
+        svg_opts: SvgOpts,
+    ) -> Result<SvgResult, AnyError> {
+        let output = serde_json::json!({"format": "svg", "svg_opts": format!("{svg_opts:?}")});
+        let request_hash = vegalite_request_hash(&vl_spec, &vl_opts, output);
+        let (svg, warnings) = self
+            .vegalite_to_svg_with_warnings(vl_spec, vl_opts, svg_opts, 1.0)
+            .await?;
+        Ok(SvgResult {
+            request_hash: Some(request_hash),
+            warnings,
+            ..svg_info(svg)?
+        })
+    }
+
+    /// Convert a Vega-Lite spec to SVG once per theme, returning the SVG images keyed by
+    /// theme. The "default" theme renders the chart without a theme. Data that the spec loads
+    /// from urls is fetched once and shared across the themes, and the theme of `vl_opts` is
+    /// ignored
+    pub async fn vegalite_to_svg_themed(
+        &mut self,
+        vl_spec: serde_json::Value,
+        vl_opts: VlOpts,
+        themes: &[&str],
+    ) -> Result<HashMap<String, String>, AnyError> {
+        self.vegalite_to_svg_themed_with_opts(vl_spec, vl_opts, themes, Default::default())
+            .await
+    }
 
-        let format_locale = match vg_opts.format_locale {
-            None => serde_json::Value::Null,
-            Some(fl) => fl.as_object()?,
-        };
+    pub async fn vegalite_to_svg_themed_with_opts(
+        &mut self,
+        vl_spec: serde_json::Value,
+        vl_opts: VlOpts,
+        themes: &[&str],
+        svg_opts: SvgOpts,
+    ) -> Result<HashMap<String, String>, AnyError> {
+        self.vegalite_to_svg_themed_with_pixel_ratio(vl_spec, vl_opts, themes, svg_opts, 1.0)
+            .await
+    }
 
-        let time_format_locale = match vg_opts.time_format_locale {
-            None => serde_json::Value::Null,
-            Some(fl) => fl.as_object()?,
-        };
+    /// Convert a Vega-Lite spec to SVG once per theme, for images that will be rasterized at
+    /// the scale `device_pixel_ratio`
+    async fn vegalite_to_svg_themed_with_pixel_ratio(
+        &mut self,
+        vl_spec: serde_json::Value,
+        vl_opts: VlOpts,
+        themes: &[&str],
+        svg_opts: SvgOpts,
+        device_pixel_ratio: f32,
+    ) -> Result<HashMap<String, String>, AnyError> {
+        let vl_spec = Arc::new(vl_spec);
+        let vl_opts = Arc::new(vl_opts);
+        let svgs = self
+            .request("SVG conversion", |responder| {
+                VlConvertCommand::VlToSvgThemed {
+                    vl_spec: vl_spec.clone(),
+                    vl_opts: vl_opts.clone(),
+                    svg_opts,
+                    themes: themes.iter().map(|theme| theme.to_string()).collect(),
+                    device_pixel_ratio,
+                    responder,
+                }
+            })
+            .await?;
+        svgs.into_iter()
+            .map(|(theme, svg)| {
+                let svg = post_process_svg(svg, &vl_opts.svg_post_processor)?;
+                Ok((theme, apply_svg_opts(svg, &svg_opts)?))
+            })
+            .collect()
+    }
 
-        let arg_id = set_json_arg(vg_spec.clone())?;
-        let format_locale_id = set_json_arg(format_locale)?;
-        let time_format_locale_id = set_json_arg(time_format_locale)?;
+    async fn vegalite_to_svg_and_datasets(
+        &mut self,
+        vl_spec: serde_json::Value,
+        vl_opts: VlOpts,
+        device_pixel_ratio: f32,
+    ) -> Result<(String, Vec<ExportedDataset>, Vec<RenderWarning>), AnyError> {
+        let vl_spec = Arc::new(vl_spec);
+        let vl_opts = Arc::new(vl_opts);
+        let ((svg, datasets), warnings) = self
+            .request_rendered("SVG conversion", |responder| {
+                VlConvertCommand::VlToSvgAndDatasets {
+                    vl_spec: vl_spec.clone(),
+                    vl_opts: vl_opts.clone(),
+                    device_pixel_ratio,
+                    responder,
+                }
+            })
+            .await?;
+        let svg = post_process_svg(svg, &vl_opts.svg_post_processor)?;
+        Ok((svg, datasets, warnings))
+    }
 
-        let code = format!(
-            r#"
-var svg;
-var errors = [];
-vegaToSvg(
-    JSON.parse(op_get_json_arg({arg_id})),
-    {allowed_base_urls},
-    JSON.parse(op_get_json_arg({format_locale_id})),
-    JSON.parse(op_get_json_arg({time_format_locale_id})),
-    errors,
-).then((result) => {{
-    if (errors != null && errors.length > 0) {{
-        throw new Error(`${{errors}}`);
-    }}
-    svg = result;
-}})
-"#
-        );
-        self.worker.execute_script("ext:<anon>", code.into())?;
-        self.worker.run_event_loop(false).await?;
+    pub async fn vegalite_to_scenegraph(
+        &mut self,
+        vl_spec: serde_json::Value,
+        vl_opts: VlOpts,
+    ) -> Result<serde_json::Value, AnyError> {
+        let vl_spec = Arc::new(vl_spec);
+        let vl_opts = Arc::new(vl_opts);
+        let (sg, _) = self
+            .request_rendered("Scenegraph conversion", |responder| {
+                VlConvertCommand::VlToSg {
+                    vl_spec: vl_spec.clone(),
+                    vl_opts: vl_opts.clone(),
+                    responder,
+                }
+            })
+            .await?;
+        Ok(sg)
+    }
 
-        let value = self.execute_script_to_string("svg").await?;
-        Ok(value)
+    /// Get the Vega spec that the Vega-Lite rendering methods build the view from, after
+    /// compiling with the theme and config and adding the format locales to `config.locale`.
+    ///
+    /// Rendering the returned spec with default VgOpts produces the same image as rendering
+    /// the Vega-Lite spec with vl_opts.
+    pub async fn get_final_vega_spec(
+        &mut self,
+        vl_spec: serde_json::Value,
+        vl_opts: VlOpts,
+    ) -> Result<serde_json::Value, AnyError> {
+        let format_locale = vl_opts.format_locale.clone();
+        let time_format_locale = vl_opts.time_format_locale.clone();
+        let vg_spec = self.vegalite_to_vega(vl_spec, vl_opts).await?;
+        apply_vega_locale(vg_spec, format_locale, time_format_locale)
     }
 
-    pub async fn vega_to_scenegraph(
+    /// Get the Vega spec that the Vega rendering methods build the view from, after merging
+    /// the theme and config into the spec's config and adding the format locales to
+    /// `config.locale`.
+    ///
+    /// Rendering the returned spec with default VgOpts produces the same image as rendering
+    /// the input spec with vg_opts.
+    pub async fn get_final_vega_spec_vg(
         &mut self,
-        vg_spec: &serde_json::Value,
+        vg_spec: serde_json::Value,
         vg_opts: VgOpts,
     ) -> Result<serde_json::Value, AnyError> {
-        self.init_vega().await?;
-        let allowed_base_urls =
-            serde_json::to_string(&serde_json::Value::from(vg_opts.allowed_base_urls))?;
-        let format_locale = match vg_opts.format_locale {
-            None => serde_json::Value::Null,
-            Some(fl) => fl.as_object()?,
-        };
+        let format_locale = vg_opts.format_locale.clone();
+        let time_format_locale = vg_opts.time_format_locale.clone();
+
+        let vg_spec = Arc::new(vg_spec);
+        let vg_opts = Arc::new(vg_opts);
+        let vg_spec = self
+            .request("Vega config", |responder| VlConvertCommand::VgApplyConfig {
+                vg_spec: vg_spec.clone(),
+                vg_opts: vg_opts.clone(),
+                responder,
+            })
+            .await?;
+        apply_vega_locale(vg_spec, format_locale, time_format_locale)
+    }
 
-        let time_format_locale = match vg_opts.time_format_locale {
-            None => serde_json::Value::Null,
-            Some(fl) => fl.as_object()?,
-        };
+    /// Freeze a Vega-Lite spec into a self-contained spec that renders identically without
+    /// network access.
+    ///
+    /// Data loaded from urls (subject to `allowed_base_urls`) is inlined as `values`, the
+    /// theme and config are merged into the spec's `config`, `$schema` is pinned to the exact
+    /// Vega-Lite version used, and `usermeta.vlConvert` records the vl-convert version and the
+    /// time the spec was frozen.
+    pub async fn vegalite_freeze(
+        &mut self,
+        vl_spec: serde_json::Value,
+        vl_opts: VlOpts,
+    ) -> Result<serde_json::Value, AnyError> {
+        let vl_spec = Arc::new(vl_spec);
+        let vl_opts = Arc::new(vl_opts);
+        self.request("freeze", |responder| VlConvertCommand::VlFreeze {
+            vl_spec: vl_spec.clone(),
+            vl_opts: vl_opts.clone(),
+            responder,
+        })
+        .await
+    }
 
-        let arg_id = set_json_arg(vg_spec.clone())?;
-        let format_locale_id = set_json_arg(format_locale)?;
-        let time_format_locale_id = set_json_arg(time_format_locale)?;
+    /// Extract the visible text in a rendered Vega chart, with absolute positions and roles
+    pub async fn vega_get_text_items(
+        &mut self,
+        vg_spec: serde_json::Value,
+        vg_opts: VgOpts,
+    ) -> Result<Vec<TextItem>, AnyError> {
+        let sg = self.vega_to_scenegraph(vg_spec, vg_opts).await?;
+        scenegraph_text_items(&sg)
+    }
 
-        let code = format!(
-            r#"
-var sg;
-var errors = [];
-vegaToScenegraph(
-    JSON.parse(op_get_json_arg({arg_id})),
-    {allowed_base_urls},
-    JSON.parse(op_get_json_arg({format_locale_id})),
-    JSON.parse(op_get_json_arg({time_format_locale_id})),
-    errors,
-).then((result) => {{
-    if (errors != null && errors.length > 0) {{
-        throw new Error(`${{errors}}`);
-    }}
-    sg = result;
-}})
-"#
-        );
-        self.worker.execute_script("ext:<anon>", code.into())?;
-        self.worker.run_event_loop(false).await?;
+    /// Extract the visible text in a rendered Vega-Lite chart, with absolute positions and roles
+    pub async fn vegalite_get_text_items(
+        &mut self,
+        vl_spec: serde_json::Value,
+        vl_opts: VlOpts,
+    ) -> Result<Vec<TextItem>, AnyError> {
+        let sg = self.vegalite_to_scenegraph(vl_spec, vl_opts).await?;
+        scenegraph_text_items(&sg)
+    }
 
-        let value = self.execute_script_to_json("sg").await?;
-        Ok(value)
+    /// Compile a Vega-Lite spec to Vega and render it to SVG in a single pass.
+    ///
+    /// The returned Vega spec is the exact spec that was rendered to produce the SVG.
+    pub async fn vegalite_to_vega_and_svg(
+        &mut self,
+        vl_spec: serde_json::Value,
+        vl_opts: VlOpts,
+    ) -> Result<(serde_json::Value, String), AnyError> {
+        self.vegalite_to_vega_and_svg_with_pixel_ratio(vl_spec, vl_opts, 1.0)
+            .await
     }
 
-    pub async fn get_local_tz(&mut self) -> Result<Option<String>, AnyError> {
-        let code = "var localTz = Intl.DateTimeFormat().resolvedOptions().timeZone ?? 'undefined';"
-            .to_string();
-        self.worker.execute_script("ext:<anon>", code.into())?;
-        self.worker.run_event_loop(false).await?;
+    /// Compile a Vega-Lite spec to Vega and render it to SVG, for an image that will be
+    /// rasterized at the scale `device_pixel_ratio`
+    async fn vegalite_to_vega_and_svg_with_pixel_ratio(
+        &mut self,
+        vl_spec: serde_json::Value,
+        vl_opts: VlOpts,
+        device_pixel_ratio: f32,
+    ) -> Result<(serde_json::Value, String), AnyError> {
+        let vl_spec = Arc::new(vl_spec);
+        let vl_opts = Arc::new(vl_opts);
+        let ((vg_spec, svg), _) = self
+            .request_rendered("conversion", |responder| VlConvertCommand::VlToVgSvg {
+                vl_spec: vl_spec.clone(),
+                vl_opts: vl_opts.clone(),
+                device_pixel_ratio,
+                responder,
+            })
+            .await?;
+        let svg = post_process_svg(svg, &vl_opts.svg_post_processor)?;
+        Ok((vg_spec, svg))
+    }
 
-        let value = self.execute_script_to_string("localTz").await?;
-        if value == "undefined" {
-            Ok(None)
-        } else {
-            Ok(Some(value))
+    /// Compile a Vega-Lite spec to Vega and render it to PNG in a single pass.
+    ///
+    /// The returned Vega spec is the exact spec that was rendered to produce the PNG.
+    pub async fn vegalite_to_vega_and_png(
+        &mut self,
+        vl_spec: serde_json::Value,
+        vl_opts: VlOpts,
+        scale: Option<f32>,
+        ppi: Option<f32>,
+    ) -> Result<(serde_json::Value, Vec<u8>), AnyError> {
+        let scale = scale.unwrap_or(1.0);
+        let rss_before = vl_opts.collect_resource_stats.then(RssSample::now);
+        let fetch_retry =
+            FetchRetry::from_opts(vl_opts.fetch_retries, vl_opts.fetch_retry_backoff_ms);
+        let lenient = vl_opts.lenient;
+        let (vg_spec, svg) = self
+            .vegalite_to_vega_and_svg_with_pixel_ratio(vl_spec, vl_opts, scale)
+            .await?;
+        let ctx = RenderContext {
+            scale,
+            ppi: ppi.unwrap_or(72.0),
+            ..Default::default()
+        };
+        let png = with_image_opts(fetch_retry, lenient, &svg, || {
+            PngFormat::default().from_svg(&svg, &ctx)
+        })
+        .0?;
+        if let Some(rss_before) = &rss_before {
+            record_pixmap_stats(rss_before, png_pixmap_bytes(&png)?)?;
         }
+        Ok((vg_spec, png))
     }
 
-    pub async fn get_themes(&mut self) -> Result<serde_json::Value, AnyError> {
-        self.init_vega().await?;
-
-        let code = r#"
-var themes = Object.assign({}, vegaThemes);
-delete themes.version
-delete themes.default
-"#
-        .to_string();
-
-        self.worker.execute_script("ext:<anon>", code.into())?;
-        self.worker.run_event_loop(false).await?;
+    pub async fn vega_to_png(
+        &mut self,
+        vg_spec: serde_json::Value,
+        vg_opts: VgOpts,
+        scale: Option<f32>,
+        ppi: Option<f32>,
+    ) -> Result<Vec<u8>, AnyError> {
+        self.vega_to_png_with_opts(vg_spec, vg_opts, scale, ppi, Default::default())
+            .await
+    }
 
-        let value = self.execute_script_to_json("themes").await?;
-        Ok(value)
+    pub async fn vega_to_png_with_opts(
+        &mut self,
+        vg_spec: serde_json::Value,
+        vg_opts: VgOpts,
+        scale: Option<f32>,
+        ppi: Option<f32>,
+        png_opts: PngOpts,
+    ) -> Result<Vec<u8>, AnyError> {
+        let png = self
+            .vega_to_png_info_with_opts(vg_spec, vg_opts, scale, ppi, png_opts)
+            .await?;
+        Ok(png.data)
     }
-}
 
-pub enum VlConvertCommand {
-    VlToVg {
-        vl_spec: serde_json::Value,
-        vl_opts: VlOpts,
-        responder: oneshot::Sender<Result<serde_json::Value, AnyError>>,
-    },
-    VgToSvg {
+    /// Convert a Vega spec to PNG, returning the PNG image along with the size that it was
+    /// rendered at, so that callers don't need to decode the image to learn its size
+    pub async fn vega_to_png_info(
+        &mut self,
         vg_spec: serde_json::Value,
         vg_opts: VgOpts,
-        responder: oneshot::Sender<Result<String, AnyError>>,
-    },
-    VgToSg {
+        scale: Option<f32>,
+        ppi: Option<f32>,
+    ) -> Result<PngResult, AnyError> {
+        self.vega_to_png_info_with_opts(vg_spec, vg_opts, scale, ppi, Default::default())
+            .await
+    }
+
+    pub async fn vega_to_png_info_with_opts(
+        &mut self,
         vg_spec: serde_json::Value,
         vg_opts: VgOpts,
-        responder: oneshot::Sender<Result<serde_json::Value, AnyError>>,
-    },
-    VlToSvg {
-        vl_spec: serde_json::Value,
-        vl_opts: VlOpts,
-        responder: oneshot::Sender<Result<String, AnyError>>,
-    },
-    VlToSg {
+        scale: Option<f32>,
+        ppi: Option<f32>,
+        png_opts: PngOpts,
+    ) -> Result<PngResult, AnyError> {
+        let scale = scale.unwrap_or(1.0);
+        let output = serde_json::json!({
+            "format": "png",
+            "scale": scale,
+            "ppi": ppi,
+            "png_opts": format!("{png_opts:?}"),
+        });
+        let request_hash = vega_request_hash(&vg_spec, &vg_opts, output);
+        let rss_before = vg_opts.collect_resource_stats.then(RssSample::now);
+        let fetch_retry =
+            FetchRetry::from_opts(vg_opts.fetch_retries, vg_opts.fetch_retry_backoff_ms);
+        let lenient = vg_opts.lenient;
+        let (svg, mut warnings) = self
+            .vega_to_svg_with_warnings(vg_spec, vg_opts, Default::default(), scale)
+            .await?;
+        let png_format = PngFormat { png_opts };
+        let ctx = RenderContext {
+            scale,
+            ppi: ppi.unwrap_or(72.0),
+            ..Default::default()
+        };
+        let (png, image_warnings) = with_image_opts(fetch_retry, lenient, &svg, || {
+            png_format.png_info(&svg, &ctx)
+        });
+        let png = png?;
+        extend_render_warnings(&mut warnings, image_warnings);
+        if let Some(rss_before) = &rss_before {
+            record_pixmap_stats(rss_before, png.width_px as u64 * png.height_px as u64 * 4)?;
+        }
+        Ok(PngResult {
+            request_hash: Some(request_hash),
+            warnings,
+            ..png
+        })
+    }
+
+    pub async fn vegalite_to_png(
+        &mut self,
         vl_spec: serde_json::Value,
         vl_opts: VlOpts,
-        responder: oneshot::Sender<Result<serde_json::Value, AnyError>>,
-    },
-    GetLocalTz {
-        responder: oneshot::Sender<Result<Option<String>, AnyError>>,
-    },
-    GetThemes {
-        responder: oneshot::Sender<Result<serde_json::Value, AnyError>>,
-    },
-}
-
-/// Struct for performing Vega-Lite to Vega conversions using the Deno v8 Runtime
-///
-/// # Examples
-///
-/// ```
-/// use vl_convert_rs::{VlConverter, VlVersion};
-/// let mut converter = VlConverter::new();
-///
-/// let vl_spec: serde_json::Value = serde_json::from_str(r#"
-/// {
-///   "$schema": "https://vega.github.io/schema/vega-lite/v5.json",
-///   "data": {"url": "data/movies.json"},
-///   "mark": "circle",
-///   "encoding": {
-///     "x": {
-///       "bin": {"maxbins": 10},
-///       "field": "IMDB Rating"
-///     },
-///     "y": {
-///       "bin": {"maxbins": 10},
-///       "field": "Rotten Tomatoes Rating"
-///     },
-///     "size": {"aggregate": "count"}
-///   }
-/// }   "#).unwrap();
-///
-///     let vega_spec = futures::executor::block_on(
-///         converter.vegalite_to_vega(vl_spec, Default::default())
-///     ).expect(
-///         "Failed to perform Vega-Lite to Vega conversion"
-///     );
-///
-///     println!("{}", vega_spec)
-/// ```
-#[derive(Clone)]
-pub struct VlConverter {
-    sender: Sender<VlConvertCommand>,
-    _handle: Arc<JoinHandle<Result<(), AnyError>>>,
-    _vegaembed_bundles: HashMap<VlVersion, String>,
-}
-
-impl VlConverter {
-    pub fn new() -> Self {
-        // Initialize environment logger
-        env_logger::try_init().ok();
-
-        let (sender, mut receiver) = mpsc::channel::<VlConvertCommand>(32);
+        scale: Option<f32>,
+        ppi: Option<f32>,
+    ) -> Result<Vec<u8>, AnyError> {
+        self.vegalite_to_png_with_opts(vl_spec, vl_opts, scale, ppi, Default::default())
+            .await
+    }
 
-        let handle = Arc::new(thread::spawn(move || {
-            TOKIO_RUNTIME.block_on(async {
-                let mut inner = InnerVlConverter::try_new().await?;
-                while let Some(cmd) = receiver.next().await {
-                    match cmd {
-                        VlConvertCommand::VlToVg {
-                            vl_spec,
-                            vl_opts,
-                            responder,
-                        } => {
-                            let vega_spec = inner.vegalite_to_vega(&vl_spec, vl_opts).await;
-                            responder.send(vega_spec).ok();
-                        }
-                        VlConvertCommand::VgToSvg {
-                            vg_spec,
-                            vg_opts,
-                            responder,
-                        } => {
-                            let svg_result = inner.vega_to_svg(&vg_spec, vg_opts).await;
-                            responder.send(svg_result).ok();
-                        }
-                        VlConvertCommand::VgToSg {
-                            vg_spec,
-                            vg_opts,
-                            responder,
-                        } => {
-                            let sg_result = inner.vega_to_scenegraph(&vg_spec, vg_opts).await;
-                            responder.send(sg_result).ok();
-                        }
-                        VlConvertCommand::VlToSvg {
-                            vl_spec,
-                            vl_opts,
-                            responder,
-                        } => {
-                            let svg_result = inner.vegalite_to_svg(&vl_spec, vl_opts).await;
-                            responder.send(svg_result).ok();
-                        }
-                        VlConvertCommand::VlToSg {
-                            vl_spec,
-                            vl_opts,
-                            responder,
-                        } => {
-                            let sg_result = inner.vegalite_to_scenegraph(&vl_spec, vl_opts).await;
-                            responder.send(sg_result).ok();
-                        }
-                        VlConvertCommand::GetLocalTz { responder } => {
-                            let local_tz = inner.get_local_tz().await;
-                            responder.send(local_tz).ok();
-                        }
-                        VlConvertCommand::GetThemes { responder } => {
-                            let themes = inner.get_themes().await;
-                            responder.send(themes).ok();
-                        }
-                    }
-                }
-                Ok::<(), AnyError>(())
-            })?;
+    pub async fn vegalite_to_png_with_opts(
+        &mut self,
+        vl_spec: serde_json::Value,
+        vl_opts: VlOpts,
+        scale: Option<f32>,
+        ppi: Option<f32>,
+        png_opts: PngOpts,
+    ) -> Result<Vec<u8>, AnyError> {
+        let png = self
+            .vegalite_to_png_info_with_opts(vl_spec, vl_opts, scale, ppi, png_opts)
+            .await?;
+        Ok(png.data)
+    }
 
-            Ok(())
-        }));
+    /// Convert a Vega-Lite spec to PNG, returning the PNG image along with the size that it was
+    /// rendered at, so that callers don't need to decode the image to learn its size
+    pub async fn vegalite_to_png_info(
+        &mut self,
+        vl_spec: serde_json::Value,
+        vl_opts: VlOpts,
+        scale: Option<f32>,
+        ppi: Option<f32>,
+    ) -> Result<PngResult, AnyError> {
+        self.vegalite_to_png_info_with_opts(vl_spec, vl_opts, scale, ppi, Default::default())
+            .await
+    }
 
-        Self {
-            sender,
-            _handle: handle,
-            _vegaembed_bundles: Default::default(),
+    pub async fn vegalite_to_png_info_with_opts(
+        &mut self,
+        vl_spec: serde_json::Value,
+        vl_opts: VlOpts,
+        scale: Option<f32>,
+        ppi: Option<f32>,
+        png_opts: PngOpts,
+    ) -> Result<PngResult, AnyError> {
+        let scale = scale.unwrap_or(1.0);
+        let output = serde_json::json!({
+            "format": "png",
+            "scale": scale,
+            "ppi": ppi,
+            "png_opts": format!("{png_opts:?}"),
+        });
+        let request_hash = vegalite_request_hash(&vl_spec, &vl_opts, output);
+        let rss_before = vl_opts.collect_resource_stats.then(RssSample::now);
+        let fetch_retry =
+            FetchRetry::from_opts(vl_opts.fetch_retries, vl_opts.fetch_retry_backoff_ms);
+        let lenient = vl_opts.lenient;
+        let (svg, mut warnings) = self
+            .vegalite_to_svg_with_warnings(vl_spec, vl_opts, Default::default(), scale)
+            .await?;
+        let png_format = PngFormat { png_opts };
+        let ctx = RenderContext {
+            scale,
+            ppi: ppi.unwrap_or(72.0),
+            ..Default::default()
+        };
+        let (png, image_warnings) = with_image_opts(fetch_retry, lenient, &svg, || {
+            png_format.png_info(&svg, &ctx)
+        });
+        let png = png?;
+        extend_render_warnings(&mut warnings, image_warnings);
+        if let Some(rss_before) = &rss_before {
+            record_pixmap_stats(rss_before, png.width_px as u64 * png.height_px as u64 * 4)?;
         }
+        Ok(PngResult {
+            request_hash: Some(request_hash),
+            warnings,
+            ..png
+        })
     }
 
-    pub async fn vegalite_to_vega(
+    /// Convert a Vega-Lite spec to PNG once per theme, returning the PNG images keyed by
+    /// theme. The "default" theme renders the chart without a theme. Data that the spec loads
+    /// from urls is fetched once and shared across the themes, and the theme of `vl_opts` is
+    /// ignored
+    pub async fn vegalite_to_png_themed(
         &mut self,
         vl_spec: serde_json::Value,
         vl_opts: VlOpts,
-    ) -> Result<serde_json::Value, AnyError> {
-        let (resp_tx, resp_rx) = oneshot::channel::<Result<serde_json::Value, AnyError>>();
-        let cmd = VlConvertCommand::VlToVg {
+        themes: &[&str],
+        scale: Option<f32>,
+        ppi: Option<f32>,
+    ) -> Result<HashMap<String, Vec<u8>>, AnyError> {
+        self.vegalite_to_png_themed_with_opts(
             vl_spec,
             vl_opts,
-            responder: resp_tx,
+            themes,
+            scale,
+            ppi,
+            Default::default(),
+        )
+        .await
+    }
+
+    pub async fn vegalite_to_png_themed_with_opts(
+        &mut self,
+        vl_spec: serde_json::Value,
+        vl_opts: VlOpts,
+        themes: &[&str],
+        scale: Option<f32>,
+        ppi: Option<f32>,
+        png_opts: PngOpts,
+    ) -> Result<HashMap<String, Vec<u8>>, AnyError> {
+        let scale = scale.unwrap_or(1.0);
+        let fetch_retry =
+            FetchRetry::from_opts(vl_opts.fetch_retries, vl_opts.fetch_retry_backoff_ms);
+        let lenient = vl_opts.lenient;
+        let svgs = self
+            .vegalite_to_svg_themed_with_pixel_ratio(
+                vl_spec,
+                vl_opts,
+                themes,
+                Default::default(),
+                scale,
+            )
+            .await?;
+        let png_format = PngFormat { png_opts };
+        let ctx = RenderContext {
+            scale,
+            ppi: ppi.unwrap_or(72.0),
+            ..Default::default()
         };
+        svgs.into_iter()
+            .map(|(theme, svg)| {
+                let png = with_image_opts(fetch_retry, lenient, &svg, || {
+                    png_format.from_svg(&svg, &ctx)
+                })
+                .0?;
+                Ok((theme, png))
+            })
+            .collect()
+    }
 
-        // Send request
-        match self.sender.send(cmd).await {
-            Ok(_) => {
-                // All good
-            }
-            Err(err) => {
-                bail!("Failed to send conversion request: {}", err.to_string())
-            }
-        }
+    /// Convert each cell of a faceted Vega-Lite spec to its own PNG image, along with the
+    /// values of the cell's row and column fields. The chart is compiled, rendered, and
+    /// rasterized once, and the images are cropped from the rasterized chart, so that the cells
+    /// share their scales and formatting, and cells of the same size have images of the same
+    /// size. Only the plot area of each cell is included, not the shared axes and headers. Use
+    /// vegalite_facets_to_pngs_with_opts to include them
+    pub async fn vegalite_facets_to_pngs(
+        &mut self,
+        vl_spec: serde_json::Value,
+        vl_opts: VlOpts,
+        scale: Option<f32>,
+        ppi: Option<f32>,
+    ) -> Result<Vec<FacetImage>, AnyError> {
+        self.vegalite_facets_to_pngs_with_opts(vl_spec, vl_opts, scale, ppi, Default::default())
+            .await
+    }
 
-        // Wait for result
-        match resp_rx.await {
-            Ok(vega_spec_result) => vega_spec_result,
-            Err(err) => bail!("Failed to retrieve conversion result: {}", err.to_string()),
-        }
+    /// Like vegalite_facets_to_pngs, with options of the images of the cells, e.g. to include
+    /// the shared axes and headers next to each cell
+    pub async fn vegalite_facets_to_pngs_with_opts(
+        &mut self,
+        vl_spec: serde_json::Value,
+        vl_opts: VlOpts,
+        scale: Option<f32>,
+        ppi: Option<f32>,
+        facet_opts: FacetOpts,
+    ) -> Result<Vec<FacetImage>, AnyError> {
+        let scale = scale.unwrap_or(1.0);
+        let fetch_retry =
+            FetchRetry::from_opts(vl_opts.fetch_retries, vl_opts.fetch_retry_backoff_ms);
+        let lenient = vl_opts.lenient;
+        let vl_spec = Arc::new(vl_spec);
+        let vl_opts = Arc::new(vl_opts);
+        let (mut facet_cells, _) = self
+            .request_rendered("Facet conversion", |responder| {
+                VlConvertCommand::VlToFacetCells {
+                    vl_spec: vl_spec.clone(),
+                    vl_opts: vl_opts.clone(),
+                    device_pixel_ratio: scale,
+                    responder,
+                }
+            })
+            .await?;
+        facet_cells.svg = post_process_svg(facet_cells.svg, &vl_opts.svg_post_processor)?;
+        with_image_opts(fetch_retry, lenient, &facet_cells.svg, || {
+            crop_facet_pngs(&facet_cells, scale, ppi, &facet_opts)
+        })
+        .0
     }
 
-    pub async fn vega_to_svg(
+    pub async fn vega_to_jpeg(
         &mut self,
         vg_spec: serde_json::Value,
         vg_opts: VgOpts,
-    ) -> Result<String, AnyError> {
-        let (resp_tx, resp_rx) = oneshot::channel::<Result<String, AnyError>>();
-        let cmd = VlConvertCommand::VgToSvg {
-            vg_spec,
-            vg_opts,
-            responder: resp_tx,
-        };
+        scale: Option<f32>,
+        quality: Option<u8>,
+    ) -> Result<Vec<u8>, AnyError> {
+        self.vega_to_jpeg_with_opts(vg_spec, vg_opts, scale, quality, Default::default())
+            .await
+    }
 
-        // Send request
-        match self.sender.send(cmd).await {
-            Ok(_) => {
-                // All good
-            }
-            Err(err) => {
-                bail!("Failed to send SVG conversion request: {}", err.to_string())
-            }
-        }
+    pub async fn vega_to_jpeg_with_opts(
+        &mut self,
+        vg_spec: serde_json::Value,
+        vg_opts: VgOpts,
+        scale: Option<f32>,
+        quality: Option<u8>,
+        jpeg_opts: JpegOpts,
+    ) -> Result<Vec<u8>, AnyError> {
+        let jpeg = self
+            .vega_to_jpeg_info_with_opts(vg_spec, vg_opts, scale, quality, jpeg_opts)
+            .await?;
+        Ok(jpeg.data)
+    }
 
-        // Wait for result
-        match resp_rx.await {
-            Ok(svg_result) => svg_result,
-            Err(err) => bail!("Failed to retrieve conversion result: {}", err.to_string()),
-        }
+    /// Convert a Vega spec to JPEG, returning the JPEG image along with the size that it was
+    /// rendered at
+    pub async fn vega_to_jpeg_info(
+        &mut self,
+        vg_spec: serde_json::Value,
+        vg_opts: VgOpts,
+        scale: Option<f32>,
+        quality: Option<u8>,
+    ) -> Result<JpegResult, AnyError> {
+        self.vega_to_jpeg_info_with_opts(vg_spec, vg_opts, scale, quality, Default::default())
+            .await
     }
 
-    pub async fn vega_to_scenegraph(
+    pub async fn vega_to_jpeg_info_with_opts(
         &mut self,
         vg_spec: serde_json::Value,
         vg_opts: VgOpts,
-    ) -> Result<serde_json::Value, AnyError> {
-        let (resp_tx, resp_rx) = oneshot::channel::<Result<serde_json::Value, AnyError>>();
-        let cmd = VlConvertCommand::VgToSg {
-            vg_spec,
-            vg_opts,
-            responder: resp_tx,
+        scale: Option<f32>,
+        quality: Option<u8>,
+        jpeg_opts: JpegOpts,
+    ) -> Result<JpegResult, AnyError> {
+        let scale = scale.unwrap_or(1.0);
+        let output = serde_json::json!({
+            "format": "jpeg",
+            "scale": scale,
+            "quality": quality,
+            "jpeg_opts": format!("{jpeg_opts:?}"),
+        });
+        let request_hash = vega_request_hash(&vg_spec, &vg_opts, output);
+        let rss_before = vg_opts.collect_resource_stats.then(RssSample::now);
+        let fetch_retry =
+            FetchRetry::from_opts(vg_opts.fetch_retries, vg_opts.fetch_retry_backoff_ms);
+        let lenient = vg_opts.lenient;
+        let (svg, mut warnings) = self
+            .vega_to_svg_with_warnings(vg_spec, vg_opts, Default::default(), scale)
+            .await?;
+        let jpeg_format = JpegFormat { quality, jpeg_opts };
+        let ctx = RenderContext {
+            scale,
+            ..Default::default()
         };
-
-        // Send request
-        match self.sender.send(cmd).await {
-            Ok(_) => {
-                // All good
-            }
-            Err(err) => {
-                bail!(
-                    "Failed to send Scenegraph conversion request: {}",
-                    err.to_string()
-                )
-            }
-        }
-
-        // Wait for result
-        match resp_rx.await {
-            Ok(svg_result) => svg_result,
-            Err(err) => bail!("Failed to retrieve conversion result: {}", err.to_string()),
+        let (jpeg, image_warnings) = with_image_opts(fetch_retry, lenient, &svg, || {
+            jpeg_format.jpeg_info(&svg, &ctx)
+        });
+        let jpeg = jpeg?;
+        extend_render_warnings(&mut warnings, image_warnings);
+        if let Some(rss_before) = &rss_before {
+            record_pixmap_stats(rss_before, jpeg.width_px as u64 * jpeg.height_px as u64 * 4)?;
         }
+        Ok(JpegResult {
+            request_hash: Some(request_hash),
+            warnings,
+            ..jpeg
+        })
     }
 
-    pub async fn vegalite_to_svg(
+    pub async fn vegalite_to_jpeg(
         &mut self,
         vl_spec: serde_json::Value,
         vl_opts: VlOpts,
-    ) -> Result<String, AnyError> {
-        let (resp_tx, resp_rx) = oneshot::channel::<Result<String, AnyError>>();
-        let cmd = VlConvertCommand::VlToSvg {
-            vl_spec,
-            vl_opts,
-            responder: resp_tx,
-        };
+        scale: Option<f32>,
+        quality: Option<u8>,
+    ) -> Result<Vec<u8>, AnyError> {
+        self.vegalite_to_jpeg_with_opts(vl_spec, vl_opts, scale, quality, Default::default())
+            .await
+    }
 
-        // Send request
-        match self.sender.send(cmd).await {
-            Ok(_) => {
-                // All good
-            }
-            Err(err) => {
-                bail!("Failed to send SVG conversion request: {}", err.to_string())
-            }
-        }
+    pub async fn vegalite_to_jpeg_with_opts(
+        &mut self,
+        vl_spec: serde_json::Value,
+        vl_opts: VlOpts,
+        scale: Option<f32>,
+        quality: Option<u8>,
+        jpeg_opts: JpegOpts,
+    ) -> Result<Vec<u8>, AnyError> {
+        let jpeg = self
+            .vegalite_to_jpeg_info_with_opts(vl_spec, vl_opts, scale, quality, jpeg_opts)
+            .await?;
+        Ok(jpeg.data)
+    }
 
-        // Wait for result
-        match resp_rx.await {
-            Ok(svg_result) => svg_result,
-            Err(err) => bail!("Failed to retrieve conversion result: {}", err.to_string()),
-        }
+    /// Convert a Vega-Lite spec to JPEG, returning the JPEG image along with the size that it was
+    /// rendered at
+    pub async fn vegalite_to_jpeg_info(
+        &mut self,
+        vl_spec: serde_json::Value,
+        vl_opts: VlOpts,
+        scale: Option<f32>,
+        quality: Option<u8>,
+    ) -> Result<JpegResult, AnyError> {
+        self.vegalite_to_jpeg_info_with_opts(vl_spec, vl_opts, scale, quality, Default::default())
+            .await
     }
 
-    pub async fn vegalite_to_scenegraph(
+    pub async fn vegalite_to_jpeg_info_with_opts(
         &mut self,
         vl_spec: serde_json::Value,
         vl_opts: VlOpts,
-    ) -> Result<serde_json::Value, AnyError> {
-        let (resp_tx, resp_rx) = oneshot::channel::<Result<serde_json::Value, AnyError>>();
-        let cmd = VlConvertCommand::VlToSg {
-            vl_spec,
-            vl_opts,
-            responder: resp_tx,
+        scale: Option<f32>,
+        quality: Option<u8>,
+        jpeg_opts: JpegOpts,
+    ) -> Result<JpegResult, AnyError> {
+        let scale = scale.unwrap_or(1.0);
+        let output = serde_json::json!({
+            "format": "jpeg",
+            "scale": scale,
+            "quality": quality,
+            "jpeg_opts": format!("{jpeg_opts:?}"),
+        });
+        let request_hash = vegalite_request_hash(&vl_spec, &vl_opts, output);
+        let rss_before = vl_opts.collect_resource_stats.then(RssSample::now);
+        let fetch_retry =
+            FetchRetry::from_opts(vl_opts.fetch_retries, vl_opts.fetch_retry_backoff_ms);
+        let lenient = vl_opts.lenient;
+        let (svg, mut warnings) = self
+            .vegalite_to_svg_with_warnings(vl_spec, vl_opts, Default::default(), scale)
+            .await?;
+        let jpeg_format = JpegFormat { quality, jpeg_opts };
+        let ctx = RenderContext {
+            scale,
+            ..Default::default()
         };
-
-        // Send request
-        match self.sender.send(cmd).await {
-            Ok(_) => {
-                // All good
-            }
-            Err(err) => {
-                bail!(
-                    "Failed to send Scenegraph conversion request: {}",
-                    err.to_string()
-                )
-            }
+        let (jpeg, image_warnings) = with_image_opts(fetch_retry, lenient, &svg, || {
+            jpeg_format.jpeg_info(&svg, &ctx)
+        });
+        let jpeg = jpeg?;
+        extend_render_warnings(&mut warnings, image_warnings);
+        if let Some(rss_before) = &rss_before {
+            record_pixmap_stats(rss_before, jpeg.width_px as u64 * jpeg.height_px as u64 * 4)?;
         }
+        Ok(JpegResult {
+            request_hash: Some(request_hash),
+            warnings,
+            ..jpeg
+        })
+    }
 
-        // Wait for result
-        match resp_rx.await {
-            Ok(svg_result) => svg_result,
-            Err(err) => bail!("Failed to retrieve conversion result: {}", err.to_string()),
-        }
+    pub async fn vega_to_pdf(
+        &mut self,
+        vg_spec: serde_json::Value,
+        vg_opts: VgOpts,
+    ) -> Result<Vec<u8>, AnyError> {
+        self.vega_to_pdf_with_opts(vg_spec, vg_opts, Default::default())
+            .await
     }
 
-    pub async fn vega_to_png(
+    pub async fn vega_to_pdf_with_opts(
         &mut self,
         vg_spec: serde_json::Value,
         vg_opts: VgOpts,
-        scale: Option<f32>,
-        ppi: Option<f32>,
+        pdf_opts: PdfOpts,
     ) -> Result<Vec<u8>, AnyError> {
-        let scale = scale.unwrap_or(1.0);
-        let svg = self.vega_to_svg(vg_spec, vg_opts).await?;
-        svg_to_png(&svg, scale, ppi)
+        let pdf = self
+            .vega_to_pdf_info_with_opts(vg_spec, vg_opts, pdf_opts)
+            .await?;
+        Ok(pdf.data)
     }
 
-    pub async fn vegalite_to_png(
+    /// Convert a Vega spec to PDF, returning the PDF document along with the render
+    /// warnings of the conversion
+    pub async fn vega_to_pdf_info(
+        &mut self,
+        vg_spec: serde_json::Value,
+        vg_opts: VgOpts,
+    ) -> Result<PdfResult, AnyError> {
+        self.vega_to_pdf_info_with_opts(vg_spec, vg_opts, Default::default())
+            .await
+    }
+
+    pub async fn vega_to_pdf_info_with_opts(
+        &mut self,
+        vg_spec: serde_json::Value,
+        vg_opts: VgOpts,
+        pdf_opts: PdfOpts,
+    ) -> Result<PdfResult, AnyError> {
+        let fetch_retry =
+            FetchRetry::from_opts(vg_opts.fetch_retries, vg_opts.fetch_retry_backoff_ms);
+        let lenient = vg_opts.lenient;
+        let (svg, mut warnings) = self
+            .vega_to_svg_with_warnings(vg_spec, vg_opts, Default::default(), 1.0)
+            .await?;
+        let (data, image_warnings) = with_image_opts(fetch_retry, lenient, &svg, || {
+            PdfFormat { pdf_opts }.from_svg(&svg, &RenderContext::default())
+        });
+        extend_render_warnings(&mut warnings, image_warnings);
+        Ok(PdfResult {
+            data: data?,
+            warnings,
+        })
+    }
+
+    pub async fn vegalite_to_pdf(
         &mut self,
         vl_spec: serde_json::Value,
         vl_opts: VlOpts,
-        scale: Option<f32>,
-        ppi: Option<f32>,
     ) -> Result<Vec<u8>, AnyError> {
-        let scale = scale.unwrap_or(1.0);
-        let svg = self.vegalite_to_svg(vl_spec, vl_opts).await?;
-        svg_to_png(&svg, scale, ppi)
+        self.vegalite_to_pdf_with_opts(vl_spec, vl_opts, Default::default())
+            .await
     }
 
-    pub async fn vega_to_jpeg(
+    pub async fn vegalite_to_pdf_with_opts(
         &mut self,
-        vg_spec: serde_json::Value,
-        vg_opts: VgOpts,
-        scale: Option<f32>,
-        quality: Option<u8>,
+        vl_spec: serde_json::Value,
+        vl_opts: VlOpts,
+        pdf_opts: PdfOpts,
     ) -> Result<Vec<u8>, AnyError> {
-        let scale = scale.unwrap_or(1.0);
-        let svg = self.vega_to_svg(vg_spec, vg_opts).await?;
-        svg_to_jpeg(&svg, scale, quality)
+        let pdf = self
+            .vegalite_to_pdf_info_with_opts(vl_spec, vl_opts, pdf_opts)
+            .await?;
+        Ok(pdf.data)
+    }
+
+    /// Convert a Vega-Lite spec to PDF, returning the PDF document along with the render
+    /// warnings of the conversion
+    pub async fn vegalite_to_pdf_info(
+        &mut self,
+        vl_spec: serde_json::Value,
+        vl_opts: VlOpts,
+    ) -> Result<PdfResult, AnyError> {
+        self.vegalite_to_pdf_info_with_opts(vl_spec, vl_opts, Default::default())
+            .await
+    }
+
+    pub async fn vegalite_to_pdf_info_with_opts(
+        &mut self,
+        vl_spec: serde_json::Value,
+        vl_opts: VlOpts,
+        pdf_opts: PdfOpts,
+    ) -> Result<PdfResult, AnyError> {
+        let fetch_retry =
+            FetchRetry::from_opts(vl_opts.fetch_retries, vl_opts.fetch_retry_backoff_ms);
+        let lenient = vl_opts.lenient;
+        let (svg, mut warnings) = self
+            .vegalite_to_svg_with_warnings(vl_spec, vl_opts, Default::default(), 1.0)
+            .await?;
+        let (data, image_warnings) = with_image_opts(fetch_retry, lenient, &svg, || {
+            PdfFormat { pdf_opts }.from_svg(&svg, &RenderContext::default())
+        });
+        extend_render_warnings(&mut warnings, image_warnings);
+        Ok(PdfResult {
+            data: data?,
+            warnings,
+        })
+    }
+
+    /// Convert a Vega-Lite spec to several formats at once. The chart is compiled and
+    /// rendered to SVG a single time, and the requested PNG, JPEG, and PDF outputs are
+    /// derived from that SVG. With `export_data`, the rows of the chart's datasets are
+    /// returned as well.
+    pub async fn vegalite_export(
+        &mut self,
+        vl_spec: serde_json::Value,
+        vl_opts: VlOpts,
+        request: ExportRequest,
+    ) -> Result<ExportResult, AnyError> {
+        let rss_before = vl_opts.collect_resource_stats.then(RssSample::now);
+        let fetch_retry =
+            FetchRetry::from_opts(vl_opts.fetch_retries, vl_opts.fetch_retry_backoff_ms);
+        let lenient = vl_opts.lenient;
+        // Expressions that read devicePixelRatio see the scale of the PNG image, or of the
+        // JPEG image when there's no PNG image
+        let device_pixel_ratio = match (&request.png, &request.jpeg) {
+            (Some(png), _) => png.scale.unwrap_or(1.0),
+            (None, Some(jpeg)) => jpeg.scale.unwrap_or(1.0),
+            (None, None) => 1.0,
+        };
+        let (svg, datasets, mut warnings) = if request.export_data {
+            let (svg, datasets, warnings) = self
+                .vegalite_to_svg_and_datasets(vl_spec, vl_opts, device_pixel_ratio)
+                .await?;
+            (svg, Some(datasets), warnings)
+        } else {
+            let (svg, warnings) = self
+                .vegalite_to_svg_with_warnings(
+                    vl_spec,
+                    vl_opts,
+                    Default::default(),
+                    device_pixel_ratio,
+                )
+                .await?;
+            (svg, None, warnings)
+        };
+        // Rasterizing the SVG to several formats leaves out the same images, which are only
+        // warned about once
+        let mut rasterize = |f: &dyn Fn() -> Result<Vec<u8>, AnyError>| {
+            let (data, image_warnings) = with_image_opts(fetch_retry, lenient, &svg, f);
+            extend_render_warnings(&mut warnings, image_warnings);
+            data
+        };
+
+        let png = request
+            .png
+            .map(|opts| {
+                let png_format = PngFormat {
+                    png_opts: opts.png_opts,
+                };
+                let ctx = RenderContext {
+                    scale: opts.scale.unwrap_or(1.0),
+                    ppi: opts.ppi.unwrap_or(72.0),
+                    ..Default::default()
+                };
+                rasterize(&|| png_format.from_svg(&svg, &ctx))
+            })
+            .transpose()?;
+        let jpeg = request
+            .jpeg
+            .map(|opts| {
+                let jpeg_format = JpegFormat {
+                    quality: opts.quality,
+                    jpeg_opts: opts.jpeg_opts,
+                };
+                let ctx = RenderContext {
+                    scale: opts.scale.unwrap_or(1.0),
+                    ..Default::default()
+                };
+                rasterize(&|| jpeg_format.from_svg(&svg, &ctx))
+            })
+            .transpose()?;
+        let pdf = request
+            .pdf
+            .then(|| rasterize(&|| PdfFormat::default().from_svg(&svg, &RenderContext::default())))
+            .transpose()?;
+
+        if let Some(rss_before) = &rss_before {
+            let png_bytes = png.as_deref().map(png_pixmap_bytes).transpose()?;
+            let jpeg_bytes = jpeg.as_deref().map(jpeg_pixmap_bytes).transpose()?;
+            if let Some(pixmap_bytes) = png_bytes.max(jpeg_bytes) {
+                record_pixmap_stats(rss_before, pixmap_bytes)?;
+            }
+        }
+
+        Ok(ExportResult {
+            svg: request.svg.then_some(svg),
+            png,
+            jpeg,
+            pdf,
+            datasets,
+            warnings,
+        })
     }
 
-    pub async fn vegalite_to_jpeg(
+    /// Convert a Vega-Lite spec to an image in the given format and write it to a file.
+    /// PNG images are encoded straight to the file, so that converting many large charts
+    /// doesn't hold their encoded images in memory. The image is written to a temporary file
+    /// in the same directory, which replaces the file at `path` once the image is complete,
+    /// so a failed conversion leaves any existing file at `path` unchanged
+    pub async fn vegalite_to_file(
         &mut self,
         vl_spec: serde_json::Value,
         vl_opts: VlOpts,
-        scale: Option<f32>,
-        quality: Option<u8>,
-    ) -> Result<Vec<u8>, AnyError> {
-        let scale = scale.unwrap_or(1.0);
-        let svg = self.vegalite_to_svg(vl_spec, vl_opts).await?;
-        svg_to_jpeg(&svg, scale, quality)
+        format: ExportFormat,
+        file_opts: ImageFileOpts,
+        path: &Path,
+    ) -> Result<(), AnyError> {
+        let fetch_retry =
+            FetchRetry::from_opts(vl_opts.fetch_retries, vl_opts.fetch_retry_backoff_ms);
+        let lenient = vl_opts.lenient;
+        let scale = file_opts.scale.unwrap_or(1.0);
+        let device_pixel_ratio = match format {
+            ExportFormat::Png | ExportFormat::Jpeg => scale,
+            ExportFormat::Svg | ExportFormat::Pdf => 1.0,
+        };
+        let (svg, _) = self
+            .vegalite_to_svg_with_warnings(vl_spec, vl_opts, Default::default(), device_pixel_ratio)
+            .await?;
+
+        let dir = match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+        let mut builder = tempfile::Builder::new();
+        builder.prefix(".vl-convert");
+        // Temporary files are only readable by their owner, unlike the files that are created
+        // for the output otherwise
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            builder.permissions(match std::fs::metadata(path) {
+                Ok(metadata) => metadata.permissions(),
+                Err(_) => std::fs::Permissions::from_mode(0o666),
+            });
+        }
+        let temp_file = builder
+            .tempfile_in(dir)
+            .map_err(|err| anyhow!("Failed to create output file {}: {}", path.display(), err))?;
+        let mut file = std::io::BufWriter::new(temp_file);
+        with_image_opts(fetch_retry, lenient, &svg, || -> Result<(), AnyError> {
+            match format {
+                ExportFormat::Svg => file.write_all(svg.as_bytes())?,
+                ExportFormat::Png => {
+                    svg_to_png_into(&svg, scale, file_opts.ppi, &file_opts.png_opts, &mut file)?;
+                }
+                ExportFormat::Jpeg => {
+                    let jpeg_format = JpegFormat {
+                        quality: file_opts.quality,
+                        jpeg_opts: file_opts.jpeg_opts,
+                    };
+                    let ctx = RenderContext {
+                        scale,
+                        ..Default::default()
+                    };
+                    file.write_all(&jpeg_format.from_svg(&svg, &ctx)?)?;
+                }
+                ExportFormat::Pdf => {
+                    let pdf_format = PdfFormat {
+                        pdf_opts: file_opts.pdf_opts,
+                    };
+                    file.write_all(&pdf_format.from_svg(&svg, &RenderContext::default())?)?;
+                }
+            }
+            file.flush()?;
+            Ok(())
+        })
+        .0?;
+        // The temporary file is removed when it's dropped without being persisted
+        let temp_file = file.into_inner().map_err(|err| anyhow!(err.to_string()))?;
+        temp_file
+            .persist(path)
+            .map_err(|err| anyhow!("Failed to write output file {}: {}", path.display(), err))?;
+        Ok(())
     }
 
-    pub async fn vega_to_pdf(
-        &mut self,
-        vg_spec: serde_json::Value,
-        vg_opts: VgOpts,
-    ) -> Result<Vec<u8>, AnyError> {
-        let svg = self.vega_to_svg(vg_spec, vg_opts).await?;
-        svg_to_pdf(&svg)
+    /// Register a format that [`convert_to`](VlConverter::convert_to) can convert charts to.
+    /// A format replaces the format with the same id that was registered before it, including
+    /// the built-in formats. Registered formats are shared with the clones of this converter
+    pub fn register_output_format(&self, format: Box<dyn OutputFormat>) -> Result<(), AnyError> {
+        let id = format.id().to_string();
+        if id.is_empty() {
+            bail!("Output format id must not be empty");
+        }
+        match self.output_formats.lock() {
+            Ok(mut formats) => {
+                formats.insert(id, Arc::from(format));
+                Ok(())
+            }
+            Err(err) => bail!("Failed to acquire lock: {}", err.to_string()),
+        }
     }
 
-    pub async fn vegalite_to_pdf(
+    /// Ids of the formats that [`convert_to`](VlConverter::convert_to) supports, in
+    /// alphabetical order
+    pub fn output_format_ids(&self) -> Result<Vec<String>, AnyError> {
+        match self.output_formats.lock() {
+            Ok(formats) => {
+                let mut ids: Vec<_> = formats.keys().cloned().collect();
+                ids.sort();
+                Ok(ids)
+            }
+            Err(err) => bail!("Failed to acquire lock: {}", err.to_string()),
+        }
+    }
+
+    /// Convert a Vega-Lite spec to the registered format with id `format_id`. The chart is
+    /// rendered to SVG and passed to the format along with `options_json`, a JSON object of
+    /// options as described by [`RenderContext::from_options`]
+    pub async fn convert_to(
         &mut self,
         vl_spec: serde_json::Value,
         vl_opts: VlOpts,
+        format_id: &str,
+        options_json: Option<serde_json::Value>,
     ) -> Result<Vec<u8>, AnyError> {
-        let svg = self.vegalite_to_svg(vl_spec, vl_opts).await?;
-        svg_to_pdf(&svg)
+        let format = match self.output_formats.lock() {
+            Ok(formats) => formats.get(format_id).cloned(),
+            Err(err) => bail!("Failed to acquire lock: {}", err.to_string()),
+        };
+        let Some(format) = format else {
+            bail!(
+                "Unsupported output format: {}. Expected one of {}",
+                format_id,
+                self.output_format_ids()?.join(", ")
+            );
+        };
+        let ctx = RenderContext::from_options(options_json)?;
+        let fetch_retry =
+            FetchRetry::from_opts(vl_opts.fetch_retries, vl_opts.fetch_retry_backoff_ms);
+        let lenient = vl_opts.lenient;
+        let (svg, _) = self
+            .vegalite_to_svg_with_warnings(vl_spec, vl_opts, Default::default(), ctx.scale)
+            .await?;
+        with_image_opts(fetch_retry, lenient, &svg, || format.from_svg(&svg, &ctx)).0
     }
 
+    /// Get the vega-embed bundle of `vl_version`, which HTML output inlines, as built by
+    /// [`crate::html::get_vegaembed_bundle`]. Bundles are cached for the life of the converter
     pub async fn get_vegaembed_bundle(
         &mut self,
         vl_version: VlVersion,
@@ -1314,11 +5444,7 @@ impl VlConverter {
         let bundle = match self._vegaembed_bundles.entry(vl_version) {
             Entry::Occupied(occupied) => occupied.get().clone(),
             Entry::Vacant(vacant) => {
-                let bundle = bundle_vega_snippet(
-                    "window.vegaEmbed=vegaEmbed; window.vega=vega; window.vegaLite=vegaLite;",
-                    vl_version,
-                )
-                .await?;
+                let bundle = get_vegaembed_bundle(vl_version).await?;
                 vacant.insert(bundle.clone());
                 bundle
             }
@@ -1327,30 +5453,50 @@ impl VlConverter {
         Ok(bundle)
     }
 
-    async fn build_html(
+    /// Get the elements for the head of an HTML document that load the Vega libraries,
+    /// preceded by a Content-Security-Policy meta element when there are allowed base urls
+    async fn get_html_head_scripts(
         &mut self,
-        code: &str,
         vl_version: VlVersion,
         bundle: bool,
+        allowed_base_urls: Option<&[String]>,
     ) -> Result<String, AnyError> {
-        let script_tags = if bundle {
-            format!(
-                r#"
-    <script type="text/javascript">{}</script>
-            "#,
+        let mut elements = Vec::new();
+        // Browsers enforce the allowed base urls of the conversion for the origins of the
+        // data and images that the page loads, including those of signals and data fields
+        if let Some(allowed_base_urls) = allowed_base_urls {
+            elements.push(get_csp_meta(allowed_base_urls)?);
+        }
+        if bundle {
+            elements.push(format!(
+                r#"<script type="text/javascript">{}</script>"#,
                 self.get_vegaembed_bundle(vl_version).await?
-            )
+            ));
         } else {
-            format!(
-                r#"
-    <script src="https://cdn.jsdelivr.net/npm/vega@5"></script>
-    <script src="https://cdn.jsdelivr.net/npm/vega-lite@{vl_ver}"></script>
-    <script src="https://cdn.jsdelivr.net/npm/vega-embed@6"></script>
-            "#,
-                vl_ver = vl_version.to_semver()
-            )
-        };
+            elements
+                .push(r#"<script src="https://cdn.jsdelivr.net/npm/vega@5"></script>"#.to_string());
+            elements.push(format!(
+                r#"<script src="https://cdn.jsdelivr.net/npm/vega-lite@{}"></script>"#,
+                vl_version.to_semver()
+            ));
+            elements.push(
+                r#"<script src="https://cdn.jsdelivr.net/npm/vega-embed@6"></script>"#.to_string(),
+            );
+        }
+        Ok(elements.join("\n    "))
+    }
 
+    async fn build_html_document(
+        &mut self,
+        title: &str,
+        body: &str,
+        vl_version: VlVersion,
+        bundle: bool,
+        allowed_base_urls: Option<&[String]>,
+    ) -> Result<String, AnyError> {
+        let scripts = self
+            .get_html_head_scripts(vl_version, bundle, allowed_base_urls)
+            .await?;
         Ok(format!(
             r#"<!DOCTYPE html>
 <html>
@@ -1366,20 +5512,22 @@ impl VlConverter {
         }}
     </style>
     <meta charset="UTF-8">
-    <title>Chart</title>
-{script_tags}
+    <title>{title}</title>
+    {scripts}
   </head>
   <body>
-    <div id="vega-chart"></div>
-    <script type="text/javascript">
-{code}
-    </script>
+{body}
   </body>
 </html>
-        "#
+"#,
+            title = escape_html(title),
         ))
     }
 
+    /// Convert a Vega-Lite spec to an HTML document that renders the chart in the browser.
+    /// With `show_warnings`, the spec is also compiled here to print the warnings that
+    /// Vega-Lite reports for it, as conversions to images do, and specs that fail to compile
+    /// are an error
     pub async fn vegalite_to_html(
         &mut self,
         vl_spec: serde_json::Value,
@@ -1387,9 +5535,108 @@ impl VlConverter {
         bundle: bool,
         renderer: Renderer,
     ) -> Result<String, AnyError> {
+        self.vegalite_to_html_with_opts(vl_spec, vl_opts, bundle, renderer, Default::default())
+            .await
+    }
+
+    pub async fn vegalite_to_html_with_opts(
+        &mut self,
+        vl_spec: serde_json::Value,
+        vl_opts: VlOpts,
+        bundle: bool,
+        renderer: Renderer,
+        html_opts: HtmlOpts,
+    ) -> Result<String, AnyError> {
+        if let Some(template) = &html_opts.template {
+            check_html_template(template)?;
+        }
+        if let Some(allowed_base_urls) = &vl_opts.allowed_base_urls {
+            check_spec_urls(&vl_spec, allowed_base_urls, html_opts.warn_only)?;
+        }
+        let vl_version = vl_opts.vl_version;
+        let embed_opts = vl_opts.to_embed_opts(renderer)?;
+        let allowed_base_urls = vl_opts.allowed_base_urls.clone();
+        // Rendering the fallback image compiles the spec, and prints its warnings when
+        // requested, so it isn't compiled a second time
+        let fallback = if html_opts.static_fallback {
+            Some(
+                self.vegalite_to_png_info(
+                    vl_spec.clone(),
+                    vl_opts,
+                    Some(html_opts.fallback_scale()),
+                    None,
+                )
+                .await?,
+            )
+        } else {
+            if vl_opts.show_warnings {
+                self.vegalite_to_vega(vl_spec.clone(), vl_opts).await?;
+            }
+            None
+        };
+        let scripts = self
+            .get_html_head_scripts(vl_version, bundle, allowed_base_urls.as_deref())
+            .await?;
+        get_chart_html(
+            &vl_spec,
+            &embed_opts,
+            &scripts,
+            fallback.as_ref(),
+            html_opts.template.as_deref(),
+        )
+    }
+
+    /// Convert several Vega-Lite specs into a single HTML document, with one section per
+    /// chart that is headed by the chart's title when there is one. The Vega libraries are
+    /// loaded once and shared by all of the charts
+    pub async fn vegalite_to_html_report(
+        &mut self,
+        specs: Vec<(Option<String>, serde_json::Value)>,
+        vl_opts: VlOpts,
+        bundle: bool,
+        renderer: Renderer,
+    ) -> Result<String, AnyError> {
+        if specs.is_empty() {
+            bail!("An HTML report requires at least one Vega-Lite spec");
+        }
         let vl_version = vl_opts.vl_version;
-        let code = get_vega_or_vegalite_script(vl_spec, vl_opts.to_embed_opts(renderer)?)?;
-        self.build_html(&code, vl_version, bundle).await
+        let embed_opts = vl_opts.to_embed_opts(renderer)?;
+
+        let mut sections = Vec::new();
+        let mut scripts = Vec::new();
+        for (i, (title, vl_spec)) in specs.into_iter().enumerate() {
+            if let Some(allowed_base_urls) = &vl_opts.allowed_base_urls {
+                check_spec_urls(&vl_spec, allowed_base_urls, false)?;
+            }
+            let chart_id = format!("vega-chart-{i}");
+            let heading = match &title {
+                None => String::new(),
+                Some(title) => format!("\n      <h2>{}</h2>", escape_html(title)),
+            };
+            sections.push(format!(
+                r#"    <section>{heading}
+      <div id="{chart_id}"></div>
+    </section>"#
+            ));
+            scripts.push(get_chart_script(vl_spec, embed_opts.clone(), &chart_id)?);
+        }
+
+        let body = format!(
+            r#"{sections}
+    <script type="text/javascript">
+{scripts}
+    </script>"#,
+            sections = sections.join("\n"),
+            scripts = scripts.join(""),
+        );
+        self.build_html_document(
+            "Report",
+            &body,
+            vl_version,
+            bundle,
+            vl_opts.allowed_base_urls.as_deref(),
+        )
+        .await
     }
 
     pub async fn vega_to_html(
@@ -1399,71 +5646,673 @@ impl VlConverter {
         bundle: bool,
         renderer: Renderer,
     ) -> Result<String, AnyError> {
-        let code = get_vega_or_vegalite_script(vg_spec, vg_opts.to_embed_opts(renderer)?)?;
-        self.build_html(&code, Default::default(), bundle).await
+        self.vega_to_html_with_opts(vg_spec, vg_opts, bundle, renderer, Default::default())
+            .await
+    }
+
+    pub async fn vega_to_html_with_opts(
+        &mut self,
+        vg_spec: serde_json::Value,
+        vg_opts: VgOpts,
+        bundle: bool,
+        renderer: Renderer,
+        html_opts: HtmlOpts,
+    ) -> Result<String, AnyError> {
+        if let Some(template) = &html_opts.template {
+            check_html_template(template)?;
+        }
+        if let Some(allowed_base_urls) = &vg_opts.allowed_base_urls {
+            check_spec_urls(&vg_spec, allowed_base_urls, html_opts.warn_only)?;
+        }
+        let embed_opts = vg_opts.to_embed_opts(renderer)?;
+        let allowed_base_urls = vg_opts.allowed_base_urls.clone();
+        let fallback = if html_opts.static_fallback {
+            Some(
+                self.vega_to_png_info(
+                    vg_spec.clone(),
+                    vg_opts,
+                    Some(html_opts.fallback_scale()),
+                    None,
+                )
+                .await?,
+            )
+        } else {
+            None
+        };
+        let scripts = self
+            .get_html_head_scripts(Default::default(), bundle, allowed_base_urls.as_deref())
+            .await?;
+        get_chart_html(
+            &vg_spec,
+            &embed_opts,
+            &scripts,
+            fallback.as_ref(),
+            html_opts.template.as_deref(),
+        )
     }
 
     pub async fn get_local_tz(&mut self) -> Result<Option<String>, AnyError> {
-        let (resp_tx, resp_rx) = oneshot::channel::<Result<Option<String>, AnyError>>();
-        let cmd = VlConvertCommand::GetLocalTz { responder: resp_tx };
+        self.request("get_local_tz", |responder| VlConvertCommand::GetLocalTz {
+            responder,
+        })
+        .await
+    }
+
+    /// Get the config of each built-in theme, keyed by theme name. The keys of the themes and
+    /// their configs are sorted, so that the same version of vega-themes always produces the
+    /// same output
+    pub async fn get_themes(&mut self) -> Result<serde_json::Value, AnyError> {
+        self.request("get_themes", |responder| VlConvertCommand::GetThemes {
+            responder,
+        })
+        .await
+    }
+
+    /// Get the sorted names of the built-in themes, without their configs
+    pub async fn get_theme_names(&mut self) -> Result<Vec<String>, AnyError> {
+        self.request("get_theme_names", |responder| {
+            VlConvertCommand::GetThemeNames { responder }
+        })
+        .await
+    }
 
-        // Send request
-        match self.sender.send(cmd).await {
-            Ok(_) => {
-                // All good
+    /// Register a Rust function that Vega expressions can call by `name`, e.g. a custom
+    /// number formatter used in an axis `labelExpr`. The function receives the arguments of
+    /// the call as JSON values, and returns a JSON value. Errors are thrown in JavaScript,
+    /// and fail the conversion like other errors that Vega operators throw.
+    ///
+    /// Registered functions persist for the lifetime of the converter and are available to
+    /// all subsequent conversions, including those of Vega-Lite specs. Each call crosses from
+    /// JavaScript into Rust and serializes its arguments and result as JSON, so it's slower
+    /// than a built-in expression function. This matters most in expressions that are
+    /// evaluated for every row of a large dataset. HTML output renders in the browser, where
+    /// registered functions aren't available.
+    pub async fn register_expression_fn<F>(&mut self, name: &str, f: F) -> Result<(), AnyError>
+    where
+        F: Fn(&[serde_json::Value]) -> Result<serde_json::Value, AnyError> + Send + 'static,
+    {
+        let f: ExpressionFn = Box::new(f);
+        let f: SharedExpressionFn = Arc::new(Mutex::new(f));
+        self.request("register_expression_fn", |responder| {
+            VlConvertCommand::RegisterExpressionFn {
+                name: name.to_string(),
+                f: shared_expression_fn(f.clone()),
+                responder,
             }
-            Err(err) => {
-                bail!("Failed to send get_local_tz request: {}", err.to_string())
+        })
+        .await?;
+
+        // Keep the function, to register it again if the worker restarts
+        match self.expression_fns.lock() {
+            Ok(mut fns) => {
+                fns.insert(name.to_string(), f);
             }
+            Err(err) => bail!("Failed to acquire lock: {}", err.to_string()),
         }
+        Ok(())
+    }
 
-        // Wait for result
-        match resp_rx.await {
-            Ok(local_tz_result) => local_tz_result,
-            Err(err) => bail!(
-                "Failed to retrieve get_local_tz result: {}",
-                err.to_string()
+    /// Load and evaluate the modules of Vega-Lite versions ahead of time, so that the first
+    /// conversion that uses each version doesn't pay for its evaluation.
+    ///
+    /// Versions that were evaluated already, by an earlier conversion or call, are skipped.
+    /// The versions are evaluated again when the worker restarts, and the number of
+    /// evaluations is reported by [`VlConverter::health_check`].
+    pub async fn warm_versions(&mut self, vl_versions: &[VlVersion]) -> Result<(), AnyError> {
+        self.request("warm_versions", |responder| {
+            VlConvertCommand::WarmVersions {
+                vl_versions: vl_versions.to_vec(),
+                responder,
+            }
+        })
+        .await?;
+
+        // Keep the versions, to warm them again if the worker restarts
+        match self.warm_vl_versions.lock() {
+            Ok(mut warm_vl_versions) => {
+                for vl_version in vl_versions {
+                    if !warm_vl_versions.contains(vl_version) {
+                        warm_vl_versions.push(*vl_version);
+                    }
+                }
+            }
+            Err(err) => bail!("Failed to acquire lock: {}", err.to_string()),
+        }
+        Ok(())
+    }
+
+    /// Check that the converter's JavaScript worker is responsive, without rendering a chart.
+    ///
+    /// The worker evaluates a trivial expression, which fails if it doesn't respond within
+    /// [`HEALTH_CHECK_TIMEOUT`], e.g. because it's busy with a long conversion. A worker that
+    /// has stopped is restarted first. The report includes counts of the conversions that the
+    /// worker has performed and of the times it was restarted, which are shared by all clones
+    /// of this converter.
+    pub async fn health_check(&mut self) -> Result<HealthReport, AnyError> {
+        let check = self.request("health_check", |responder| VlConvertCommand::HealthCheck {
+            responder,
+        });
+
+        // The timer of the timeout is registered with the caller's tokio runtime, or with
+        // TIMER_RUNTIME, so that this works with any async executor. It's cancelled as soon
+        // as the check completes
+        let handle = tokio::runtime::Handle::try_current()
+            .unwrap_or_else(|_| TIMER_RUNTIME.handle().clone());
+        let check = {
+            let _guard = handle.enter();
+            tokio::time::timeout(HEALTH_CHECK_TIMEOUT, check)
+        };
+        let vega_version = match check.await {
+            Ok(result) => result?,
+            Err(_) => bail!(
+                "Health check timed out after {} seconds",
+                HEALTH_CHECK_TIMEOUT.as_secs()
             ),
+        };
+
+        let (worker_uptime, worker_restarts) = match self.worker.lock() {
+            Ok(worker) => (worker.started_at.elapsed(), worker.generation),
+            Err(err) => bail!("Failed to acquire lock: {}", err.to_string()),
+        };
+        Ok(HealthReport {
+            vega_version,
+            vl_default_version: VlVersion::default().to_semver().to_string(),
+            worker_uptime,
+            worker_restarts,
+            last_conversion_at: self.counters.last_conversion_at(),
+            conversions_completed: self.counters.completed(),
+            conversions_failed: self.counters.failed(),
+            vl_module_evaluations: self.counters.vl_module_evaluations(),
+        })
+    }
+}
+
+impl Default for VlConverter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Options that control how rendered charts are written as SVG
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SvgOpts {
+    /// Whether to add textLength attributes to text elements, so that applications which
+    /// substitute fonts (e.g. Inkscape or PowerPoint) reproduce the measured text layout
+    pub text_length: bool,
+    /// Whether to write the tooltip content of marks as <title> elements, which browsers
+    /// display as native tooltips when hovering over the mark
+    pub native_tooltips: bool,
+    /// Whether to write the smallest SVG that draws the same image, without comments,
+    /// accessibility metadata, class names, or attributes set to their default values
+    pub minify: bool,
+    /// Number of decimals that minified SVG rounds numbers to. When None,
+    /// [`DEFAULT_SVG_PRECISION`] is used. Ignored unless `minify` is enabled
+    pub precision: Option<u8>,
+}
+
+fn apply_svg_opts(svg: String, svg_opts: &SvgOpts) -> Result<String, AnyError> {
+    let svg = if svg_opts.text_length {
+        add_svg_text_length(&svg)?
+    } else {
+        svg
+    };
+    if svg_opts.minify {
+        minify_svg(&svg, svg_opts.precision.unwrap_or(DEFAULT_SVG_PRECISION))
+    } else {
+        Ok(svg)
+    }
+}
+
+/// Options that control how rendered images are rasterized and encoded as PNG
+#[derive(Debug, Clone, Copy)]
+pub struct PngOpts {
+    /// Whether to write an Adam7 interlaced PNG, which browsers can display progressively
+    pub interlaced: bool,
+    /// Number of pixel rows to demultiply and stream to the encoder at a time. The compressed
+    /// data of each chunk of rows is flushed to the output as its own IDAT chunk.
+    /// When None, the full image is encoded in a single pass.
+    pub chunk_rows: Option<u32>,
+    /// Whether to antialias the edges of shapes. When disabled, shapes are drawn with crisp
+    /// edges on whole pixels, so that adjacent rects (e.g. the cells of a heatmap) don't show
+    /// seams. Text is antialiased either way
+    pub antialias: bool,
+    /// Whether the ppi resizes the image or is only recorded in its metadata
+    pub ppi_mode: PpiMode,
+    /// How embedded raster images are resampled when they're scaled. Images with an
+    /// image-rendering attribute of their own keep it
+    pub image_rendering: ImageRendering,
+    /// Color to composite the image over before it's encoded. When None, transparent parts of
+    /// the chart are left transparent
+    pub flatten_background: Option<BackgroundColor>,
+}
+
+impl Default for PngOpts {
+    fn default() -> Self {
+        Self {
+            interlaced: false,
+            chunk_rows: None,
+            antialias: true,
+            ppi_mode: PpiMode::default(),
+            image_rendering: ImageRendering::default(),
+            flatten_background: None,
         }
     }
+}
 
-    pub async fn get_themes(&mut self) -> Result<serde_json::Value, AnyError> {
-        let (resp_tx, resp_rx) = oneshot::channel::<Result<serde_json::Value, AnyError>>();
-        let cmd = VlConvertCommand::GetThemes { responder: resp_tx };
+/// Options that control how rendered images are encoded as JPEG
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JpegOpts {
+    /// Color to composite the image over, since JPEG images can't be transparent. The alpha
+    /// of the color is ignored. Defaults to white
+    pub background: BackgroundColor,
+}
+
+/// How the pixels per inch (ppi) of a PNG image affect its size.
+///
+/// Either way, the ppi is written to the pHYs chunk of the PNG, and charts are laid out in
+/// CSS pixels, which are 1/72 of an inch at the default ppi of 72. With `ResizePixels`, the
+/// image is `width * scale * ppi / 72` by `height * scale * ppi / 72` pixels, so that it has
+/// the same physical size at any ppi. With `MetadataOnly`, the image is `width * scale` by
+/// `height * scale` pixels whatever the ppi, and the ppi only changes the size that
+/// applications print or place the image at
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PpiMode {
+    #[default]
+    ResizePixels,
+    MetadataOnly,
+}
+
+impl Display for PpiMode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mode = match self {
+            PpiMode::ResizePixels => "resize-pixels",
+            PpiMode::MetadataOnly => "metadata-only",
+        };
+        std::fmt::Display::fmt(mode, f)
+    }
+}
+
+impl FromStr for PpiMode {
+    type Err = AnyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(
+            match s.trim().to_ascii_lowercase().replace('_', "-").as_str() {
+                "resize-pixels" => Self::ResizePixels,
+                "metadata-only" => Self::MetadataOnly,
+                _ => return Err(anyhow!("Unsupported ppi mode: {}", s)),
+            },
+        )
+    }
+}
+
+/// Options that control how rendered images are written as PDF documents
+#[derive(Debug, Clone, Copy)]
+pub struct PdfOpts {
+    /// Whether to write a PDF/A-2b document for archiving, with XMP metadata, an sRGB output
+    /// intent, and a document ID
+    pub pdfa: bool,
+    /// Pixels per inch of the SVG image. PDF pages are measured in points, which are 1/72 of
+    /// an inch, so the page and its content are scaled by 72 / `source_dpi`. The default of
+    /// 96 matches CSS, so that a chart 960 pixels wide is written to a page 720 points wide
+    pub source_dpi: f32,
+}
+
+impl Default for PdfOpts {
+    fn default() -> Self {
+        Self {
+            pdfa: false,
+            source_dpi: 96.0,
+        }
+    }
+}
+
+/// Options that control how charts are exported as HTML documents
+#[derive(Debug, Clone, Default)]
+pub struct HtmlOpts {
+    /// Whether to embed a PNG rendering of the chart in the document, which is shown until
+    /// the chart is rendered in the browser, and in viewers that don't run scripts at all
+    pub static_fallback: bool,
+    /// Scale of the fallback image. When None, the image is rendered at a scale of 2 so that
+    /// it stays crisp on high density displays
+    pub static_fallback_scale: Option<f32>,
+    /// Log a warning for the data and image urls of the spec that `allowed_base_urls`
+    /// doesn't allow, rather than failing the export
+    pub warn_only: bool,
+    /// HTML template to write the document with, with the placeholders described by
+    /// [`DEFAULT_HTML_TEMPLATE`](crate::html::DEFAULT_HTML_TEMPLATE). When None, the default
+    /// template is used
+    pub template: Option<String>,
+}
+
+impl HtmlOpts {
+    fn fallback_scale(&self) -> f32 {
+        self.static_fallback_scale.unwrap_or(2.0)
+    }
+}
+
+/// An SVG image, with the size that it's displayed at
+#[derive(Debug, Clone, PartialEq)]
+pub struct SvgResult {
+    pub data: String,
+    /// Width of the image in CSS pixels, from the width attribute of the svg element
+    pub width: f64,
+    /// Height of the image in CSS pixels, from the height attribute of the svg element
+    pub height: f64,
+    /// The min-x, min-y, width, and height of the viewBox of the svg element, when it has one
+    pub view_box: Option<[f64; 4]>,
+    /// Hash of the spec and options that the image was converted from, which is equal for
+    /// equal requests with a given version of vl-convert. See
+    /// [`crate::request_hash::request_hash`]. None for images converted from SVG
+    pub request_hash: Option<String>,
+    /// Render warnings of the conversion, like the images that a lenient conversion left out
+    pub warnings: Vec<RenderWarning>,
+}
+
+/// A PNG image, with the size that it was rendered at
+#[derive(Debug, Clone, PartialEq)]
+pub struct PngResult {
+    pub data: Vec<u8>,
+    /// Width of the image in pixels
+    pub width_px: u32,
+    /// Height of the image in pixels
+    pub height_px: u32,
+    /// Width of the chart in CSS pixels, before scaling
+    pub css_width: f64,
+    /// Height of the chart in CSS pixels, before scaling
+    pub css_height: f64,
+    pub scale: f32,
+    pub ppi: f32,
+    /// Hash of the spec and options that the image was converted from, which is equal for
+    /// equal requests with a given version of vl-convert. See
+    /// [`crate::request_hash::request_hash`]. None for images converted from SVG
+    pub request_hash: Option<String>,
+    /// Render warnings of the conversion, like the images that a lenient conversion left out
+    pub warnings: Vec<RenderWarning>,
+}
+
+/// A JPEG image, with the size that it was rendered at
+#[derive(Debug, Clone, PartialEq)]
+pub struct JpegResult {
+    pub data: Vec<u8>,
+    /// Width of the image in pixels
+    pub width_px: u32,
+    /// Height of the image in pixels
+    pub height_px: u32,
+    /// Width of the chart in CSS pixels, before scaling
+    pub css_width: f64,
+    /// Height of the chart in CSS pixels, before scaling
+    pub css_height: f64,
+    pub scale: f32,
+    pub quality: u8,
+    /// Hash of the spec and options that the image was converted from, which is equal for
+    /// equal requests with a given version of vl-convert. See
+    /// [`crate::request_hash::request_hash`]. None for images converted from SVG
+    pub request_hash: Option<String>,
+    /// Render warnings of the conversion, like the images that a lenient conversion left out
+    pub warnings: Vec<RenderWarning>,
+}
+
+/// A PDF document, with the render warnings of the conversion
+#[derive(Debug, Clone, PartialEq)]
+pub struct PdfResult {
+    pub data: Vec<u8>,
+    /// Render warnings of the conversion, like the images that a lenient conversion left out
+    pub warnings: Vec<RenderWarning>,
+}
+
+/// An image format that can be produced by [`VlConverter::vegalite_export`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ExportFormat {
+    Svg,
+    Png,
+    Jpeg,
+    Pdf,
+}
+
+impl ExportFormat {
+    /// File extension for the format, without the leading dot
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Svg => "svg",
+            ExportFormat::Png => "png",
+            ExportFormat::Jpeg => "jpeg",
+            ExportFormat::Pdf => "pdf",
+        }
+    }
+}
+
+impl Display for ExportFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Display::fmt(self.extension(), f)
+    }
+}
+
+impl FromStr for ExportFormat {
+    type Err = AnyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.trim().to_ascii_lowercase().as_str() {
+            "svg" => Self::Svg,
+            "png" => Self::Png,
+            "jpeg" | "jpg" => Self::Jpeg,
+            "pdf" => Self::Pdf,
+            _ => return Err(anyhow!("Unsupported export format: {}", s)),
+        })
+    }
+}
+
+/// The formats to produce from a single conversion with [`VlConverter::vegalite_export`].
+/// Raster formats that are None aren't rasterized.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ExportRequest {
+    pub svg: bool,
+    pub png: Option<PngExportOpts>,
+    pub jpeg: Option<JpegExportOpts>,
+    pub pdf: bool,
+    /// Whether to return the rows of the chart's top-level datasets after it's rendered
+    pub export_data: bool,
+}
+
+impl ExportRequest {
+    /// Request the given formats with default options for each format
+    pub fn from_formats(formats: &[ExportFormat]) -> Self {
+        let mut request = Self::default();
+        for format in formats {
+            match format {
+                ExportFormat::Svg => request.svg = true,
+                ExportFormat::Png => request.png = Some(Default::default()),
+                ExportFormat::Jpeg => request.jpeg = Some(Default::default()),
+                ExportFormat::Pdf => request.pdf = true,
+            }
+        }
+        request
+    }
+}
+
+/// PNG options for [`ExportRequest`]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PngExportOpts {
+    pub scale: Option<f32>,
+    pub ppi: Option<f32>,
+    pub png_opts: PngOpts,
+}
+
+/// JPEG options for [`ExportRequest`]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JpegExportOpts {
+    pub scale: Option<f32>,
+    pub quality: Option<u8>,
+    pub jpeg_opts: JpegOpts,
+}
+
+/// Image options for [`VlConverter::vegalite_to_file`]. Options that don't apply to the
+/// format of the file are ignored
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ImageFileOpts {
+    pub scale: Option<f32>,
+    pub ppi: Option<f32>,
+    pub quality: Option<u8>,
+    pub png_opts: PngOpts,
+    pub jpeg_opts: JpegOpts,
+    pub pdf_opts: PdfOpts,
+}
+
+/// The images produced by [`VlConverter::vegalite_export`]. Formats that weren't requested
+/// are None.
+#[derive(Debug, Clone, Default)]
+pub struct ExportResult {
+    pub svg: Option<String>,
+    pub png: Option<Vec<u8>>,
+    pub jpeg: Option<Vec<u8>>,
+    pub pdf: Option<Vec<u8>>,
+    /// Rows of the chart's top-level datasets, when `export_data` was requested
+    pub datasets: Option<Vec<ExportedDataset>>,
+    /// Render warnings of the conversion, like the images that a lenient conversion left out
+    pub warnings: Vec<RenderWarning>,
+}
+
+// Adam7 passes as (x_start, y_start, x_step, y_step)
+const ADAM7_PASSES: [(u32, u32, u32, u32); 7] = [
+    (0, 0, 8, 8),
+    (4, 0, 8, 8),
+    (0, 4, 4, 8),
+    (2, 0, 4, 4),
+    (0, 2, 2, 4),
+    (1, 0, 2, 2),
+    (0, 1, 1, 2),
+];
+
+// Modified from tiny-skia-0.10.0/src/pixmap.rs to include DPI
+pub fn encode_png(pixmap: Pixmap, ppi: f32) -> Result<Vec<u8>, AnyError> {
+    encode_png_with_opts(pixmap, ppi, &PngOpts::default())
+}
 
-        // Send request
-        match self.sender.send(cmd).await {
-            Ok(_) => {
-                // All good
+pub fn encode_png_with_opts(
+    pixmap: Pixmap,
+    ppi: f32,
+    png_opts: &PngOpts,
+) -> Result<Vec<u8>, AnyError> {
+    let mut data = Vec::new();
+    encode_png_into(pixmap, ppi, png_opts, &mut data)?;
+    Ok(data)
+}
+
+/// Encode a pixmap as PNG, writing the result to the provided writer
+pub fn encode_png_into<W: Write>(
+    pixmap: Pixmap,
+    ppi: f32,
+    png_opts: &PngOpts,
+    writer: W,
+) -> Result<(), AnyError> {
+    let mut pixmap = pixmap;
+    if let Some(background) = &png_opts.flatten_background {
+        background.flatten(&mut pixmap);
+    }
+    let width = pixmap.width();
+    let height = pixmap.height();
+
+    let mut info = png::Info::with_size(width, height);
+    info.color_type = png::ColorType::Rgba;
+    info.bit_depth = png::BitDepth::Eight;
+    info.interlaced = png_opts.interlaced;
+    let ppm = (ppi.max(0.0) / 0.0254).round() as u32;
+    info.pixel_dims = Some(PixelDimensions {
+        xppu: ppm,
+        yppu: ppm,
+        unit: Unit::Meter,
+    });
+
+    let mut encoder = png::Encoder::with_info(writer, info)?;
+    if png_opts.interlaced {
+        // Interlaced image data is written as raw IDAT chunks below, so the encoder
+        // doesn't know that an image was written
+        encoder.validate_sequence(false);
+    }
+    let mut writer = encoder.write_header()?;
+
+    let row_len = width as usize * 4;
+    let chunk_rows = png_opts.chunk_rows.map(|rows| rows.max(1) as usize);
+
+    if png_opts.interlaced {
+        demultiply_pixels(pixmap.pixels_mut());
+        let mut zlib = ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+        let mut row = Vec::with_capacity(row_len + 1);
+        // Number of scanlines written to the zlib stream since its output was last flushed
+        let mut pending_rows = 0;
+        for (x_start, y_start, x_step, y_step) in ADAM7_PASSES {
+            if x_start >= width || y_start >= height {
+                // Empty pass, no scanlines are written
+                continue;
+            }
+            for y in (y_start..height).step_by(y_step as usize) {
+                row.clear();
+                // Filter type None
+                row.push(0);
+                let line = &pixmap.data()[y as usize * row_len..(y as usize + 1) * row_len];
+                for x in (x_start..width).step_by(x_step as usize) {
+                    row.extend_from_slice(&line[x as usize * 4..x as usize * 4 + 4]);
+                }
+                zlib.write_all(&row)?;
+                pending_rows += 1;
+                if Some(pending_rows) == chunk_rows {
+                    write_idat_chunk(&mut writer, &mut zlib)?;
+                    pending_rows = 0;
+                }
             }
-            Err(err) => {
-                bail!("Failed to send get_themes request: {}", err.to_string())
+        }
+        if chunk_rows.is_some() && pending_rows > 0 {
+            write_idat_chunk(&mut writer, &mut zlib)?;
+        }
+        let compressed = zlib.finish()?;
+        if !compressed.is_empty() {
+            writer.write_chunk(png::chunk::IDAT, &compressed)?;
+        }
+        writer.finish()?;
+    } else if let Some(chunk_rows) = chunk_rows {
+        // Demultiply and stream chunks of rows, flushing each chunk to the output writer as
+        // an IDAT chunk, so that the compressed image never needs to be held in memory all at
+        // once. The buffer fits a chunk of rows even when they don't compress
+        let buffer_size = chunk_rows * (row_len + 1) + 1024;
+        let mut stream = writer.stream_writer_with_size(buffer_size)?;
+        for start in (0..height as usize).step_by(chunk_rows) {
+            let end = (start + chunk_rows).min(height as usize);
+            demultiply_pixels(
+                &mut pixmap.pixels_mut()[start * width as usize..end * width as usize],
+            );
+            stream.write_all(&pixmap.data()[start * row_len..end * row_len])?;
+            if end < height as usize {
+                // The last chunk of rows is flushed when the stream is finished
+                stream.flush()?;
             }
         }
-
-        // Wait for result
-        match resp_rx.await {
-            Ok(themes_result) => themes_result,
-            Err(err) => bail!("Failed to retrieve get_themes result: {}", err.to_string()),
-        }
+        stream.finish()?;
+    } else {
+        demultiply_pixels(pixmap.pixels_mut());
+        writer.write_image_data(pixmap.data())?;
+        writer.finish()?;
     }
-}
 
-impl Default for VlConverter {
-    fn default() -> Self {
-        Self::new()
-    }
+    Ok(())
 }
 
-// Modified from tiny-skia-0.10.0/src/pixmap.rs to include DPI
-pub fn encode_png(pixmap: Pixmap, ppi: f32) -> Result<Vec<u8>, AnyError> {
-    let mut pixmap = pixmap;
+/// Flush the scanlines written to a zlib stream and write their compressed data as an IDAT
+/// chunk, so that no more than a chunk of rows is held in memory
+fn write_idat_chunk<W: Write>(
+    writer: &mut png::Writer<W>,
+    zlib: &mut ZlibEncoder<Vec<u8>>,
+) -> Result<(), AnyError> {
+    zlib.flush()?;
+    let compressed = std::mem::take(zlib.get_mut());
+    writer.write_chunk(png::chunk::IDAT, &compressed)?;
+    Ok(())
+}
 
-    // Demultiply alpha.
-    //
+/// Demultiply alpha of pixels in place
+fn demultiply_pixels(pixels: &mut [PremultipliedColorU8]) {
     // RasterPipeline is 15% faster here, but produces slightly different results
     // due to rounding. So we stick with this method for now.
-    for pixel in pixmap.pixels_mut() {
+    for pixel in pixels {
         let c = pixel.demultiply();
         let alpha = c.alpha();
 
@@ -1478,94 +6327,553 @@ pub fn encode_png(pixmap: Pixmap, ppi: f32) -> Result<Vec<u8>, AnyError> {
         )
         .expect("Failed to construct PremultipliedColorU8 from rgba");
     }
+}
 
-    let mut data = Vec::new();
-    {
-        let mut encoder = png::Encoder::new(&mut data, pixmap.width(), pixmap.height());
-        encoder.set_color(png::ColorType::Rgba);
-        encoder.set_depth(png::BitDepth::Eight);
-        let ppm = (ppi.max(0.0) / 0.0254).round() as u32;
-        encoder.set_pixel_dims(Some(PixelDimensions {
-            xppu: ppm,
-            yppu: ppm,
-            unit: Unit::Meter,
-        }));
-
-        let mut writer = encoder.write_header()?;
-        writer.write_image_data(pixmap.data())?;
+/// A visible text item in a rendered chart
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct TextItem {
+    pub text: String,
+    /// Absolute x position of the text anchor, in pixels
+    pub x: f64,
+    /// Absolute y position of the text anchor, in pixels
+    pub y: f64,
+    /// One of "axis-label", "axis-title", "legend-label", "legend-title", "title",
+    /// "subtitle", or "mark"
+    pub role: String,
+    pub font: Option<String>,
+    pub size: Option<f64>,
+}
+
+/// Counts of the marks and items in the scenegraph of a rendered chart, for harnesses that
+/// check that a chart renders what it should without comparing images
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct RenderStats {
+    /// Number of group items, including the root group
+    pub group_count: u64,
+    /// Number of items of each mark type, like "rect" or "symbol"
+    pub item_counts_by_marktype: HashMap<String, u64>,
+    /// Number of text items with text that isn't empty
+    pub text_item_count: u64,
+    /// Number of marks and items in the scenegraph
+    pub total_scenegraph_nodes: u64,
+}
+
+/// Collect the visible text items from the result of a scenegraph conversion, in rendering order
+pub fn scenegraph_text_items(sg: &serde_json::Value) -> Result<Vec<TextItem>, AnyError> {
+    let Some(root) = sg.get("scenegraph") else {
+        bail!("Scenegraph result has no scenegraph property")
+    };
+    let origin_x = sg
+        .pointer("/origin/0")
+        .and_then(|v| v.as_f64())
+        .unwrap_or(0.0);
+    let origin_y = sg
+        .pointer("/origin/1")
+        .and_then(|v| v.as_f64())
+        .unwrap_or(0.0);
+
+    let mut text_items: Vec<TextItem> = Vec::new();
+    collect_text_items(root, origin_x, origin_y, &mut text_items);
+    Ok(text_items)
+}
+
+fn collect_text_items(mark: &serde_json::Value, x: f64, y: f64, text_items: &mut Vec<TextItem>) {
+    let Some(items) = mark.get("items").and_then(|items| items.as_array()) else {
+        return;
+    };
+    let number = |item: &serde_json::Value, key: &str| item.get(key).and_then(|v| v.as_f64());
+
+    match mark.get("marktype").and_then(|v| v.as_str()) {
+        Some("group") => {
+            // Group items offset the marks they contain
+            for item in items {
+                let item_x = x + number(item, "x").unwrap_or(0.0);
+                let item_y = y + number(item, "y").unwrap_or(0.0);
+                if let Some(marks) = item.get("items").and_then(|marks| marks.as_array()) {
+                    for child in marks {
+                        collect_text_items(child, item_x, item_y, text_items);
+                    }
+                }
+            }
+        }
+        Some("text") => {
+            let role = match mark.get("role").and_then(|v| v.as_str()) {
+                Some(role @ ("axis-label" | "axis-title" | "legend-label" | "legend-title")) => {
+                    role
+                }
+                Some("title-text") => "title",
+                Some("title-subtitle") => "subtitle",
+                _ => "mark",
+            };
+            for item in items {
+                // Labels hidden by overlap removal have zero opacity
+                let hidden = ["opacity", "fillOpacity"]
+                    .iter()
+                    .any(|key| number(item, key) == Some(0.0));
+                let text = match item.get("text") {
+                    Some(serde_json::Value::String(text)) => text.clone(),
+                    Some(serde_json::Value::Array(lines)) => lines
+                        .iter()
+                        .map(|line| match line {
+                            serde_json::Value::String(line) => line.clone(),
+                            line => line.to_string(),
+                        })
+                        .collect::<Vec<_>>()
+                        .join("\n"),
+                    Some(serde_json::Value::Null) | None => String::new(),
+                    Some(text) => text.to_string(),
+                };
+                if hidden || text.is_empty() {
+                    continue;
+                }
+                text_items.push(TextItem {
+                    text,
+                    x: x + number(item, "x").unwrap_or(0.0),
+                    y: y + number(item, "y").unwrap_or(0.0),
+                    role: role.to_string(),
+                    font: item
+                        .get("font")
+                        .and_then(|v| v.as_str())
+                        .map(|font| font.to_string()),
+                    size: number(item, "fontSize"),
+                });
+            }
+        }
+        _ => {}
     }
+}
 
-    Ok(data)
+/// Add format locales to a Vega spec's `config.locale`.
+///
+/// Locales that are already defined in the spec's config take precedence, as they do when the
+/// locales are applied during rendering
+fn apply_vega_locale(
+    mut vg_spec: serde_json::Value,
+    format_locale: Option<FormatLocale>,
+    time_format_locale: Option<TimeFormatLocale>,
+) -> Result<serde_json::Value, AnyError> {
+    if format_locale.is_none() && time_format_locale.is_none() {
+        return Ok(vg_spec);
+    }
+    let Some(spec_obj) = vg_spec.as_object_mut() else {
+        bail!("Vega spec must be an object");
+    };
+    let config = spec_obj
+        .entry("config")
+        .or_insert_with(|| serde_json::Value::Object(Default::default()));
+    let Some(config_obj) = config.as_object_mut() else {
+        bail!("Vega spec config must be an object");
+    };
+    let locale = config_obj
+        .entry("locale")
+        .or_insert_with(|| serde_json::Value::Object(Default::default()));
+    let Some(locale_obj) = locale.as_object_mut() else {
+        bail!("Vega spec config.locale must be an object");
+    };
+    if let Some(format_locale) = format_locale {
+        if !locale_obj.contains_key("number") {
+            locale_obj.insert("number".to_string(), format_locale.as_object()?);
+        }
+    }
+    if let Some(time_format_locale) = time_format_locale {
+        if !locale_obj.contains_key("time") {
+            locale_obj.insert("time".to_string(), time_format_locale.as_object()?);
+        }
+    }
+    Ok(vg_spec)
 }
 
 pub fn svg_to_png(svg: &str, scale: f32, ppi: Option<f32>) -> Result<Vec<u8>, AnyError> {
+    svg_to_png_with_opts(svg, scale, ppi, &PngOpts::default())
+}
+
+pub fn svg_to_png_with_opts(
+    svg: &str,
+    scale: f32,
+    ppi: Option<f32>,
+    png_opts: &PngOpts,
+) -> Result<Vec<u8>, AnyError> {
+    Ok(svg_to_png_info(svg, scale, ppi, png_opts)?.data)
+}
+
+/// Convert an SVG image to PNG, returning the PNG image along with the size of the pixmap
+/// that it was encoded from and the size of the SVG image
+pub fn svg_to_png_info(
+    svg: &str,
+    scale: f32,
+    ppi: Option<f32>,
+    png_opts: &PngOpts,
+) -> Result<PngResult, AnyError> {
+    let mut data = Vec::new();
+    let png = svg_to_png_into(svg, scale, ppi, png_opts, &mut data)?;
+    Ok(PngResult { data, ..png })
+}
+
+/// Convert an SVG image to PNG, encoding the PNG image straight to the provided writer
+/// (e.g. a file) instead of holding it in memory. The `data` of the returned result is empty
+pub fn svg_to_png_into<W: Write>(
+    svg: &str,
+    scale: f32,
+    ppi: Option<f32>,
+    png_opts: &PngOpts,
+    writer: W,
+) -> Result<PngResult, AnyError> {
     // default ppi to 72
     let ppi = ppi.unwrap_or(72.0);
-    let scale = scale * ppi / 72.0;
+    let (pixmap, size) = render_svg_pixmap(svg, scale, ppi, png_opts)?;
+    let (width, height) = (pixmap.width(), pixmap.height());
+    encode_png_into(pixmap, ppi, png_opts, writer)?;
+    Ok(PngResult {
+        data: Vec::new(),
+        width_px: width,
+        height_px: height,
+        css_width: size.width() as f64,
+        css_height: size.height() as f64,
+        scale,
+        ppi,
+        request_hash: None,
+        warnings: Vec::new(),
+    })
+}
+
+/// Rasterize an SVG image at the given scale and ppi, as it's rasterized for PNG images
+pub fn svg_to_pixmap(
+    svg: &str,
+    scale: f32,
+    ppi: Option<f32>,
+    png_opts: &PngOpts,
+) -> Result<Pixmap, AnyError> {
+    let (pixmap, _) = render_svg_pixmap(svg, scale, ppi.unwrap_or(72.0), png_opts)?;
+    Ok(pixmap)
+}
+
+/// Number of pixels that `css_size` CSS pixels of an SVG image are rasterized to at
+/// `pixel_scale`. Fractional sizes are rounded up, so that an image at scale 1.5 of an SVG
+/// image 421.6 pixels wide is 633 pixels wide, and the image is stretched to fill the last
+/// pixel. Sizes within float error of a whole number (e.g. 100 × 1.1) are rounded to it
+fn raster_size(css_size: f32, pixel_scale: f32) -> u32 {
+    let size = css_size as f64 * pixel_scale as f64;
+    let rounded = size.round();
+    if (size - rounded).abs() <= 1e-5 * size.max(1.0) {
+        rounded as u32
+    } else {
+        size.ceil() as u32
+    }
+}
+
+/// Rasterize an SVG image, returning the pixmap along with the size of the SVG image
+fn render_svg_pixmap(
+    svg: &str,
+    scale: f32,
+    ppi: f32,
+    png_opts: &PngOpts,
+) -> Result<(Pixmap, usvg::Size), AnyError> {
+    if !scale.is_finite() || scale <= 0.0 {
+        bail!("Image scale must be a finite number greater than 0. Received: {scale}");
+    }
+    if !ppi.is_finite() || ppi <= 0.0 {
+        bail!("Image ppi must be a finite number greater than 0. Received: {ppi}");
+    }
+    let pixel_scale = match png_opts.ppi_mode {
+        PpiMode::ResizePixels => scale * ppi / 72.0,
+        PpiMode::MetadataOnly => scale,
+    };
 
     // catch_unwind so that we don't poison Mutexes
     // if usvg/resvg panics
     let response = panic::catch_unwind(|| {
-        let rtree = match parse_svg(svg) {
+        // Keep the dashes of dashed lines from blending into solid lines at small scales
+        let svg = clamp_stroke_dasharrays(svg, pixel_scale);
+        let rtree = match parse_svg_with_opts(&svg, png_opts) {
             Ok(rtree) => rtree,
             Err(err) => return Err(err),
         };
 
-        let mut pixmap = tiny_skia::Pixmap::new(
-            (rtree.size().width() * scale) as u32,
-            (rtree.size().height() * scale) as u32,
-        )
-        .unwrap();
+        let width = raster_size(rtree.size().width(), pixel_scale);
+        let height = raster_size(rtree.size().height(), pixel_scale);
+        if width == 0 || height == 0 {
+            bail!(
+                "Image scale {scale} with ppi {ppi} produces an image of {width}x{height} pixels. \
+                The image must be at least 1x1 pixels"
+            );
+        }
+        let Some(mut pixmap) = tiny_skia::Pixmap::new(width, height) else {
+            bail!("Failed to allocate an image of {width}x{height} pixels");
+        };
 
-        let transform = tiny_skia::Transform::from_scale(scale, scale);
+        // Stretch the image to the rounded size, so that its last row and column of pixels
+        // aren't left partly blank
+        let transform = tiny_skia::Transform::from_scale(
+            width as f32 / rtree.size().width(),
+            height as f32 / rtree.size().height(),
+        );
         render(&rtree, transform, &mut pixmap.as_mut());
-        Ok(encode_png(pixmap, ppi))
+        Ok((pixmap, rtree.size()))
     });
     match response {
-        Ok(Ok(Ok(png_result))) => Ok(png_result),
-        Ok(Err(err)) => Err(err),
-        err => bail!("{err:?}"),
+        Ok(result) => result,
+        Err(err) => bail!("{err:?}"),
     }
 }
 
+/// Convert an SVG image to JPEG with the given quality, between 1 (worst) and 100 (best).
+///
+/// Quality values outside of this range are an error rather than being clamped, since a
+/// quality of 0 produces files that some decoders reject.
 pub fn svg_to_jpeg(svg: &str, scale: f32, quality: Option<u8>) -> Result<Vec<u8>, AnyError> {
-    let png_bytes = svg_to_png(svg, scale, None)?;
-    let img = ImageReader::new(Cursor::new(png_bytes))
-        .with_guessed_format()?
-        .decode()?;
+    svg_to_jpeg_with_opts(svg, scale, quality, &JpegOpts::default())
+}
 
+/// Convert an SVG image to JPEG, compositing it over the background color of `jpeg_opts`
+pub fn svg_to_jpeg_with_opts(
+    svg: &str,
+    scale: f32,
+    quality: Option<u8>,
+    jpeg_opts: &JpegOpts,
+) -> Result<Vec<u8>, AnyError> {
+    Ok(svg_to_jpeg_info(svg, scale, quality, jpeg_opts)?.data)
+}
+
+/// Convert an SVG image to JPEG, returning the JPEG image along with the size of the pixmap
+/// that it was encoded from and the size of the SVG image
+pub fn svg_to_jpeg_info(
+    svg: &str,
+    scale: f32,
+    quality: Option<u8>,
+    jpeg_opts: &JpegOpts,
+) -> Result<JpegResult, AnyError> {
     let quality = quality.unwrap_or(90);
-    if quality > 100 {
-        bail!("JPEG quality parameter must be between 0 and 100 inclusive. Received: {quality}");
-    }
+    check_jpeg_quality(quality)?;
+    let (pixmap, size) = render_svg_pixmap(svg, scale, 72.0, &PngOpts::default())?;
+    let (width, height) = (pixmap.width(), pixmap.height());
+    Ok(JpegResult {
+        data: encode_jpeg(pixmap, Some(quality), jpeg_opts)?,
+        width_px: width,
+        height_px: height,
+        css_width: size.width() as f64,
+        css_height: size.height() as f64,
+        scale,
+        quality,
+        request_hash: None,
+        warnings: Vec::new(),
+    })
+}
 
+/// Encode a pixmap as JPEG, compositing it over the background color of `jpeg_opts`
+pub fn encode_jpeg(
+    pixmap: Pixmap,
+    quality: Option<u8>,
+    jpeg_opts: &JpegOpts,
+) -> Result<Vec<u8>, AnyError> {
+    let quality = quality.unwrap_or(90);
+    check_jpeg_quality(quality)?;
+    let mut pixmap = pixmap;
+    BackgroundColor {
+        alpha: 255,
+        ..jpeg_opts.background
+    }
+    .flatten(&mut pixmap);
+
+    // Pixels are opaque once they're flattened, so their premultiplied colors are their colors
+    let (width, height) = (pixmap.width(), pixmap.height());
+    let pixels = pixmap
+        .pixels()
+        .iter()
+        .flat_map(|pixel| [pixel.red(), pixel.green(), pixel.blue()])
+        .collect();
+    let Some(img) = RgbImage::from_raw(width, height, pixels) else {
+        bail!("Failed to read an image of {width}x{height} pixels");
+    };
     let mut jpeg_bytes: Vec<u8> = Vec::new();
-    let mut encoder = JpegEncoder::new_with_quality(&mut jpeg_bytes, quality);
+    JpegEncoder::new_with_quality(&mut jpeg_bytes, quality).encode_image(&img)?;
+    Ok(jpeg_bytes)
+}
+
+fn check_jpeg_quality(quality: u8) -> Result<(), AnyError> {
+    if !(1..=100).contains(&quality) {
+        bail!("JPEG quality parameter must be between 1 and 100 inclusive. Received: {quality}");
+    }
+    Ok(())
+}
 
-    // Encode the image
-    encoder.encode_image(&img)?;
+/// Read the size and viewBox of an SVG image from the attributes of its svg element, without
+/// rendering it
+pub fn svg_info(svg: String) -> Result<SvgResult, AnyError> {
+    let (width, height, view_box) = svg_size(&svg)?;
+    Ok(SvgResult {
+        data: svg,
+        width,
+        height,
+        view_box,
+        request_hash: None,
+        warnings: Vec::new(),
+    })
+}
 
-    Ok(jpeg_bytes)
+/// The width, height, and viewBox of the svg element of an SVG image
+fn svg_size(svg: &str) -> Result<(f64, f64, Option<[f64; 4]>), AnyError> {
+    let xml_opt = usvg::roxmltree::ParsingOptions {
+        allow_dtd: true,
+        ..Default::default()
+    };
+    let doc = usvg::roxmltree::Document::parse_with_options(svg, xml_opt)?;
+    let root = doc.root_element();
+    let view_box = match root.attribute("viewBox") {
+        Some(view_box) => {
+            let numbers = view_box
+                .split(|c: char| c.is_whitespace() || c == ',')
+                .filter(|number| !number.is_empty())
+                .map(|number| number.parse::<f64>())
+                .collect::<Result<Vec<_>, _>>()
+                .ok()
+                .filter(|numbers| numbers.len() == 4);
+            let Some(numbers) = numbers else {
+                bail!("Invalid viewBox of SVG image: {view_box:?}");
+            };
+            Some([numbers[0], numbers[1], numbers[2], numbers[3]])
+        }
+        None => None,
+    };
+
+    // A missing width or height is the size of the viewBox, as in browsers
+    let length = |name: &str, index: usize| -> Result<f64, AnyError> {
+        match root.attribute(name) {
+            Some(value) => value
+                .trim()
+                .trim_end_matches("px")
+                .parse::<f64>()
+                .map_err(|_| anyhow!("Invalid {name} of SVG image: {value:?}")),
+            None => match view_box {
+                Some(view_box) => Ok(view_box[index]),
+                None => bail!("SVG image has neither a {name} nor a viewBox"),
+            },
+        }
+    };
+    Ok((length("width", 2)?, length("height", 3)?, view_box))
+}
+
+/// Check that a rendered chart is at least 1x1 pixels, enlarging it to `min_size` when that's
+/// set. Degenerate charts, like those with empty data that are sized to fit it, can resolve to
+/// a size of zero or less, which can't be rasterized
+fn check_chart_size(svg: String, min_size: Option<(u32, u32)>) -> Result<String, AnyError> {
+    let (width, height, view_box) = svg_size(&svg)?;
+    if width >= 1.0 && height >= 1.0 {
+        return Ok(svg);
+    }
+    let Some((min_width, min_height)) = min_size else {
+        bail!("Chart resolved to {width}×{height}; check data and width/height settings")
+    };
+    if min_width == 0 || min_height == 0 {
+        bail!("min_size must be at least 1x1 pixels. Received: {min_width}x{min_height}")
+    }
+    let width = width.max(min_width as f64);
+    let height = height.max(min_height as f64);
+    let [x, y, ..] = view_box.unwrap_or_default();
+
+    // Replace the size attributes of the start tag of the svg element
+    let tag_start = svg
+        .find("<svg")
+        .ok_or_else(|| anyhow!("Failed to locate the svg element"))?;
+    let tag_end = tag_start
+        + svg[tag_start..]
+            .find('>')
+            .ok_or_else(|| anyhow!("Failed to locate the svg element"))?;
+    let tag =
+        SVG_SIZE_ATTR_RE.replace_all(&svg[tag_start..tag_end], |caps: &Captures| match &caps[1] {
+            "width" => format!(" width=\"{width}\""),
+            "height" => format!(" height=\"{height}\""),
+            _ => format!(" viewBox=\"{x} {y} {width} {height}\""),
+        });
+    Ok(format!("{}{}{}", &svg[..tag_start], tag, &svg[tag_end..]))
+}
+
+/// Evaluate `f`, which rasterizes `svg`, with the conversion's options for loading the images
+/// that the SVG references. The warnings for the images that a lenient conversion left out are
+/// returned along with the result of `f`
+fn with_image_opts<T>(
+    fetch_retry: Option<FetchRetry>,
+    lenient: bool,
+    svg: &str,
+    f: impl FnOnce() -> T,
+) -> (T, Vec<RenderWarning>) {
+    with_lenient_images(lenient, svg, || with_fetch_retry(fetch_retry, f))
+}
+
+/// Size of the RGBA pixmap that a PNG image was encoded from
+fn png_pixmap_bytes(png: &[u8]) -> Result<u64, AnyError> {
+    let reader = png::Decoder::new(Cursor::new(png)).read_info()?;
+    let info = reader.info();
+    Ok(info.width as u64 * info.height as u64 * 4)
+}
+
+/// Size of the RGBA pixmap that a JPEG image was encoded from
+fn jpeg_pixmap_bytes(jpeg: &[u8]) -> Result<u64, AnyError> {
+    let (width, height) =
+        ImageReader::with_format(Cursor::new(jpeg), ImageFormat::Jpeg).into_dimensions()?;
+    Ok(width as u64 * height as u64 * 4)
+}
+
+/// Sort the keys of the objects in a JSON value, recursively. Objects keep the order that
+/// JavaScript created their keys in otherwise
+fn sort_json_keys(value: serde_json::Value) -> serde_json::Value {
+    match value {
+        serde_json::Value::Object(obj) => {
+            let mut entries: Vec<_> = obj.into_iter().collect();
+            entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+            entries
+                .into_iter()
+                .map(|(key, value)| (key, sort_json_keys(value)))
+                .collect()
+        }
+        serde_json::Value::Array(values) => values.into_iter().map(sort_json_keys).collect(),
+        value => value,
+    }
 }
 
 pub fn svg_to_pdf(svg: &str) -> Result<Vec<u8>, AnyError> {
+    svg_to_pdf_with_opts(svg, &Default::default())
+}
+
+pub fn svg_to_pdf_with_opts(svg: &str, pdf_opts: &PdfOpts) -> Result<Vec<u8>, AnyError> {
+    let dpi = pdf_opts.source_dpi;
+    if !dpi.is_finite() || dpi <= 0.0 {
+        bail!("PDF source dpi must be a finite number greater than 0. Received: {dpi}");
+    }
     let tree = parse_svg(svg)?;
-    let pdf = svg2pdf::to_pdf(&tree, ConversionOptions::default(), PageOptions::default());
+    if pdf_opts.pdfa {
+        return svg_tree_to_pdfa(&tree, dpi);
+    }
+    // svg2pdf embeds text with the outlines of its fonts, which the glyphs of color fonts
+    // like emoji fonts don't have, so text with color glyphs is drawn as paths and images
+    let options = ConversionOptions {
+        embed_text: !has_color_glyphs(&tree),
+        ..Default::default()
+    };
+    let pdf = svg2pdf::to_pdf(&tree, options, PageOptions { dpi });
     pdf.map_err(|err| anyhow!("Failed to convert SVG to PDF: {}", err))
 }
 
 /// Helper to parse svg string to usvg Tree with more helpful error messages
 fn parse_svg(svg: &str) -> Result<usvg::Tree, AnyError> {
+    parse_svg_with_opts(svg, &PngOpts::default())
+}
+
+/// Parse an SVG image like [`parse_svg`], for rasterizing with `png_opts`. Unless
+/// `png_opts.antialias` is set, shapes without a shape-rendering attribute of their own are
+/// parsed with crispEdges rendering, which resvg draws without antialiasing. Images without
+/// an image-rendering attribute of their own are parsed with `png_opts.image_rendering`
+fn parse_svg_with_opts(svg: &str, png_opts: &PngOpts) -> Result<usvg::Tree, AnyError> {
     let xml_opt = usvg::roxmltree::ParsingOptions {
         allow_dtd: true,
         ..Default::default()
     };
 
-    let opts = USVG_OPTIONS
+    let mut opts = USVG_OPTIONS
         .lock()
         .map_err(|err| anyhow!("Failed to acquire usvg options lock: {}", err.to_string()))?;
 
-    let doc = usvg::roxmltree::Document::parse_with_options(svg, xml_opt)?;
+    let svg = normalize_image_rendering(svg);
+    let doc = usvg::roxmltree::Document::parse_with_options(&svg, xml_opt)?;
 
     match doc.root_element().tag_name().namespace() {
         Some("http://www.w3.org/2000/svg") => {
@@ -1586,7 +6894,29 @@ fn parse_svg(svg: &str) -> Result<usvg::Tree, AnyError> {
         }
     }
 
-    Ok(usvg::Tree::from_xmltree(&doc, &opts)?)
+    let shape_rendering = opts.shape_rendering;
+    let image_rendering = opts.image_rendering;
+    if !png_opts.antialias {
+        opts.shape_rendering = usvg::ShapeRendering::CrispEdges;
+    }
+    opts.image_rendering = png_opts.image_rendering.to_usvg();
+    let tree = usvg::Tree::from_xmltree(&doc, &opts);
+    opts.shape_rendering = shape_rendering;
+    opts.image_rendering = image_rendering;
+    Ok(tree?)
+}
+
+/// The file:// URL of the directory that contains `path`. Used as the `base_url` of a spec
+/// that was read from `path`, so that relative data urls and image hrefs resolve next to it
+pub fn directory_base_url(path: &Path) -> Result<String, AnyError> {
+    let path = std::fs::canonicalize(path)?;
+    let Some(dir) = path.parent() else {
+        bail!("{} has no parent directory", path.display())
+    };
+    match deno_core::url::Url::from_directory_path(dir) {
+        Ok(url) => Ok(url.to_string()),
+        Err(_) => bail!("Failed to convert {} to a file URL", dir.display()),
+    }
 }
 
 pub fn vegalite_to_url(vl_spec: &serde_json::Value, fullscreen: bool) -> Result<String, AnyError> {
@@ -1615,6 +6945,117 @@ pub fn vega_to_url(vg_spec: &serde_json::Value, fullscreen: bool) -> Result<Stri
     ))
 }
 
+/// Default timeout of the request to a URL shortening service
+pub const DEFAULT_SHORTEN_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Default field of a URL shortening service's JSON response that holds the short URL
+pub const DEFAULT_SHORTEN_RESPONSE_FIELD: &str = "short_url";
+
+/// Options for shortening Vega editor URLs with a URL shortening service
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShortenOptions {
+    /// URL of the service, which receives a POST request with the JSON body
+    /// `{"url": "<editor URL>"}`
+    pub endpoint: String,
+    /// Value of the request's Authorization header
+    pub auth_header: Option<String>,
+    /// Open the chart in fullscreen mode
+    pub fullscreen: bool,
+    /// Field of the service's JSON response that holds the short URL
+    pub response_field: String,
+    /// Timeout of the request
+    pub timeout: Duration,
+    /// Return the editor URL, with a warning, when shortening fails rather than failing
+    pub fallback: bool,
+}
+
+impl ShortenOptions {
+    /// Options that shorten URLs with the service at `endpoint`. There's no default
+    /// endpoint, so that URLs are only sent over the network when one is given
+    pub fn new(endpoint: impl Into<String>) -> Self {
+        Self {
+            endpoint: endpoint.into(),
+            auth_header: None,
+            fullscreen: false,
+            response_field: DEFAULT_SHORTEN_RESPONSE_FIELD.to_string(),
+            timeout: DEFAULT_SHORTEN_TIMEOUT,
+            fallback: false,
+        }
+    }
+}
+
+/// Convert a Vega-Lite spec to a URL that opens the chart in the Vega editor, shortened with
+/// the service at `opts.endpoint`
+pub async fn vegalite_to_short_url(
+    vl_spec: &serde_json::Value,
+    opts: &ShortenOptions,
+) -> Result<String, AnyError> {
+    let url = vegalite_to_url(vl_spec, opts.fullscreen)?;
+    shorten_url(url, opts).await
+}
+
+/// Convert a Vega spec to a URL that opens the chart in the Vega editor, shortened with the
+/// service at `opts.endpoint`
+pub async fn vega_to_short_url(
+    vg_spec: &serde_json::Value,
+    opts: &ShortenOptions,
+) -> Result<String, AnyError> {
+    let url = vega_to_url(vg_spec, opts.fullscreen)?;
+    shorten_url(url, opts).await
+}
+
+/// Shorten `url` with the service at `opts.endpoint`. `opts.fullscreen` is ignored, as it's
+/// part of the URL already
+pub async fn shorten_url(url: String, opts: &ShortenOptions) -> Result<String, AnyError> {
+    match request_short_url(&url, opts).await {
+        Ok(short_url) => Ok(short_url),
+        Err(err) if opts.fallback => {
+            log::warn!(
+                target: LOG_TARGET,
+                "Failed to shorten the editor URL, returning it unshortened: {err}"
+            );
+            Ok(url)
+        }
+        Err(err) => Err(err),
+    }
+}
+
+async fn request_short_url(url: &str, opts: &ShortenOptions) -> Result<String, AnyError> {
+    let client = reqwest::Client::builder().timeout(opts.timeout).build()?;
+    let mut request = client
+        .post(&opts.endpoint)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .body(serde_json::json!({ "url": url }).to_string());
+    if let Some(auth_header) = &opts.auth_header {
+        request = request.header(reqwest::header::AUTHORIZATION, auth_header);
+    }
+    let response = request
+        .send()
+        .await
+        .map_err(|err| anyhow!("Request to URL shortener {} failed: {err}", opts.endpoint))?;
+    let status = response.status();
+    if !status.is_success() {
+        bail!(
+            "URL shortener {} responded with status {status}",
+            opts.endpoint
+        );
+    }
+    let body: serde_json::Value = serde_json::from_str(&response.text().await?).map_err(|err| {
+        anyhow!(
+            "URL shortener {} responded with invalid JSON: {err}",
+            opts.endpoint
+        )
+    })?;
+    match body.get(&opts.response_field).and_then(|v| v.as_str()) {
+        Some(short_url) => Ok(short_url.to_string()),
+        None => bail!(
+            "Response of URL shortener {} has no {:?} string field",
+            opts.endpoint,
+            opts.response_field
+        ),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1686,6 +7127,388 @@ mod tests {
         println!("vg_spec2: {}", vg_spec2);
     }
 
+    const PNG_TEST_SVG: &str = r##"<svg xmlns="http://www.w3.org/2000/svg" width="37" height="23">
+    <rect x="3" y="2" width="20" height="15" fill="#4c78a8" fill-opacity="0.6"/>
+    <circle cx="25" cy="12" r="8" fill="#f58518"/>
+</svg>"##;
+
+    fn decode_png(png_data: &[u8]) -> (bool, Vec<u8>) {
+        let decoder = png::Decoder::new(png_data);
+        let mut reader = decoder.read_info().unwrap();
+        let interlaced = reader.info().interlaced;
+        let mut buf = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buf).unwrap();
+        buf.truncate(info.buffer_size());
+        (interlaced, buf)
+    }
+
+    #[test]
+    fn test_png_interlaced() {
+        let default_png = svg_to_png(PNG_TEST_SVG, 2.0, None).unwrap();
+        let interlaced_png = svg_to_png_with_opts(
+            PNG_TEST_SVG,
+            2.0,
+            None,
+            &PngOpts {
+                interlaced: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        // Interlace method is the last byte of the IHDR chunk data, which follows the
+        // 8 byte signature and 8 byte chunk length/type header
+        assert_eq!(&interlaced_png[12..16], b"IHDR");
+        assert_eq!(interlaced_png[28], 1);
+        assert_eq!(default_png[28], 0);
+
+        let (default_interlaced, default_pixels) = decode_png(&default_png);
+        let (interlaced, interlaced_pixels) = decode_png(&interlaced_png);
+        assert!(!default_interlaced);
+        assert!(interlaced);
+        assert_eq!(default_pixels, interlaced_pixels);
+    }
+
+    #[test]
+    fn test_png_chunk_rows() {
+        let (_, default_pixels) = decode_png(&svg_to_png(PNG_TEST_SVG, 3.0, None).unwrap());
+        for chunk_rows in [1, 7, 10_000] {
+            for interlaced in [false, true] {
+                let png_data = svg_to_png_with_opts(
+                    PNG_TEST_SVG,
+                    3.0,
+                    None,
+                    &PngOpts {
+                        interlaced,
+                        chunk_rows: Some(chunk_rows),
+                        ..Default::default()
+                    },
+                )
+                .unwrap();
+                let (_, pixels) = decode_png(&png_data);
+                assert_eq!(pixels, default_pixels);
+            }
+        }
+    }
+
+    /// Number of IDAT chunks of a PNG image. Chunks follow the 8 byte signature, and each has
+    /// a 4 byte length, a 4 byte type, its data, and a 4 byte CRC
+    fn idat_chunk_count(png_data: &[u8]) -> usize {
+        let mut count = 0;
+        let mut offset = 8;
+        while offset + 8 <= png_data.len() {
+            let len = u32::from_be_bytes(png_data[offset..offset + 4].try_into().unwrap());
+            if &png_data[offset + 4..offset + 8] == b"IDAT" {
+                count += 1;
+            }
+            offset += len as usize + 12;
+        }
+        count
+    }
+
+    #[test]
+    fn test_png_chunk_rows_idat_chunks() {
+        // The 74x46 pixel image has 46 rows, and 87 rows across the passes of the interlaced
+        // image. Each chunk of rows is flushed as an IDAT chunk, followed by a final IDAT
+        // chunk with the end of the zlib stream
+        for (interlaced, chunk_rows, idat_chunks) in [
+            (false, None, 1),
+            (false, Some(10), 6),
+            (false, Some(46), 2),
+            (true, None, 1),
+            (true, Some(10), 10),
+            (true, Some(87), 2),
+        ] {
+            let mut png_data = Vec::new();
+            svg_to_png_into(
+                PNG_TEST_SVG,
+                2.0,
+                None,
+                &PngOpts {
+                    interlaced,
+                    chunk_rows,
+                    ..Default::default()
+                },
+                &mut png_data,
+            )
+            .unwrap();
+            assert_eq!(
+                idat_chunk_count(&png_data),
+                idat_chunks,
+                "interlaced: {interlaced}, chunk_rows: {chunk_rows:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_png_info() {
+        for (scale, ppi) in [
+            (1.0, None),
+            (2.0, None),
+            (1.5, Some(144.0)),
+            (0.5, Some(96.0)),
+        ] {
+            let png = svg_to_png_info(PNG_TEST_SVG, scale, ppi, &Default::default()).unwrap();
+            let reader = png::Decoder::new(Cursor::new(&png.data))
+                .read_info()
+                .unwrap();
+            assert_eq!(
+                (png.width_px, png.height_px),
+                (reader.info().width, reader.info().height)
+            );
+            assert_eq!((png.css_width, png.css_height), (37.0, 23.0));
+            assert_eq!(png.scale, scale);
+            assert_eq!(png.ppi, ppi.unwrap_or(72.0));
+            assert_eq!(png.data, svg_to_png(PNG_TEST_SVG, scale, ppi).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_png_ppi_mode() {
+        for (ppi_mode, width, height) in [
+            (PpiMode::ResizePixels, 155, 96),
+            (PpiMode::MetadataOnly, 37, 23),
+        ] {
+            let png_opts = PngOpts {
+                ppi_mode,
+                ..Default::default()
+            };
+            let png = svg_to_png_info(PNG_TEST_SVG, 1.0, Some(300.0), &png_opts).unwrap();
+            let reader = png::Decoder::new(Cursor::new(&png.data))
+                .read_info()
+                .unwrap();
+            assert_eq!((reader.info().width, reader.info().height), (width, height));
+            assert_eq!((png.width_px, png.height_px), (width, height));
+            let dims = reader.info().pixel_dims.unwrap();
+            assert_eq!(
+                (dims.xppu, dims.yppu, dims.unit),
+                (11811, 11811, Unit::Meter)
+            );
+        }
+        assert_eq!(
+            PpiMode::from_str("metadata-only").unwrap(),
+            PpiMode::MetadataOnly
+        );
+        assert_eq!(PpiMode::ResizePixels.to_string(), "resize-pixels");
+        assert!(PpiMode::from_str("resize").is_err());
+    }
+
+    #[test]
+    fn test_png_fractional_scale() {
+        for (css_width, css_height) in [(37.0, 23.0), (421.6, 101.3), (17.5, 9.25)] {
+            let svg = format!(
+                r##"<svg xmlns="http://www.w3.org/2000/svg" width="{css_width}" height="{css_height}">
+    <rect width="100%" height="100%" fill="#4c78a8"/>
+</svg>"##
+            );
+            for scale in [1.25, 1.5, 2.5] {
+                let expected = (
+                    (css_width * scale as f64).ceil() as u32,
+                    (css_height * scale as f64).ceil() as u32,
+                );
+                let png = svg_to_png_info(&svg, scale, None, &Default::default()).unwrap();
+                assert_eq!((png.width_px, png.height_px), expected);
+                let jpeg = svg_to_jpeg_info(&svg, scale, None, &JpegOpts::default()).unwrap();
+                assert_eq!((jpeg.width_px, jpeg.height_px), expected);
+
+                // The last row and column are drawn, rather than left transparent
+                let pixmap = svg_to_pixmap(&svg, scale, None, &Default::default()).unwrap();
+                let (width, height) = (pixmap.width(), pixmap.height());
+                let alpha = |x: u32, y: u32| pixmap.pixel(x, y).unwrap().alpha();
+                assert!((0..height).all(|y| alpha(width - 1, y) == 255));
+                assert!((0..width).all(|x| alpha(x, height - 1) == 255));
+            }
+        }
+        // Float error doesn't add a pixel
+        assert_eq!(raster_size(100.0, 1.1), 110);
+        assert_eq!(raster_size(10_000.0, 1.1), 11_000);
+        assert_eq!(raster_size(421.6, 1.5), 633);
+    }
+
+    /// A 4x4 black and white checkerboard, drawn at 64x64
+    fn checkerboard_svg(image_rendering_attr: &str) -> String {
+        format!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" width="64" height="64">
+    <image width="64" height="64" {image_rendering_attr} xlink:href="data:image/png;base64,iVBORw0KGgoAAAANSUhEUgAAAAQAAAAECAIAAAAmkwkpAAAAFklEQVR42mNgYGD4//8/lESwIAC7DABt4hfpR7+BjAAAAABJRU5ErkJggg=="/>
+</svg>"#
+        )
+    }
+
+    /// Red channel of each pixel of a checkerboard image rendered with `image_rendering`
+    fn render_checkerboard(svg: &str, image_rendering: ImageRendering) -> Vec<u8> {
+        let png_opts = PngOpts {
+            image_rendering,
+            ..Default::default()
+        };
+        let png = svg_to_png_with_opts(svg, 1.0, None, &png_opts).unwrap();
+        let (_, pixels) = decode_png(&png);
+        pixels.chunks(4).map(|pixel| pixel[0]).collect()
+    }
+
+    #[test]
+    fn test_png_image_rendering() {
+        // Nearest-neighbor sampling draws each pixel of the checkerboard as a 16x16 square
+        let expected: Vec<u8> = (0..64 * 64)
+            .map(|i| {
+                let (x, y) = (i % 64, i / 64);
+                if (x / 16 + y / 16) % 2 == 0 {
+                    0
+                } else {
+                    255
+                }
+            })
+            .collect();
+        let svg = checkerboard_svg("");
+        assert_eq!(
+            render_checkerboard(&svg, ImageRendering::Pixelated),
+            expected
+        );
+
+        // Smoothing blends the edges of the squares
+        for image_rendering in [ImageRendering::Auto, ImageRendering::Smooth] {
+            let red = render_checkerboard(&svg, image_rendering);
+            assert!(red.iter().any(|v| *v != 0 && *v != 255));
+        }
+
+        // An image-rendering attribute on the image wins over the option
+        for attr in [
+            r#"image-rendering="pixelated""#,
+            r#"style="image-rendering: optimizeSpeed; image-rendering: pixelated;""#,
+        ] {
+            let svg = checkerboard_svg(attr);
+            assert_eq!(render_checkerboard(&svg, ImageRendering::Smooth), expected);
+        }
+        let svg = checkerboard_svg(r#"image-rendering="smooth""#);
+        let red = render_checkerboard(&svg, ImageRendering::Pixelated);
+        assert!(red.iter().any(|v| *v != 0 && *v != 255));
+    }
+
+    #[test]
+    fn test_pdf_source_dpi() {
+        let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" width="960" height="480">
+            <rect x="10" y="10" width="100" height="50" fill="steelblue"/>
+        </svg>"#;
+        let media_box = |source_dpi: f32, pdfa: bool| {
+            let pdf_opts = PdfOpts { pdfa, source_dpi };
+            let pdf = svg_to_pdf_with_opts(svg, &pdf_opts).unwrap();
+            String::from_utf8_lossy(&pdf)
+                .lines()
+                .find_map(|line| line.trim().strip_prefix("/MediaBox ").map(str::to_string))
+                .unwrap()
+        };
+
+        // Pixels are 1/96 of an inch by default, like CSS pixels
+        assert_eq!(media_box(96.0, false), "[0 0 720 360]");
+        assert_eq!(media_box(96.0, true), "[0 0 720 360]");
+        assert_eq!(media_box(72.0, false), "[0 0 960 480]");
+        assert_eq!(media_box(72.0, true), "[0 0 960 480]");
+        assert_eq!(
+            media_box(PdfOpts::default().source_dpi, false),
+            "[0 0 720 360]"
+        );
+
+        let pdf_opts = PdfOpts {
+            source_dpi: 0.0,
+            ..Default::default()
+        };
+        assert!(svg_to_pdf_with_opts(svg, &pdf_opts).is_err());
+    }
+
+    #[test]
+    fn test_jpeg_info() {
+        for scale in [1.0, 2.5] {
+            let jpeg =
+                svg_to_jpeg_info(PNG_TEST_SVG, scale, Some(80), &JpegOpts::default()).unwrap();
+            let (width, height) =
+                ImageReader::with_format(Cursor::new(&jpeg.data), ImageFormat::Jpeg)
+                    .into_dimensions()
+                    .unwrap();
+            assert_eq!((jpeg.width_px, jpeg.height_px), (width, height));
+            assert_eq!((jpeg.css_width, jpeg.css_height), (37.0, 23.0));
+            assert_eq!(jpeg.quality, 80);
+        }
+    }
+
+    #[test]
+    fn test_jpeg_background() {
+        // The top-left corner of the test image is transparent
+        let corner_color = |jpeg_opts: &JpegOpts| {
+            let jpeg = svg_to_jpeg_with_opts(PNG_TEST_SVG, 1.0, Some(100), jpeg_opts).unwrap();
+            let img = image::load_from_memory_with_format(&jpeg, ImageFormat::Jpeg)
+                .unwrap()
+                .to_rgb8();
+            img.get_pixel(0, 0).0
+        };
+        let assert_close = |color: [u8; 3], expected: [u8; 3]| {
+            for (channel, expected) in color.into_iter().zip(expected) {
+                assert!(channel.abs_diff(expected) <= 3, "{color:?} != {expected:?}");
+            }
+        };
+        assert_close(corner_color(&JpegOpts::default()), [255, 255, 255]);
+        let jpeg_opts = JpegOpts {
+            background: BackgroundColor::from_str("#ff0000").unwrap(),
+        };
+        assert_close(corner_color(&jpeg_opts), [255, 0, 0]);
+        // The alpha of the background is ignored
+        let jpeg_opts = JpegOpts {
+            background: BackgroundColor::from_str("rgba(0, 0, 255, 0)").unwrap(),
+        };
+        assert_close(corner_color(&jpeg_opts), [0, 0, 255]);
+    }
+
+    #[test]
+    fn test_png_flatten_background() {
+        let png = svg_to_png(PNG_TEST_SVG, 1.0, None).unwrap();
+        let (_, pixels) = decode_png(&png);
+        assert_eq!(&pixels[..4], &[0, 0, 0, 0]);
+
+        let png_opts = PngOpts {
+            flatten_background: Some(BackgroundColor::from_str("#000").unwrap()),
+            ..Default::default()
+        };
+        let png = svg_to_png_with_opts(PNG_TEST_SVG, 1.0, None, &png_opts).unwrap();
+        let (_, pixels) = decode_png(&png);
+        assert_eq!(&pixels[..4], &[0, 0, 0, 255]);
+        assert!(pixels.chunks(4).all(|pixel| pixel[3] == 255));
+    }
+
+    #[test]
+    fn test_svg_info() {
+        let svg = svg_info(PNG_TEST_SVG.to_string()).unwrap();
+        assert_eq!((svg.width, svg.height, svg.view_box), (37.0, 23.0, None));
+        assert_eq!(svg.data, PNG_TEST_SVG);
+
+        let svg = svg_info(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="200" height="100" viewBox="0 0 400 200"></svg>"#
+                .to_string(),
+        )
+        .unwrap();
+        assert_eq!((svg.width, svg.height), (200.0, 100.0));
+        assert_eq!(svg.view_box, Some([0.0, 0.0, 400.0, 200.0]));
+
+        // Without a width and height, the image is the size of its viewBox
+        let svg = svg_info(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="-5,-5 50 30"></svg>"#.to_string(),
+        )
+        .unwrap();
+        assert_eq!((svg.width, svg.height), (50.0, 30.0));
+        assert_eq!(svg.view_box, Some([-5.0, -5.0, 50.0, 30.0]));
+    }
+
+    #[test]
+    fn test_check_chart_size() {
+        let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" class="marks" width="0" height="0" viewBox="0 0 0 0"></svg>"#;
+        let err = check_chart_size(svg.to_string(), None).unwrap_err();
+        assert!(err.to_string().starts_with("Chart resolved to 0×0"));
+
+        let svg = check_chart_size(svg.to_string(), Some((20, 10))).unwrap();
+        assert_eq!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" class="marks" width="20" height="10" viewBox="0 0 20 10"></svg>"#
+        );
+        assert_eq!(check_chart_size(svg.clone(), None).unwrap(), svg);
+    }
+
     #[test]
     fn test_convert_vegalite_to_url() {
         let vl_spec: serde_json::Value = serde_json::from_str(r#"
@@ -1819,4 +7642,26 @@ mod tests {
         );
         assert_eq!(url, expected);
     }
+
+    #[test]
+    fn test_apply_vega_locale() {
+        let vg_spec = serde_json::json!({
+            "marks": [],
+            "config": {"locale": {"number": {"decimal": ","}}}
+        });
+        let time_locale = serde_json::json!({"dateTime": "%x, %X"});
+        let vg_spec = apply_vega_locale(
+            vg_spec,
+            Some(FormatLocale::Object(serde_json::json!({"decimal": "."}))),
+            Some(TimeFormatLocale::Object(time_locale.clone())),
+        )
+        .unwrap();
+
+        // The spec's own number locale takes precedence
+        assert_eq!(vg_spec["config"]["locale"]["number"]["decimal"], ",");
+        assert_eq!(vg_spec["config"]["locale"]["time"], time_locale);
+
+        let vg_spec = apply_vega_locale(serde_json::json!({"marks": []}), None, None).unwrap();
+        assert_eq!(vg_spec, serde_json::json!({"marks": []}));
+    }
 }
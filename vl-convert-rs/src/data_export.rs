@@ -0,0 +1,187 @@
+use crate::anyhow::anyhow;
+use deno_core::error::AnyError;
+use serde::ser::SerializeMap;
+use serde::{Deserialize, Serialize, Serializer};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// A file format that the datasets of a chart can be exported to
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum DataExportFormat {
+    #[default]
+    Csv,
+    Json,
+}
+
+impl DataExportFormat {
+    /// File extension for the format, without the leading dot
+    pub fn extension(&self) -> &'static str {
+        match self {
+            DataExportFormat::Csv => "csv",
+            DataExportFormat::Json => "json",
+        }
+    }
+}
+
+impl Display for DataExportFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Display::fmt(self.extension(), f)
+    }
+}
+
+impl FromStr for DataExportFormat {
+    type Err = AnyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.trim().to_ascii_lowercase().as_str() {
+            "csv" => Self::Csv,
+            "json" => Self::Json,
+            _ => return Err(anyhow!("Unsupported data export format: {}", s)),
+        })
+    }
+}
+
+/// Rows of a top-level dataset of a chart after it was rendered, exported by
+/// [`crate::VlConverter::vegalite_export`] with the `export_data` option
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportedDataset {
+    pub name: String,
+    /// Fields of the first row in the order that they appear, followed by the fields of
+    /// the other rows in alphabetical order
+    pub fields: Vec<String>,
+    /// Values of each row in the order of `fields`. Dates are ISO 8601 strings, and fields
+    /// that a row doesn't have are null
+    pub rows: Vec<Vec<serde_json::Value>>,
+}
+
+impl ExportedDataset {
+    /// Name of the file that the dataset is written to for a chart written to `basename`
+    pub fn file_name(&self, basename: &str, format: DataExportFormat) -> String {
+        format!("{basename}.{}.{format}", self.name)
+    }
+
+    /// Write the dataset in the given format
+    pub fn to_format(&self, format: DataExportFormat) -> Result<String, AnyError> {
+        match format {
+            DataExportFormat::Csv => Ok(self.to_csv()),
+            DataExportFormat::Json => self.to_json(),
+        }
+    }
+
+    /// Write the dataset as CSV with a header row. Null values are written as empty cells,
+    /// and nested arrays and objects as JSON
+    pub fn to_csv(&self) -> String {
+        let mut csv = csv_line(self.fields.iter().map(|field| csv_cell(field)));
+        for row in &self.rows {
+            csv.push_str(&csv_line(row.iter().map(csv_value)));
+        }
+        csv
+    }
+
+    /// Write the dataset as a JSON array of row objects, with fields in the order of `fields`
+    pub fn to_json(&self) -> Result<String, AnyError> {
+        let rows = self
+            .rows
+            .iter()
+            .map(|values| JsonRow {
+                fields: &self.fields,
+                values,
+            })
+            .collect::<Vec<_>>();
+        Ok(serde_json::to_string_pretty(&rows)?)
+    }
+}
+
+struct JsonRow<'a> {
+    fields: &'a [String],
+    values: &'a [serde_json::Value],
+}
+
+impl Serialize for JsonRow<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.fields.len()))?;
+        for (field, value) in self.fields.iter().zip(self.values) {
+            map.serialize_entry(field, value)?;
+        }
+        map.end()
+    }
+}
+
+fn csv_line(cells: impl Iterator<Item = String>) -> String {
+    let mut line = cells.collect::<Vec<_>>().join(",");
+    line.push('\n');
+    line
+}
+
+fn csv_value(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::Null => String::new(),
+        serde_json::Value::String(value) => csv_cell(value),
+        serde_json::Value::Number(number) => match number.as_f64() {
+            // JavaScript numbers outside the i32 range are floats, even when they're whole
+            Some(float)
+                if number.is_f64() && float.fract() == 0.0 && float.abs() < 2f64.powi(53) =>
+            {
+                (float as i64).to_string()
+            }
+            _ => number.to_string(),
+        },
+        value => csv_cell(&value.to_string()),
+    }
+}
+
+/// Quote a CSV cell if it contains a delimiter, quote, or line break
+fn csv_cell(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn dataset() -> ExportedDataset {
+        ExportedDataset {
+            name: "data_0".to_string(),
+            fields: vec!["a".to_string(), "date".to_string(), "total".to_string()],
+            rows: vec![
+                vec![json!("A, B"), json!("2020-01-05T00:00:00.000Z"), json!(3e9)],
+                vec![json!("say \"C\""), json!(null), json!([1, 2])],
+            ],
+        }
+    }
+
+    #[test]
+    fn test_to_csv() {
+        assert_eq!(
+            dataset().to_csv(),
+            "a,date,total\n\
+             \"A, B\",2020-01-05T00:00:00.000Z,3000000000\n\
+             \"say \"\"C\"\"\",,\"[1,2]\"\n"
+        );
+    }
+
+    #[test]
+    fn test_to_json_keeps_field_order() {
+        let json = dataset().to_json().unwrap();
+        assert!(json.find("\"a\"").unwrap() < json.find("\"date\"").unwrap());
+        assert!(json.find("\"date\"").unwrap() < json.find("\"total\"").unwrap());
+        let rows: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            rows[1],
+            json!({"a": "say \"C\"", "date": null, "total": [1, 2]})
+        );
+    }
+
+    #[test]
+    fn test_file_name() {
+        assert_eq!(
+            dataset().file_name("chart", DataExportFormat::Json),
+            "chart.data_0.json"
+        );
+    }
+}
@@ -0,0 +1,203 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt::{Display, Formatter};
+
+/// Step of a Vega conversion that failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DataflowPhase {
+    /// Parsing the Vega spec and constructing the view
+    Parse,
+    /// Evaluating the operators of the view's dataflow
+    Dataflow,
+}
+
+/// Error of a conversion that failed while Vega parsed a spec or evaluated its dataflow.
+/// Dataflow errors name the operator that failed and, when it can be found, the transform of
+/// the converted spec that the operator runs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DataflowError {
+    pub phase: DataflowPhase,
+    /// Type of the Vega operator that failed (e.g. "Formula")
+    pub operator: Option<String>,
+    pub message: String,
+    /// Location of the transform in the spec (e.g. "layer[0].transform[2]")
+    #[serde(default)]
+    pub spec_path: Option<String>,
+    /// Type of the transform at `spec_path` (e.g. "calculate")
+    #[serde(default)]
+    pub transform: Option<String>,
+    /// Vega transform that the operator runs, used to find the Vega-Lite transform that it
+    /// was compiled from
+    #[serde(default, skip_serializing)]
+    vega_transform: Option<Value>,
+}
+
+impl DataflowError {
+    /// Replace the location of the transform in the compiled Vega spec with the location of
+    /// the Vega-Lite transform that it was compiled from. Only calculate and filter
+    /// transforms can be traced back, and the location is cleared for other transforms
+    pub(crate) fn locate_in_vegalite(&mut self, vl_spec: &Value) {
+        let location = self
+            .vega_transform
+            .as_ref()
+            .and_then(|vega_transform| find_vegalite_transform(vl_spec, vega_transform));
+        match location {
+            Some((spec_path, transform)) => {
+                self.spec_path = Some(spec_path);
+                self.transform = Some(transform.to_string());
+            }
+            None => {
+                self.spec_path = None;
+                self.transform = None;
+            }
+        }
+    }
+}
+
+impl Display for DataflowError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.phase {
+            DataflowPhase::Parse => write!(f, "Vega parse error")?,
+            DataflowPhase::Dataflow => {
+                write!(f, "Vega dataflow error")?;
+                match (&self.spec_path, &self.transform, &self.operator) {
+                    (Some(spec_path), Some(transform), _) => {
+                        // "layer[0].transform[2]" is written as "transform[2] of layer[0]"
+                        match spec_path.rsplit_once('.') {
+                            Some((parent, last)) => {
+                                write!(f, " in {last} ({transform}) of {parent}")?
+                            }
+                            None => write!(f, " in {spec_path} ({transform})")?,
+                        }
+                    }
+                    (_, _, Some(operator)) => write!(f, " in {operator} operator")?,
+                    _ => {}
+                }
+            }
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+impl std::error::Error for DataflowError {}
+
+/// Find the Vega-Lite transform that compiles to a Vega formula or filter transform, as its
+/// location in the spec and its type
+fn find_vegalite_transform(
+    vl_spec: &Value,
+    vega_transform: &Value,
+) -> Option<(String, &'static str)> {
+    let expr = vega_transform.get("expr")?;
+    let (transform, as_field) = match vega_transform.get("type")?.as_str()? {
+        "formula" => ("calculate", vega_transform.get("as")),
+        "filter" => ("filter", None),
+        _ => return None,
+    };
+
+    let mut path = Vec::new();
+    find_in_view(vl_spec, transform, expr, as_field, &mut path)?;
+    Some((path.join("."), transform))
+}
+
+fn find_in_view(
+    view: &Value,
+    transform: &str,
+    expr: &Value,
+    as_field: Option<&Value>,
+    path: &mut Vec<String>,
+) -> Option<()> {
+    if let Some(Value::Array(transforms)) = view.get("transform") {
+        for (index, candidate) in transforms.iter().enumerate() {
+            if candidate.get(transform) == Some(expr)
+                && (as_field.is_none() || candidate.get("as") == as_field)
+            {
+                path.push(format!("transform[{index}]"));
+                return Some(());
+            }
+        }
+    }
+
+    for key in ["layer", "concat", "hconcat", "vconcat"] {
+        if let Some(Value::Array(views)) = view.get(key) {
+            for (index, child) in views.iter().enumerate() {
+                path.push(format!("{key}[{index}]"));
+                if find_in_view(child, transform, expr, as_field, path).is_some() {
+                    return Some(());
+                }
+                path.pop();
+            }
+        }
+    }
+
+    // The inner view of facet and repeat specs
+    if let Some(child @ Value::Object(_)) = view.get("spec") {
+        path.push("spec".to_string());
+        if find_in_view(child, transform, expr, as_field, path).is_some() {
+            return Some(());
+        }
+        path.pop();
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn formula_error() -> DataflowError {
+        serde_json::from_value(json!({
+            "phase": "dataflow",
+            "operator": "Formula",
+            "message": "Cannot read properties of undefined (reading 'field')",
+            "spec_path": "data[2].transform[2]",
+            "transform": "formula",
+            "vega_transform": {"type": "formula", "expr": "datum.missing.field", "as": "d"}
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_display_vega_location() {
+        assert_eq!(
+            formula_error().to_string(),
+            "Vega dataflow error in transform[2] (formula) of data[2]: \
+             Cannot read properties of undefined (reading 'field')"
+        );
+    }
+
+    #[test]
+    fn test_locate_in_vegalite() {
+        let vl_spec = json!({
+            "layer": [
+                {"mark": "rule"},
+                {
+                    "transform": [
+                        {"calculate": "datum.b * 2", "as": "c"},
+                        {"calculate": "datum.missing.field", "as": "d"}
+                    ],
+                    "mark": "bar"
+                }
+            ]
+        });
+        let mut error = formula_error();
+        error.locate_in_vegalite(&vl_spec);
+        assert_eq!(error.spec_path.as_deref(), Some("layer[1].transform[1]"));
+        assert_eq!(
+            error.to_string(),
+            "Vega dataflow error in transform[1] (calculate) of layer[1]: \
+             Cannot read properties of undefined (reading 'field')"
+        );
+
+        // Operators that can't be traced back are named by their type
+        let mut error = formula_error();
+        error.locate_in_vegalite(&json!({"mark": "bar"}));
+        assert_eq!(error.spec_path, None);
+        assert_eq!(
+            error.to_string(),
+            "Vega dataflow error in Formula operator: \
+             Cannot read properties of undefined (reading 'field')"
+        );
+    }
+}
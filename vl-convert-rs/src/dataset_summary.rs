@@ -0,0 +1,31 @@
+use serde::{Deserialize, Serialize};
+
+/// Summary of the datasets of a rendered chart, collected when the `collect_dataset_summary`
+/// option is enabled. Useful for finding out why a chart came out empty
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DatasetSummary {
+    /// Top-level datasets of the Vega spec, in the order that they're defined
+    pub datasets: Vec<DatasetInfo>,
+    /// Whether any items were drawn by data marks. Axes, legends, and titles aren't counted
+    pub any_marks_rendered: bool,
+}
+
+/// Row count and fields of a dataset after the chart was rendered
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DatasetInfo {
+    pub name: String,
+    pub rows: usize,
+    /// Fields of the dataset's rows, in the order that they first appear
+    pub fields: Vec<String>,
+    /// Whether a mark is drawn from the dataset, directly or through a facet
+    pub feeds_mark: bool,
+}
+
+impl DatasetSummary {
+    /// Datasets that marks are drawn from that have no rows, which leave those marks empty
+    pub fn empty_mark_datasets(&self) -> impl Iterator<Item = &DatasetInfo> {
+        self.datasets
+            .iter()
+            .filter(|dataset| dataset.feeds_mark && dataset.rows == 0)
+    }
+}
@@ -0,0 +1,89 @@
+use crate::anyhow::anyhow;
+use deno_core::error::AnyError;
+use serde_json::{json, Value};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// Writing direction that Vega-Lite charts are laid out for
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Direction {
+    #[default]
+    Ltr,
+    /// Mirror the layout of the chart for right-to-left locales, as browsers do for pages
+    /// with `dir="rtl"`: y axes and row headers are drawn on the right, legends on the left,
+    /// titles are anchored at the end, and left and right config padding are swapped
+    Rtl,
+}
+
+impl Display for Direction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let direction = match self {
+            Direction::Ltr => "ltr",
+            Direction::Rtl => "rtl",
+        };
+        std::fmt::Display::fmt(direction, f)
+    }
+}
+
+impl FromStr for Direction {
+    type Err = AnyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.trim().to_ascii_lowercase().as_str() {
+            "ltr" => Self::Ltr,
+            "rtl" => Self::Rtl,
+            _ => return Err(anyhow!("Unsupported direction: {}", s)),
+        })
+    }
+}
+
+/// Vega-Lite config that mirrors the layout of a chart for right-to-left locales. This is a
+/// best-effort transformation that covers:
+///
+///  - y axes, which are drawn on the right instead of the left
+///  - legends, which are drawn on the left instead of the right
+///  - titles and subtitles, which are anchored at the end instead of the start
+///  - row headers of faceted charts, which are drawn on the right instead of the left
+///
+/// The config is applied beneath the user's config, so axes, legends, titles, and headers
+/// with an orient or anchor set in the spec or config keep it. Mark positions, the direction
+/// of x scales, and the order of legend entries aren't changed
+pub(crate) fn rtl_config() -> Value {
+    json!({
+        "axisY": {"orient": "right"},
+        "legend": {"orient": "left"},
+        "title": {"anchor": "end"},
+        "headerRow": {"labelOrient": "right", "titleOrient": "right"},
+    })
+}
+
+/// Swap the left and right padding of a config, when its padding is set per side
+pub(crate) fn mirror_padding(config: &mut Value) {
+    let Some(Value::Object(padding)) = config.get_mut("padding") else {
+        return;
+    };
+    let left = padding.remove("left");
+    let right = padding.remove("right");
+    if let Some(right) = right {
+        padding.insert("left".to_string(), right);
+    }
+    if let Some(left) = left {
+        padding.insert("right".to_string(), left);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mirror_padding() {
+        let mut config = json!({"padding": {"left": 5, "top": 10}});
+        mirror_padding(&mut config);
+        assert_eq!(config, json!({"padding": {"right": 5, "top": 10}}));
+
+        let mut config = json!({"padding": 5});
+        mirror_padding(&mut config);
+        assert_eq!(config, json!({"padding": 5}));
+    }
+}
@@ -0,0 +1,185 @@
+use crate::anyhow::bail;
+use crate::converter::{encode_png_with_opts, svg_to_pixmap, PngOpts};
+use deno_core::error::AnyError;
+use serde::Deserialize;
+use tiny_skia::IntRect;
+
+/// A PNG image of one cell of a faceted chart
+#[derive(Debug, Clone, PartialEq)]
+pub struct FacetImage {
+    /// Value of the row field of the cell, or None when the chart has no row facet
+    pub row_value: Option<serde_json::Value>,
+    /// Value of the column field of the cell, or None when the chart has no column facet.
+    /// The cells of wrapped facets (with a facet field) have the value of the facet field
+    pub column_value: Option<serde_json::Value>,
+    pub data: Vec<u8>,
+}
+
+/// Options of the images of the cells of a faceted chart
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FacetOpts {
+    /// Whether to include the headers and shared axes next to each cell in its image, along
+    /// with the plot area of the cell. Only the plot area is included by default
+    pub include_guides: bool,
+}
+
+/// A cell of a rendered faceted chart, with the bounds of its plot area in the SVG image of
+/// the chart
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub(crate) struct FacetCell {
+    pub row_value: Option<serde_json::Value>,
+    pub column_value: Option<serde_json::Value>,
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+    /// Bounds of the cell along with the row and column headers and shared axes next to it
+    pub with_guides: CellBounds,
+}
+
+/// Bounds of a part of a faceted chart in the SVG image of the chart
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub(crate) struct CellBounds {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+/// The SVG image of a faceted chart along with its size, its row and column fields, and its
+/// cells, from rendering the chart once
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub(crate) struct FacetCells {
+    pub svg: String,
+    pub width: f64,
+    pub height: f64,
+    pub row_field: Option<String>,
+    pub column_field: Option<String>,
+    pub cells: Vec<FacetCell>,
+}
+
+/// Rasterize the SVG image of a faceted chart once, and crop a PNG image of each cell from
+/// it. Every cell is cropped to its width and height scaled to pixels, so that cells of the
+/// same size produce images of the same size. With `include_guides`, cells are cropped to
+/// their bounds with guides instead, limited to the chart
+pub(crate) fn crop_facet_pngs(
+    facet_cells: &FacetCells,
+    scale: f32,
+    ppi: Option<f32>,
+    facet_opts: &FacetOpts,
+) -> Result<Vec<FacetImage>, AnyError> {
+    if facet_cells.cells.is_empty() {
+        bail!(
+            "Spec has no facet cells. Facet images require a spec with a top-level row, \
+            column, or facet"
+        );
+    }
+    let ppi = ppi.unwrap_or(72.0);
+    let png_opts = PngOpts::default();
+    let pixmap = svg_to_pixmap(&facet_cells.svg, scale, Some(ppi), &png_opts)?;
+    let scale_x = pixmap.width() as f64 / facet_cells.width;
+    let scale_y = pixmap.height() as f64 / facet_cells.height;
+
+    facet_cells
+        .cells
+        .iter()
+        .map(|cell| {
+            let (x, y, width, height) = if facet_opts.include_guides {
+                let bounds = &cell.with_guides;
+                let (x, y) = (bounds.x.max(0.0), bounds.y.max(0.0));
+                let width = (bounds.x + bounds.width).min(facet_cells.width) - x;
+                let height = (bounds.y + bounds.height).min(facet_cells.height) - y;
+                (x, y, width, height)
+            } else {
+                (cell.x, cell.y, cell.width, cell.height)
+            };
+            let rect = IntRect::from_xywh(
+                (x * scale_x).round() as i32,
+                (y * scale_y).round() as i32,
+                ((width * scale_x).round() as u32).max(1),
+                ((height * scale_y).round() as u32).max(1),
+            );
+            let Some(cell_pixmap) = rect.and_then(|rect| pixmap.clone_rect(rect)) else {
+                bail!("Facet cell at ({x}, {y}) is outside of the chart");
+            };
+            Ok(FacetImage {
+                row_value: cell.row_value.clone(),
+                column_value: cell.column_value.clone(),
+                data: encode_png_with_opts(cell_pixmap, ppi, &png_opts)?,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn cell(row_value: &str, column_value: &str, x: f64) -> FacetCell {
+        FacetCell {
+            row_value: Some(json!(row_value)),
+            column_value: Some(json!(column_value)),
+            x,
+            y: 10.0,
+            width: 30.0,
+            height: 20.0,
+            with_guides: CellBounds {
+                x: x - 10.0,
+                y: 0.0,
+                width: 40.0,
+                height: 40.0,
+            },
+        }
+    }
+
+    #[test]
+    fn test_crop_facet_pngs() {
+        let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" width="100" height="40">
+            <rect x="10" y="10" width="30" height="20" fill="red"/>
+            <rect x="55.5" y="10" width="30" height="20" fill="blue"/>
+        </svg>"#;
+        let facet_cells = FacetCells {
+            svg: svg.to_string(),
+            width: 100.0,
+            height: 40.0,
+            row_field: Some("r".to_string()),
+            column_field: Some("c".to_string()),
+            cells: vec![cell("r1", "c1", 10.0), cell("r1", "c2", 55.5)],
+        };
+        let images = crop_facet_pngs(&facet_cells, 2.0, None, &Default::default()).unwrap();
+        assert_eq!(images.len(), 2);
+        assert_eq!(images[1].row_value, Some(json!("r1")));
+        assert_eq!(images[1].column_value, Some(json!("c2")));
+        for (facet_image, color) in images.iter().zip([[255u8, 0, 0], [0, 0, 255]]) {
+            let image = image::load_from_memory(&facet_image.data)
+                .unwrap()
+                .to_rgba8();
+            assert_eq!(image.dimensions(), (60, 40));
+            assert_eq!(image.get_pixel(30, 20).0[..3], color);
+        }
+
+        // With guides, cells are cropped to their bounds with guides
+        let facet_opts = FacetOpts {
+            include_guides: true,
+        };
+        let images = crop_facet_pngs(&facet_cells, 2.0, None, &facet_opts).unwrap();
+        let image = image::load_from_memory(&images[0].data).unwrap().to_rgba8();
+        assert_eq!(image.dimensions(), (80, 80));
+        assert_eq!(image.get_pixel(50, 40).0[..3], [255, 0, 0]);
+    }
+
+    #[test]
+    fn test_crop_facet_pngs_no_cells() {
+        let facet_cells = FacetCells {
+            svg: r#"<svg xmlns="http://www.w3.org/2000/svg" width="10" height="10"/>"#.to_string(),
+            width: 10.0,
+            height: 10.0,
+            row_field: None,
+            column_field: None,
+            cells: vec![],
+        };
+        let err = crop_facet_pngs(&facet_cells, 1.0, None, &Default::default()).unwrap_err();
+        assert!(err.to_string().contains("no facet cells"));
+    }
+}
@@ -0,0 +1,79 @@
+use serde_json::Value;
+use std::borrow::Cow;
+
+/// Make the force transforms of a Vega spec run as static simulations of `iterations` ticks,
+/// so that their nodes are laid out before the chart is rendered rather than animated
+/// afterwards. Force transforms that set `static` or `iterations` themselves keep them.
+/// Returns the spec unchanged when `iterations` is None
+pub(crate) fn with_static_force(spec: &Value, iterations: Option<u32>) -> Cow<'_, Value> {
+    let Some(iterations) = iterations else {
+        return Cow::Borrowed(spec);
+    };
+    let mut spec = spec.clone();
+    set_static_force(&mut spec, iterations);
+    Cow::Owned(spec)
+}
+
+fn set_static_force(value: &mut Value, iterations: u32) {
+    match value {
+        Value::Object(obj) => {
+            if let Some(Value::Array(transforms)) = obj.get_mut("transform") {
+                for transform in transforms.iter_mut() {
+                    let Value::Object(transform) = transform else {
+                        continue;
+                    };
+                    if transform.get("type").and_then(Value::as_str) != Some("force") {
+                        continue;
+                    }
+                    transform.entry("static").or_insert(Value::Bool(true));
+                    transform
+                        .entry("iterations")
+                        .or_insert(Value::from(iterations));
+                }
+            }
+            for child in obj.values_mut() {
+                set_static_force(child, iterations);
+            }
+        }
+        Value::Array(values) => {
+            for child in values {
+                set_static_force(child, iterations);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_with_static_force() {
+        let spec = json!({
+            "marks": [{
+                "type": "group",
+                "marks": [{
+                    "type": "symbol",
+                    "transform": [
+                        {"type": "force", "forces": [{"force": "nbody"}]},
+                        {"type": "force", "static": false, "iterations": 50},
+                        {"type": "formula", "expr": "1", "as": "one"}
+                    ]
+                }]
+            }]
+        });
+        assert!(matches!(with_static_force(&spec, None), Cow::Borrowed(_)));
+
+        let spec = with_static_force(&spec, Some(500)).into_owned();
+        assert_eq!(
+            spec["marks"][0]["marks"][0]["transform"],
+            json!([
+                {"type": "force", "forces": [{"force": "nbody"}], "static": true, "iterations": 500},
+                {"type": "force", "static": false, "iterations": 50},
+                {"type": "formula", "expr": "1", "as": "one"}
+            ])
+        );
+    }
+}
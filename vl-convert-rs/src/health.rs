@@ -0,0 +1,98 @@
+use serde::{Serialize, Serializer};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Maximum time that [`crate::VlConverter::health_check`] waits for the worker to respond
+pub const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Health of a converter's JavaScript worker, returned by
+/// [`crate::VlConverter::health_check`]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HealthReport {
+    /// Version of Vega that the worker evaluated
+    pub vega_version: String,
+    /// Vega-Lite version that's used when conversions don't specify one
+    pub vl_default_version: String,
+    /// Time since the converter started its current worker. Serialized as seconds
+    #[serde(serialize_with = "serialize_duration_secs")]
+    pub worker_uptime: Duration,
+    /// Number of times that the converter restarted its worker after the worker stopped,
+    /// e.g. because its JavaScript runtime was terminated
+    pub worker_restarts: u64,
+    /// Completion time of the most recent conversion, successful or not. Serialized as seconds
+    /// since the Unix epoch
+    #[serde(serialize_with = "serialize_system_time_secs")]
+    pub last_conversion_at: Option<SystemTime>,
+    /// Number of conversions that succeeded
+    pub conversions_completed: u64,
+    /// Number of conversions that failed
+    pub conversions_failed: u64,
+    /// Number of times that the modules of Vega-Lite versions were evaluated. Each worker
+    /// evaluates a version once, when a conversion first uses it or when it's warmed with
+    /// [`crate::VlConverter::warm_versions`]
+    pub vl_module_evaluations: u64,
+}
+
+/// Outcomes of the conversions performed by a converter's worker, and the Vega-Lite modules
+/// that it evaluated. These are shared by the clones of a converter, and outlive its worker
+#[derive(Debug, Default)]
+pub(crate) struct ConversionCounters {
+    completed: AtomicU64,
+    failed: AtomicU64,
+    last_conversion_at: Mutex<Option<SystemTime>>,
+    vl_module_evaluations: AtomicU64,
+}
+
+impl ConversionCounters {
+    /// Record the outcome of a conversion
+    pub fn record<T, E>(&self, result: &Result<T, E>) {
+        let counter = if result.is_ok() {
+            &self.completed
+        } else {
+            &self.failed
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        if let Ok(mut guard) = self.last_conversion_at.lock() {
+            *guard = Some(SystemTime::now());
+        }
+    }
+
+    pub fn completed(&self) -> u64 {
+        self.completed.load(Ordering::Relaxed)
+    }
+
+    pub fn failed(&self) -> u64 {
+        self.failed.load(Ordering::Relaxed)
+    }
+
+    pub fn last_conversion_at(&self) -> Option<SystemTime> {
+        self.last_conversion_at.lock().ok().and_then(|guard| *guard)
+    }
+
+    /// Record that a worker evaluated the module of a Vega-Lite version
+    pub fn record_vl_module_evaluation(&self) {
+        self.vl_module_evaluations.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn vl_module_evaluations(&self) -> u64 {
+        self.vl_module_evaluations.load(Ordering::Relaxed)
+    }
+}
+
+fn serialize_duration_secs<S: Serializer>(
+    duration: &Duration,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64(duration.as_secs_f64())
+}
+
+fn serialize_system_time_secs<S: Serializer>(
+    time: &Option<SystemTime>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match time.and_then(|time| time.duration_since(UNIX_EPOCH).ok()) {
+        Some(since_epoch) => serializer.serialize_f64(since_epoch.as_secs_f64()),
+        None => serializer.serialize_none(),
+    }
+}
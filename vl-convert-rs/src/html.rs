@@ -1,18 +1,71 @@
-use crate::module_loader::import_map::{DEBOUNCE_PATH, SKYPACK_URL, VEGA_EMBED_PATH, VEGA_PATH};
+use crate::converter::PngResult;
+use crate::module_loader::import_map::{
+    DEBOUNCE_PATH, SKYPACK_URL, VEGA_EMBED_PATH, VEGA_EMBED_VERSION, VEGA_PATH, VEGA_VERSION,
+};
 use crate::module_loader::VlConvertBundleLoader;
 use crate::VlVersion;
+use base64::Engine;
+use deno_core::anyhow::{anyhow, bail};
 use deno_core::error::AnyError;
-use deno_emit::{bundle, BundleOptions, BundleType, EmitOptions, SourceMapOption};
+use deno_core::url::Url;
+use deno_emit::{bundle, BundleOptions, BundleType, EmitOptions, ModuleSpecifier, SourceMapOption};
+use deno_graph::{DefaultModuleParser, MediaType, ModuleParser, ParseOptions};
+use std::ops::Range;
 use std::path::Path;
+use std::str::FromStr;
+
+/// Source language of a snippet passed to bundle_vega_snippet_with_language
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SnippetLanguage {
+    #[default]
+    JavaScript,
+    TypeScript,
+}
+
+impl SnippetLanguage {
+    fn media_type(&self) -> MediaType {
+        match self {
+            SnippetLanguage::JavaScript => MediaType::JavaScript,
+            SnippetLanguage::TypeScript => MediaType::TypeScript,
+        }
+    }
+
+    fn extension(&self) -> &'static str {
+        match self {
+            SnippetLanguage::JavaScript => "js",
+            SnippetLanguage::TypeScript => "ts",
+        }
+    }
+}
+
+impl FromStr for SnippetLanguage {
+    type Err = AnyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.to_ascii_lowercase().as_str() {
+            "js" | "javascript" => Self::JavaScript,
+            "ts" | "typescript" => Self::TypeScript,
+            _ => bail!("Unsupported snippet language: {}. Expected 'js' or 'ts'", s),
+        })
+    }
+}
 
 pub fn get_vega_or_vegalite_script(
     spec: serde_json::Value,
     opts: serde_json::Value,
 ) -> Result<String, AnyError> {
-    let chart_id = "vega-chart";
+    get_chart_script(spec, opts, "vega-chart")
+}
 
+/// Build a script that embeds a chart in the element with the given id. The spec and opts
+/// are scoped to a block, so scripts for several charts can share a page
+pub fn get_chart_script(
+    spec: serde_json::Value,
+    opts: serde_json::Value,
+    chart_id: &str,
+) -> Result<String, AnyError> {
     // Setup embed opts
-    let opts = format!("const opts = {}", serde_json::to_string(&opts)?);
+    let opts = format!("const opts = {}", script_json(&opts)?);
 
     let index_js = format!(
         r##"
@@ -22,15 +75,405 @@ pub fn get_vega_or_vegalite_script(
     vegaEmbed('#{chart_id}', spec, opts).catch(console.error);
 }}
 "##,
-        SPEC = serde_json::to_string(&spec)?
+        SPEC = script_json(&spec)?
     );
     Ok(index_js)
 }
 
+/// Serialize a value as JSON that can be written inside a script element. `<`, `>`, and `&`
+/// in strings are written as unicode escapes, so that a string like "</script>" can't end the
+/// element, along with the line and paragraph separators that older JavaScript engines reject
+/// in string literals
+pub fn script_json(value: &serde_json::Value) -> Result<String, AnyError> {
+    let json = serde_json::to_string(value)?;
+    let mut escaped = String::with_capacity(json.len());
+    for c in json.chars() {
+        match c {
+            '<' => escaped.push_str("\\u003c"),
+            '>' => escaped.push_str("\\u003e"),
+            '&' => escaped.push_str("\\u0026"),
+            '\u{2028}' => escaped.push_str("\\u2028"),
+            '\u{2029}' => escaped.push_str("\\u2029"),
+            _ => escaped.push(c),
+        }
+    }
+    Ok(escaped)
+}
+
+/// The template that HTML documents of single charts are written with when no template is
+/// given. Templates have these placeholders, which are filled in when a chart is converted:
+///
+///  - `{{ scripts }}`: elements for the head of the document that load the Vega libraries,
+///    preceded by a Content-Security-Policy meta element when there are allowed base urls
+///  - `{{ element_id }}`: id of the element that the chart is embedded in
+///  - `{{ spec }}`: the spec, as JSON that can be written inside a script element
+///  - `{{ embed_options }}`: options for vegaEmbed, as JSON like the spec
+///  - `{{ title }}`: title of the document
+///  - `{{ fallback }}`: the static fallback image, for the content of the chart element
+///  - `{{ noscript }}`: a noscript element that shows the static fallback image in viewers
+///    that don't run scripts
+///
+/// The first four are required. The fallback placeholders are empty unless a static fallback
+/// is requested, and other placeholders are left as they are
+pub const DEFAULT_HTML_TEMPLATE: &str = r##"<!DOCTYPE html>
+<html>
+  <head>
+    <style>
+        vega-chart.vega-embed {
+          width: 100%;
+          display: flex;
+        }
+        vega-chart.vega-embed details,
+        vega-chart.vega-embed details summary {
+          position: relative;
+        }
+    </style>
+    <meta charset="UTF-8">
+    <title>{{ title }}</title>
+    {{ scripts }}
+  </head>
+  <body>
+    <div id="{{ element_id }}">{{ fallback }}</div>{{ noscript }}
+    <script type="text/javascript">
+      vegaEmbed("#{{ element_id }}", {{ spec }}, {{ embed_options }}).catch(console.error);
+    </script>
+  </body>
+</html>
+"##;
+
+/// Placeholders that every HTML template must have
+pub const REQUIRED_HTML_TEMPLATE_PLACEHOLDERS: &[&str] =
+    &["scripts", "element_id", "spec", "embed_options"];
+
+/// Check that an HTML template has all of the required placeholders
+pub fn check_html_template(template: &str) -> Result<(), AnyError> {
+    let names: Vec<&str> = template_placeholders(template)
+        .map(|(_range, name)| name)
+        .collect();
+    let missing: Vec<String> = REQUIRED_HTML_TEMPLATE_PLACEHOLDERS
+        .iter()
+        .filter(|name| !names.contains(name))
+        .map(|name| format!("{{{{ {name} }}}}"))
+        .collect();
+    if !missing.is_empty() {
+        bail!(
+            "The HTML template is missing required placeholders: {}",
+            missing.join(", ")
+        )
+    }
+    Ok(())
+}
+
+/// Fill in the placeholders of an HTML template. Placeholders without a value are left as
+/// they are, and values are inserted as given, so they must already be escaped
+pub fn render_html_template(template: &str, values: &[(&str, &str)]) -> Result<String, AnyError> {
+    check_html_template(template)?;
+    let mut html = String::with_capacity(template.len());
+    let mut end = 0;
+    for (range, name) in template_placeholders(template) {
+        if let Some((_name, value)) = values.iter().find(|(key, _value)| *key == name) {
+            html.push_str(&template[end..range.start]);
+            html.push_str(value);
+            end = range.end;
+        }
+    }
+    html.push_str(&template[end..]);
+    Ok(html)
+}
+
+/// The placeholders of a template, which are names wrapped in double braces with optional
+/// whitespace (e.g. `{{ spec }}` or `{{spec}}`), with their byte ranges
+fn template_placeholders(template: &str) -> impl Iterator<Item = (Range<usize>, &str)> {
+    let mut pos = 0;
+    std::iter::from_fn(move || loop {
+        let start = pos + template[pos..].find("{{")?;
+        let Some(len) = template[start + 2..].find("}}") else {
+            return None;
+        };
+        let end = start + 2 + len + 2;
+        let name = template[start + 2..end - 2].trim();
+        if !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            pos = end;
+            return Some((start..end, name));
+        }
+        pos = start + 2;
+    })
+}
+
+/// Build an HTML document that embeds a single chart from a template, which is
+/// [`DEFAULT_HTML_TEMPLATE`] when None. `scripts` are the head elements that load the Vega
+/// libraries
+pub fn get_chart_html(
+    spec: &serde_json::Value,
+    embed_opts: &serde_json::Value,
+    scripts: &str,
+    fallback: Option<&PngResult>,
+    template: Option<&str>,
+) -> Result<String, AnyError> {
+    let element_id = "vega-chart";
+    // The fallback image is the initial content of the chart element, which vegaEmbed clears,
+    // and is repeated in a noscript block that hides the element when scripts are disabled
+    let (fallback, noscript) = match fallback {
+        None => (String::new(), String::new()),
+        Some(png) => {
+            let img = get_static_fallback_img(png);
+            let noscript = format!(
+                r#"
+    <noscript>
+      <style>#{element_id} {{ display: none; }}</style>
+      {img}
+    </noscript>"#
+            );
+            (img, noscript)
+        }
+    };
+    render_html_template(
+        template.unwrap_or(DEFAULT_HTML_TEMPLATE),
+        &[
+            ("scripts", scripts),
+            ("element_id", element_id),
+            ("spec", &script_json(spec)?),
+            ("embed_options", &script_json(embed_opts)?),
+            ("title", "Chart"),
+            ("fallback", &fallback),
+            ("noscript", &noscript),
+        ],
+    )
+}
+
+/// Escape text for use in HTML element content or attribute values
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Find the data and image URLs of a Vega or Vega-Lite spec that a browser would load from
+/// outside of `allowed_base_urls`, in the order they appear in the spec. URLs are allowed when
+/// they start with one of the base URLs, as in conversions. Relative URLs are never allowed,
+/// because in HTML output they load from the origin of the page, wherever it's served from.
+/// URLs that are computed by signals or fields of the data can't be checked
+pub fn disallowed_spec_urls(spec: &serde_json::Value, allowed_base_urls: &[String]) -> Vec<String> {
+    let mut urls = Vec::new();
+    collect_spec_urls(spec, &mut urls);
+    let mut disallowed: Vec<String> = Vec::new();
+    for url in urls {
+        if url.starts_with("data:") {
+            continue;
+        }
+        let allowed = match Url::parse(&url) {
+            Ok(parsed) => allowed_base_urls
+                .iter()
+                .any(|base_url| parsed.as_str().starts_with(base_url.as_str())),
+            Err(_) => false,
+        };
+        if !allowed && !disallowed.contains(&url) {
+            disallowed.push(url);
+        }
+    }
+    disallowed
+}
+
+/// Check the data and image URLs of a spec against `allowed_base_urls` before it's written
+/// to HTML. With `warn_only`, disallowed URLs are logged as a warning instead of failing
+pub fn check_spec_urls(
+    spec: &serde_json::Value,
+    allowed_base_urls: &[String],
+    warn_only: bool,
+) -> Result<(), AnyError> {
+    let disallowed = disallowed_spec_urls(spec, allowed_base_urls);
+    if disallowed.is_empty() {
+        return Ok(());
+    }
+    let message = format!(
+        "The spec loads from urls that allowed_base_urls doesn't allow: {}",
+        disallowed.join(", ")
+    );
+    if warn_only {
+        log::warn!("{message}");
+        Ok(())
+    } else {
+        bail!("{message}")
+    }
+}
+
+/// Collect the URLs of data sources and image marks, which are "url" properties that are
+/// strings, or objects with a string "value" as in encodings
+fn collect_spec_urls(value: &serde_json::Value, urls: &mut Vec<String>) {
+    match value {
+        serde_json::Value::Object(obj) => {
+            for (key, child) in obj {
+                if key == "url" {
+                    let url = match child {
+                        serde_json::Value::String(url) => Some(url.as_str()),
+                        serde_json::Value::Object(encoding) => {
+                            encoding.get("value").and_then(serde_json::Value::as_str)
+                        }
+                        _ => None,
+                    };
+                    if let Some(url) = url {
+                        urls.push(url.to_string());
+                        continue;
+                    }
+                }
+                collect_spec_urls(child, urls);
+            }
+        }
+        serde_json::Value::Array(values) => {
+            for child in values {
+                collect_spec_urls(child, urls);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Build a meta element with a Content-Security-Policy that only lets the page connect to,
+/// and load images from, the origins of `allowed_base_urls`. Images may also be data URIs,
+/// like the static fallback image and the images that vega-embed exports
+pub fn get_csp_meta(allowed_base_urls: &[String]) -> Result<String, AnyError> {
+    let mut origins: Vec<String> = Vec::new();
+    for base_url in allowed_base_urls {
+        let origin = Url::parse(base_url)
+            .map_err(|err| anyhow!("Invalid allowed base url {}: {}", base_url, err))?
+            .origin();
+        if !origin.is_tuple() {
+            bail!("Allowed base url {} has no origin", base_url);
+        }
+        let origin = origin.ascii_serialization();
+        if !origins.contains(&origin) {
+            origins.push(origin);
+        }
+    }
+    let sources = if origins.is_empty() {
+        "'none'".to_string()
+    } else {
+        origins.join(" ")
+    };
+    let img_sources = if origins.is_empty() {
+        "data:".to_string()
+    } else {
+        format!("{sources} data:")
+    };
+    Ok(format!(
+        r#"<meta http-equiv="Content-Security-Policy" content="{}">"#,
+        escape_html(&format!("connect-src {sources}; img-src {img_sources}"))
+    ))
+}
+
+/// Build an img element that shows a PNG rendering of a chart at the chart's CSS size, with
+/// the image inlined as a data URI
+pub fn get_static_fallback_img(png: &PngResult) -> String {
+    format!(
+        r#"<img src="data:image/png;base64,{data}" width="{width}" height="{height}" alt="Chart">"#,
+        data = base64::engine::general_purpose::STANDARD.encode(&png.data),
+        width = png.css_width,
+        height = png.css_height,
+    )
+}
+
+/// Snippet of the vega-embed bundle, which makes the libraries available to other scripts
+const VEGAEMBED_BUNDLE_SNIPPET: &str = "window.vegaEmbed=vegaEmbed; window.vega=vega; \
+    window.vegaLite=vegaLite; window.lodashDebounce=lodashDebounce;";
+
+/// Script tags that load the Vega libraries into an HTML page, split by where they belong
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HtmlScripts {
+    /// Tags to place in the head element, which define the vega, vegaLite, and vegaEmbed
+    /// globals
+    pub head: String,
+    /// Tags to place at the end of the body element. When the libraries are loaded from a
+    /// CDN, this defines the lodashDebounce global, which bundles define in the head instead
+    pub body: String,
+}
+
+/// Bundle the vendored Vega and Vega-Lite libraries, without vega-embed, into a script that
+/// assigns them to `window.vega` and `window.vegaLite`
+pub async fn get_vega_bundle(vl_version: VlVersion) -> Result<String, AnyError> {
+    let script = format!(
+        r#"
+import vega from "{SKYPACK_URL}{VEGA_PATH}"
+import vegaLite from "{SKYPACK_URL}{VEGA_LITE_PATH}"
+window.vega = vega;
+window.vegaLite = vegaLite;
+"#,
+        VEGA_LITE_PATH = vl_version.to_path()
+    );
+    bundle_script(script, vl_version).await
+}
+
+/// Bundle the vendored vega-embed, Vega, Vega-Lite, and lodash.debounce libraries into a
+/// script that assigns them to `window.vegaEmbed`, `window.vega`, `window.vegaLite`, and
+/// `window.lodashDebounce`. This is the bundle that HTML output inlines
+pub async fn get_vegaembed_bundle(vl_version: VlVersion) -> Result<String, AnyError> {
+    bundle_vega_snippet(VEGAEMBED_BUNDLE_SNIPPET, vl_version).await
+}
+
+/// Get the script tags that load the Vega libraries of `vl_version` into a custom HTML
+/// template. With `bundle`, the vega-embed bundle is inlined in the head. Otherwise, the
+/// libraries are loaded from jsDelivr at the exact versions that are vendored
+pub async fn get_script_tags(vl_version: VlVersion, bundle: bool) -> Result<HtmlScripts, AnyError> {
+    if bundle {
+        return Ok(HtmlScripts {
+            head: format!(
+                r#"<script type="text/javascript">{}</script>"#,
+                get_vegaembed_bundle(vl_version).await?
+            ),
+            body: String::new(),
+        });
+    }
+    let vl_version = pinned_version(&vl_version.to_path(), "vega-lite")?;
+    let debounce_version = pinned_version(DEBOUNCE_PATH, "lodash.debounce")?;
+    Ok(HtmlScripts {
+        head: format!(
+            r#"<script src="https://cdn.jsdelivr.net/npm/vega@{VEGA_VERSION}"></script>
+<script src="https://cdn.jsdelivr.net/npm/vega-lite@{vl_version}"></script>
+<script src="https://cdn.jsdelivr.net/npm/vega-embed@{VEGA_EMBED_VERSION}"></script>"#
+        ),
+        body: format!(
+            r#"<script type="module">
+import lodashDebounce from "https://cdn.jsdelivr.net/npm/lodash.debounce@{debounce_version}/+esm";
+window.lodashDebounce = lodashDebounce;
+</script>"#
+        ),
+    })
+}
+
+/// The version of a package in its pinned Skypack path (e.g. "5.21.0" in
+/// "/pin/vega-lite@v5.21.0-FWbVtRVTj7vqBi6QZX8A/...")
+fn pinned_version(path: &str, package: &str) -> Result<String, AnyError> {
+    path.strip_prefix(&format!("/pin/{package}@v"))
+        .and_then(|rest| rest.split('-').next())
+        .map(|version| version.to_string())
+        .ok_or_else(|| anyhow!("No {} version in pinned path {}", package, path))
+}
+
 pub async fn bundle_script(script: String, vl_version: VlVersion) -> Result<String, AnyError> {
-    // Bundle dependencies
-    let bundle_entry_point =
-        deno_core::resolve_path("vl-convert-index.js", Path::new(env!("CARGO_MANIFEST_DIR")))?;
+    bundle_script_with_language(script, vl_version, SnippetLanguage::JavaScript).await
+}
+
+/// Bundle a script, transpiling it first if it is written in TypeScript.
+///
+/// Top-level await in the script is preserved in the ES module output
+pub async fn bundle_script_with_language(
+    script: String,
+    vl_version: VlVersion,
+    language: SnippetLanguage,
+) -> Result<String, AnyError> {
+    // Bundle dependencies. The entry point extension determines how the script is parsed
+    let bundle_entry_point = deno_core::resolve_path(
+        &format!("vl-convert-index.{}", language.extension()),
+        Path::new(env!("CARGO_MANIFEST_DIR")),
+    )?;
     let mut loader = VlConvertBundleLoader::new(script, vl_version);
     let bundled = bundle(
         bundle_entry_point,
@@ -47,12 +490,27 @@ pub async fn bundle_script(script: String, vl_version: VlVersion) -> Result<Stri
             minify: true,
         },
     )
-    .await?;
+    .await
+    .map_err(|err| anyhow!("Failed to bundle script: {:#}", err))?;
     Ok(bundled.code)
 }
 
 /// Bundle a JavaScript snippet that may contain references to vegaEmbed, vegaLite, or vega
 pub async fn bundle_vega_snippet(snippet: &str, vl_version: VlVersion) -> Result<String, AnyError> {
+    bundle_vega_snippet_with_language(snippet, vl_version, SnippetLanguage::JavaScript).await
+}
+
+/// Bundle a JavaScript or TypeScript snippet that may contain references to vegaEmbed,
+/// vegaLite, or vega
+pub async fn bundle_vega_snippet_with_language(
+    snippet: &str,
+    vl_version: VlVersion,
+    language: SnippetLanguage,
+) -> Result<String, AnyError> {
+    // Parse the snippet on its own first so that syntax errors are reported with
+    // line and column numbers relative to the snippet
+    check_snippet_syntax(snippet, language)?;
+
     let script = format!(
         r#"
 import vegaEmbed from "{SKYPACK_URL}{VEGA_EMBED_PATH}"
@@ -64,5 +522,112 @@ import lodashDebounce from "{SKYPACK_URL}{DEBOUNCE_PATH}"
         VEGA_LITE_PATH = vl_version.to_path()
     );
 
-    bundle_script(script.to_string(), vl_version).await
+    bundle_script_with_language(script.to_string(), vl_version, language).await
+}
+
+fn check_snippet_syntax(snippet: &str, language: SnippetLanguage) -> Result<(), AnyError> {
+    let specifier = ModuleSpecifier::parse(&format!(
+        "file:///vl-convert-snippet.{}",
+        language.extension()
+    ))?;
+    let parsed = DefaultModuleParser.parse_module(ParseOptions {
+        specifier: &specifier,
+        source: snippet.into(),
+        media_type: language.media_type(),
+        scope_analysis: false,
+    });
+    match parsed {
+        Ok(parsed) => match parsed.diagnostics().first() {
+            Some(diagnostic) => bail!("Failed to parse snippet: {}", diagnostic),
+            None => Ok(()),
+        },
+        Err(diagnostic) => bail!("Failed to parse snippet: {}", diagnostic),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pinned_version() {
+        assert_eq!(
+            pinned_version(&VlVersion::v5_20.to_path(), "vega-lite").unwrap(),
+            "5.20.1"
+        );
+        assert_eq!(
+            pinned_version(DEBOUNCE_PATH, "lodash.debounce").unwrap(),
+            "4.0.8"
+        );
+        assert!(pinned_version(VEGA_PATH, "vega-lite").is_err());
+    }
+
+    #[test]
+    fn test_script_json() {
+        let value = serde_json::json!({"title": "</script><!-- a & b \u{2028}"});
+        assert_eq!(
+            script_json(&value).unwrap(),
+            r#"{"title":"\u003c/script\u003e\u003c!-- a \u0026 b \u2028"}"#
+        );
+    }
+
+    #[test]
+    fn test_render_html_template() {
+        let template = "{{scripts}}|{{ element_id }}|{{ spec }}|{{ embed_options }}|{{ footer }}";
+        let html = render_html_template(
+            template,
+            &[
+                ("scripts", "S"),
+                ("element_id", "E"),
+                ("spec", "{{ footer }}"),
+                ("embed_options", "O"),
+            ],
+        )
+        .unwrap();
+        assert_eq!(html, "S|E|{{ footer }}|O|{{ footer }}");
+
+        let err = check_html_template("<p>{{ spec }}</p>").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "The HTML template is missing required placeholders: {{ scripts }}, \
+             {{ element_id }}, {{ embed_options }}"
+        );
+        check_html_template(DEFAULT_HTML_TEMPLATE).unwrap();
+    }
+
+    #[test]
+    fn test_disallowed_spec_urls() {
+        let spec = serde_json::json!({
+            "data": {"url": "https://example.com/data/cars.json"},
+            "layer": [
+                {"data": {"url": "data/local.csv"}, "mark": "point"},
+                {"mark": "image", "encoding": {"url": {"value": "data:image/png;base64,AAAA"}}},
+                {"data": {"url": "https://example.com.evil.com/cars.json"}, "mark": "bar"}
+            ]
+        });
+        let allowed = vec!["https://example.com/".to_string()];
+        assert_eq!(
+            disallowed_spec_urls(&spec, &allowed),
+            vec!["data/local.csv", "https://example.com.evil.com/cars.json"]
+        );
+        assert!(check_spec_urls(&spec, &allowed, true).is_ok());
+        assert!(check_spec_urls(&spec, &allowed, false).is_err());
+    }
+
+    #[test]
+    fn test_csp_meta() {
+        let allowed = vec![
+            "https://example.com/data/".to_string(),
+            "https://example.com/images/".to_string(),
+            "http://localhost:8000/".to_string(),
+        ];
+        assert_eq!(
+            get_csp_meta(&allowed).unwrap(),
+            r#"<meta http-equiv="Content-Security-Policy" content="connect-src https://example.com http://localhost:8000; img-src https://example.com http://localhost:8000 data:">"#
+        );
+        assert_eq!(
+            get_csp_meta(&[]).unwrap(),
+            r#"<meta http-equiv="Content-Security-Policy" content="connect-src &#39;none&#39;; img-src data:">"#
+        );
+    }
 }
@@ -1,8 +1,15 @@
+use crate::render_warnings::RenderWarning;
+use crate::LOG_TARGET;
 use log::{error, info};
 use reqwest::{Client, StatusCode};
-use std::io::Write;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tokio::task;
-use usvg::{ImageHrefResolver, Options};
+use usvg::{roxmltree, ImageHrefResolver, ImageKind, Options};
 
 static VL_CONVERT_USER_AGENT: &str =
     concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
@@ -19,48 +26,262 @@ lazy_static! {
         .expect("Failed to construct reqwest client");
 }
 
+thread_local! {
+    static FETCH_RETRY: Cell<Option<FetchRetry>> = const { Cell::new(None) };
+    static IMAGE_FAILURES: RefCell<Option<Vec<String>>> = const { RefCell::new(None) };
+}
+
+static IGNORE_EXIF_ORIENTATION: AtomicBool = AtomicBool::new(false);
+
+/// Set whether the EXIF orientation of JPEG images is ignored when rasterizing charts.
+///
+/// By default, JPEG images are rotated and flipped according to their EXIF orientation, as
+/// browsers display them. Ignoring the orientation restores the behavior of earlier versions,
+/// which drew the pixels as they're stored.
+pub fn set_ignore_exif_orientation(ignore: bool) {
+    IGNORE_EXIF_ORIENTATION.store(ignore, Ordering::Relaxed);
+}
+
+/// Whether the EXIF orientation of JPEG images is ignored when rasterizing charts
+pub fn ignore_exif_orientation() -> bool {
+    IGNORE_EXIF_ORIENTATION.load(Ordering::Relaxed)
+}
+
+/// Default delay before the first retry of a failed fetch, in milliseconds
+pub const DEFAULT_FETCH_RETRY_BACKOFF_MS: u64 = 200;
+
+/// Retry policy for transient failures when fetching remote data and images.
+///
+/// Connection errors and 5xx responses are retried with exponential backoff. 4xx responses
+/// are never retried.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FetchRetry {
+    /// Number of retries after the initial attempt
+    pub retries: u32,
+    /// Delay before the first retry, doubled for each subsequent retry
+    pub backoff_ms: u64,
+}
+
+impl FetchRetry {
+    /// Build a retry policy from the `fetch_retries` and `fetch_retry_backoff_ms` options,
+    /// or None if retries are disabled
+    pub fn from_opts(retries: Option<u32>, backoff_ms: Option<u64>) -> Option<Self> {
+        match retries {
+            None | Some(0) => None,
+            Some(retries) => Some(Self {
+                retries,
+                backoff_ms: backoff_ms.unwrap_or(DEFAULT_FETCH_RETRY_BACKOFF_MS),
+            }),
+        }
+    }
+
+    /// Delay before the given retry (starting from 1), with up to 50% jitter added
+    pub fn delay(&self, retry: u32) -> Duration {
+        let base = self
+            .backoff_ms
+            .saturating_mul(1u64 << (retry.saturating_sub(1)).min(16));
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.subsec_nanos() as u64)
+            .unwrap_or_default();
+        let jitter = nanos % (base / 2 + 1);
+        Duration::from_millis(base.saturating_add(jitter))
+    }
+}
+
+/// Evaluate `f` with remote images loaded on the current thread retried according to
+/// `fetch_retry`
+pub fn with_fetch_retry<T>(fetch_retry: Option<FetchRetry>, f: impl FnOnce() -> T) -> T {
+    let previous = FETCH_RETRY.with(|cell| cell.replace(fetch_retry));
+    let result = f();
+    FETCH_RETRY.with(|cell| cell.set(previous));
+    result
+}
+
+/// Evaluate `f`, which rasterizes `svg` on the current thread, and return the warnings for the
+/// images of `svg` that fail to load when `lenient` is true, along with the result of `f`.
+/// Images that fail to load are left out either way
+pub fn with_lenient_images<T>(
+    lenient: bool,
+    svg: &str,
+    f: impl FnOnce() -> T,
+) -> (T, Vec<RenderWarning>) {
+    if !lenient {
+        return (f(), Vec::new());
+    }
+    let previous = IMAGE_FAILURES.with(|cell| cell.replace(Some(Vec::new())));
+    let result = f();
+    let failures = IMAGE_FAILURES
+        .with(|cell| cell.replace(previous))
+        .unwrap_or_default();
+    if failures.is_empty() {
+        return (result, Vec::new());
+    }
+    let marks = image_mark_names(svg);
+    let warnings = failures
+        .into_iter()
+        .map(|href| RenderWarning {
+            mark: marks.get(&href).cloned(),
+            message: format!("Failed to load image: {href}"),
+        })
+        .collect();
+    (result, warnings)
+}
+
+/// Record an image that failed to load, if failures are being collected
+fn record_image_failure(href: &str) {
+    IMAGE_FAILURES.with(|cell| {
+        if let Some(failures) = cell.borrow_mut().as_mut() {
+            failures.push(href.to_string());
+        }
+    });
+}
+
+/// Names of the marks that the images of an SVG image generated by Vega belong to, keyed by
+/// the images' hrefs. Vega renders each mark as a group with the "role-mark" class, followed
+/// by the name of the mark when it has one
+fn image_mark_names(svg: &str) -> HashMap<String, String> {
+    let Ok(doc) = roxmltree::Document::parse(svg) else {
+        return HashMap::new();
+    };
+    let mut names = HashMap::new();
+    for image in doc
+        .descendants()
+        .filter(|node| node.tag_name().name() == "image")
+    {
+        let Some(href) = image
+            .attributes()
+            .find(|attr| attr.name() == "href")
+            .map(|attr| attr.value())
+        else {
+            continue;
+        };
+        let mark = image.ancestors().find_map(|node| {
+            let mut classes = node.attribute("class")?.split_whitespace();
+            classes.clone().any(|class| class == "role-mark").then(|| {
+                classes.find(|class| !class.starts_with("mark-") && !class.starts_with("role-"))
+            })
+        });
+        if let Some(Some(mark)) = mark {
+            names.insert(href.to_string(), mark.to_string());
+        }
+    }
+    names
+}
+
+/// Download a remote image, retrying connection errors and 5xx responses according to the
+/// active fetch retry policy.
+///
+/// Returns the image bytes and content type, or None for each on failure
+async fn fetch_image(href: &str) -> (Option<Vec<u8>>, Option<String>) {
+    let fetch_retry = FETCH_RETRY.with(|cell| cell.get());
+    let max_attempts = fetch_retry.map(|r| r.retries).unwrap_or(0) + 1;
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        let retry_after = |attempt: u32| {
+            fetch_retry
+                .filter(|_| attempt < max_attempts)
+                .map(|r| r.delay(attempt))
+        };
+        match REQWEST_CLIENT.get(href).send().await {
+            Ok(response) => {
+                let content_type = response
+                    .headers()
+                    .get("Content-Type")
+                    .and_then(|h| h.to_str().ok().map(|c| c.to_string()));
+
+                // Check status code.
+                let status = response.status();
+                if status == StatusCode::OK {
+                    return (
+                        response.bytes().await.ok().map(|b| b.to_vec()),
+                        content_type,
+                    );
+                }
+                if status.is_server_error() {
+                    if let Some(delay) = retry_after(attempt) {
+                        info!(
+                            target: LOG_TARGET,
+                            "Retrying image url {href} after status code {status:?}"
+                        );
+                        tokio::time::sleep(delay).await;
+                        continue;
+                    }
+                }
+                let msg = response
+                    .bytes()
+                    .await
+                    .map(|b| String::from_utf8_lossy(b.as_ref()).to_string());
+                let attempts = attempts_message(attempt);
+                if let Ok(msg) = msg {
+                    error!(
+                        target: LOG_TARGET,
+                        "Failed to load image from url {} with status code {:?}{}\n{}",
+                        href, status, attempts, msg
+                    );
+                } else {
+                    error!(
+                        target: LOG_TARGET,
+                        "Failed to load image from url {} with status code {:?}{}",
+                        href, status, attempts
+                    );
+                }
+                return (None, None);
+            }
+            Err(err) => {
+                if let Some(delay) = retry_after(attempt) {
+                    info!(target: LOG_TARGET, "Retrying image url {href} after error: {err}");
+                    tokio::time::sleep(delay).await;
+                    continue;
+                }
+                if fetch_retry.is_some() {
+                    error!(
+                        target: LOG_TARGET,
+                        "Failed to load image from url {}{}: {}",
+                        href,
+                        attempts_message(attempt),
+                        err
+                    );
+                }
+                return (None, None);
+            }
+        }
+    }
+}
+
+/// Describe the number of attempts made for a fetch that failed after being retried
+fn attempts_message(attempts: u32) -> String {
+    if attempts > 1 {
+        format!(" after {attempts} attempts")
+    } else {
+        String::new()
+    }
+}
+
 /// Custom image url string resolver that handles downloading remote files
 /// (The default usvg implementation only supports local image files)
 pub fn custom_string_resolver() -> usvg::ImageHrefStringResolverFn<'static> {
+    let resolve = download_string_resolver();
+    Box::new(move |href: &str, opts: &Options| {
+        let image = resolve(href, opts);
+        // Images without an href were already left out when the SVG was generated
+        if image.is_none() && !href.is_empty() {
+            record_image_failure(href);
+        }
+        image
+    })
+}
+
+fn download_string_resolver() -> usvg::ImageHrefStringResolverFn<'static> {
     let default_string_resolver = ImageHrefResolver::default_string_resolver();
 
     Box::new(move |href: &str, opts: &Options| {
-        info!("Resolving image: {href}");
+        info!(target: LOG_TARGET, "Resolving image: {href}");
         if href.starts_with("http://") || href.starts_with("https://") {
             // Download image to temporary file with reqwest
-            let (bytes, content_type): (Option<_>, Option<_>) = task::block_in_place(move || {
-                IMAGE_TOKIO_RUNTIME.block_on(async {
-                    if let Ok(response) = REQWEST_CLIENT.get(href).send().await {
-                        let content_type = response.headers().get("Content-Type")
-                            .and_then(|h| h.to_str().ok().map(|c| c.to_string()));
-
-                        // Check status code.
-                        match response.status() {
-                            StatusCode::OK => (response.bytes().await.ok(), content_type),
-                            status => {
-                                let msg = response
-                                    .bytes()
-                                    .await
-                                    .map(|b| String::from_utf8_lossy(b.as_ref()).to_string());
-                                if let Ok(msg) = msg {
-                                    error!(
-                                        "Failed to load image from url {} with status code {:?}\n{}",
-                                        href, status, msg
-                                    );
-                                } else {
-                                    error!(
-                                        "Failed to load image from url {} with status code {:?}",
-                                        href, status
-                                    );
-                                }
-                                (None, None)
-                            }
-                        }
-                    } else {
-                        (None, None)
-                    }
-                })
-            });
+            let (bytes, content_type) =
+                task::block_in_place(move || IMAGE_TOKIO_RUNTIME.block_on(fetch_image(href)));
 
             // Compute file extension, which usvg uses to infer the image type
             let href_path = std::path::Path::new(href);
@@ -74,6 +295,7 @@ pub fn custom_string_resolver() -> usvg::ImageHrefStringResolverFn<'static> {
                             "image/jpeg" => ".jpg".to_string(),
                             "image/png" => ".png".to_string(),
                             "image/gif" => ".gif".to_string(),
+                            "image/webp" => ".webp".to_string(),
                             "image/svg+xml" => ".svg".to_string(),
                             _ => String::new(),
                         }
@@ -83,6 +305,30 @@ pub fn custom_string_resolver() -> usvg::ImageHrefStringResolverFn<'static> {
                 });
 
             if let Some(bytes) = bytes {
+                let mime_type = sniff_image_mime_type(bytes.as_ref()).or_else(|| {
+                    content_type
+                        .as_deref()
+                        .map(|c| c.split(';').next().unwrap_or_default().trim())
+                        .filter(|c| c.starts_with("image/"))
+                });
+                match mime_type {
+                    Some("image/avif") => return decode_avif_image(href, bytes.as_ref()),
+                    Some("image/jpeg") => {
+                        if let Some(image) = orient_jpeg_image(href, bytes.as_ref()) {
+                            return Some(image);
+                        }
+                    }
+                    Some(mime_type) if !is_supported_mime_type(mime_type) => {
+                        error!(
+                            target: LOG_TARGET,
+                            "{}",
+                            unsupported_image_message(href, mime_type)
+                        );
+                        return None;
+                    }
+                    _ => {}
+                }
+
                 // Create the temporary file (maybe with an extension)
                 let mut builder = tempfile::Builder::new();
                 builder.suffix(extension.as_str());
@@ -97,9 +343,243 @@ pub fn custom_string_resolver() -> usvg::ImageHrefStringResolverFn<'static> {
                     }
                 }
             }
+        } else {
+            // Check the magic bytes of local files for formats that usvg doesn't handle
+            let path = opts.get_abs_path(std::path::Path::new(href));
+            let mut header = [0u8; 16];
+            let header_len = std::fs::File::open(&path)
+                .and_then(|mut file| file.read(&mut header))
+                .unwrap_or(0);
+            match sniff_image_mime_type(&header[..header_len]) {
+                Some("image/avif") => {
+                    return std::fs::read(&path)
+                        .ok()
+                        .and_then(|data| decode_avif_image(href, &data));
+                }
+                Some("image/jpeg") => {
+                    let oriented = std::fs::read(&path)
+                        .ok()
+                        .and_then(|data| orient_jpeg_image(href, &data));
+                    if oriented.is_some() {
+                        return oriented;
+                    }
+                }
+                Some(mime_type) if !is_supported_mime_type(mime_type) => {
+                    error!(target: LOG_TARGET, "{}", unsupported_image_message(href, mime_type));
+                    return None;
+                }
+                _ => {}
+            }
         }
 
         // Delegate to default implementation
         default_string_resolver(href, opts)
     })
 }
+
+/// Custom data url resolver that adds AVIF support, applies the EXIF orientation of JPEG
+/// images, and reports unsupported image formats
+pub fn custom_data_resolver() -> usvg::ImageHrefDataResolverFn<'static> {
+    let default_data_resolver = ImageHrefResolver::default_data_resolver();
+
+    Box::new(move |mime: &str, data: Arc<Vec<u8>>, opts: &Options| {
+        let mime_type = sniff_image_mime_type(&data).unwrap_or(mime);
+        if mime_type == "image/avif" {
+            return decode_avif_image("data url", &data);
+        } else if mime_type == "image/jpeg" {
+            if let Some(image) = orient_jpeg_image("data url", &data) {
+                return Some(image);
+            }
+        } else if mime_type.starts_with("image/") && !is_supported_mime_type(mime_type) {
+            error!(target: LOG_TARGET, "{}", unsupported_image_message("data url", mime_type));
+            return None;
+        }
+
+        // Delegate to default implementation
+        default_data_resolver(mime, data, opts)
+    })
+}
+
+/// Detect the MIME type of image data from its magic bytes
+pub fn sniff_image_mime_type(data: &[u8]) -> Option<&'static str> {
+    if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some("image/png")
+    } else if data.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some("image/jpeg")
+    } else if data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a") {
+        Some("image/gif")
+    } else if data.len() >= 12 && &data[..4] == b"RIFF" && &data[8..12] == b"WEBP" {
+        Some("image/webp")
+    } else if data.len() >= 12 && &data[4..8] == b"ftyp" {
+        match &data[8..12] {
+            b"avif" | b"avis" => Some("image/avif"),
+            b"heic" | b"heix" | b"mif1" => Some("image/heic"),
+            _ => None,
+        }
+    } else if data.starts_with(b"BM") {
+        Some("image/bmp")
+    } else if data.starts_with(b"II*\0") || data.starts_with(b"MM\0*") {
+        Some("image/tiff")
+    } else {
+        None
+    }
+}
+
+fn is_supported_mime_type(mime_type: &str) -> bool {
+    matches!(
+        mime_type,
+        "image/png" | "image/jpeg" | "image/jpg" | "image/gif" | "image/webp" | "image/svg+xml"
+    ) || (cfg!(feature = "avif") && mime_type == "image/avif")
+}
+
+fn unsupported_image_message(href: &str, mime_type: &str) -> String {
+    let avif_note = if cfg!(feature = "avif") {
+        ", AVIF"
+    } else {
+        " (AVIF requires the avif feature)"
+    };
+    format!(
+        "Failed to load image {href} with unsupported format {mime_type}. \
+        Supported formats are PNG, JPEG, GIF, WebP, SVG{avif_note}"
+    )
+}
+
+/// Encode a decoded image as PNG, which resvg can render
+fn encode_png_image(image: &image::DynamicImage) -> Result<Vec<u8>, crate::anyhow::Error> {
+    let rgba = image.to_rgba8();
+    let mut png_data: Vec<u8> = Vec::new();
+    let mut encoder = png::Encoder::new(&mut png_data, rgba.width(), rgba.height());
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.write_header()?.write_image_data(rgba.as_raw())?;
+    Ok(png_data)
+}
+
+/// Decode a JPEG image that has an EXIF orientation, and re-encode it as PNG with the
+/// orientation applied to its pixels, so that it's drawn as browsers display it.
+///
+/// Returns None when the image should be drawn as it's stored: when it has no orientation,
+/// when orientations are ignored, or when it fails to decode.
+fn orient_jpeg_image(href: &str, data: &[u8]) -> Option<ImageKind> {
+    if ignore_exif_orientation() {
+        return None;
+    }
+    let orientation = jpeg_exif_orientation(data).filter(|o| (2..=8).contains(o))?;
+    let orient = || -> Result<Vec<u8>, crate::anyhow::Error> {
+        let image = image::load_from_memory_with_format(data, image::ImageFormat::Jpeg)?;
+        let image = match orientation {
+            2 => image.fliph(),
+            3 => image.rotate180(),
+            4 => image.flipv(),
+            5 => image.rotate90().fliph(),
+            6 => image.rotate90(),
+            7 => image.rotate270().fliph(),
+            _ => image.rotate270(),
+        };
+        encode_png_image(&image)
+    };
+    match orient() {
+        Ok(png_data) => Some(ImageKind::PNG(Arc::new(png_data))),
+        Err(err) => {
+            error!(
+                target: LOG_TARGET,
+                "Failed to apply the EXIF orientation of image {href}: {err}"
+            );
+            None
+        }
+    }
+}
+
+/// Read the EXIF orientation tag of a JPEG image, which is a value from 1 to 8
+fn jpeg_exif_orientation(data: &[u8]) -> Option<u16> {
+    const ORIENTATION_TAG: u32 = 0x0112;
+
+    // Walk the marker segments that precede the image data, looking for the APP1 segment
+    // that holds the EXIF data
+    let mut pos = 2;
+    while pos + 4 <= data.len() && data[pos] == 0xFF {
+        let marker = data[pos + 1];
+        if marker == 0xD9 || marker == 0xDA {
+            // End of image or start of scan
+            return None;
+        }
+        let len = u16::from_be_bytes([data[pos + 2], data[pos + 3]]) as usize;
+        let segment = data.get(pos + 4..pos + 2 + len)?;
+        if let Some(tiff) = segment.strip_prefix(b"Exif\0\0").filter(|_| marker == 0xE1) {
+            let big_endian = match tiff.get(..2)? {
+                b"MM" => true,
+                b"II" => false,
+                _ => return None,
+            };
+            let read = |offset: usize, size: usize| -> Option<u32> {
+                let bytes = tiff.get(offset..offset + size)?;
+                let fold = |n: u32, b: &u8| n << 8 | *b as u32;
+                Some(if big_endian {
+                    bytes.iter().fold(0, fold)
+                } else {
+                    bytes.iter().rev().fold(0, fold)
+                })
+            };
+            if read(2, 2)? != 42 {
+                return None;
+            }
+            // The orientation is stored in the first image file directory
+            let ifd = read(4, 4)? as usize;
+            for i in 0..read(ifd, 2)? as usize {
+                let entry = ifd + 2 + i * 12;
+                if read(entry, 2)? == ORIENTATION_TAG {
+                    return read(entry + 8, 2).map(|o| o as u16);
+                }
+            }
+            return None;
+        }
+        pos += 2 + len;
+    }
+    None
+}
+
+/// Decode AVIF data and re-encode it as PNG, which resvg can render
+#[cfg(feature = "avif")]
+fn decode_avif_image(href: &str, data: &[u8]) -> Option<ImageKind> {
+    let decode = || -> Result<Vec<u8>, crate::anyhow::Error> {
+        let image = image::load_from_memory_with_format(data, image::ImageFormat::Avif)?;
+        encode_png_image(&image)
+    };
+    match decode() {
+        Ok(png_data) => Some(ImageKind::PNG(Arc::new(png_data))),
+        Err(err) => {
+            error!(target: LOG_TARGET, "Failed to decode AVIF image {href}: {err}");
+            None
+        }
+    }
+}
+
+#[cfg(not(feature = "avif"))]
+fn decode_avif_image(href: &str, _data: &[u8]) -> Option<ImageKind> {
+    error!(target: LOG_TARGET, "{}", unsupported_image_message(href, "image/avif"));
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_image_mark_names() {
+        let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink">
+            <g class="mark-group role-frame root">
+                <g class="mark-image role-mark layer_1_marks">
+                    <image xlink:href="/images/logo.png"/>
+                </g>
+                <g class="mark-image role-mark">
+                    <image href="https://example.com/unnamed.png"/>
+                </g>
+            </g>
+        </svg>"#;
+        let names = image_mark_names(svg);
+        assert_eq!(
+            names,
+            HashMap::from([("/images/logo.png".to_string(), "layer_1_marks".to_string())])
+        );
+    }
+}
@@ -0,0 +1,115 @@
+use crate::anyhow::anyhow;
+use deno_core::error::AnyError;
+use regex::{Captures, Regex};
+use std::borrow::Cow;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+lazy_static! {
+    /// image-rendering attributes and style declarations with one of the CSS values that
+    /// usvg doesn't parse
+    static ref CSS_IMAGE_RENDERING_RE: Regex = Regex::new(
+        r#"(image-rendering\s*(?:=\s*["']\s*|:\s*))(pixelated|crisp-edges|smooth|high-quality)\b"#
+    )
+    .unwrap();
+}
+
+/// How embedded raster images, like those of image marks, are resampled when they're drawn
+/// at a different size than their own. Images with an image-rendering attribute or style of
+/// their own keep it, whatever this option is
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ImageRendering {
+    /// Smooth images, as browsers do for images that don't set image-rendering
+    #[default]
+    Auto,
+    /// Smooth images with bilinear filtering
+    Smooth,
+    /// Scale images with nearest-neighbor sampling, so that each pixel of the image is drawn
+    /// as a sharp square. Suited to pixel-art logos and icons
+    Pixelated,
+}
+
+impl ImageRendering {
+    pub(crate) fn to_usvg(self) -> usvg::ImageRendering {
+        match self {
+            ImageRendering::Auto | ImageRendering::Smooth => usvg::ImageRendering::OptimizeQuality,
+            ImageRendering::Pixelated => usvg::ImageRendering::OptimizeSpeed,
+        }
+    }
+}
+
+impl Display for ImageRendering {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let image_rendering = match self {
+            ImageRendering::Auto => "auto",
+            ImageRendering::Smooth => "smooth",
+            ImageRendering::Pixelated => "pixelated",
+        };
+        std::fmt::Display::fmt(image_rendering, f)
+    }
+}
+
+impl FromStr for ImageRendering {
+    type Err = AnyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.trim().to_ascii_lowercase().as_str() {
+            "auto" => Self::Auto,
+            "smooth" => Self::Smooth,
+            "pixelated" => Self::Pixelated,
+            _ => return Err(anyhow!("Unsupported image rendering: {}", s)),
+        })
+    }
+}
+
+/// Rewrite the CSS values of image-rendering attributes and styles in an SVG image to the
+/// SVG 1.1 values that usvg understands. usvg ignores values like `pixelated`, which would
+/// otherwise make the images that set them fall back to the image rendering of the
+/// conversion
+pub(crate) fn normalize_image_rendering(svg: &str) -> Cow<'_, str> {
+    if !svg.contains("image-rendering") {
+        return Cow::Borrowed(svg);
+    }
+    CSS_IMAGE_RENDERING_RE.replace_all(svg, |caps: &Captures| {
+        let value = match &caps[2] {
+            "pixelated" | "crisp-edges" => "optimizeSpeed",
+            _ => "optimizeQuality",
+        };
+        format!("{}{}", &caps[1], value)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_image_rendering() {
+        assert_eq!(
+            normalize_image_rendering(r#"<image image-rendering="pixelated" href="a.png"/>"#),
+            r#"<image image-rendering="optimizeSpeed" href="a.png"/>"#
+        );
+        assert_eq!(
+            normalize_image_rendering(
+                r#"<image style="image-rendering: optimizeSpeed; image-rendering: pixelated;"/>"#
+            ),
+            r#"<image style="image-rendering: optimizeSpeed; image-rendering: optimizeSpeed;"/>"#
+        );
+        assert_eq!(
+            normalize_image_rendering(r#"<image image-rendering='smooth'/>"#),
+            r#"<image image-rendering='optimizeQuality'/>"#
+        );
+        let svg = r#"<image image-rendering="auto"/>"#;
+        assert!(matches!(normalize_image_rendering(svg), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn test_parse_image_rendering() {
+        assert_eq!(
+            ImageRendering::from_str("Pixelated").unwrap(),
+            ImageRendering::Pixelated
+        );
+        assert_eq!(ImageRendering::Smooth.to_string(), "smooth");
+        assert!(ImageRendering::from_str("nearest").is_err());
+    }
+}
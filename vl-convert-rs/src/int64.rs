@@ -0,0 +1,231 @@
+use serde_json::{Number, Value};
+
+/// Largest integer that JavaScript numbers represent exactly (Number.MAX_SAFE_INTEGER)
+const MAX_SAFE_INTEGER: u64 = (1 << 53) - 1;
+
+/// Integers in the inline data of a spec that were passed to JavaScript as decimal strings,
+/// because converting them to JavaScript numbers would round them.
+///
+/// Inline data is the `values` of `data` definitions and the datasets of a Vega-Lite
+/// `datasets` object. Integers elsewhere in a spec are passed to JavaScript as numbers.
+#[derive(Debug, Default)]
+pub(crate) struct Int64Strings {
+    datasets: Vec<EncodedValues>,
+}
+
+/// The inline values of a data definition or dataset, as passed to JavaScript, along with
+/// the JSON pointers of the integers in them that were replaced by strings
+#[derive(Debug)]
+struct EncodedValues {
+    values: Value,
+    pointers: Vec<String>,
+}
+
+impl Int64Strings {
+    /// Copy a Vega or Vega-Lite spec for conversion. When `enabled`, integers in inline data
+    /// that JavaScript numbers can't represent exactly are replaced by their decimal strings
+    pub fn encode(spec: &Value, enabled: bool) -> (Value, Self) {
+        let mut spec = spec.clone();
+        let mut int64_strings = Self::default();
+        if enabled {
+            visit_inline_data(&mut spec, &mut |values| {
+                let mut pointers = Vec::new();
+                visit_scalars(values, String::new(), &mut |pointer, value| {
+                    if let Value::Number(n) = value {
+                        if is_unsafe_integer(n) {
+                            *value = Value::String(n.to_string());
+                            pointers.push(pointer);
+                        }
+                    }
+                });
+                if !pointers.is_empty() {
+                    int64_strings.datasets.push(EncodedValues {
+                        values: values.clone(),
+                        pointers,
+                    });
+                }
+            });
+        }
+        (spec, int64_strings)
+    }
+
+    /// Restore the integers that were replaced by strings in the inline data of a spec
+    /// returned from JavaScript. Inline values are matched to the values that were encoded
+    /// by content, as compiling a spec may move or rename them, and only the strings at the
+    /// positions of replaced integers are restored
+    pub fn decode(&self, spec: &mut Value) {
+        if self.datasets.is_empty() {
+            return;
+        }
+        visit_inline_data(spec, &mut |values| {
+            let Some(encoded) = self.datasets.iter().find(|ds| ds.values == *values) else {
+                return;
+            };
+            for pointer in &encoded.pointers {
+                if let Some(value) = values.pointer_mut(pointer) {
+                    if let Some(n) = value.as_str().and_then(|s| s.parse::<Number>().ok()) {
+                        *value = Value::Number(n);
+                    }
+                }
+            }
+        });
+    }
+
+    /// Restore a value of `field` returned from JavaScript outside of a spec (e.g. the facet
+    /// value of a cell), when it's the string of an integer that was replaced in that field
+    pub fn decode_field_value(&self, field: &str, value: &mut Value) {
+        let Some(s) = value.as_str() else {
+            return;
+        };
+        let suffix = format!("/{}", field.replace('~', "~0").replace('/', "~1"));
+        let replaced = self.datasets.iter().any(|ds| {
+            ds.pointers.iter().any(|pointer| {
+                pointer.ends_with(&suffix) && ds.values.pointer(pointer) == Some(&*value)
+            })
+        });
+        if replaced {
+            if let Ok(n) = s.parse::<Number>() {
+                *value = Value::Number(n);
+            }
+        }
+    }
+}
+
+fn is_unsafe_integer(n: &Number) -> bool {
+    if let Some(n) = n.as_u64() {
+        n > MAX_SAFE_INTEGER
+    } else if let Some(n) = n.as_i64() {
+        n.unsigned_abs() > MAX_SAFE_INTEGER
+    } else {
+        false
+    }
+}
+
+/// Call `f` on the values of every data definition and dataset in a spec. Values that are
+/// strings hold the text of a CSV or TSV file, and aren't visited
+fn visit_inline_data(spec: &mut Value, f: &mut dyn FnMut(&mut Value)) {
+    fn visit_values(values: &mut Value, f: &mut dyn FnMut(&mut Value)) {
+        if values.is_object() || values.is_array() {
+            f(values);
+        }
+    }
+    match spec {
+        Value::Object(obj) => {
+            for (key, value) in obj.iter_mut() {
+                match (key.as_str(), value) {
+                    // Vega-Lite data definition
+                    ("data", Value::Object(data)) => {
+                        if let Some(values) = data.get_mut("values") {
+                            visit_values(values, f);
+                        }
+                    }
+                    // Vega data definitions
+                    ("data", Value::Array(data)) => {
+                        for data in data.iter_mut() {
+                            if let Some(values) = data.get_mut("values") {
+                                visit_values(values, f);
+                            }
+                        }
+                    }
+                    ("datasets", Value::Object(datasets)) => {
+                        for values in datasets.values_mut() {
+                            visit_values(values, f);
+                        }
+                    }
+                    (_, value) => visit_inline_data(value, f),
+                }
+            }
+        }
+        Value::Array(arr) => {
+            for value in arr.iter_mut() {
+                visit_inline_data(value, f);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Call `f` on every scalar in `value`, along with its JSON pointer relative to the value
+/// that the visit started from
+fn visit_scalars(value: &mut Value, pointer: String, f: &mut dyn FnMut(String, &mut Value)) {
+    match value {
+        Value::Object(obj) => {
+            for (key, v) in obj.iter_mut() {
+                let key = key.replace('~', "~0").replace('/', "~1");
+                visit_scalars(v, format!("{pointer}/{key}"), f);
+            }
+        }
+        Value::Array(arr) => {
+            for (i, v) in arr.iter_mut().enumerate() {
+                visit_scalars(v, format!("{pointer}/{i}"), f);
+            }
+        }
+        _ => f(pointer, value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_round_trip() {
+        let spec = json!({
+            "data": {"values": [
+                {"id": 9007199254740991_u64, "big": 9007199254740992_u64},
+                {"id": 9007199254740993_u64, "neg": -9007199254740993_i64, "x": 1.5},
+                {"id": "9007199254740993"}
+            ]},
+            "datasets": {"other": [[18446744073709551615_u64]]},
+            "layer": [{"data": {"values": "a\n9007199254740993"}}],
+            "encoding": {"x": {"scale": {"domain": [0, 9007199254740993_u64]}}}
+        });
+        let (encoded, int64_strings) = Int64Strings::encode(&spec, true);
+        assert_eq!(
+            encoded,
+            json!({
+                "data": {"values": [
+                    {"id": 9007199254740991_u64, "big": "9007199254740992"},
+                    {"id": "9007199254740993", "neg": "-9007199254740993", "x": 1.5},
+                    {"id": "9007199254740993"}
+                ]},
+                "datasets": {"other": [["18446744073709551615"]]},
+                "layer": [{"data": {"values": "a\n9007199254740993"}}],
+                "encoding": {"x": {"scale": {"domain": [0, 9007199254740993_u64]}}}
+            })
+        );
+
+        // Vega data definitions are arrays. Strings in the input data stay strings
+        let mut vg_spec =
+            json!({"data": [{"name": "source", "values": encoded["data"]["values"]}]});
+        int64_strings.decode(&mut vg_spec);
+        assert_eq!(vg_spec["data"][0]["values"], spec["data"]["values"]);
+    }
+
+    #[test]
+    fn test_decode_field_value() {
+        let spec = json!({"data": {"values": [
+            {"id": 9007199254740993_u64, "label": "9007199254740995"}
+        ]}});
+        let (_, int64_strings) = Int64Strings::encode(&spec, true);
+        let mut value = json!("9007199254740993");
+        int64_strings.decode_field_value("id", &mut value);
+        assert_eq!(value, json!(9007199254740993_u64));
+
+        // Only strings of replaced integers in the same field are restored
+        for (field, s) in [("label", "9007199254740993"), ("id", "9007199254740995")] {
+            let mut value = json!(s);
+            int64_strings.decode_field_value(field, &mut value);
+            assert_eq!(value, json!(s));
+        }
+    }
+
+    #[test]
+    fn test_disabled() {
+        let spec = json!({"data": {"values": [{"id": 9007199254740993_u64}]}});
+        let (encoded, int64_strings) = Int64Strings::encode(&spec, false);
+        assert_eq!(encoded, spec);
+        assert!(int64_strings.datasets.is_empty());
+    }
+}
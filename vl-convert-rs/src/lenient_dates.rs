@@ -0,0 +1,534 @@
+use crate::render_warnings::RenderWarning;
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use regex::Regex;
+use serde_json::Value;
+use std::borrow::Cow;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::ops::Range;
+
+/// Formats of the date strings that the `lenient_dates` option rewrites to ISO 8601, in the
+/// order they're tried, as chrono format strings. Each field of a dataset is parsed with the
+/// first format that parses all of its dates, so dates with a slash are read month first
+/// like browsers read them, unless one of them only makes sense day first
+pub const LENIENT_DATE_FORMATS: &[&str] = &[
+    // US style, month first
+    "%m/%d/%Y %I:%M:%S %p",
+    "%m/%d/%Y %I:%M %p",
+    "%m/%d/%Y %H:%M:%S",
+    "%m/%d/%Y %H:%M",
+    "%m/%d/%Y",
+    // European style, day first
+    "%d/%m/%Y %H:%M:%S",
+    "%d/%m/%Y %H:%M",
+    "%d/%m/%Y",
+    "%d.%m.%Y %H:%M:%S",
+    "%d.%m.%Y %H:%M",
+    "%d.%m.%Y",
+    // Month names
+    "%b %d, %Y",
+    "%d %b %Y",
+    // ISO 8601 dates with a space or slashes
+    "%Y-%m-%d %H:%M:%S",
+    "%Y-%m-%d %H:%M",
+    "%Y/%m/%d",
+];
+
+lazy_static! {
+    /// Date strings in the ISO 8601 format that JavaScript parses
+    static ref ISO_DATE_RE: Regex =
+        Regex::new(r"^[+-]?\d{4,6}-\d{2}(-\d{2}(T[\d:.]+(Z|[+-]\d{2}:?\d{2})?)?)?$").unwrap();
+}
+
+/// Rewrite the non-ISO date strings of the temporal fields of a Vega-Lite spec's inline
+/// data to ISO 8601, when `lenient_dates` is set. Temporal fields are the fields of
+/// encodings and other field definitions with a temporal type or a time unit, and fields
+/// that `format.parse` parses as dates. Their values in inline `values` and top-level
+/// `datasets` are parsed with [`LENIENT_DATE_FORMATS`], and rewritten as local date-times
+/// like `2024-03-27T17:00:00`, which JavaScript parses in the local time zone as browsers
+/// parse the original strings. Fields with dates that none of the formats parse are left
+/// as they are. Inline data that's CSV, TSV, or DSV text is rewritten in place, leaving
+/// the rest of the text as it is.
+///
+/// Returns the spec, and a warning that lists the rewritten fields when there are any.
+/// Inline data that's text in another format, like JSON, isn't rewritten, and is reported
+/// with a warning. Data that's loaded from urls isn't rewritten
+pub(crate) fn with_lenient_dates(
+    spec: &Value,
+    lenient_dates: bool,
+) -> (Cow<'_, Value>, Vec<RenderWarning>) {
+    if !lenient_dates {
+        return (Cow::Borrowed(spec), Vec::new());
+    }
+    let mut fields = HashSet::new();
+    collect_temporal_fields(spec, &mut fields);
+    if fields.is_empty() {
+        return (Cow::Borrowed(spec), Vec::new());
+    }
+
+    let mut dataset_formats = HashMap::new();
+    collect_dataset_formats(spec, &mut dataset_formats);
+
+    let mut rewritten_spec = spec.clone();
+    let mut inline_data = InlineData {
+        fields: &fields,
+        dataset_formats: &dataset_formats,
+        rewritten: BTreeSet::new(),
+        skipped_text: false,
+    };
+    inline_data.rewrite(&mut rewritten_spec);
+
+    let mut warnings = Vec::new();
+    if inline_data.skipped_text {
+        warnings.push(RenderWarning {
+            mark: None,
+            message: "Left the dates of inline data that's text in a format other than CSV, \
+                TSV, or DSV as they are"
+                .to_string(),
+        });
+    }
+    if inline_data.rewritten.is_empty() {
+        return (Cow::Borrowed(spec), warnings);
+    }
+    let fields = inline_data
+        .rewritten
+        .iter()
+        .map(|field| format!("{field:?}"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    warnings.insert(
+        0,
+        RenderWarning {
+            mark: None,
+            message: format!("Rewrote the non-ISO 8601 dates of fields {fields} to ISO 8601"),
+        },
+    );
+    (Cow::Owned(rewritten_spec), warnings)
+}
+
+/// Collect the fields that a spec declares temporal
+fn collect_temporal_fields(value: &Value, fields: &mut HashSet<String>) {
+    match value {
+        Value::Object(obj) => {
+            if let Some(Value::String(field)) = obj.get("field") {
+                if obj.get("type").and_then(Value::as_str) == Some("temporal")
+                    || obj.contains_key("timeUnit")
+                {
+                    fields.insert(field.clone());
+                }
+            }
+            if let Some(Value::Object(parse)) = obj.get("parse") {
+                for (field, parse) in parse {
+                    if parse.as_str() == Some("date") {
+                        fields.insert(field.clone());
+                    }
+                }
+            }
+            for (key, value) in obj {
+                if key != "values" && key != "datasets" {
+                    collect_temporal_fields(value, fields);
+                }
+            }
+        }
+        Value::Array(values) => {
+            for value in values {
+                collect_temporal_fields(value, fields);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Collect the `format` of the named data sources of a spec, which apply to the top-level
+/// datasets with the same names
+fn collect_dataset_formats<'a>(value: &'a Value, formats: &mut HashMap<&'a str, &'a Value>) {
+    match value {
+        Value::Object(obj) => {
+            if let Some(Value::Object(data)) = obj.get("data") {
+                if let (Some(Value::String(name)), Some(format)) =
+                    (data.get("name"), data.get("format"))
+                {
+                    formats.insert(name.as_str(), format);
+                }
+            }
+            for (key, value) in obj {
+                if key != "values" && key != "datasets" {
+                    collect_dataset_formats(value, formats);
+                }
+            }
+        }
+        Value::Array(values) => {
+            for value in values {
+                collect_dataset_formats(value, formats);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Rewrites the dates of `fields` in the inline data of a spec
+struct InlineData<'a> {
+    fields: &'a HashSet<String>,
+    dataset_formats: &'a HashMap<&'a str, &'a Value>,
+    /// Fields whose dates were rewritten
+    rewritten: BTreeSet<String>,
+    /// Whether there's inline data that's text in a format whose dates aren't rewritten
+    skipped_text: bool,
+}
+
+impl InlineData<'_> {
+    fn rewrite(&mut self, value: &mut Value) {
+        match value {
+            Value::Object(obj) => {
+                for (key, value) in obj.iter_mut() {
+                    match (key.as_str(), value) {
+                        ("data", Value::Object(data)) => {
+                            let format = data.get("format").cloned();
+                            if let Some(values) = data.get_mut("values") {
+                                self.rewrite_values(values, format.as_ref());
+                            }
+                        }
+                        ("datasets", Value::Object(datasets)) => {
+                            for (name, values) in datasets.iter_mut() {
+                                let format = self.dataset_formats.get(name.as_str()).copied();
+                                self.rewrite_values(values, format);
+                            }
+                        }
+                        (_, value) => self.rewrite(value),
+                    }
+                }
+            }
+            Value::Array(values) => {
+                for value in values {
+                    self.rewrite(value);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Rewrite the dates of inline values, which are rows, or text in `format`
+    fn rewrite_values(&mut self, values: &mut Value, format: Option<&Value>) {
+        match values {
+            Value::Array(rows) => self.rewrite_rows(rows),
+            Value::String(text) => match dsv_delimiter(format) {
+                Some(delimiter) => self.rewrite_dsv(text, delimiter),
+                None => self.skipped_text = true,
+            },
+            _ => {}
+        }
+    }
+
+    fn rewrite_rows(&mut self, rows: &mut [Value]) {
+        for field in self.fields {
+            let dates = rows
+                .iter()
+                .filter_map(|row| row.get(field)?.as_str())
+                .filter(|date| needs_rewrite(date))
+                .collect::<Vec<_>>();
+            let Some(format) = find_format(&dates) else {
+                continue;
+            };
+            for row in rows.iter_mut() {
+                let Some(Value::String(date)) = row.get_mut(field) else {
+                    continue;
+                };
+                if !needs_rewrite(date) {
+                    continue;
+                }
+                if let Some(iso_date) = to_iso_date(date, format) {
+                    *date = iso_date;
+                }
+            }
+            self.rewritten.insert(field.clone());
+        }
+    }
+
+    /// Rewrite the dates of delimiter-separated text, whose first record is the header.
+    /// Only the cells of rewritten dates are replaced, so the rest of the text, including
+    /// its quoting and line endings, is left as it is
+    fn rewrite_dsv(&mut self, text: &mut String, delimiter: char) {
+        let records = parse_dsv(text, delimiter);
+        let Some((header, rows)) = records.split_first() else {
+            return;
+        };
+        let mut replacements = Vec::new();
+        for field in self.fields {
+            let Some(column) = header.iter().position(|cell| cell.value == *field) else {
+                continue;
+            };
+            let cells = rows
+                .iter()
+                .filter_map(|row| row.get(column))
+                .filter(|cell| needs_rewrite(&cell.value))
+                .collect::<Vec<_>>();
+            let dates = cells
+                .iter()
+                .map(|cell| cell.value.as_ref())
+                .collect::<Vec<_>>();
+            let Some(format) = find_format(&dates) else {
+                continue;
+            };
+            for cell in cells {
+                if let Some(iso_date) = to_iso_date(&cell.value, format) {
+                    replacements.push((cell.range.clone(), iso_date));
+                }
+            }
+            self.rewritten.insert(field.clone());
+        }
+        replacements.sort_by_key(|(range, _)| std::cmp::Reverse(range.start));
+        for (range, iso_date) in replacements {
+            text.replace_range(range, &iso_date);
+        }
+    }
+}
+
+/// Delimiter of inline text in `format`, when it's CSV, TSV, or DSV
+fn dsv_delimiter(format: Option<&Value>) -> Option<char> {
+    let format = format?;
+    match format.get("type")?.as_str()? {
+        "csv" => Some(','),
+        "tsv" => Some('\t'),
+        "dsv" => {
+            let mut delimiter = format.get("delimiter")?.as_str()?.chars();
+            match (delimiter.next(), delimiter.next()) {
+                (Some(delimiter), None) => Some(delimiter),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Cell of delimiter-separated text
+struct DsvCell<'a> {
+    /// Range of the cell in the text, including its quotes
+    range: Range<usize>,
+    /// Text of the cell, without quotes
+    value: Cow<'a, str>,
+}
+
+/// Split delimiter-separated text into records of cells. Cells may be quoted with double
+/// quotes, which are escaped by doubling them, like d3-dsv (and so Vega) parses them
+fn parse_dsv(text: &str, delimiter: char) -> Vec<Vec<DsvCell<'_>>> {
+    let cell_end = |from: usize| {
+        text[from..]
+            .find([delimiter, '\n', '\r'])
+            .map_or(text.len(), |pos| from + pos)
+    };
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut start = 0;
+    loop {
+        let (value, end) = if let Some(quoted) = text[start..].strip_prefix('"') {
+            let mut value = String::new();
+            let mut rest = quoted;
+            loop {
+                match rest.find('"') {
+                    Some(pos) if rest[pos + 1..].starts_with('"') => {
+                        value.push_str(&rest[..=pos]);
+                        rest = &rest[pos + 2..];
+                    }
+                    Some(pos) => {
+                        value.push_str(&rest[..pos]);
+                        rest = &rest[pos + 1..];
+                        break;
+                    }
+                    None => {
+                        value.push_str(rest);
+                        rest = "";
+                        break;
+                    }
+                }
+            }
+            (Cow::Owned(value), cell_end(text.len() - rest.len()))
+        } else {
+            let end = cell_end(start);
+            (Cow::Borrowed(&text[start..end]), end)
+        };
+        record.push(DsvCell {
+            range: start..end,
+            value,
+        });
+
+        match text[end..].chars().next() {
+            Some(c) if c == delimiter => start = end + c.len_utf8(),
+            Some(c) => {
+                records.push(std::mem::take(&mut record));
+                start = end + 1;
+                if c == '\r' && text[start..].starts_with('\n') {
+                    start += 1;
+                }
+                if start == text.len() {
+                    break;
+                }
+            }
+            None => {
+                records.push(record);
+                break;
+            }
+        }
+    }
+    records
+}
+
+/// First of the [`LENIENT_DATE_FORMATS`] that parses all of `dates`, when there are any
+fn find_format(dates: &[&str]) -> Option<&'static str> {
+    if dates.is_empty() {
+        return None;
+    }
+    LENIENT_DATE_FORMATS
+        .iter()
+        .copied()
+        .find(|format| dates.iter().all(|date| parse_date(date, format).is_some()))
+}
+
+/// A date string parsed with a format, as an ISO 8601 local date-time
+fn to_iso_date(date: &str, format: &str) -> Option<String> {
+    let parsed = parse_date(date, format)?;
+    Some(parsed.format("%Y-%m-%dT%H:%M:%S").to_string())
+}
+
+/// Whether a date string isn't empty or in the ISO 8601 format already
+fn needs_rewrite(date: &str) -> bool {
+    !date.trim().is_empty() && !ISO_DATE_RE.is_match(date.trim())
+}
+
+/// Parse a date string with a format, at midnight when the format has no time
+fn parse_date(date: &str, format: &str) -> Option<NaiveDateTime> {
+    let date = date.trim();
+    NaiveDateTime::parse_from_str(date, format)
+        .ok()
+        .or_else(|| {
+            NaiveDate::parse_from_str(date, format)
+                .ok()
+                .map(|date| date.and_time(NaiveTime::MIN))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn rows(spec: &Value) -> Vec<Value> {
+        spec["data"]["values"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|row| row["date"].clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_with_lenient_dates() {
+        let spec = json!({
+            "data": {"values": [
+                {"date": "03/27/2024 5:00 PM", "value": 1},
+                {"date": "2024-03-28", "value": 2},
+                {"date": null, "value": 3}
+            ]},
+            "mark": "line",
+            "encoding": {
+                "x": {"field": "date", "type": "temporal"},
+                "y": {"field": "value", "type": "quantitative"}
+            }
+        });
+        let (rewritten, warnings) = with_lenient_dates(&spec, true);
+        assert_eq!(
+            rows(&rewritten),
+            vec![
+                json!("2024-03-27T17:00:00"),
+                json!("2024-03-28"),
+                Value::Null
+            ]
+        );
+        assert_eq!(
+            warnings[0].message,
+            r#"Rewrote the non-ISO 8601 dates of fields "date" to ISO 8601"#
+        );
+
+        let (unchanged, warnings) = with_lenient_dates(&spec, false);
+        assert!(matches!(unchanged, Cow::Borrowed(_)));
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn test_day_first_dates() {
+        // 27 can't be a month, so the field is parsed day first
+        let spec = json!({
+            "data": {
+                "values": [{"date": "01/03/2024"}, {"date": "27/03/2024"}, {"date": "27.03.2024"}],
+                "format": {"parse": {"date": "date"}}
+            },
+            "mark": "point"
+        });
+        let (rewritten, _) = with_lenient_dates(&spec, true);
+        // Fields with dates in formats that don't match are left as they are
+        assert_eq!(
+            rows(&rewritten),
+            vec![
+                json!("01/03/2024"),
+                json!("27/03/2024"),
+                json!("27.03.2024")
+            ]
+        );
+
+        let spec = json!({
+            "datasets": {"table": [{"date": "01/03/2024"}, {"date": "27/03/2024"}]},
+            "data": {"name": "table"},
+            "mark": "point",
+            "encoding": {"x": {"field": "date", "timeUnit": "yearmonthdate"}}
+        });
+        let (rewritten, warnings) = with_lenient_dates(&spec, true);
+        assert_eq!(
+            rewritten["datasets"]["table"],
+            json!([{"date": "2024-03-01T00:00:00"}, {"date": "2024-03-27T00:00:00"}])
+        );
+        assert_eq!(warnings.len(), 1);
+    }
+
+    #[test]
+    fn test_dsv_dates() {
+        let spec = json!({
+            "data": {
+                "values": "date,label\r\n03/27/2024 5:00 PM,\"a, b\"\r\n\"03/28/2024 9:30 AM\",c\r\n",
+                "format": {"type": "csv"}
+            },
+            "mark": "point",
+            "encoding": {"x": {"field": "date", "type": "temporal"}}
+        });
+        let (rewritten, warnings) = with_lenient_dates(&spec, true);
+        assert_eq!(
+            rewritten["data"]["values"],
+            json!("date,label\r\n2024-03-27T17:00:00,\"a, b\"\r\n2024-03-28T09:30:00,c\r\n")
+        );
+        assert_eq!(warnings.len(), 1);
+
+        // Datasets take the format of the data sources with their names
+        let spec = json!({
+            "datasets": {"table": "id;date\n1;27.03.2024"},
+            "data": {"name": "table", "format": {"type": "dsv", "delimiter": ";"}},
+            "mark": "point",
+            "encoding": {"x": {"field": "date", "type": "temporal"}}
+        });
+        let (rewritten, _) = with_lenient_dates(&spec, true);
+        assert_eq!(
+            rewritten["datasets"]["table"],
+            json!("id;date\n1;2024-03-27T00:00:00")
+        );
+
+        // Text in other formats is left as it is, with a warning
+        let spec = json!({
+            "data": {"values": "[{\"date\": \"03/27/2024\"}]", "format": {"type": "json"}},
+            "mark": "point",
+            "encoding": {"x": {"field": "date", "type": "temporal"}}
+        });
+        let (unchanged, warnings) = with_lenient_dates(&spec, true);
+        assert!(matches!(unchanged, Cow::Borrowed(_)));
+        assert_eq!(
+            warnings[0].message,
+            "Left the dates of inline data that's text in a format other than CSV, TSV, or DSV \
+            as they are"
+        );
+    }
+}
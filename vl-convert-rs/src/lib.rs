@@ -1,10 +1,46 @@
 #![doc = include_str!("../README.md")]
 
+pub mod anonymize;
+pub mod background;
+pub mod browser_shims;
+pub mod cli_schema;
+pub mod config_overrides;
+pub mod container_size;
 pub mod converter;
+pub mod data_export;
+pub mod dataflow_error;
+pub mod dataset_summary;
+pub mod direction;
+pub mod facets;
+mod force_layout;
+pub mod health;
 pub mod html;
 pub mod image_loading;
+pub mod image_rendering;
+mod int64;
+pub mod lenient_dates;
+pub mod lint;
+pub mod log_level;
 pub mod module_loader;
+pub mod output_format;
+mod pdfa;
+mod pre_aggregate;
+pub mod render_warnings;
+pub mod request_hash;
+pub mod resize;
+pub mod resource_stats;
+pub mod row_limit;
+pub mod spec_limits;
+pub mod static_export;
+pub mod stroke_dash;
+pub mod suggestions;
+pub mod suppress;
+pub mod svg_diff;
+pub mod svg_minify;
 pub mod text;
+mod text_style;
+pub mod topojson;
+pub mod vendor_integrity;
 
 #[macro_use]
 extern crate lazy_static;
@@ -13,3 +49,8 @@ pub use converter::VlConverter;
 pub use deno_runtime::deno_core::anyhow;
 pub use module_loader::import_map::VlVersion;
 pub use serde_json;
+pub use tiny_skia;
+
+/// Target of the log records emitted by vl-convert, including messages logged by the
+/// JavaScript runtime
+pub const LOG_TARGET: &str = "vl_convert";
@@ -0,0 +1,666 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::{HashMap, HashSet};
+
+/// Signals that Vega defines in the top-level scope of every spec
+const ROOT_SIGNALS: [&str; 5] = ["width", "height", "padding", "autosize", "background"];
+
+/// Identifiers that a signal reference may name without them being defined as signals
+const EXPRESSION_IDENTIFIERS: [&str; 16] = [
+    "datum",
+    "item",
+    "event",
+    "parent",
+    "NaN",
+    "E",
+    "LN2",
+    "LN10",
+    "LOG2E",
+    "LOG10E",
+    "PI",
+    "SQRT1_2",
+    "SQRT2",
+    "MIN_VALUE",
+    "MAX_VALUE",
+    "undefined",
+];
+
+/// Legend properties that name the scale the legend is drawn for
+const LEGEND_SCALE_KEYS: [&str; 7] = [
+    "fill",
+    "opacity",
+    "shape",
+    "size",
+    "stroke",
+    "strokeDash",
+    "strokeWidth",
+];
+
+/// Properties of group marks that are parsed in the group's own scope
+const GROUP_SCOPE_KEYS: [&str; 8] = [
+    "signals",
+    "data",
+    "scales",
+    "projections",
+    "axes",
+    "legends",
+    "title",
+    "marks",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LintSeverity {
+    Error,
+    Warning,
+}
+
+/// Problem found in a Vega spec by [`lint_vega`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LintIssue {
+    pub severity: LintSeverity,
+    /// Name of the rule that found the issue. One of undefined-scale, undefined-signal,
+    /// undefined-data, missing-field, unused-dataset, or duplicate-signal
+    pub code: String,
+    pub message: String,
+    /// JSON pointer to the part of the spec with the issue (e.g. /marks/0/encode/update/x/scale)
+    pub path: String,
+}
+
+/// Statically check a Vega spec for mistakes that Vega either rejects at runtime or that
+/// silently leave marks empty: references to undefined scales, signals, and datasets, scale
+/// domains with fields that are missing from inline data, unused datasets, and duplicate
+/// signal names.
+///
+/// Names are resolved through the nested scopes of group marks, as Vega does. References
+/// made inside expression strings aren't checked
+pub fn lint_vega(vg_spec: &Value) -> Vec<LintIssue> {
+    let mut linter = Linter::default();
+    if let Value::Object(spec) = vg_spec {
+        linter.lint_scope(spec, "", None);
+        linter.check_unused_datasets();
+    }
+    linter.issues
+}
+
+#[derive(Default)]
+struct Scope<'a> {
+    signals: HashSet<&'a str>,
+    scales: HashSet<&'a str>,
+    /// Datasets of the scope, with their rows when they're given inline and aren't transformed
+    data: HashMap<&'a str, Option<&'a [Value]>>,
+}
+
+#[derive(Default)]
+struct Linter<'a> {
+    issues: Vec<LintIssue>,
+    scopes: Vec<Scope<'a>>,
+    /// Datasets defined anywhere in the spec, with the paths they're defined at
+    defined_data: Vec<(&'a str, String)>,
+    /// Names of datasets referenced anywhere in the spec
+    referenced_data: HashSet<&'a str>,
+    /// Strings of the spec outside of inline data, which include expressions that may
+    /// reference datasets by name
+    strings: Vec<&'a str>,
+}
+
+impl<'a> Linter<'a> {
+    fn lint_scope(&mut self, spec: &'a Map<String, Value>, path: &str, facet: Option<&'a str>) {
+        let is_root = self.scopes.is_empty();
+        let mut scope = Scope::default();
+        if is_root {
+            scope.signals.extend(ROOT_SIGNALS);
+        }
+        if let Some(facet) = facet {
+            scope.data.insert(facet, None);
+        }
+
+        // Built-in signals aren't counted, as the spec may define them once to configure them
+        let mut signal_names = HashSet::new();
+        for (i, signal) in array_items(spec, "signals") {
+            let Some(name) = signal.get("name").and_then(Value::as_str) else {
+                continue;
+            };
+            let name_path = format!("{path}/signals/{i}/name");
+            if signal.get("push").and_then(Value::as_str) == Some("outer") {
+                // Pushed signals update a signal of an enclosing scope
+                if !self.signal_defined(name) {
+                    self.undefined("undefined-signal", "Signal", name, name_path);
+                }
+                signal_names.insert(name);
+            } else if !signal_names.insert(name) {
+                self.issue(
+                    LintSeverity::Error,
+                    "duplicate-signal",
+                    format!("Signal \"{name}\" is defined more than once in the same scope"),
+                    name_path,
+                );
+            }
+            scope.signals.insert(name);
+        }
+
+        for (i, data) in array_items(spec, "data") {
+            let Some(name) = data.get("name").and_then(Value::as_str) else {
+                continue;
+            };
+            scope.data.insert(name, inline_rows(data));
+            self.defined_data
+                .push((name, format!("{path}/data/{i}/name")));
+
+            // Transforms like bin and extent write their output to a signal
+            for (_, transform) in array_items(data, "transform") {
+                if let Some(signal) = transform.get("signal").and_then(Value::as_str) {
+                    scope.signals.insert(signal);
+                }
+            }
+        }
+
+        for (_, scale) in array_items(spec, "scales") {
+            if let Some(name) = scale.get("name").and_then(Value::as_str) {
+                scope.scales.insert(name);
+            }
+        }
+
+        self.scopes.push(scope);
+
+        for (key, value) in spec {
+            // The other properties of group marks are checked in the enclosing scope
+            if !is_root && !GROUP_SCOPE_KEYS.contains(&key.as_str()) {
+                continue;
+            }
+            let value_path = format!("{path}/{}", escape_pointer(key));
+            match key.as_str() {
+                "$schema" | "description" | "config" | "usermeta" | "marks" => {}
+                "data" => {
+                    for (i, data) in array_items(spec, "data") {
+                        self.lint_data(data, &format!("{value_path}/{i}"));
+                    }
+                }
+                "legends" => {
+                    self.check_refs(value, &value_path);
+                    for (i, legend) in array_items(spec, "legends") {
+                        for key in LEGEND_SCALE_KEYS {
+                            if let Some(scale) = legend.get(key).and_then(Value::as_str) {
+                                self.check_scale(scale, format!("{value_path}/{i}/{key}"));
+                            }
+                        }
+                    }
+                }
+                _ => self.check_refs(value, &value_path),
+            }
+        }
+
+        for (i, mark) in array_items(spec, "marks") {
+            self.lint_mark(mark, &format!("{path}/marks/{i}"));
+        }
+
+        self.scopes.pop();
+    }
+
+    fn lint_mark(&mut self, mark: &'a Map<String, Value>, path: &str) {
+        let is_group = mark.get("type").and_then(Value::as_str) == Some("group");
+        for (key, value) in mark {
+            if !is_group || !GROUP_SCOPE_KEYS.contains(&key.as_str()) {
+                self.check_refs(value, &format!("{path}/{}", escape_pointer(key)));
+            }
+        }
+
+        if is_group {
+            // Groups that facet their data define the faceted dataset in their own scope
+            let facet = mark
+                .get("from")
+                .and_then(|from| from.get("facet"))
+                .and_then(|facet| facet.get("name"))
+                .and_then(Value::as_str);
+            self.lint_scope(mark, path, facet);
+        }
+    }
+
+    fn lint_data(&mut self, data: &'a Map<String, Value>, path: &str) {
+        for (key, value) in data {
+            let value_path = format!("{path}/{}", escape_pointer(key));
+            match key.as_str() {
+                "values" => {}
+                "source" => {
+                    let sources = match value {
+                        Value::Array(sources) => sources.iter().collect(),
+                        source => vec![source],
+                    };
+                    for (i, source) in sources.into_iter().enumerate() {
+                        if let Some(source) = source.as_str() {
+                            let source_path = if value.is_array() {
+                                format!("{value_path}/{i}")
+                            } else {
+                                value_path.clone()
+                            };
+                            self.check_data(source, source_path);
+                        }
+                    }
+                }
+                _ => self.check_refs(value, &value_path),
+            }
+        }
+    }
+
+    /// Check the scale, signal, and data references of a part of the spec that doesn't
+    /// define any of them
+    fn check_refs(&mut self, value: &'a Value, path: &str) {
+        match value {
+            Value::Object(obj) => {
+                // Transforms name the signal that they write their output to
+                if !obj.contains_key("type") {
+                    if let Some(signal) = obj.get("signal").and_then(Value::as_str) {
+                        self.check_signal(signal, format!("{path}/signal"));
+                    }
+                }
+                if let Some(scale) = obj.get("scale").and_then(Value::as_str) {
+                    self.check_scale(scale, format!("{path}/scale"));
+                }
+                if let Some(scale) = obj.get("gridScale").and_then(Value::as_str) {
+                    self.check_scale(scale, format!("{path}/gridScale"));
+                }
+                if let Some(data) = obj.get("data").and_then(Value::as_str) {
+                    if let Some(rows) = self.check_data(data, format!("{path}/data")) {
+                        self.check_fields(obj, data, rows, path);
+                    }
+                }
+                if obj.get("type").and_then(Value::as_str) == Some("lookup") {
+                    if let Some(from) = obj.get("from").and_then(Value::as_str) {
+                        self.check_data(from, format!("{path}/from"));
+                    }
+                }
+                for (key, child) in obj {
+                    self.check_refs(child, &format!("{path}/{}", escape_pointer(key)));
+                }
+            }
+            Value::Array(items) => {
+                for (i, item) in items.iter().enumerate() {
+                    self.check_refs(item, &format!("{path}/{i}"));
+                }
+            }
+            Value::String(s) => self.strings.push(s),
+            _ => {}
+        }
+    }
+
+    fn check_signal(&mut self, name: &'a str, path: String) {
+        // Only references that are a bare name are checked, not expressions
+        if is_identifier(name)
+            && !EXPRESSION_IDENTIFIERS.contains(&name)
+            && !self.signal_defined(name)
+        {
+            self.undefined("undefined-signal", "Signal", name, path);
+        }
+    }
+
+    fn check_scale(&mut self, name: &'a str, path: String) {
+        if !self.scopes.iter().any(|scope| scope.scales.contains(name)) {
+            self.undefined("undefined-scale", "Scale", name, path);
+        }
+    }
+
+    /// Check that a dataset is defined, returning its inline rows if it has them
+    fn check_data(&mut self, name: &'a str, path: String) -> Option<&'a [Value]> {
+        self.referenced_data.insert(name);
+        match self
+            .scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.data.get(name))
+        {
+            Some(rows) => *rows,
+            None => {
+                self.undefined("undefined-data", "Dataset", name, path);
+                None
+            }
+        }
+    }
+
+    /// Check that the fields of a data reference (e.g. a scale domain) are in the inline rows
+    /// of the dataset that it references
+    fn check_fields(
+        &mut self,
+        data_ref: &'a Map<String, Value>,
+        data: &str,
+        rows: &'a [Value],
+        path: &str,
+    ) {
+        let mut fields = Vec::new();
+        if let Some(field) = data_ref.get("field").and_then(Value::as_str) {
+            fields.push((field, format!("{path}/field")));
+        }
+        for (i, field) in array_values(data_ref, "fields").enumerate() {
+            if let Some(field) = field.as_str() {
+                fields.push((field, format!("{path}/fields/{i}")));
+            }
+        }
+
+        for (field, field_path) in fields {
+            if !rows_have_field(rows, field) {
+                self.issue(
+                    LintSeverity::Error,
+                    "missing-field",
+                    format!("Field \"{field}\" is not in the inline rows of dataset \"{data}\""),
+                    field_path,
+                );
+            }
+        }
+    }
+
+    fn check_unused_datasets(&mut self) {
+        let defined_data = std::mem::take(&mut self.defined_data);
+        for (name, path) in defined_data {
+            if self.referenced_data.contains(name) {
+                continue;
+            }
+            // Expressions reference datasets by quoted name, e.g. data('table')
+            let single_quoted = format!("'{name}'");
+            let double_quoted = format!("\"{name}\"");
+            if self
+                .strings
+                .iter()
+                .any(|s| s.contains(&single_quoted) || s.contains(&double_quoted))
+            {
+                continue;
+            }
+            self.issue(
+                LintSeverity::Warning,
+                "unused-dataset",
+                format!("Dataset \"{name}\" is never used"),
+                path,
+            );
+        }
+    }
+
+    fn signal_defined(&self, name: &str) -> bool {
+        self.scopes.iter().any(|scope| scope.signals.contains(name))
+    }
+
+    fn undefined(&mut self, code: &str, kind: &str, name: &str, path: String) {
+        self.issue(
+            LintSeverity::Error,
+            code,
+            format!("{kind} \"{name}\" is not defined"),
+            path,
+        );
+    }
+
+    fn issue(&mut self, severity: LintSeverity, code: &str, message: String, path: String) {
+        self.issues.push(LintIssue {
+            severity,
+            code: code.to_string(),
+            message,
+            path,
+        });
+    }
+}
+
+/// Iterate over the objects of an array property, with their indices
+fn array_items<'a>(
+    obj: &'a Map<String, Value>,
+    key: &str,
+) -> impl Iterator<Item = (usize, &'a Map<String, Value>)> {
+    array_values(obj, key)
+        .enumerate()
+        .filter_map(|(i, value)| value.as_object().map(|item| (i, item)))
+}
+
+fn array_values<'a>(obj: &'a Map<String, Value>, key: &str) -> impl Iterator<Item = &'a Value> {
+    obj.get(key).and_then(Value::as_array).into_iter().flatten()
+}
+
+/// Rows of a dataset whose rows are exactly its inline values, without a format or transforms
+/// that could change their fields
+fn inline_rows(data: &Map<String, Value>) -> Option<&[Value]> {
+    if data.contains_key("format")
+        || data.contains_key("source")
+        || data.contains_key("url")
+        || array_values(data, "transform").next().is_some()
+    {
+        return None;
+    }
+    let rows = data.get("values")?.as_array()?;
+    if rows.is_empty() || !rows.iter().all(Value::is_object) {
+        return None;
+    }
+    Some(rows)
+}
+
+/// Whether any row has a field, following the nested field syntax of Vega (e.g. "a.b").
+/// Fields with escapes or brackets are assumed to be present
+fn rows_have_field(rows: &[Value], field: &str) -> bool {
+    if field.contains(['\\', '[', ']', '"', '\'']) {
+        return true;
+    }
+    rows.iter().any(|row| {
+        field
+            .split('.')
+            .try_fold(row, |value, key| value.get(key))
+            .is_some()
+    })
+}
+
+fn is_identifier(s: &str) -> bool {
+    let mut chars = s.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
+
+/// Escape a key for use as a JSON pointer token
+fn escape_pointer(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn codes(vg_spec: Value) -> Vec<(String, String)> {
+        lint_vega(&vg_spec)
+            .into_iter()
+            .map(|issue| (issue.code, issue.path))
+            .collect()
+    }
+
+    fn issue(code: &str, path: &str) -> (String, String) {
+        (code.to_string(), path.to_string())
+    }
+
+    #[test]
+    fn test_valid_spec() {
+        let vg_spec = json!({
+            "signals": [
+                {"name": "width", "value": 300},
+                {"name": "hover", "value": null, "on": [{"events": "rect:mouseover", "update": "datum"}]}
+            ],
+            "data": [
+                {"name": "table", "values": [{"a": "A", "b": 28}, {"a": "B", "b": {"c": 55}}]},
+                {"name": "counts", "source": "table", "transform": [
+                    {"type": "extent", "field": "b", "signal": "b_extent"}
+                ]},
+                {"name": "lookup_only", "values": []}
+            ],
+            "scales": [
+                {"name": "x", "type": "band", "domain": {"data": "table", "field": "a"}, "range": "width"},
+                {"name": "y", "domain": {"data": "table", "field": "b.c"}, "range": "height"}
+            ],
+            "axes": [{"orient": "bottom", "scale": "x", "title": {"signal": "hover"}}],
+            "marks": [
+                {
+                    "type": "rect",
+                    "from": {"data": "counts"},
+                    "encode": {"update": {
+                        "x": {"scale": "x", "field": "a"},
+                        "y": {"scale": "y", "signal": "b_extent[1]"},
+                        "fill": [{"test": "indata('lookup_only', 'a', datum.a)", "value": "red"}, {"signal": "datum"}]
+                    }}
+                }
+            ]
+        });
+        assert_eq!(codes(vg_spec), vec![]);
+    }
+
+    #[test]
+    fn test_undefined_scale() {
+        let vg_spec = json!({
+            "scales": [{"name": "xscale", "domain": [0, 1]}],
+            "axes": [{"orient": "bottom", "scale": "x_scale"}],
+            "legends": [{"fill": "color"}],
+            "marks": [{"type": "rect", "encode": {"update": {"x": {"scale": "xscale", "value": 0}, "y": {"scale": "yscale", "value": 0}}}}]
+        });
+        assert_eq!(
+            codes(vg_spec),
+            vec![
+                issue("undefined-scale", "/axes/0/scale"),
+                issue("undefined-scale", "/legends/0/fill"),
+                issue("undefined-scale", "/marks/0/encode/update/y/scale"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_undefined_signal() {
+        let vg_spec = json!({
+            "signals": [{"name": "size", "value": 10}],
+            "marks": [{"type": "symbol", "encode": {"update": {
+                "size": {"signal": "size"},
+                "opacity": {"signal": "opacity"},
+                "angle": {"signal": "rotation * 2"},
+                "x": {"signal": "width / 2"}
+            }}}]
+        });
+        assert_eq!(
+            codes(vg_spec),
+            vec![issue(
+                "undefined-signal",
+                "/marks/0/encode/update/opacity/signal"
+            )]
+        );
+    }
+
+    #[test]
+    fn test_undefined_data() {
+        let vg_spec = json!({
+            "data": [
+                {"name": "table", "values": [{"a": 1}]},
+                {"name": "derived", "source": "tabel"},
+                {"name": "joined", "source": "table", "transform": [
+                    {"type": "lookup", "from": "other", "key": "a", "fields": ["a"]}
+                ]}
+            ],
+            "scales": [{"name": "x", "domain": {"data": "missing", "field": "a"}}],
+            "marks": [
+                {"type": "rect", "from": {"data": "derived"}, "encode": {"update": {"x": {"scale": "x", "field": "a"}}}},
+                {"type": "rect", "from": {"data": "joined"}}
+            ]
+        });
+        assert_eq!(
+            codes(vg_spec),
+            vec![
+                issue("undefined-data", "/data/1/source"),
+                issue("undefined-data", "/data/2/transform/0/from"),
+                issue("undefined-data", "/scales/0/domain/data"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_missing_field() {
+        let vg_spec = json!({
+            "data": [
+                {"name": "table", "values": [{"category": "A", "amount": 28}]},
+                {"name": "parsed", "values": [{"a": "1"}], "format": {"parse": {"a": "number"}}}
+            ],
+            "scales": [
+                {"name": "x", "domain": {"data": "table", "field": "categroy"}},
+                {"name": "y", "domain": {"fields": [
+                    {"data": "table", "field": "amount"},
+                    {"data": "table", "field": "amount.value"}
+                ]}},
+                {"name": "z", "domain": {"data": "parsed", "field": "b"}}
+            ],
+            "marks": [{"type": "rect", "from": {"data": "table"}}]
+        });
+        assert_eq!(
+            codes(vg_spec),
+            vec![
+                issue("missing-field", "/scales/0/domain/field"),
+                issue("missing-field", "/scales/1/domain/fields/1/field"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unused_dataset() {
+        let vg_spec = json!({
+            "data": [
+                {"name": "table", "values": [{"a": 1}]},
+                {"name": "unused", "values": [{"a": 1}]},
+                {"name": "in_expression", "values": [{"a": 1}]}
+            ],
+            "signals": [{"name": "n", "update": "length(data('in_expression'))"}],
+            "marks": [{"type": "rect", "from": {"data": "table"}}]
+        });
+        let issues = lint_vega(&vg_spec);
+        assert_eq!(
+            issues,
+            vec![LintIssue {
+                severity: LintSeverity::Warning,
+                code: "unused-dataset".to_string(),
+                message: "Dataset \"unused\" is never used".to_string(),
+                path: "/data/1/name".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_duplicate_signal() {
+        let vg_spec = json!({
+            "signals": [
+                {"name": "width", "value": 200},
+                {"name": "size", "value": 1},
+                {"name": "size", "value": 2}
+            ],
+            "marks": [{
+                "type": "group",
+                "signals": [{"name": "size", "value": 3}, {"name": "width", "push": "outer"}]
+            }]
+        });
+        assert_eq!(
+            codes(vg_spec),
+            vec![issue("duplicate-signal", "/signals/2/name")]
+        );
+    }
+
+    #[test]
+    fn test_group_scopes() {
+        let vg_spec = json!({
+            "data": [{"name": "table", "values": [{"g": "a", "v": 1}]}],
+            "marks": [
+                {
+                    "type": "group",
+                    "from": {"facet": {"name": "faceted", "data": "table", "groupby": "g"}},
+                    "encode": {"update": {"width": {"scale": "inner", "value": 1}}},
+                    "signals": [{"name": "local", "value": 1}, {"name": "missing", "push": "outer"}],
+                    "scales": [{"name": "inner", "domain": {"data": "faceted", "field": "v"}}],
+                    "marks": [{
+                        "type": "rect",
+                        "from": {"data": "faceted"},
+                        "encode": {"update": {"x": {"scale": "inner", "field": "v"}, "y": {"signal": "local"}}}
+                    }]
+                },
+                {"type": "rect", "encode": {"update": {"x": {"scale": "inner", "value": 0}, "y": {"signal": "local"}}}}
+            ]
+        });
+        assert_eq!(
+            codes(vg_spec),
+            vec![
+                issue("undefined-scale", "/marks/0/encode/update/width/scale"),
+                issue("undefined-signal", "/marks/0/signals/1/name"),
+                issue("undefined-scale", "/marks/1/encode/update/x/scale"),
+                issue("undefined-signal", "/marks/1/encode/update/y/signal"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,132 @@
+use crate::anyhow::anyhow;
+use deno_core::error::AnyError;
+use serde_json::json;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// Level of the messages that Vega views log while they're rendered, from least to most
+/// verbose. Messages are logged through the `log` crate, and warnings are also collected
+/// with the render warnings of the conversion
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    None,
+    Error,
+    Warn,
+    Info,
+    Debug,
+}
+
+impl LogLevel {
+    /// Value of the level in Vega, e.g. `vega.Warn`
+    fn vega_level(&self) -> u8 {
+        match self {
+            LogLevel::None => 0,
+            LogLevel::Error => 1,
+            LogLevel::Warn => 2,
+            LogLevel::Info => 3,
+            LogLevel::Debug => 4,
+        }
+    }
+}
+
+impl Display for LogLevel {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            LogLevel::None => "none",
+            LogLevel::Error => "error",
+            LogLevel::Warn => "warn",
+            LogLevel::Info => "info",
+            LogLevel::Debug => "debug",
+        };
+        write!(f, "{s}")
+    }
+}
+
+impl FromStr for LogLevel {
+    type Err = AnyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.trim().to_ascii_lowercase().as_str() {
+            "none" => Self::None,
+            "error" => Self::Error,
+            "warn" | "warning" => Self::Warn,
+            "info" => Self::Info,
+            "debug" => Self::Debug,
+            _ => {
+                return Err(anyhow!(
+                    "Unsupported log level: {}. Expected none, error, warn, info, or debug",
+                    s
+                ))
+            }
+        })
+    }
+}
+
+/// JavaScript literal for the log level of the views of a conversion and the filters of the
+/// warnings that it collects. `null` when neither is set.
+///
+/// Warnings that contain a filter, or that match it as a regular expression when it's
+/// written as `/pattern/`, are counted but left out of the warnings of the conversion
+pub(crate) fn view_log_arg(
+    log_level: Option<LogLevel>,
+    warning_filters: Option<&[String]>,
+) -> Result<String, AnyError> {
+    if log_level.is_none() && warning_filters.is_none() {
+        return Ok("null".to_string());
+    }
+    let filters = warning_filters
+        .unwrap_or_default()
+        .iter()
+        .map(|filter| match filter.strip_prefix('/') {
+            Some(pattern) if pattern.len() > 1 && pattern.ends_with('/') => {
+                json!({"regex": &pattern[..pattern.len() - 1]})
+            }
+            _ => json!({"substring": filter}),
+        })
+        .collect::<Vec<_>>();
+    Ok(json!({
+        "level": log_level.map(|level| level.vega_level()),
+        "filters": filters,
+    })
+    .to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    #[test]
+    fn test_parse_log_level() {
+        for level in [
+            LogLevel::None,
+            LogLevel::Error,
+            LogLevel::Warn,
+            LogLevel::Info,
+            LogLevel::Debug,
+        ] {
+            assert_eq!(LogLevel::from_str(&level.to_string()).unwrap(), level);
+        }
+        assert_eq!(LogLevel::from_str(" WARNING ").unwrap(), LogLevel::Warn);
+        assert!(LogLevel::from_str("trace").is_err());
+    }
+
+    #[test]
+    fn test_view_log_arg() {
+        assert_eq!(view_log_arg(None, None).unwrap(), "null");
+        let filters = vec!["Infinite extent".to_string(), "/^Scale \\w+/".to_string()];
+        let arg: Value =
+            serde_json::from_str(&view_log_arg(Some(LogLevel::Warn), Some(&filters)).unwrap())
+                .unwrap();
+        assert_eq!(
+            arg,
+            json!({
+                "level": 2,
+                "filters": [{"substring": "Infinite extent"}, {"regex": "^Scale \\w+"}],
+            })
+        );
+        let arg: Value =
+            serde_json::from_str(&view_log_arg(None, Some(&["/".to_string()])).unwrap()).unwrap();
+        assert_eq!(arg, json!({"level": null, "filters": [{"substring": "/"}]}));
+    }
+}
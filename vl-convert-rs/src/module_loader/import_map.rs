@@ -19,6 +19,24 @@ pub const VEGA_VERSION: &str = "5.30.0";
 pub const VEGA_THEMES_VERSION: &str = "2.15.0";
 pub const VEGA_EMBED_VERSION: &str = "6.26.0";
 
+/// Names of the themes of the vega-themes module, sorted
+pub const VEGA_THEME_NAMES: &[&str] = &[
+    "carbong10",
+    "carbong100",
+    "carbong90",
+    "carbonwhite",
+    "dark",
+    "excel",
+    "fivethirtyeight",
+    "ggplot2",
+    "googlecharts",
+    "latimes",
+    "powerbi",
+    "quartz",
+    "urbaninstitute",
+    "vox",
+];
+
 pub fn url_for_path(path: &str) -> String {
     format!("{}{}", SKYPACK_URL, path)
 }
@@ -80,6 +98,23 @@ impl VlVersion {
             v5_21 => "5.21",
         }
     }
+
+    /// Check that the Vega-Lite library of the version is included in this build. Versions
+    /// older than 5.14 are only included with the vl_legacy feature
+    pub fn ensure_included(self) -> Result<(), AnyError> {
+        use VlVersion::*;
+        let included = match self {
+            v5_8 => cfg!(feature = "vl_legacy"),
+            _ => true,
+        };
+        if !included {
+            bail!(
+                "This build does not include v{}; install vl-convert-python-full or build with feature vl_legacy",
+                self.to_semver()
+            );
+        }
+        Ok(())
+    }
 }
 
 impl Default for VlVersion {
@@ -92,7 +127,7 @@ impl FromStr for VlVersion {
     type Err = AnyError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(match s {
+        let version = match s {
             "5.8" | "v5.8" | "5_8" | "v5_8" => Self::v5_8,
             "5.14" | "v5.14" | "5_14" | "v5_14" => Self::v5_14,
             "5.15" | "v5.15" | "5_15" | "v5_15" => Self::v5_15,
@@ -103,11 +138,14 @@ impl FromStr for VlVersion {
             "5.20" | "v5.20" | "5_20" | "v5_20" => Self::v5_20,
             "5.21" | "v5.21" | "5_21" | "v5_21" => Self::v5_21,
             _ => bail!("Unsupported Vega-Lite version string {}", s),
-        })
+        };
+        version.ensure_included()?;
+        Ok(version)
     }
 }
 
 pub const VL_VERSIONS: &[VlVersion] = &[
+    #[cfg(feature = "vl_legacy")]
     VlVersion::v5_8,
     VlVersion::v5_14,
     VlVersion::v5_15,
@@ -172,6 +210,7 @@ pub fn build_import_map() -> HashMap<String, String> {
     m.insert("/-/vega-lite@v5.19.0-4m5nwXbwdKW9Bc7adV02/dist=es2020,mode=imports,min/optimized/vega-lite.js".to_string(), include_str!("../../vendor/cdn.skypack.dev/-/vega-lite@v5.19.0-4m5nwXbwdKW9Bc7adV02/dist=es2020,mode=imports,min/optimized/vega-lite.js").to_string());
     m.insert("/-/vega-lite@v5.20.1-5FloWSAHKfabpxOoogY3/dist=es2020,mode=imports,min/optimized/vega-lite.js".to_string(), include_str!("../../vendor/cdn.skypack.dev/-/vega-lite@v5.20.1-5FloWSAHKfabpxOoogY3/dist=es2020,mode=imports,min/optimized/vega-lite.js").to_string());
     m.insert("/-/vega-lite@v5.21.0-FWbVtRVTj7vqBi6QZX8A/dist=es2020,mode=imports,min/optimized/vega-lite.js".to_string(), include_str!("../../vendor/cdn.skypack.dev/-/vega-lite@v5.21.0-FWbVtRVTj7vqBi6QZX8A/dist=es2020,mode=imports,min/optimized/vega-lite.js").to_string());
+    #[cfg(feature = "vl_legacy")]
     m.insert("/-/vega-lite@v5.8.0-4snbURNltT4se5LjMOKF/dist=es2020,mode=imports,min/optimized/vega-lite.js".to_string(), include_str!("../../vendor/cdn.skypack.dev/-/vega-lite@v5.8.0-4snbURNltT4se5LjMOKF/dist=es2020,mode=imports,min/optimized/vega-lite.js").to_string());
     m.insert("/-/vega-loader@v4.5.2-1ImBl2TigOVzvurACWyz/dist=es2020,mode=imports,min/optimized/vega-loader.js".to_string(), include_str!("../../vendor/cdn.skypack.dev/-/vega-loader@v4.5.2-1ImBl2TigOVzvurACWyz/dist=es2020,mode=imports,min/optimized/vega-loader.js").to_string());
     m.insert("/-/vega-parser@v6.4.0-nwGMLAa2L4N1N7f1iRh9/dist=es2020,mode=imports,min/optimized/vega-parser.js".to_string(), include_str!("../../vendor/cdn.skypack.dev/-/vega-parser@v6.4.0-nwGMLAa2L4N1N7f1iRh9/dist=es2020,mode=imports,min/optimized/vega-parser.js").to_string());
@@ -203,12 +242,14 @@ pub fn build_import_map() -> HashMap<String, String> {
     m.insert("/pin/vega-lite@v5.19.0-4m5nwXbwdKW9Bc7adV02/mode=imports,min/optimized/vega-lite.js".to_string(), include_str!("../../vendor/cdn.skypack.dev/pin/vega-lite@v5.19.0-4m5nwXbwdKW9Bc7adV02/mode=imports,min/optimized/vega-lite.js").to_string());
     m.insert("/pin/vega-lite@v5.20.1-5FloWSAHKfabpxOoogY3/mode=imports,min/optimized/vega-lite.js".to_string(), include_str!("../../vendor/cdn.skypack.dev/pin/vega-lite@v5.20.1-5FloWSAHKfabpxOoogY3/mode=imports,min/optimized/vega-lite.js").to_string());
     m.insert("/pin/vega-lite@v5.21.0-FWbVtRVTj7vqBi6QZX8A/mode=imports,min/optimized/vega-lite.js".to_string(), include_str!("../../vendor/cdn.skypack.dev/pin/vega-lite@v5.21.0-FWbVtRVTj7vqBi6QZX8A/mode=imports,min/optimized/vega-lite.js").to_string());
+    #[cfg(feature = "vl_legacy")]
     m.insert("/pin/vega-lite@v5.8.0-4snbURNltT4se5LjMOKF/mode=imports,min/optimized/vega-lite.js".to_string(), include_str!("../../vendor/cdn.skypack.dev/pin/vega-lite@v5.8.0-4snbURNltT4se5LjMOKF/mode=imports,min/optimized/vega-lite.js").to_string());
     m.insert("/pin/vega-themes@v2.15.0-wV2Z38N5Dk8FL05cPDbE/mode=imports,min/optimized/vega-themes.js".to_string(), include_str!("../../vendor/cdn.skypack.dev/pin/vega-themes@v2.15.0-wV2Z38N5Dk8FL05cPDbE/mode=imports,min/optimized/vega-themes.js").to_string());
     m.insert("/pin/vega@v5.30.0-fYDVG3pUN16BiGmbVNdw/mode=imports,min/optimized/vega.js".to_string(), include_str!("../../vendor/cdn.skypack.dev/pin/vega@v5.30.0-fYDVG3pUN16BiGmbVNdw/mode=imports,min/optimized/vega.js").to_string());
     m
 }
 
+#[cfg(feature = "embed-locales")]
 pub fn build_format_locale_map() -> HashMap<String, String> {
     let mut m: HashMap<String, String> = HashMap::new();
     m.insert(
@@ -450,6 +491,7 @@ pub fn build_format_locale_map() -> HashMap<String, String> {
     m
 }
 
+#[cfg(feature = "embed-locales")]
 pub fn build_time_format_locale_map() -> HashMap<String, String> {
     let mut m: HashMap<String, String> = HashMap::new();
     m.insert(
@@ -1,26 +1,90 @@
 pub mod import_map;
+pub mod vendor_manifest;
 
-use crate::module_loader::import_map::{
-    build_format_locale_map, build_import_map, build_time_format_locale_map, VEGA_PATH,
-    VEGA_THEMES_PATH,
-};
+#[cfg(feature = "embed-locales")]
+use crate::module_loader::import_map::{build_format_locale_map, build_time_format_locale_map};
+use crate::module_loader::import_map::{build_import_map, VEGA_PATH, VEGA_THEMES_PATH};
 use crate::VlVersion;
 use deno_core::{ModuleLoadResponse, ModuleSourceCode, RequestedModuleType, ResolutionKind};
 use deno_emit::{LoadFuture, LoadOptions, Loader};
 use deno_graph::source::LoadResponse;
-use deno_runtime::deno_core::anyhow::Error;
+use deno_runtime::deno_core::anyhow::{anyhow, Error};
 use deno_runtime::deno_core::{
     resolve_import, ModuleLoader, ModuleSource, ModuleSpecifier, ModuleType,
 };
 use regex::Regex;
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Environment variable with the directory that locales are read from, in builds without the
+/// embed-locales feature, when no directory is set with [`set_locale_dir`]
+pub const LOCALE_DIR_ENV_VAR: &str = "VL_CONVERT_LOCALE_DIR";
 
 lazy_static! {
     pub static ref IMPORT_MAP: HashMap<String, String> = build_import_map();
     pub static ref FORMATE_LOCALE_MAP: HashMap<String, String> = build_format_locale_map();
     pub static ref TIME_FORMATE_LOCALE_MAP: HashMap<String, String> =
         build_time_format_locale_map();
+    static ref LOCALE_DIR: Mutex<Option<PathBuf>> = Mutex::new(None);
+}
+
+/// Set the directory that d3-format and d3-time-format locales are read from, in builds
+/// without the embed-locales feature, like the Python package, which ships them as package
+/// data. The directory holds format/<NAME>.json and time-format/<NAME>.json files, like the
+/// locales directory of vl-convert-rs.
+///
+/// Locales are read when the first one is used, so the directory must be set before then
+pub fn set_locale_dir(dir: impl Into<PathBuf>) {
+    *LOCALE_DIR.lock().unwrap() = Some(dir.into());
+}
+
+/// Read the <NAME>.json locale files of a directory, keyed by name
+pub fn read_locale_dir(dir: &Path) -> Result<HashMap<String, String>, Error> {
+    let mut locales = HashMap::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|ext| ext == "json") {
+            if let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) {
+                locales.insert(name.to_string(), fs::read_to_string(&path)?);
+            }
+        }
+    }
+    Ok(locales)
+}
+
+#[cfg(not(feature = "embed-locales"))]
+fn build_format_locale_map() -> HashMap<String, String> {
+    read_runtime_locales("format")
+}
+
+#[cfg(not(feature = "embed-locales"))]
+fn build_time_format_locale_map() -> HashMap<String, String> {
+    read_runtime_locales("time-format")
+}
+
+/// Read the locales of a subdirectory of the locale directory. When they can't be read, no
+/// locales are available, and names of locales are reported as unknown when they're used
+#[cfg(not(feature = "embed-locales"))]
+fn read_runtime_locales(kind: &str) -> HashMap<String, String> {
+    let dir = LOCALE_DIR
+        .lock()
+        .unwrap()
+        .clone()
+        .or_else(|| std::env::var_os(LOCALE_DIR_ENV_VAR).map(PathBuf::from));
+    let Some(dir) = dir else {
+        log::warn!(
+            "Locales aren't embedded in this build, and no locale directory is set with \
+            set_locale_dir or {LOCALE_DIR_ENV_VAR}"
+        );
+        return HashMap::new();
+    };
+    let dir = dir.join(kind);
+    read_locale_dir(&dir).unwrap_or_else(|err| {
+        log::warn!("Failed to read locales from {}: {err}", dir.display());
+        HashMap::new()
+    })
 }
 
 pub struct VlConvertModuleLoader;
@@ -51,7 +115,6 @@ impl ModuleLoader for VlConvertModuleLoader {
     ) -> ModuleLoadResponse {
         let module_specifier = module_specifier.clone();
         let string_specifier = module_specifier.to_string();
-        // println!("load: {}", string_specifier);
 
         let code = if string_specifier.ends_with("vl-convert-rs.js") {
             // Load vl-convert-rs.js as an empty file
@@ -59,15 +122,15 @@ impl ModuleLoader for VlConvertModuleLoader {
             // run any code here
             "".to_string()
         } else {
-            IMPORT_MAP
-                .get(module_specifier.path())
-                .unwrap_or_else(|| {
-                    panic!(
+            match IMPORT_MAP.get(module_specifier.path()) {
+                Some(code) => code.clone(),
+                None => {
+                    return ModuleLoadResponse::Sync(Err(anyhow!(
                         "Unexpected source file with path: {}",
                         module_specifier.path()
-                    )
-                })
-                .clone()
+                    )))
+                }
+            }
         };
 
         ModuleLoadResponse::Sync(Ok(ModuleSource::new(
@@ -118,18 +181,21 @@ impl Loader for VlConvertBundleLoader {
     fn load(&self, module_specifier: &ModuleSpecifier, _options: LoadOptions) -> LoadFuture {
         let module_specifier = module_specifier.clone();
         let last_path_part = module_specifier.path().split('/').next_back().unwrap();
-        let code = if last_path_part == "vl-convert-index.js" {
+        let is_index = matches!(
+            last_path_part,
+            "vl-convert-index.js" | "vl-convert-index.ts"
+        );
+        let code = if is_index {
             self.index_js.clone()
         } else {
-            let mut src = IMPORT_MAP
-                .get(module_specifier.path())
-                .unwrap_or_else(|| {
-                    panic!(
-                        "Unexpected source file with path: {}",
-                        module_specifier.path()
-                    )
-                })
-                .clone();
+            let Some(src) = IMPORT_MAP.get(module_specifier.path()) else {
+                let err = anyhow!(
+                    "Unexpected source file with path: {}",
+                    module_specifier.path()
+                );
+                return Box::pin(async move { Err(err) });
+            };
+            let mut src = src.clone();
 
             if let Some(caps) = self.name_version_re.captures(module_specifier.path()) {
                 // Drop any leading slash segments
@@ -170,3 +236,32 @@ impl Loader for VlConvertBundleLoader {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_locale_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("xx-XX.json"), r#"{"decimal": ","}"#).unwrap();
+        fs::write(dir.path().join("README.md"), "Not a locale").unwrap();
+        let locales = read_locale_dir(dir.path()).unwrap();
+        assert_eq!(locales.len(), 1);
+        assert_eq!(locales["xx-XX"], r#"{"decimal": ","}"#);
+        assert!(read_locale_dir(&dir.path().join("missing")).is_err());
+    }
+
+    #[test]
+    fn test_locale_maps() {
+        // The locales that are read at runtime are the ones that are embedded. The locale
+        // directory is only used in builds without the embed-locales feature
+        let locales_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("locales");
+        set_locale_dir(&locales_path);
+        let format_locales = read_locale_dir(&locales_path.join("format")).unwrap();
+        let time_format_locales = read_locale_dir(&locales_path.join("time-format")).unwrap();
+        assert_eq!(*FORMATE_LOCALE_MAP, format_locales);
+        assert_eq!(*TIME_FORMATE_LOCALE_MAP, time_format_locales);
+        assert!(format_locales.contains_key("it-IT"));
+    }
+}
@@ -0,0 +1,350 @@
+// *************************************************************************
+// * This file is generated by vl-convert-vendor/src/main.rs. Do not edit! *
+// *************************************************************************
+
+/// Path of each vendored module in the import map, paired with the hex SHA-256 hash of its
+/// source
+pub const VENDOR_MANIFEST: &[(&str, &str)] = &[
+    (
+        "/-/clone@v2.1.2-inH2VLNzDGiYU9HUWyZM/dist=es2020,mode=imports,min/optimized/clone.js",
+        "854af05bde492977f309da50c7d5bd629c49810284f5234bee6799d6a2f3286d",
+    ),
+    (
+        "/-/d3-array@v3.2.4-G4hy00bPnjF6FrSYpT32/dist=es2020,mode=imports,min/optimized/d3-array.js",
+        "0ea6dee19e8c4fcaa524d9d953d43b0db86815adbf13c7cbc424689abeddb098",
+    ),
+    (
+        "/-/d3-color@v3.1.0-MWHDMwd2Pvp3NFjvrHgn/dist=es2020,mode=imports,min/optimized/d3-color.js",
+        "345423cff39016ed31a25e0ce76cea13437803be66c904819a4e41866aec7240",
+    ),
+    (
+        "/-/d3-delaunay@v6.0.4-8vxm7aIldY6XMTxF521F/dist=es2020,mode=imports,min/optimized/d3-delaunay.js",
+        "18b0eaa1b9e3ee8aba7399591b547118931a7a64082bdf65e12d6cec69e57fe0",
+    ),
+    (
+        "/-/d3-dispatch@v3.0.1-v6nbfqO2iWOSwp77fYdB/dist=es2020,mode=imports,min/optimized/d3-dispatch.js",
+        "5defbd37a2e6b1bb2149d2a37f8a3d5e387587ca0f3c407b1afcab4013b800c9",
+    ),
+    (
+        "/-/d3-dsv@v3.0.1-u1xCRjaLJc0qqv1Z5ERe/dist=es2020,mode=imports,min/optimized/d3-dsv.js",
+        "b242e1f70a275ac00188dd243d302400f51de16a73d410883ba0587df79d5246",
+    ),
+    (
+        "/-/d3-force@v3.0.0-cshj62qMoyIGNIXoil9u/dist=es2020,mode=imports,min/optimized/d3-force.js",
+        "59d984bc533b9629bf8b5a3f05883f3a1c63adb5b36554eda67320f863581bf1",
+    ),
+    (
+        "/-/d3-format@v3.1.0-D5wAD2odDPNNWsKloKgL/dist=es2020,mode=imports,min/optimized/d3-format.js",
+        "ab95dbe7651b272beeaa0eb9997561bcd4b39c2f83d26b410dfb89b6d4719dfc",
+    ),
+    (
+        "/-/d3-geo-projection@v4.0.0-5Hhxj2zKHEqWYAQIFo3r/dist=es2020,mode=imports,min/optimized/d3-geo-projection.js",
+        "23aba5fdf6c8aad647053db373884120b22457b70f10c1bced3543f6408fd2b4",
+    ),
+    (
+        "/-/d3-geo@v3.1.1-IFH102ROpDQE4rIMzW42/dist=es2020,mode=imports,min/optimized/d3-geo.js",
+        "d07b818d4ac3bafdfea4e3bc3f2d176346f5e85b73e5159394d6a9172c00c884",
+    ),
+    (
+        "/-/d3-hierarchy@v3.1.2-wx7sW10pU4OkfBLgCDCU/dist=es2020,mode=imports,min/optimized/d3-hierarchy.js",
+        "754b1f6f3102b8595d58b2f05ee4b55fc73532acf7d199ed3d304ab1fb6940af",
+    ),
+    (
+        "/-/d3-interpolate@v3.0.1-i9AsUdFHwyaukRBWNe8d/dist=es2020,mode=imports,min/optimized/d3-interpolate.js",
+        "c39171725431af2941696814ae0eea8dde8fdde9aed7db1e14ba2a058234cd89",
+    ),
+    (
+        "/-/d3-path@v3.1.0-nHaUoYzlRDYONpece9h0/dist=es2020,mode=imports,min/optimized/d3-path.js",
+        "309ba45d25d0c8c217b93e43d878e7823f99d074b3e2d8aaa6b6fe8dc631cbdf",
+    ),
+    (
+        "/-/d3-quadtree@v3.0.1-sMtwlDFghZGCTQ3UxKMT/dist=es2020,mode=imports,min/optimized/d3-quadtree.js",
+        "2e090ad8bcd3c537ca42e0c2d2f4a507fb24fba0a1ae27dbf8a0c4afde20b379",
+    ),
+    (
+        "/-/d3-scale-chromatic@v3.1.0-xNM6NGi6M0zu1PK6q2i9/dist=es2020,mode=imports,min/optimized/d3-scale-chromatic.js",
+        "b2a4030ef3b41fcff1b6682048e0c31ff07e5585c4cace2dccfeed199c996f42",
+    ),
+    (
+        "/-/d3-scale@v4.0.2-qUv67mnQQKwRMEsPRKcO/dist=es2020,mode=imports,min/optimized/d3-scale.js",
+        "ae7412c98139c35090dad7d3fa036433619e4603b9bb9d765182d1257bdae8ec",
+    ),
+    (
+        "/-/d3-shape@v3.2.0-jvLE9CjF3Vp4eEpVme8s/dist=es2020,mode=imports,min/optimized/d3-shape.js",
+        "494768d162039f23fa9b4565871d7aa7d1bef1e4162b56f74699cde38cb6b6f6",
+    ),
+    (
+        "/-/d3-time-format@v4.1.0-f8eZV7eLtGIxvK8uvO3o/dist=es2020,mode=imports,min/optimized/d3-time-format.js",
+        "73493073e31cf8769d2614fbc76c7a844f283f76b43eaee70e4655eb315a05ac",
+    ),
+    (
+        "/-/d3-time@v3.1.0-hkusO1LcNQpH1ccXwop7/dist=es2020,mode=imports,min/optimized/d3-time.js",
+        "a870b24c2e6b315cfada4610c495b81981cf90a89fc80afeaf82ff7164548729",
+    ),
+    (
+        "/-/d3-timer@v3.0.1-O0QpYiI2jhOLEJodLnN1/dist=es2020,mode=imports,min/optimized/d3-timer.js",
+        "47d14273d242e29d975a04666a8187fd1344897abd6b2659b2a7268d53df33fe",
+    ),
+    (
+        "/-/delaunator@v5.0.0-5MwqNhLRHOkBYnNXuoGN/dist=es2020,mode=imports,min/optimized/delaunator.js",
+        "c275f251b8a8cb145b4874bebe9bfcd22b208a0a89230fdee3b6edcfcb0cfc6e",
+    ),
+    (
+        "/-/fast-deep-equal@v3.1.3-ysejKs1WDEDPxUJhgGoP/dist=es2020,mode=imports,min/optimized/fast-deep-equal.js",
+        "e183aa8e6d9d42aa9403a6187691d48509d1254cca21fa30071e6aff5b9ace03",
+    ),
+    (
+        "/-/fast-json-patch@v3.1.1-IjacxII42OC4A6OXhkDe/dist=es2020,mode=imports,min/optimized/fast-json-patch.js",
+        "01cf95fc15a22bf8aa38a1518d5b11524f451847d4eeea9ede270c65981251a3",
+    ),
+    (
+        "/-/fast-json-stable-stringify@v2.1.0-HLgsuOtxPikt0pw16nth/dist=es2020,mode=imports,min/optimized/fast-json-stable-stringify.js",
+        "f6b960d28b482134a1798bdc7d722b1d61fa740862e696efb44a342184926302",
+    ),
+    (
+        "/-/internmap@v2.0.3-GWZlRrRMFcDlELwTQEZq/dist=es2020,mode=imports,min/optimized/internmap.js",
+        "c026464c4f8e67f18229e3a69cd834cd9c0c1ddff486baf5b5cc1f3b6b6e29c9",
+    ),
+    (
+        "/-/json-stringify-pretty-compact@v3.0.0-RM0i5NMwoiFhg7YNuXef/dist=es2020,mode=imports,min/optimized/json-stringify-pretty-compact.js",
+        "da5e4784895eee5857c97bdd03e074667720edce36e7eb111a313542ca94ac83",
+    ),
+    (
+        "/-/lodash.debounce@v4.0.8-aOLIwnE2RethWPrEzTeR/dist=es2020,mode=imports,min/optimized/lodash.debounce.js",
+        "a97d16af22d6209711ae7a4c83a99b31907e775b5d0e59cbfbc77e1d1e23ec99",
+    ),
+    (
+        "/-/robust-predicates@v3.0.1-4p4J15SSeLeNloSXgtZt/dist=es2020,mode=imports,min/optimized/robust-predicates.js",
+        "114a45cd5bf123ca82212f24383e3a196acf2ece11601bd85a316243d3bee8f5",
+    ),
+    (
+        "/-/topojson-client@v3.1.0-fyhI24JwGwsqazuuSEoq/dist=es2020,mode=imports,min/optimized/topojson-client.js",
+        "a4e9812a85bb20c7423e22018831e7d5ff9ad83659e6207e755687b1aa2b37c7",
+    ),
+    (
+        "/-/vega-canvas@v1.2.7-hCEcvULuKIOqBVGX1Tn8/dist=es2020,mode=imports,min/optimized/vega-canvas.js",
+        "030efaa9e812e620d012586d0ac6dba051aeb6b78badc3062f0b7221dd25ff07",
+    ),
+    (
+        "/-/vega-crossfilter@v4.1.2-4tsGV0HzWFU39iiKcBjX/dist=es2020,mode=imports,min/optimized/vega-crossfilter.js",
+        "7ff63ea51439ae7bafcf1cf192b127f643744a10df2aae5195f216cd86e7c00c",
+    ),
+    (
+        "/-/vega-dataflow@v5.7.6-WDoszrJUyhTwz7sPwzda/dist=es2020,mode=imports,min/optimized/vega-dataflow.js",
+        "0fe3d791f9598671dd833a02122007a73f93958a2973c89d0c7f9183f1875afd",
+    ),
+    (
+        "/-/vega-embed@v6.26.0-0yAdpZbj3i4rRevkUMhK/dist=es2020,mode=imports,min/optimized/vega-embed.js",
+        "732928d1f3737c629053f1ffec070330496f61007212f003017ad771cd7a3b4d",
+    ),
+    (
+        "/-/vega-encode@v4.10.1-Pg43i2sI9rM8xoeOkJ8i/dist=es2020,mode=imports,min/optimized/vega-encode.js",
+        "cb4e287874f0821ac9bde16f577ba266467ea052ea7167a2f7dbd9a36702e15d",
+    ),
+    (
+        "/-/vega-event-selector@v3.0.1-UgiEAWJA4WQL4DTKnV4R/dist=es2020,mode=imports,min/optimized/vega-event-selector.js",
+        "989e03b5cac918c561bff9ce0fac0de1f4babec32ddf84b45c0018b49233b8d8",
+    ),
+    (
+        "/-/vega-expression@v5.1.1-K4clrYr2THuj5KncykTn/dist=es2020,mode=imports,min/optimized/vega-expression.js",
+        "c32a0055e8bf68f80de5d89cb4a7c75966430a8f0e1ff38b79bbc58abd8c1318",
+    ),
+    (
+        "/-/vega-force@v4.2.1-a7V5XmM7au6PlfMbPpDy/dist=es2020,mode=imports,min/optimized/vega-force.js",
+        "73e3d6f9c4d2e13e635e33c9815cee9d2c84fcfd21e19acbd2755204802ff01c",
+    ),
+    (
+        "/-/vega-format@v1.1.2-rBsMjG1MOmb395qvySoI/dist=es2020,mode=imports,min/optimized/vega-format.js",
+        "46413dabf78207d061f7c0338d9605a9fd3f6e4214b513c544547bf42f261b58",
+    ),
+    (
+        "/-/vega-functions@v5.15.0-Bjrw9nnQutKMtsMi1DSI/dist=es2020,mode=imports,min/optimized/vega-functions.js",
+        "b5922b1aa5e4b78e214a6e62f3679bfe388fd92b743b7133cd930ccecf51bd5d",
+    ),
+    (
+        "/-/vega-geo@v4.4.2-VUooSgw91eB4Vs4BcK3h/dist=es2020,mode=imports,min/optimized/vega-geo.js",
+        "181516a49c5ec81ef993a9e70e75caf4d5f3c59b28d0bd66341252a3284c30d6",
+    ),
+    (
+        "/-/vega-hierarchy@v4.1.2-3wVtPHq13u3t0KUNwYDf/dist=es2020,mode=imports,min/optimized/vega-hierarchy.js",
+        "bdda308689e233f93c846eb1ddf41991921e3de8e087b4433abb26af1a2663c1",
+    ),
+    (
+        "/-/vega-interpreter@v1.0.5-xGayK8haM1EVgaoW7oOi/dist=es2020,mode=imports,min/optimized/vega-interpreter.js",
+        "d327a5a4989f2a47721228246cdf4f76ce46fdcce997366ac76a52494bc62233",
+    ),
+    (
+        "/-/vega-label@v1.3.0-9aipnSY6IQFGc3Lm2JSc/dist=es2020,mode=imports,min/optimized/vega-label.js",
+        "0a19e0332a8747cfe6202bdeecb2c001f172f7e892efbfec15dbc47adb502dd2",
+    ),
+    (
+        "/-/vega-lite@v5.14.1-0IRM1VigcIVzRzBRoLFR/dist=es2020,mode=imports,min/optimized/vega-lite.js",
+        "a967fe66cbc83c44b12edbcab2811f28e4dc0eabd87b847160c1c26c8a97cd66",
+    ),
+    (
+        "/-/vega-lite@v5.15.1-lQeQs8sDPgFa9d7Jm3sd/dist=es2020,mode=imports,min/optimized/vega-lite.js",
+        "1b74c05873dafc1625dfb5beef2a9852a4cd385d85cedaebf113ee340ea0b341",
+    ),
+    (
+        "/-/vega-lite@v5.16.3-Hw7pZxUuaiVgThsNMjY9/dist=es2020,mode=imports,min/optimized/vega-lite.js",
+        "a56dacb7897a43da113338b90608a9f86210fdde61217bbeea215408c518e6f6",
+    ),
+    (
+        "/-/vega-lite@v5.17.0-jkfrfJOQ30TsVIlEEbKQ/dist=es2020,mode=imports,min/optimized/vega-lite.js",
+        "015f1570c4655ef2a8b28339b3e9e5766e004fc07ca093b6fe1e07774f451ca2",
+    ),
+    (
+        "/-/vega-lite@v5.18.1-CIbWw1F4YnIlhO9UCtHA/dist=es2020,mode=imports,min/optimized/vega-lite.js",
+        "11c6472c5df6eed4f2358ef9e579528bd51db15318a9e8c1868b8f290913df7b",
+    ),
+    (
+        "/-/vega-lite@v5.19.0-4m5nwXbwdKW9Bc7adV02/dist=es2020,mode=imports,min/optimized/vega-lite.js",
+        "0596a09edd84b4909c44e823ef080f032442deb5bb97ab16c0d6ed3d590083e2",
+    ),
+    (
+        "/-/vega-lite@v5.20.1-5FloWSAHKfabpxOoogY3/dist=es2020,mode=imports,min/optimized/vega-lite.js",
+        "ed1be36d17c924e64d798a2d7f405a85f20dec2ec64716b8db0a3560c6aadaa1",
+    ),
+    (
+        "/-/vega-lite@v5.21.0-FWbVtRVTj7vqBi6QZX8A/dist=es2020,mode=imports,min/optimized/vega-lite.js",
+        "541ca513a5ab2ef8ddf0832f5b29e9163be04cdd4b521fe1303f5df1da203e1a",
+    ),
+    #[cfg(feature = "vl_legacy")]
+    (
+        "/-/vega-lite@v5.8.0-4snbURNltT4se5LjMOKF/dist=es2020,mode=imports,min/optimized/vega-lite.js",
+        "fd7f651db9a17c336304137faacacd4c6f095fed43432e18c8a9a94304d99969",
+    ),
+    (
+        "/-/vega-loader@v4.5.2-1ImBl2TigOVzvurACWyz/dist=es2020,mode=imports,min/optimized/vega-loader.js",
+        "7f40b6caec38abdb3e1bc6d5d4e87d74357c8900a9a8ddf3de600670ffea9cfb",
+    ),
+    (
+        "/-/vega-parser@v6.4.0-nwGMLAa2L4N1N7f1iRh9/dist=es2020,mode=imports,min/optimized/vega-parser.js",
+        "a45e09ee8289898f94325dc3e94277205bed3333ab7f79ae6e387508ecf21977",
+    ),
+    (
+        "/-/vega-projection@v1.6.1-zBdxeV9K67u1hPOXPxMf/dist=es2020,mode=imports,min/optimized/vega-projection.js",
+        "43cead0086bb17c8f3e282fd14d47bd0e8512675fb841c170ab02bf7eeb500f1",
+    ),
+    (
+        "/-/vega-regression@v1.3.0-nz37hjrP5F5zaTiwVMMX/dist=es2020,mode=imports,min/optimized/vega-regression.js",
+        "34d761c0ab44c918070008f8bfa7849c7a5038e70a8e10768984ab82e3154c09",
+    ),
+    (
+        "/-/vega-runtime@v6.2.0-6BElb2bSMc7jDTsbOGBN/dist=es2020,mode=imports,min/optimized/vega-runtime.js",
+        "9cc4c451f0d5327765c6a224c8ac99a71ea556a988df18707e0423890341e36c",
+    ),
+    (
+        "/-/vega-scale@v7.4.1-M0T9Gn9zHGGuV6XhZsTO/dist=es2020,mode=imports,min/optimized/vega-scale.js",
+        "0ca97e5e2406bc0fce00556f77b4d922bf142c1befbaf74807a5f88a7ccf3aaf",
+    ),
+    (
+        "/-/vega-scenegraph@v4.13.0-kC2RziuM0eIjiq6SsQ1g/dist=es2020,mode=imports,min/optimized/vega-scenegraph.js",
+        "78d80da8ab41436d70425426fef88cb83ba2ba1c647da0abe6ae3090a8e1fa84",
+    ),
+    (
+        "/-/vega-schema-url-parser@v2.2.0-YmXJGRcKOXOac3VG4xfw/dist=es2020,mode=imports,min/optimized/vega-schema-url-parser.js",
+        "ccf010628054283f5ff16e3aefcc2e339740760a96100a29290f0fbfe249a1f9",
+    ),
+    (
+        "/-/vega-selections@v5.4.2-kOpqaoZxiZQE5rKxTnvY/dist=es2020,mode=imports,min/optimized/vega-selections.js",
+        "b9bc45e9afb94b3b48f90de05f7d4eb081821137e86d902bf49818df1526307f",
+    ),
+    (
+        "/-/vega-statistics@v1.9.0-Qw8CjSQVQOg2M6VMgsme/dist=es2020,mode=imports,min/optimized/vega-statistics.js",
+        "b53eda8d47a6aa20a250960cdab31e07b40badf2211641965830672f54dc7d30",
+    ),
+    (
+        "/-/vega-themes@v2.15.0-wV2Z38N5Dk8FL05cPDbE/dist=es2020,mode=imports,min/optimized/vega-themes.js",
+        "a5ff887f32454cc67355840bdf7aeadb1cbb386d9bf569d692db301db8290643",
+    ),
+    (
+        "/-/vega-time@v2.1.2-0zBPNlF5GMoxR6YChBaP/dist=es2020,mode=imports,min/optimized/vega-time.js",
+        "addb9aed846f641690a6619bf3a510d05486b11b5370332e93338db0dc766e0a",
+    ),
+    (
+        "/-/vega-tooltip@v0.34.0-YVV3uKnnOnCt2kW7Vclb/dist=es2020,mode=imports,min/optimized/vega-tooltip.js",
+        "1cc562deac364eb9cd70a3b3a1bae6cccc556c6af38a2dc1ac42785ae18069b7",
+    ),
+    (
+        "/-/vega-transforms@v4.12.0-yproJe1RDXbiUml7qQ0X/dist=es2020,mode=imports,min/optimized/vega-transforms.js",
+        "654ea92a6354f5097b0a39470c50985633a4121528d57d27b22fd94aae00db7e",
+    ),
+    (
+        "/-/vega-util@v1.17.2-LUfkDhormMyfWqy3Ts6U/dist=es2020,mode=imports,min/optimized/vega-util.js",
+        "196e4300a9dab1cedf9ec44e4d900aacb85ba702a9cc10fe32e320e32f039da3",
+    ),
+    (
+        "/-/vega-view-transforms@v4.6.0-xmN4bj51geArzzY2TWCo/dist=es2020,mode=imports,min/optimized/vega-view-transforms.js",
+        "df9eef9cc8f386202a849985c62597eb134bbd01392f703b045d4f85aebabef9",
+    ),
+    (
+        "/-/vega-view@v5.13.0-kQxd3tMOiOBtyjX1ukMi/dist=es2020,mode=imports,min/optimized/vega-view.js",
+        "2c7e3d5fc737ae25039a1361e2efdfaa80b69bf0c2d236e30dd456b5645b1c25",
+    ),
+    (
+        "/-/vega-voronoi@v4.2.3-8V3D15dKJKAY9dyHESDq/dist=es2020,mode=imports,min/optimized/vega-voronoi.js",
+        "e2250e8014406021eeaffd00c433e92bbb64d7b87c7917183026a6535a9f1e0a",
+    ),
+    (
+        "/-/vega-wordcloud@v4.1.5-5xdRMt86NkNNCDjMSy6z/dist=es2020,mode=imports,min/optimized/vega-wordcloud.js",
+        "0c00dd48606ce4ca5c8fb568a799a478ca3e0949c71cc5a6fc42d2cbd13668b6",
+    ),
+    (
+        "/-/vega@v5.30.0-fYDVG3pUN16BiGmbVNdw/dist=es2020,mode=imports,min/optimized/vega.js",
+        "51335cc4c76cf2bcfcdfb3839d12f3ac7360da0f8cb4a7adf014af3943d1d5f6",
+    ),
+    (
+        "/pin/lodash.debounce@v4.0.8-aOLIwnE2RethWPrEzTeR/mode=imports,min/optimized/lodash.debounce.js",
+        "580ebc0a1474b64514daec8d72cb7ebf8ea8e4d88ae3337d7d9532dbd1c9474d",
+    ),
+    (
+        "/pin/vega-embed@v6.26.0-0yAdpZbj3i4rRevkUMhK/mode=imports,min/optimized/vega-embed.js",
+        "4539901dc6246005556ef19c57829a84b65ab601612e9ad6eddaaed0d8a8b591",
+    ),
+    (
+        "/pin/vega-lite@v5.14.1-0IRM1VigcIVzRzBRoLFR/mode=imports,min/optimized/vega-lite.js",
+        "1deefcadf929e50f6673aaecc6f6e27551c63ab6176cb60a1e6abedd0da288ec",
+    ),
+    (
+        "/pin/vega-lite@v5.15.1-lQeQs8sDPgFa9d7Jm3sd/mode=imports,min/optimized/vega-lite.js",
+        "77df09b00f23f614624c90755dbc6a84d5c45301cdc110909732ae3abe9bb9de",
+    ),
+    (
+        "/pin/vega-lite@v5.16.3-Hw7pZxUuaiVgThsNMjY9/mode=imports,min/optimized/vega-lite.js",
+        "914c222fda43afc18ea3d982abe8b254191053b774d92c352141f9586f7380d3",
+    ),
+    (
+        "/pin/vega-lite@v5.17.0-jkfrfJOQ30TsVIlEEbKQ/mode=imports,min/optimized/vega-lite.js",
+        "e02d9382524f6229d4e9c70ac689731567dbed67eeaf94366d3f889e28e20050",
+    ),
+    (
+        "/pin/vega-lite@v5.18.1-CIbWw1F4YnIlhO9UCtHA/mode=imports,min/optimized/vega-lite.js",
+        "2af67a3635b8232917a27d045933b749a0442f7ce46db1d35ab2da1117423b4b",
+    ),
+    (
+        "/pin/vega-lite@v5.19.0-4m5nwXbwdKW9Bc7adV02/mode=imports,min/optimized/vega-lite.js",
+        "c7e77e6dd06e00e4597675257059b2bee3606d321e2cea49d328e32d282c45e6",
+    ),
+    (
+        "/pin/vega-lite@v5.20.1-5FloWSAHKfabpxOoogY3/mode=imports,min/optimized/vega-lite.js",
+        "410d0577c376f15d782b36ecb9d154669430793736f686b6902aa0803bf97d36",
+    ),
+    (
+        "/pin/vega-lite@v5.21.0-FWbVtRVTj7vqBi6QZX8A/mode=imports,min/optimized/vega-lite.js",
+        "a8e1c978a241f3c2b2b9920017f9045ea4ad2d125fcd2e9515be38bf4c4a909a",
+    ),
+    #[cfg(feature = "vl_legacy")]
+    (
+        "/pin/vega-lite@v5.8.0-4snbURNltT4se5LjMOKF/mode=imports,min/optimized/vega-lite.js",
+        "4f825745d2760414fa566302818ccfce7fed762cea5a4f8501dba59e255a1256",
+    ),
+    (
+        "/pin/vega-themes@v2.15.0-wV2Z38N5Dk8FL05cPDbE/mode=imports,min/optimized/vega-themes.js",
+        "9053c3dfaeaca18a92e7064cca449d86997b639c71a85060022c025effe96c38",
+    ),
+    (
+        "/pin/vega@v5.30.0-fYDVG3pUN16BiGmbVNdw/mode=imports,min/optimized/vega.js",
+        "16ac2d057167e8eb2f2019ef2265abc51370236dddff72dda772a37912eba84e",
+    ),
+];
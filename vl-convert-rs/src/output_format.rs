@@ -0,0 +1,286 @@
+use crate::anyhow::{anyhow, bail};
+use crate::background::BackgroundColor;
+use crate::converter::{
+    encode_jpeg, encode_png_with_opts, svg_to_jpeg_info, svg_to_pdf_with_opts, svg_to_pixmap,
+    svg_to_png_info, JpegOpts, JpegResult, PdfOpts, PngOpts, PngResult, PpiMode,
+};
+use crate::image_rendering::ImageRendering;
+use deno_core::error::AnyError;
+use serde_json::{Map, Value};
+use std::str::FromStr;
+use std::sync::Arc;
+use tiny_skia::Pixmap;
+
+/// A format that charts can be converted to with
+/// [`VlConverter::convert_to`](crate::VlConverter::convert_to).
+///
+/// Formats implement [`from_svg`](OutputFormat::from_svg) to convert the SVG image of a chart.
+/// Raster formats rasterize the image with [`RenderContext::rasterize`] and encode the pixmap
+/// in [`from_pixmap`](OutputFormat::from_pixmap). The built-in svg, png, jpeg, and pdf formats
+/// are implemented with this trait, and other formats are added with
+/// [`VlConverter::register_output_format`](crate::VlConverter::register_output_format)
+#[allow(clippy::wrong_self_convention)]
+pub trait OutputFormat: Send + Sync {
+    /// Identifier that the format is requested by, e.g. "png"
+    fn id(&self) -> &str;
+
+    /// Convert the SVG image of a chart to this format, with the options of `ctx`
+    fn from_svg(&self, svg: &str, ctx: &RenderContext) -> Result<Vec<u8>, AnyError>;
+
+    /// Encode a chart that was rasterized at the scale and ppi of `ctx` in this format
+    fn from_pixmap(&self, _pixmap: Pixmap, _ctx: &RenderContext) -> Result<Vec<u8>, AnyError> {
+        bail!(
+            "The {} output format doesn't encode raster images",
+            self.id()
+        )
+    }
+}
+
+/// Options of a conversion with [`OutputFormat`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderContext {
+    /// Scale of raster images, relative to the size of the chart in CSS pixels
+    pub scale: f32,
+    /// Pixels per inch of raster images
+    pub ppi: f32,
+    /// Options of the conversion, including those that are specific to the format
+    pub options: Map<String, Value>,
+}
+
+impl Default for RenderContext {
+    fn default() -> Self {
+        Self {
+            scale: 1.0,
+            ppi: 72.0,
+            options: Map::new(),
+        }
+    }
+}
+
+impl RenderContext {
+    /// Context for the options of a conversion, as a JSON object. The `scale` and `ppi`
+    /// options set the scale and ppi of the context, and default to 1 and 72
+    pub fn from_options(options: Option<Value>) -> Result<Self, AnyError> {
+        let options = match options {
+            None | Some(Value::Null) => Map::new(),
+            Some(Value::Object(options)) => options,
+            Some(_) => bail!("Output format options must be a JSON object"),
+        };
+        let scale = number_option(&options, "scale")?.unwrap_or(1.0) as f32;
+        let ppi = number_option(&options, "ppi")?.unwrap_or(72.0) as f32;
+        Ok(Self {
+            scale,
+            ppi,
+            options,
+        })
+    }
+
+    /// Rasterize an SVG image at the scale and ppi of the context
+    pub fn rasterize(&self, svg: &str) -> Result<Pixmap, AnyError> {
+        svg_to_pixmap(svg, self.scale, Some(self.ppi), &PngOpts::default())
+    }
+
+    /// Value of a boolean option, when it's set
+    pub fn bool_option(&self, key: &str) -> Result<Option<bool>, AnyError> {
+        match self.options.get(key) {
+            None | Some(Value::Null) => Ok(None),
+            Some(Value::Bool(value)) => Ok(Some(*value)),
+            Some(_) => bail!("Output format option {} must be a boolean", key),
+        }
+    }
+
+    /// Value of a number option, when it's set
+    pub fn number_option(&self, key: &str) -> Result<Option<f64>, AnyError> {
+        number_option(&self.options, key)
+    }
+
+    /// Value of a string option, when it's set
+    pub fn str_option(&self, key: &str) -> Result<Option<&str>, AnyError> {
+        match self.options.get(key) {
+            None | Some(Value::Null) => Ok(None),
+            Some(Value::String(value)) => Ok(Some(value)),
+            Some(_) => bail!("Output format option {} must be a string", key),
+        }
+    }
+}
+
+fn number_option(options: &Map<String, Value>, key: &str) -> Result<Option<f64>, AnyError> {
+    match options.get(key) {
+        None | Some(Value::Null) => Ok(None),
+        Some(value) => value
+            .as_f64()
+            .map(Some)
+            .ok_or_else(|| anyhow!("Output format option {} must be a number", key)),
+    }
+}
+
+/// The SVG image of the chart, as UTF-8 bytes
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SvgFormat;
+
+impl OutputFormat for SvgFormat {
+    fn id(&self) -> &str {
+        "svg"
+    }
+
+    fn from_svg(&self, svg: &str, _ctx: &RenderContext) -> Result<Vec<u8>, AnyError> {
+        Ok(svg.as_bytes().to_vec())
+    }
+}
+
+/// PNG images. The `interlaced`, `antialias`, `ppi_mode`, `image_rendering`, and
+/// `flatten_background` options override those of `png_opts`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PngFormat {
+    pub png_opts: PngOpts,
+}
+
+impl PngFormat {
+    fn png_opts(&self, ctx: &RenderContext) -> Result<PngOpts, AnyError> {
+        let mut png_opts = self.png_opts;
+        if let Some(interlaced) = ctx.bool_option("interlaced")? {
+            png_opts.interlaced = interlaced;
+        }
+        if let Some(antialias) = ctx.bool_option("antialias")? {
+            png_opts.antialias = antialias;
+        }
+        if let Some(ppi_mode) = ctx.str_option("ppi_mode")? {
+            png_opts.ppi_mode = PpiMode::from_str(ppi_mode)?;
+        }
+        if let Some(image_rendering) = ctx.str_option("image_rendering")? {
+            png_opts.image_rendering = ImageRendering::from_str(image_rendering)?;
+        }
+        if let Some(background) = ctx.str_option("flatten_background")? {
+            png_opts.flatten_background = Some(BackgroundColor::from_str(background)?);
+        }
+        Ok(png_opts)
+    }
+
+    /// Convert the SVG image of a chart to PNG, returning the PNG image along with the size
+    /// that it was rendered at
+    pub fn png_info(&self, svg: &str, ctx: &RenderContext) -> Result<PngResult, AnyError> {
+        svg_to_png_info(svg, ctx.scale, Some(ctx.ppi), &self.png_opts(ctx)?)
+    }
+}
+
+impl OutputFormat for PngFormat {
+    fn id(&self) -> &str {
+        "png"
+    }
+
+    fn from_svg(&self, svg: &str, ctx: &RenderContext) -> Result<Vec<u8>, AnyError> {
+        Ok(self.png_info(svg, ctx)?.data)
+    }
+
+    fn from_pixmap(&self, pixmap: Pixmap, ctx: &RenderContext) -> Result<Vec<u8>, AnyError> {
+        encode_png_with_opts(pixmap, ctx.ppi, &self.png_opts(ctx)?)
+    }
+}
+
+/// JPEG images. The `quality` and `background` options override `quality` and the
+/// background of `jpeg_opts`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JpegFormat {
+    /// Quality between 1 (worst) and 100 (best). When None, a quality of 90 is used
+    pub quality: Option<u8>,
+    pub jpeg_opts: JpegOpts,
+}
+
+impl JpegFormat {
+    fn quality(&self, ctx: &RenderContext) -> Result<Option<u8>, AnyError> {
+        match ctx.number_option("quality")? {
+            None => Ok(self.quality),
+            Some(quality) if quality.fract() == 0.0 && (1.0..=100.0).contains(&quality) => {
+                Ok(Some(quality as u8))
+            }
+            Some(quality) => bail!(
+                "JPEG quality parameter must be between 1 and 100 inclusive. Received: {quality}"
+            ),
+        }
+    }
+
+    fn jpeg_opts(&self, ctx: &RenderContext) -> Result<JpegOpts, AnyError> {
+        let mut jpeg_opts = self.jpeg_opts;
+        if let Some(background) = ctx.str_option("background")? {
+            jpeg_opts.background = BackgroundColor::from_str(background)?;
+        }
+        Ok(jpeg_opts)
+    }
+
+    /// Convert the SVG image of a chart to JPEG, returning the JPEG image along with the size
+    /// that it was rendered at
+    pub fn jpeg_info(&self, svg: &str, ctx: &RenderContext) -> Result<JpegResult, AnyError> {
+        svg_to_jpeg_info(svg, ctx.scale, self.quality(ctx)?, &self.jpeg_opts(ctx)?)
+    }
+}
+
+impl OutputFormat for JpegFormat {
+    fn id(&self) -> &str {
+        "jpeg"
+    }
+
+    fn from_svg(&self, svg: &str, ctx: &RenderContext) -> Result<Vec<u8>, AnyError> {
+        Ok(self.jpeg_info(svg, ctx)?.data)
+    }
+
+    fn from_pixmap(&self, pixmap: Pixmap, ctx: &RenderContext) -> Result<Vec<u8>, AnyError> {
+        encode_jpeg(pixmap, self.quality(ctx)?, &self.jpeg_opts(ctx)?)
+    }
+}
+
+/// PDF documents. The `pdfa` and `source_dpi` options override those of `pdf_opts`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PdfFormat {
+    pub pdf_opts: PdfOpts,
+}
+
+impl OutputFormat for PdfFormat {
+    fn id(&self) -> &str {
+        "pdf"
+    }
+
+    fn from_svg(&self, svg: &str, ctx: &RenderContext) -> Result<Vec<u8>, AnyError> {
+        let mut pdf_opts = self.pdf_opts;
+        if let Some(pdfa) = ctx.bool_option("pdfa")? {
+            pdf_opts.pdfa = pdfa;
+        }
+        if let Some(source_dpi) = ctx.number_option("source_dpi")? {
+            pdf_opts.source_dpi = source_dpi as f32;
+        }
+        svg_to_pdf_with_opts(svg, &pdf_opts)
+    }
+}
+
+/// The formats that converters support before any are registered
+pub(crate) fn builtin_output_formats() -> Vec<Arc<dyn OutputFormat>> {
+    vec![
+        Arc::new(SvgFormat),
+        Arc::new(PngFormat::default()),
+        Arc::new(JpegFormat::default()),
+        Arc::new(PdfFormat::default()),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_render_context_from_options() {
+        let ctx = RenderContext::from_options(None).unwrap();
+        assert_eq!(ctx, RenderContext::default());
+
+        let ctx =
+            RenderContext::from_options(Some(json!({"scale": 2, "quality": 50, "pdfa": true})))
+                .unwrap();
+        assert_eq!((ctx.scale, ctx.ppi), (2.0, 72.0));
+        assert_eq!(ctx.number_option("quality").unwrap(), Some(50.0));
+        assert_eq!(ctx.bool_option("pdfa").unwrap(), Some(true));
+        assert!(ctx.str_option("pdfa").is_err());
+        assert_eq!(ctx.str_option("ppi_mode").unwrap(), None);
+
+        assert!(RenderContext::from_options(Some(json!([1, 2]))).is_err());
+        assert!(RenderContext::from_options(Some(json!({"scale": "2"}))).is_err());
+    }
+}
@@ -0,0 +1,132 @@
+use crate::anyhow::anyhow;
+use crate::text::has_color_glyphs;
+use deno_core::error::AnyError;
+use pdf_writer::types::OutputIntentSubtype;
+use pdf_writer::{Content, Finish, Name, Pdf, Rect, Ref, TextStr};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use svg2pdf::ConversionOptions;
+
+/// sRGB color profile that colors of PDF/A documents are interpreted with
+const SRGB_ICC: &[u8] = include_bytes!("../icc/sRGB-v4.icc");
+
+/// Producer of PDF documents, which is written to both the document information dictionary
+/// and the XMP metadata of PDF/A documents
+const PRODUCER: &str = concat!("vl-convert ", env!("CARGO_PKG_VERSION"));
+
+/// Convert an SVG image to a single page PDF document that conforms to PDF/A-2b, the PDF
+/// profile for long-term archiving.
+///
+/// svg2pdf writes the image as a form XObject without the features that PDF/A prohibits,
+/// which is placed on a page of a document with the XMP metadata packet that identifies it
+/// as PDF/A-2b, an sRGB output intent, and a document ID in the trailer. Like svg2pdf's own
+/// pages, the page is scaled by 72 / `dpi` from the pixels of the image to points
+pub(crate) fn svg_tree_to_pdfa(tree: &usvg::Tree, dpi: f32) -> Result<Vec<u8>, AnyError> {
+    let options = ConversionOptions {
+        pdfa: true,
+        embed_text: !has_color_glyphs(tree),
+        ..Default::default()
+    };
+    let (chunk, svg_ref) = svg2pdf::to_chunk(tree, options)
+        .map_err(|err| anyhow!("Failed to convert SVG to PDF: {}", err))?;
+
+    let mut alloc = Ref::new(1);
+    let catalog_ref = alloc.bump();
+    let page_tree_ref = alloc.bump();
+    let page_ref = alloc.bump();
+    let content_ref = alloc.bump();
+    let metadata_ref = alloc.bump();
+    let srgb_ref = alloc.bump();
+    let info_ref = alloc.bump();
+    let mut chunk_refs = HashMap::new();
+    let chunk = chunk.renumber(|old| *chunk_refs.entry(old).or_insert_with(|| alloc.bump()));
+    let svg_ref = chunk_refs[&svg_ref];
+
+    // The XObject is one point square, so it's scaled to the size of the page
+    let dpi_ratio = 72.0 / dpi;
+    let (width, height) = (
+        tree.size().width() * dpi_ratio,
+        tree.size().height() * dpi_ratio,
+    );
+    let svg_name = Name(b"S1");
+    let mut content = Content::new();
+    content.save_state();
+    content.transform([width, 0.0, 0.0, height, 0.0, 0.0]);
+    content.x_object(svg_name);
+    content.restore_state();
+    let content = content.finish();
+
+    let mut pdf = Pdf::new();
+    pdf.set_file_id(document_id(&content, chunk.as_bytes()));
+
+    let mut catalog = pdf.catalog(catalog_ref);
+    catalog.pages(page_tree_ref);
+    catalog.metadata(metadata_ref);
+    catalog
+        .output_intents()
+        .push()
+        .subtype(OutputIntentSubtype::PDFA)
+        .output_condition_identifier(TextStr("sRGB"))
+        .info(TextStr("sRGB IEC61966-2.1"))
+        .dest_output_profile(srgb_ref);
+    catalog.finish();
+
+    pdf.pages(page_tree_ref).count(1).kids([page_ref]);
+    let mut page = pdf.page(page_ref);
+    page.media_box(Rect::new(0.0, 0.0, width, height));
+    page.parent(page_tree_ref);
+    page.contents(content_ref);
+    page.resources().x_objects().pair(svg_name, svg_ref);
+    page.group()
+        .transparency()
+        .isolated(true)
+        .knockout(false)
+        .color_space()
+        .icc_based(srgb_ref);
+    page.finish();
+
+    pdf.stream(content_ref, &content);
+    pdf.icc_profile(srgb_ref, SRGB_ICC).n(3);
+    pdf.metadata(metadata_ref, xmp_metadata().as_bytes());
+    pdf.document_info(info_ref).producer(TextStr(PRODUCER));
+    pdf.extend(&chunk);
+    Ok(pdf.finish())
+}
+
+/// XMP metadata packet that identifies a document as PDF/A-2b. The producer must match the
+/// document information dictionary
+fn xmp_metadata() -> String {
+    format!(
+        r#"<?xpacket begin="{bom}" id="W5M0MpCehiHzreSzNTczkc9d"?>
+<x:xmpmeta xmlns:x="adobe:ns:meta/">
+  <rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+    <rdf:Description rdf:about=""
+        xmlns:dc="http://purl.org/dc/elements/1.1/"
+        xmlns:pdf="http://ns.adobe.com/pdf/1.3/"
+        xmlns:pdfaid="http://www.aiim.org/pdfa/ns/id/">
+      <dc:format>application/pdf</dc:format>
+      <pdf:Producer>{PRODUCER}</pdf:Producer>
+      <pdfaid:part>2</pdfaid:part>
+      <pdfaid:conformance>B</pdfaid:conformance>
+    </rdf:Description>
+  </rdf:RDF>
+</x:xmpmeta>
+<?xpacket end="w"?>"#,
+        bom = '\u{feff}'
+    )
+}
+
+/// Document ID of the trailer, which is derived from the content of the document so that
+/// converting the same chart twice gives the same document
+fn document_id(content: &[u8], chunk: &[u8]) -> (Vec<u8>, Vec<u8>) {
+    let id: Vec<u8> = [0u8, 1]
+        .iter()
+        .flat_map(|seed| {
+            let mut hasher = DefaultHasher::new();
+            (seed, content, chunk).hash(&mut hasher);
+            Hasher::finish(&hasher).to_be_bytes()
+        })
+        .collect();
+    (id.clone(), id)
+}
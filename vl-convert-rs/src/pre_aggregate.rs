@@ -0,0 +1,551 @@
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+
+/// Top-level properties of the single view specs that can be pre-aggregated
+const TOP_LEVEL_KEYS: &[&str] = &[
+    "$schema",
+    "data",
+    "datasets",
+    "mark",
+    "encoding",
+    "width",
+    "height",
+    "title",
+    "description",
+    "name",
+    "config",
+    "background",
+    "padding",
+    "autosize",
+    "usermeta",
+];
+
+/// Marks that render pre-aggregated rows the same way as the rows they're computed from
+const MARKS: &[&str] = &[
+    "bar", "rect", "tick", "point", "circle", "square", "line", "area", "trail",
+];
+
+/// Properties of the binned channel's field definition
+const BIN_DEF_KEYS: &[&str] = &["field", "type", "bin", "title", "axis"];
+
+/// Properties of the aggregated channel's field definition
+const AGGREGATE_DEF_KEYS: &[&str] = &["field", "type", "aggregate", "title", "axis", "scale"];
+
+/// Config properties that change the titles or ticks that Vega-Lite derives for binned and
+/// aggregated fields, which differ for pre-aggregated fields
+const UNSUPPORTED_CONFIG_KEYS: &[&str] = &["countTitle", "fieldTitle", "tickCount"];
+
+/// Vega-Lite's default maximum number of bins of the x and y channels
+const DEFAULT_MAXBINS: u64 = 10;
+
+/// Tolerance that Vega's bin transform adds when assigning values to bins
+const BIN_EPSILON: f64 = 1e-14;
+
+/// Bin and aggregate the inline data of a Vega-Lite spec, and rewrite the spec to encode the
+/// aggregated rows with `bin: {binned: true}`. This does the work of Vega-Lite's bin and
+/// aggregate transforms before the spec reaches JavaScript, which is where most of the time
+/// and memory of converting charts of large datasets goes.
+///
+/// Supported specs are single views of bar, rect, tick, point, circle, square, line, area,
+/// or trail marks with exactly an x and a y encoding: one binned quantitative field, and one
+/// quantitative count, sum, mean, min, or max aggregate. Their data must be inline `values`,
+/// or a named dataset of the top-level `datasets`, with numbers in the binned and aggregated
+/// fields. `configs` are the theme and config that the spec is rendered with.
+///
+/// The rewritten spec renders the same chart as the original spec. Unsupported specs return
+/// the reason they can't be pre-aggregated
+pub(crate) fn pre_aggregate_spec(spec: &Value, configs: &[&Value]) -> Result<Value, String> {
+    let Value::Object(spec) = spec else {
+        return Err("the spec is not an object".to_string());
+    };
+    if let Some(key) = spec
+        .keys()
+        .find(|key| !TOP_LEVEL_KEYS.contains(&key.as_str()))
+    {
+        return Err(format!("unsupported top-level property {key:?}"));
+    }
+    for config in configs.iter().copied().chain(spec.get("config")) {
+        if let Some(key) = find_key(config, UNSUPPORTED_CONFIG_KEYS) {
+            return Err(format!("unsupported config property {key:?}"));
+        }
+    }
+
+    let mark = match spec.get("mark") {
+        Some(Value::Object(mark)) => mark.get("type"),
+        mark => mark,
+    }
+    .and_then(Value::as_str);
+    if !mark.is_some_and(|mark| MARKS.contains(&mark)) {
+        return Err("unsupported mark".to_string());
+    }
+
+    let encoding = match spec.get("encoding") {
+        Some(Value::Object(encoding)) if encoding.len() == 2 => encoding,
+        _ => return Err("the encoding is not exactly x and y".to_string()),
+    };
+    let (Some(x), Some(y)) = (encoding.get("x"), encoding.get("y")) else {
+        return Err("the encoding is not exactly x and y".to_string());
+    };
+    let (bin_channel, bin_def, aggregate_channel, aggregate_def) =
+        match (x.get("bin").is_some(), y.get("bin").is_some()) {
+            (true, false) => ("x", x, "y", y),
+            (false, true) => ("y", y, "x", x),
+            _ => return Err("the encoding doesn't bin exactly one channel".to_string()),
+        };
+    let bin_field = BinField::try_new(bin_def)?;
+    let aggregate_field = AggregateField::try_new(aggregate_def)?;
+
+    let rows = inline_rows(spec)?;
+    let mut bin_values = Vec::with_capacity(rows.len());
+    let mut aggregate_values = Vec::with_capacity(rows.len());
+    for row in rows {
+        let Value::Object(row) = row else {
+            return Err("the data has rows that aren't objects".to_string());
+        };
+        let Some(value) = row.get(&bin_field.field).and_then(Value::as_f64) else {
+            return Err(format!(
+                "{:?} has values that aren't numbers",
+                bin_field.field
+            ));
+        };
+        bin_values.push(value);
+        if let Some(field) = &aggregate_field.field {
+            let Some(value) = row.get(field).and_then(Value::as_f64) else {
+                return Err(format!("{field:?} has values that aren't numbers"));
+            };
+            aggregate_values.push(value);
+        }
+    }
+    let Some(extent) = extent(&bin_values) else {
+        return Err("the data is empty".to_string());
+    };
+    let bins = Bins::new(extent, bin_field.maxbins as f64);
+
+    // Group the rows by bin, in the order the bins first appear like Vega's aggregate transform
+    let mut groups: Vec<(f64, Aggregate)> = Vec::new();
+    let mut group_indices: HashMap<u64, usize> = HashMap::new();
+    for (i, value) in bin_values.iter().enumerate() {
+        let bin0 = bins.bin0(*value);
+        let index = *group_indices.entry(bin0.to_bits()).or_insert_with(|| {
+            groups.push((bin0, Aggregate::default()));
+            groups.len() - 1
+        });
+        groups[index].1.add(aggregate_values.get(i).copied());
+    }
+
+    let bin_name = format!("bin_maxbins_{}_{}", bin_field.maxbins, bin_field.field);
+    let bin_end_name = format!("{bin_name}_end");
+    let aggregate_name = match &aggregate_field.field {
+        None => "__count".to_string(),
+        Some(field) => format!("{}_{field}", aggregate_field.op),
+    };
+    let values: Vec<Value> = groups
+        .iter()
+        .map(|(bin0, aggregate)| {
+            let mut row = Map::new();
+            row.insert(bin_name.clone(), json!(bin0));
+            row.insert(bin_end_name.clone(), json!(bin0 + bins.step));
+            row.insert(aggregate_name.clone(), aggregate.value(aggregate_field.op));
+            Value::Object(row)
+        })
+        .collect();
+
+    let mut rewritten = spec.clone();
+    rewritten.remove("datasets");
+    rewritten.insert("data".to_string(), json!({ "values": values }));
+    rewritten.insert(
+        "encoding".to_string(),
+        json!({
+            bin_channel: bin_field.binned_def(&bin_name, bins.step, bin_channel, bin_def),
+            format!("{bin_channel}2"): { "field": bin_end_name },
+            aggregate_channel: aggregate_field.aggregated_def(&aggregate_name, aggregate_def),
+        }),
+    );
+    Ok(Value::Object(rewritten))
+}
+
+/// The binned field of a supported encoding
+struct BinField {
+    field: String,
+    maxbins: u64,
+}
+
+impl BinField {
+    fn try_new(def: &Value) -> Result<Self, String> {
+        check_field_def(def, BIN_DEF_KEYS)?;
+        let field = match def.get("field").and_then(Value::as_str) {
+            Some(field) if is_plain_field(field) => field.to_string(),
+            _ => return Err("unsupported binned field".to_string()),
+        };
+        let maxbins = match &def["bin"] {
+            Value::Bool(true) => DEFAULT_MAXBINS,
+            Value::Object(bin) if bin.keys().all(|key| key == "maxbins") => {
+                match bin.get("maxbins").map(Value::as_u64) {
+                    None => DEFAULT_MAXBINS,
+                    Some(Some(maxbins)) if maxbins > 0 => maxbins,
+                    Some(_) => return Err("unsupported maxbins".to_string()),
+                }
+            }
+            _ => return Err("unsupported bin parameters".to_string()),
+        };
+        if def
+            .get("axis")
+            .is_some_and(|axis| axis.get("tickCount").is_some())
+        {
+            return Err("unsupported tickCount of the binned axis".to_string());
+        }
+        Ok(Self { field, maxbins })
+    }
+
+    /// Field definition of the pre-binned field, with the title and axis ticks that Vega-Lite
+    /// derives for the original field
+    fn binned_def(&self, name: &str, step: f64, channel: &str, def: &Value) -> Value {
+        let title = def
+            .get("title")
+            .cloned()
+            .unwrap_or_else(|| json!(format!("{} (binned)", self.field)));
+        let size = if channel == "x" { "width" } else { "height" };
+        let axis = match def.get("axis") {
+            Some(Value::Null) => Value::Null,
+            axis => {
+                let mut axis = axis.cloned().unwrap_or_else(|| json!({}));
+                axis["tickCount"] = json!({ "expr": format!("ceil({size}/10)") });
+                axis
+            }
+        };
+        json!({
+            "field": name,
+            "type": "quantitative",
+            "bin": { "binned": true, "step": step },
+            "title": title,
+            "axis": axis,
+        })
+    }
+}
+
+/// The aggregated field of a supported encoding. The field is None for count aggregates
+struct AggregateField {
+    op: &'static str,
+    field: Option<String>,
+}
+
+impl AggregateField {
+    fn try_new(def: &Value) -> Result<Self, String> {
+        check_field_def(def, AGGREGATE_DEF_KEYS)?;
+        let field = match def.get("field") {
+            None => None,
+            Some(Value::String(field)) if is_plain_field(field) => Some(field.clone()),
+            Some(_) => return Err("unsupported aggregated field".to_string()),
+        };
+        let op = match (def.get("aggregate").and_then(Value::as_str), &field) {
+            (Some("count"), None) => "count",
+            (Some("sum"), Some(_)) => "sum",
+            (Some("mean"), Some(_)) => "mean",
+            (Some("min"), Some(_)) => "min",
+            (Some("max"), Some(_)) => "max",
+            _ => return Err("unsupported aggregate".to_string()),
+        };
+        Ok(Self { op, field })
+    }
+
+    /// Field definition of the pre-aggregated field. Aggregating the single row of each bin
+    /// again keeps the stacking and opacity that Vega-Lite gives aggregated marks
+    fn aggregated_def(&self, name: &str, def: &Value) -> Value {
+        let title = def.get("title").cloned().unwrap_or_else(|| {
+            json!(match (self.op, &self.field) {
+                (_, None) => "Count of Records".to_string(),
+                (op, Some(field)) => format!("{}{} of {field}", op[..1].to_uppercase(), &op[1..]),
+            })
+        });
+        let mut aggregated = json!({
+            "field": name,
+            "type": "quantitative",
+            "aggregate": if self.op == "count" { "sum" } else { self.op },
+            "title": title,
+        });
+        for key in ["axis", "scale"] {
+            if let Some(value) = def.get(key) {
+                aggregated[key] = value.clone();
+            }
+        }
+        aggregated
+    }
+}
+
+/// Running aggregates of the rows of a bin, computed like Vega's aggregate operations so that
+/// the results match to the last bit
+#[derive(Default)]
+struct Aggregate {
+    count: u64,
+    sum: f64,
+    mean: f64,
+    min: Option<f64>,
+    max: Option<f64>,
+}
+
+impl Aggregate {
+    fn add(&mut self, value: Option<f64>) {
+        self.count += 1;
+        if let Some(value) = value {
+            self.sum += value;
+            self.mean += (value - self.mean) / self.count as f64;
+            self.min = Some(self.min.map_or(value, |min| min.min(value)));
+            self.max = Some(self.max.map_or(value, |max| max.max(value)));
+        }
+    }
+
+    fn value(&self, op: &str) -> Value {
+        match op {
+            "count" => json!(self.count),
+            "sum" => json!(self.sum),
+            "mean" => json!(self.mean),
+            "min" => json!(self.min),
+            _ => json!(self.max),
+        }
+    }
+}
+
+/// Bin boundaries, computed like the bin function of vega-statistics with its defaults
+#[derive(Debug, PartialEq)]
+struct Bins {
+    start: f64,
+    stop: f64,
+    step: f64,
+}
+
+impl Bins {
+    fn new((min, max): (f64, f64), maxbins: f64) -> Self {
+        let span = nonzero(max - min)
+            .or_else(|| nonzero(min.abs()))
+            .unwrap_or(1.0);
+        let level = (maxbins.ln() / 10f64.ln()).ceil();
+        let mut step = 10f64
+            .powf(js_round(span.ln() / 10f64.ln()) - level)
+            .max(0.0);
+        while (span / step).ceil() > maxbins {
+            step *= 10.0;
+        }
+        for div in [5.0, 2.0] {
+            let v = step / div;
+            if span / v <= maxbins {
+                step = v;
+            }
+        }
+
+        let v = step.ln();
+        let precision = if v >= 0.0 {
+            0.0
+        } else {
+            (-v / 10f64.ln()).trunc() + 1.0
+        };
+        let eps = 10f64.powf(-precision - 1.0);
+        let v = (min / step + eps).floor() * step;
+        let start = if min < v { v - step } else { v };
+        let stop = (max / step).ceil() * step;
+        Self {
+            start,
+            stop: if stop == start { start + step } else { stop },
+            step,
+        }
+    }
+
+    /// Start of the bin of a value
+    fn bin0(&self, value: f64) -> f64 {
+        let value = value.min(self.stop - self.step).max(self.start);
+        self.start + self.step * (BIN_EPSILON + (value - self.start) / self.step).floor()
+    }
+}
+
+fn nonzero(value: f64) -> Option<f64> {
+    (value != 0.0).then_some(value)
+}
+
+/// JavaScript's Math.round, which rounds halves up
+fn js_round(value: f64) -> f64 {
+    (value + 0.5).floor()
+}
+
+fn extent(values: &[f64]) -> Option<(f64, f64)> {
+    values.iter().fold(None, |extent, value| match extent {
+        None => Some((*value, *value)),
+        Some((min, max)) => Some((min.min(*value), max.max(*value))),
+    })
+}
+
+/// The rows of a spec's inline data, or of the named dataset that its data refers to
+fn inline_rows(spec: &Map<String, Value>) -> Result<&Vec<Value>, String> {
+    let Some(Value::Object(data)) = spec.get("data") else {
+        return Err("the data is not inline".to_string());
+    };
+    let values = match (data.len(), data.get("values"), data.get("name")) {
+        (1, Some(values), _) => Some(values),
+        (1, _, Some(Value::String(name))) => spec.get("datasets").and_then(|d| d.get(name)),
+        _ => None,
+    };
+    match values {
+        Some(Value::Array(rows)) => Ok(rows),
+        _ => Err("the data is not inline".to_string()),
+    }
+}
+
+fn check_field_def(def: &Value, keys: &[&str]) -> Result<(), String> {
+    let Value::Object(def) = def else {
+        return Err("unsupported field definition".to_string());
+    };
+    if let Some(key) = def.keys().find(|key| !keys.contains(&key.as_str())) {
+        return Err(format!("unsupported field definition property {key:?}"));
+    }
+    if def.get("type").and_then(Value::as_str) != Some("quantitative") {
+        return Err("the binned and aggregated fields aren't quantitative".to_string());
+    }
+    Ok(())
+}
+
+/// Whether a field name refers to a top-level property of the rows, without the nested
+/// access or escapes that Vega-Lite parses in field names
+fn is_plain_field(field: &str) -> bool {
+    !field.is_empty() && field.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// The first of `keys` that's a property of an object nested anywhere in `value`
+fn find_key<'a>(value: &Value, keys: &[&'a str]) -> Option<&'a str> {
+    match value {
+        Value::Object(obj) => keys
+            .iter()
+            .copied()
+            .find(|key| obj.contains_key(*key))
+            .or_else(|| obj.values().find_map(|value| find_key(value, keys))),
+        Value::Array(values) => values.iter().find_map(|value| find_key(value, keys)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn histogram(mark: &str) -> Value {
+        json!({
+            "data": {"values": [
+                {"a": 1.5, "b": 2}, {"a": 3.25, "b": 4}, {"a": 1.75, "b": 6}, {"a": 9.5, "b": 1}
+            ]},
+            "mark": mark,
+            "encoding": {
+                "x": {"field": "a", "bin": true, "type": "quantitative"},
+                "y": {"aggregate": "count", "type": "quantitative"}
+            }
+        })
+    }
+
+    #[test]
+    fn test_bins() {
+        assert_eq!(
+            Bins::new((1.5, 9.5), 10.0),
+            Bins {
+                start: 1.0,
+                stop: 10.0,
+                step: 1.0
+            }
+        );
+        assert_eq!(
+            Bins::new((3.1, 100.4), 20.0),
+            Bins {
+                start: 0.0,
+                stop: 105.0,
+                step: 5.0
+            }
+        );
+        assert_eq!(
+            Bins::new((4.0, 4.0), 10.0),
+            Bins {
+                start: 4.0,
+                stop: 4.5,
+                step: 0.5
+            }
+        );
+        let bins = Bins::new((1.5, 9.5), 10.0);
+        assert_eq!(bins.bin0(1.5), 1.0);
+        assert_eq!(bins.bin0(9.5), 9.0);
+        assert_eq!(bins.bin0(3.0), 3.0);
+    }
+
+    #[test]
+    fn test_pre_aggregate_spec() {
+        let spec = pre_aggregate_spec(&histogram("bar"), &[]).unwrap();
+        assert_eq!(
+            spec,
+            json!({
+                "data": {"values": [
+                    {"bin_maxbins_10_a": 1.0, "bin_maxbins_10_a_end": 2.0, "__count": 2},
+                    {"bin_maxbins_10_a": 3.0, "bin_maxbins_10_a_end": 4.0, "__count": 1},
+                    {"bin_maxbins_10_a": 9.0, "bin_maxbins_10_a_end": 10.0, "__count": 1}
+                ]},
+                "mark": "bar",
+                "encoding": {
+                    "x": {
+                        "field": "bin_maxbins_10_a",
+                        "type": "quantitative",
+                        "bin": {"binned": true, "step": 1.0},
+                        "title": "a (binned)",
+                        "axis": {"tickCount": {"expr": "ceil(width/10)"}}
+                    },
+                    "x2": {"field": "bin_maxbins_10_a_end"},
+                    "y": {
+                        "field": "__count",
+                        "type": "quantitative",
+                        "aggregate": "sum",
+                        "title": "Count of Records"
+                    }
+                }
+            })
+        );
+
+        let mut named = histogram("tick");
+        named["datasets"] = json!({"rows": named["data"]["values"].take()});
+        named["data"] = json!({"name": "rows"});
+        named["encoding"] = json!({
+            "y": {"field": "a", "bin": {"maxbins": 5}, "type": "quantitative", "axis": null},
+            "x": {"field": "b", "aggregate": "mean", "type": "quantitative", "title": "B"}
+        });
+        let spec = pre_aggregate_spec(&named, &[]).unwrap();
+        assert!(spec.get("datasets").is_none());
+        assert_eq!(
+            spec["data"]["values"],
+            json!([
+                {"bin_maxbins_5_a": 0.0, "bin_maxbins_5_a_end": 2.0, "mean_b": 4.0},
+                {"bin_maxbins_5_a": 2.0, "bin_maxbins_5_a_end": 4.0, "mean_b": 4.0},
+                {"bin_maxbins_5_a": 8.0, "bin_maxbins_5_a_end": 10.0, "mean_b": 1.0}
+            ])
+        );
+        assert_eq!(spec["encoding"]["y"]["axis"], Value::Null);
+        assert_eq!(spec["encoding"]["x"]["title"], json!("B"));
+    }
+
+    #[test]
+    fn test_pre_aggregate_spec_unsupported() {
+        let mut transform = histogram("bar");
+        transform["transform"] = json!([{"filter": "datum.a > 2"}]);
+        let mut color = histogram("bar");
+        color["encoding"]["color"] = json!({"value": "red"});
+        let mut null_value = histogram("bar");
+        null_value["data"]["values"][1]["a"] = Value::Null;
+        let mut url = histogram("bar");
+        url["data"] = json!({"url": "data/cars.json"});
+        for spec in [
+            transform,
+            color,
+            null_value,
+            url,
+            histogram("arc"),
+            histogram("text"),
+        ] {
+            assert!(pre_aggregate_spec(&spec, &[]).is_err(), "{spec}");
+        }
+
+        let theme_config = json!({"axisQuantitative": {"tickCount": 3}});
+        assert_eq!(
+            pre_aggregate_spec(&histogram("bar"), &[&theme_config]),
+            Err("unsupported config property \"tickCount\"".to_string())
+        );
+    }
+}
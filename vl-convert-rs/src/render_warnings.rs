@@ -0,0 +1,20 @@
+use serde::{Deserialize, Serialize};
+
+/// Part of a chart that was left out of a conversion performed with the `lenient` option,
+/// rather than failing the conversion
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RenderWarning {
+    /// Name of the mark that the skipped item belongs to, when it's known
+    pub mark: Option<String>,
+    pub message: String,
+}
+
+/// Add `more` warnings to `warnings`. Warnings that were already added, e.g. by rasterizing
+/// the same SVG to another format, are skipped
+pub(crate) fn extend_render_warnings(warnings: &mut Vec<RenderWarning>, more: Vec<RenderWarning>) {
+    for warning in more {
+        if !warnings.contains(&warning) {
+            warnings.push(warning);
+        }
+    }
+}
@@ -0,0 +1,210 @@
+use crate::converter::{FormatLocale, TimeFormatLocale, VgOpts, VlOpts};
+use serde_json::{json, Number, Value};
+use sha2::{Digest, Sha256};
+
+/// Largest integer that's exactly representable as an f64 (2^53)
+const MAX_SAFE_INTEGER: f64 = 9007199254740992.0;
+
+/// Serialize a JSON value canonically, so that equal values serialize to the same string
+/// across runs and releases: object keys are sorted by their UTF-16 code units (like
+/// JavaScript's default sort), there's no whitespace, and numbers with an integer value are
+/// written as integers (e.g. `1.0` and `-0.0` are written as `1` and `0`). Other numbers are
+/// written in their shortest round-trip decimal form, without an exponent. Strings are
+/// escaped as serde_json escapes them
+pub fn canonical_json(value: &Value) -> String {
+    let mut out = String::new();
+    write_canonical(value, &mut out);
+    out
+}
+
+fn write_canonical(value: &Value, out: &mut String) {
+    match value {
+        Value::Null | Value::Bool(_) | Value::String(_) => out.push_str(&value.to_string()),
+        Value::Number(number) => out.push_str(&canonical_number(number)),
+        Value::Array(values) => {
+            out.push('[');
+            for (i, value) in values.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_canonical(value, out);
+            }
+            out.push(']');
+        }
+        Value::Object(obj) => {
+            let mut entries = obj.iter().collect::<Vec<_>>();
+            entries.sort_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16()));
+            out.push('{');
+            for (i, (key, value)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str(&Value::String(key.clone()).to_string());
+                out.push(':');
+                write_canonical(value, out);
+            }
+            out.push('}');
+        }
+    }
+}
+
+fn canonical_number(number: &Number) -> String {
+    if number.is_i64() || number.is_u64() {
+        return number.to_string();
+    }
+    match number.as_f64() {
+        Some(f) if f.fract() == 0.0 && f.abs() <= MAX_SAFE_INTEGER => (f as i64).to_string(),
+        Some(f) => f.to_string(),
+        None => number.to_string(),
+    }
+}
+
+/// Hash of a conversion request, as a hex SHA-256 digest of the canonical JSON of the spec,
+/// the options of the conversion, and the vl-convert version. Requests with equal specs and
+/// options have the same hash with a given version of vl-convert, so callers can use it to
+/// deduplicate requests and to check whether an artifact they hold is up to date
+pub fn request_hash(spec: &Value, options: &Value) -> String {
+    let request = json!({
+        "spec": spec,
+        "options": options,
+        "version": env!("CARGO_PKG_VERSION"),
+    });
+    format!("{:x}", Sha256::digest(canonical_json(&request).as_bytes()))
+}
+
+/// Request hash of the conversion of a Vega-Lite spec with the given options to an image,
+/// which `output` describes (e.g. its format and scale).
+///
+/// Options that hold JSON (like the config) are hashed canonically, and the others by their
+/// Debug representation. An SVG post-processor is hashed by whether it's set, since functions
+/// can't be compared
+pub fn vegalite_request_hash(vl_spec: &Value, vl_opts: &VlOpts, output: Value) -> String {
+    let mut opts = vl_opts.clone();
+    let json_opts = json!({
+        "config": opts.config.take(),
+        "config_overrides": opts.config_overrides.take(),
+        "format_locale": opts.format_locale.take().map(format_locale_json),
+        "time_format_locale": opts.time_format_locale.take().map(time_format_locale_json),
+        "interaction_script": opts.interaction_script.take(),
+        "generic_font_mapping": opts.generic_font_mapping.take(),
+    });
+    request_hash(vl_spec, &opts_json(format!("{opts:?}"), json_opts, output))
+}
+
+/// Request hash of the conversion of a Vega spec with the given options to an image, like
+/// [`vegalite_request_hash`]
+pub fn vega_request_hash(vg_spec: &Value, vg_opts: &VgOpts, output: Value) -> String {
+    let mut opts = vg_opts.clone();
+    let json_opts = json!({
+        "config": opts.config.take(),
+        "format_locale": opts.format_locale.take().map(format_locale_json),
+        "time_format_locale": opts.time_format_locale.take().map(time_format_locale_json),
+        "interaction_script": opts.interaction_script.take(),
+        "generic_font_mapping": opts.generic_font_mapping.take(),
+    });
+    request_hash(vg_spec, &opts_json(format!("{opts:?}"), json_opts, output))
+}
+
+fn opts_json(opts: String, json_opts: Value, output: Value) -> Value {
+    json!({"opts": opts, "json_opts": json_opts, "output": output})
+}
+
+fn format_locale_json(locale: FormatLocale) -> Value {
+    match locale {
+        FormatLocale::Name(name) => Value::String(name),
+        FormatLocale::Object(object) => object,
+    }
+}
+
+fn time_format_locale_json(locale: TimeFormatLocale) -> Value {
+    match locale {
+        TimeFormatLocale::Name(name) => Value::String(name),
+        TimeFormatLocale::Object(object) => object,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_canonical_json() {
+        let value = json!({
+            "b": [1.0, -0.0, 2.5, 10],
+            "a": {"z": null, "é": "tab\t", "Z": true},
+            "": "empty"
+        });
+        assert_eq!(
+            canonical_json(&value),
+            r#"{"":"empty","a":{"Z":true,"z":null,"é":"tab\t"},"b":[1,0,2.5,10]}"#
+        );
+        assert_eq!(
+            canonical_json(&json!([1e21, 1.5e-7])),
+            "[1000000000000000000000,0.00000015]"
+        );
+    }
+
+    #[test]
+    fn test_canonical_json_key_order() {
+        // Keys are sorted by UTF-16 code units, so characters outside the Basic Multilingual
+        // Plane sort before the characters at the end of it
+        let value = json!({"\u{ffff}": 1, "\u{1f600}": 2});
+        assert_eq!(canonical_json(&value), "{\"\u{1f600}\":2,\"\u{ffff}\":1}");
+    }
+
+    #[test]
+    fn test_request_hash() {
+        let spec = json!({"mark": "bar", "width": 100.0});
+        let hash = request_hash(&spec, &json!({"scale": 2, "theme": "dark"}));
+        assert_eq!(hash.len(), 64);
+
+        // Key order and the representation of numbers don't change the hash
+        let reordered = json!({"width": 100, "mark": "bar"});
+        assert_eq!(
+            request_hash(&reordered, &json!({"theme": "dark", "scale": 2.0})),
+            hash
+        );
+        assert_ne!(request_hash(&spec, &json!({"scale": 1})), hash);
+    }
+
+    #[test]
+    fn test_vegalite_request_hash() {
+        let spec = json!({"mark": "bar"});
+        let output = json!({"format": "png", "scale": 2.0});
+        let vl_opts = VlOpts {
+            config: Some(json!({"a": 1, "b": 2.0})),
+            generic_font_mapping: Some(
+                [
+                    ("serif", "Liberation Serif"),
+                    ("sans-serif", "Liberation Sans"),
+                ]
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            ),
+            ..Default::default()
+        };
+        let hash = vegalite_request_hash(&spec, &vl_opts, output.clone());
+
+        // The order of the config's keys doesn't change the hash
+        let reordered = VlOpts {
+            config: Some(json!({"b": 2, "a": 1})),
+            ..vl_opts.clone()
+        };
+        assert_eq!(
+            vegalite_request_hash(&spec, &reordered, output.clone()),
+            hash
+        );
+
+        let themed = VlOpts {
+            theme: Some("dark".to_string()),
+            ..vl_opts.clone()
+        };
+        assert_ne!(vegalite_request_hash(&spec, &themed, output), hash);
+        assert_ne!(
+            vegalite_request_hash(&spec, &vl_opts, json!({"format": "png", "scale": 1.0})),
+            hash
+        );
+    }
+}
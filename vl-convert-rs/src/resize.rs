@@ -0,0 +1,189 @@
+use crate::anyhow::{anyhow, bail};
+use deno_core::error::AnyError;
+use serde_json::{json, Value};
+use std::borrow::Cow;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// How a chart is laid out in a width and height that override its own
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ResizeMode {
+    /// The width and height are the size of the whole chart, including its axes, legends, and
+    /// titles, and the plot area shrinks to make room for them (autosize "fit")
+    #[default]
+    Fit,
+    /// The width and height are the size of the plot area, and the chart grows to make room
+    /// for its axes, legends, and titles (autosize "pad")
+    Pad,
+    /// Only the width and height of the spec are replaced, and it keeps its own autosize
+    None,
+}
+
+impl Display for ResizeMode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let resize_mode = match self {
+            ResizeMode::Fit => "fit",
+            ResizeMode::Pad => "pad",
+            ResizeMode::None => "none",
+        };
+        std::fmt::Display::fmt(resize_mode, f)
+    }
+}
+
+impl FromStr for ResizeMode {
+    type Err = AnyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.trim().to_ascii_lowercase().as_str() {
+            "fit" => Self::Fit,
+            "pad" => Self::Pad,
+            "none" => Self::None,
+            _ => return Err(anyhow!("Unsupported resize mode: {}", s)),
+        })
+    }
+}
+
+/// Parse a `WIDTHxHEIGHT` minimum chart size, like those given with the --min-size flag of
+/// the CLI (e.g. `100x100`)
+pub fn parse_min_size(arg: &str) -> Result<(u32, u32), AnyError> {
+    let size = arg
+        .split_once(['x', 'X'])
+        .and_then(|(width, height)| {
+            Some((
+                width.trim().parse::<u32>().ok()?,
+                height.trim().parse::<u32>().ok()?,
+            ))
+        })
+        .filter(|(width, height)| *width > 0 && *height > 0);
+    match size {
+        Some(size) => Ok(size),
+        None => bail!("Invalid minimum size {arg:?}, expected WIDTHxHEIGHT in whole pixels"),
+    }
+}
+
+/// Replace the width and height of a Vega or Vega-Lite spec, and set its autosize type for
+/// `resize_mode`, before the view is constructed. Vega then lays out the axes and legends of
+/// the chart in the new size, rather than in the size that the spec was written for.
+/// Top-level width and height signals of Vega specs, like those that Vega-Lite compiles
+/// step-based sizes to, are removed so that they don't replace the size. Returns the spec
+/// unchanged when neither width nor height is set
+pub(crate) fn with_size_override(
+    spec: &Value,
+    width: Option<f32>,
+    height: Option<f32>,
+    resize_mode: ResizeMode,
+) -> Result<Cow<'_, Value>, AnyError> {
+    for (name, value) in [("width", width), ("height", height)] {
+        if let Some(value) = value {
+            if !value.is_finite() || value <= 0.0 {
+                bail!("{name} must be a positive number, got {value}")
+            }
+        }
+    }
+    let Value::Object(obj) = spec else {
+        return Ok(Cow::Borrowed(spec));
+    };
+    if width.is_none() && height.is_none() {
+        return Ok(Cow::Borrowed(spec));
+    }
+
+    let mut obj = obj.clone();
+    for (name, value) in [("width", width), ("height", height)] {
+        let Some(value) = value else {
+            continue;
+        };
+        obj.insert(name.to_string(), json!(value));
+        if let Some(Value::Array(signals)) = obj.get_mut("signals") {
+            signals.retain(|signal| signal.get("name").and_then(Value::as_str) != Some(name));
+        }
+    }
+
+    let autosize_type = match resize_mode {
+        ResizeMode::Fit => "fit",
+        ResizeMode::Pad => "pad",
+        ResizeMode::None => return Ok(Cow::Owned(Value::Object(obj))),
+    };
+    let mut autosize = match obj.remove("autosize") {
+        Some(Value::Object(autosize)) => autosize,
+        Some(Value::String(autosize_type)) => {
+            let mut autosize = serde_json::Map::new();
+            autosize.insert("type".to_string(), Value::String(autosize_type));
+            autosize
+        }
+        _ => serde_json::Map::new(),
+    };
+    autosize.insert("type".to_string(), json!(autosize_type));
+    if resize_mode == ResizeMode::Fit {
+        // Include the padding in the size, so that the image is exactly width x height
+        autosize.insert("contains".to_string(), json!("padding"));
+    }
+    obj.insert("autosize".to_string(), Value::Object(autosize));
+    Ok(Cow::Owned(Value::Object(obj)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_with_size_override() {
+        let spec = json!({
+            "width": 200,
+            "autosize": {"type": "pad", "resize": true},
+            "signals": [
+                {"name": "width", "update": "bandspace(10, 0.1, 0.05) * 20"},
+                {"name": "x_step", "value": 20}
+            ]
+        });
+        assert!(matches!(
+            with_size_override(&spec, None, None, ResizeMode::Fit).unwrap(),
+            Cow::Borrowed(_)
+        ));
+
+        let fit = with_size_override(&spec, Some(300.0), None, ResizeMode::Fit).unwrap();
+        assert_eq!(
+            fit.into_owned(),
+            json!({
+                "width": 300.0,
+                "autosize": {"type": "fit", "resize": true, "contains": "padding"},
+                "signals": [{"name": "x_step", "value": 20}]
+            })
+        );
+
+        let spec = json!({"mark": "bar", "autosize": "fit"});
+        let pad = with_size_override(&spec, Some(300.0), Some(100.0), ResizeMode::Pad).unwrap();
+        assert_eq!(
+            pad.into_owned(),
+            json!({
+                "mark": "bar",
+                "width": 300.0,
+                "height": 100.0,
+                "autosize": {"type": "pad"}
+            })
+        );
+
+        let none = with_size_override(&spec, None, Some(100.0), ResizeMode::None).unwrap();
+        assert_eq!(
+            none.into_owned(),
+            json!({"mark": "bar", "height": 100.0, "autosize": "fit"})
+        );
+
+        assert!(with_size_override(&spec, Some(0.0), None, ResizeMode::Fit).is_err());
+    }
+
+    #[test]
+    fn test_parse_resize_mode() {
+        assert_eq!(ResizeMode::from_str("Pad").unwrap(), ResizeMode::Pad);
+        assert_eq!(ResizeMode::None.to_string(), "none");
+        assert!(ResizeMode::from_str("stretch").is_err());
+    }
+
+    #[test]
+    fn test_parse_min_size() {
+        assert_eq!(parse_min_size("100x50").unwrap(), (100, 50));
+        assert_eq!(parse_min_size(" 20 X 10 ").unwrap(), (20, 10));
+        assert!(parse_min_size("0x10").is_err());
+        assert!(parse_min_size("100").is_err());
+        assert!(parse_min_size("1.5x2").is_err());
+    }
+}
@@ -0,0 +1,161 @@
+use crate::anyhow::bail;
+use deno_core::error::AnyError;
+use serde::Serialize;
+use std::sync::Mutex;
+
+lazy_static! {
+    static ref LAST_RESOURCE_STATS: Mutex<Option<ResourceStats>> = Mutex::new(None);
+}
+
+/// Memory statistics for a single conversion, collected when the `collect_resource_stats`
+/// option is enabled
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct ResourceStats {
+    /// Bytes in use by the V8 heap after the JavaScript portion of the conversion
+    pub v8_heap_used_bytes: u64,
+    /// Total bytes reserved by the V8 heap after the JavaScript portion of the conversion
+    pub v8_heap_total_bytes: u64,
+    /// Size of the RGBA pixmap that was rasterized, or 0 for non-raster output formats
+    pub pixmap_bytes: u64,
+    /// Size of the rendered SVG, or 0 if the conversion didn't render SVG
+    pub svg_bytes: u64,
+    /// Growth of the process resident set size over the course of the conversion
+    pub peak_rss_delta_bytes: u64,
+}
+
+/// Get the resource statistics of the most recent conversion that was performed with
+/// `collect_resource_stats` enabled
+pub fn get_last_resource_stats() -> Result<Option<ResourceStats>, AnyError> {
+    match LAST_RESOURCE_STATS.lock() {
+        Ok(guard) => Ok(*guard),
+        Err(err) => {
+            bail!("Failed to acquire lock: {}", err)
+        }
+    }
+}
+
+pub(crate) fn set_last_resource_stats(stats: ResourceStats) -> Result<(), AnyError> {
+    match LAST_RESOURCE_STATS.lock() {
+        Ok(mut guard) => {
+            *guard = Some(stats);
+        }
+        Err(err) => {
+            bail!("Failed to acquire lock: {}", err)
+        }
+    }
+    Ok(())
+}
+
+/// Record the size of the rasterized pixmap against the most recent resource statistics,
+/// extending the RSS delta to cover rasterization
+pub(crate) fn record_pixmap_stats(
+    rss_before: &RssSample,
+    pixmap_bytes: u64,
+) -> Result<(), AnyError> {
+    let mut stats = get_last_resource_stats()?.unwrap_or_default();
+    stats.pixmap_bytes = pixmap_bytes;
+    stats.peak_rss_delta_bytes = stats
+        .peak_rss_delta_bytes
+        .max(RssSample::now().delta_since(rss_before));
+    set_last_resource_stats(stats)
+}
+
+/// Current and peak resident set size of the process, in bytes.
+///
+/// Values are 0 on platforms where they aren't available
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct RssSample {
+    rss: u64,
+    peak_rss: u64,
+}
+
+impl RssSample {
+    pub fn now() -> Self {
+        let (rss, peak_rss) = process_rss_bytes().unwrap_or_default();
+        Self { rss, peak_rss }
+    }
+
+    /// Memory growth since an earlier sample.
+    ///
+    /// The process peak only moves when a new high-water mark is reached, so growth of the
+    /// peak is attributed to the conversion when there is any. Otherwise, the growth of the
+    /// current resident set size is used.
+    pub fn delta_since(&self, before: &RssSample) -> u64 {
+        let peak_delta = self.peak_rss.saturating_sub(before.peak_rss);
+        let rss_delta = self.rss.saturating_sub(before.rss);
+        peak_delta.max(rss_delta)
+    }
+}
+
+/// Returns the (current, peak) resident set size of the process in bytes
+#[cfg(target_os = "linux")]
+fn process_rss_bytes() -> Option<(u64, u64)> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let mut rss: Option<u64> = None;
+    let mut peak_rss: Option<u64> = None;
+    for line in status.lines() {
+        if let Some(value) = line.strip_prefix("VmRSS:") {
+            rss = parse_status_kb(value);
+        } else if let Some(value) = line.strip_prefix("VmHWM:") {
+            peak_rss = parse_status_kb(value);
+        }
+    }
+    let rss = rss?;
+    Some((rss, peak_rss.unwrap_or(rss)))
+}
+
+/// Parse a value like "   12345 kB" from /proc/self/status into bytes
+#[cfg(target_os = "linux")]
+fn parse_status_kb(value: &str) -> Option<u64> {
+    let kb = value
+        .trim()
+        .strip_suffix("kB")?
+        .trim()
+        .parse::<u64>()
+        .ok()?;
+    Some(kb * 1024)
+}
+
+/// Returns the (current, peak) resident set size of the process in bytes
+#[cfg(target_os = "macos")]
+fn process_rss_bytes() -> Option<(u64, u64)> {
+    let mut info: libc::mach_task_basic_info = unsafe { std::mem::zeroed() };
+    let mut count = libc::MACH_TASK_BASIC_INFO_COUNT;
+    let result = unsafe {
+        libc::task_info(
+            libc::mach_task_self(),
+            libc::MACH_TASK_BASIC_INFO,
+            &mut info as *mut libc::mach_task_basic_info as libc::task_info_t,
+            &mut count,
+        )
+    };
+    if result != libc::KERN_SUCCESS {
+        return None;
+    }
+    Some((info.resident_size, info.resident_size_max))
+}
+
+/// Returns the (current, peak) resident set size of the process in bytes
+#[cfg(windows)]
+fn process_rss_bytes() -> Option<(u64, u64)> {
+    use windows_sys::Win32::System::ProcessStatus::{
+        GetProcessMemoryInfo, PROCESS_MEMORY_COUNTERS,
+    };
+    use windows_sys::Win32::System::Threading::GetCurrentProcess;
+
+    let mut counters: PROCESS_MEMORY_COUNTERS = unsafe { std::mem::zeroed() };
+    let cb = std::mem::size_of::<PROCESS_MEMORY_COUNTERS>() as u32;
+    let result = unsafe { GetProcessMemoryInfo(GetCurrentProcess(), &mut counters, cb) };
+    if result == 0 {
+        return None;
+    }
+    Some((
+        counters.WorkingSetSize as u64,
+        counters.PeakWorkingSetSize as u64,
+    ))
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+fn process_rss_bytes() -> Option<(u64, u64)> {
+    None
+}
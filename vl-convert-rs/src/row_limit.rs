@@ -0,0 +1,125 @@
+use crate::anyhow::{anyhow, bail};
+use deno_core::error::AnyError;
+use serde_json::{json, Value};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// How datasets with more rows than the `max_rows` option are handled
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RowLimitStrategy {
+    /// Fail the conversion, reporting the number of rows of the dataset
+    #[default]
+    Error,
+    /// Keep the first `max_rows` rows
+    Head,
+    /// Keep a uniform random sample of `max_rows` rows, in their original order. The sample
+    /// is drawn with the given seed, so that it's the same for every conversion
+    Sample(u32),
+}
+
+impl Display for RowLimitStrategy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RowLimitStrategy::Error => write!(f, "error"),
+            RowLimitStrategy::Head => write!(f, "head"),
+            RowLimitStrategy::Sample(seed) => write!(f, "sample:{seed}"),
+        }
+    }
+}
+
+impl FromStr for RowLimitStrategy {
+    type Err = AnyError;
+
+    /// Parse `error`, `head`, `sample`, or `sample:SEED`. `sample` without a seed uses a
+    /// seed of 0
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim().to_ascii_lowercase();
+        Ok(match s.split_once(':') {
+            None if s == "error" => Self::Error,
+            None if s == "head" => Self::Head,
+            None if s == "sample" => Self::Sample(0),
+            Some(("sample", seed)) => Self::Sample(
+                seed.trim()
+                    .parse()
+                    .map_err(|_| anyhow!("Invalid row limit sample seed: {}", seed))?,
+            ),
+            _ => {
+                return Err(anyhow!(
+                    "Unsupported row limit strategy: {}. Expected error, head, sample, or \
+                    sample:SEED",
+                    s
+                ))
+            }
+        })
+    }
+}
+
+/// JavaScript literal for the row limit of a conversion, which is enforced as the datasets of
+/// the view are parsed. `null` when `max_rows` isn't set
+pub(crate) fn row_limit_arg(
+    max_rows: Option<u64>,
+    strategy: RowLimitStrategy,
+    annotate_truncation: bool,
+) -> Result<String, AnyError> {
+    let Some(max_rows) = max_rows else {
+        return Ok("null".to_string());
+    };
+    if max_rows == 0 {
+        bail!("max_rows must be at least 1")
+    }
+    let (strategy, seed) = match strategy {
+        RowLimitStrategy::Error => ("error", Value::Null),
+        RowLimitStrategy::Head => ("head", Value::Null),
+        RowLimitStrategy::Sample(seed) => ("sample", Value::from(seed)),
+    };
+    Ok(json!({
+        "maxRows": max_rows,
+        "strategy": strategy,
+        "seed": seed,
+        "annotate": annotate_truncation,
+    })
+    .to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_row_limit_strategy() {
+        for strategy in [
+            RowLimitStrategy::Error,
+            RowLimitStrategy::Head,
+            RowLimitStrategy::Sample(42),
+        ] {
+            assert_eq!(
+                RowLimitStrategy::from_str(&strategy.to_string()).unwrap(),
+                strategy
+            );
+        }
+        assert_eq!(
+            RowLimitStrategy::from_str(" Sample ").unwrap(),
+            RowLimitStrategy::Sample(0)
+        );
+        assert!(RowLimitStrategy::from_str("tail").is_err());
+        assert!(RowLimitStrategy::from_str("sample:-1").is_err());
+        assert!(RowLimitStrategy::from_str("head:3").is_err());
+    }
+
+    #[test]
+    fn test_row_limit_arg() {
+        assert_eq!(
+            row_limit_arg(None, RowLimitStrategy::Head, true).unwrap(),
+            "null"
+        );
+        assert!(row_limit_arg(Some(0), RowLimitStrategy::Head, false).is_err());
+        let arg: Value = serde_json::from_str(
+            &row_limit_arg(Some(10), RowLimitStrategy::Sample(7), true).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            arg,
+            json!({"maxRows": 10, "strategy": "sample", "seed": 7, "annotate": true})
+        );
+    }
+}
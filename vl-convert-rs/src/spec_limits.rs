@@ -0,0 +1,431 @@
+use crate::anyhow::bail;
+use deno_core::error::AnyError;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::collections::HashSet;
+use std::io::Write;
+use std::sync::Mutex;
+
+/// Default maximum size, in bytes, of a serialized JSON spec
+pub const DEFAULT_MAX_SPEC_BYTES: usize = 256 * 1024 * 1024;
+
+/// Default maximum nesting depth of arrays and objects in a JSON spec
+pub const DEFAULT_MAX_JSON_DEPTH: usize = 1000;
+
+/// Default maximum number of views that a Vega-Lite spec may expand to
+pub const DEFAULT_MAX_VIEWS: usize = 500;
+
+lazy_static! {
+    static ref SPEC_LIMITS: Mutex<SpecLimits> = Mutex::new(SpecLimits::default());
+}
+
+/// Limits applied to input specs before they are parsed or passed to the JavaScript runtime
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpecLimits {
+    pub max_spec_bytes: usize,
+    pub max_json_depth: usize,
+    /// Maximum number of views that a Vega-Lite spec may expand to through repeat, facet,
+    /// concat, and layer, as estimated by [`estimate_view_count`]
+    pub max_views: usize,
+}
+
+impl Default for SpecLimits {
+    fn default() -> Self {
+        Self {
+            max_spec_bytes: DEFAULT_MAX_SPEC_BYTES,
+            max_json_depth: DEFAULT_MAX_JSON_DEPTH,
+            max_views: DEFAULT_MAX_VIEWS,
+        }
+    }
+}
+
+impl SpecLimits {
+    pub fn check_spec_bytes(&self, num_bytes: usize) -> Result<(), AnyError> {
+        if num_bytes > self.max_spec_bytes {
+            bail!(
+                "Spec size of {} bytes exceeds the max_spec_bytes limit of {} bytes. \
+                Raise max_spec_bytes to convert larger specs",
+                num_bytes,
+                self.max_spec_bytes
+            )
+        }
+        Ok(())
+    }
+
+    pub fn check_json_depth(&self, depth: usize) -> Result<(), AnyError> {
+        if depth > self.max_json_depth {
+            bail!(
+                "Spec nesting depth exceeds the max_json_depth limit of {}. \
+                Raise max_json_depth to convert more deeply nested specs",
+                self.max_json_depth
+            )
+        }
+        Ok(())
+    }
+
+    /// Check a JSON string against the size and depth limits without parsing it
+    pub fn check_json_str(&self, json_str: &str) -> Result<(), AnyError> {
+        self.check_spec_bytes(json_str.len())?;
+        self.check_json_depth(json_str_depth(json_str, self.max_json_depth))
+    }
+
+    /// Check a parsed JSON value against the depth and size limits.
+    ///
+    /// Returns the serialized size of the value in bytes
+    pub fn check_json_value(&self, value: &Value) -> Result<usize, AnyError> {
+        self.check_json_depth(json_value_depth(value, self.max_json_depth))?;
+
+        // Count serialized bytes without allocating the serialized string
+        let mut counter = ByteCounter(0);
+        serde_json::to_writer(&mut counter, value)?;
+        self.check_spec_bytes(counter.0)?;
+        Ok(counter.0)
+    }
+
+    /// Check the number of views that a Vega-Lite spec expands to, before it's compiled
+    pub fn check_view_count(&self, vl_spec: &Value) -> Result<(), AnyError> {
+        // Views are counted recursively, so the depth of the spec is checked first
+        self.check_json_depth(json_value_depth(vl_spec, self.max_json_depth))?;
+        let views = estimate_view_count(vl_spec);
+        if views > self.max_views {
+            bail!(
+                "Spec expands to an estimated {} views, which exceeds the max_views limit of {}. \
+                Raise max_views to convert specs with more views",
+                views,
+                self.max_views
+            )
+        }
+        Ok(())
+    }
+}
+
+/// Set the process-wide limits applied to input specs
+pub fn set_spec_limits(limits: SpecLimits) -> Result<(), AnyError> {
+    match SPEC_LIMITS.lock() {
+        Ok(mut guard) => {
+            *guard = limits;
+        }
+        Err(err) => {
+            bail!("Failed to acquire lock: {}", err)
+        }
+    }
+    Ok(())
+}
+
+/// Get the process-wide limits applied to input specs
+pub fn get_spec_limits() -> Result<SpecLimits, AnyError> {
+    match SPEC_LIMITS.lock() {
+        Ok(guard) => Ok(*guard),
+        Err(err) => {
+            bail!("Failed to acquire lock: {}", err)
+        }
+    }
+}
+
+/// Parse a JSON spec string, enforcing the current spec limits.
+///
+/// serde_json's built-in recursion limit is disabled in favor of max_json_depth, and
+/// parsing grows the stack on demand so that deep specs within the limit don't overflow it.
+pub fn parse_json_spec(json_str: &str) -> Result<Value, AnyError> {
+    get_spec_limits()?.check_json_str(json_str)?;
+
+    let mut deserializer = serde_json::Deserializer::from_str(json_str);
+    deserializer.disable_recursion_limit();
+    let value = Value::deserialize(serde_stacker::Deserializer::new(&mut deserializer))?;
+    deserializer.end()?;
+    Ok(value)
+}
+
+/// Compute the nesting depth of a JSON string, stopping early once max_depth is exceeded
+fn json_str_depth(json_str: &str, max_depth: usize) -> usize {
+    let mut depth: usize = 0;
+    let mut max_seen: usize = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+    for b in json_str.bytes() {
+        if in_string {
+            if escaped {
+                escaped = false;
+            } else if b == b'\\' {
+                escaped = true;
+            } else if b == b'"' {
+                in_string = false;
+            }
+            continue;
+        }
+        match b {
+            b'"' => in_string = true,
+            b'[' | b'{' => {
+                depth += 1;
+                if depth > max_seen {
+                    max_seen = depth;
+                    if max_seen > max_depth {
+                        break;
+                    }
+                }
+            }
+            b']' | b'}' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+    max_seen
+}
+
+/// Compute the nesting depth of a JSON value, stopping early once max_depth is exceeded.
+///
+/// Uses an explicit stack rather than recursion so that arbitrarily deep values are safe to check
+fn json_value_depth(value: &Value, max_depth: usize) -> usize {
+    let mut max_seen: usize = 0;
+    let mut stack: Vec<(&Value, usize)> = vec![(value, 0)];
+    while let Some((value, depth)) = stack.pop() {
+        if !(value.is_array() || value.is_object()) {
+            continue;
+        }
+        let depth = depth + 1;
+        if depth > max_seen {
+            max_seen = depth;
+            if max_seen > max_depth {
+                break;
+            }
+        }
+        match value {
+            Value::Array(arr) => stack.extend(arr.iter().map(|child| (child, depth))),
+            Value::Object(obj) => stack.extend(obj.values().map(|child| (child, depth))),
+            _ => {}
+        }
+    }
+    max_seen
+}
+
+/// Estimate the number of views that a Vega-Lite spec expands to, by multiplying the lengths
+/// of repeat lists and the number of facet values through nested views, and adding up the
+/// views of concatenated and layered specs.
+///
+/// Facets are counted by the distinct values of their fields in inline data. Facets of data
+/// that isn't inline count as one view, so the estimate is a lower bound for those specs
+pub fn estimate_view_count(vl_spec: &Value) -> usize {
+    let datasets = vl_spec.get("datasets").and_then(Value::as_object);
+    count_views(vl_spec, None, datasets)
+}
+
+fn count_views(
+    view: &Value,
+    parent_data: Option<&Value>,
+    datasets: Option<&Map<String, Value>>,
+) -> usize {
+    let data = view.get("data").or(parent_data);
+    let inner_views = || {
+        view.get("spec")
+            .map_or(1, |spec| count_views(spec, data, datasets))
+    };
+
+    if let Some(repeat) = view.get("repeat") {
+        let repeats = match repeat {
+            Value::Array(fields) => fields.len(),
+            Value::Object(channels) => channels
+                .values()
+                .map(|fields| fields.as_array().map_or(1, Vec::len))
+                .fold(1, usize::saturating_mul),
+            _ => 1,
+        };
+        return repeats.saturating_mul(inner_views());
+    }
+    if let Some(facet) = view.get("facet") {
+        return facet_count(facet, data, datasets).saturating_mul(inner_views());
+    }
+    for key in ["layer", "concat", "hconcat", "vconcat"] {
+        if let Some(Value::Array(views)) = view.get(key) {
+            return views
+                .iter()
+                .map(|child| count_views(child, data, datasets))
+                .fold(0, usize::saturating_add);
+        }
+    }
+
+    // Unit specs are faceted by their row, column, and facet encoding channels
+    view.get("encoding")
+        .map_or(1, |encoding| facet_count(encoding, data, datasets))
+}
+
+/// Number of facets of a facet operator or of the encoding of a unit spec
+fn facet_count(
+    facet: &Value,
+    data: Option<&Value>,
+    datasets: Option<&Map<String, Value>>,
+) -> usize {
+    let field_values = |field_def: &Value| {
+        field_def
+            .get("field")
+            .and_then(Value::as_str)
+            .map_or(1, |field| distinct_values(field, data, datasets))
+    };
+    if facet.get("field").is_some() {
+        return field_values(facet);
+    }
+    ["row", "column", "facet"]
+        .iter()
+        .filter_map(|channel| facet.get(channel))
+        .map(field_values)
+        .fold(1, usize::saturating_mul)
+}
+
+/// Number of distinct values of a field in inline data, or 1 when the data isn't inline
+fn distinct_values(
+    field: &str,
+    data: Option<&Value>,
+    datasets: Option<&Map<String, Value>>,
+) -> usize {
+    let rows = match data {
+        Some(data) => match (data.get("values"), data.get("name")) {
+            (Some(values), _) => values.as_array(),
+            (None, Some(Value::String(name))) => {
+                datasets.and_then(|datasets| datasets.get(name)?.as_array())
+            }
+            _ => None,
+        },
+        None => None,
+    };
+    let Some(rows) = rows else {
+        return 1;
+    };
+
+    let values: HashSet<String> = rows
+        .iter()
+        .map(|row| {
+            // Fields may be paths into nested objects, like "a.b"
+            let value = row
+                .get(field)
+                .or_else(|| field.split('.').try_fold(row, |value, key| value.get(key)));
+            value.map(Value::to_string).unwrap_or_default()
+        })
+        .collect();
+    values.len()
+}
+
+struct ByteCounter(usize);
+
+impl Write for ByteCounter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn deep_json(depth: usize) -> String {
+        format!("{}1{}", "[".repeat(depth), "]".repeat(depth))
+    }
+
+    #[test]
+    fn test_parse_within_limits() {
+        let value =
+            parse_json_spec(r#"{"mark": "point", "data": {"values": [[1], "[{"]}}"#).unwrap();
+        assert_eq!(value["mark"], "point");
+
+        let limits = SpecLimits::default();
+        assert_eq!(json_str_depth(r#"{"a": "[[[[", "b": [{}]}"#, 10), 3);
+        assert_eq!(json_value_depth(&value, limits.max_json_depth), 4);
+    }
+
+    #[test]
+    fn test_deep_spec() {
+        let limits = SpecLimits {
+            max_spec_bytes: DEFAULT_MAX_SPEC_BYTES,
+            max_json_depth: 200,
+            max_views: DEFAULT_MAX_VIEWS,
+        };
+
+        // Deeper than serde_json's built-in limit of 128, but within max_json_depth
+        let json_str = deep_json(150);
+        limits.check_json_str(&json_str).unwrap();
+        parse_json_spec(&json_str).unwrap();
+
+        // Deeper than max_json_depth
+        let json_str = deep_json(100_000);
+        let err = limits.check_json_str(&json_str).unwrap_err().to_string();
+        assert!(err.contains("max_json_depth limit of 200"), "{err}");
+        let err = parse_json_spec(&json_str).unwrap_err().to_string();
+        assert!(err.contains("max_json_depth"), "{err}");
+
+        let mut value = Value::from(1);
+        for _ in 0..300 {
+            value = Value::Array(vec![value]);
+        }
+        let err = limits.check_json_value(&value).unwrap_err().to_string();
+        assert!(err.contains("max_json_depth limit of 200"), "{err}");
+    }
+
+    #[test]
+    fn test_oversized_spec() {
+        let limits = SpecLimits {
+            max_spec_bytes: 1024,
+            max_json_depth: DEFAULT_MAX_JSON_DEPTH,
+            max_views: DEFAULT_MAX_VIEWS,
+        };
+        let value = serde_json::json!({"data": {"values": vec![0; 1000]}});
+        let json_str = serde_json::to_string(&value).unwrap();
+
+        let err = limits.check_json_str(&json_str).unwrap_err().to_string();
+        assert!(err.contains("max_spec_bytes limit of 1024 bytes"), "{err}");
+
+        let err = limits.check_json_value(&value).unwrap_err().to_string();
+        assert!(
+            err.contains(&format!("Spec size of {} bytes", json_str.len())),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn test_estimate_view_count() {
+        let rows: Vec<_> = (0..30)
+            .map(|i| serde_json::json!({"a": i % 5, "b": {"c": i % 3}}))
+            .collect();
+
+        // 4 repeated fields, each faceted into 5 rows and 3 columns
+        let spec = serde_json::json!({
+            "data": {"values": rows},
+            "repeat": ["w", "x", "y", "z"],
+            "spec": {
+                "facet": {"row": {"field": "a"}, "column": {"field": "b.c"}},
+                "spec": {"mark": "point"}
+            }
+        });
+        assert_eq!(estimate_view_count(&spec), 60);
+
+        // Layers and concatenated views are added up, and facet encoding channels multiply
+        let spec = serde_json::json!({
+            "datasets": {"source": rows},
+            "data": {"name": "source"},
+            "hconcat": [
+                {"layer": [{"mark": "bar"}, {"mark": "rule"}]},
+                {"mark": "point", "encoding": {"facet": {"field": "a"}}}
+            ]
+        });
+        assert_eq!(estimate_view_count(&spec), 7);
+
+        // Facets of data that isn't inline count as one view
+        let spec = serde_json::json!({
+            "data": {"url": "data/cars.json"},
+            "repeat": {"row": ["w", "x"], "column": ["y", "z"]},
+            "spec": {"mark": "point", "encoding": {"row": {"field": "Origin"}}}
+        });
+        assert_eq!(estimate_view_count(&spec), 4);
+
+        let limits = SpecLimits {
+            max_views: 3,
+            ..Default::default()
+        };
+        let err = limits.check_view_count(&spec).unwrap_err().to_string();
+        assert!(
+            err.contains("estimated 4 views, which exceeds the max_views limit of 3"),
+            "{err}"
+        );
+    }
+}
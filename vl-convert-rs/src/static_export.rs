@@ -0,0 +1,429 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// Keys of Vega-Lite specs that hold the views of a composition
+const VIEW_KEYS: [&str; 4] = ["layer", "concat", "hconcat", "vconcat"];
+
+/// Events that selections are triggered by when they highlight marks on hover
+const HOVER_EVENTS: [&str; 6] = [
+    "mouseover",
+    "pointerover",
+    "mousemove",
+    "pointermove",
+    "mouseenter",
+    "pointerenter",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StaticExportSeverity {
+    /// The feature is drawn in a fixed state, e.g. a selection with its initial value
+    Degraded,
+    /// The feature has no effect on the exported image
+    Unsupported,
+}
+
+/// Feature of a Vega-Lite spec that behaves differently in static images than in an
+/// interactive view, found by [`analyze_static_export`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StaticExportFinding {
+    /// One of selection, hover, bind, tooltip, or streaming-data
+    pub feature: String,
+    pub severity: StaticExportSeverity,
+    pub message: String,
+    /// JSON pointer to the part of the spec with the feature (e.g. /params/0)
+    pub spec_path: String,
+}
+
+/// Find the interactive features of a Vega-Lite spec, which silently disappear or are frozen
+/// in SVG, PNG, JPEG, and PDF output: selections, hover highlights, inputs and other
+/// bindings of params, tooltips, and named datasets that are meant to be streamed into the
+/// view once it's rendered.
+///
+/// Only the spec itself is inspected, so tooltips that are enabled by a theme or a config
+/// that isn't part of the spec aren't found. See
+/// [`VlConverter::analyze_static_export`](crate::VlConverter::analyze_static_export) for
+/// an analysis that includes them
+pub fn analyze_static_export(vl_spec: &Value) -> Vec<StaticExportFinding> {
+    let mut findings = Vec::new();
+    if let Value::Object(spec) = vl_spec {
+        let datasets = spec.get("datasets").and_then(Value::as_object);
+        analyze_view(spec, "", datasets, &mut findings);
+        analyze_config(spec, &mut findings);
+    }
+    findings
+}
+
+/// Whether any mark of a compiled Vega spec has a tooltip encoding
+pub(crate) fn vega_has_tooltips(vg_spec: &Value) -> bool {
+    let Some(marks) = vg_spec.get("marks").and_then(Value::as_array) else {
+        return false;
+    };
+    marks.iter().any(|mark| {
+        let encodes_tooltip = mark
+            .get("encode")
+            .and_then(Value::as_object)
+            .is_some_and(|encode| {
+                encode
+                    .values()
+                    .any(|set| set.get("tooltip").is_some_and(|tooltip| !tooltip.is_null()))
+            });
+        encodes_tooltip || vega_has_tooltips(mark)
+    })
+}
+
+fn analyze_view(
+    spec: &Map<String, Value>,
+    path: &str,
+    datasets: Option<&Map<String, Value>>,
+    findings: &mut Vec<StaticExportFinding>,
+) {
+    if let Some(Value::Array(params)) = spec.get("params") {
+        for (i, param) in params.iter().enumerate() {
+            if let Value::Object(param) = param {
+                analyze_param(param, &format!("{path}/params/{i}"), findings);
+            }
+        }
+    }
+
+    if let Some(name) = spec
+        .get("data")
+        .filter(|data| data.get("values").is_none() && data.get("url").is_none())
+        .and_then(|data| data.get("name"))
+        .and_then(Value::as_str)
+    {
+        if !datasets.is_some_and(|datasets| datasets.contains_key(name)) {
+            findings.push(finding(
+                "streaming-data",
+                StaticExportSeverity::Unsupported,
+                format!(
+                    "Dataset \"{name}\" is empty in static output, since rows can't be \
+                    inserted into it once the chart is rendered. Pass the rows in the \
+                    datasets of the spec instead"
+                ),
+                format!("{path}/data"),
+            ));
+        }
+    }
+
+    if let Some(Value::Object(mark)) = spec.get("mark") {
+        if mark.get("tooltip").is_some_and(is_enabled) {
+            findings.push(tooltip_finding(format!("{path}/mark/tooltip")));
+        }
+    }
+    if let Some(tooltip) = spec
+        .get("encoding")
+        .and_then(|encoding| encoding.get("tooltip"))
+    {
+        if is_enabled(tooltip) {
+            findings.push(tooltip_finding(format!("{path}/encoding/tooltip")));
+        }
+    }
+
+    for key in VIEW_KEYS {
+        if let Some(Value::Array(views)) = spec.get(key) {
+            for (i, view) in views.iter().enumerate() {
+                if let Value::Object(view) = view {
+                    analyze_view(view, &format!("{path}/{key}/{i}"), datasets, findings);
+                }
+            }
+        }
+    }
+    if let Some(Value::Object(view)) = spec.get("spec") {
+        analyze_view(view, &format!("{path}/spec"), datasets, findings);
+    }
+}
+
+fn analyze_param(param: &Map<String, Value>, path: &str, findings: &mut Vec<StaticExportFinding>) {
+    let name = param.get("name").and_then(Value::as_str).unwrap_or("");
+    let bind = param.get("bind").filter(|bind| !bind.is_null());
+    let Some(select) = param.get("select") else {
+        if bind.is_some() {
+            findings.push(input_finding("parameter", name, param, path));
+        }
+        return;
+    };
+
+    match bind {
+        Some(Value::String(bind)) if bind == "scales" => {
+            findings.push(finding(
+                "bind",
+                StaticExportSeverity::Unsupported,
+                format!(
+                    "Zooming and panning with selection \"{name}\" have no effect in static output"
+                ),
+                format!("{path}/bind"),
+            ));
+            // Scale bindings don't select anything to highlight
+            return;
+        }
+        Some(bind) if bind == "legend" || bind.get("legend").is_some() => {
+            findings.push(finding(
+                "bind",
+                StaticExportSeverity::Unsupported,
+                format!("Clicking the legend to change selection \"{name}\" has no effect in static output"),
+                format!("{path}/bind"),
+            ));
+        }
+        Some(_) => findings.push(input_finding("selection", name, param, path)),
+        None => {}
+    }
+
+    let on = select.get("on");
+    if on.is_some_and(is_hover_event) {
+        findings.push(finding(
+            "hover",
+            StaticExportSeverity::Unsupported,
+            format!(
+                "Hover highlight of selection \"{name}\" has no effect in static output, so \
+                marks are drawn as they are when nothing is hovered"
+            ),
+            format!("{path}/select/on"),
+        ));
+    } else if param.contains_key("value") {
+        findings.push(finding(
+            "selection",
+            StaticExportSeverity::Degraded,
+            format!(
+                "Selection \"{name}\" is drawn with its initial value, and can't be changed in \
+                static output"
+            ),
+            path.to_string(),
+        ));
+    } else {
+        findings.push(finding(
+            "selection",
+            StaticExportSeverity::Unsupported,
+            format!("Selection \"{name}\" has no effect in static output"),
+            path.to_string(),
+        ));
+    }
+}
+
+/// Tooltips enabled for every mark by the config of the spec
+fn analyze_config(spec: &Map<String, Value>, findings: &mut Vec<StaticExportFinding>) {
+    let Some(Value::Object(config)) = spec.get("config") else {
+        return;
+    };
+    for (key, mark_config) in config {
+        if mark_config.get("tooltip").is_some_and(is_enabled) {
+            findings.push(tooltip_finding(format!("/config/{key}/tooltip")));
+        }
+    }
+}
+
+fn input_finding(
+    kind: &str,
+    name: &str,
+    param: &Map<String, Value>,
+    path: &str,
+) -> StaticExportFinding {
+    let value = match param.get("value") {
+        Some(value) => format!("its initial value of {value}"),
+        None => "no value".to_string(),
+    };
+    finding(
+        "bind",
+        StaticExportSeverity::Degraded,
+        format!(
+            "Input bound to {kind} \"{name}\" isn't shown in static output, and the chart is \
+            drawn with {value}"
+        ),
+        format!("{path}/bind"),
+    )
+}
+
+fn tooltip_finding(spec_path: String) -> StaticExportFinding {
+    finding(
+        "tooltip",
+        StaticExportSeverity::Unsupported,
+        "Tooltips aren't shown in static output. SVG images can include them as native \
+        tooltips instead"
+            .to_string(),
+        spec_path,
+    )
+}
+
+fn finding(
+    feature: &str,
+    severity: StaticExportSeverity,
+    message: String,
+    spec_path: String,
+) -> StaticExportFinding {
+    StaticExportFinding {
+        feature: feature.to_string(),
+        severity,
+        message,
+        spec_path,
+    }
+}
+
+/// Whether a tooltip property turns tooltips on. `false` and `null` turn them off
+fn is_enabled(tooltip: &Value) -> bool {
+    !matches!(tooltip, Value::Null | Value::Bool(false))
+}
+
+/// Whether the `on` property of a selection is a hover event, like "pointerover", rather than
+/// a click or a drag between events (e.g. "[pointerdown, pointerup] > pointermove")
+fn is_hover_event(on: &Value) -> bool {
+    match on {
+        Value::String(on) => {
+            !on.contains('[') && HOVER_EVENTS.iter().any(|event| on.contains(event))
+        }
+        Value::Object(on) => on
+            .get("type")
+            .and_then(Value::as_str)
+            .is_some_and(|event_type| HOVER_EVENTS.contains(&event_type)),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn features(vl_spec: Value) -> Vec<(String, StaticExportSeverity, String)> {
+        analyze_static_export(&vl_spec)
+            .into_iter()
+            .map(|finding| (finding.feature, finding.severity, finding.spec_path))
+            .collect()
+    }
+
+    #[test]
+    fn test_plain_chart() {
+        let vl_spec = json!({
+            "data": {"values": [{"a": "A", "b": 28}]},
+            "mark": {"type": "bar", "tooltip": false},
+            "encoding": {
+                "x": {"field": "a", "type": "nominal"},
+                "y": {"field": "b", "type": "quantitative"},
+                "tooltip": null
+            }
+        });
+        assert_eq!(features(vl_spec), vec![]);
+    }
+
+    #[test]
+    fn test_params() {
+        use StaticExportSeverity::*;
+        let vl_spec = json!({
+            "params": [{"name": "size", "value": 40, "bind": {"input": "range", "min": 10, "max": 100}}],
+            "data": {"name": "table"},
+            "layer": [
+                {
+                    "params": [
+                        {"name": "brush", "select": "interval"},
+                        {"name": "grid", "select": "interval", "bind": "scales"},
+                        {"name": "hover", "select": {"type": "point", "on": "pointerover"}},
+                        {"name": "drag", "select": {"type": "interval", "on": "[pointerdown, pointerup] > pointermove"}},
+                        {"name": "picked", "select": {"type": "point", "fields": ["a"]}, "value": [{"a": "A"}]},
+                        {"name": "legend", "select": {"type": "point", "fields": ["c"]}, "bind": "legend"}
+                    ],
+                    "mark": {"type": "point", "tooltip": true}
+                },
+                {"mark": "rule", "encoding": {"tooltip": {"field": "b"}}}
+            ],
+            "config": {"mark": {"tooltip": {"content": "data"}}, "bar": {"tooltip": null}}
+        });
+        assert_eq!(
+            features(vl_spec),
+            vec![
+                ("bind".to_string(), Degraded, "/params/0/bind".to_string()),
+                (
+                    "streaming-data".to_string(),
+                    Unsupported,
+                    "/data".to_string()
+                ),
+                (
+                    "selection".to_string(),
+                    Unsupported,
+                    "/layer/0/params/0".to_string()
+                ),
+                (
+                    "bind".to_string(),
+                    Unsupported,
+                    "/layer/0/params/1/bind".to_string()
+                ),
+                (
+                    "hover".to_string(),
+                    Unsupported,
+                    "/layer/0/params/2/select/on".to_string()
+                ),
+                (
+                    "selection".to_string(),
+                    Unsupported,
+                    "/layer/0/params/3".to_string()
+                ),
+                (
+                    "selection".to_string(),
+                    Degraded,
+                    "/layer/0/params/4".to_string()
+                ),
+                (
+                    "bind".to_string(),
+                    Unsupported,
+                    "/layer/0/params/5/bind".to_string()
+                ),
+                (
+                    "selection".to_string(),
+                    Unsupported,
+                    "/layer/0/params/5".to_string()
+                ),
+                (
+                    "tooltip".to_string(),
+                    Unsupported,
+                    "/layer/0/mark/tooltip".to_string()
+                ),
+                (
+                    "tooltip".to_string(),
+                    Unsupported,
+                    "/layer/1/encoding/tooltip".to_string()
+                ),
+                (
+                    "tooltip".to_string(),
+                    Unsupported,
+                    "/config/mark/tooltip".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_messages() {
+        let vl_spec = json!({
+            "params": [
+                {"name": "brush", "select": "interval"},
+                {"name": "size", "value": 40, "bind": {"input": "range"}}
+            ],
+            "datasets": {"table": [{"a": 1}]},
+            "data": {"name": "table"},
+            "mark": "point"
+        });
+        let messages: Vec<_> = analyze_static_export(&vl_spec)
+            .into_iter()
+            .map(|finding| finding.message)
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                "Selection \"brush\" has no effect in static output",
+                "Input bound to parameter \"size\" isn't shown in static output, and the chart \
+                is drawn with its initial value of 40",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_vega_has_tooltips() {
+        let vg_spec = json!({
+            "marks": [{"type": "group", "marks": [
+                {"type": "rect", "encode": {"update": {"tooltip": {"signal": "datum"}}}}
+            ]}]
+        });
+        assert!(vega_has_tooltips(&vg_spec));
+        let vg_spec =
+            json!({"marks": [{"type": "rect", "encode": {"update": {"x": {"value": 0}}}}]});
+        assert!(!vega_has_tooltips(&vg_spec));
+    }
+}
@@ -0,0 +1,113 @@
+use regex::{Captures, Regex};
+use std::borrow::Cow;
+
+/// Shortest dash or gap, in pixels of the rasterized image, that rasterized SVG images
+/// draw. Shorter ones blend with their neighbors into what looks like a solid line
+pub const MIN_DASH_PIXELS: f32 = 1.0;
+
+lazy_static! {
+    /// stroke-dasharray attributes and style declarations, with their value
+    static ref STROKE_DASHARRAY_RE: Regex =
+        Regex::new(r#"(stroke-dasharray\s*(?:=\s*["']|:))([^"';]*)"#).unwrap();
+}
+
+/// Lengthen the dashes and gaps of the stroke-dasharray attributes and styles in an SVG
+/// image that are shorter than [`MIN_DASH_PIXELS`] once the image is rasterized at
+/// `pixel_scale`, so that dashed lines stay dashed at small scales like they do in
+/// browsers. Zero-length segments are kept, and dash arrays whose segments are all zero are
+/// left to be drawn solid. Dash arrays with units or other values that aren't plain numbers
+/// are left as they are
+pub(crate) fn clamp_stroke_dasharrays(svg: &str, pixel_scale: f32) -> Cow<'_, str> {
+    if !svg.contains("stroke-dasharray") || !pixel_scale.is_finite() || pixel_scale <= 0.0 {
+        return Cow::Borrowed(svg);
+    }
+    let min_length = MIN_DASH_PIXELS / pixel_scale;
+    STROKE_DASHARRAY_RE.replace_all(svg, |caps: &Captures| {
+        let value = &caps[2];
+        match clamp_dasharray(value, min_length) {
+            Some(clamped) => format!("{}{}", &caps[1], clamped),
+            None => format!("{}{}", &caps[1], value),
+        }
+    })
+}
+
+/// Clamp the positive lengths of a dash array value to `min_length`, returning None when
+/// the value doesn't need to change
+fn clamp_dasharray(value: &str, min_length: f32) -> Option<String> {
+    let lengths = value
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|length| !length.is_empty())
+        .map(|length| {
+            length
+                .parse::<f32>()
+                .ok()
+                .filter(|length| length.is_finite())
+        })
+        .collect::<Option<Vec<_>>>()?;
+    if lengths.iter().sum::<f32>() <= 0.0
+        || lengths.iter().any(|length| *length < 0.0)
+        || !lengths
+            .iter()
+            .any(|length| *length > 0.0 && *length < min_length)
+    {
+        return None;
+    }
+    let clamped: Vec<String> = lengths
+        .into_iter()
+        .map(|length| {
+            if length > 0.0 && length < min_length {
+                min_length.to_string()
+            } else {
+                length.to_string()
+            }
+        })
+        .collect();
+    Some(clamped.join(","))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clamp_stroke_dasharrays() {
+        assert_eq!(
+            clamp_stroke_dasharrays(r#"<path stroke-dasharray="1,4" d="M0,0L10,0"/>"#, 0.25),
+            r#"<path stroke-dasharray="4,4" d="M0,0L10,0"/>"#
+        );
+        assert_eq!(
+            clamp_stroke_dasharrays(
+                r#"<path style="stroke-dasharray: 0.5 2; stroke: red"/>"#,
+                1.0
+            ),
+            r#"<path style="stroke-dasharray:1,2; stroke: red"/>"#
+        );
+
+        // Zero-length dots are kept
+        assert_eq!(
+            clamp_stroke_dasharrays(r#"<path stroke-dasharray="0,1"/>"#, 0.5),
+            r#"<path stroke-dasharray="0,2"/>"#
+        );
+    }
+
+    #[test]
+    fn test_clamp_stroke_dasharrays_unchanged() {
+        for svg in [
+            // Long enough at the scale
+            r#"<path stroke-dasharray="4,4"/>"#,
+            // Drawn solid
+            r#"<path stroke-dasharray="0,0"/>"#,
+            r#"<path stroke-dasharray=""/>"#,
+            r#"<path stroke-dasharray="none"/>"#,
+            // Units aren't resolved
+            r#"<path stroke-dasharray="1px,4px"/>"#,
+        ] {
+            assert_eq!(clamp_stroke_dasharrays(svg, 0.5), svg);
+        }
+        let svg = r#"<path stroke="red"/>"#;
+        assert!(matches!(
+            clamp_stroke_dasharrays(svg, 0.25),
+            Cow::Borrowed(_)
+        ));
+    }
+}
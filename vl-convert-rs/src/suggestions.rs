@@ -0,0 +1,119 @@
+use crate::module_loader::import_map::VEGA_THEME_NAMES;
+use crate::module_loader::{FORMATE_LOCALE_MAP, TIME_FORMATE_LOCALE_MAP};
+
+/// Number of edits (insertions, deletions, substitutions, and transpositions of adjacent
+/// characters) that turn one string into the other
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    // distances[i][j] is the distance between the first i characters of a and the first j
+    // characters of b
+    let mut distances = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, distance) in distances[0].iter_mut().enumerate() {
+        *distance = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = (distances[i - 1][j] + 1)
+                .min(distances[i][j - 1] + 1)
+                .min(distances[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(distances[i - 2][j - 2] + 1);
+            }
+            distances[i][j] = distance;
+        }
+    }
+    distances[a.len()][b.len()]
+}
+
+/// The candidate closest to a misspelled name, ignoring case. Candidates more than a third
+/// of the length of the name (and at least one) edits away are not suggested
+pub fn closest_name<'a>(name: &str, candidates: &'a [String]) -> Option<&'a str> {
+    let name = name.to_lowercase();
+    let max_distance = (name.chars().count() / 3).max(1);
+    candidates
+        .iter()
+        .map(|candidate| (edit_distance(&name, &candidate.to_lowercase()), candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate.as_str())
+}
+
+/// Message for a name that is not one of the candidates, suggesting the closest candidate
+/// when there is one (e.g. "Unknown theme 'drk'. Did you mean 'dark'?")
+pub fn unknown_name_message(kind: &str, name: &str, candidates: &[String]) -> String {
+    match closest_name(name, candidates) {
+        Some(closest) => format!("Unknown {kind} '{name}'. Did you mean '{closest}'?"),
+        None => format!("Unknown {kind} '{name}'."),
+    }
+}
+
+/// Sorted names of the built-in d3-format locales
+pub fn format_locale_names() -> Vec<String> {
+    let mut names: Vec<String> = FORMATE_LOCALE_MAP.keys().cloned().collect();
+    names.sort();
+    names
+}
+
+/// Sorted names of the built-in d3-time-format locales
+pub fn time_format_locale_names() -> Vec<String> {
+    let mut names: Vec<String> = TIME_FORMATE_LOCALE_MAP.keys().cloned().collect();
+    names.sort();
+    names
+}
+
+/// Sorted names of the themes of the vega-themes package, which are the same names that
+/// VlConverter::get_theme_names returns, without starting a converter
+pub fn theme_names() -> Vec<String> {
+    VEGA_THEME_NAMES
+        .iter()
+        .map(|name| name.to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("dark", "dark"), 0);
+        assert_eq!(edit_distance("drk", "dark"), 1);
+        assert_eq!(edit_distance("drak", "dark"), 1);
+        assert_eq!(edit_distance("", "dark"), 4);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+    }
+
+    #[test]
+    fn test_unknown_name_message() {
+        let themes = names(&["dark", "excel", "fivethirtyeight", "ggplot2"]);
+        assert_eq!(
+            unknown_name_message("theme", "drk", &themes),
+            "Unknown theme 'drk'. Did you mean 'dark'?"
+        );
+        assert_eq!(
+            unknown_name_message("theme", "GGPLOT", &themes),
+            "Unknown theme 'GGPLOT'. Did you mean 'ggplot2'?"
+        );
+        assert_eq!(
+            unknown_name_message("theme", "unicorn", &themes),
+            "Unknown theme 'unicorn'."
+        );
+    }
+
+    #[test]
+    fn test_locale_names() {
+        let names = format_locale_names();
+        assert!(names.contains(&"it-IT".to_string()));
+        assert_eq!(closest_name("it_IT", &names), Some("it-IT"));
+        assert!(time_format_locale_names().contains(&"fr-FR".to_string()));
+    }
+}
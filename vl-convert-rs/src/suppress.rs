@@ -0,0 +1,316 @@
+use crate::anyhow::anyhow;
+use crate::text_style::merge_config;
+use deno_core::error::AnyError;
+use serde_json::{json, Map, Value};
+use std::borrow::Cow;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// Encoding channels of Vega-Lite that are shown with legends
+const LEGEND_CHANNELS: &[&str] = &[
+    "color",
+    "fill",
+    "stroke",
+    "opacity",
+    "fillOpacity",
+    "strokeOpacity",
+    "size",
+    "shape",
+    "strokeWidth",
+    "strokeDash",
+    "angle",
+];
+
+/// Keys of Vega-Lite views that hold the views that they're composed of
+const VIEW_ARRAY_KEYS: &[&str] = &["layer", "hconcat", "vconcat", "concat"];
+
+/// Component of a chart that can be left out of its image, for applications that draw it
+/// themselves next to the chart
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Component {
+    /// Chart titles, including the titles of composed views
+    Title,
+    /// Legends
+    Legend,
+    /// Axes along the x channel
+    AxisX,
+    /// Axes along the y channel
+    AxisY,
+    /// Grid lines of the axes
+    Grid,
+}
+
+impl Display for Component {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let component = match self {
+            Component::Title => "title",
+            Component::Legend => "legend",
+            Component::AxisX => "axis-x",
+            Component::AxisY => "axis-y",
+            Component::Grid => "grid",
+        };
+        std::fmt::Display::fmt(component, f)
+    }
+}
+
+impl FromStr for Component {
+    type Err = AnyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.trim().to_ascii_lowercase().as_str() {
+            "title" => Self::Title,
+            "legend" => Self::Legend,
+            "axis-x" => Self::AxisX,
+            "axis-y" => Self::AxisY,
+            "grid" => Self::Grid,
+            _ => return Err(anyhow!("Unsupported component: {}", s)),
+        })
+    }
+}
+
+/// Leave `components` out of a Vega-Lite spec before it's compiled, so that the chart is
+/// laid out without them. Titles are removed from the spec and its composed views, x and y
+/// encodings get a null axis or an axis without grid lines, and encodings shown with
+/// legends get a null legend. The spec's config disables legends and grid lines too, for
+/// ones that aren't set by an encoding. Returns the spec unchanged when `components` is None
+/// or empty
+pub(crate) fn suppress_vegalite_components<'a>(
+    spec: &'a Value,
+    components: Option<&[Component]>,
+) -> Cow<'a, Value> {
+    let Some(components) = components.filter(|components| !components.is_empty()) else {
+        return Cow::Borrowed(spec);
+    };
+    let Value::Object(obj) = spec else {
+        return Cow::Borrowed(spec);
+    };
+    let mut obj = obj.clone();
+    suppress_in_view(&mut obj, components);
+
+    let mut config = json!({});
+    if components.contains(&Component::Legend) {
+        merge_config(&mut config, &json!({"legend": {"disable": true}}));
+    }
+    if components.contains(&Component::Grid) {
+        merge_config(&mut config, &json!({"axis": {"grid": false}}));
+    }
+    if config != json!({}) {
+        let spec_config = obj.entry("config").or_insert_with(|| json!({}));
+        merge_config(spec_config, &config);
+    }
+    Cow::Owned(Value::Object(obj))
+}
+
+fn suppress_in_view(view: &mut Map<String, Value>, components: &[Component]) {
+    if components.contains(&Component::Title) {
+        view.remove("title");
+    }
+    if let Some(Value::Object(encoding)) = view.get_mut("encoding") {
+        suppress_in_encoding(encoding, components);
+    }
+    for key in VIEW_ARRAY_KEYS {
+        if let Some(Value::Array(views)) = view.get_mut(*key) {
+            for view in views.iter_mut().filter_map(Value::as_object_mut) {
+                suppress_in_view(view, components);
+            }
+        }
+    }
+    // The views that facets and repeats are made of
+    if let Some(Value::Object(spec)) = view.get_mut("spec") {
+        suppress_in_view(spec, components);
+    }
+}
+
+fn suppress_in_encoding(encoding: &mut Map<String, Value>, components: &[Component]) {
+    for (channel, component) in [("x", Component::AxisX), ("y", Component::AxisY)] {
+        let Some(Value::Object(def)) = encoding.get_mut(channel) else {
+            continue;
+        };
+        if !is_field_or_datum_def(def) {
+            continue;
+        }
+        if components.contains(&component) {
+            def.insert("axis".to_string(), Value::Null);
+        } else if components.contains(&Component::Grid) {
+            // A null axis has no grid lines to turn off
+            if let Value::Object(axis) = def.entry("axis").or_insert_with(|| json!({})) {
+                axis.insert("grid".to_string(), Value::Bool(false));
+            }
+        }
+    }
+    if components.contains(&Component::Legend) {
+        for channel in LEGEND_CHANNELS {
+            if let Some(Value::Object(def)) = encoding.get_mut(*channel) {
+                if is_field_or_datum_def(def) {
+                    def.insert("legend".to_string(), Value::Null);
+                }
+            }
+        }
+    }
+}
+
+/// Whether an encoding channel definition encodes a field or datum, rather than a value
+fn is_field_or_datum_def(def: &Map<String, Value>) -> bool {
+    def.contains_key("field") || def.contains_key("aggregate") || def.contains_key("datum")
+}
+
+/// Leave `components` out of a Vega spec, for hand-written Vega specs that can't be patched
+/// before they're compiled. Titles and legends are removed from the spec and its group
+/// marks, x axes (with a top or bottom orient) and y axes (with a left or right orient)
+/// are removed, and the grid lines of the axes that are kept are turned off. Vega lays out
+/// the chart without them when it's rendered. Returns the spec unchanged when `components`
+/// is None or empty
+pub(crate) fn suppress_vega_components<'a>(
+    spec: &'a Value,
+    components: Option<&[Component]>,
+) -> Cow<'a, Value> {
+    let Some(components) = components.filter(|components| !components.is_empty()) else {
+        return Cow::Borrowed(spec);
+    };
+    let Value::Object(obj) = spec else {
+        return Cow::Borrowed(spec);
+    };
+    let mut obj = obj.clone();
+    suppress_in_group(&mut obj, components);
+    Cow::Owned(Value::Object(obj))
+}
+
+fn suppress_in_group(group: &mut Map<String, Value>, components: &[Component]) {
+    if components.contains(&Component::Title) {
+        group.remove("title");
+    }
+    if components.contains(&Component::Legend) {
+        group.remove("legends");
+    }
+    if let Some(Value::Array(axes)) = group.get_mut("axes") {
+        axes.retain(|axis| {
+            let component = match axis.get("orient").and_then(Value::as_str) {
+                Some("top" | "bottom") => Component::AxisX,
+                Some("left" | "right") => Component::AxisY,
+                _ => return true,
+            };
+            !components.contains(&component)
+        });
+        if components.contains(&Component::Grid) {
+            for axis in axes.iter_mut().filter_map(Value::as_object_mut) {
+                axis.insert("grid".to_string(), Value::Bool(false));
+            }
+        }
+    }
+    if let Some(Value::Array(marks)) = group.get_mut("marks") {
+        for mark in marks.iter_mut().filter_map(Value::as_object_mut) {
+            if mark.get("type").and_then(Value::as_str) == Some("group") {
+                suppress_in_group(mark, components);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_suppress_vegalite_components() {
+        let spec = json!({
+            "title": "Sales",
+            "mark": "point",
+            "encoding": {
+                "x": {"field": "a", "type": "quantitative"},
+                "y": {"field": "b", "type": "quantitative", "axis": {"title": "B"}},
+                "color": {"field": "c", "type": "nominal"},
+                "size": {"value": 10}
+            }
+        });
+        let components = [
+            Component::Title,
+            Component::Legend,
+            Component::AxisX,
+            Component::Grid,
+        ];
+        let suppressed = suppress_vegalite_components(&spec, Some(&components));
+        assert_eq!(
+            suppressed.as_ref(),
+            &json!({
+                "mark": "point",
+                "encoding": {
+                    "x": {"field": "a", "type": "quantitative", "axis": null},
+                    "y": {
+                        "field": "b",
+                        "type": "quantitative",
+                        "axis": {"title": "B", "grid": false}
+                    },
+                    "color": {"field": "c", "type": "nominal", "legend": null},
+                    "size": {"value": 10}
+                },
+                "config": {"legend": {"disable": true}, "axis": {"grid": false}}
+            })
+        );
+        assert!(matches!(
+            suppress_vegalite_components(&spec, None),
+            Cow::Borrowed(_)
+        ));
+    }
+
+    #[test]
+    fn test_suppress_composed_views() {
+        let spec = json!({
+            "vconcat": [
+                {"title": "Top", "mark": "bar", "encoding": {"y": {"field": "b"}}},
+                {"facet": {"field": "f"}, "spec": {"title": "Cell", "mark": "bar"}}
+            ]
+        });
+        let suppressed =
+            suppress_vegalite_components(&spec, Some(&[Component::Title, Component::AxisY]));
+        assert_eq!(
+            suppressed.as_ref(),
+            &json!({
+                "vconcat": [
+                    {"mark": "bar", "encoding": {"y": {"field": "b", "axis": null}}},
+                    {"facet": {"field": "f"}, "spec": {"mark": "bar"}}
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn test_suppress_vega_components() {
+        let spec = json!({
+            "title": {"text": "Sales"},
+            "axes": [
+                {"scale": "x", "orient": "bottom"},
+                {"scale": "y", "orient": "left", "grid": true}
+            ],
+            "legends": [{"fill": "color"}],
+            "marks": [{
+                "type": "group",
+                "title": "Cell",
+                "axes": [{"scale": "y", "orient": "right"}]
+            }]
+        });
+        let components = [
+            Component::Title,
+            Component::Legend,
+            Component::AxisX,
+            Component::Grid,
+        ];
+        let suppressed = suppress_vega_components(&spec, Some(&components));
+        assert_eq!(
+            suppressed.as_ref(),
+            &json!({
+                "axes": [{"scale": "y", "orient": "left", "grid": false}],
+                "marks": [{
+                    "type": "group",
+                    "axes": [{"scale": "y", "orient": "right", "grid": false}]
+                }]
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_component() {
+        assert_eq!(Component::from_str("Axis-X").unwrap(), Component::AxisX);
+        assert_eq!(Component::Legend.to_string(), "legend");
+        assert!(Component::from_str("axes").is_err());
+    }
+}
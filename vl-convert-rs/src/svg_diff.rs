@@ -0,0 +1,399 @@
+use deno_core::error::AnyError;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use usvg::roxmltree;
+
+lazy_static! {
+    static ref NUMBER_RE: Regex = Regex::new(r"[-+]?(?:\d+\.?\d*|\.\d+)(?:[eE][-+]?\d+)?").unwrap();
+    static ref ID_REF_RE: Regex = Regex::new(r"#([^\s)'\x22]+)").unwrap();
+}
+
+/// Attributes whose values are numbers or lists of numbers, like coordinates, sizes, path
+/// data, and transforms, which are compared within the tolerance. Other attributes, like
+/// colors, ids, and font names, are compared exactly, even when they contain digits
+const NUMERIC_ATTRIBUTES: &[&str] = &[
+    "cx",
+    "cy",
+    "d",
+    "dx",
+    "dy",
+    "fill-opacity",
+    "font-size",
+    "height",
+    "offset",
+    "opacity",
+    "points",
+    "r",
+    "rx",
+    "ry",
+    "stroke-dasharray",
+    "stroke-dashoffset",
+    "stroke-miterlimit",
+    "stroke-opacity",
+    "stroke-width",
+    "textLength",
+    "transform",
+    "viewBox",
+    "width",
+    "x",
+    "x1",
+    "x2",
+    "y",
+    "y1",
+    "y2",
+];
+
+/// Options for [`compare_svgs`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct SvgCompareOptions {
+    /// Largest difference between two numbers in a numeric attribute value (e.g. a coordinate,
+    /// or a number in path data) for them to count as equal. Attributes that aren't numeric,
+    /// like colors and ids, are compared exactly
+    pub tolerance: f64,
+    /// Ignore the values of id attributes, such as the clip path and gradient ids that Vega
+    /// generates. Ids are matched by the order they appear in, so references to them
+    /// (e.g. `url(#clip1)`) are still compared
+    pub ignore_ids: bool,
+}
+
+impl Default for SvgCompareOptions {
+    fn default() -> Self {
+        Self {
+            tolerance: 0.0,
+            ignore_ids: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SvgDifferenceKind {
+    /// Elements at the same position have different tag names
+    Tag,
+    /// An attribute has a different value, or is only set on one side
+    Attribute,
+    /// An element has different text content
+    Text,
+    /// An element is only in the second SVG
+    Added,
+    /// An element is only in the first SVG
+    Removed,
+}
+
+/// Difference between two SVG documents found by [`compare_svgs`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SvgDifference {
+    /// Path of the element that differs (e.g. /svg[0]/g[0]/g[1]/rect[0]), where the index counts
+    /// the preceding siblings with the same tag name. Attribute differences end with /@name
+    pub path: String,
+    pub kind: SvgDifferenceKind,
+    /// Value in the first SVG: the tag name, attribute value, or text content. None when the
+    /// element or attribute is missing from it
+    pub a: Option<String>,
+    /// Value in the second SVG
+    pub b: Option<String>,
+}
+
+/// Result of [`compare_svgs`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SvgDiff {
+    pub equal: bool,
+    pub differences: Vec<SvgDifference>,
+}
+
+/// Compare the structure of two SVG documents, for visual regression tests that need to be
+/// stable across platforms where pixel comparisons aren't.
+///
+/// Elements are matched by their position among their parent's child elements, and compared
+/// by tag name, attributes, and text content. Numbers in the values of numeric attributes,
+/// like coordinates, sizes, path data, and transforms, are equal when they're within
+/// `opts.tolerance` of each other. Comments and whitespace between elements are ignored
+pub fn compare_svgs(a: &str, b: &str, opts: &SvgCompareOptions) -> Result<SvgDiff, AnyError> {
+    let xml_opt = roxmltree::ParsingOptions {
+        allow_dtd: true,
+        ..Default::default()
+    };
+    let doc_a = roxmltree::Document::parse_with_options(a, xml_opt)?;
+    let doc_b = roxmltree::Document::parse_with_options(b, xml_opt)?;
+
+    let comparer = Comparer {
+        opts,
+        ids_a: id_aliases(&doc_a),
+        ids_b: id_aliases(&doc_b),
+    };
+    let mut differences = Vec::new();
+    let root_a = doc_a.root_element();
+    let root_b = doc_b.root_element();
+    comparer.compare_elements(
+        &root_a,
+        &root_b,
+        &format!("/{}[0]", root_a.tag_name().name()),
+        &mut differences,
+    );
+
+    Ok(SvgDiff {
+        equal: differences.is_empty(),
+        differences,
+    })
+}
+
+/// Map each id in the document to a name based on the order it's defined in
+fn id_aliases(doc: &roxmltree::Document) -> HashMap<String, String> {
+    doc.descendants()
+        .filter_map(|node| node.attribute("id"))
+        .enumerate()
+        .map(|(i, id)| (id.to_string(), format!("id{i}")))
+        .collect()
+}
+
+struct Comparer<'a> {
+    opts: &'a SvgCompareOptions,
+    ids_a: HashMap<String, String>,
+    ids_b: HashMap<String, String>,
+}
+
+impl Comparer<'_> {
+    fn compare_elements(
+        &self,
+        a: &roxmltree::Node,
+        b: &roxmltree::Node,
+        path: &str,
+        differences: &mut Vec<SvgDifference>,
+    ) {
+        if a.tag_name().name() != b.tag_name().name() {
+            differences.push(SvgDifference {
+                path: path.to_string(),
+                kind: SvgDifferenceKind::Tag,
+                a: Some(a.tag_name().name().to_string()),
+                b: Some(b.tag_name().name().to_string()),
+            });
+            return;
+        }
+
+        // Attributes, in name order so that the report doesn't depend on attribute order
+        let attrs_a = attribute_map(a);
+        let attrs_b = attribute_map(b);
+        let names: BTreeSet<&str> = attrs_a.keys().chain(attrs_b.keys()).copied().collect();
+        for name in names {
+            let value_a = attrs_a.get(name).copied();
+            let value_b = attrs_b.get(name).copied();
+            let equal = match (value_a, value_b) {
+                (Some(value_a), Some(value_b)) => self.values_equal(name, value_a, value_b),
+                (None, None) => true,
+                _ => false,
+            };
+            if !equal {
+                differences.push(SvgDifference {
+                    path: format!("{path}/@{name}"),
+                    kind: SvgDifferenceKind::Attribute,
+                    a: value_a.map(String::from),
+                    b: value_b.map(String::from),
+                });
+            }
+        }
+
+        let text_a = element_text(a);
+        let text_b = element_text(b);
+        if text_a != text_b {
+            differences.push(SvgDifference {
+                path: path.to_string(),
+                kind: SvgDifferenceKind::Text,
+                a: Some(text_a),
+                b: Some(text_b),
+            });
+        }
+
+        let children_a: Vec<_> = a.children().filter(|n| n.is_element()).collect();
+        let children_b: Vec<_> = b.children().filter(|n| n.is_element()).collect();
+        let mut tag_counts: HashMap<&str, usize> = HashMap::new();
+        for i in 0..children_a.len().max(children_b.len()) {
+            let (child_a, child_b) = (children_a.get(i), children_b.get(i));
+            let Some(tag) = child_a.or(child_b).map(|n| n.tag_name().name()) else {
+                continue;
+            };
+            let index = tag_counts.entry(tag).or_default();
+            let child_path = format!("{path}/{tag}[{index}]");
+            *index += 1;
+            match (child_a, child_b) {
+                (Some(child_a), Some(child_b)) => {
+                    self.compare_elements(child_a, child_b, &child_path, differences)
+                }
+                (Some(_), None) => differences.push(SvgDifference {
+                    path: child_path,
+                    kind: SvgDifferenceKind::Removed,
+                    a: Some(tag.to_string()),
+                    b: None,
+                }),
+                (None, Some(child_b)) => differences.push(SvgDifference {
+                    path: child_path,
+                    kind: SvgDifferenceKind::Added,
+                    a: None,
+                    b: Some(child_b.tag_name().name().to_string()),
+                }),
+                (None, None) => {}
+            }
+        }
+    }
+
+    fn values_equal(&self, name: &str, a: &str, b: &str) -> bool {
+        if NUMERIC_ATTRIBUTES.contains(&name) {
+            return numbers_equal(a, b, self.opts.tolerance);
+        }
+        if self.opts.ignore_ids {
+            if name == "id" {
+                return self.ids_a.get(a) == self.ids_b.get(b);
+            }
+            replace_id_refs(a, &self.ids_a) == replace_id_refs(b, &self.ids_b)
+        } else {
+            a == b
+        }
+    }
+}
+
+/// Replace references to ids (e.g. `url(#clip1)` or `#gradient_0`) with their aliases
+fn replace_id_refs(value: &str, ids: &HashMap<String, String>) -> String {
+    ID_REF_RE
+        .replace_all(value, |caps: &regex::Captures| match ids.get(&caps[1]) {
+            Some(alias) => format!("#{alias}"),
+            None => caps[0].to_string(),
+        })
+        .into_owned()
+}
+
+/// Whether two attribute values are the same, apart from numbers that differ by no more than
+/// `tolerance`
+fn numbers_equal(a: &str, b: &str, tolerance: f64) -> bool {
+    if a == b {
+        return true;
+    }
+    let numbers_a: Vec<_> = NUMBER_RE.find_iter(a).collect();
+    let numbers_b: Vec<_> = NUMBER_RE.find_iter(b).collect();
+    if numbers_a.len() != numbers_b.len() || NUMBER_RE.split(a).ne(NUMBER_RE.split(b)) {
+        return false;
+    }
+    numbers_a.iter().zip(&numbers_b).all(|(num_a, num_b)| {
+        match (num_a.as_str().parse::<f64>(), num_b.as_str().parse::<f64>()) {
+            (Ok(num_a), Ok(num_b)) => (num_a - num_b).abs() <= tolerance,
+            _ => num_a.as_str() == num_b.as_str(),
+        }
+    })
+}
+
+/// Attribute values of an element by local name, so that e.g. `xlink:href` and `href` match
+fn attribute_map<'a>(node: &roxmltree::Node<'a, '_>) -> HashMap<&'a str, &'a str> {
+    node.attributes()
+        .map(|attr| (attr.name(), attr.value()))
+        .collect()
+}
+
+/// Text directly inside an element, with leading and trailing whitespace removed
+fn element_text(node: &roxmltree::Node) -> String {
+    node.children()
+        .filter(|n| n.is_text())
+        .filter_map(|n| n.text())
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_numbers_equal() {
+        assert!(numbers_equal("translate(10,5)", "translate(10.4,5)", 0.5));
+        assert!(!numbers_equal("translate(10,5)", "translate(11,5)", 0.5));
+        assert!(!numbers_equal("M0,0L1,1", "M0,0L1,1Z", 0.5));
+        assert!(!numbers_equal("#4c78a8", "#f58518", 0.5));
+    }
+
+    #[test]
+    fn test_tolerance_only_applies_to_numeric_attributes() {
+        let a = r##"<svg><rect x="1" fill="#4c78a8"/><g clip-path="url(#c1)" id="g1"/></svg>"##;
+        let b = r##"<svg><rect x="1.5" fill="#4c78a9"/><g clip-path="url(#c2)" id="g2"/></svg>"##;
+        let opts = SvgCompareOptions {
+            tolerance: 1.0,
+            ignore_ids: false,
+        };
+        let diff = compare_svgs(a, b, &opts).unwrap();
+        assert_eq!(
+            diff.differences
+                .iter()
+                .map(|d| d.path.as_str())
+                .collect::<Vec<_>>(),
+            vec![
+                "/svg[0]/rect[0]/@fill",
+                "/svg[0]/g[0]/@clip-path",
+                "/svg[0]/g[0]/@id"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_equal_modulo_ids() {
+        let a = r##"<svg xmlns="http://www.w3.org/2000/svg"><defs><clipPath id="clip1"><rect width="10" height="10"/></clipPath></defs><g clip-path="url(#clip1)"><rect x="1"/></g></svg>"##;
+        let b = r##"<svg xmlns="http://www.w3.org/2000/svg"><defs><clipPath id="clip7"><rect width="10" height="10"/></clipPath></defs><g clip-path="url(#clip7)"><rect x="1"/></g></svg>"##;
+        let diff = compare_svgs(a, b, &SvgCompareOptions::default()).unwrap();
+        assert!(diff.equal);
+
+        let opts = SvgCompareOptions {
+            ignore_ids: false,
+            ..Default::default()
+        };
+        let diff = compare_svgs(a, b, &opts).unwrap();
+        assert_eq!(
+            diff.differences
+                .iter()
+                .map(|d| d.path.as_str())
+                .collect::<Vec<_>>(),
+            vec!["/svg[0]/defs[0]/clipPath[0]/@id", "/svg[0]/g[0]/@clip-path"]
+        );
+    }
+
+    #[test]
+    fn test_moved_rect() {
+        let a = r#"<svg><g><rect x="1" y="2"/><rect x="5" y="2"/></g></svg>"#;
+        let b = r#"<svg><g><rect x="1.2" y="2"/><rect x="8" y="2"/></g></svg>"#;
+        let opts = SvgCompareOptions {
+            tolerance: 0.5,
+            ..Default::default()
+        };
+        let diff = compare_svgs(a, b, &opts).unwrap();
+        assert_eq!(
+            diff.differences,
+            vec![SvgDifference {
+                path: "/svg[0]/g[0]/rect[1]/@x".to_string(),
+                kind: SvgDifferenceKind::Attribute,
+                a: Some("5".to_string()),
+                b: Some("8".to_string()),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_changed_label_and_added_element() {
+        let a = r#"<svg><text x="1"> Sales </text></svg>"#;
+        let b = r#"<svg><text x="1">Revenue</text><path d="M0,0"/></svg>"#;
+        let diff = compare_svgs(a, b, &SvgCompareOptions::default()).unwrap();
+        assert!(!diff.equal);
+        assert_eq!(
+            diff.differences,
+            vec![
+                SvgDifference {
+                    path: "/svg[0]/text[0]".to_string(),
+                    kind: SvgDifferenceKind::Text,
+                    a: Some("Sales".to_string()),
+                    b: Some("Revenue".to_string()),
+                },
+                SvgDifference {
+                    path: "/svg[0]/path[0]".to_string(),
+                    kind: SvgDifferenceKind::Added,
+                    a: None,
+                    b: Some("path".to_string()),
+                },
+            ]
+        );
+    }
+}
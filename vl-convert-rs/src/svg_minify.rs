@@ -0,0 +1,397 @@
+use deno_core::error::AnyError;
+use std::collections::HashMap;
+use usvg::roxmltree;
+
+/// Number of decimals that numbers are rounded to when minifying SVG, unless configured
+pub const DEFAULT_SVG_PRECISION: u8 = 6;
+
+/// Attributes that only matter to assistive technologies and interactive use, which don't
+/// affect how the SVG is drawn
+const METADATA_ATTRIBUTES: &[&str] = &["role", "pointer-events"];
+
+/// Inherited presentation attributes paired with their initial values. An attribute set to
+/// its initial value is dropped when no ancestor sets the property to something else
+const INHERITED_DEFAULTS: &[(&str, &str)] = &[
+    ("fill-opacity", "1"),
+    ("fill-rule", "nonzero"),
+    ("stroke", "none"),
+    ("stroke-dashoffset", "0"),
+    ("stroke-linecap", "butt"),
+    ("stroke-linejoin", "miter"),
+    ("stroke-miterlimit", "4"),
+    ("stroke-opacity", "1"),
+    ("stroke-width", "1"),
+    ("font-style", "normal"),
+    ("font-variant", "normal"),
+    ("font-weight", "normal"),
+    ("text-anchor", "start"),
+    ("visibility", "visible"),
+];
+
+/// Presentation attributes that aren't inherited, paired with their initial values
+const NON_INHERITED_DEFAULTS: &[(&str, &str)] =
+    &[("opacity", "1"), ("transform", "translate(0,0)")];
+
+/// Attributes whose numbers are rounded to the configured precision
+const NUMERIC_ATTRIBUTES: &[&str] = &[
+    "d",
+    "points",
+    "transform",
+    "viewBox",
+    "x",
+    "y",
+    "x1",
+    "y1",
+    "x2",
+    "y2",
+    "cx",
+    "cy",
+    "r",
+    "rx",
+    "ry",
+    "dx",
+    "dy",
+    "width",
+    "height",
+    "font-size",
+    "stroke-width",
+    "textLength",
+];
+
+/// Elements whose whitespace-only text is kept, because it's rendered or displayed
+const TEXT_ELEMENTS: &[&str] = &["text", "tspan", "textPath", "title", "desc", "style"];
+
+/// Rewrite an SVG document with the smallest markup that draws the same image.
+///
+/// Comments, whitespace between elements, accessibility metadata (role and aria-*
+/// attributes), pointer-events attributes, class names (unless the document has a <style>
+/// element), and presentation attributes set to their initial values are removed. Numbers
+/// in geometry attributes are rounded to `precision` decimals.
+pub fn minify_svg(svg: &str, precision: u8) -> Result<String, AnyError> {
+    let xml_opt = roxmltree::ParsingOptions {
+        allow_dtd: true,
+        ..Default::default()
+    };
+    let doc = roxmltree::Document::parse_with_options(svg, xml_opt)?;
+    let minifier = Minifier {
+        precision,
+        keep_class: doc.descendants().any(|n| n.has_tag_name("style")),
+    };
+
+    let mut result = String::with_capacity(svg.len());
+    minifier.write_element(&doc.root_element(), &mut HashMap::new(), false, &mut result);
+    Ok(result)
+}
+
+struct Minifier {
+    precision: u8,
+    keep_class: bool,
+}
+
+impl Minifier {
+    /// Write an element and its descendants. `inherited` holds the inherited presentation
+    /// attributes set by the element's ancestors, and `styled` is true when an ancestor has
+    /// a style attribute, which may set them as well
+    fn write_element<'a>(
+        &self,
+        node: &roxmltree::Node<'a, '_>,
+        inherited: &mut HashMap<&'a str, &'a str>,
+        styled: bool,
+        out: &mut String,
+    ) {
+        let styled = styled || node.has_attribute("style");
+        out.push('<');
+        write_qualified_name(
+            node,
+            node.tag_name().namespace(),
+            node.tag_name().name(),
+            out,
+        );
+
+        // Namespaces declared on this element, rather than inherited from its parent
+        let parent_namespaces: Vec<_> = node
+            .parent_element()
+            .map(|p| p.namespaces().collect())
+            .unwrap_or_default();
+        for ns in node.namespaces() {
+            let declared_by_parent = parent_namespaces
+                .iter()
+                .any(|p| p.name() == ns.name() && p.uri() == ns.uri());
+            if declared_by_parent {
+                continue;
+            }
+            match ns.name() {
+                Some(prefix) => out.push_str(&format!(" xmlns:{prefix}=\"")),
+                None => out.push_str(" xmlns=\""),
+            }
+            escape_attribute(ns.uri(), out);
+            out.push('"');
+        }
+
+        let mut overridden: Vec<(&'a str, Option<&'a str>)> = Vec::new();
+        for attr in node.attributes() {
+            let name = attr.name();
+            let value = attr.value();
+            if attr.namespace().is_none() && self.is_dropped(name, value, inherited, styled) {
+                continue;
+            }
+            if attr.namespace().is_none() && is_inherited(name) {
+                overridden.push((name, inherited.insert(name, value)));
+            }
+
+            out.push(' ');
+            write_qualified_name(node, attr.namespace(), name, out);
+            out.push_str("=\"");
+            if attr.namespace().is_none() && NUMERIC_ATTRIBUTES.contains(&name) {
+                escape_attribute(&round_numbers(value, self.precision), out);
+            } else {
+                escape_attribute(value, out);
+            }
+            out.push('"');
+        }
+
+        let keep_whitespace = node
+            .ancestors()
+            .any(|n| TEXT_ELEMENTS.contains(&n.tag_name().name()) || n.has_attribute(XML_SPACE));
+        let children: Vec<_> = node
+            .children()
+            .filter(|child| {
+                child.is_element()
+                    || (child.is_text()
+                        && (keep_whitespace || !child.text().unwrap_or_default().trim().is_empty()))
+            })
+            .collect();
+
+        if children.is_empty() {
+            out.push_str("/>");
+        } else {
+            out.push('>');
+            for child in &children {
+                if child.is_element() {
+                    self.write_element(child, inherited, styled, out);
+                } else {
+                    escape_text(child.text().unwrap_or_default(), out);
+                }
+            }
+            out.push_str("</");
+            write_qualified_name(
+                node,
+                node.tag_name().namespace(),
+                node.tag_name().name(),
+                out,
+            );
+            out.push('>');
+        }
+
+        // Restore the inherited attributes of the parent
+        for (name, previous) in overridden.into_iter().rev() {
+            match previous {
+                Some(previous) => inherited.insert(name, previous),
+                None => inherited.remove(name),
+            };
+        }
+    }
+
+    fn is_dropped(
+        &self,
+        name: &str,
+        value: &str,
+        inherited: &HashMap<&str, &str>,
+        styled: bool,
+    ) -> bool {
+        if name.starts_with("aria-") || METADATA_ATTRIBUTES.contains(&name) {
+            return true;
+        }
+        if name == "class" {
+            return !self.keep_class;
+        }
+        let value = value.trim();
+        if NON_INHERITED_DEFAULTS.contains(&(name, value)) {
+            return true;
+        }
+        if let Some((_, initial)) = INHERITED_DEFAULTS.iter().find(|(n, _)| *n == name) {
+            // The initial value only takes effect when no ancestor sets the property to
+            // something else
+            return !styled
+                && value == *initial
+                && inherited.get(name).is_none_or(|v| v.trim() == *initial);
+        }
+        false
+    }
+}
+
+const XML_SPACE: (&str, &str) = ("http://www.w3.org/XML/1998/namespace", "space");
+
+fn is_inherited(name: &str) -> bool {
+    INHERITED_DEFAULTS.iter().any(|(n, _)| *n == name)
+}
+
+/// Write an element or attribute name, with the prefix that its namespace is bound to
+fn write_qualified_name(
+    node: &roxmltree::Node,
+    namespace: Option<&str>,
+    name: &str,
+    out: &mut String,
+) {
+    if let Some(prefix) = namespace.and_then(|uri| node.lookup_prefix(uri)) {
+        out.push_str(prefix);
+        out.push(':');
+    }
+    out.push_str(name);
+}
+
+/// Round the numbers in an attribute value to `precision` decimals, keeping the original
+/// text of numbers that rounding wouldn't shorten
+fn round_numbers(value: &str, precision: u8) -> String {
+    let bytes = value.as_bytes();
+    let mut result = String::with_capacity(value.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let end = number_end(bytes, i);
+        if end == i {
+            let c = value[i..].chars().next().unwrap_or_default();
+            result.push(c);
+            i += c.len_utf8();
+            continue;
+        }
+
+        let token = &value[i..end];
+        let rounded = token
+            .parse::<f64>()
+            .ok()
+            .map(|n| format_number(n, precision))
+            .filter(|rounded| rounded.len() < token.len())
+            .unwrap_or_else(|| token.to_string());
+
+        // Numbers can be written without a separator when the second one starts with a
+        // sign or a decimal point (e.g. "1.5.5" or "1-2"). Rounding may remove that
+        // boundary, so add a space when the rounded number starts with a digit
+        let needs_separator = result.ends_with(|c: char| c.is_ascii_digit() || c == '.')
+            && rounded.starts_with(|c: char| c.is_ascii_digit() || c == '.');
+        if needs_separator {
+            result.push(' ');
+        }
+        result.push_str(&rounded);
+        i = end;
+    }
+    result
+}
+
+/// Byte offset just past the number that starts at `start`, or `start` when there isn't one
+fn number_end(bytes: &[u8], start: usize) -> usize {
+    let digits_end = |mut i: usize| {
+        while i < bytes.len() && bytes[i].is_ascii_digit() {
+            i += 1;
+        }
+        i
+    };
+
+    let mut i = start;
+    if i < bytes.len() && (bytes[i] == b'-' || bytes[i] == b'+') {
+        i += 1;
+    }
+    let int_end = digits_end(i);
+    let mut end = int_end;
+    if end < bytes.len() && bytes[end] == b'.' {
+        let frac_end = digits_end(end + 1);
+        if frac_end > end + 1 || int_end > i {
+            end = frac_end;
+        }
+    }
+    if end == i {
+        // No digits
+        return start;
+    }
+
+    // Exponent, only when followed by digits so that units like "em" aren't consumed
+    if end < bytes.len() && (bytes[end] == b'e' || bytes[end] == b'E') {
+        let mut j = end + 1;
+        if j < bytes.len() && (bytes[j] == b'-' || bytes[j] == b'+') {
+            j += 1;
+        }
+        let exp_end = digits_end(j);
+        if exp_end > j {
+            end = exp_end;
+        }
+    }
+    end
+}
+
+/// Format a number with at most `precision` decimals, without trailing zeros
+fn format_number(n: f64, precision: u8) -> String {
+    let mut s = format!("{:.*}", precision as usize, n);
+    if s.contains('.') {
+        s.truncate(s.trim_end_matches('0').trim_end_matches('.').len());
+    }
+    if s == "-0" {
+        s = "0".to_string();
+    }
+    s
+}
+
+fn escape_text(text: &str, out: &mut String) {
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            _ => out.push(c),
+        }
+    }
+}
+
+fn escape_attribute(value: &str, out: &mut String) {
+    for c in value.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '"' => out.push_str("&quot;"),
+            '\n' => out.push_str("&#10;"),
+            '\r' => out.push_str("&#13;"),
+            '\t' => out.push_str("&#9;"),
+            _ => out.push(c),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_numbers() {
+        assert_eq!(
+            round_numbers("M0.123456789,10L5,-0.0000001Z", 2),
+            "M0.12,10L5,0Z"
+        );
+        assert_eq!(
+            round_numbers("translate(10.5051,0.5)", 2),
+            "translate(10.51,0.5)"
+        );
+        assert_eq!(round_numbers("M1.555.555", 1), "M1.6 0.6");
+        assert_eq!(round_numbers("1e-7 2.5e2 1em 100%", 2), "0 250 1em 100%");
+    }
+
+    #[test]
+    fn test_minify_svg() {
+        let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" class="marks" width="20" height="20">
+  <!-- comment -->
+  <g fill="none" stroke-width="2" stroke-miterlimit="10" role="graphics-object" aria-roledescription="group">
+    <path d="M0.3333333,0L10,0" stroke-width="1" stroke-opacity="1" opacity="1" pointer-events="none"/>
+    <image xlink:href="a&amp;b.png" width="5" height="5" transform="translate(0,0)"/>
+    <text x="1" y="2"> a &lt; b </text>
+  </g>
+</svg>"#;
+        assert_eq!(
+            minify_svg(svg, 2).unwrap(),
+            concat!(
+                r#"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" width="20" height="20">"#,
+                r#"<g fill="none" stroke-width="2" stroke-miterlimit="10">"#,
+                r#"<path d="M0.33,0L10,0" stroke-width="1"/>"#,
+                r#"<image xlink:href="a&amp;b.png" width="5" height="5"/>"#,
+                r#"<text x="1" y="2"> a &lt; b </text>"#,
+                "</g></svg>"
+            )
+        );
+    }
+}
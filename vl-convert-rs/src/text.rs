@@ -1,11 +1,13 @@
 use crate::anyhow;
 use crate::anyhow::{anyhow, bail};
-use crate::image_loading::custom_string_resolver;
+use crate::image_loading::{custom_data_resolver, custom_string_resolver};
+use crate::LOG_TARGET;
 use deno_core::error::AnyError;
 use deno_core::op2;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use usvg::fontdb::Database;
 use usvg::{
@@ -13,7 +15,16 @@ use usvg::{
     ImageHrefResolver,
 };
 
-deno_core::extension!(vl_convert_text_runtime, ops = [op_text_width]);
+deno_core::extension!(
+    vl_convert_text_runtime,
+    ops = [op_text_width, op_text_metrics]
+);
+
+/// Font size used by SVG renderers when no font-size is specified
+const DEFAULT_SVG_FONT_SIZE: f64 = 16.0;
+
+/// Baseline of the text in the SVG documents that text is measured with
+const TEXT_BASELINE: f32 = 50.0;
 
 lazy_static! {
     pub static ref USVG_OPTIONS: Mutex<usvg::Options<'static>> = Mutex::new(init_usvg_options());
@@ -30,8 +41,8 @@ const LIBERATION_SANS_BOLDITALIC: &[u8] =
 
 fn init_usvg_options() -> usvg::Options<'static> {
     let image_href_resolver = ImageHrefResolver {
+        resolve_data: custom_data_resolver(),
         resolve_string: custom_string_resolver(),
-        ..Default::default()
     };
 
     let font_resolver = FontResolver {
@@ -169,6 +180,7 @@ pub fn custom_font_selector() -> FontSelectionFn<'static> {
         }
 
         log::warn!(
+            target: LOG_TARGET,
             "No match for '{}' font-family.",
             font.families()
                 .iter()
@@ -188,6 +200,21 @@ pub fn custom_fallback_selector() -> FallbackSelectionFn<'static> {
     Box::new(|c, exclude_fonts, fontdb| {
         let base_font_id = exclude_fonts[0];
 
+        // Emoji are drawn with an emoji font when there is one, rather than with the first
+        // text font that happens to have a monochrome glyph for them
+        if is_emoji(c) {
+            if let Some(id) = select_emoji_font(c, exclude_fonts, fontdb) {
+                return Some(id);
+            }
+            if !EMOJI_FONT_WARNED.swap(true, Ordering::Relaxed) {
+                log::warn!(
+                    target: LOG_TARGET,
+                    "No emoji font has a glyph for {c} (U+{:04X}). Install a color emoji font such as Noto Color Emoji, or register a font directory that contains one.",
+                    c as u32
+                );
+            }
+        }
+
         // Prevent fallback to fonts that won't work, like LastResort on macOS
         let forbidden_fallback = ["LastResort"];
 
@@ -209,19 +236,7 @@ pub fn custom_fallback_selector() -> FallbackSelectionFn<'static> {
                 continue;
             }
 
-            // has_char is private in fontdb
-            // if !fontdb.has_char(face.id, c) {
-            //     continue;
-            // }
-
-            // Implement `fontdb.has_char`, which is not public in fontdb
-            let res = fontdb.with_face_data(face.id, |font_data, face_index| -> Option<bool> {
-                let font = ttf_parser::Face::parse(font_data, face_index).ok()?;
-
-                font.glyph_index(c)?;
-                Some(true)
-            });
-            if res != Some(Some(true)) {
+            if !face_has_char(fontdb, face.id, c) {
                 continue;
             }
 
@@ -237,7 +252,7 @@ pub fn custom_fallback_selector() -> FallbackSelectionFn<'static> {
                 .find(|f| f.1 == fontdb::Language::English_UnitedStates)
                 .unwrap_or(&base_face.families[0]);
 
-            log::warn!("Fallback from {} to {}.", base_family.0, new_family.0);
+            log::warn!(target: LOG_TARGET, "Fallback from {} to {}.", base_family.0, new_family.0);
             return Some(face.id);
         }
 
@@ -245,6 +260,149 @@ pub fn custom_fallback_selector() -> FallbackSelectionFn<'static> {
     })
 }
 
+/// Implement `fontdb.has_char`, which is not public in fontdb
+fn face_has_char(fontdb: &Database, id: fontdb::ID, c: char) -> bool {
+    let res = fontdb.with_face_data(id, |font_data, face_index| -> Option<bool> {
+        let font = ttf_parser::Face::parse(font_data, face_index).ok()?;
+
+        font.glyph_index(c)?;
+        Some(true)
+    });
+    res == Some(Some(true))
+}
+
+/// Emoji font families that are preferred for emoji, in order, after the family set with
+/// [`set_emoji_font`]. Other families with "Emoji" in their name are used after these
+const DEFAULT_EMOJI_FONTS: &[&str] = &[
+    "Noto Color Emoji",
+    "Apple Color Emoji",
+    "Segoe UI Emoji",
+    "Twemoji Mozilla",
+    "Noto Emoji",
+];
+
+lazy_static! {
+    static ref EMOJI_FONT: Mutex<Option<String>> = Mutex::new(None);
+}
+
+/// Whether the warning for emoji without an emoji font has been logged
+static EMOJI_FONT_WARNED: AtomicBool = AtomicBool::new(false);
+
+/// Set the font family that emoji in chart text are drawn with when the font of the text
+/// doesn't have them. When None, or when the family isn't installed or registered with
+/// [`register_font_directory`], the first of the common color emoji fonts that's installed
+/// is used (e.g. Noto Color Emoji or Apple Color Emoji).
+pub fn set_emoji_font(family: Option<String>) -> Result<(), AnyError> {
+    let mut emoji_font = EMOJI_FONT
+        .lock()
+        .map_err(|err| anyhow!("Failed to acquire emoji font lock: {}", err.to_string()))?;
+    *emoji_font = family;
+    EMOJI_FONT_WARNED.store(false, Ordering::Relaxed);
+    Ok(())
+}
+
+/// The font family that emoji in chart text are drawn with, or None when no emoji font is
+/// installed or registered
+pub fn get_emoji_font() -> Result<Option<String>, AnyError> {
+    let opts = USVG_OPTIONS
+        .lock()
+        .map_err(|err| anyhow!("Failed to acquire usvg options lock: {}", err.to_string()))?;
+    let Some(id) = select_emoji_font('\u{1F600}', &[], &opts.fontdb) else {
+        return Ok(None);
+    };
+    Ok(opts
+        .fontdb
+        .face(id)
+        .and_then(|face| face.families.first())
+        .map(|(family, _lang)| family.clone()))
+}
+
+/// Whether a character is an emoji, or part of an emoji sequence like the regional
+/// indicators of flags and the variation selector that requests emoji presentation
+fn is_emoji(c: char) -> bool {
+    matches!(
+        c,
+        '\u{1F000}'..='\u{1FAFF}'
+            | '\u{2300}'..='\u{23FF}'
+            | '\u{2600}'..='\u{27BF}'
+            | '\u{2B00}'..='\u{2BFF}'
+            | '\u{FE0F}'
+            | '\u{20E3}'
+            | '\u{E0020}'..='\u{E007F}'
+    )
+}
+
+fn has_family(face: &fontdb::FaceInfo, family: &str) -> bool {
+    face.families.iter().any(|(name, _lang)| name == family)
+}
+
+/// Select the emoji font to draw an emoji with: the family set with [`set_emoji_font`], then
+/// the common color emoji fonts, then any other font with "Emoji" in its family name
+fn select_emoji_font(
+    c: char,
+    exclude_fonts: &[fontdb::ID],
+    fontdb: &Database,
+) -> Option<fontdb::ID> {
+    let emoji_font = EMOJI_FONT.lock().ok().and_then(|family| family.clone());
+    let families: Vec<&str> = emoji_font
+        .iter()
+        .map(String::as_str)
+        .chain(DEFAULT_EMOJI_FONTS.iter().copied())
+        .collect();
+    let preferred = families
+        .iter()
+        .flat_map(|family| fontdb.faces().filter(|face| has_family(face, family)));
+    let other = fontdb.faces().filter(|face| {
+        face.families
+            .iter()
+            .any(|(name, _lang)| name.contains("Emoji"))
+    });
+    preferred
+        .chain(other)
+        .map(|face| face.id)
+        .find(|id| !exclude_fonts.contains(id) && face_has_char(fontdb, *id, c))
+}
+
+/// Whether any text of an SVG tree is drawn with glyphs of a color font, which have COLR,
+/// CBDT, sbix, or SVG glyphs in place of outlines (like color emoji fonts)
+pub(crate) fn has_color_glyphs(tree: &usvg::Tree) -> bool {
+    let fontdb = tree.fontdb();
+    let mut color_fonts: HashMap<fontdb::ID, bool> = HashMap::new();
+    let mut is_color_font = |id: fontdb::ID| {
+        *color_fonts.entry(id).or_insert_with(|| {
+            fontdb
+                .with_face_data(id, |font_data, face_index| {
+                    let face = ttf_parser::Face::parse(font_data, face_index).ok()?;
+                    let tables = face.tables();
+                    Some(
+                        tables.colr.is_some()
+                            || tables.cbdt.is_some()
+                            || tables.sbix.is_some()
+                            || tables.svg.is_some(),
+                    )
+                })
+                .flatten()
+                .unwrap_or(false)
+        })
+    };
+    group_has_color_glyphs(tree.root(), &mut is_color_font)
+}
+
+fn group_has_color_glyphs(
+    group: &usvg::Group,
+    is_color_font: &mut impl FnMut(fontdb::ID) -> bool,
+) -> bool {
+    group.children().iter().any(|node| match node {
+        usvg::Node::Group(group) => group_has_color_glyphs(group, is_color_font),
+        usvg::Node::Text(text) => text.layouted().iter().any(|span| {
+            span.positioned_glyphs
+                .iter()
+                .any(|glyph| is_color_font(glyph.font))
+        }),
+        _ => false,
+    })
+}
+
 #[derive(Deserialize, Clone, Debug)]
 struct TextInfo {
     style: Option<String>,
@@ -304,7 +462,7 @@ impl TextInfo {
         format!(
             r#"
 <svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" version="1.1" width="100" height="100">
-    <text x="20" y="50" {text_attrs_str}>{escaped_text}</text>
+    <text x="20" y="{TEXT_BASELINE}" {text_attrs_str}>{escaped_text}</text>
 </svg>"#,
             text_attrs_str = text_attrs_str,
             escaped_text = escaped_text
@@ -332,7 +490,11 @@ pub fn op_text_width(#[string] text_info_str: String) -> Result<f64, AnyError> {
         Ok(text_info) => text_info,
         Err(err) => bail!("Failed to deserialize text info: {}", err.to_string()),
     };
+    text_width(&text_info)
+}
 
+/// Measure the width of text with the same font database that's used for rasterization
+fn text_width(text_info: &TextInfo) -> Result<f64, AnyError> {
     // Return width zero for text with non-positive size
     if text_info.size <= 0.0 {
         return Ok(0.0);
@@ -389,6 +551,143 @@ fn extract_text_width(svg: &String) -> Result<f64, AnyError> {
     bail!("Failed to locate text in SVG:\n{}\n{:?}", svg, node_strs)
 }
 
+#[op2]
+#[serde]
+pub fn op_text_metrics(#[string] text_info_str: String) -> Result<TextMetrics, AnyError> {
+    let text_info = match serde_json::from_str::<TextInfo>(&text_info_str) {
+        Ok(text_info) => text_info,
+        Err(err) => bail!("Failed to deserialize text info: {}", err.to_string()),
+    };
+    text_metrics(&text_info)
+}
+
+/// Width and glyph positions of measured text
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+pub struct TextMetrics {
+    /// Width of the text, the same as the width used to lay out labels
+    pub width: f64,
+    /// Glyphs in the order that they're drawn
+    pub glyphs: Vec<GlyphMetrics>,
+}
+
+/// Position of a single glyph of measured text
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct GlyphMetrics {
+    /// Byte offset in the text of the cluster that the glyph belongs to
+    pub cluster: usize,
+    /// Distance from the glyph to the next cluster. Only the last glyph of a cluster has
+    /// an advance, so the advances sum to the width of the text
+    pub x_advance: f64,
+    /// Horizontal distance of the glyph from the start of its cluster
+    pub x_offset: f64,
+    /// Vertical distance of the glyph above the baseline
+    pub y_offset: f64,
+    /// Index of the glyph in its font
+    pub glyph_id: u16,
+    /// Family of the font the glyph is drawn with, which differs from the requested
+    /// family when the glyph comes from a fallback font
+    pub font_family: String,
+}
+
+/// Measure text glyph by glyph with the same font database that's used for rasterization,
+/// for tools that lay out labels outside of Vega
+pub fn measure_text_detailed(
+    text: &str,
+    family: Option<&str>,
+    size: f64,
+    weight: Option<&str>,
+) -> Result<TextMetrics, AnyError> {
+    text_metrics(&TextInfo {
+        style: None,
+        variant: None,
+        weight: weight.map(String::from),
+        family: family.map(String::from),
+        size,
+        text: Some(Value::String(text.to_string())),
+    })
+}
+
+fn text_metrics(text_info: &TextInfo) -> Result<TextMetrics, AnyError> {
+    let text = match &text_info.text {
+        Some(Value::String(s)) => s.to_string(),
+        Some(text) => text.to_string(),
+        None => "".to_string(),
+    };
+    if text_info.size <= 0.0 || text.trim().is_empty() {
+        return Ok(TextMetrics::default());
+    }
+
+    let svg = text_info.to_svg();
+    let opts = USVG_OPTIONS
+        .lock()
+        .map_err(|err| anyhow!("Failed to acquire usvg options lock: {}", err.to_string()))?;
+    let rtree = usvg::Tree::from_str(&svg, &opts)?;
+    let Some(usvg::Node::Text(node)) = rtree
+        .root()
+        .children()
+        .iter()
+        .find(|node| matches!(node, usvg::Node::Text(_)))
+    else {
+        bail!("Failed to locate text in SVG:\n{}", svg)
+    };
+    let bbox = node.bounding_box();
+    let width = (bbox.right() - bbox.left()) as f64;
+
+    // Glyphs of a cluster are consecutive, and only the last one holds the cluster's text
+    let mut clusters: Vec<Vec<&usvg::layout::PositionedGlyph>> = vec![vec![]];
+    for glyph in node
+        .layouted()
+        .iter()
+        .flat_map(|span| &span.positioned_glyphs)
+    {
+        clusters.last_mut().unwrap().push(glyph);
+        if !glyph.text.is_empty() {
+            clusters.push(vec![]);
+        }
+    }
+    clusters.retain(|cluster| !cluster.is_empty());
+
+    // Whitespace that's collapsed by the SVG layout has no glyphs, so clusters are located
+    // by searching forward in the text
+    let pen_x =
+        |cluster: &[&usvg::layout::PositionedGlyph]| cluster[0].transform().tx - bbox.left();
+    let mut glyphs = Vec::new();
+    let mut offset = 0;
+    for (i, cluster) in clusters.iter().enumerate() {
+        let cluster_text = &cluster.last().unwrap().text;
+        let cluster_offset = text[offset..]
+            .find(cluster_text.as_str())
+            .map_or(offset, |pos| offset + pos);
+        offset = cluster_offset + cluster_text.len();
+
+        let start = pen_x(cluster);
+        let end = clusters.get(i + 1).map_or(width as f32, |next| pen_x(next));
+        for (j, glyph) in cluster.iter().enumerate() {
+            let ts = glyph.transform();
+            let font_family = rtree
+                .fontdb()
+                .face(glyph.font)
+                .and_then(|face| face.families.first())
+                .map(|(family, _)| family.clone())
+                .unwrap_or_default();
+            glyphs.push(GlyphMetrics {
+                cluster: cluster_offset,
+                x_advance: if j + 1 == cluster.len() {
+                    (end - start) as f64
+                } else {
+                    0.0
+                },
+                x_offset: (ts.tx - bbox.left() - start) as f64,
+                y_offset: (TEXT_BASELINE - ts.ty) as f64,
+                glyph_id: glyph.id.0,
+                font_family,
+            });
+        }
+    }
+
+    Ok(TextMetrics { width, glyphs })
+}
+
 pub fn register_font_directory(dir: &str) -> Result<(), anyhow::Error> {
     let mut opts = USVG_OPTIONS
         .lock()
@@ -406,3 +705,114 @@ pub fn register_font_directory(dir: &str) -> Result<(), anyhow::Error> {
 
     Ok(())
 }
+
+/// Generic font families that the generic_font_mapping option can map to specific fonts
+pub const GENERIC_FONT_FAMILIES: &[&str] = &["sans-serif", "serif", "monospace", "cursive"];
+
+/// Parse a `generic=font` mapping of a generic font family to a specific font, like those
+/// given with the --map-font flag of the CLI (e.g. `sans-serif=Inter`)
+pub fn parse_generic_font_mapping(arg: &str) -> Result<(String, String), AnyError> {
+    let Some((generic, font)) = arg.split_once('=') else {
+        bail!("Invalid font mapping {arg:?}, expected generic=font")
+    };
+    let generic = generic.trim().to_ascii_lowercase();
+    let font = font.trim();
+    check_generic_font_family(&generic)?;
+    if font.is_empty() {
+        bail!("Invalid font mapping {arg:?}, the font is empty")
+    }
+    Ok((generic, font.to_string()))
+}
+
+/// Check that the keys of a generic_font_mapping option are generic font families
+pub(crate) fn check_generic_font_mapping(
+    mapping: &HashMap<String, String>,
+) -> Result<(), AnyError> {
+    mapping
+        .keys()
+        .try_for_each(|generic| check_generic_font_family(generic))
+}
+
+fn check_generic_font_family(generic: &str) -> Result<(), AnyError> {
+    if !GENERIC_FONT_FAMILIES.contains(&generic) {
+        bail!(
+            "Unsupported generic font family {generic:?}, expected one of: {}",
+            GENERIC_FONT_FAMILIES.join(", ")
+        )
+    }
+    Ok(())
+}
+
+/// Add `textLength` and `lengthAdjust="spacingAndGlyphs"` attributes to the text in an SVG
+/// document.
+///
+/// Text is measured with the same font database that's used for rasterization, so that
+/// applications which substitute fonts reproduce the original layout. Text elements with
+/// `tspan` children have each `tspan` annotated instead of the parent element.
+pub fn add_svg_text_length(svg: &str) -> Result<String, AnyError> {
+    let xml_opt = usvg::roxmltree::ParsingOptions {
+        allow_dtd: true,
+        ..Default::default()
+    };
+    let doc = usvg::roxmltree::Document::parse_with_options(svg, xml_opt)?;
+
+    // Byte offsets just past the start tag name, paired with the attributes to insert there
+    let mut insertions: Vec<(usize, String)> = Vec::new();
+    for node in doc.descendants() {
+        let tag_name = node.tag_name().name();
+        if tag_name != "text" && tag_name != "tspan" {
+            continue;
+        }
+        let has_tspans = node
+            .children()
+            .any(|child| child.tag_name().name() == "tspan");
+        if has_tspans || node.has_attribute("textLength") {
+            continue;
+        }
+
+        // Skip the text of <title> elements, which holds native tooltips rather than
+        // rendered text
+        let text: String = node
+            .descendants()
+            .filter(|n| n.is_text() && !n.parent().is_some_and(|p| p.has_tag_name("title")))
+            .filter_map(|n| n.text())
+            .collect();
+        let width = text_width(&svg_text_info(&node, text))?;
+        if !width.is_finite() || width <= 0.0 {
+            continue;
+        }
+
+        let offset = node.range().start + 1 + tag_name.len();
+        insertions.push((
+            offset,
+            format!(" textLength=\"{width:.2}\" lengthAdjust=\"spacingAndGlyphs\""),
+        ));
+    }
+
+    let mut result = svg.to_string();
+    for (offset, attrs) in insertions.into_iter().rev() {
+        result.insert_str(offset, &attrs);
+    }
+    Ok(result)
+}
+
+/// Build the font properties of an SVG text or tspan element, including those inherited
+/// from its ancestors
+fn svg_text_info(node: &usvg::roxmltree::Node, text: String) -> TextInfo {
+    let inherited = |name: &str| {
+        node.ancestors()
+            .find_map(|n| n.attribute(name))
+            .map(|v| v.to_string())
+    };
+    let size = inherited("font-size")
+        .and_then(|size| size.trim().trim_end_matches("px").parse::<f64>().ok())
+        .unwrap_or(DEFAULT_SVG_FONT_SIZE);
+    TextInfo {
+        style: inherited("font-style"),
+        variant: inherited("font-variant"),
+        weight: inherited("font-weight"),
+        family: inherited("font-family"),
+        size,
+        text: Some(Value::String(text)),
+    }
+}
@@ -0,0 +1,132 @@
+use serde_json::{Map, Value};
+
+/// Text properties of the Vega-Lite config that the text style options set, as the config
+/// group, the prefix of the property names, and Vega's default font size for the text
+const TEXT_PROPERTIES: &[(&str, &str, f64)] = &[
+    ("axis", "label", 10.0),
+    ("axis", "title", 11.0),
+    ("legend", "label", 10.0),
+    ("legend", "title", 11.0),
+    ("header", "label", 10.0),
+    ("header", "title", 11.0),
+    ("title", "", 13.0),
+    ("title", "subtitle", 12.0),
+    ("text", "", 11.0),
+];
+
+/// Expand the font, font size scale, and text color options into a Vega-Lite config that
+/// sets them for axes, legends, headers, titles, and text marks. Font sizes are Vega's
+/// default sizes multiplied by `font_size_scale`. Returns None when no option is set
+pub(crate) fn text_style_config(
+    font: Option<&str>,
+    font_size_scale: Option<f32>,
+    text_color: Option<&str>,
+) -> Option<Value> {
+    if font.is_none() && font_size_scale.is_none() && text_color.is_none() {
+        return None;
+    }
+
+    let mut config = Map::new();
+    if let Some(font) = font {
+        config.insert("font".to_string(), Value::from(font));
+    }
+    for (group, prefix, default_size) in TEXT_PROPERTIES {
+        let property = |name: &str| {
+            if prefix.is_empty() {
+                let mut chars = name.chars();
+                chars
+                    .next()
+                    .map(|first| first.to_ascii_lowercase().to_string() + chars.as_str())
+                    .unwrap_or_default()
+            } else {
+                format!("{prefix}{name}")
+            }
+        };
+        let Value::Object(group) = config
+            .entry(group.to_string())
+            .or_insert_with(|| Value::Object(Map::new()))
+        else {
+            continue;
+        };
+        if let Some(font) = font {
+            group.insert(property("Font"), Value::from(font));
+        }
+        if let Some(scale) = font_size_scale {
+            let size = (default_size * scale as f64 * 100.0).round() / 100.0;
+            group.insert(property("FontSize"), Value::from(size));
+        }
+        if let Some(color) = text_color {
+            group.insert(property("Color"), Value::from(color));
+        }
+    }
+    Some(Value::Object(config))
+}
+
+/// Merge `overrides` into `base`, merging nested objects and replacing all other values
+pub(crate) fn merge_config(base: &mut Value, overrides: &Value) {
+    match (base, overrides) {
+        (Value::Object(base), Value::Object(overrides)) => {
+            for (key, value) in overrides {
+                match base.get_mut(key) {
+                    Some(base_value) => merge_config(base_value, value),
+                    None => {
+                        base.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+        }
+        (base, overrides) => *base = overrides.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_text_style_config() {
+        assert_eq!(text_style_config(None, None, None), None);
+
+        let config = text_style_config(Some("Courier New"), Some(1.5), Some("#333")).unwrap();
+        assert_eq!(config["font"], json!("Courier New"));
+        assert_eq!(
+            config["axis"],
+            json!({
+                "labelFont": "Courier New",
+                "labelFontSize": 15.0,
+                "labelColor": "#333",
+                "titleFont": "Courier New",
+                "titleFontSize": 16.5,
+                "titleColor": "#333"
+            })
+        );
+        assert_eq!(
+            config["title"],
+            json!({
+                "font": "Courier New",
+                "fontSize": 19.5,
+                "color": "#333",
+                "subtitleFont": "Courier New",
+                "subtitleFontSize": 18.0,
+                "subtitleColor": "#333"
+            })
+        );
+        assert_eq!(
+            config["text"],
+            json!({"font": "Courier New", "fontSize": 16.5, "color": "#333"})
+        );
+    }
+
+    #[test]
+    fn test_merge_config() {
+        let mut config = text_style_config(Some("Courier New"), None, None).unwrap();
+        merge_config(
+            &mut config,
+            &json!({"axis": {"labelFont": "Georgia"}, "background": "#eee"}),
+        );
+        assert_eq!(config["axis"]["labelFont"], json!("Georgia"));
+        assert_eq!(config["axis"]["titleFont"], json!("Courier New"));
+        assert_eq!(config["background"], json!("#eee"));
+    }
+}
@@ -0,0 +1,326 @@
+use crate::anyhow::{anyhow, bail};
+use deno_core::error::AnyError;
+use deno_core::op2;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Number of vertices of the arcs of a TopoJSON topology before and after it was simplified
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TopologySimplification {
+    pub vertices: usize,
+    pub simplified_vertices: usize,
+}
+
+/// Transform of a quantized TopoJSON topology, whose arcs hold delta-encoded integer positions
+#[derive(Debug, Clone, Deserialize)]
+struct Transform {
+    scale: [f64; 2],
+    translate: [f64; 2],
+}
+
+/// Simplify the arcs of a TopoJSON topology with the Douglas-Peucker algorithm, dropping the
+/// vertices that are within `tolerance` of the line that's kept. The tolerance is in the
+/// units of the topology's coordinates, after the transform of quantized topologies (degrees
+/// for unprojected topologies, and pixels for topologies that are drawn with the identity
+/// projection).
+///
+/// Each arc is simplified once, so that the borders that geometries share stay shared, and
+/// the endpoints of arcs are kept, so that the rings of geometries stay closed. Closed arcs
+/// keep at least four vertices, so that rings made of one arc don't collapse. The geometries
+/// of the topology aren't changed. Returns None when the value isn't a topology
+pub fn simplify_topology(
+    topology: &mut Value,
+    tolerance: f64,
+) -> Result<Option<TopologySimplification>, AnyError> {
+    if topology.get("type").and_then(Value::as_str) != Some("Topology") {
+        return Ok(None);
+    }
+    let transform = match topology.get("transform") {
+        None | Some(Value::Null) => None,
+        Some(transform) => Some(
+            serde_json::from_value::<Transform>(transform.clone())
+                .map_err(|err| anyhow!("Invalid TopoJSON transform: {err}"))?,
+        ),
+    };
+    let Some(Value::Array(arcs)) = topology.get_mut("arcs") else {
+        bail!("TopoJSON topology has no arcs array")
+    };
+
+    let mut simplification = TopologySimplification {
+        vertices: 0,
+        simplified_vertices: 0,
+    };
+    for arc in arcs.iter_mut() {
+        let Value::Array(positions) = arc else {
+            bail!("TopoJSON arc is not an array of positions")
+        };
+        simplification.vertices += positions.len();
+        let absolute = absolute_positions(positions, transform.is_some())?;
+        let points: Vec<[f64; 2]> = match &transform {
+            Some(Transform { scale, translate }) => absolute
+                .iter()
+                .map(|[x, y]| [x * scale[0] + translate[0], y * scale[1] + translate[1]])
+                .collect(),
+            None => absolute.clone(),
+        };
+        let kept = simplify_points(&points, tolerance);
+        if kept.len() < positions.len() {
+            *positions = kept_positions(positions, &absolute, &kept, transform.is_some());
+        }
+        simplification.simplified_vertices += positions.len();
+    }
+    Ok(Some(simplification))
+}
+
+/// The x and y of the positions of an arc, decoding the deltas of quantized arcs
+fn absolute_positions(positions: &[Value], quantized: bool) -> Result<Vec<[f64; 2]>, AnyError> {
+    let mut absolute: Vec<[f64; 2]> = Vec::with_capacity(positions.len());
+    for position in positions {
+        let coordinate = |i: usize| position.get(i).and_then(Value::as_f64);
+        let (Some(x), Some(y)) = (coordinate(0), coordinate(1)) else {
+            bail!("Invalid TopoJSON arc position: {position}")
+        };
+        absolute.push(match absolute.last() {
+            Some([previous_x, previous_y]) if quantized => [previous_x + x, previous_y + y],
+            _ => [x, y],
+        });
+    }
+    Ok(absolute)
+}
+
+/// The kept positions of an arc, with the deltas of quantized arcs encoded between the kept
+/// positions. Coordinates after x and y (e.g. z) are kept as they are
+fn kept_positions(
+    positions: &[Value],
+    absolute: &[[f64; 2]],
+    kept: &[usize],
+    quantized: bool,
+) -> Vec<Value> {
+    if !quantized {
+        return kept.iter().map(|&i| positions[i].clone()).collect();
+    }
+    let mut previous = [0.0, 0.0];
+    kept.iter()
+        .map(|&i| {
+            let [x, y] = absolute[i];
+            let mut position = positions[i].clone();
+            position[0] = integer_or_float(x - previous[0]);
+            position[1] = integer_or_float(y - previous[1]);
+            previous = [x, y];
+            position
+        })
+        .collect()
+}
+
+fn integer_or_float(value: f64) -> Value {
+    if value.fract() == 0.0 && value.abs() < 2f64.powi(53) {
+        Value::from(value as i64)
+    } else {
+        Value::from(value)
+    }
+}
+
+/// Indices of the points of an arc that are kept when it's simplified with `tolerance`
+fn simplify_points(points: &[[f64; 2]], tolerance: f64) -> Vec<usize> {
+    if points.len() <= 2 {
+        return (0..points.len()).collect();
+    }
+    let last = points.len() - 1;
+    let mut keep = vec![false; points.len()];
+    keep[0] = true;
+    keep[last] = true;
+    if points[0] == points[last] && points.len() >= 4 {
+        // A closed arc is split at the point farthest from its start, and the farthest point
+        // of each half is kept whatever the tolerance, so that at least four points are left
+        let split = (1..last)
+            .max_by(|&a, &b| {
+                distance(points[a], points[0]).total_cmp(&distance(points[b], points[0]))
+            })
+            .unwrap();
+        keep[split] = true;
+        douglas_peucker(points, 0, split, tolerance, true, &mut keep);
+        douglas_peucker(points, split, last, tolerance, true, &mut keep);
+    } else {
+        douglas_peucker(points, 0, last, tolerance, false, &mut keep);
+    }
+    (0..points.len()).filter(|&i| keep[i]).collect()
+}
+
+/// Mark the points between `start` and `end` that are farther than `tolerance` from the line
+/// that's kept. With `force`, the farthest point is kept whatever its distance
+fn douglas_peucker(
+    points: &[[f64; 2]],
+    start: usize,
+    end: usize,
+    tolerance: f64,
+    force: bool,
+    keep: &mut [bool],
+) {
+    let mut spans = vec![(start, end, force)];
+    while let Some((start, end, force)) = spans.pop() {
+        let farthest = (start + 1..end)
+            .map(|i| (i, segment_distance(points[i], points[start], points[end])))
+            .max_by(|(_, a), (_, b)| a.total_cmp(b));
+        if let Some((index, farthest_distance)) = farthest {
+            if force || farthest_distance > tolerance {
+                keep[index] = true;
+                spans.push((start, index, false));
+                spans.push((index, end, false));
+            }
+        }
+    }
+}
+
+fn distance(a: [f64; 2], b: [f64; 2]) -> f64 {
+    (a[0] - b[0]).hypot(a[1] - b[1])
+}
+
+/// Distance from a point to the segment between `a` and `b`
+fn segment_distance(point: [f64; 2], a: [f64; 2], b: [f64; 2]) -> f64 {
+    let (dx, dy) = (b[0] - a[0], b[1] - a[1]);
+    let length_squared = dx * dx + dy * dy;
+    if length_squared == 0.0 {
+        return distance(point, a);
+    }
+    let t = (((point[0] - a[0]) * dx + (point[1] - a[1]) * dy) / length_squared).clamp(0.0, 1.0);
+    distance(point, [a[0] + t * dx, a[1] + t * dy])
+}
+
+/// JavaScript literal for the tolerance that the TopoJSON datasets of a conversion are
+/// simplified with. `null` when `simplify_topojson` isn't set
+pub(crate) fn simplify_topojson_arg(tolerance: Option<f64>) -> Result<String, AnyError> {
+    match tolerance {
+        None => Ok("null".to_string()),
+        Some(tolerance) if tolerance.is_finite() && tolerance >= 0.0 => {
+            Ok(serde_json::to_string(&tolerance)?)
+        }
+        Some(tolerance) => {
+            bail!("simplify_topojson must be a non-negative number, not {tolerance}")
+        }
+    }
+}
+
+/// TopoJSON text that was simplified by [`op_simplify_topojson`], with the number of vertices
+/// of its arcs before and after
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SimplifiedTopojson {
+    topology: String,
+    vertices: usize,
+    simplified_vertices: usize,
+}
+
+/// Simplify the text of a TopoJSON dataset before Vega parses it. Returns null when the text
+/// isn't a topology
+#[op2]
+#[serde]
+pub(crate) fn op_simplify_topojson(
+    #[string] text: String,
+    tolerance: f64,
+) -> Result<Option<SimplifiedTopojson>, AnyError> {
+    let mut topology: Value = serde_json::from_str(&text)?;
+    let Some(simplification) = simplify_topology(&mut topology, tolerance)? else {
+        return Ok(None);
+    };
+    Ok(Some(SimplifiedTopojson {
+        topology: serde_json::to_string(&topology)?,
+        vertices: simplification.vertices,
+        simplified_vertices: simplification.simplified_vertices,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_simplify_topology() {
+        // A wiggly border shared by two squares, and the outer boundaries of the squares
+        let mut topology = json!({
+            "type": "Topology",
+            "objects": {"regions": {"type": "GeometryCollection", "geometries": [
+                {"type": "Polygon", "arcs": [[0, 1]]},
+                {"type": "Polygon", "arcs": [[-1, 2]]},
+            ]}},
+            "arcs": [
+                [[10, 0], [10.1, 2], [9.9, 4], [10.1, 6], [9.9, 8], [10, 10]],
+                [[10, 10], [0, 10], [0, 5], [0, 0], [10, 0]],
+                [[10, 0], [20, 0], [20, 10], [10, 10]],
+            ],
+        });
+        let simplification = simplify_topology(&mut topology, 0.5).unwrap().unwrap();
+        assert_eq!(
+            simplification,
+            TopologySimplification {
+                vertices: 15,
+                simplified_vertices: 10,
+            }
+        );
+        assert_eq!(topology["arcs"][0], json!([[10, 0], [10, 10]]));
+        assert_eq!(
+            topology["arcs"][1],
+            json!([[10, 10], [0, 10], [0, 0], [10, 0]])
+        );
+        assert_eq!(
+            topology["arcs"][2],
+            json!([[10, 0], [20, 0], [20, 10], [10, 10]])
+        );
+        assert_eq!(
+            topology["objects"]["regions"]["geometries"]
+                .as_array()
+                .unwrap()
+                .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_simplify_quantized_topology() {
+        // Positions are (0, 0), (1, 0), (2, 1), (3, 0), (4, 0) after decoding the deltas,
+        // which are scaled by 2
+        let mut topology = json!({
+            "type": "Topology",
+            "transform": {"scale": [2, 2], "translate": [100, 0]},
+            "objects": {},
+            "arcs": [[[0, 0], [1, 0], [1, 1], [1, -1], [1, 0, 7]]],
+        });
+        simplify_topology(&mut topology, 1.0).unwrap().unwrap();
+        assert_eq!(topology["arcs"][0], json!([[0, 0], [2, 1], [2, -1, 7]]));
+
+        simplify_topology(&mut topology, 3.0).unwrap().unwrap();
+        assert_eq!(topology["arcs"][0], json!([[0, 0], [4, 0, 7]]));
+    }
+
+    #[test]
+    fn test_simplify_closed_arc() {
+        // An island whose ring is a single arc keeps four points whatever the tolerance
+        let mut topology = json!({
+            "type": "Topology",
+            "objects": {},
+            "arcs": [[[0, 0], [1, 0], [2, 0.1], [2, 2], [1, 2], [0, 2], [0, 0]]],
+        });
+        let simplification = simplify_topology(&mut topology, 100.0).unwrap().unwrap();
+        assert_eq!(simplification.simplified_vertices, 5);
+        assert_eq!(
+            topology["arcs"][0],
+            json!([[0, 0], [2, 0.1], [2, 2], [0, 2], [0, 0]])
+        );
+    }
+
+    #[test]
+    fn test_simplify_non_topology() {
+        let mut geojson = json!({"type": "FeatureCollection", "features": []});
+        assert_eq!(simplify_topology(&mut geojson, 1.0).unwrap(), None);
+        let mut invalid = json!({"type": "Topology", "arcs": [[[0]]]});
+        assert!(simplify_topology(&mut invalid, 1.0).is_err());
+    }
+
+    #[test]
+    fn test_simplify_topojson_arg() {
+        assert_eq!(simplify_topojson_arg(None).unwrap(), "null");
+        assert_eq!(simplify_topojson_arg(Some(0.5)).unwrap(), "0.5");
+        assert!(simplify_topojson_arg(Some(-1.0)).is_err());
+        assert!(simplify_topojson_arg(Some(f64::NAN)).is_err());
+    }
+}
@@ -0,0 +1,118 @@
+use crate::module_loader::vendor_manifest::VENDOR_MANIFEST;
+use crate::module_loader::IMPORT_MAP;
+use deno_core::anyhow::{anyhow, bail};
+use deno_core::error::AnyError;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+
+lazy_static! {
+    /// Outcome of the integrity check that's run when the first converter starts
+    #[cfg(feature = "verify-vendor")]
+    static ref VENDOR_INTEGRITY: Result<usize, String> =
+        verify_vendor_integrity().map_err(|err| err.to_string());
+}
+
+/// Check the embedded sources of Vega, Vega-Lite, and their dependencies against the SHA-256
+/// hashes that vl-convert-vendor recorded in the vendor manifest when they were vendored.
+///
+/// Returns the number of modules that were checked, or an error naming each module that's
+/// modified, missing, or not listed in the manifest
+pub fn verify_vendor_integrity() -> Result<usize, AnyError> {
+    verify_modules(VENDOR_MANIFEST, &IMPORT_MAP)
+}
+
+/// Run [`verify_vendor_integrity`] the first time it's called, and return its outcome on
+/// every call
+#[cfg(feature = "verify-vendor")]
+pub(crate) fn ensure_vendor_integrity() -> Result<(), AnyError> {
+    match &*VENDOR_INTEGRITY {
+        Ok(_) => Ok(()),
+        Err(err) => Err(anyhow!("{err}")),
+    }
+}
+
+fn verify_modules(
+    manifest: &[(&str, &str)],
+    modules: &HashMap<String, String>,
+) -> Result<usize, AnyError> {
+    let mut problems = Vec::new();
+    for (path, expected_hash) in manifest {
+        match modules.get(*path) {
+            Some(source) => {
+                let hash = format!("{:x}", Sha256::digest(source.as_bytes()));
+                if hash != *expected_hash {
+                    problems.push(format!("{path} (hash mismatch)"));
+                }
+            }
+            None => problems.push(format!("{path} (missing)")),
+        }
+    }
+
+    let mut unlisted: Vec<_> = modules
+        .keys()
+        .filter(|path| !manifest.iter().any(|(listed, _)| *listed == path.as_str()))
+        .collect();
+    unlisted.sort();
+    problems.extend(
+        unlisted
+            .into_iter()
+            .map(|path| format!("{path} (not in manifest)")),
+    );
+
+    if !problems.is_empty() {
+        bail!(
+            "Vendored JavaScript doesn't match the vendor manifest: {}",
+            problems.join(", ")
+        )
+    }
+    Ok(manifest.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verify_vendor_integrity() {
+        assert_eq!(verify_vendor_integrity().unwrap(), IMPORT_MAP.len());
+    }
+
+    #[test]
+    fn test_detect_corrupted_module() {
+        let vega_path = "/pin/vega@v5.30.0/vega.js";
+        let (_, vega_hash) = VENDOR_MANIFEST
+            .iter()
+            .find(|(path, _)| path.starts_with("/pin/vega@"))
+            .unwrap();
+        let source = IMPORT_MAP
+            .iter()
+            .find(|(path, _)| path.starts_with("/pin/vega@"))
+            .map(|(_, source)| source.clone())
+            .unwrap();
+
+        let manifest = [(vega_path, *vega_hash)];
+        let mut modules = HashMap::from([(vega_path.to_string(), source)]);
+        assert_eq!(verify_modules(&manifest, &modules).unwrap(), 1);
+
+        // Corrupt the module
+        modules
+            .get_mut(vega_path)
+            .unwrap()
+            .push_str("\nfetch('https://example.com');");
+        let err = verify_modules(&manifest, &modules).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Vendored JavaScript doesn't match the vendor manifest: \
+             /pin/vega@v5.30.0/vega.js (hash mismatch)"
+        );
+
+        // Add a module that isn't in the manifest, and drop one that is
+        let modules = HashMap::from([("/extra.js".to_string(), String::new())]);
+        let err = verify_modules(&manifest, &modules).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Vendored JavaScript doesn't match the vendor manifest: \
+             /pin/vega@v5.30.0/vega.js (missing), /extra.js (not in manifest)"
+        );
+    }
+}
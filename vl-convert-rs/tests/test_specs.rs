@@ -1,16 +1,31 @@
+use base64::Engine;
 use dssim::{Dssim, DssimImage};
 use rstest::rstest;
+use std::collections::HashMap;
 use std::fs;
 use std::io::Write;
 use std::path::{Path, PathBuf};
+use vl_convert_rs::dataflow_error::{DataflowError, DataflowPhase};
+use vl_convert_rs::dataset_summary::DatasetInfo;
+use vl_convert_rs::direction::Direction;
+use vl_convert_rs::facets::FacetOpts;
+use vl_convert_rs::request_hash::vegalite_request_hash;
 use vl_convert_rs::text::register_font_directory;
 use vl_convert_rs::{VlConverter, VlVersion};
 
 use serde_json::Value;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Once;
-use vl_convert_rs::converter::{FormatLocale, TimeFormatLocale, VlOpts};
+use vl_convert_rs::converter::{
+    ExportFormat, ExportRequest, FormatLocale, HtmlOpts, ImageFileOpts, InteractionStep, PdfOpts,
+    PngOpts, Renderer, TimeFormatLocale, VlOpts,
+};
 
 static INIT: Once = Once::new();
+
+/// Serializes tests that read the statistics of the most recent conversion, which are
+/// shared by the whole process
+static RESOURCE_STATS_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
 const BACKGROUND_COLOR: &str = "#abc";
 
 pub fn initialize() {
@@ -445,6 +460,475 @@ mod test_vegalite_to_html_bundle {
     fn test_marker() {} // Help IDE detect test module
 }
 
+#[tokio::test]
+async fn test_vegalite_to_html_report() {
+    initialize();
+
+    let specs = vec![
+        (
+            Some("Binned <circles> & counts".to_string()),
+            load_vl_spec("circle_binned"),
+        ),
+        (None, load_vl_spec("stacked_bar_h")),
+        (Some("Weather".to_string()), load_vl_spec("seattle-weather")),
+    ];
+
+    let mut converter = VlConverter::new();
+    let html = converter
+        .vegalite_to_html_report(specs, Default::default(), true, Renderer::Svg)
+        .await
+        .unwrap();
+
+    assert!(html.starts_with("<!DOCTYPE html>"));
+
+    // The bundle is included once, followed by a single script that embeds every chart
+    assert_eq!(html.matches("<script").count(), 2);
+    assert_eq!(html.matches("vegaEmbed('#vega-chart-").count(), 3);
+    for i in 0..3 {
+        assert_eq!(html.matches(&format!("id=\"vega-chart-{i}\"")).count(), 1);
+        assert!(html.contains(&format!("vegaEmbed('#vega-chart-{i}'")));
+    }
+
+    assert_eq!(html.matches("<section>").count(), 3);
+    assert_eq!(html.matches("<h2>").count(), 2);
+    assert!(html.contains("<h2>Binned &lt;circles&gt; &amp; counts</h2>"));
+    assert!(html.contains("<h2>Weather</h2>"));
+
+    let err = converter
+        .vegalite_to_html_report(vec![], Default::default(), false, Renderer::Svg)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("at least one Vega-Lite spec"));
+}
+
+#[tokio::test]
+async fn test_vegalite_to_html_static_fallback() {
+    initialize();
+
+    let vl_spec = load_vl_spec("circle_binned");
+    let mut converter = VlConverter::new();
+    let html = converter
+        .vegalite_to_html_with_opts(
+            vl_spec.clone(),
+            Default::default(),
+            false,
+            Renderer::Svg,
+            HtmlOpts {
+                static_fallback: true,
+                static_fallback_scale: None,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+    // The image is the initial content of the chart div, and is repeated in a noscript block
+    assert!(html.contains(r#"<div id="vega-chart"><img src="data:image/png;base64,"#));
+    assert_eq!(html.matches("<noscript>").count(), 1);
+    let prefix = r#"src="data:image/png;base64,"#;
+    let start = html.find(prefix).unwrap() + prefix.len();
+    let end = start + html[start..].find('"').unwrap();
+    let png = base64::engine::general_purpose::STANDARD
+        .decode(&html[start..end])
+        .unwrap();
+
+    // The fallback image is rendered at a scale of 2 by default
+    let expected = converter
+        .vegalite_to_png_info(vl_spec.clone(), Default::default(), Some(2.0), None)
+        .await
+        .unwrap();
+    assert_eq!(png, expected.data);
+    assert!(html.contains(&format!(
+        r#"width="{}" height="{}""#,
+        expected.css_width, expected.css_height
+    )));
+
+    let html = converter
+        .vegalite_to_html(vl_spec, Default::default(), false, Renderer::Svg)
+        .await
+        .unwrap();
+    assert!(html.contains(r#"<div id="vega-chart"></div>"#));
+    assert!(!html.contains("<noscript>"));
+}
+
+#[tokio::test]
+async fn test_image_info_request_hash() {
+    initialize();
+
+    let vl_spec = serde_json::json!({
+        "data": {"values": [{"a": 1}, {"a": 2}]},
+        "mark": "point",
+        "encoding": {"x": {"field": "a", "type": "quantitative"}}
+    });
+    let mut converter = VlConverter::new();
+    let png = converter
+        .vegalite_to_png_info(vl_spec.clone(), Default::default(), Some(2.0), None)
+        .await
+        .unwrap();
+    let png_hash = png.request_hash.unwrap();
+    assert_eq!(
+        png_hash,
+        vegalite_request_hash(
+            &vl_spec,
+            &Default::default(),
+            serde_json::json!({
+                "format": "png",
+                "scale": 2.0,
+                "ppi": null,
+                "png_opts": format!("{:?}", PngOpts::default()),
+            })
+        )
+    );
+
+    let vl_opts = VlOpts {
+        theme: Some("dark".to_string()),
+        ..Default::default()
+    };
+    let png = converter
+        .vegalite_to_png_info(vl_spec.clone(), vl_opts, Some(2.0), None)
+        .await
+        .unwrap();
+    assert_ne!(png.request_hash.unwrap(), png_hash);
+
+    let jpeg = converter
+        .vegalite_to_jpeg_info(vl_spec.clone(), Default::default(), Some(2.0), None)
+        .await
+        .unwrap();
+    let svg = converter
+        .vegalite_to_svg_info(vl_spec, Default::default())
+        .await
+        .unwrap();
+    assert_ne!(jpeg.request_hash.unwrap(), png_hash);
+    assert_ne!(svg.request_hash.unwrap(), png_hash);
+}
+
+#[tokio::test]
+async fn test_vegalite_to_html_template() {
+    initialize();
+
+    let vl_spec = serde_json::json!({
+        "data": {"values": [{"a": "</script><script>alert(1)</script>"}]},
+        "mark": "text",
+        "encoding": {"text": {"field": "a"}}
+    });
+    let template = r##"<html><head>{{ scripts }}</head><body>
+<div id="{{ element_id }}"></div>
+<script>vegaEmbed("#{{ element_id }}", {{spec}}, {{ embed_options }});</script>
+<footer>{{ footer }}</footer>
+</body></html>"##;
+    let mut converter = VlConverter::new();
+    let html = converter
+        .vegalite_to_html_with_opts(
+            vl_spec,
+            Default::default(),
+            false,
+            Renderer::Canvas,
+            HtmlOpts {
+                template: Some(template.to_string()),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+    assert!(html.starts_with(r#"<html><head><script src="https://cdn.jsdelivr.net/npm/vega@5">"#));
+    assert!(html.contains(r#"<div id="vega-chart"></div>"#));
+    assert!(html.contains(r##"vegaEmbed("#vega-chart", {"##));
+    assert!(html.contains(r#""renderer":"canvas""#));
+    assert!(html.contains("<footer>{{ footer }}</footer>"));
+
+    // The spec can't end the script element that it's written in
+    assert_eq!(html.matches("</script>").count(), 4);
+    assert!(html.contains(r#"\u003c/script\u003e\u003cscript\u003ealert(1)"#));
+
+    let err = converter
+        .vegalite_to_html_with_opts(
+            load_vl_spec("circle_binned"),
+            Default::default(),
+            false,
+            Renderer::Svg,
+            HtmlOpts {
+                template: Some("<div>{{ spec }}</div>".to_string()),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains(
+        "missing required placeholders: {{ scripts }}, {{ element_id }}, {{ embed_options }}"
+    ));
+}
+
+#[tokio::test]
+async fn test_vegalite_to_html_allowed_base_urls() {
+    initialize();
+
+    let vl_spec = serde_json::json!({
+        "data": {"url": "https://data.example.com/cars.json"},
+        "mark": "image",
+        "encoding": {"url": {"value": "https://cdn.example.com/logo.png"}}
+    });
+    let mut converter = VlConverter::new();
+    let html_opts = |warn_only| HtmlOpts {
+        warn_only,
+        ..Default::default()
+    };
+    let vl_opts = |allowed: &[&str]| VlOpts {
+        allowed_base_urls: Some(allowed.iter().map(|s| s.to_string()).collect()),
+        ..Default::default()
+    };
+
+    // The image url isn't allowed, so the export fails
+    let err = converter
+        .vegalite_to_html_with_opts(
+            vl_spec.clone(),
+            vl_opts(&["https://data.example.com/"]),
+            false,
+            Renderer::Svg,
+            html_opts(false),
+        )
+        .await
+        .unwrap_err();
+    let message = err.to_string();
+    assert!(message.contains("allowed_base_urls doesn't allow"));
+    assert!(message.contains("https://cdn.example.com/logo.png"));
+    assert!(!message.contains("https://data.example.com/cars.json"));
+
+    // With warn_only, the document is still written
+    let html = converter
+        .vegalite_to_html_with_opts(
+            vl_spec.clone(),
+            vl_opts(&["https://data.example.com/"]),
+            false,
+            Renderer::Svg,
+            html_opts(true),
+        )
+        .await
+        .unwrap();
+    assert!(html.contains(
+        r#"<meta http-equiv="Content-Security-Policy" content="connect-src https://data.example.com; img-src https://data.example.com data:">"#
+    ));
+
+    // Both origins end up in the content security policy
+    let html = converter
+        .vegalite_to_html_with_opts(
+            vl_spec.clone(),
+            vl_opts(&["https://data.example.com/", "https://cdn.example.com/"]),
+            false,
+            Renderer::Svg,
+            html_opts(false),
+        )
+        .await
+        .unwrap();
+    assert!(html.contains(
+        "connect-src https://data.example.com https://cdn.example.com; img-src https://data.example.com https://cdn.example.com data:"
+    ));
+
+    // Without allowed_base_urls there is no policy
+    let html = converter
+        .vegalite_to_html(vl_spec, Default::default(), false, Renderer::Svg)
+        .await
+        .unwrap();
+    assert!(!html.contains("Content-Security-Policy"));
+}
+
+#[tokio::test]
+async fn test_dataset_summary() {
+    initialize();
+
+    // The filter removes every row, so the chart is empty
+    let vl_spec = serde_json::json!({
+        "data": {"values": [{"a": "A", "b": 28}, {"a": "B", "b": 55}]},
+        "transform": [{"filter": "datum.b > 100"}],
+        "mark": "bar",
+        "encoding": {
+            "x": {"field": "a", "type": "nominal"},
+            "y": {"field": "b", "type": "quantitative"}
+        }
+    });
+    let vl_opts = VlOpts {
+        collect_dataset_summary: true,
+        ..Default::default()
+    };
+
+    let mut converter = VlConverter::new();
+    converter
+        .vegalite_to_svg(vl_spec.clone(), vl_opts.clone())
+        .await
+        .unwrap();
+    let summary = converter.get_last_dataset_summary().unwrap();
+    assert!(!summary.any_marks_rendered);
+    assert_eq!(
+        summary.datasets,
+        vec![
+            DatasetInfo {
+                name: "source_0".to_string(),
+                rows: 2,
+                fields: vec!["a".to_string(), "b".to_string()],
+                feeds_mark: false,
+            },
+            DatasetInfo {
+                name: "data_0".to_string(),
+                rows: 0,
+                fields: vec![],
+                feeds_mark: true,
+            },
+        ]
+    );
+    let empty: Vec<_> = summary
+        .empty_mark_datasets()
+        .map(|d| d.name.as_str())
+        .collect();
+    assert_eq!(empty, vec!["data_0"]);
+
+    // Without the filter, the marks are drawn
+    let mut vl_spec = vl_spec;
+    vl_spec["transform"] = serde_json::json!([]);
+    converter
+        .vegalite_to_scenegraph(vl_spec, vl_opts)
+        .await
+        .unwrap();
+    let summary = converter.get_last_dataset_summary().unwrap();
+    assert!(summary.any_marks_rendered);
+    assert_eq!(summary.empty_mark_datasets().count(), 0);
+}
+
+#[tokio::test]
+async fn test_local_data_base_url() {
+    initialize();
+
+    // Spec that loads data relative to its own location
+    let dir = tempfile::tempdir().unwrap();
+    fs::create_dir(dir.path().join("data")).unwrap();
+    fs::write(
+        dir.path().join("data").join("local.csv"),
+        "category,amount\nLocal A,28\nLocal B,55\n",
+    )
+    .unwrap();
+    let spec_path = dir.path().join("chart.vl.json");
+    fs::write(&spec_path, "{}").unwrap();
+    let vl_spec = serde_json::json!({
+        "data": {"url": "data/local.csv"},
+        "mark": "bar",
+        "encoding": {
+            "x": {"field": "category", "type": "nominal"},
+            "y": {"field": "amount", "type": "quantitative"}
+        }
+    });
+    let base_url = vl_convert_rs::converter::directory_base_url(&spec_path).unwrap();
+    assert!(base_url.starts_with("file://"), "{base_url}");
+    assert!(base_url.ends_with('/'), "{base_url}");
+
+    let mut converter = VlConverter::new();
+    let svg = converter
+        .vegalite_to_svg(
+            vl_spec.clone(),
+            VlOpts {
+                base_url: Some(base_url.clone()),
+                allow_local_data: true,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+    assert!(svg.contains("Local A"), "{svg}");
+    assert!(svg.contains("Local B"), "{svg}");
+
+    // Local files aren't read unless they're allowed
+    let err = converter
+        .vegalite_to_svg(
+            vl_spec,
+            VlOpts {
+                base_url: Some(base_url),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("Local file access is not allowed"),
+        "{err}"
+    );
+}
+
+#[tokio::test]
+async fn test_preserve_int64() {
+    initialize();
+
+    // 2^53 - 1, 2^53, and 2^53 + 1, along with their negations
+    let ids: Vec<i64> = vec![
+        9007199254740991,
+        9007199254740992,
+        9007199254740993,
+        -9007199254740991,
+        -9007199254740993,
+    ];
+    let values: Vec<_> = ids
+        .iter()
+        .enumerate()
+        .map(|(i, id)| serde_json::json!({"id": id, "b": i}))
+        .collect();
+    let vl_spec = serde_json::json!({
+        "data": {"values": values},
+        "mark": "text",
+        "encoding": {
+            "y": {"field": "b", "type": "quantitative"},
+            "text": {"field": "id", "type": "nominal"}
+        }
+    });
+    let opts = VlOpts {
+        preserve_int64: true,
+        ..Default::default()
+    };
+
+    let mut converter = VlConverter::new();
+    let vg_spec = converter
+        .vegalite_to_vega(vl_spec.clone(), opts.clone())
+        .await
+        .unwrap();
+    let vg_values = vg_spec["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find_map(|data| data.get("values"))
+        .unwrap();
+    assert_eq!(vg_values, &serde_json::Value::from(values.clone()));
+
+    let frozen = converter
+        .vegalite_freeze(vl_spec.clone(), opts.clone())
+        .await
+        .unwrap();
+    assert_eq!(frozen["data"]["values"], serde_json::Value::from(values));
+
+    // Values that are carried through to the scenegraph keep all of their digits
+    let sg = converter
+        .vegalite_to_scenegraph(vl_spec.clone(), opts)
+        .await
+        .unwrap();
+    let text_items = vl_convert_rs::converter::scenegraph_text_items(&sg).unwrap();
+    for id in &ids {
+        let id = id.to_string();
+        assert!(
+            text_items.iter().any(|item| item.text == id),
+            "{text_items:?}"
+        );
+    }
+
+    // Without the option, integers beyond 2^53 are rounded by JavaScript
+    let vg_spec = converter
+        .vegalite_to_vega(vl_spec, Default::default())
+        .await
+        .unwrap();
+    let vg_values = vg_spec["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find_map(|data| data.get("values"))
+        .unwrap();
+    assert_eq!(vg_values[0]["id"].as_f64(), Some(9007199254740991.0));
+    assert_ne!(vg_values[2]["id"].as_i64(), Some(9007199254740993));
+}
+
 #[rustfmt::skip]
 mod test_svg {
     use crate::*;
@@ -721,6 +1205,7 @@ mod test_png_theme_config {
                     allowed_base_urls: None,
                     format_locale: None,
                     time_format_locale: None,
+                    ..Default::default()
                 },
                 Some(scale),
                 None
@@ -747,6 +1232,7 @@ mod test_png_theme_config {
                     allowed_base_urls: None,
                     format_locale: None,
                     time_format_locale: None,
+                    ..Default::default()
                 },
                 Some(scale),
                 None
@@ -887,3 +1373,4134 @@ mod test_jpeg {
     #[test]
     fn test_marker() {} // Help IDE detect test module
 }
+
+#[tokio::test]
+async fn test_vegalite_to_vega_and_png() {
+    initialize();
+
+    let vl_version = VlVersion::v5_8;
+    let name = "circle_binned";
+    let vl_spec = load_vl_spec(name);
+
+    // Create Vega-Lite Converter and perform combined conversion
+    let mut converter = VlConverter::new();
+    let (vg_spec, png_data) = converter
+        .vegalite_to_vega_and_png(
+            vl_spec,
+            VlOpts {
+                vl_version,
+                ..Default::default()
+            },
+            Some(2.0),
+            None,
+        )
+        .await
+        .unwrap();
+
+    // Returned Vega spec matches a standalone compilation
+    check_vg(name, vl_version, Some(vg_spec.clone()));
+
+    // Rendering the returned Vega spec separately produces the returned image
+    let vg_png_data = converter
+        .vega_to_png(vg_spec, Default::default(), Some(2.0), None)
+        .await
+        .unwrap();
+    assert_eq!(png_data, vg_png_data);
+}
+
+#[tokio::test]
+async fn test_pdf_text_halo() {
+    initialize();
+
+    let mut converter = VlConverter::new();
+    let pdf = converter
+        .vegalite_to_pdf(load_vl_spec("text_halo"), Default::default())
+        .await
+        .unwrap();
+
+    // Halo strokes are drawn with their own alpha, in a graphics state that's nested in the
+    // group alpha of the mark's opacity
+    let pdf = String::from_utf8_lossy(&pdf);
+    assert!(pdf.contains("/Type /ExtGState"));
+    assert!(pdf.contains("/CA 0.8"));
+    assert!(pdf.contains("/ca 0.6"));
+}
+
+#[tokio::test]
+async fn test_pdfa() {
+    initialize();
+
+    let mut converter = VlConverter::new();
+    let vl_spec = load_vl_spec("circle_binned");
+    let pdfa_opts = PdfOpts {
+        pdfa: true,
+        ..Default::default()
+    };
+    let pdf = converter
+        .vegalite_to_pdf_with_opts(vl_spec.clone(), Default::default(), pdfa_opts)
+        .await
+        .unwrap();
+    let text = String::from_utf8_lossy(&pdf);
+    assert!(text.contains("<pdfaid:part>2</pdfaid:part>"));
+    assert!(text.contains("<pdfaid:conformance>B</pdfaid:conformance>"));
+    assert!(text.contains("/S /GTS_PDFA1"));
+    assert!(text.contains("/ID ["));
+
+    // The page has the size of the regular PDF
+    let regular = converter
+        .vegalite_to_pdf(vl_spec.clone(), Default::default())
+        .await
+        .unwrap();
+    let media_box = |text: &str| {
+        text.lines()
+            .find(|line| line.contains("/MediaBox"))
+            .map(str::to_string)
+    };
+    assert!(media_box(&text).is_some());
+    assert_eq!(
+        media_box(&text),
+        media_box(&String::from_utf8_lossy(&regular))
+    );
+
+    // The document ID is derived from the content, so the same chart converts to the same
+    // document
+    let pdf2 = converter
+        .vegalite_to_pdf_with_opts(vl_spec, Default::default(), pdfa_opts)
+        .await
+        .unwrap();
+    assert_eq!(pdf, pdf2);
+}
+
+#[tokio::test]
+async fn test_vegalite_to_file() {
+    initialize();
+
+    let mut converter = VlConverter::new();
+    let vl_spec = load_vl_spec("circle_binned");
+    let dir = tempfile::tempdir().unwrap();
+
+    // PNG images encoded straight to the file match the in-memory conversion
+    let png_path = dir.path().join("chart.png");
+    let file_opts = ImageFileOpts {
+        scale: Some(2.0),
+        ..Default::default()
+    };
+    converter
+        .vegalite_to_file(
+            vl_spec.clone(),
+            Default::default(),
+            ExportFormat::Png,
+            file_opts,
+            &png_path,
+        )
+        .await
+        .unwrap();
+    let png = converter
+        .vegalite_to_png(vl_spec.clone(), Default::default(), Some(2.0), None)
+        .await
+        .unwrap();
+    assert_eq!(fs::read(&png_path).unwrap(), png);
+
+    let svg_path = dir.path().join("chart.svg");
+    converter
+        .vegalite_to_file(
+            vl_spec,
+            Default::default(),
+            ExportFormat::Svg,
+            Default::default(),
+            &svg_path,
+        )
+        .await
+        .unwrap();
+    assert!(fs::read_to_string(&svg_path).unwrap().starts_with("<svg"));
+
+    // No file is left behind by a failed conversion
+    let failed_path = dir.path().join("failed.png");
+    let file_opts = ImageFileOpts {
+        scale: Some(0.0),
+        ..Default::default()
+    };
+    converter
+        .vegalite_to_file(
+            load_vl_spec("circle_binned"),
+            Default::default(),
+            ExportFormat::Png,
+            file_opts,
+            &failed_path,
+        )
+        .await
+        .unwrap_err();
+    assert!(!failed_path.exists());
+
+    // A failed conversion leaves an existing file unchanged
+    let file_opts = ImageFileOpts {
+        scale: Some(0.0),
+        ..Default::default()
+    };
+    converter
+        .vegalite_to_file(
+            load_vl_spec("circle_binned"),
+            Default::default(),
+            ExportFormat::Png,
+            file_opts,
+            &png_path,
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(fs::read(&png_path).unwrap(), png);
+    assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 2);
+}
+
+#[test]
+fn test_legacy_vl_versions() {
+    use std::str::FromStr;
+    use vl_convert_rs::module_loader::import_map::VL_VERSIONS;
+
+    // Versions older than 5.14 are only included with the vl_legacy feature
+    let result = VlVersion::from_str("5.8");
+    if cfg!(feature = "vl_legacy") {
+        assert_eq!(result.unwrap(), VlVersion::v5_8);
+        assert!(VL_VERSIONS.contains(&VlVersion::v5_8));
+    } else {
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "This build does not include v5.8; install vl-convert-python-full or build with \
+            feature vl_legacy"
+        );
+        assert!(!VL_VERSIONS.contains(&VlVersion::v5_8));
+    }
+    assert_eq!(VlVersion::from_str("v5_14").unwrap(), VlVersion::v5_14);
+}
+
+fn image_path(filename: &str) -> String {
+    let root_path = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let image_path = root_path.join("tests").join("images").join(filename);
+    image_path.to_str().unwrap().to_string()
+}
+
+#[test]
+fn test_webp_image() {
+    initialize();
+
+    let svg = format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="24" height="16">
+<image href="{}" width="24" height="16"/>
+</svg>"#,
+        image_path("gradient.webp")
+    );
+    let png_data = vl_convert_rs::converter::svg_to_png(&svg, 1.0, None).unwrap();
+    let pixmap = tiny_skia::Pixmap::decode_png(&png_data).unwrap();
+    assert_eq!((pixmap.width(), pixmap.height()), (24, 16));
+
+    // Left half of the image is opaque and right half is semi-transparent
+    let left = pixmap.pixel(2, 8).unwrap();
+    let right = pixmap.pixel(20, 8).unwrap();
+    assert_eq!(left.alpha(), 255);
+    assert!(right.alpha() > 100 && right.alpha() < 160);
+}
+
+#[test]
+fn test_avif_image() {
+    initialize();
+
+    // An opaque black image, as a file and as a data url. Without the avif feature, the
+    // image is reported as unsupported and left blank
+    let avif = fs::read(image_path("black.avif")).unwrap();
+    let data_url = format!(
+        "data:image/avif;base64,{}",
+        base64::engine::general_purpose::STANDARD.encode(&avif)
+    );
+    for href in [image_path("black.avif"), data_url] {
+        let svg = format!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="32" height="24">
+<image href="{href}" width="32" height="24"/>
+</svg>"#
+        );
+        let png_data = vl_convert_rs::converter::svg_to_png(&svg, 1.0, None).unwrap();
+        let pixmap = tiny_skia::Pixmap::decode_png(&png_data).unwrap();
+        assert_eq!((pixmap.width(), pixmap.height()), (32, 24));
+        let pixel = pixmap.pixel(16, 12).unwrap();
+        if cfg!(feature = "avif") {
+            assert_eq!(
+                (pixel.red(), pixel.green(), pixel.blue(), pixel.alpha()),
+                (0, 0, 0, 255)
+            );
+        } else {
+            assert!(pixmap.pixels().iter().all(|p| p.alpha() == 0));
+        }
+    }
+}
+
+#[test]
+fn test_emoji_text() {
+    use vl_convert_rs::text::{get_emoji_font, measure_text_detailed};
+    initialize();
+
+    // CI installs a color emoji font on Linux, and Windows and macOS come with one
+    let emoji_font = get_emoji_font()
+        .unwrap()
+        .expect("No emoji font is installed. Install a color emoji font like Noto Color Emoji");
+
+    // Emoji that text fonts don't have are drawn with the emoji font, rather than as the
+    // missing glyph box of the sans-serif font
+    let text = "\u{1F600}\u{1F680}";
+    let metrics = measure_text_detailed(text, Some("sans-serif"), 40.0, None).unwrap();
+    assert!(!metrics.glyphs.is_empty());
+    for glyph in &metrics.glyphs {
+        assert_eq!(glyph.font_family, emoji_font, "{glyph:?}");
+        assert_ne!(glyph.glyph_id, 0, "{glyph:?}");
+    }
+
+    let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" width="120" height="60">
+<rect width="120" height="60" fill="white"/>
+<text x="10" y="45" font-family="sans-serif" font-size="40">&#x26A0;&#xFE0F;&#x1F600;</text>
+</svg>"#;
+    let png_data = vl_convert_rs::converter::svg_to_png(svg, 1.0, None).unwrap();
+    let pixmap = tiny_skia::Pixmap::decode_png(&png_data).unwrap();
+
+    // The emoji are drawn in color
+    assert!(pixmap
+        .pixels()
+        .iter()
+        .any(|p| p.red().abs_diff(p.blue()) > 64));
+
+    let pdf_data = vl_convert_rs::converter::svg_to_pdf(svg).unwrap();
+    assert!(pdf_data.starts_with(b"%PDF"));
+}
+
+#[test]
+fn test_measure_text_detailed() {
+    use vl_convert_rs::text::{add_svg_text_length, measure_text_detailed};
+    initialize();
+
+    // The glyph advances sum to the width that text marks are laid out with, which is the
+    // textLength that's added to SVG text
+    let layout_width = |text: &str, family: &str| -> f64 {
+        let svg = format!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg"><text font-family="{family}" font-size="12">{text}</text></svg>"#
+        );
+        let svg = add_svg_text_length(&svg).unwrap();
+        let (_, rest) = svg.split_once(r#"textLength=""#).unwrap();
+        rest.split('"').next().unwrap().parse().unwrap()
+    };
+
+    let metrics =
+        measure_text_detailed("Hello,  World", Some("Liberation Sans"), 12.0, None).unwrap();
+    let advances: f64 = metrics.glyphs.iter().map(|g| g.x_advance).sum();
+    assert!((advances - layout_width("Hello,  World", "Liberation Sans")).abs() < 0.01);
+
+    // The collapsed whitespace has no glyph
+    let clusters: Vec<_> = metrics.glyphs.iter().map(|g| g.cluster).collect();
+    assert_eq!(clusters, vec![0, 1, 2, 3, 4, 5, 6, 8, 9, 10, 11, 12]);
+    assert!(metrics
+        .glyphs
+        .iter()
+        .all(|g| g.font_family == "Liberation Sans" && g.y_offset == 0.0));
+
+    // Matter doesn't have Greek letters, which are drawn with a fallback font
+    let metrics = measure_text_detailed("A\u{3a9}B", Some("Matter"), 12.0, None).unwrap();
+    let omega: Vec<_> = metrics.glyphs.iter().filter(|g| g.cluster == 1).collect();
+    assert_eq!(omega.len(), 1);
+    assert_ne!(omega[0].font_family, "Matter");
+    assert_ne!(omega[0].glyph_id, 0);
+    let advances: f64 = metrics.glyphs.iter().map(|g| g.x_advance).sum();
+    assert!((advances - layout_width("A\u{3a9}B", "Matter")).abs() < 0.01);
+}
+
+#[test]
+fn test_exif_orientation() {
+    use vl_convert_rs::image_loading::set_ignore_exif_orientation;
+    initialize();
+
+    let render = |filename: &str| {
+        let svg = format!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="24" height="16">
+<image href="{}" width="24" height="16"/>
+</svg>"#,
+            image_path(filename)
+        );
+        let png_data = vl_convert_rs::converter::svg_to_png(&svg, 1.0, None).unwrap();
+        tiny_skia::Pixmap::decode_png(&png_data).unwrap()
+    };
+    let max_diff = |a: &tiny_skia::Pixmap, b: &tiny_skia::Pixmap| {
+        a.data()
+            .iter()
+            .zip(b.data())
+            .map(|(a, b)| a.abs_diff(*b))
+            .max()
+            .unwrap()
+    };
+
+    // The JPEG fixtures store the reference image rotated, with the EXIF orientation that
+    // restores it
+    let expected = render("exif_reference.png");
+    for orientation in [3, 6, 8] {
+        let filename = format!("exif_orientation_{orientation}.jpg");
+        let diff = max_diff(&render(&filename), &expected);
+        assert!(diff <= 16, "orientation {orientation}: max diff {diff}");
+
+        // Ignoring the orientation draws the pixels as they're stored
+        set_ignore_exif_orientation(true);
+        let diff = max_diff(&render(&filename), &expected);
+        set_ignore_exif_orientation(false);
+        assert!(diff > 100, "orientation {orientation}: max diff {diff}");
+    }
+}
+
+#[test]
+fn test_sniff_image_mime_type() {
+    use vl_convert_rs::image_loading::sniff_image_mime_type;
+
+    let webp = fs::read(image_path("gradient.webp")).unwrap();
+    assert_eq!(sniff_image_mime_type(&webp), Some("image/webp"));
+
+    let avif = fs::read(image_path("black.avif")).unwrap();
+    assert_eq!(sniff_image_mime_type(&avif), Some("image/avif"));
+
+    let avif_header = b"\x00\x00\x00\x1cftypavif\x00\x00\x00\x00";
+    assert_eq!(sniff_image_mime_type(avif_header), Some("image/avif"));
+    assert_eq!(sniff_image_mime_type(b"BM\x00\x00"), Some("image/bmp"));
+    assert_eq!(sniff_image_mime_type(b"<svg"), None);
+}
+
+#[test]
+fn test_unsupported_image() {
+    initialize();
+
+    // A BMP data url is reported as unsupported and leaves the image blank
+    let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" width="8" height="8">
+<image href="data:image/bmp;base64,Qk0AAAAA" width="8" height="8"/>
+</svg>"#;
+    let png_data = vl_convert_rs::converter::svg_to_png(svg, 1.0, None).unwrap();
+    let pixmap = tiny_skia::Pixmap::decode_png(&png_data).unwrap();
+    assert!(pixmap.pixels().iter().all(|p| p.alpha() == 0));
+}
+
+#[rstest]
+#[case::zero_scale(0.0, None, "Image scale must be a finite number greater than 0")]
+#[case::nan_scale(f32::NAN, None, "Image scale must be a finite number greater than 0")]
+#[case::negative_ppi(1.0, Some(-72.0), "Image ppi must be a finite number greater than 0")]
+#[case::empty_image(1e-9, None, "produces an image of 0x0 pixels")]
+fn test_invalid_png_size(#[case] scale: f32, #[case] ppi: Option<f32>, #[case] message: &str) {
+    initialize();
+
+    let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" width="20" height="10"/>"#;
+    let err = vl_convert_rs::converter::svg_to_png(svg, scale, ppi).unwrap_err();
+    assert!(err.to_string().contains(message), "{err}");
+}
+
+#[rstest]
+#[case(0)]
+#[case(101)]
+fn test_invalid_jpeg_quality(#[case] quality: u8) {
+    initialize();
+
+    let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" width="20" height="10"/>"#;
+    let err = vl_convert_rs::converter::svg_to_jpeg(svg, 1.0, Some(quality)).unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("JPEG quality parameter must be between 1 and 100"),
+        "{err}"
+    );
+}
+
+fn world_map_spec(projection_type: &str) -> serde_json::Value {
+    serde_json::json!({
+        "width": 500,
+        "height": 300,
+        "data": {
+            "url": "https://raw.githubusercontent.com/vega/vega-datasets/main/data/world-110m.json",
+            "format": {"type": "topojson", "feature": "countries"}
+        },
+        "projection": {"type": projection_type},
+        "mark": {"type": "geoshape", "fill": "lightgray", "stroke": "white"}
+    })
+}
+
+#[rstest]
+#[case("robinson")]
+#[case("winkel3")]
+#[case("interruptedHomolosine")]
+#[tokio::test]
+async fn test_extended_projection(#[case] projection_type: &str) {
+    initialize();
+
+    let mut converter = VlConverter::new();
+    let svg = converter
+        .vegalite_to_svg(world_map_spec(projection_type), Default::default())
+        .await
+        .unwrap();
+
+    // Each country is drawn as a path with a non-empty outline
+    let re = regex::Regex::new(r#"<path [^>]*d="M[^"]+""#).unwrap();
+    assert!(re.find_iter(&svg).count() > 100, "{svg}");
+}
+
+#[tokio::test]
+async fn test_unknown_projection() {
+    initialize();
+
+    let mut converter = VlConverter::new();
+    let err = converter
+        .vegalite_to_svg(world_map_spec("notAProjection"), Default::default())
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(
+        err.contains(r#"Unrecognized projection type: "notAProjection""#),
+        "{err}"
+    );
+    assert!(err.contains("Available projection types: albers,"), "{err}");
+    assert!(err.contains("robinson"), "{err}");
+}
+
+#[tokio::test]
+async fn test_vegalite_get_text_items() {
+    initialize();
+
+    let vl_spec = serde_json::json!({
+        "title": "Bar Chart",
+        "data": {"values": [
+            {"a": "A", "b": 28}, {"a": "B", "b": 55}, {"a": "C", "b": 43}
+        ]},
+        "mark": "bar",
+        "encoding": {
+            "x": {"field": "a", "type": "nominal", "axis": {"labelAngle": 0}},
+            "y": {"field": "b", "type": "quantitative"}
+        }
+    });
+
+    let mut converter = VlConverter::new();
+    let text_items = converter
+        .vegalite_get_text_items(vl_spec, Default::default())
+        .await
+        .unwrap();
+
+    // x-axis tick labels are reported left-to-right
+    let x_labels: Vec<_> = text_items
+        .iter()
+        .filter(|item| item.role == "axis-label" && ["A", "B", "C"].contains(&item.text.as_str()))
+        .collect();
+    assert_eq!(
+        x_labels
+            .iter()
+            .map(|item| item.text.as_str())
+            .collect::<Vec<_>>(),
+        vec!["A", "B", "C"]
+    );
+    assert!(x_labels.windows(2).all(|pair| pair[0].x < pair[1].x));
+
+    let titles: Vec<_> = text_items
+        .iter()
+        .filter(|item| item.role == "title")
+        .collect();
+    assert_eq!(titles.len(), 1);
+    assert_eq!(titles[0].text, "Bar Chart");
+    assert!(text_items
+        .iter()
+        .any(|item| item.role == "axis-title" && item.text == "b"));
+}
+
+#[rstest]
+#[case(1)]
+#[case(7)]
+#[tokio::test]
+async fn test_vega_get_render_stats(#[case] n: usize) {
+    initialize();
+
+    let values: Vec<_> = (0..n)
+        .map(|i| serde_json::json!({"a": i.to_string(), "b": i + 1}))
+        .collect();
+    let vl_spec = serde_json::json!({
+        "data": {"values": values},
+        "mark": "bar",
+        "encoding": {
+            "x": {"field": "a", "type": "nominal"},
+            "y": {"field": "b", "type": "quantitative"}
+        }
+    });
+
+    let mut converter = VlConverter::new();
+    let vg_spec = converter
+        .vegalite_to_vega(vl_spec, Default::default())
+        .await
+        .unwrap();
+    let stats = converter
+        .vega_get_render_stats(vg_spec.clone(), Default::default())
+        .await
+        .unwrap();
+    assert_eq!(stats.item_counts_by_marktype["rect"], n as u64);
+    assert!(stats.group_count >= 1);
+    assert!(stats.text_item_count > 0);
+    assert!(stats.total_scenegraph_nodes > n as u64);
+
+    // Renders of the same spec have the same stats
+    let rerendered = converter
+        .vega_get_render_stats(vg_spec, Default::default())
+        .await
+        .unwrap();
+    assert_eq!(rerendered, stats);
+}
+
+#[tokio::test]
+async fn test_vegalite_facets_to_pngs() {
+    initialize();
+
+    let values: Vec<_> = ["r1", "r2"]
+        .iter()
+        .flat_map(|row| {
+            ["c1", "c2", "c3"].iter().enumerate().map(move |(i, column)| {
+                serde_json::json!({"row": row, "column": column, "x": i, "y": i * 2})
+            })
+        })
+        .collect();
+    let vl_spec = serde_json::json!({
+        "data": {"values": values},
+        "mark": "point",
+        "width": 100,
+        "height": 80,
+        "encoding": {
+            "row": {"field": "row", "type": "nominal"},
+            "column": {"field": "column", "type": "nominal"},
+            "x": {"field": "x", "type": "quantitative"},
+            "y": {"field": "y", "type": "quantitative"}
+        }
+    });
+
+    let mut converter = VlConverter::new();
+    let images = converter
+        .vegalite_facets_to_pngs(vl_spec.clone(), Default::default(), Some(2.0), None)
+        .await
+        .unwrap();
+    let cells: Vec<_> = images
+        .iter()
+        .map(|image| (image.row_value.clone(), image.column_value.clone()))
+        .collect();
+    let cell = |row: &str, column: &str| {
+        (
+            Some(serde_json::json!(row)),
+            Some(serde_json::json!(column)),
+        )
+    };
+    assert_eq!(
+        cells,
+        vec![
+            cell("r1", "c1"),
+            cell("r1", "c2"),
+            cell("r1", "c3"),
+            cell("r2", "c1"),
+            cell("r2", "c2"),
+            cell("r2", "c3"),
+        ]
+    );
+
+    // Every cell is cropped to the scaled size of the cell
+    for image in &images {
+        let reader = png::Decoder::new(image.data.as_slice())
+            .read_info()
+            .unwrap();
+        assert_eq!((reader.info().width, reader.info().height), (200, 160));
+    }
+
+    // With guides, the first cell includes the row header and y axis on its left and the
+    // column header above it, and the last cell includes the x axis below it
+    let images = converter
+        .vegalite_facets_to_pngs_with_opts(
+            vl_spec,
+            Default::default(),
+            Some(2.0),
+            None,
+            FacetOpts {
+                include_guides: true,
+            },
+        )
+        .await
+        .unwrap();
+    let sizes: Vec<_> = images
+        .iter()
+        .map(|image| {
+            let reader = png::Decoder::new(image.data.as_slice())
+                .read_info()
+                .unwrap();
+            (reader.info().width, reader.info().height)
+        })
+        .collect();
+    assert!(sizes[0].0 > 200 && sizes[0].1 > 160, "{sizes:?}");
+    assert!(sizes[5].1 > 160, "{sizes:?}");
+    // The row header is next to the first cell of each row only
+    assert!(sizes[2].0 < sizes[0].0, "{sizes:?}");
+
+    // Specs without facets have no cells to export
+    let unfaceted = serde_json::json!({
+        "data": {"values": [{"x": 1}]},
+        "mark": "point",
+        "encoding": {"x": {"field": "x", "type": "quantitative"}}
+    });
+    let err = converter
+        .vegalite_facets_to_pngs(unfaceted, Default::default(), None, None)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("Spec has no facet cells"));
+}
+
+#[tokio::test]
+async fn test_vega_theme() {
+    initialize();
+
+    let vg_spec = serde_json::json!({
+        "$schema": "https://vega.github.io/schema/vega/v5.json",
+        "width": 100,
+        "height": 100,
+        "data": [{"name": "table", "values": [{"a": "A", "b": 28}, {"a": "B", "b": 55}]}],
+        "scales": [
+            {"name": "x", "type": "band", "domain": {"data": "table", "field": "a"}, "range": "width"},
+            {"name": "y", "domain": {"data": "table", "field": "b"}, "range": "height"}
+        ],
+        "axes": [{"orient": "bottom", "scale": "x"}, {"orient": "left", "scale": "y"}],
+        "marks": [{
+            "type": "rect",
+            "from": {"data": "table"},
+            "encode": {"enter": {
+                "x": {"scale": "x", "field": "a"},
+                "width": {"scale": "x", "band": 1},
+                "y": {"scale": "y", "field": "b"},
+                "y2": {"scale": "y", "value": 0}
+            }}
+        }]
+    });
+
+    let mut converter = VlConverter::new();
+
+    // Dark theme background
+    let svg = converter
+        .vega_to_svg(
+            vg_spec.clone(),
+            vl_convert_rs::converter::VgOpts {
+                theme: Some("dark".to_string()),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+    assert!(svg.contains(r##"fill="#333""##), "{svg}");
+
+    // User config overrides theme config
+    let svg = converter
+        .vega_to_svg(
+            vg_spec.clone(),
+            vl_convert_rs::converter::VgOpts {
+                theme: Some("dark".to_string()),
+                config: Some(serde_json::json!({"background": "#abcdef"})),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+    assert!(svg.contains(r##"fill="#abcdef""##), "{svg}");
+    assert!(!svg.contains(r##"fill="#333""##), "{svg}");
+
+    // No theme stays light
+    let svg = converter
+        .vega_to_svg(vg_spec, Default::default())
+        .await
+        .unwrap();
+    assert!(!svg.contains(r##"fill="#333""##), "{svg}");
+}
+
+#[tokio::test]
+async fn test_resource_stats() {
+    initialize();
+    let _stats_guard = RESOURCE_STATS_LOCK.lock().await;
+
+    let values: Vec<Value> = (0..500)
+        .map(|i| serde_json::json!({"x": i, "y": (i * 37) % 101, "c": i % 5}))
+        .collect();
+    let vl_spec = serde_json::json!({
+        "$schema": "https://vega.github.io/schema/vega-lite/v5.json",
+        "width": 400,
+        "height": 300,
+        "data": {"values": values},
+        "mark": "point",
+        "encoding": {
+            "x": {"field": "x", "type": "quantitative"},
+            "y": {"field": "y", "type": "quantitative"},
+            "color": {"field": "c", "type": "nominal"}
+        }
+    });
+
+    let mut converter = VlConverter::new();
+    let png_data = converter
+        .vegalite_to_png(
+            vl_spec,
+            VlOpts {
+                collect_resource_stats: true,
+                ..Default::default()
+            },
+            Some(2.0),
+            None,
+        )
+        .await
+        .unwrap();
+    assert!(!png_data.is_empty());
+
+    let stats = vl_convert_rs::resource_stats::get_last_resource_stats()
+        .unwrap()
+        .expect("resource stats should be collected");
+    assert!(stats.v8_heap_used_bytes > 0, "{stats:?}");
+    assert!(
+        stats.v8_heap_total_bytes >= stats.v8_heap_used_bytes,
+        "{stats:?}"
+    );
+    assert!(stats.svg_bytes > 10_000, "{stats:?}");
+
+    // The 2x scaled PNG is rasterized from an RGBA pixmap of at least 800x600 pixels
+    assert!(stats.pixmap_bytes >= 800 * 600 * 4, "{stats:?}");
+}
+
+#[tokio::test]
+async fn test_vegalite_export() {
+    initialize();
+    let _stats_guard = RESOURCE_STATS_LOCK.lock().await;
+
+    let vl_spec = serde_json::json!({
+        "$schema": "https://vega.github.io/schema/vega-lite/v5.json",
+        "data": {"values": [
+            {"a": "A", "b": 28}, {"a": "B", "b": 55}, {"a": "C", "b": 43}
+        ]},
+        "mark": "bar",
+        "encoding": {
+            "x": {"field": "a", "type": "nominal"},
+            "y": {"field": "b", "type": "quantitative"},
+            "color": {"field": "a", "type": "nominal"}
+        }
+    });
+    let vl_opts = VlOpts {
+        collect_resource_stats: true,
+        ..Default::default()
+    };
+
+    let mut converter = VlConverter::new();
+    let result = converter
+        .vegalite_export(
+            vl_spec.clone(),
+            vl_opts.clone(),
+            ExportRequest::from_formats(&[
+                ExportFormat::Svg,
+                ExportFormat::Png,
+                ExportFormat::Jpeg,
+                ExportFormat::Pdf,
+            ]),
+        )
+        .await
+        .unwrap();
+    assert!(result.svg.unwrap().starts_with("<svg"));
+    assert!(result.png.unwrap().starts_with(b"\x89PNG"));
+    assert!(result.jpeg.unwrap().starts_with(&[0xFF, 0xD8]));
+    assert!(result.pdf.unwrap().starts_with(b"%PDF"));
+    let stats = vl_convert_rs::resource_stats::get_last_resource_stats()
+        .unwrap()
+        .expect("resource stats should be collected");
+    assert!(stats.pixmap_bytes > 0, "{stats:?}");
+
+    // Nothing is rasterized when only vector formats are requested
+    let result = converter
+        .vegalite_export(
+            vl_spec,
+            vl_opts,
+            ExportRequest::from_formats(&[ExportFormat::Svg, ExportFormat::Pdf]),
+        )
+        .await
+        .unwrap();
+    assert!(result.svg.is_some() && result.pdf.is_some());
+    assert!(result.png.is_none() && result.jpeg.is_none());
+    let stats = vl_convert_rs::resource_stats::get_last_resource_stats()
+        .unwrap()
+        .expect("resource stats should be collected");
+    assert_eq!(stats.pixmap_bytes, 0, "{stats:?}");
+}
+
+#[tokio::test]
+async fn test_vegalite_export_data() {
+    initialize();
+
+    let mut converter = VlConverter::new();
+    let result = converter
+        .vegalite_export(
+            load_vl_spec("aggregated_totals"),
+            Default::default(),
+            ExportRequest {
+                png: Some(Default::default()),
+                export_data: true,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+    assert!(result.png.unwrap().starts_with(b"\x89PNG"));
+
+    let datasets = result.datasets.expect("datasets should be exported");
+    let names = datasets.iter().map(|d| d.name.as_str()).collect::<Vec<_>>();
+    assert_eq!(names, vec!["source_0", "data_0"]);
+
+    // The aggregated rows that the bars are drawn from, with dates written as ISO 8601
+    assert_eq!(
+        datasets[1].to_csv(),
+        "category,date,total,total_start,total_end\n\
+         A,2024-01-15T00:00:00.000Z,30,0,30\n\
+         B,2024-06-15T00:00:00.000Z,55,0,55\n"
+    );
+
+    // Datasets are only exported when requested
+    let result = converter
+        .vegalite_export(
+            load_vl_spec("aggregated_totals"),
+            Default::default(),
+            ExportRequest::from_formats(&[ExportFormat::Svg]),
+        )
+        .await
+        .unwrap();
+    assert!(result.datasets.is_none());
+}
+
+#[tokio::test]
+async fn test_vegalite_text_style_options() {
+    initialize();
+
+    let mut converter = VlConverter::new();
+    let svg = converter
+        .vegalite_to_svg(
+            load_vl_spec("circle_binned"),
+            VlOpts {
+                font: Some("Courier New".to_string()),
+                text_color: Some("#ff0000".to_string()),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+    assert!(svg.contains(r#"font-family="Courier New""#));
+    assert!(!svg.contains(r#"font-family="sans-serif""#));
+    assert!(svg.contains(r##"fill="#ff0000""##));
+
+    // Font sizes are scaled, and config takes precedence over the options
+    let vg_spec = converter
+        .vegalite_to_vega(
+            load_vl_spec("circle_binned"),
+            VlOpts {
+                config: Some(serde_json::json!({"axis": {"labelFontSize": 9}})),
+                font_size_scale: Some(2.0),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(vg_spec["config"]["axis"]["labelFontSize"], 9);
+    assert_eq!(vg_spec["config"]["axis"]["titleFontSize"], 22.0);
+}
+
+#[tokio::test]
+async fn test_vegalite_config_overrides() {
+    initialize();
+
+    let vl_spec = serde_json::json!({
+        "data": {"values": [{"a": "A", "b": 28}, {"a": "B", "b": 55}]},
+        "mark": "bar",
+        "encoding": {
+            "x": {"field": "a", "type": "nominal"},
+            "y": {"field": "b", "type": "quantitative"},
+            "color": {"field": "a", "type": "nominal"}
+        }
+    });
+
+    let mut converter = VlConverter::new();
+    let svg = converter
+        .vegalite_to_svg(
+            vl_spec,
+            VlOpts {
+                theme: Some("dark".to_string()),
+                config: Some(serde_json::json!({"axis": {"labelFontSize": 12}})),
+                config_overrides: Some(vec![
+                    ("axis.labelFontSize".to_string(), serde_json::json!(14)),
+                    (
+                        "range.category".to_string(),
+                        serde_json::json!(["#111111", "#222222"]),
+                    ),
+                    ("background".to_string(), serde_json::json!("#abcdef")),
+                    ("background".to_string(), serde_json::json!("#123456")),
+                ]),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+    // Overrides take precedence over the config and the theme, and later ones over earlier
+    assert!(svg.contains(r#"font-size="14px""#), "{svg}");
+    assert!(!svg.contains(r#"font-size="12px""#), "{svg}");
+    assert!(svg.contains(r##"fill="#111111""##), "{svg}");
+    assert!(svg.contains(r##"fill="#222222""##), "{svg}");
+    assert!(svg.contains(r##"fill="#123456""##), "{svg}");
+    assert!(!svg.contains(r##"fill="#333""##), "{svg}");
+}
+
+#[tokio::test]
+async fn test_vegalite_direction_rtl() {
+    initialize();
+
+    let vl_spec = serde_json::json!({
+        "data": {"values": [{"a": "A", "b": 28}, {"a": "B", "b": 55}]},
+        "mark": "bar",
+        "width": 200,
+        "height": 100,
+        "encoding": {
+            "x": {"field": "a", "type": "nominal"},
+            "y": {"field": "b", "type": "quantitative"}
+        }
+    });
+
+    // Transform of the group that the y axis is drawn in
+    fn y_axis_transform(svg: &str) -> &str {
+        let axis = &svg[svg.find(r#"aria-label="Y-axis"#).unwrap()..];
+        let transform = &axis[axis.find(r#"transform=""#).unwrap() + 11..];
+        &transform[..transform.find('"').unwrap()]
+    }
+
+    let mut converter = VlConverter::new();
+    let svg = converter
+        .vegalite_to_svg(vl_spec.clone(), Default::default())
+        .await
+        .unwrap();
+    assert_eq!(y_axis_transform(&svg), "translate(0.5,0.5)");
+
+    let svg = converter
+        .vegalite_to_svg(
+            vl_spec.clone(),
+            VlOpts {
+                direction: Some(Direction::Rtl),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(y_axis_transform(&svg), "translate(200.5,0.5)");
+
+    // An orient set in the spec takes precedence
+    let mut vl_spec = vl_spec;
+    vl_spec["encoding"]["y"]["axis"] = serde_json::json!({"orient": "left"});
+    let svg = converter
+        .vegalite_to_svg(
+            vl_spec,
+            VlOpts {
+                direction: Some(Direction::Rtl),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(y_axis_transform(&svg), "translate(0.5,0.5)");
+}
+
+#[tokio::test]
+async fn test_generic_font_mapping() {
+    initialize();
+
+    let mapped_opts = || VlOpts {
+        generic_font_mapping: Some(HashMap::from([(
+            "sans-serif".to_string(),
+            "Caveat".to_string(),
+        )])),
+        ..Default::default()
+    };
+    let font_opts = || VlOpts {
+        font: Some("Caveat".to_string()),
+        ..Default::default()
+    };
+
+    // Text is measured and drawn with the mapped font, exactly as if the chart used it
+    let mut converter = VlConverter::new();
+    let svg = converter
+        .vegalite_to_svg(load_vl_spec("circle_binned"), mapped_opts())
+        .await
+        .unwrap();
+    assert!(svg.contains(r#"font-family="Caveat""#), "{svg}");
+    assert!(!svg.contains(r#"font-family="sans-serif""#), "{svg}");
+    let expected_svg = converter
+        .vegalite_to_svg(load_vl_spec("circle_binned"), font_opts())
+        .await
+        .unwrap();
+    assert_eq!(svg, expected_svg);
+
+    let png = converter
+        .vegalite_to_png(load_vl_spec("circle_binned"), mapped_opts(), None, None)
+        .await
+        .unwrap();
+    let expected_png = converter
+        .vegalite_to_png(load_vl_spec("circle_binned"), font_opts(), None, None)
+        .await
+        .unwrap();
+    let default_png = converter
+        .vegalite_to_png(
+            load_vl_spec("circle_binned"),
+            Default::default(),
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(png, expected_png);
+    assert_ne!(png, default_png);
+
+    let pdf = converter
+        .vegalite_to_pdf(load_vl_spec("circle_binned"), mapped_opts())
+        .await
+        .unwrap();
+    assert!(String::from_utf8_lossy(&pdf).contains("Caveat"));
+
+    // Only generic families can be mapped
+    let err = converter
+        .vegalite_to_svg(
+            load_vl_spec("circle_binned"),
+            VlOpts {
+                generic_font_mapping: Some(HashMap::from([(
+                    "Helvetica".to_string(),
+                    "Caveat".to_string(),
+                )])),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("Unsupported generic font family \"Helvetica\""));
+}
+
+#[tokio::test]
+async fn test_max_views() {
+    initialize();
+
+    // Repeated fields, each faceted into 20 rows
+    let repeat_facet_spec = |num_fields: usize| {
+        let fields: Vec<_> = (0..num_fields).map(|i| format!("f{i}")).collect();
+        let values: Vec<_> = (0..40)
+            .map(|i| serde_json::json!({"row": i % 20, "f0": i}))
+            .collect();
+        serde_json::json!({
+            "data": {"values": values},
+            "repeat": fields,
+            "spec": {
+                "facet": {"row": {"field": "row"}},
+                "spec": {
+                    "mark": "point",
+                    "encoding": {"x": {"field": {"repeat": "repeat"}, "type": "quantitative"}}
+                }
+            }
+        })
+    };
+
+    let mut converter = VlConverter::new();
+    converter
+        .vegalite_to_vega(repeat_facet_spec(25), Default::default())
+        .await
+        .unwrap();
+
+    let err = converter
+        .vegalite_to_svg(repeat_facet_spec(26), Default::default())
+        .await
+        .unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("estimated 520 views, which exceeds the max_views limit of 500"),
+        "{err}"
+    );
+}
+
+#[tokio::test]
+async fn test_dataflow_error_location() {
+    initialize();
+
+    let mut converter = VlConverter::new();
+    let err = converter
+        .vegalite_to_svg(load_vl_spec("broken_calculate"), Default::default())
+        .await
+        .unwrap_err();
+    assert!(
+        err.to_string()
+            .starts_with("Vega dataflow error in transform[2] (calculate) of layer[1]: "),
+        "{err}"
+    );
+    let dataflow_error = err.downcast_ref::<DataflowError>().unwrap();
+    assert_eq!(dataflow_error.phase, DataflowPhase::Dataflow);
+    assert_eq!(dataflow_error.operator.as_deref(), Some("Formula"));
+
+    // Vega specs are located by their datasets
+    let vg_spec = converter
+        .vegalite_to_vega(load_vl_spec("broken_calculate"), Default::default())
+        .await
+        .unwrap();
+    let err = converter
+        .vega_to_svg(vg_spec, Default::default())
+        .await
+        .unwrap_err();
+    let dataflow_error = err.downcast_ref::<DataflowError>().unwrap();
+    assert_eq!(dataflow_error.transform.as_deref(), Some("formula"));
+    assert!(dataflow_error
+        .spec_path
+        .as_deref()
+        .is_some_and(|path| path.ends_with(".transform[2]")));
+
+    // Errors of a previous conversion aren't reported for the next one
+    converter
+        .vegalite_to_svg(load_vl_spec("circle_binned"), Default::default())
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_get_final_vega_spec() {
+    initialize();
+
+    let vl_spec = serde_json::json!({
+        "$schema": "https://vega.github.io/schema/vega-lite/v5.json",
+        "data": {"values": [
+            {"a": "2024-01-15", "b": 1250.5},
+            {"a": "2024-06-15", "b": 3200.25},
+            {"a": "2024-11-15", "b": 2100.75}
+        ]},
+        "mark": "bar",
+        "encoding": {
+            "x": {"field": "a", "type": "temporal", "timeUnit": "month"},
+            "y": {"field": "b", "type": "quantitative", "axis": {"format": ",.2f"}}
+        }
+    });
+    let vl_opts = VlOpts {
+        theme: Some("dark".to_string()),
+        config: Some(serde_json::json!({"axis": {"labelFontSize": 14}})),
+        format_locale: Some(FormatLocale::Name("de-DE".to_string())),
+        time_format_locale: Some(TimeFormatLocale::Name("fr-FR".to_string())),
+        ..Default::default()
+    };
+
+    let mut converter = VlConverter::new();
+    let final_spec = converter
+        .get_final_vega_spec(vl_spec.clone(), vl_opts.clone())
+        .await
+        .unwrap();
+    assert_eq!(final_spec["config"]["locale"]["number"]["decimal"], ",");
+    assert!(final_spec["config"]["locale"]["time"].is_object());
+
+    // Rendering the final spec with default options matches the direct conversion
+    let expected_png = converter
+        .vegalite_to_png(vl_spec, vl_opts, None, None)
+        .await
+        .unwrap();
+    let png = converter
+        .vega_to_png(final_spec.clone(), Default::default(), None, None)
+        .await
+        .unwrap();
+    assert_eq!(png, expected_png);
+
+    // The final spec of a Vega spec with theme and config renders the same way
+    let vg_opts = vl_convert_rs::converter::VgOpts {
+        theme: Some("dark".to_string()),
+        config: Some(serde_json::json!({"background": "#abcdef"})),
+        format_locale: Some(FormatLocale::Name("de-DE".to_string())),
+        ..Default::default()
+    };
+    let final_vg_spec = converter
+        .get_final_vega_spec_vg(final_spec.clone(), vg_opts.clone())
+        .await
+        .unwrap();
+    assert_eq!(final_vg_spec["config"]["background"], "#abcdef");
+    let expected_png = converter
+        .vega_to_png(final_spec, vg_opts, None, None)
+        .await
+        .unwrap();
+    let png = converter
+        .vega_to_png(final_vg_spec, Default::default(), None, None)
+        .await
+        .unwrap();
+    assert_eq!(png, expected_png);
+}
+
+#[tokio::test]
+async fn test_svg_text_length() {
+    initialize();
+
+    let vl_spec = serde_json::json!({
+        "$schema": "https://vega.github.io/schema/vega-lite/v5.json",
+        "title": {"text": ["Multi-line", "title"]},
+        "data": {"values": [
+            {"a": "A", "b": 28}, {"a": "B", "b": 55}, {"a": "C & D", "b": 43}
+        ]},
+        "mark": "bar",
+        "encoding": {
+            "x": {"field": "a", "type": "nominal"},
+            "y": {"field": "b", "type": "quantitative"}
+        }
+    });
+
+    let mut converter = VlConverter::new();
+    let svg = converter
+        .vegalite_to_svg_with_opts(
+            vl_spec,
+            Default::default(),
+            vl_convert_rs::converter::SvgOpts {
+                text_length: true,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+    let doc = usvg::roxmltree::Document::parse(&svg).unwrap();
+    let mut num_annotated = 0;
+    for node in doc.descendants().filter(|n| n.has_tag_name("text")) {
+        let tspans: Vec<_> = node
+            .children()
+            .filter(|n| n.has_tag_name("tspan"))
+            .collect();
+        let annotated = if tspans.is_empty() {
+            vec![node]
+        } else {
+            assert!(node.attribute("textLength").is_none());
+            tspans
+        };
+        for element in annotated {
+            let text_length: f64 = element
+                .attribute("textLength")
+                .unwrap_or_else(|| panic!("Missing textLength: {element:?}"))
+                .parse()
+                .unwrap();
+            assert!(text_length.is_finite() && text_length > 0.0);
+            assert_eq!(element.attribute("lengthAdjust"), Some("spacingAndGlyphs"));
+            num_annotated += 1;
+        }
+    }
+    // 3 x-axis labels, y-axis labels, 2 axis titles, and 2 title lines
+    assert!(num_annotated > 10, "{svg}");
+}
+
+#[tokio::test]
+async fn test_svg_native_tooltips() {
+    initialize();
+
+    let vl_spec = serde_json::json!({
+        "$schema": "https://vega.github.io/schema/vega-lite/v5.json",
+        "data": {"values": [
+            {"a": "A", "b": 28}, {"a": "B", "b": 55}, {"a": "C & D", "b": 43}
+        ]},
+        "layer": [
+            {
+                "mark": "bar",
+                "encoding": {
+                    "x": {"field": "a", "type": "nominal"},
+                    "y": {"field": "b", "type": "quantitative"},
+                    "tooltip": [
+                        {"field": "a", "type": "nominal"},
+                        {"field": "b", "type": "quantitative"}
+                    ]
+                }
+            },
+            {
+                "mark": {"type": "text", "dy": -5},
+                "encoding": {
+                    "x": {"field": "a", "type": "nominal"},
+                    "y": {"field": "b", "type": "quantitative"},
+                    "text": {"field": "b", "type": "quantitative"}
+                }
+            }
+        ]
+    });
+
+    let mut converter = VlConverter::new();
+    let svg = converter
+        .vegalite_to_svg_with_opts(
+            vl_spec.clone(),
+            Default::default(),
+            vl_convert_rs::converter::SvgOpts {
+                native_tooltips: true,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+    let doc = usvg::roxmltree::Document::parse(&svg).unwrap();
+    let titles: Vec<_> = doc
+        .descendants()
+        .filter(|n| n.has_tag_name("title"))
+        .map(|n| (n.parent_element().unwrap(), n.text().unwrap_or_default()))
+        .collect();
+    let bars: Vec<_> = titles
+        .iter()
+        .filter(|(parent, _)| parent.attribute("aria-roledescription") == Some("bar"))
+        .collect();
+    assert_eq!(titles.len(), 3, "{svg}");
+    assert_eq!(bars.len(), 3, "{svg}");
+    let texts: Vec<_> = titles.iter().map(|(_, text)| *text).collect();
+    assert_eq!(texts, vec!["a: A\nb: 28", "a: B\nb: 55", "a: C & D\nb: 43"]);
+
+    // Text marks without tooltips keep their content and get no title
+    for node in doc.descendants().filter(|n| n.has_tag_name("text")) {
+        assert!(!node.children().any(|n| n.has_tag_name("title")));
+    }
+
+    // Tooltips aren't written unless requested
+    let svg = converter
+        .vegalite_to_svg(vl_spec, Default::default())
+        .await
+        .unwrap();
+    assert!(!svg.contains("<title>"), "{svg}");
+}
+
+#[rstest]
+#[case(None)]
+#[case(Some(2))]
+#[tokio::test]
+async fn test_svg_minify(#[case] precision: Option<u8>) {
+    initialize();
+
+    let vl_spec = load_vl_spec("stacked_bar_h");
+    let mut converter = VlConverter::new();
+    let svg = converter
+        .vegalite_to_svg(vl_spec.clone(), Default::default())
+        .await
+        .unwrap();
+    let minified = converter
+        .vegalite_to_svg_with_opts(
+            vl_spec,
+            Default::default(),
+            vl_convert_rs::converter::SvgOpts {
+                minify: true,
+                precision,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+    assert!(minified.len() < svg.len());
+    for removed in [
+        "aria-",
+        " role=",
+        " class=",
+        "pointer-events",
+        "translate(0,0)",
+    ] {
+        assert!(!minified.contains(removed), "{removed}: {minified}");
+    }
+
+    // Minified SVG draws the same image
+    let expected = to_dssim(&vl_convert_rs::converter::svg_to_png(&svg, 1.0, None).unwrap());
+    let img = to_dssim(&vl_convert_rs::converter::svg_to_png(&minified, 1.0, None).unwrap());
+    let (diff, _) = Dssim::new().compare(&expected, img);
+    assert!(diff < 0.00011, "DSSIM diff {diff}");
+}
+
+#[tokio::test]
+async fn test_register_expression_fn() {
+    initialize();
+
+    let mut converter = VlConverter::new();
+    converter
+        .register_expression_fn("usd", |args| {
+            let Some(value) = args.first().and_then(Value::as_f64) else {
+                vl_convert_rs::anyhow::bail!("usd expects a number, got {args:?}")
+            };
+            Ok(Value::String(format!("USD {value:.2}")))
+        })
+        .await
+        .unwrap();
+
+    let vl_spec = serde_json::json!({
+        "data": {"values": [{"a": "A", "b": 28}, {"a": "B", "b": 55}]},
+        "mark": "bar",
+        "encoding": {
+            "x": {"field": "a", "type": "nominal"},
+            "y": {
+                "field": "b",
+                "type": "quantitative",
+                "axis": {"labelExpr": "usd(datum.value)"}
+            }
+        }
+    });
+
+    // Registered functions are available to every conversion
+    for _ in 0..2 {
+        let svg = converter
+            .vegalite_to_svg(vl_spec.clone(), Default::default())
+            .await
+            .unwrap();
+        assert!(svg.contains(">USD 0.00<"), "{svg}");
+        assert!(svg.contains(">USD 50.00<"), "{svg}");
+    }
+
+    // Errors returned by the function fail the conversion. The x-axis labels are strings
+    let mut vl_spec = vl_spec;
+    vl_spec["encoding"]["x"]["axis"] = serde_json::json!({"labelExpr": "usd(datum.label)"});
+    assert!(converter
+        .vegalite_to_svg(vl_spec, Default::default())
+        .await
+        .is_err());
+
+    let err = converter
+        .register_expression_fn("not a name", |_| Ok(Value::Null))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("Invalid expression function name"));
+}
+
+#[tokio::test]
+async fn test_health_check() {
+    initialize();
+
+    let mut converter = VlConverter::new();
+    let report = converter.health_check().await.unwrap();
+    assert_eq!(report.vega_version, "5.30.0");
+    assert_eq!(report.vl_default_version, VlVersion::default().to_semver());
+    assert_eq!(report.conversions_completed, 0);
+    assert_eq!(report.conversions_failed, 0);
+    assert_eq!(report.last_conversion_at, None);
+
+    converter
+        .vegalite_to_vega(load_vl_spec("circle_binned"), Default::default())
+        .await
+        .unwrap();
+    assert!(converter
+        .vega_to_svg(serde_json::json!({"marks": 23}), Default::default())
+        .await
+        .is_err());
+
+    // Counts are shared by clones of the converter
+    let mut cloned = converter.clone();
+    let report = cloned.health_check().await.unwrap();
+    assert_eq!(report.conversions_completed, 1);
+    assert_eq!(report.conversions_failed, 1);
+    assert!(report.last_conversion_at.is_some());
+    assert!(report.worker_uptime > std::time::Duration::ZERO);
+    assert_eq!(report.worker_restarts, 0);
+}
+
+#[test]
+fn test_health_check_without_tokio() {
+    use futures::executor::block_on;
+    initialize();
+
+    // The timeout of the check doesn't need the caller to run on a tokio runtime
+    let mut converter = VlConverter::new();
+    let report = block_on(converter.health_check()).unwrap();
+    assert_eq!(report.vega_version, "5.30.0");
+}
+
+#[tokio::test]
+async fn test_warm_versions() {
+    initialize();
+
+    let mut converter = VlConverter::new();
+    let evaluations = converter
+        .health_check()
+        .await
+        .unwrap()
+        .vl_module_evaluations;
+    converter
+        .warm_versions(&[VlVersion::v5_8, VlVersion::v5_21])
+        .await
+        .unwrap();
+    let report = converter.health_check().await.unwrap();
+    assert_eq!(report.vl_module_evaluations, evaluations + 2);
+    assert_eq!(report.conversions_completed, 0);
+
+    // Warm versions aren't evaluated again, by warming or by conversions that use them
+    converter.warm_versions(&[VlVersion::v5_8]).await.unwrap();
+    converter
+        .vegalite_to_vega(
+            load_vl_spec("circle_binned"),
+            VlOpts {
+                vl_version: VlVersion::v5_8,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+    let report = converter.health_check().await.unwrap();
+    assert_eq!(report.vl_module_evaluations, evaluations + 2);
+
+    // A version that wasn't warmed is evaluated by the first conversion that uses it
+    converter
+        .vegalite_to_vega(
+            load_vl_spec("circle_binned"),
+            VlOpts {
+                vl_version: VlVersion::v5_14,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+    let report = converter.health_check().await.unwrap();
+    assert_eq!(report.vl_module_evaluations, evaluations + 3);
+}
+
+#[tokio::test]
+async fn test_worker_restart() {
+    initialize();
+
+    let mut converter = VlConverter::new();
+    let terminator = converter.clone();
+    let terminated = AtomicBool::new(false);
+    converter
+        .register_expression_fn("shout", move |args| {
+            // Terminate the worker's runtime in the middle of the first conversion
+            if !terminated.swap(true, Ordering::Relaxed) {
+                terminator.terminate_worker()?;
+            }
+            let text = args.first().and_then(Value::as_str).unwrap_or_default();
+            Ok(Value::String(text.to_uppercase()))
+        })
+        .await
+        .unwrap();
+    let vl_spec = serde_json::json!({
+        "data": {"values": [{"a": "first", "b": 28}, {"a": "second", "b": 55}]},
+        "mark": "bar",
+        "encoding": {
+            "x": {"field": "a", "type": "nominal", "axis": {"labelExpr": "shout(datum.label)"}},
+            "y": {"field": "b", "type": "quantitative"}
+        }
+    });
+
+    // The conversion during which the worker's runtime is terminated is retried on a new
+    // worker, which has the registered expression functions
+    let svg = converter
+        .vegalite_to_svg(vl_spec.clone(), Default::default())
+        .await
+        .unwrap();
+    assert!(svg.contains(">FIRST<"), "{svg}");
+
+    let mut cloned = converter.clone();
+    let svg = cloned
+        .vegalite_to_svg(vl_spec, Default::default())
+        .await
+        .unwrap();
+    assert!(svg.contains(">SECOND<"), "{svg}");
+
+    let report = converter.health_check().await.unwrap();
+    assert_eq!(report.worker_restarts, 1);
+}
+
+#[tokio::test]
+async fn test_interaction_script() {
+    initialize();
+
+    let vl_spec = serde_json::json!({
+        "$schema": "https://vega.github.io/schema/vega-lite/v5.json",
+        "data": {"values": [
+            {"year": 2006, "v": 3}, {"year": 2007, "v": 7}, {"year": 2008, "v": 4},
+            {"year": 2009, "v": 9}, {"year": 2010, "v": 5}, {"year": 2011, "v": 8},
+            {"year": 2012, "v": 2}, {"year": 2013, "v": 6}
+        ]},
+        "params": [{
+            "name": "brush",
+            "select": {"type": "interval", "encodings": ["x"]}
+        }],
+        "mark": "point",
+        "encoding": {
+            "x": {"field": "year", "type": "quantitative", "scale": {"zero": false}},
+            "y": {"field": "v", "type": "quantitative"},
+            "color": {
+                "condition": {"param": "brush", "value": "red", "empty": false},
+                "value": "gray"
+            }
+        }
+    });
+
+    let mut converter = VlConverter::new();
+    let mut num_selected = Vec::new();
+    for extent in [serde_json::json!([0, 50]), serde_json::json!([100, 200])] {
+        let svg = converter
+            .vegalite_to_svg(
+                vl_spec.clone(),
+                VlOpts {
+                    interaction_script: Some(vec![InteractionStep::Signal {
+                        signal: "brush_x".to_string(),
+                        value: extent,
+                    }]),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        num_selected.push(svg.matches(r#"stroke="red""#).count());
+    }
+
+    assert!(num_selected.iter().all(|n| *n > 0), "{num_selected:?}");
+    assert_ne!(num_selected[0], num_selected[1]);
+}
+
+mod test_bundle_snippet {
+    use deno_graph::{DefaultModuleParser, MediaType, ModuleParser, ParseOptions};
+    use vl_convert_rs::html::{
+        bundle_vega_snippet, bundle_vega_snippet_with_language, get_script_tags, get_vega_bundle,
+        get_vegaembed_bundle, SnippetLanguage,
+    };
+    use vl_convert_rs::module_loader::import_map::{VEGA_EMBED_VERSION, VEGA_VERSION};
+    use vl_convert_rs::VlVersion;
+
+    /// Check that bundled output is a syntactically valid ES module
+    fn check_bundle_parses(bundle: &str) {
+        let specifier = deno_graph::ModuleSpecifier::parse("file:///bundle.js").unwrap();
+        let parsed = DefaultModuleParser
+            .parse_module(ParseOptions {
+                specifier: &specifier,
+                source: bundle.into(),
+                media_type: MediaType::JavaScript,
+                scope_analysis: false,
+            })
+            .unwrap();
+        assert!(
+            parsed.diagnostics().is_empty(),
+            "{:?}",
+            parsed.diagnostics()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_top_level_await() {
+        let snippet = r#"
+const response = await fetch("https://example.com/data.json");
+window.vegaData = await response.json();
+window.vegaEmbed = vegaEmbed;
+"#;
+        let bundle = bundle_vega_snippet(snippet, VlVersion::v5_21)
+            .await
+            .unwrap();
+        assert!(bundle.contains("await fetch("), "{bundle}");
+        check_bundle_parses(&bundle);
+    }
+
+    #[tokio::test]
+    async fn test_typescript() {
+        let snippet = r#"
+const width: number = 400;
+window.vegaEmbed = vegaEmbed;
+window.chartWidth = width;
+"#;
+        let bundle = bundle_vega_snippet_with_language(
+            snippet,
+            VlVersion::v5_21,
+            SnippetLanguage::TypeScript,
+        )
+        .await
+        .unwrap();
+        assert!(!bundle.contains("width: number"), "{bundle}");
+        check_bundle_parses(&bundle);
+
+        // Type annotations are a syntax error in JavaScript snippets
+        let err = bundle_vega_snippet(snippet, VlVersion::v5_21)
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("vl-convert-snippet.js:2:"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_library_bundles() {
+        let bundle = get_vega_bundle(VlVersion::v5_20).await.unwrap();
+        check_bundle_parses(&bundle);
+        assert!(bundle.contains(VEGA_VERSION));
+        assert!(bundle.contains("5.20.1"));
+        assert!(!bundle.contains(VEGA_EMBED_VERSION));
+
+        let bundle = get_vegaembed_bundle(VlVersion::v5_20).await.unwrap();
+        check_bundle_parses(&bundle);
+        assert!(bundle.contains(VEGA_VERSION));
+        assert!(bundle.contains(VEGA_EMBED_VERSION));
+        assert!(bundle.contains("window.lodashDebounce"));
+    }
+
+    #[tokio::test]
+    async fn test_script_tags() {
+        let scripts = get_script_tags(VlVersion::v5_20, false).await.unwrap();
+        assert!(scripts
+            .head
+            .contains(&format!("cdn.jsdelivr.net/npm/vega@{VEGA_VERSION}")));
+        assert!(scripts
+            .head
+            .contains("cdn.jsdelivr.net/npm/vega-lite@5.20.1"));
+        assert!(scripts.head.contains(&format!(
+            "cdn.jsdelivr.net/npm/vega-embed@{VEGA_EMBED_VERSION}"
+        )));
+        assert!(scripts.body.contains("lodash.debounce@4.0.8"));
+
+        let scripts = get_script_tags(VlVersion::v5_20, true).await.unwrap();
+        assert!(scripts.head.starts_with("<script"));
+        assert!(scripts.head.contains(VEGA_EMBED_VERSION));
+        assert!(!scripts.head.contains("cdn.jsdelivr.net"));
+        assert_eq!(scripts.body, "");
+    }
+}
+
+mod test_fetch_retry {
+    use crate::*;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+    use vl_convert_rs::converter::VgOpts;
+    use vl_convert_rs::image_loading::{with_fetch_retry, FetchRetry};
+
+    /// Serve `body` from a local server that responds with the given status codes before
+    /// succeeding. Returns the server's base url and a counter of the requests received.
+    pub(crate) fn serve_after_failures(
+        failures: Vec<u16>,
+        content_type: &'static str,
+        body: Vec<u8>,
+    ) -> (String, Arc<AtomicUsize>) {
+        let (url, requests, _) = serve_after_failures_recording(failures, content_type, body);
+        (url, requests)
+    }
+
+    /// Like [`serve_after_failures`], but also returns the requests received, each with its
+    /// headers and body.
+    pub(crate) fn serve_after_failures_recording(
+        failures: Vec<u16>,
+        content_type: &'static str,
+        body: Vec<u8>,
+    ) -> (String, Arc<AtomicUsize>, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(AtomicUsize::new(0));
+        let counter = requests.clone();
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let recorder = received.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else { break };
+
+                // Read the request headers, then the body that follows them
+                let mut request = Vec::new();
+                let mut buf = [0u8; 1024];
+                let header_end = loop {
+                    if let Some(pos) = request.windows(4).position(|w| w == b"\r\n\r\n") {
+                        break pos + 4;
+                    }
+                    match stream.read(&mut buf) {
+                        Ok(0) | Err(_) => break request.len(),
+                        Ok(n) => request.extend_from_slice(&buf[..n]),
+                    }
+                };
+                let headers = String::from_utf8_lossy(&request[..header_end]).to_lowercase();
+                let content_length: usize = headers
+                    .lines()
+                    .find_map(|line| line.strip_prefix("content-length:"))
+                    .and_then(|len| len.trim().parse().ok())
+                    .unwrap_or(0);
+                while request.len() < header_end + content_length {
+                    match stream.read(&mut buf) {
+                        Ok(0) | Err(_) => break,
+                        Ok(n) => request.extend_from_slice(&buf[..n]),
+                    }
+                }
+                recorder
+                    .lock()
+                    .unwrap()
+                    .push(String::from_utf8_lossy(&request).to_string());
+
+                let attempt = counter.fetch_add(1, Ordering::SeqCst);
+                let (status, content_type, body) = match failures.get(attempt) {
+                    Some(status) => (*status, "text/plain", b"failure".to_vec()),
+                    None => (200, content_type, body.clone()),
+                };
+                let header = format!(
+                    "HTTP/1.1 {status} Status\r\nContent-Type: {content_type}\r\n\
+                    Content-Length: {}\r\nAccess-Control-Allow-Origin: *\r\n\
+                    Connection: close\r\n\r\n",
+                    body.len()
+                );
+                stream.write_all(header.as_bytes()).ok();
+                stream.write_all(&body).ok();
+            }
+        });
+        (url, requests)
+    }
+
+    fn data_spec(url: &str) -> serde_json::Value {
+        serde_json::json!({
+            "$schema": "https://vega.github.io/schema/vega-lite/v5.json",
+            "data": {"url": format!("{url}/data.json")},
+            "mark": "bar",
+            "encoding": {
+                "x": {"field": "a", "type": "nominal"},
+                "y": {"field": "b", "type": "quantitative"}
+            }
+        })
+    }
+
+    fn data_body() -> Vec<u8> {
+        br#"[{"a": "A", "b": 28}, {"a": "B", "b": 55}, {"a": "C", "b": 43}]"#.to_vec()
+    }
+
+    #[tokio::test]
+    async fn test_data_retried_after_server_errors() {
+        initialize();
+        let (url, requests) = serve_after_failures(vec![503, 500], "application/json", data_body());
+
+        let mut converter = VlConverter::new();
+        let sg = converter
+            .vegalite_to_scenegraph(
+                data_spec(&url),
+                VlOpts {
+                    fetch_retries: Some(3),
+                    fetch_retry_backoff_ms: Some(10),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(requests.load(Ordering::SeqCst), 3);
+
+        // All three bars are rendered from the fetched data
+        let text_items = vl_convert_rs::converter::scenegraph_text_items(&sg).unwrap();
+        for label in ["A", "B", "C"] {
+            assert!(
+                text_items.iter().any(|item| item.text == label),
+                "{text_items:?}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_data_retries_exhausted() {
+        initialize();
+        let (url, requests) =
+            serve_after_failures(vec![503, 503, 503], "application/json", data_body());
+
+        let mut converter = VlConverter::new();
+        let err = converter
+            .vegalite_to_svg(
+                data_spec(&url),
+                VlOpts {
+                    fetch_retries: Some(2),
+                    fetch_retry_backoff_ms: Some(10),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap_err()
+            .to_string();
+        assert_eq!(requests.load(Ordering::SeqCst), 3);
+        assert!(err.contains("after 3 attempts"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_client_errors_not_retried() {
+        initialize();
+        let (url, requests) = serve_after_failures(vec![404], "application/json", data_body());
+
+        // The failed request is reported as a warning and the chart renders without data
+        let mut converter = VlConverter::new();
+        let vg_spec = converter
+            .vegalite_to_vega(data_spec(&url), Default::default())
+            .await
+            .unwrap();
+        converter
+            .vega_to_svg(
+                vg_spec,
+                VgOpts {
+                    fetch_retries: Some(3),
+                    fetch_retry_backoff_ms: Some(10),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_image_retried_after_server_errors() {
+        initialize();
+        let webp = fs::read(image_path("gradient.webp")).unwrap();
+        let (url, requests) = serve_after_failures(vec![502, 503], "image/webp", webp);
+
+        let svg = format!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="24" height="16">
+<image href="{url}/gradient.webp" width="24" height="16"/>
+</svg>"#
+        );
+        let fetch_retry = FetchRetry::from_opts(Some(2), Some(10));
+        let png_data = with_fetch_retry(fetch_retry, || {
+            vl_convert_rs::converter::svg_to_png(&svg, 1.0, None)
+        })
+        .unwrap();
+        assert_eq!(requests.load(Ordering::SeqCst), 3);
+
+        let pixmap = tiny_skia::Pixmap::decode_png(&png_data).unwrap();
+        assert_eq!(pixmap.pixel(2, 8).unwrap().alpha(), 255);
+    }
+}
+
+mod test_short_url {
+    use crate::test_fetch_retry::serve_after_failures_recording;
+    use std::net::TcpListener;
+    use std::time::Duration;
+    use vl_convert_rs::converter::{
+        vega_to_short_url, vega_to_url, vegalite_to_short_url, vegalite_to_url, ShortenOptions,
+    };
+
+    /// Serve a mock URL shortener that responds with the given status codes before succeeding
+    /// with `body`. Returns the shortener's endpoint, and a function that returns the
+    /// Authorization header and JSON body of each request the shortener received
+    fn serve_shortener(
+        failures: Vec<u16>,
+        body: &'static str,
+    ) -> (
+        String,
+        impl Fn() -> Vec<(Option<String>, serde_json::Value)>,
+    ) {
+        let (url, _, requests) =
+            serve_after_failures_recording(failures, "application/json", body.as_bytes().to_vec());
+        let received = move || {
+            requests
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|request| {
+                    let (headers, body) = request.split_once("\r\n\r\n").unwrap_or_default();
+                    let authorization = headers.lines().find_map(|line| {
+                        let (key, value) = line.split_once(':')?;
+                        key.eq_ignore_ascii_case("authorization")
+                            .then(|| value.trim().to_string())
+                    });
+                    (
+                        authorization,
+                        serde_json::from_str(body).unwrap_or_default(),
+                    )
+                })
+                .collect()
+        };
+        (format!("{url}/shorten"), received)
+    }
+
+    fn vl_spec() -> serde_json::Value {
+        serde_json::json!({
+            "data": {"values": [{"a": "A", "b": 28}, {"a": "B", "b": 55}]},
+            "mark": "bar",
+            "encoding": {
+                "x": {"field": "a", "type": "nominal"},
+                "y": {"field": "b", "type": "quantitative"}
+            }
+        })
+    }
+
+    #[tokio::test]
+    async fn test_short_url() {
+        let (endpoint, requests) = serve_shortener(vec![], r#"{"link": "https://sho.rt/abc"}"#);
+        let opts = ShortenOptions {
+            auth_header: Some("Bearer secret".to_string()),
+            fullscreen: true,
+            response_field: "link".to_string(),
+            ..ShortenOptions::new(endpoint)
+        };
+        let url = vegalite_to_short_url(&vl_spec(), &opts).await.unwrap();
+        assert_eq!(url, "https://sho.rt/abc");
+
+        // The shortener receives the compressed editor URL
+        assert_eq!(
+            requests(),
+            vec![(
+                Some("Bearer secret".to_string()),
+                serde_json::json!({"url": vegalite_to_url(&vl_spec(), true).unwrap()})
+            )]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_short_url_failure() {
+        let (endpoint, _) = serve_shortener(vec![503], r#"{"link": "https://sho.rt/abc"}"#);
+        let opts = ShortenOptions::new(endpoint);
+        let err = vegalite_to_short_url(&vl_spec(), &opts)
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("503"), "{err}");
+
+        // A response without the short URL field fails too
+        let (endpoint, _) = serve_shortener(vec![], r#"{"link": "https://sho.rt/abc"}"#);
+        let err = vegalite_to_short_url(&vl_spec(), &ShortenOptions::new(endpoint))
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("\"short_url\""), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_short_url_fallback() {
+        // Nothing listens at the endpoint once the listener is dropped
+        let endpoint = {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            format!("http://{}/shorten", listener.local_addr().unwrap())
+        };
+        let vg_spec = serde_json::json!({"marks": []});
+        let opts = ShortenOptions {
+            fallback: true,
+            timeout: Duration::from_secs(2),
+            ..ShortenOptions::new(endpoint)
+        };
+        let url = vega_to_short_url(&vg_spec, &opts).await.unwrap();
+        assert_eq!(url, vega_to_url(&vg_spec, false).unwrap());
+    }
+}
+
+mod test_freeze {
+    use crate::test_fetch_retry::serve_after_failures;
+    use crate::*;
+
+    fn layered_url_spec(csv_url: &str, json_url: &str) -> serde_json::Value {
+        serde_json::json!({
+            "$schema": "https://vega.github.io/schema/vega-lite/v5.json",
+            "layer": [
+                {
+                    "data": {"url": format!("{csv_url}/values.csv")},
+                    "mark": "bar",
+                    "encoding": {
+                        "x": {"field": "k", "type": "nominal"},
+                        "y": {"field": "v", "type": "quantitative"}
+                    }
+                },
+                {
+                    "data": {"url": format!("{csv_url}/values.csv")},
+                    "transform": [{
+                        "lookup": "k",
+                        "from": {
+                            "data": {
+                                "url": format!("{json_url}/weights"),
+                                "format": {"property": "rows"}
+                            },
+                            "key": "k",
+                            "fields": ["w"]
+                        }
+                    }],
+                    "mark": {"type": "text", "dy": -8},
+                    "encoding": {
+                        "x": {"field": "k", "type": "nominal"},
+                        "y": {"field": "v", "type": "quantitative"},
+                        "text": {"field": "w", "type": "quantitative"}
+                    }
+                }
+            ]
+        })
+    }
+
+    #[tokio::test]
+    async fn test_frozen_spec_renders_identically_offline() {
+        initialize();
+        let (csv_url, _) =
+            serve_after_failures(vec![], "text/csv", b"k,v\nA,3\nB,7\nC,4\n".to_vec());
+        let (json_url, _) = serve_after_failures(
+            vec![],
+            "application/json",
+            br#"{"rows": [{"k": "A", "w": 1}, {"k": "B", "w": 5}, {"k": "C", "w": 2}]}"#.to_vec(),
+        );
+        let vl_spec = layered_url_spec(&csv_url, &json_url);
+        let vl_opts = VlOpts {
+            vl_version: VlVersion::v5_21,
+            theme: Some("dark".to_string()),
+            ..Default::default()
+        };
+
+        let mut converter = VlConverter::new();
+        let frozen = converter
+            .vegalite_freeze(vl_spec.clone(), vl_opts.clone())
+            .await
+            .unwrap();
+
+        let frozen_str = serde_json::to_string(&frozen).unwrap();
+        assert!(!frozen_str.contains("\"url\""), "{frozen_str}");
+        assert_eq!(
+            frozen["$schema"],
+            "https://vega.github.io/schema/vega-lite/v5.21.0.json"
+        );
+        assert_eq!(
+            frozen["usermeta"]["vlConvert"]["version"],
+            env!("CARGO_PKG_VERSION")
+        );
+        assert!(frozen["usermeta"]["vlConvert"]["timestamp"].is_string());
+        assert_eq!(frozen["config"]["background"], "#333");
+
+        // No external data can be loaded when no base urls are allowed
+        let offline_opts = VlOpts {
+            vl_version: VlVersion::v5_21,
+            allowed_base_urls: Some(vec![]),
+            ..Default::default()
+        };
+        let svg = converter
+            .vegalite_to_svg(vl_spec.clone(), vl_opts)
+            .await
+            .unwrap();
+        let frozen_svg = converter
+            .vegalite_to_svg(frozen, offline_opts.clone())
+            .await
+            .unwrap();
+        assert_eq!(svg, frozen_svg);
+
+        assert!(converter
+            .vegalite_to_svg(vl_spec, offline_opts)
+            .await
+            .is_err());
+    }
+}
+
+mod test_lenient {
+    use crate::test_fetch_retry::serve_after_failures;
+    use crate::*;
+    use vl_convert_rs::render_warnings::RenderWarning;
+
+    #[tokio::test]
+    async fn test_missing_image_skipped() {
+        initialize();
+        let vl_spec = load_vl_spec("missing_local_image");
+
+        let mut converter = VlConverter::new();
+        let err = converter
+            .vegalite_to_png(vl_spec.clone(), Default::default(), None, None)
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("Local file access is not allowed"), "{err}");
+
+        // The chart is drawn without the image
+        let lenient_opts = VlOpts {
+            lenient: true,
+            ..Default::default()
+        };
+        let png = converter
+            .vegalite_to_png_info(vl_spec.clone(), lenient_opts.clone(), None, None)
+            .await
+            .unwrap();
+        assert!(png.data.starts_with(b"\x89PNG"));
+
+        let warnings = png.warnings;
+        assert_eq!(warnings.len(), 1, "{warnings:?}");
+        assert_eq!(warnings[0].mark.as_deref(), Some("layer_1_marks"));
+        assert!(warnings[0]
+            .message
+            .contains("Local file access is not allowed: /vl-convert/missing.png"));
+
+        // Warnings aren't repeated when the same SVG is rasterized to several formats
+        let result = converter
+            .vegalite_export(
+                vl_spec,
+                lenient_opts,
+                ExportRequest {
+                    png: Some(Default::default()),
+                    pdf: true,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(result.warnings, warnings);
+    }
+
+    #[tokio::test]
+    async fn test_failed_image_fetch_reported() {
+        initialize();
+        let (url, _) = serve_after_failures(vec![404], "image/png", vec![]);
+        let vl_spec = serde_json::json!({
+            "data": {"values": [{"a": "A", "b": 28, "icon": format!("{url}/icon.png")}]},
+            "mark": {"type": "image", "width": 20, "height": 20},
+            "encoding": {
+                "x": {"field": "a", "type": "nominal"},
+                "y": {"field": "b", "type": "quantitative"},
+                "url": {"field": "icon"}
+            }
+        });
+
+        // Images that fail to load when the SVG is rasterized are named by the mark groups
+        // that they're drawn in
+        let mut converter = VlConverter::new();
+        let png = converter
+            .vegalite_to_png_info(
+                vl_spec,
+                VlOpts {
+                    lenient: true,
+                    ..Default::default()
+                },
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            png.warnings,
+            vec![RenderWarning {
+                mark: Some("marks".to_string()),
+                message: format!("Failed to load image: {url}/icon.png"),
+            }]
+        );
+    }
+}
+
+mod test_row_limit {
+    use crate::test_fetch_retry::serve_after_failures;
+    use crate::*;
+    use vl_convert_rs::converter::scenegraph_text_items;
+    use vl_convert_rs::row_limit::RowLimitStrategy;
+
+    /// CSV with a column of the row numbers 0 to rows - 1
+    fn csv_rows(rows: usize) -> String {
+        let mut csv = "i\n".to_string();
+        for i in 0..rows {
+            csv.push_str(&format!("{i}\n"));
+        }
+        csv
+    }
+
+    /// Chart that draws the number of rows of its data and the largest row number, as
+    /// "<count>:<max>"
+    fn count_spec(mut data: serde_json::Value) -> serde_json::Value {
+        data["format"]["parse"] = serde_json::json!({"i": "number"});
+        serde_json::json!({
+            "data": data,
+            "transform": [
+                {"aggregate": [{"op": "count", "as": "n"}, {"op": "max", "field": "i", "as": "m"}]},
+                {"calculate": "datum.n + ':' + datum.m", "as": "label"}
+            ],
+            "mark": "text",
+            "encoding": {"text": {"field": "label", "type": "nominal"}}
+        })
+    }
+
+    fn row_limit_opts(strategy: RowLimitStrategy) -> VlOpts {
+        VlOpts {
+            max_rows: Some(1000),
+            row_limit_strategy: strategy,
+            ..Default::default()
+        }
+    }
+
+    async fn label(
+        converter: &mut VlConverter,
+        vl_spec: &serde_json::Value,
+        vl_opts: VlOpts,
+    ) -> String {
+        let sg = converter
+            .vegalite_to_scenegraph(vl_spec.clone(), vl_opts)
+            .await
+            .unwrap();
+        let text_items = scenegraph_text_items(&sg).unwrap();
+        assert_eq!(text_items.len(), 1, "{text_items:?}");
+        text_items[0].text.clone()
+    }
+
+    #[tokio::test]
+    async fn test_row_limit_error() {
+        initialize();
+        let (url, _) = serve_after_failures(vec![], "text/csv", csv_rows(10_000).into_bytes());
+        let vl_spec = count_spec(serde_json::json!({"url": format!("{url}/data.csv")}));
+
+        let mut converter = VlConverter::new();
+        let err = converter
+            .vegalite_to_svg(vl_spec.clone(), row_limit_opts(RowLimitStrategy::Error))
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("10000 rows, more than max_rows (1000)"),
+            "{err}"
+        );
+
+        // Datasets within the limit are rendered
+        let vl_opts = VlOpts {
+            max_rows: Some(10_000),
+            ..Default::default()
+        };
+        assert_eq!(label(&mut converter, &vl_spec, vl_opts).await, "10000:9999");
+    }
+
+    #[tokio::test]
+    async fn test_row_limit_head() {
+        initialize();
+        let (url, _) = serve_after_failures(vec![], "text/csv", csv_rows(10_000).into_bytes());
+        let vl_spec = count_spec(serde_json::json!({"url": format!("{url}/data.csv")}));
+
+        let mut converter = VlConverter::new();
+        let vl_opts = row_limit_opts(RowLimitStrategy::Head);
+        assert_eq!(
+            label(&mut converter, &vl_spec, vl_opts.clone()).await,
+            "1000:999"
+        );
+        let warnings = converter
+            .vegalite_to_svg_info(vl_spec, vl_opts)
+            .await
+            .unwrap()
+            .warnings;
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(
+            warnings[0].message,
+            "A dataset with 10000 rows was truncated to 1000 rows (first rows)"
+        );
+
+        // Inline data is limited too
+        let vl_spec = count_spec(serde_json::json!({
+            "values": csv_rows(10_000),
+            "format": {"type": "csv"}
+        }));
+        let vl_opts = row_limit_opts(RowLimitStrategy::Head);
+        assert_eq!(label(&mut converter, &vl_spec, vl_opts).await, "1000:999");
+    }
+
+    #[tokio::test]
+    async fn test_row_limit_sample() {
+        initialize();
+        let (url, _) = serve_after_failures(vec![], "text/csv", csv_rows(10_000).into_bytes());
+        let vl_spec = count_spec(serde_json::json!({"url": format!("{url}/data.csv")}));
+
+        // The sample is drawn from all of the rows, and is the same for each conversion
+        let mut converter = VlConverter::new();
+        let vl_opts = row_limit_opts(RowLimitStrategy::Sample(42));
+        let sampled = label(&mut converter, &vl_spec, vl_opts.clone()).await;
+        let (count, max) = sampled.split_once(':').unwrap();
+        assert_eq!(count, "1000");
+        assert!(max.parse::<u32>().unwrap() > 999, "{sampled}");
+        assert_eq!(label(&mut converter, &vl_spec, vl_opts).await, sampled);
+    }
+
+    #[tokio::test]
+    async fn test_annotate_truncation() {
+        initialize();
+        let vl_spec = count_spec(serde_json::json!({
+            "values": csv_rows(10_000),
+            "format": {"type": "csv"}
+        }));
+
+        let mut converter = VlConverter::new();
+        let vl_opts = VlOpts {
+            annotate_truncation: true,
+            ..row_limit_opts(RowLimitStrategy::Head)
+        };
+        let svg = converter
+            .vegalite_to_svg(vl_spec.clone(), vl_opts)
+            .await
+            .unwrap();
+        assert!(svg.contains("Data truncated to 1000 rows"), "{svg}");
+
+        // Charts with datasets within the limit aren't annotated
+        let vl_opts = VlOpts {
+            max_rows: Some(10_000),
+            annotate_truncation: true,
+            ..Default::default()
+        };
+        let svg = converter.vegalite_to_svg(vl_spec, vl_opts).await.unwrap();
+        assert!(!svg.contains("Data truncated"), "{svg}");
+    }
+}
+
+mod test_log_level {
+    use crate::*;
+    use vl_convert_rs::converter::VgOpts;
+    use vl_convert_rs::log_level::LogLevel;
+    use vl_convert_rs::render_warnings::RenderWarning;
+
+    /// Chart with an empty dataset whose extents Vega warns are infinite
+    fn infinite_extent_spec() -> serde_json::Value {
+        serde_json::json!({
+            "width": 100,
+            "height": 100,
+            "data": [{
+                "name": "table",
+                "values": [],
+                "transform": [
+                    {"type": "extent", "field": "v", "signal": "v_extent"},
+                    {"type": "extent", "field": "w", "signal": "w_extent"}
+                ]
+            }],
+            "marks": [{
+                "type": "rect",
+                "encode": {"enter": {
+                    "width": {"value": 100},
+                    "height": {"value": 100},
+                    "fill": {"value": "steelblue"}
+                }}
+            }]
+        })
+    }
+
+    async fn warning_messages(log_level: LogLevel, warning_filters: &[&str]) -> Vec<String> {
+        let vg_opts = VgOpts {
+            log_level: Some(log_level),
+            warning_filters: (!warning_filters.is_empty())
+                .then(|| warning_filters.iter().map(|f| f.to_string()).collect()),
+            ..Default::default()
+        };
+        let mut converter = VlConverter::new();
+        let svg = converter
+            .vega_to_svg_info(infinite_extent_spec(), vg_opts)
+            .await
+            .unwrap();
+        svg.warnings
+            .into_iter()
+            .map(|RenderWarning { message, .. }| message)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_log_level_warnings() {
+        initialize();
+
+        let messages = warning_messages(LogLevel::Warn, &[]).await;
+        assert_eq!(
+            messages,
+            vec![
+                "Infinite extent for field \"v\": [Infinity, -Infinity]",
+                "Infinite extent for field \"w\": [Infinity, -Infinity]",
+            ]
+        );
+
+        // Warnings are below the error level
+        assert!(warning_messages(LogLevel::Error, &[]).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_warning_filters() {
+        initialize();
+
+        let messages = warning_messages(LogLevel::Warn, &["Infinite extent"]).await;
+        assert_eq!(
+            messages,
+            vec!["Suppressed 2 warnings matching \"Infinite extent\""]
+        );
+
+        let messages = warning_messages(LogLevel::Warn, &["/field \"w\"/"]).await;
+        assert_eq!(
+            messages,
+            vec![
+                "Infinite extent for field \"v\": [Infinity, -Infinity]",
+                "Suppressed 1 warning matching /field \"w\"/",
+            ]
+        );
+
+        // Filters that match nothing leave the warnings as they are
+        let messages = warning_messages(LogLevel::Warn, &["Unrelated"]).await;
+        assert_eq!(messages.len(), 2, "{messages:?}");
+    }
+}
+
+mod test_output_format {
+    use crate::*;
+    use vl_convert_rs::anyhow::Error;
+    use vl_convert_rs::converter::VlOpts;
+    use vl_convert_rs::output_format::{OutputFormat, RenderContext};
+    use vl_convert_rs::tiny_skia::Pixmap;
+
+    /// Output format that writes the size of the rasterized chart as "<width>x<height>"
+    struct SizeFormat;
+
+    impl OutputFormat for SizeFormat {
+        fn id(&self) -> &str {
+            "size"
+        }
+
+        fn from_svg(&self, svg: &str, ctx: &RenderContext) -> Result<Vec<u8>, Error> {
+            self.from_pixmap(ctx.rasterize(svg)?, ctx)
+        }
+
+        fn from_pixmap(&self, pixmap: Pixmap, _ctx: &RenderContext) -> Result<Vec<u8>, Error> {
+            Ok(format!("{}x{}", pixmap.width(), pixmap.height()).into_bytes())
+        }
+    }
+
+    /// Output format that writes the options it was passed as JSON
+    struct OptionsFormat;
+
+    impl OutputFormat for OptionsFormat {
+        fn id(&self) -> &str {
+            "options"
+        }
+
+        fn from_svg(&self, _svg: &str, ctx: &RenderContext) -> Result<Vec<u8>, Error> {
+            Ok(serde_json::to_vec(&ctx.options)?)
+        }
+    }
+
+    fn vl_spec() -> serde_json::Value {
+        serde_json::json!({
+            "width": 100,
+            "height": 50,
+            "autosize": "none",
+            "padding": 0,
+            "mark": "rect",
+        })
+    }
+
+    #[tokio::test]
+    async fn test_register_output_format() {
+        initialize();
+        let mut converter = VlConverter::new();
+        assert_eq!(
+            converter.output_format_ids().unwrap(),
+            vec!["jpeg", "pdf", "png", "svg"]
+        );
+
+        let err = converter
+            .convert_to(vl_spec(), VlOpts::default(), "size", None)
+            .await
+            .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("Unsupported output format: size. Expected one of jpeg, pdf, png, svg"),
+            "{err}"
+        );
+
+        converter
+            .register_output_format(Box::new(SizeFormat))
+            .unwrap();
+        converter
+            .clone()
+            .register_output_format(Box::new(OptionsFormat))
+            .unwrap();
+        assert_eq!(
+            converter.output_format_ids().unwrap(),
+            vec!["jpeg", "options", "pdf", "png", "size", "svg"]
+        );
+
+        let size = converter
+            .convert_to(vl_spec(), VlOpts::default(), "size", None)
+            .await
+            .unwrap();
+        assert_eq!(String::from_utf8(size).unwrap(), "100x50");
+        let size = converter
+            .convert_to(
+                vl_spec(),
+                VlOpts::default(),
+                "size",
+                Some(serde_json::json!({"scale": 2, "ppi": 144})),
+            )
+            .await
+            .unwrap();
+        assert_eq!(String::from_utf8(size).unwrap(), "400x200");
+
+        let options = serde_json::json!({"scale": 2, "background": "white"});
+        let written = converter
+            .convert_to(
+                vl_spec(),
+                VlOpts::default(),
+                "options",
+                Some(options.clone()),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&written).unwrap(),
+            options
+        );
+    }
+
+    #[tokio::test]
+    async fn test_builtin_output_formats() {
+        initialize();
+        let mut converter = VlConverter::new();
+
+        let svg = converter
+            .convert_to(vl_spec(), VlOpts::default(), "svg", None)
+            .await
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(svg).unwrap(),
+            converter
+                .vegalite_to_svg(vl_spec(), VlOpts::default())
+                .await
+                .unwrap()
+        );
+
+        let png = converter
+            .convert_to(
+                vl_spec(),
+                VlOpts::default(),
+                "png",
+                Some(serde_json::json!({"scale": 2})),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            png,
+            converter
+                .vegalite_to_png(vl_spec(), VlOpts::default(), Some(2.0), None)
+                .await
+                .unwrap()
+        );
+
+        let jpeg = converter
+            .convert_to(
+                vl_spec(),
+                VlOpts::default(),
+                "jpeg",
+                Some(serde_json::json!({"quality": 50})),
+            )
+            .await
+            .unwrap();
+        assert!(jpeg.starts_with(&[0xFF, 0xD8]));
+        let err = converter
+            .convert_to(
+                vl_spec(),
+                VlOpts::default(),
+                "jpeg",
+                Some(serde_json::json!({"quality": 0})),
+            )
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("JPEG quality"), "{err}");
+
+        let pdf = converter
+            .convert_to(vl_spec(), VlOpts::default(), "pdf", None)
+            .await
+            .unwrap();
+        assert!(pdf.starts_with(b"%PDF"));
+
+        let err = converter
+            .convert_to(
+                vl_spec(),
+                VlOpts::default(),
+                "png",
+                Some(serde_json::json!({"interlaced": "yes"})),
+            )
+            .await
+            .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("Output format option interlaced must be a boolean"),
+            "{err}"
+        );
+    }
+}
+
+mod test_themed {
+    use crate::test_fetch_retry::serve_after_failures;
+    use crate::*;
+    use std::sync::atomic::Ordering;
+
+    #[tokio::test]
+    async fn test_themes_share_data_fetches() {
+        initialize();
+        let (url, requests) = serve_after_failures(
+            vec![],
+            "application/json",
+            br#"[{"a": "A", "b": 28}, {"a": "B", "b": 55}, {"a": "C", "b": 43}]"#.to_vec(),
+        );
+        let vl_spec = serde_json::json!({
+            "$schema": "https://vega.github.io/schema/vega-lite/v5.json",
+            "data": {"url": format!("{url}/data.json")},
+            "mark": "bar",
+            "encoding": {
+                "x": {"field": "a", "type": "nominal"},
+                "y": {"field": "b", "type": "quantitative"}
+            }
+        });
+
+        let mut converter = VlConverter::new();
+        let pngs = converter
+            .vegalite_to_png_themed(
+                vl_spec.clone(),
+                Default::default(),
+                &["default", "dark"],
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+        assert_eq!(pngs.len(), 2);
+        assert_ne!(pngs["default"], pngs["dark"]);
+
+        // Each themed conversion fetches the data again
+        let svgs = converter
+            .vegalite_to_svg_themed(vl_spec.clone(), Default::default(), &["default", "dark"])
+            .await
+            .unwrap();
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+
+        // The themed images match the images that are converted with each theme
+        let dark_svg = converter
+            .vegalite_to_svg(
+                vl_spec,
+                VlOpts {
+                    theme: Some("dark".to_string()),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(svgs["dark"], dark_svg);
+        assert_ne!(svgs["default"], dark_svg);
+    }
+}
+
+mod test_container_size {
+    use crate::*;
+
+    fn container_spec() -> serde_json::Value {
+        serde_json::json!({
+            "$schema": "https://vega.github.io/schema/vega-lite/v5.json",
+            "data": {"values": [{"a": "A", "b": 28}, {"a": "B", "b": 55}]},
+            "mark": "bar",
+            "width": "container",
+            "height": "container",
+            "encoding": {
+                "x": {"field": "a", "type": "nominal"},
+                "y": {"field": "b", "type": "quantitative"}
+            }
+        })
+    }
+
+    #[tokio::test]
+    async fn test_default_container_size() {
+        initialize();
+        let mut converter = VlConverter::new();
+        let svg = converter
+            .vegalite_to_svg(container_spec(), Default::default())
+            .await
+            .unwrap();
+        assert!(svg.contains(r#"width="600""#), "{svg}");
+        assert!(svg.contains(r#"height="400""#), "{svg}");
+    }
+
+    #[tokio::test]
+    async fn test_container_size_options() {
+        initialize();
+        let mut converter = VlConverter::new();
+        let svg = converter
+            .vegalite_to_svg(
+                container_spec(),
+                VlOpts {
+                    container_width: Some(500.0),
+                    container_height: Some(300.0),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert!(svg.contains(r#"width="500""#), "{svg}");
+        assert!(svg.contains(r#"height="300""#), "{svg}");
+
+        // Each view inside of a concatenation is sized to the container
+        let concat_spec = serde_json::json!({
+            "$schema": "https://vega.github.io/schema/vega-lite/v5.json",
+            "vconcat": [container_spec(), container_spec()]
+        });
+        let svg = converter
+            .vegalite_to_svg(
+                concat_spec,
+                VlOpts {
+                    container_width: Some(500.0),
+                    container_height: Some(300.0),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(svg.matches("h500v300h-500Z").count(), 2, "{svg}");
+
+        let err = converter
+            .vegalite_to_svg(
+                container_spec(),
+                VlOpts {
+                    container_width: Some(0.0),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("container_width must be a positive number"));
+    }
+
+    #[tokio::test]
+    async fn test_strict_sizing() {
+        initialize();
+        let mut converter = VlConverter::new();
+        let err = converter
+            .vegalite_to_svg(
+                container_spec(),
+                VlOpts {
+                    strict_sizing: true,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("sized to its container"), "{err}");
+
+        // Charts with explicit sizes convert as usual
+        let mut vl_spec = container_spec();
+        vl_spec["width"] = serde_json::json!(200);
+        vl_spec["height"] = serde_json::json!(100);
+        converter
+            .vegalite_to_svg(
+                vl_spec,
+                VlOpts {
+                    strict_sizing: true,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+    }
+}
+
+mod test_resize_mode {
+    use crate::*;
+    use vl_convert_rs::converter::VgOpts;
+    use vl_convert_rs::resize::ResizeMode;
+
+    /// A scatter plot with a color legend of long labels, which takes up much of its width
+    fn legend_spec() -> Value {
+        let values: Vec<_> = (0..12)
+            .map(|i| {
+                serde_json::json!({
+                    "x": i,
+                    "y": i * i,
+                    "category": format!("A category with a rather long label {i}")
+                })
+            })
+            .collect();
+        serde_json::json!({
+            "$schema": "https://vega.github.io/schema/vega-lite/v5.json",
+            "data": {"values": values},
+            "mark": "point",
+            "width": 400,
+            "height": 200,
+            "encoding": {
+                "x": {"field": "x", "type": "quantitative"},
+                "y": {"field": "y", "type": "quantitative"},
+                "color": {"field": "category", "type": "nominal"}
+            }
+        })
+    }
+
+    /// The left and right edges of the legends of a scenegraph, in image coordinates
+    fn legend_bounds(sg: &Value) -> Vec<(f64, f64)> {
+        let origin_x = sg["origin"][0].as_f64().unwrap();
+        let mut bounds = Vec::new();
+        for root in sg["scenegraph"]["items"].as_array().unwrap() {
+            let root_x = origin_x + root["x"].as_f64().unwrap_or(0.0);
+            for mark in root["items"].as_array().unwrap() {
+                if mark["role"] != "legend" {
+                    continue;
+                }
+                for legend in mark["items"].as_array().unwrap() {
+                    let x = root_x + legend["x"].as_f64().unwrap();
+                    bounds.push((x, x + legend["width"].as_f64().unwrap()));
+                }
+            }
+        }
+        bounds
+    }
+
+    #[tokio::test]
+    async fn test_resize_fit() {
+        initialize();
+        let mut converter = VlConverter::new();
+        let sg = converter
+            .vegalite_to_scenegraph(
+                legend_spec(),
+                VlOpts {
+                    width: Some(300.0),
+                    resize_mode: ResizeMode::Fit,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(sg["width"].as_f64(), Some(300.0));
+
+        // The legend is laid out inside of the chart, rather than beside a 300px plot
+        let bounds = legend_bounds(&sg);
+        assert_eq!(bounds.len(), 1);
+        for (left, right) in bounds {
+            assert!(left >= 0.0, "{left}");
+            assert!(right <= 300.0, "{right}");
+        }
+
+        // Vega specs are resized the same way
+        let vg_spec = converter
+            .vegalite_to_vega(legend_spec(), Default::default())
+            .await
+            .unwrap();
+        let sg = converter
+            .vega_to_scenegraph(
+                vg_spec,
+                VgOpts {
+                    width: Some(300.0),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(sg["width"].as_f64(), Some(300.0));
+        for (left, right) in legend_bounds(&sg) {
+            assert!(left >= 0.0 && right <= 300.0, "{left} {right}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resize_pad() {
+        initialize();
+        let mut converter = VlConverter::new();
+        let sg = converter
+            .vegalite_to_scenegraph(
+                legend_spec(),
+                VlOpts {
+                    width: Some(300.0),
+                    height: Some(100.0),
+                    resize_mode: ResizeMode::Pad,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+
+        // The plot area is 300px wide, and the axes and legend are added around it
+        let width = sg["width"].as_f64().unwrap();
+        assert!(width > 300.0, "{width}");
+        for (left, right) in legend_bounds(&sg) {
+            assert!(left >= 300.0 && right <= width, "{left} {right}");
+        }
+
+        let err = converter
+            .vegalite_to_scenegraph(
+                legend_spec(),
+                VlOpts {
+                    width: Some(-1.0),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("width must be a positive number"));
+    }
+}
+
+mod test_min_size {
+    use crate::*;
+    use vl_convert_rs::converter::VgOpts;
+
+    /// A faceted chart without data, which has no cells to size it
+    fn empty_facet_spec() -> Value {
+        serde_json::json!({
+            "$schema": "https://vega.github.io/schema/vega-lite/v5.json",
+            "data": {"values": []},
+            "padding": 0,
+            "autosize": {"type": "fit", "contains": "padding"},
+            "facet": {"column": {"field": "c", "type": "nominal"}},
+            "spec": {
+                "mark": "point",
+                "encoding": {
+                    "x": {"field": "x", "type": "quantitative"},
+                    "y": {"field": "y", "type": "quantitative"}
+                }
+            }
+        })
+    }
+
+    fn zero_width_spec() -> Value {
+        serde_json::json!({
+            "$schema": "https://vega.github.io/schema/vega/v5.json",
+            "width": 0,
+            "height": 100,
+            "padding": 0,
+            "autosize": "none",
+            "marks": [{
+                "type": "rect",
+                "encode": {"enter": {"width": {"value": 10}, "height": {"value": 10}}}
+            }]
+        })
+    }
+
+    fn assert_size_error(err: vl_convert_rs::anyhow::Error) {
+        let message = err.to_string();
+        assert!(message.contains("Chart resolved to"), "{message}");
+        assert!(
+            message.contains("check data and width/height settings"),
+            "{message}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_zero_size_error() {
+        initialize();
+        let mut converter = VlConverter::new();
+
+        let vl_spec = empty_facet_spec();
+        assert_size_error(
+            converter
+                .vegalite_to_svg(vl_spec.clone(), Default::default())
+                .await
+                .unwrap_err(),
+        );
+        assert_size_error(
+            converter
+                .vegalite_to_png(vl_spec.clone(), Default::default(), None, None)
+                .await
+                .unwrap_err(),
+        );
+        assert_size_error(
+            converter
+                .vegalite_to_jpeg(vl_spec.clone(), Default::default(), None, None)
+                .await
+                .unwrap_err(),
+        );
+        assert_size_error(
+            converter
+                .vegalite_to_pdf(vl_spec, Default::default())
+                .await
+                .unwrap_err(),
+        );
+
+        let vg_spec = zero_width_spec();
+        assert_size_error(
+            converter
+                .vega_to_svg(vg_spec.clone(), Default::default())
+                .await
+                .unwrap_err(),
+        );
+        assert_size_error(
+            converter
+                .vega_to_png(vg_spec.clone(), Default::default(), None, None)
+                .await
+                .unwrap_err(),
+        );
+        assert_size_error(
+            converter
+                .vega_to_jpeg(vg_spec.clone(), Default::default(), None, None)
+                .await
+                .unwrap_err(),
+        );
+        assert_size_error(
+            converter
+                .vega_to_pdf(vg_spec, Default::default())
+                .await
+                .unwrap_err(),
+        );
+    }
+
+    #[tokio::test]
+    async fn test_min_size() {
+        initialize();
+        let mut converter = VlConverter::new();
+        let vl_opts = VlOpts {
+            min_size: Some((40, 30)),
+            ..Default::default()
+        };
+        let vg_opts = VgOpts {
+            min_size: Some((40, 30)),
+            ..Default::default()
+        };
+
+        let svg = converter
+            .vegalite_to_svg_info(empty_facet_spec(), vl_opts.clone())
+            .await
+            .unwrap();
+        assert!(svg.width >= 40.0 && svg.height >= 30.0);
+        let png = converter
+            .vegalite_to_png_info(empty_facet_spec(), vl_opts.clone(), None, None)
+            .await
+            .unwrap();
+        assert!(png.width_px >= 40 && png.height_px >= 30);
+        let jpeg = converter
+            .vegalite_to_jpeg(empty_facet_spec(), vl_opts.clone(), None, None)
+            .await
+            .unwrap();
+        assert!(jpeg.starts_with(&[0xFF, 0xD8]));
+        let pdf = converter
+            .vegalite_to_pdf(empty_facet_spec(), vl_opts)
+            .await
+            .unwrap();
+        assert!(pdf.starts_with(b"%PDF"));
+
+        // Only the dimension that's below 1 pixel is enlarged
+        let svg = converter
+            .vega_to_svg_info(zero_width_spec(), vg_opts.clone())
+            .await
+            .unwrap();
+        assert_eq!((svg.width, svg.height), (40.0, 100.0));
+        let png = converter
+            .vega_to_png_info(zero_width_spec(), vg_opts.clone(), None, None)
+            .await
+            .unwrap();
+        assert_eq!((png.width_px, png.height_px), (40, 100));
+        let pdf = converter
+            .vega_to_pdf(zero_width_spec(), vg_opts)
+            .await
+            .unwrap();
+        assert!(pdf.starts_with(b"%PDF"));
+    }
+}
+
+mod test_antialias {
+    use crate::*;
+
+    /// Alpha values of the pixels of a PNG image
+    fn png_alphas(png_data: &[u8]) -> Vec<u8> {
+        let mut reader = png::Decoder::new(png_data).read_info().unwrap();
+        let mut buf = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buf).unwrap();
+        buf.truncate(info.buffer_size());
+        buf.chunks(4).map(|pixel| pixel[3]).collect()
+    }
+
+    #[tokio::test]
+    async fn test_heatmap_without_antialias() {
+        initialize();
+
+        // A 10x10 heatmap with cells at fractional coordinates, on a transparent background
+        let values: Vec<_> = (0..100)
+            .map(|i| serde_json::json!({"x": i % 10, "y": i / 10, "v": (i * 7) % 10}))
+            .collect();
+        let vl_spec = serde_json::json!({
+            "$schema": "https://vega.github.io/schema/vega-lite/v5.json",
+            "data": {"values": values},
+            "mark": "rect",
+            "width": 97,
+            "height": 83,
+            "background": null,
+            "config": {"view": {"stroke": null}},
+            "encoding": {
+                "x": {"field": "x", "type": "ordinal", "axis": null},
+                "y": {"field": "y", "type": "ordinal", "axis": null},
+                "color": {"field": "v", "type": "quantitative", "legend": null}
+            }
+        });
+
+        let mut converter = VlConverter::new();
+        for scale in [1.0, 1.5, 0.7] {
+            let png_data = converter
+                .vegalite_to_png_with_opts(
+                    vl_spec.clone(),
+                    Default::default(),
+                    Some(scale),
+                    None,
+                    PngOpts {
+                        antialias: false,
+                        ..Default::default()
+                    },
+                )
+                .await
+                .unwrap();
+            let alphas = png_alphas(&png_data);
+            assert!(alphas.iter().all(|alpha| *alpha == 0 || *alpha == 255));
+            assert!(alphas.contains(&255));
+
+            // Antialiased cell edges blend with the transparent background
+            let png_data = converter
+                .vegalite_to_png(vl_spec.clone(), Default::default(), Some(scale), None)
+                .await
+                .unwrap();
+            let alphas = png_alphas(&png_data);
+            assert!(alphas.iter().any(|alpha| *alpha != 0 && *alpha != 255));
+        }
+    }
+}
+
+mod test_background {
+    use crate::*;
+    use std::str::FromStr;
+    use vl_convert_rs::background::BackgroundColor;
+    use vl_convert_rs::converter::JpegOpts;
+
+    /// A bar chart with the given background, whose top-left corner is left uncovered
+    fn bar_spec(background: Value) -> Value {
+        serde_json::json!({
+            "data": {"values": [{"a": "A", "b": 28}, {"a": "B", "b": 55}]},
+            "mark": "bar",
+            "background": background,
+            "encoding": {
+                "x": {"field": "a", "type": "nominal"},
+                "y": {"field": "b", "type": "quantitative"}
+            }
+        })
+    }
+
+    fn jpeg_corner(jpeg_data: &[u8]) -> [u8; 3] {
+        let img = image::load_from_memory_with_format(jpeg_data, image::ImageFormat::Jpeg)
+            .unwrap()
+            .to_rgb8();
+        img.get_pixel(0, 0).0
+    }
+
+    fn assert_color_close(color: [u8; 3], expected: [u8; 3]) {
+        for (channel, expected_channel) in color.into_iter().zip(expected) {
+            assert!(
+                channel.abs_diff(expected_channel) <= 3,
+                "{color:?} != {expected:?}"
+            );
+        }
+    }
+
+    async fn to_jpeg(
+        converter: &mut VlConverter,
+        vl_spec: Value,
+        background: Option<&str>,
+    ) -> [u8; 3] {
+        let jpeg_opts = JpegOpts {
+            background: background
+                .map(|background| BackgroundColor::from_str(background).unwrap())
+                .unwrap_or_default(),
+        };
+        let jpeg_data = converter
+            .vegalite_to_jpeg_with_opts(vl_spec, Default::default(), None, Some(100), jpeg_opts)
+            .await
+            .unwrap();
+        jpeg_corner(&jpeg_data)
+    }
+
+    #[tokio::test]
+    async fn test_jpeg_background() {
+        initialize();
+        let mut converter = VlConverter::new();
+
+        // Transparent charts are composited over white rather than turning black
+        let transparent = bar_spec(Value::Null);
+        let color = to_jpeg(&mut converter, transparent.clone(), None).await;
+        assert_color_close(color, [255, 255, 255]);
+        let color = to_jpeg(&mut converter, transparent, Some("#ff0000")).await;
+        assert_color_close(color, [255, 0, 0]);
+
+        // The chart's own background covers the JPEG background
+        let blue = bar_spec(serde_json::json!("#0000ff"));
+        let color = to_jpeg(&mut converter, blue, Some("#ff0000")).await;
+        assert_color_close(color, [0, 0, 255]);
+    }
+
+    #[tokio::test]
+    async fn test_png_flatten_background() {
+        initialize();
+        let mut converter = VlConverter::new();
+        let png_opts = PngOpts {
+            flatten_background: Some(BackgroundColor::from_str("rgb(0, 128, 0)").unwrap()),
+            ..Default::default()
+        };
+        let png_data = converter
+            .vegalite_to_png_with_opts(
+                bar_spec(Value::Null),
+                Default::default(),
+                None,
+                None,
+                png_opts,
+            )
+            .await
+            .unwrap();
+        let mut reader = png::Decoder::new(png_data.as_slice()).read_info().unwrap();
+        let mut buf = vec![0; reader.output_buffer_size()];
+        reader.next_frame(&mut buf).unwrap();
+        assert_eq!(&buf[..4], &[0, 128, 0, 255]);
+    }
+}
+
+mod test_static_export {
+    use crate::*;
+    use serde_json::json;
+    use vl_convert_rs::static_export::StaticExportSeverity;
+
+    fn bar_spec() -> Value {
+        json!({
+            "data": {"values": [{"a": "A", "b": 28}, {"a": "B", "b": 55}]},
+            "mark": "bar",
+            "encoding": {
+                "x": {"field": "a", "type": "nominal"},
+                "y": {"field": "b", "type": "quantitative"}
+            }
+        })
+    }
+
+    #[tokio::test]
+    async fn test_analyze_static_export() {
+        initialize();
+        let mut converter = VlConverter::new();
+        let findings = converter
+            .analyze_static_export(bar_spec(), Default::default())
+            .await
+            .unwrap();
+        assert_eq!(findings, vec![]);
+
+        let mut brush_spec = bar_spec();
+        brush_spec["params"] = json!([{"name": "brush", "select": "interval"}]);
+        brush_spec["encoding"]["tooltip"] = json!({"field": "b"});
+        let findings = converter
+            .analyze_static_export(brush_spec, Default::default())
+            .await
+            .unwrap();
+        let summary: Vec<_> = findings
+            .iter()
+            .map(|finding| (finding.feature.as_str(), finding.spec_path.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![("selection", "/params/0"), ("tooltip", "/encoding/tooltip")]
+        );
+        assert_eq!(findings[0].severity, StaticExportSeverity::Unsupported);
+        assert_eq!(
+            findings[0].message,
+            "Selection \"brush\" has no effect in static output"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_config_tooltips() {
+        initialize();
+        let mut converter = VlConverter::new();
+        let vl_opts = VlOpts {
+            config: Some(json!({"mark": {"tooltip": true}})),
+            ..Default::default()
+        };
+        let findings = converter
+            .analyze_static_export(bar_spec(), vl_opts)
+            .await
+            .unwrap();
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].feature, "tooltip");
+        assert_eq!(findings[0].spec_path, "");
+    }
+}
+
+mod test_pre_aggregate {
+    use crate::*;
+    use serde_json::json;
+
+    /// Deterministic rows of a binned field `v` and an aggregated field `w`
+    fn rows(n: usize) -> Vec<Value> {
+        let mut seed: u64 = 1;
+        let mut rand = move || {
+            seed = seed * 16807 % 2147483647;
+            seed as f64 / 2147483647.0
+        };
+        (0..n)
+            .map(|_| {
+                let v = rand() * 97.3 + 3.1;
+                let w = (rand() * 1000.0).round() / 10.0 - 20.0;
+                json!({"v": v, "w": w})
+            })
+            .collect()
+    }
+
+    /// Check that a spec renders the same SVG with and without the pre_aggregate option
+    async fn assert_same_svg(converter: &mut VlConverter, vl_spec: Value, vl_opts: VlOpts) {
+        let svg = converter
+            .vegalite_to_svg(vl_spec.clone(), vl_opts.clone())
+            .await
+            .unwrap();
+        let pre_aggregated_svg = converter
+            .vegalite_to_svg(
+                vl_spec.clone(),
+                VlOpts {
+                    pre_aggregate: true,
+                    ..vl_opts
+                },
+            )
+            .await
+            .unwrap();
+        assert!(
+            svg == pre_aggregated_svg,
+            "SVGs differ for {}",
+            vl_spec["mark"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_pre_aggregate_matches_js() {
+        initialize();
+        let rows = rows(50_000);
+        let mut converter = VlConverter::new();
+
+        let histogram = json!({
+            "data": {"values": rows},
+            "mark": "bar",
+            "encoding": {
+                "x": {"field": "v", "bin": true, "type": "quantitative"},
+                "y": {"aggregate": "count", "type": "quantitative"}
+            }
+        });
+        assert_same_svg(&mut converter, histogram, Default::default()).await;
+
+        let ticks = json!({
+            "datasets": {"rows": rows},
+            "data": {"name": "rows"},
+            "mark": {"type": "tick", "color": "firebrick"},
+            "width": 400,
+            "encoding": {
+                "y": {"field": "v", "bin": {"maxbins": 20}, "type": "quantitative"},
+                "x": {"field": "w", "aggregate": "mean", "type": "quantitative"}
+            }
+        });
+        assert_same_svg(&mut converter, ticks, Default::default()).await;
+
+        let line = json!({
+            "data": {"values": rows},
+            "mark": "line",
+            "title": "Sums",
+            "encoding": {
+                "x": {"field": "v", "bin": {"maxbins": 7}, "type": "quantitative"},
+                "y": {"field": "w", "aggregate": "sum", "type": "quantitative"}
+            }
+        });
+        assert_same_svg(&mut converter, line, Default::default()).await;
+    }
+
+    #[tokio::test]
+    async fn test_pre_aggregate_fallback() {
+        initialize();
+        let mut data = rows(1000);
+        data[10]["v"] = Value::Null;
+        let mut converter = VlConverter::new();
+
+        // Unsupported data, transforms, and themes are converted without pre-aggregation
+        let mut vl_spec = json!({
+            "data": {"values": data},
+            "mark": "area",
+            "encoding": {
+                "x": {"field": "v", "bin": true, "type": "quantitative"},
+                "y": {"aggregate": "count", "type": "quantitative"}
+            }
+        });
+        assert_same_svg(&mut converter, vl_spec.clone(), Default::default()).await;
+        vl_spec["transform"] = json!([{"filter": "datum.w > 0"}]);
+        assert_same_svg(&mut converter, vl_spec, Default::default()).await;
+
+        let vl_spec = json!({
+            "data": {"values": rows(1000)},
+            "mark": "bar",
+            "encoding": {
+                "x": {"field": "v", "bin": true, "type": "quantitative"},
+                "y": {"aggregate": "count", "type": "quantitative"}
+            }
+        });
+        let vl_opts = VlOpts {
+            theme: Some("powerbi".to_string()),
+            ..Default::default()
+        };
+        assert_same_svg(&mut converter, vl_spec, vl_opts).await;
+    }
+}
+
+mod test_force_layout {
+    use crate::*;
+    use vl_convert_rs::converter::VgOpts;
+
+    /// A force-directed layout of nodes that start at random positions
+    fn force_spec() -> Value {
+        let nodes: Vec<_> = (0..12).map(|i| serde_json::json!({"id": i})).collect();
+        serde_json::json!({
+            "$schema": "https://vega.github.io/schema/vega/v5.json",
+            "width": 200,
+            "height": 200,
+            "data": [{
+                "name": "nodes",
+                "values": nodes,
+                "transform": [
+                    {"type": "formula", "as": "x0", "expr": "random() * width"},
+                    {"type": "formula", "as": "y0", "expr": "random() * height"}
+                ]
+            }],
+            "marks": [{
+                "type": "symbol",
+                "from": {"data": "nodes"},
+                "encode": {
+                    "enter": {
+                        "x": {"field": "x0"},
+                        "y": {"field": "y0"},
+                        "size": {"value": 100},
+                        "fill": {"value": "steelblue"}
+                    }
+                },
+                "transform": [{
+                    "type": "force",
+                    "forces": [
+                        {"force": "center", "x": 100, "y": 100},
+                        {"force": "collide", "radius": 8},
+                        {"force": "nbody", "strength": -20}
+                    ]
+                }]
+            }]
+        })
+    }
+
+    async fn render(converter: &mut VlConverter, random_seed: u32) -> String {
+        converter
+            .vega_to_svg(
+                force_spec(),
+                VgOpts {
+                    force_iterations: Some(300),
+                    random_seed: Some(random_seed),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_force_layout_is_reproducible() {
+        initialize();
+        let mut converter = VlConverter::new();
+
+        let svg = render(&mut converter, 42).await;
+        assert_eq!(render(&mut converter, 42).await, svg);
+        assert_ne!(render(&mut converter, 7).await, svg);
+
+        // The final spec has the static force transform
+        let final_spec = converter
+            .get_final_vega_spec_vg(
+                force_spec(),
+                VgOpts {
+                    force_iterations: Some(300),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        let force = &final_spec["marks"][0]["transform"][0];
+        assert_eq!(force["static"], true);
+        assert_eq!(force["iterations"], 300);
+    }
+}
+
+mod test_svg_post_processor {
+    use crate::*;
+    use serde_json::json;
+    use vl_convert_rs::anyhow::bail;
+    use vl_convert_rs::converter::SvgPostProcessor;
+
+    fn bar_spec() -> Value {
+        json!({
+            "data": {"values": [{"a": "A", "b": 28}, {"a": "B", "b": 55}]},
+            "mark": "bar",
+            "encoding": {
+                "x": {"field": "a", "type": "nominal"},
+                "y": {"field": "b", "type": "quantitative"}
+            }
+        })
+    }
+
+    /// Options with a post-processor that draws a red square over the top-left corner
+    fn red_corner_opts() -> VlOpts {
+        VlOpts {
+            svg_post_processor: Some(SvgPostProcessor::new(|svg: String| {
+                let Some(end) = svg.rfind("</svg>") else {
+                    bail!("Missing closing svg tag");
+                };
+                Ok(format!(
+                    r#"{}<rect id="corner" width="10" height="10" fill="#ff0000"/></svg>"#,
+                    &svg[..end]
+                ))
+            })),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_svg_post_processor() {
+        initialize();
+        let mut converter = VlConverter::new();
+        let svg = converter
+            .vegalite_to_svg(bar_spec(), red_corner_opts())
+            .await
+            .unwrap();
+        assert!(svg.contains(r#"<rect id="corner""#));
+
+        // Rasterized formats are rendered from the post-processed SVG
+        let png_data = converter
+            .vegalite_to_png(bar_spec(), red_corner_opts(), None, None)
+            .await
+            .unwrap();
+        let mut reader = png::Decoder::new(png_data.as_slice()).read_info().unwrap();
+        let mut buf = vec![0; reader.output_buffer_size()];
+        reader.next_frame(&mut buf).unwrap();
+        assert_eq!(&buf[..4], &[255, 0, 0, 255]);
+    }
+
+    #[tokio::test]
+    async fn test_svg_post_processor_error() {
+        initialize();
+        let mut converter = VlConverter::new();
+        let vl_opts = VlOpts {
+            svg_post_processor: Some(SvgPostProcessor::new(|_svg: String| {
+                bail!("watermark not found")
+            })),
+            ..Default::default()
+        };
+        let err = converter
+            .vegalite_to_pdf(bar_spec(), vl_opts)
+            .await
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("watermark not found"),
+            "unexpected error: {err}"
+        );
+    }
+}
+
+mod test_stroke_dash {
+    use crate::*;
+    use serde_json::json;
+
+    /// Vega spec of a horizontal rule 8 pixels thick at y=20, with the given dash array
+    fn dashed_rule_spec(stroke_dash: Value) -> Value {
+        json!({
+            "width": 200,
+            "height": 40,
+            "padding": 0,
+            "autosize": "none",
+            "marks": [{
+                "type": "rule",
+                "encode": {
+                    "enter": {
+                        "x": {"value": 0},
+                        "x2": {"value": 200},
+                        "y": {"value": 20},
+                        "stroke": {"value": "black"},
+                        "strokeWidth": {"value": 8},
+                        "strokeDash": {"value": stroke_dash}
+                    }
+                }
+            }]
+        })
+    }
+
+    /// Alpha values of the row of pixels through the middle of the rule
+    async fn rule_alphas(stroke_dash: Value, scale: f32) -> Vec<u8> {
+        let mut converter = VlConverter::new();
+        let png_data = converter
+            .vega_to_png(
+                dashed_rule_spec(stroke_dash),
+                Default::default(),
+                Some(scale),
+                None,
+            )
+            .await
+            .unwrap();
+        let mut reader = png::Decoder::new(png_data.as_slice()).read_info().unwrap();
+        let mut buf = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buf).unwrap();
+        let row = (20.0 * scale) as usize;
+        let row_bytes = &buf[row * info.line_size..(row + 1) * info.line_size];
+        row_bytes.chunks(4).map(|pixel| pixel[3]).collect()
+    }
+
+    /// Number of times that the pixels switch between drawn and blank, skipping the partly
+    /// drawn pixels in between
+    fn count_transitions(alphas: &[u8]) -> usize {
+        let mut transitions = 0;
+        let mut drawn = None;
+        for alpha in alphas {
+            let pixel_drawn = match alpha {
+                192.. => true,
+                0..=63 => false,
+                _ => continue,
+            };
+            if drawn.is_some_and(|drawn| drawn != pixel_drawn) {
+                transitions += 1;
+            }
+            drawn = Some(pixel_drawn);
+        }
+        transitions
+    }
+
+    #[rstest]
+    #[case(0.5)]
+    #[case(0.25)]
+    #[tokio::test]
+    async fn test_small_scale_stroke_dash(#[case] scale: f32) {
+        initialize();
+
+        // Dashes and gaps of 1 pixel are a fraction of a pixel at these scales
+        let alphas = rule_alphas(json!([1, 1]), scale).await;
+        let transitions = count_transitions(&alphas);
+        assert!(
+            transitions >= alphas.len() / 4,
+            "{transitions} transitions in {alphas:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_zero_stroke_dash() {
+        initialize();
+
+        // Dash arrays without length are drawn solid, like browsers draw them
+        let alphas = rule_alphas(json!([0, 0]), 0.25).await;
+        assert!(alphas.iter().all(|alpha| *alpha == 255), "{alphas:?}");
+    }
+}
+
+mod test_suppress_components {
+    use crate::*;
+    use serde_json::json;
+    use vl_convert_rs::converter::VgOpts;
+    use vl_convert_rs::resize::ResizeMode;
+    use vl_convert_rs::suppress::Component;
+
+    /// A titled bar chart with a color legend
+    fn legend_spec() -> Value {
+        json!({
+            "title": "Fruit",
+            "data": {"values": [
+                {"a": "apples", "b": 28},
+                {"a": "bananas", "b": 55},
+                {"a": "cherries", "b": 43}
+            ]},
+            "mark": "bar",
+            "encoding": {
+                "x": {"field": "a", "type": "nominal"},
+                "y": {"field": "b", "type": "quantitative"},
+                "color": {"field": "a", "type": "nominal"}
+            }
+        })
+    }
+
+    /// Width of the plot area of a scenegraph's root group
+    fn plot_width(sg: &Value) -> f64 {
+        sg["scenegraph"]["items"][0]["width"].as_f64().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_suppress_legend() {
+        initialize();
+        let mut converter = VlConverter::new();
+        let svg = converter
+            .vegalite_to_svg(
+                legend_spec(),
+                VlOpts {
+                    suppress_components: Some(vec![Component::Legend, Component::Title]),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert!(!svg.contains("role-legend"));
+        assert!(!svg.contains("role-title"));
+        assert!(svg.contains("role-axis"));
+
+        // The plot area grows into the space of the legend
+        let fit_opts = |suppress_components| VlOpts {
+            width: Some(300.0),
+            resize_mode: ResizeMode::Fit,
+            suppress_components,
+            ..Default::default()
+        };
+        let sg = converter
+            .vegalite_to_scenegraph(legend_spec(), fit_opts(None))
+            .await
+            .unwrap();
+        let suppressed_sg = converter
+            .vegalite_to_scenegraph(legend_spec(), fit_opts(Some(vec![Component::Legend])))
+            .await
+            .unwrap();
+        assert_eq!(suppressed_sg["width"].as_f64(), Some(300.0));
+        assert!(
+            plot_width(&suppressed_sg) > plot_width(&sg),
+            "{} {}",
+            plot_width(&suppressed_sg),
+            plot_width(&sg)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_suppress_vega_components() {
+        initialize();
+        let mut converter = VlConverter::new();
+        let vg_spec = converter
+            .vegalite_to_vega(legend_spec(), Default::default())
+            .await
+            .unwrap();
+        let svg = converter
+            .vega_to_svg(
+                vg_spec,
+                VgOpts {
+                    suppress_components: Some(vec![
+                        Component::Legend,
+                        Component::AxisX,
+                        Component::AxisY,
+                    ]),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert!(!svg.contains("role-legend"));
+        assert!(!svg.contains("role-axis"));
+        assert!(svg.contains("role-title"));
+    }
+}
+
+mod test_lenient_dates {
+    use crate::*;
+    use serde_json::json;
+    use vl_convert_rs::converter::scenegraph_text_items;
+
+    /// Chart that draws the earliest and latest dates of its data, as the extent of the
+    /// temporal field that's parsed from the date strings
+    fn extent_spec(dates: &[&str]) -> Value {
+        let values: Vec<_> = dates.iter().map(|date| json!({"date": date})).collect();
+        json!({
+            "data": {"values": values, "format": {"parse": {"date": "date"}}},
+            "transform": [
+                {"aggregate": [
+                    {"op": "min", "field": "date", "as": "lo"},
+                    {"op": "max", "field": "date", "as": "hi"}
+                ]},
+                {
+                    "calculate": "timeFormat(datum.lo, '%Y-%m-%d %H:%M') + ' to ' + timeFormat(datum.hi, '%Y-%m-%d %H:%M')",
+                    "as": "label"
+                }
+            ],
+            "mark": "text",
+            "encoding": {"text": {"field": "label", "type": "nominal"}}
+        })
+    }
+
+    async fn extent_label(converter: &mut VlConverter, dates: &[&str]) -> String {
+        let sg = converter
+            .vegalite_to_scenegraph(
+                extent_spec(dates),
+                VlOpts {
+                    lenient_dates: true,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        let text_items = scenegraph_text_items(&sg).unwrap();
+        assert_eq!(text_items.len(), 1, "{text_items:?}");
+        text_items[0].text.clone()
+    }
+
+    #[tokio::test]
+    async fn test_lenient_dates() {
+        initialize();
+        let mut converter = VlConverter::new();
+
+        // US style, month first
+        let us_dates = [
+            "03/28/2024 9:15 AM",
+            "03/27/2024 5:00 PM",
+            "04/02/2024 11:30 PM",
+        ];
+        let label = extent_label(&mut converter, &us_dates).await;
+        assert_eq!(label, "2024-03-27 17:00 to 2024-04-02 23:30");
+        let warnings = converter
+            .vegalite_to_svg_info(
+                extent_spec(&us_dates),
+                VlOpts {
+                    lenient_dates: true,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap()
+            .warnings;
+        assert_eq!(
+            warnings
+                .iter()
+                .map(|warning| warning.message.as_str())
+                .collect::<Vec<_>>(),
+            vec![r#"Rewrote the non-ISO 8601 dates of fields "date" to ISO 8601"#]
+        );
+
+        // European style, day first
+        let label = extent_label(
+            &mut converter,
+            &["27.03.2024 17:00", "02.04.2024 23:30", "28.03.2024 09:15"],
+        )
+        .await;
+        assert_eq!(label, "2024-03-27 17:00 to 2024-04-02 23:30");
+        let label = extent_label(&mut converter, &["02/04/2024", "27/03/2024"]).await;
+        assert_eq!(label, "2024-03-27 00:00 to 2024-04-02 00:00");
+    }
+}
+
+mod test_browser_shims {
+    use crate::*;
+    use serde_json::json;
+
+    /// A text chart that shows the value of a browser global, as read by an expression
+    fn browser_global_spec(expr: &str) -> Value {
+        json!({
+            "width": 320,
+            "height": 200,
+            "data": {"values": [{}]},
+            "transform": [{"calculate": expr, "as": "label"}],
+            "mark": "text",
+            "encoding": {"text": {"field": "label", "type": "nominal"}}
+        })
+    }
+
+    #[tokio::test]
+    async fn test_navigator_language() {
+        initialize();
+        let mut converter = VlConverter::new();
+        let spec = browser_global_spec(
+            "navigator.language + ' ' + screen.width + ' ' + window.devicePixelRatio",
+        );
+        let svg = converter
+            .vegalite_to_svg(spec.clone(), Default::default())
+            .await
+            .unwrap();
+        assert!(svg.contains("en-US 320 1"), "{svg}");
+
+        // The language is the name of the format locale
+        let svg = converter
+            .vegalite_to_svg(
+                spec,
+                VlOpts {
+                    format_locale: Some(FormatLocale::Name("de-DE".to_string())),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert!(svg.contains("de-DE 320 1"), "{svg}");
+    }
+
+    #[tokio::test]
+    async fn test_device_pixel_ratio_png_scale() {
+        initialize();
+        let mut converter = VlConverter::new();
+        // A black rect that spans the chart only when devicePixelRatio is 2
+        let spec = json!({
+            "width": 100,
+            "height": 10,
+            "autosize": "none",
+            "padding": 0,
+            "config": {"view": {"stroke": null}},
+            "data": {"values": [{}]},
+            "mark": {
+                "type": "rect",
+                "color": "black",
+                "x": 0,
+                "y": 0,
+                "y2": 10,
+                "x2": {"expr": "window.devicePixelRatio * 50"}
+            }
+        });
+        let png_data = converter
+            .vegalite_to_png(spec, Default::default(), Some(2.0), None)
+            .await
+            .unwrap();
+        let mut reader = png::Decoder::new(png_data.as_slice()).read_info().unwrap();
+        let mut buf = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buf).unwrap();
+        assert_eq!((info.width, info.height), (200, 20));
+        let offset = (10 * info.line_size) + 150 * 4;
+        assert_eq!(&buf[offset..offset + 4], &[0, 0, 0, 255]);
+    }
+
+    #[tokio::test]
+    async fn test_browser_globals_error() {
+        initialize();
+        let mut converter = VlConverter::new();
+        let err = converter
+            .vegalite_to_svg(
+                browser_global_spec("navigator.language"),
+                VlOpts {
+                    browser_shims: false,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains(r#"An expression reads the browser API "navigator""#),
+            "{err}"
+        );
+
+        // APIs that aren't shimmed are named in the error
+        let err = converter
+            .vegalite_to_svg(
+                browser_global_spec("localStorage.length"),
+                Default::default(),
+            )
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains(r#"An expression reads the browser API "localStorage""#),
+            "{err}"
+        );
+    }
+}
+
+mod test_simplify_topojson {
+    use crate::*;
+    use vl_convert_rs::topojson::simplify_topology;
+
+    fn geoshape_count(svg: &str) -> usize {
+        svg.matches(r#"aria-roledescription="geoshape""#).count()
+    }
+
+    #[test]
+    fn test_simplify_topology() {
+        // Three regions with wiggly shared borders and noisy outer boundaries
+        let vl_spec = load_vl_spec("topojson_regions");
+        let mut topology = vl_spec["data"]["values"].clone();
+        let simplification = simplify_topology(&mut topology, 1.0).unwrap().unwrap();
+        assert_eq!(simplification.vertices, 126);
+        assert_eq!(simplification.simplified_vertices, 18);
+
+        // Arcs are simplified in place, and the geometries that reference them are unchanged
+        let arcs = topology["arcs"].as_array().unwrap();
+        assert_eq!(arcs.len(), 6);
+        assert_eq!(arcs[0], serde_json::json!([[100, 0], [100, 100]]));
+        assert_eq!(topology["objects"], vl_spec["data"]["values"]["objects"]);
+    }
+
+    #[tokio::test]
+    async fn test_vegalite_to_svg_simplify_topojson() {
+        initialize();
+        let vl_spec = load_vl_spec("topojson_regions");
+
+        let mut converter = VlConverter::new();
+        let svg = converter
+            .vegalite_to_svg(vl_spec.clone(), Default::default())
+            .await
+            .unwrap();
+        let simplified = converter
+            .vegalite_to_svg_info(
+                vl_spec,
+                VlOpts {
+                    simplify_topojson: Some(1.0),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+
+        // One path per feature, drawn with fewer vertices
+        assert_eq!(geoshape_count(&svg), 3);
+        assert_eq!(geoshape_count(&simplified.data), 3);
+        assert!(simplified.data.len() < svg.len());
+
+        let warnings = simplified.warnings;
+        assert_eq!(warnings.len(), 1, "{warnings:?}");
+        assert_eq!(
+            warnings[0].message,
+            "Simplified the arcs of a TopoJSON dataset from 126 to 18 vertices (85.7% fewer)"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_invalid_simplify_topojson() {
+        initialize();
+        let mut converter = VlConverter::new();
+        let err = converter
+            .vegalite_to_svg(
+                load_vl_spec("topojson_regions"),
+                VlOpts {
+                    simplify_topojson: Some(-1.0),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("non-negative"), "{err}");
+    }
+}
@@ -1,3 +1,4 @@
+use vl_convert_rs::suggestions::theme_names;
 use vl_convert_rs::VlConverter;
 
 #[tokio::test]
@@ -15,3 +16,33 @@ async fn test_get_themes_dark_background() {
         panic!("Expected themes to be an object")
     }
 }
+
+#[tokio::test]
+async fn test_get_themes_sorted() {
+    let mut converter = VlConverter::new();
+    let names = converter.get_theme_names().await.unwrap();
+    let mut sorted = names.clone();
+    sorted.sort();
+    assert_eq!(names, sorted);
+    assert!(names.contains(&"dark".to_string()));
+    assert!(!names.contains(&"default".to_string()));
+
+    // The names that the CLI checks themes against, without starting a converter, match
+    assert_eq!(theme_names(), names);
+
+    // Themes are keyed by the same names, in the same order, and the keys of their configs
+    // are sorted too
+    let themes = converter.get_themes().await.unwrap();
+    let themes = themes.as_object().unwrap();
+    assert_eq!(themes.keys().cloned().collect::<Vec<_>>(), names);
+    let dark_keys: Vec<_> = themes["dark"].as_object().unwrap().keys().collect();
+    let mut sorted_keys = dark_keys.clone();
+    sorted_keys.sort();
+    assert_eq!(dark_keys, sorted_keys);
+
+    let again = converter.get_themes().await.unwrap();
+    assert_eq!(
+        serde_json::to_string(&again).unwrap(),
+        serde_json::to_string(themes).unwrap()
+    );
+}
@@ -1,6 +1,7 @@
 use anyhow::Error as AnyError;
 use dircpy::copy_dir;
 use semver::Version;
+use sha2::{Digest, Sha256};
 use std::collections::HashMap;
 use std::fmt::Write;
 use std::fs;
@@ -55,6 +56,10 @@ const VL_PATHS: &[(&str, &str)] = &[
         "/pin/vega-lite@v5.21.0-FWbVtRVTj7vqBi6QZX8A/mode=imports,min/optimized/vega-lite.js",
     ),
 ];
+// Vega-Lite versions older than this are only included in vl-convert-rs builds with the
+// vl_legacy feature, which the Python package leaves off to keep its wheels small
+const LEGACY_VL_BEFORE: &str = "5.14";
+const VL_LEGACY_CFG: &str = "#[cfg(feature = \"vl_legacy\")]\n    ";
 const SKYPACK_URL: &str = "https://cdn.skypack.dev";
 const VEGA_PATH: &str = "/pin/vega@v5.30.0-fYDVG3pUN16BiGmbVNdw/mode=imports,min/optimized/vega.js";
 const VEGA_THEMES_PATH: &str =
@@ -184,6 +189,13 @@ fn main() {
         .collect();
     let vl_versions_csv = ver_unders.join(",\n    ");
 
+    // Theme names csv
+    let theme_names_csv = vega_theme_names(&vendor_path.join("cdn.skypack.dev"))
+        .iter()
+        .map(|name| format!("\"{name}\""))
+        .collect::<Vec<_>>()
+        .join(",\n    ");
+
     // Path match csv
     let ver_path_matches: Vec<_> = VL_PATHS
         .iter()
@@ -212,12 +224,29 @@ fn main() {
         .collect();
     let to_semver_match_csv = to_semver_matches.join(",\n            ");
 
+    // Included match csv, with the legacy versions that are only included with vl_legacy
+    let included_match_csv: String = VL_PATHS
+        .iter()
+        .filter(|(ver, _)| is_legacy_vl_version(ver))
+        .map(|(ver, _)| {
+            format!(
+                "v{} => cfg!(feature = \"vl_legacy\"),\n            ",
+                ver.replace('.', "_")
+            )
+        })
+        .collect();
+
     // Variants csv
     let version_instances: Vec<_> = VL_PATHS
         .iter()
         .map(|(ver, _)| {
             let ver_under = ver.replace('.', "_");
-            format!("VlVersion::v{ver_under}", ver_under = ver_under)
+            let cfg = if is_legacy_vl_version(ver) {
+                VL_LEGACY_CFG
+            } else {
+                ""
+            };
+            format!("{cfg}VlVersion::v{ver_under}", ver_under = ver_under)
         })
         .collect();
     let version_instances_csv = version_instances.join(",\n    ");
@@ -242,6 +271,11 @@ pub const VEGA_VERSION: &str = "{VEGA_VERSION}";
 pub const VEGA_THEMES_VERSION: &str = "{VEGA_THEMES_VERSION}";
 pub const VEGA_EMBED_VERSION: &str = "{VEGA_EMBED_VERSION}";
 
+/// Names of the themes of the vega-themes module, sorted
+pub const VEGA_THEME_NAMES: &[&str] = &[
+    {theme_names_csv},
+];
+
 pub fn url_for_path(path: &str) -> String {{
     format!("{{}}{{}}", SKYPACK_URL, path)
 }}
@@ -279,6 +313,22 @@ impl VlVersion {{
             {to_semver_match_csv}
         }}
     }}
+
+    /// Check that the Vega-Lite library of the version is included in this build. Versions
+    /// older than {LEGACY_VL_BEFORE} are only included with the vl_legacy feature
+    pub fn ensure_included(self) -> Result<(), AnyError> {{
+        use VlVersion::*;
+        let included = match self {{
+            {included_match_csv}_ => true,
+        }};
+        if !included {{
+            bail!(
+                "This build does not include v{{}}; install vl-convert-python-full or build with feature vl_legacy",
+                self.to_semver()
+            );
+        }}
+        Ok(())
+    }}
 }}
 
 impl Default for VlVersion {{
@@ -291,10 +341,12 @@ impl FromStr for VlVersion {{
     type Err = AnyError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {{
-        Ok(match s {{
+        let version = match s {{
             {from_str_matches_csv},
             _ => bail!("Unsupported Vega-Lite version string {{}}", s)
-        }})
+        }};
+        version.ensure_included()?;
+        Ok(version)
     }}
 }}
 
@@ -309,7 +361,10 @@ pub fn build_import_map() -> HashMap<String, String> {{
         path_match_csv = path_match_csv,
         from_str_matches_csv = from_str_matches_csv,
         to_semver_match_csv = to_semver_match_csv,
+        included_match_csv = included_match_csv,
+        LEGACY_VL_BEFORE = LEGACY_VL_BEFORE,
         version_instances_csv = version_instances_csv,
+        theme_names_csv = theme_names_csv,
         SKYPACK_URL = SKYPACK_URL,
         VEGA_PATH = VEGA_PATH,
         VEGA_VERSION = vega_version,
@@ -371,7 +426,8 @@ pub fn build_import_map() -> HashMap<String, String> {{
         if let Some(relative_sub) = relative.strip_prefix(skypack_domain) {
             writeln!(
                 content,
-                "    m.insert(\"{relative_sub}\".to_string(), include_str!(\"../../vendor/{skypack_domain}{relative_sub}\").to_string());",
+                "    {cfg}m.insert(\"{relative_sub}\".to_string(), include_str!(\"../../vendor/{skypack_domain}{relative_sub}\").to_string());",
+                cfg = vl_legacy_cfg(relative_sub),
             )
                 .unwrap();
         }
@@ -391,10 +447,40 @@ pub fn build_import_map() -> HashMap<String, String> {{
     })
     .unwrap();
 
+    // Write vendor_manifest.rs with the SHA-256 hash of each vendored module, after patching,
+    // so that vl-convert-rs can check the sources it embeds at runtime
+    let mut manifest = String::from(
+        r#"
+// *************************************************************************
+// * This file is generated by vl-convert-vendor/src/main.rs. Do not edit! *
+// *************************************************************************
+
+/// Path of each vendored module in the import map, paired with the hex SHA-256 hash of its
+/// source
+pub const VENDOR_MANIFEST: &[(&str, &str)] = &[
+"#,
+    );
+    visit_dirs(&vendor_path, &mut |f| {
+        let p = f.path().canonicalize().unwrap();
+        let relative = &p.to_str().unwrap()[(vendor_path_str.len() + 1)..];
+        if let Some(relative_sub) = relative.strip_prefix(skypack_domain) {
+            let hash = Sha256::digest(fs::read(&p).unwrap());
+            writeln!(
+                manifest,
+                "    {cfg}(\"{relative_sub}\", \"{hash:x}\"),",
+                cfg = vl_legacy_cfg(relative_sub),
+            )
+            .unwrap();
+        }
+    })
+    .unwrap();
+    manifest.push_str("];\n");
+
     // Write locale maps
     writeln!(
         content,
         "
+#[cfg(feature = \"embed-locales\")]
 pub fn build_format_locale_map() -> HashMap<String, String> {{
     let mut m: HashMap<String, String> = HashMap::new();"
     )
@@ -417,6 +503,7 @@ pub fn build_format_locale_map() -> HashMap<String, String> {{
     writeln!(
         content,
         "
+#[cfg(feature = \"embed-locales\")]
 pub fn build_time_format_locale_map() -> HashMap<String, String> {{
     let mut m: HashMap<String, String> = HashMap::new();"
     )
@@ -446,15 +533,68 @@ pub fn build_time_format_locale_map() -> HashMap<String, String> {{
     let import_map_path = deno_deps_path.join("import_map.rs");
     fs::write(&import_map_path, content).unwrap();
 
-    // Run rustfmt on import_map.rs
+    let vendor_manifest_path = deno_deps_path.join("vendor_manifest.rs");
+    fs::write(&vendor_manifest_path, manifest).unwrap();
+
+    // Run rustfmt on import_map.rs and vendor_manifest.rs
     if let Err(err) = Command::new("rustfmt")
         .arg(import_map_path.to_str().unwrap())
+        .arg(vendor_manifest_path.to_str().unwrap())
         .output()
     {
         panic!("rustfmt command failed: {}", err);
     }
 }
 
+/// Names of the themes that the vendored vega-themes module exports, sorted. The pinned module
+/// re-exports the optimized module, which exports the themes along with the version
+fn vega_theme_names(skypack_path: &Path) -> Vec<String> {
+    let read = |path: &str| fs::read_to_string(skypack_path.join(path.trim_start_matches('/')));
+    let pin = read(VEGA_THEMES_PATH).expect("Failed to read the vega-themes module");
+    let module_path = pin
+        .split("export * from '")
+        .nth(1)
+        .and_then(|rest| rest.split('\'').next())
+        .expect("Failed to find the optimized vega-themes module");
+    let module = read(module_path).expect("Failed to read the optimized vega-themes module");
+    let exports = module
+        .rsplit("export{")
+        .next()
+        .and_then(|rest| rest.split('}').next())
+        .expect("Failed to find the exports of vega-themes");
+    let mut names: Vec<String> = exports
+        .split(',')
+        .filter_map(|export| export.split(" as ").nth(1))
+        .map(|name| name.trim().to_string())
+        .filter(|name| name != "version" && name != "default")
+        .collect();
+    names.sort();
+    names
+}
+
+/// Whether a Vega-Lite version (e.g. "5.8" or "5.8.0") is older than LEGACY_VL_BEFORE
+fn is_legacy_vl_version(ver: &str) -> bool {
+    let semver = |ver: &str| match ver.matches('.').count() {
+        1 => Version::parse(&format!("{ver}.0")).unwrap(),
+        _ => Version::parse(ver).unwrap(),
+    };
+    semver(ver) < semver(LEGACY_VL_BEFORE)
+}
+
+/// Attribute that only includes an item for a vendored file with the vl_legacy feature, if the
+/// file belongs to a legacy Vega-Lite version
+fn vl_legacy_cfg(relative_sub: &str) -> &'static str {
+    let legacy = relative_sub
+        .split_once("/vega-lite@v")
+        .and_then(|(_, rest)| rest.split_once('-'))
+        .is_some_and(|(version, _)| is_legacy_vl_version(version));
+    if legacy {
+        VL_LEGACY_CFG
+    } else {
+        ""
+    }
+}
+
 fn visit_dirs(dir: &Path, cb: &mut dyn FnMut(&DirEntry)) -> io::Result<()> {
     if dir.is_dir() {
         let mut entries = fs::read_dir(dir)?
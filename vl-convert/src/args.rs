@@ -0,0 +1,177 @@
+use clap::CommandFactory;
+use std::path::{Path, PathBuf};
+use vl_convert_rs::{anyhow, anyhow::bail};
+
+/// Maximum nesting depth of response files that reference other response files
+const MAX_RESPONSE_FILE_DEPTH: usize = 10;
+
+/// Expand command line arguments before they are parsed by clap.
+///
+/// Arguments of the form `@path/to/args.txt` are replaced by the arguments listed in that file,
+/// and the options in an `--options-json FILE` object are appended unless the same option was
+/// also given as a flag.
+pub fn expand_args<C: CommandFactory>(
+    args: impl IntoIterator<Item = String>,
+) -> Result<Vec<String>, anyhow::Error> {
+    let mut args = args.into_iter();
+    let mut expanded: Vec<String> = args.next().into_iter().collect();
+    for arg in args {
+        expand_arg(arg, None, 0, &mut expanded)?;
+    }
+    append_options_json::<C>(expanded)
+}
+
+fn expand_arg(
+    arg: String,
+    base_dir: Option<&Path>,
+    depth: usize,
+    expanded: &mut Vec<String>,
+) -> Result<(), anyhow::Error> {
+    let Some(path) = arg.strip_prefix('@') else {
+        expanded.push(arg);
+        return Ok(());
+    };
+    if depth >= MAX_RESPONSE_FILE_DEPTH {
+        bail!(
+            "Response files nested more than {} levels deep at @{}",
+            MAX_RESPONSE_FILE_DEPTH,
+            path
+        );
+    }
+
+    // Nested response file paths are relative to the file that references them
+    let path = match base_dir {
+        Some(base_dir) => base_dir.join(path),
+        None => PathBuf::from(path),
+    };
+    let contents = match std::fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(err) => {
+            bail!("Failed to read response file {}: {}", path.display(), err);
+        }
+    };
+
+    let base_dir = path.parent().map(Path::to_path_buf);
+    for line in contents.lines() {
+        if let Some(arg) = parse_response_file_line(line) {
+            expand_arg(arg, base_dir.as_deref(), depth + 1, expanded)?;
+        }
+    }
+    Ok(())
+}
+
+/// Parse one line of a response file into an argument.
+///
+/// Blank lines and lines starting with `#` are skipped. A line wrapped in matching single or
+/// double quotes is taken literally without the quotes, which preserves surrounding whitespace
+/// and leading `#` or `@` characters.
+fn parse_response_file_line(line: &str) -> Option<String> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+    for quote in ['"', '\''] {
+        if line.len() >= 2 && line.starts_with(quote) && line.ends_with(quote) {
+            return Some(line[1..line.len() - 1].to_string());
+        }
+    }
+    Some(line.to_string())
+}
+
+fn append_options_json<C: CommandFactory>(
+    mut args: Vec<String>,
+) -> Result<Vec<String>, anyhow::Error> {
+    let Some(options_path) = find_flag_value(&args, "options-json") else {
+        return Ok(args);
+    };
+    let options_str = match std::fs::read_to_string(&options_path) {
+        Ok(options_str) => options_str,
+        Err(err) => {
+            bail!("Failed to read options JSON file {}: {}", options_path, err);
+        }
+    };
+    let options: serde_json::Map<String, serde_json::Value> =
+        match serde_json::from_str(&options_str) {
+            Ok(options) => options,
+            Err(err) => {
+                bail!(
+                    "Failed to parse options JSON file {} as a JSON object: {}",
+                    options_path,
+                    err
+                );
+            }
+        };
+
+    // Options are looked up on the subcommand named on the command line
+    let mut command = C::command();
+    command.build();
+    let Some(subcommand) = args
+        .iter()
+        .skip(1)
+        .find_map(|arg| command.find_subcommand(arg))
+    else {
+        bail!("--options-json requires a subcommand");
+    };
+
+    let mut option_args: Vec<String> = Vec::new();
+    for (key, value) in options {
+        let long = key.replace('_', "-");
+        let Some(option) = subcommand
+            .get_arguments()
+            .find(|arg| arg.get_long() == Some(long.as_str()))
+        else {
+            bail!(
+                "Unknown option '{}' in options JSON file for the {} subcommand",
+                key,
+                subcommand.get_name()
+            );
+        };
+
+        // Flags given on the command line take precedence over the options JSON file
+        let short = option.get_short().map(|short| format!("-{short}"));
+        let given_as_flag = args.iter().any(|arg| {
+            arg == &format!("--{long}")
+                || arg.starts_with(&format!("--{long}="))
+                || Some(arg) == short.as_ref()
+        });
+        if given_as_flag {
+            continue;
+        }
+
+        let values = match value {
+            serde_json::Value::Array(values) => values,
+            value => vec![value],
+        };
+        for value in values {
+            match value {
+                serde_json::Value::Bool(true) => option_args.push(format!("--{long}")),
+                serde_json::Value::Bool(false) | serde_json::Value::Null => {}
+                serde_json::Value::String(value) => {
+                    option_args.push(format!("--{long}"));
+                    option_args.push(value);
+                }
+                value => {
+                    option_args.push(format!("--{long}"));
+                    option_args.push(value.to_string());
+                }
+            }
+        }
+    }
+
+    args.extend(option_args);
+    Ok(args)
+}
+
+fn find_flag_value(args: &[String], long: &str) -> Option<String> {
+    let flag = format!("--{long}");
+    let prefix = format!("--{long}=");
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == &flag {
+            return args.next().cloned();
+        } else if let Some(value) = arg.strip_prefix(&prefix) {
+            return Some(value.to_string());
+        }
+    }
+    None
+}
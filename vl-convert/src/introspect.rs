@@ -0,0 +1,211 @@
+use clap::{Arg, ArgAction, Command};
+use serde_json::{json, Map, Value};
+use std::any::TypeId;
+use std::path::PathBuf;
+
+/// Oldest version of vl-convert that the `since` annotations of the schema tell apart.
+/// Subcommands and options without an entry in SINCE_VERSIONS were available in this version
+/// or earlier
+const BASE_VERSION: &str = "1.7.0";
+
+/// Version of vl-convert that added each subcommand and option that's newer than
+/// BASE_VERSION, as (subcommand, option, version). The subcommand is empty for global options,
+/// and "*" for an option that was added to every subcommand that has it. The option is empty
+/// for a subcommand, whose options have its version unless they have an entry of their own,
+/// and for all of the global options.
+///
+/// Add an entry here along with every new subcommand or option
+const SINCE_VERSIONS: &[(&str, &str, &str)] = &[
+    // Global options
+    ("", "", "1.8.0"),
+    // Subcommands
+    ("vl2facets", "", "1.8.0"),
+    ("vl2vgpng", "", "1.8.0"),
+    ("vl2labels", "", "1.8.0"),
+    ("freeze", "", "1.8.0"),
+    ("export", "", "1.8.0"),
+    ("report", "", "1.8.0"),
+    ("vg2stats", "", "1.8.0"),
+    ("bundle", "", "1.8.0"),
+    ("anonymize", "", "1.8.0"),
+    ("lint", "", "1.8.0"),
+    ("svg-diff", "", "1.8.0"),
+    ("verify-vendor", "", "1.8.0"),
+    ("health", "", "1.8.0"),
+    ("introspect", "", "1.8.0"),
+    // Options of the subcommands of BASE_VERSION
+    ("*", "dump_final_spec", "1.8.0"),
+    ("*", "explain_static", "1.8.0"),
+    ("*", "export_data", "1.8.0"),
+    ("*", "export_data_format", "1.8.0"),
+    ("*", "flatten_background", "1.8.0"),
+    ("*", "image_rendering", "1.8.0"),
+    ("*", "jpeg_background", "1.8.0"),
+    ("*", "no_antialias", "1.8.0"),
+    ("*", "pdfa", "1.8.0"),
+    ("*", "png_interlaced", "1.8.0"),
+    ("*", "ppi_mode", "1.8.0"),
+    ("*", "preserve_int64", "1.8.0"),
+    ("*", "shorten_auth_header", "1.8.0"),
+    ("*", "shorten_endpoint", "1.8.0"),
+    ("*", "shorten_fallback", "1.8.0"),
+    ("*", "shorten_response_field", "1.8.0"),
+    ("*", "shorten_timeout", "1.8.0"),
+    ("*", "source_dpi", "1.8.0"),
+    ("*", "static_fallback", "1.8.0"),
+    ("*", "static_fallback_scale", "1.8.0"),
+    ("*", "svg_minify", "1.8.0"),
+    ("*", "svg_native_tooltips", "1.8.0"),
+    ("*", "svg_precision", "1.8.0"),
+    ("*", "svg_text_length", "1.8.0"),
+    ("*", "template_file", "1.8.0"),
+    ("*", "warn_only", "1.8.0"),
+    ("vl2html", "allowed_base_url", "1.8.0"),
+    ("vl2html", "show_warnings", "1.8.0"),
+    ("vg2html", "allowed_base_url", "1.8.0"),
+    ("vg2svg", "config", "1.8.0"),
+    ("vg2svg", "theme", "1.8.0"),
+    ("vg2png", "config", "1.8.0"),
+    ("vg2png", "theme", "1.8.0"),
+    ("vg2jpeg", "config", "1.8.0"),
+    ("vg2jpeg", "theme", "1.8.0"),
+    ("vg2pdf", "config", "1.8.0"),
+    ("vg2pdf", "theme", "1.8.0"),
+    ("vg2html", "config", "1.8.0"),
+    ("vg2html", "theme", "1.8.0"),
+];
+
+/// Describe the subcommands and options of the command line interface as JSON, for wrappers
+/// that build vl-convert command lines.
+///
+/// The description is generated from the clap definitions of `command`, before clap adds its
+/// help and version flags. Options that apply to every subcommand are listed once, under
+/// `global_options`. Every subcommand and option has the version of vl-convert that added it
+/// under `since`
+pub fn cli_schema(command: &Command) -> Value {
+    json!({
+        "name": command.get_name(),
+        "version": command.get_version(),
+        "global_options": options_schema(command, ""),
+        "subcommands": command
+            .get_subcommands()
+            .map(|subcommand| {
+                let name = subcommand.get_name();
+                json!({
+                    "name": name,
+                    "about": subcommand.get_about().map(|about| about.to_string()),
+                    "since": since_version(name, ""),
+                    "options": options_schema(subcommand, name),
+                })
+            })
+            .collect::<Vec<_>>(),
+    })
+}
+
+/// Version of vl-convert that added an option of `subcommand` (empty for global options), or
+/// the subcommand itself when `option` is empty
+fn since_version(subcommand: &str, option: &str) -> &'static str {
+    let find = |subcommand: &str, option: &str| {
+        SINCE_VERSIONS
+            .iter()
+            .find(|(s, o, _)| *s == subcommand && *o == option)
+            .map(|(_, _, version)| *version)
+    };
+    let option_version = if option.is_empty() || subcommand.is_empty() {
+        None
+    } else {
+        find("*", option)
+    };
+    find(subcommand, option)
+        .or(option_version)
+        .or_else(|| find(subcommand, ""))
+        .unwrap_or(BASE_VERSION)
+}
+
+fn options_schema(command: &Command, subcommand: &str) -> Vec<Value> {
+    command
+        .get_arguments()
+        .filter(|arg| !arg.is_hide_set())
+        .map(|arg| option_schema(arg, subcommand))
+        .collect()
+}
+
+fn option_schema(arg: &Arg, subcommand: &str) -> Value {
+    let value_type = value_type(arg);
+    let mut schema = Map::new();
+    schema.insert("name".to_string(), Value::from(arg.get_id().as_str()));
+    if let Some(long) = arg.get_long() {
+        schema.insert("long".to_string(), Value::from(format!("--{long}")));
+    }
+    if let Some(short) = arg.get_short() {
+        schema.insert("short".to_string(), Value::from(format!("-{short}")));
+    }
+    schema.insert("type".to_string(), Value::from(value_type));
+    schema.insert(
+        "default".to_string(),
+        default_value(arg, value_type).unwrap_or(Value::Null),
+    );
+    let possible_values: Vec<_> = arg
+        .get_possible_values()
+        .iter()
+        .map(|value| Value::from(value.get_name()))
+        .collect();
+    if !possible_values.is_empty() && value_type != "boolean" {
+        schema.insert("possible_values".to_string(), Value::from(possible_values));
+    }
+    schema.insert("required".to_string(), Value::from(arg.is_required_set()));
+    schema.insert(
+        "repeatable".to_string(),
+        Value::from(matches!(arg.get_action(), ArgAction::Append)),
+    );
+    schema.insert(
+        "help".to_string(),
+        arg.get_help()
+            .map(|help| Value::from(help.to_string()))
+            .unwrap_or(Value::Null),
+    );
+    schema.insert(
+        "since".to_string(),
+        Value::from(since_version(subcommand, arg.get_id().as_str())),
+    );
+    Value::Object(schema)
+}
+
+/// JSON type of the values of an option: "boolean" for flags, "integer", "number", or
+/// "path" for options parsed as those types, and "string" otherwise
+fn value_type(arg: &Arg) -> &'static str {
+    if matches!(arg.get_action(), ArgAction::SetTrue | ArgAction::SetFalse) {
+        return "boolean";
+    }
+    let type_id = arg.get_value_parser().type_id();
+    let integer_types = [
+        TypeId::of::<u8>(),
+        TypeId::of::<u16>(),
+        TypeId::of::<u32>(),
+        TypeId::of::<u64>(),
+        TypeId::of::<usize>(),
+        TypeId::of::<i32>(),
+        TypeId::of::<i64>(),
+    ];
+    if integer_types.iter().any(|id| type_id == *id) {
+        "integer"
+    } else if type_id == TypeId::of::<f32>() || type_id == TypeId::of::<f64>() {
+        "number"
+    } else if type_id == TypeId::of::<PathBuf>() {
+        "path"
+    } else {
+        "string"
+    }
+}
+
+fn default_value(arg: &Arg, value_type: &str) -> Option<Value> {
+    if value_type == "boolean" {
+        return Some(Value::from(matches!(arg.get_action(), ArgAction::SetFalse)));
+    }
+    let default = arg.get_default_values().first()?.to_str()?;
+    Some(match value_type {
+        "integer" => default.parse::<i64>().map(Value::from).ok()?,
+        "number" => default.parse::<f64>().map(Value::from).ok()?,
+        _ => Value::from(default),
+    })
+}
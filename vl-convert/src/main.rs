@@ -1,15 +1,49 @@
 #![doc = include_str!("../README.md")]
 
-use clap::{arg, Parser, Subcommand};
-use itertools::Itertools;
+mod args;
+mod introspect;
+
+use clap::{arg, CommandFactory, Parser, Subcommand};
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
 use std::path::Path;
 use std::str::FromStr;
+use vl_convert_rs::anonymize::{anonymize_spec, AnonymizeOptions};
+use vl_convert_rs::background::BackgroundColor;
+use vl_convert_rs::config_overrides::parse_config_override;
+use vl_convert_rs::container_size::{DEFAULT_CONTAINER_HEIGHT, DEFAULT_CONTAINER_WIDTH};
 use vl_convert_rs::converter::{
-    vega_to_url, vegalite_to_url, FormatLocale, Renderer, TimeFormatLocale, VgOpts, VlConverter,
-    VlOpts,
+    directory_base_url, shorten_url, vega_to_url, vegalite_to_url, ExportFormat, ExportRequest,
+    FormatLocale, HtmlOpts, InteractionStep, JpegOpts, JpegResult, PdfOpts, PngOpts, PngResult,
+    PpiMode, Renderer, ShortenOptions, SvgOpts, SvgResult, TimeFormatLocale, VgOpts, VlConverter,
+    VlOpts, DEFAULT_SHORTEN_RESPONSE_FIELD,
 };
+use vl_convert_rs::data_export::{DataExportFormat, ExportedDataset};
+use vl_convert_rs::dataset_summary::DatasetSummary;
+use vl_convert_rs::direction::Direction;
+use vl_convert_rs::facets::FacetOpts;
+use vl_convert_rs::html::{get_vega_bundle, get_vegaembed_bundle};
+use vl_convert_rs::image_loading::set_ignore_exif_orientation;
+use vl_convert_rs::image_rendering::ImageRendering;
+use vl_convert_rs::lint::{lint_vega, LintSeverity};
+use vl_convert_rs::log_level::LogLevel;
 use vl_convert_rs::module_loader::import_map::VlVersion;
-use vl_convert_rs::text::register_font_directory;
+use vl_convert_rs::render_warnings::RenderWarning;
+use vl_convert_rs::resize::{parse_min_size, ResizeMode};
+use vl_convert_rs::resource_stats::get_last_resource_stats;
+use vl_convert_rs::row_limit::RowLimitStrategy;
+use vl_convert_rs::spec_limits::{
+    parse_json_spec, set_spec_limits, SpecLimits, DEFAULT_MAX_JSON_DEPTH, DEFAULT_MAX_SPEC_BYTES,
+    DEFAULT_MAX_VIEWS,
+};
+use vl_convert_rs::static_export::StaticExportSeverity;
+use vl_convert_rs::suggestions::{
+    format_locale_names, theme_names, time_format_locale_names, unknown_name_message,
+};
+use vl_convert_rs::suppress::Component;
+use vl_convert_rs::svg_diff::{compare_svgs, SvgCompareOptions, SvgDifferenceKind};
+use vl_convert_rs::text::{parse_generic_font_mapping, register_font_directory, set_emoji_font};
+use vl_convert_rs::vendor_integrity::verify_vendor_integrity;
 use vl_convert_rs::{anyhow, anyhow::bail};
 
 const DEFAULT_VL_VERSION: &str = "5.21";
@@ -21,6 +55,214 @@ const DEFAULT_CONFIG_PATH: &str = "~/.config/vl-convert/config.json";
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+
+    /// Maximum size, in bytes, of input specifications
+    #[arg(long, global = true, default_value_t = DEFAULT_MAX_SPEC_BYTES)]
+    max_spec_bytes: usize,
+
+    /// Maximum nesting depth of arrays and objects in input specifications
+    #[arg(long, global = true, default_value_t = DEFAULT_MAX_JSON_DEPTH)]
+    max_json_depth: usize,
+
+    /// Maximum number of views that a Vega-Lite spec may expand to through repeat, facet,
+    /// concat, and layer. Facets are counted by the distinct values of their fields in inline
+    /// data
+    #[arg(long, global = true, default_value_t = DEFAULT_MAX_VIEWS)]
+    max_views: usize,
+
+    /// JSON file with an object of subcommand options. Flags take precedence over these options
+    #[arg(long, global = true)]
+    options_json: Option<String>,
+
+    /// Collect memory statistics for the conversion and print them to stderr as JSON
+    #[arg(long, global = true)]
+    stats: bool,
+
+    /// Number of times to retry data and image requests that fail with a connection error or
+    /// 5xx response
+    #[arg(long, global = true)]
+    fetch_retries: Option<u32>,
+
+    /// Delay before the first retry of a failed request in milliseconds, doubled for each
+    /// subsequent retry
+    #[arg(long, global = true)]
+    fetch_retry_backoff_ms: Option<u64>,
+
+    /// Base URL that relative data urls and image hrefs are resolved against. Defaults to the
+    /// directory of the input file with --allow-local-data, and to
+    /// https://vega.github.io/vega-datasets/ otherwise
+    #[arg(long, global = true)]
+    base_url: Option<String>,
+
+    /// Allow data and images to be loaded from the local file system
+    #[arg(long, global = true)]
+    allow_local_data: bool,
+
+    /// Leave out images that fail to load instead of failing the conversion, and print a
+    /// warning for each of them
+    #[arg(long, global = true)]
+    lenient: bool,
+
+    /// Font to use for all text of Vega-Lite charts: axis, legend, and header labels and
+    /// titles, chart titles, and text marks. Config set by --config takes precedence
+    #[arg(long, global = true)]
+    font: Option<String>,
+
+    /// Factor to scale the default font sizes of all text of Vega-Lite charts by
+    #[arg(long, global = true)]
+    font_size_scale: Option<f32>,
+
+    /// Color to use for all text of Vega-Lite charts
+    #[arg(long, global = true)]
+    text_color: Option<String>,
+
+    /// Set a value of the config of Vega-Lite charts at a dotted path, e.g.
+    /// --set axis.labelFontSize=14. The value is parsed as JSON, or used as a string if it
+    /// isn't valid JSON. May be repeated, and is applied over --config and --theme
+    #[arg(long = "set", global = true, value_name = "PATH=VALUE", value_parser = parse_config_override)]
+    config_overrides: Vec<(String, serde_json::Value)>,
+
+    /// Writing direction to lay Vega-Lite charts out for, ltr or rtl. With rtl, the layout is
+    /// mirrored for right-to-left locales: y axes and row headers are drawn on the right,
+    /// legends on the left, and titles are anchored at the end. Orients and anchors set in the
+    /// spec or config take precedence
+    #[arg(long, global = true)]
+    direction: Option<String>,
+
+    /// Render a generic font family with a specific font in SVG, PNG, JPEG, and PDF output,
+    /// e.g. --map-font sans-serif=Inter. The generic family is one of sans-serif, serif,
+    /// monospace, or cursive. May be repeated
+    #[arg(long = "map-font", global = true, value_name = "GENERIC=FONT", value_parser = parse_generic_font_mapping)]
+    generic_font_mapping: Vec<(String, String)>,
+
+    /// Font family to draw emoji with in PNG, JPEG, and PDF output when the font of the text
+    /// doesn't have them. Defaults to the first installed of the common color emoji fonts,
+    /// like Noto Color Emoji and Apple Color Emoji
+    #[arg(long, global = true)]
+    emoji_font: Option<String>,
+
+    /// Width of the container that charts sized to their container (e.g. with Vega-Lite's
+    /// "width": "container") are rendered in
+    #[arg(long, global = true, default_value_t = DEFAULT_CONTAINER_WIDTH)]
+    container_width: f32,
+
+    /// Height of the container that charts sized to their container are rendered in
+    #[arg(long, global = true, default_value_t = DEFAULT_CONTAINER_HEIGHT)]
+    container_height: f32,
+
+    /// Fail the conversion of charts sized to their container, instead of rendering them with
+    /// --container-width and --container-height
+    #[arg(long, global = true)]
+    strict_sizing: bool,
+
+    /// Width to render charts with instead of their own, laid out as --resize-mode says
+    #[arg(long, global = true)]
+    width: Option<f32>,
+
+    /// Height to render charts with instead of their own, laid out as --resize-mode says
+    #[arg(long, global = true)]
+    height: Option<f32>,
+
+    /// How charts are laid out in --width and --height. One of fit (the size of the whole
+    /// chart, including axes, legends, and titles), pad (the size of the plot area, which
+    /// the chart grows around), or none (keep the autosize of the spec)
+    #[arg(long, global = true, default_value = "fit")]
+    resize_mode: String,
+
+    /// Smallest size to render charts with, as WIDTHxHEIGHT pixels (e.g. 100x100). Charts
+    /// that resolve to a size below 1 pixel, like those with empty data, are enlarged to it
+    /// instead of failing the conversion
+    #[arg(long, global = true, value_name = "WIDTHxHEIGHT", value_parser = parse_min_size)]
+    min_size: Option<(u32, u32)>,
+
+    /// Largest number of rows that a dataset of a chart may have once it's loaded, including
+    /// inline data and data fetched from urls. Datasets with more rows are handled according
+    /// to --row-limit-strategy
+    #[arg(long, global = true)]
+    max_rows: Option<u64>,
+
+    /// How datasets with more rows than --max-rows are handled. One of error (fail the
+    /// conversion), head (keep the first rows), or sample (keep a random sample of the rows).
+    /// The sample is drawn with a seed of 0, or another seed given as sample:SEED
+    #[arg(long, global = true, default_value = "error")]
+    row_limit_strategy: String,
+
+    /// Add a subtitle that notes the truncation to charts with datasets that were truncated to
+    /// --max-rows rows
+    #[arg(long, global = true)]
+    annotate_truncation: bool,
+
+    /// Level of the messages that Vega logs while charts are rendered. One of none, error,
+    /// warn, info, or debug. Warnings at that level are printed once the chart is converted
+    #[arg(long, global = true)]
+    vega_log_level: Option<String>,
+
+    /// Leave out warnings that contain this text, or that match it as a regular expression
+    /// when it's written as /pattern/. The number of warnings that were left out is printed
+    /// instead. May be repeated
+    #[arg(long = "suppress-warning", global = true, value_name = "FILTER")]
+    suppress_warning: Vec<String>,
+
+    /// Leave this component out of the image, for applications that draw it themselves. One
+    /// of title, legend, axis-x, axis-y, or grid. The chart is laid out without it. May be
+    /// repeated
+    #[arg(long = "suppress", global = true, value_name = "COMPONENT", value_parser = Component::from_str)]
+    suppress: Vec<Component>,
+
+    /// Rewrite the dates of the temporal fields of Vega-Lite specs' inline data that aren't
+    /// in the ISO 8601 format, like 03/27/2024 5:00 PM, to ISO 8601 before the chart is
+    /// rendered
+    #[arg(long, global = true)]
+    lenient_dates: bool,
+
+    /// Don't define the browser globals that expressions copied from web pages read, like
+    /// window, navigator.language, screen.width, and devicePixelRatio. Expressions that read
+    /// them fail with an error that names them instead
+    #[arg(long, global = true)]
+    no_browser_shims: bool,
+
+    /// Simplify the arcs of TopoJSON datasets as they're parsed, dropping the vertices that are
+    /// within this tolerance of the simplified lines, in the units of the topology's
+    /// coordinates (degrees for unprojected topologies). Borders that features share stay
+    /// shared. The reduction in the number of vertices is printed as a warning
+    #[arg(long, global = true, value_name = "TOLERANCE")]
+    simplify_topojson: Option<f64>,
+
+    /// Run the force transforms of Vega charts as static simulations of this many ticks before
+    /// they're rendered, so that their layout is reproducible. Force transforms that set static
+    /// or iterations keep them
+    #[arg(long, global = true)]
+    force_iterations: Option<u32>,
+
+    /// Seed for the random numbers of Vega charts, which are used by the random() expression
+    /// function and by transforms like sample
+    #[arg(long, global = true)]
+    random_seed: Option<u32>,
+
+    /// Draw JPEG images as their pixels are stored, ignoring their EXIF orientation. By
+    /// default, images are rotated and flipped as browsers display them
+    #[arg(long, global = true)]
+    ignore_exif_orientation: bool,
+
+    /// JSON file with a list of interaction steps to apply before rendering. Each step is
+    /// either {"signal": name, "value": value} or {"event": {"type": type, "x": x, "y": y}}
+    #[arg(long, global = true)]
+    interactions_file: Option<String>,
+
+    /// Minimum level of log messages to write to stderr. One of off, error, warn, info, debug,
+    /// trace. Defaults to the RUST_LOG environment variable, or error if it isn't set
+    #[arg(long, global = true)]
+    log_level: Option<log::LevelFilter>,
+
+    /// Suppress all output to stderr other than errors, including warnings requested with
+    /// --show-warnings or --log-level
+    #[arg(long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+
+    /// Print the time taken by the command, and the size and request hash of SVG, PNG, and JPEG
+    /// images, to stderr
+    #[arg(long, global = true)]
+    verbose: bool,
 }
 
 #[derive(Debug, Subcommand)]
@@ -55,6 +297,11 @@ enum Commands {
         /// Whether to show Vega-Lite compilation warnings
         #[arg(long)]
         show_warnings: bool,
+
+        /// Pass integers in inline data that JavaScript numbers can't represent exactly
+        /// (beyond ±2^53) through the conversion as strings, so they're written unchanged
+        #[arg(long)]
+        preserve_int64: bool,
     },
 
     /// Convert a Vega-Lite specification to an SVG image
@@ -72,9 +319,11 @@ enum Commands {
         #[arg(short, long, default_value = DEFAULT_VL_VERSION)]
         vl_version: String,
 
-        /// Named theme provided by the vegaThemes package (e.g. "dark")
+        /// Named theme provided by the vegaThemes package (e.g. "dark"). May be repeated to
+        /// render the chart with each theme, writing <OUTPUT STEM>.<THEME>.svg for each one.
+        /// The "default" theme renders the chart without a theme
         #[arg(long)]
-        theme: Option<String>,
+        theme: Vec<String>,
 
         /// Path to Vega-Lite config file. Defaults to ~/.config/vl-convert/config.json
         #[arg(short, long)]
@@ -99,6 +348,44 @@ enum Commands {
         /// d3-time-format locale name or file with .json extension
         #[arg(long)]
         time_format_locale: Option<String>,
+
+        /// Path to write the final Vega spec that the image is rendered from, after applying
+        /// the theme, config, and format locales
+        #[arg(long)]
+        dump_final_spec: Option<String>,
+
+        /// Directory to write the rows of the chart's datasets to once it's rendered, as
+        /// <OUTPUT NAME>.<DATASET>.<FORMAT>
+        #[arg(long)]
+        export_data: Option<String>,
+
+        /// Format of the dataset files written with --export-data. One of csv or json
+        #[arg(long, default_value = "csv")]
+        export_data_format: String,
+
+        /// Add textLength attributes to text elements so that applications which substitute
+        /// fonts (e.g. Inkscape or PowerPoint) reproduce the measured text layout
+        #[arg(long)]
+        svg_text_length: bool,
+
+        /// Write the tooltip content of marks as <title> elements, which browsers display as
+        /// native tooltips on hover
+        #[arg(long)]
+        svg_native_tooltips: bool,
+
+        /// Write the smallest SVG that draws the same image, without comments, accessibility
+        /// metadata, class names, or attributes set to their default values
+        #[arg(long)]
+        svg_minify: bool,
+
+        /// Number of decimals that numbers are rounded to in minified SVG
+        #[arg(long, requires = "svg_minify", default_value = "6")]
+        svg_precision: u8,
+
+        /// Print the interactive features of the chart, like selections, tooltips, and bound
+        /// inputs, that have no effect or are frozen in the static image
+        #[arg(long)]
+        explain_static: bool,
     },
 
     /// Convert a Vega-Lite specification to an PNG image
@@ -116,9 +403,11 @@ enum Commands {
         #[arg(short, long, default_value = DEFAULT_VL_VERSION)]
         vl_version: String,
 
-        /// Named theme provided by the vegaThemes package (e.g. "dark")
+        /// Named theme provided by the vegaThemes package (e.g. "dark"). May be repeated to
+        /// render the chart with each theme, writing <OUTPUT STEM>.<THEME>.png for each one.
+        /// The "default" theme renders the chart without a theme
         #[arg(long)]
-        theme: Option<String>,
+        theme: Vec<String>,
 
         /// Path to Vega-Lite config file. Defaults to ~/.config/vl-convert/config.json
         #[arg(short, long)]
@@ -132,6 +421,31 @@ enum Commands {
         #[arg(short, long, default_value = "72.0")]
         ppi: f32,
 
+        /// Write an interlaced (Adam7) PNG for progressive display
+        #[arg(long)]
+        png_interlaced: bool,
+
+        /// Draw shapes without antialiasing, with crisp edges on whole pixels, so that
+        /// adjacent rects (e.g. heatmap cells) don't show seams. Text is still antialiased
+        #[arg(long)]
+        no_antialias: bool,
+
+        /// How the ppi affects the image. One of resize-pixels, which multiplies the number of
+        /// pixels by ppi / 72, or metadata-only, which only writes the ppi to the PNG metadata
+        #[arg(long, default_value = "resize-pixels")]
+        ppi_mode: String,
+
+        /// How embedded raster images are resampled when they're scaled. One of auto, smooth,
+        /// or pixelated, which draws each pixel of an image as a sharp square. Images with an
+        /// image-rendering attribute of their own keep it
+        #[arg(long, default_value = "auto")]
+        image_rendering: String,
+
+        /// Color to composite the image over so that it has no transparent pixels, e.g. white
+        /// or "#f0f0f0". By default, transparent parts of the chart are left transparent
+        #[arg(long)]
+        flatten_background: Option<String>,
+
         /// Whether to show Vega-Lite compilation warnings
         #[arg(long)]
         show_warnings: bool,
@@ -151,16 +465,37 @@ enum Commands {
         /// d3-time-format locale name or file with .json extension
         #[arg(long)]
         time_format_locale: Option<String>,
+
+        /// Path to write the final Vega spec that the image is rendered from, after applying
+        /// the theme, config, and format locales
+        #[arg(long)]
+        dump_final_spec: Option<String>,
+
+        /// Directory to write the rows of the chart's datasets to once it's rendered, as
+        /// <OUTPUT NAME>.<DATASET>.<FORMAT>
+        #[arg(long)]
+        export_data: Option<String>,
+
+        /// Format of the dataset files written with --export-data. One of csv or json
+        #[arg(long, default_value = "csv")]
+        export_data_format: String,
+
+        /// Print the interactive features of the chart, like selections, tooltips, and bound
+        /// inputs, that have no effect or are frozen in the static image
+        #[arg(long)]
+        explain_static: bool,
     },
 
-    /// Convert a Vega-Lite specification to an JPEG image
+    /// Convert each cell of a faceted Vega-Lite specification to its own PNG image
     #[command(arg_required_else_help = true)]
-    Vl2jpeg {
+    Vl2facets {
         /// Path to input Vega-Lite file
         #[arg(short, long)]
         input: String,
 
-        /// Path to output JPEG file to be created
+        /// Path template of the PNG files to be created, with {row} and {column} replaced by
+        /// the values of the row and column fields of each cell (e.g. "out/{row}_{column}.png").
+        /// The cells of wrapped facets have the value of the facet field as their column
         #[arg(short, long)]
         output: String,
 
@@ -180,12 +515,12 @@ enum Commands {
         #[arg(long, default_value = "1.0")]
         scale: f32,
 
-        /// JPEG Quality between 0 (worst) and 100 (best)
-        #[arg(short, long, default_value = "90")]
-        quality: u8,
+        /// Pixels per inch
+        #[arg(short, long, default_value = "72.0")]
+        ppi: f32,
 
         /// Whether to show Vega-Lite compilation warnings
-        #[arg(short, long)]
+        #[arg(long)]
         show_warnings: bool,
 
         /// Additional directory to search for fonts
@@ -203,18 +538,27 @@ enum Commands {
         /// d3-time-format locale name or file with .json extension
         #[arg(long)]
         time_format_locale: Option<String>,
+
+        /// Include the headers and shared axes next to each cell in its image, along with the
+        /// plot area of the cell
+        #[arg(long)]
+        include_guides: bool,
     },
 
-    /// Convert a Vega-Lite specification to a PDF image
+    /// Convert a Vega-Lite specification to both a Vega specification and a PNG image
     #[command(arg_required_else_help = true)]
-    Vl2pdf {
+    Vl2vgpng {
         /// Path to input Vega-Lite file
         #[arg(short, long)]
         input: String,
 
-        /// Path to output PDF file to be created
-        #[arg(short, long)]
-        output: String,
+        /// Path to output Vega file to be created
+        #[arg(long)]
+        output_vega: String,
+
+        /// Path to output PNG file to be created
+        #[arg(long)]
+        output_png: String,
 
         /// Vega-Lite Version. One of 5.8, 5.14, 5.15, 5.16, 5.17, 5.18, 5.19, 5.20, 5.21
         #[arg(short, long, default_value = DEFAULT_VL_VERSION)]
@@ -228,6 +572,18 @@ enum Commands {
         #[arg(short, long)]
         config: Option<String>,
 
+        /// Pretty-print JSON in output Vega file
+        #[arg(long)]
+        pretty: bool,
+
+        /// Image scale factor
+        #[arg(long, default_value = "1.0")]
+        scale: f32,
+
+        /// Pixels per inch
+        #[arg(short, long, default_value = "72.0")]
+        ppi: f32,
+
         /// Whether to show Vega-Lite compilation warnings
         #[arg(long)]
         show_warnings: bool,
@@ -249,26 +605,14 @@ enum Commands {
         time_format_locale: Option<String>,
     },
 
-    /// Convert a Vega-Lite specification to a URL that opens the chart in the Vega editor
-    #[command(arg_required_else_help = true)]
-    Vl2url {
-        /// Path to input Vega-Lite file
-        #[arg(short, long)]
-        input: String,
-
-        /// Open chart in fullscreen mode
-        #[arg(long, default_value = "false")]
-        fullscreen: bool,
-    },
-
-    /// Convert a Vega-Lite specification to an HTML file
+    /// Convert a Vega-Lite specification to an JPEG image
     #[command(arg_required_else_help = true)]
-    Vl2html {
+    Vl2jpeg {
         /// Path to input Vega-Lite file
         #[arg(short, long)]
         input: String,
 
-        /// Path to output HTML file to be created
+        /// Path to output JPEG file to be created
         #[arg(short, long)]
         output: String,
 
@@ -284,10 +628,30 @@ enum Commands {
         #[arg(short, long)]
         config: Option<String>,
 
-        /// Whether to bundle JavaScript dependencies in the HTML file
-        /// instead of loading them from a CDN
+        /// Image scale factor
+        #[arg(long, default_value = "1.0")]
+        scale: f32,
+
+        /// JPEG Quality between 1 (worst) and 100 (best)
+        #[arg(short, long, default_value = "90")]
+        quality: u8,
+
+        /// Color to composite the image over, since JPEG images can't be transparent, e.g.
+        /// white or "#f0f0f0"
+        #[arg(long, default_value = "white")]
+        jpeg_background: String,
+
+        /// Whether to show Vega-Lite compilation warnings
         #[arg(short, long)]
-        bundle: bool,
+        show_warnings: bool,
+
+        /// Additional directory to search for fonts
+        #[arg(long)]
+        font_dir: Option<String>,
+
+        /// Allowed base URL for external data requests. Default allows any base URL
+        #[arg(short, long)]
+        allowed_base_url: Option<Vec<String>>,
 
         /// d3-format locale name or file with .json extension
         #[arg(long)]
@@ -297,22 +661,48 @@ enum Commands {
         #[arg(long)]
         time_format_locale: Option<String>,
 
-        /// Vega renderer. One of 'svg' (default), 'canvas', or 'hybrid'
+        /// Path to write the final Vega spec that the image is rendered from, after applying
+        /// the theme, config, and format locales
         #[arg(long)]
-        renderer: Option<String>,
+        dump_final_spec: Option<String>,
+
+        /// Directory to write the rows of the chart's datasets to once it's rendered, as
+        /// <OUTPUT NAME>.<DATASET>.<FORMAT>
+        #[arg(long)]
+        export_data: Option<String>,
+
+        /// Format of the dataset files written with --export-data. One of csv or json
+        #[arg(long, default_value = "csv")]
+        export_data_format: String,
     },
 
-    /// Convert a Vega specification to an SVG image
+    /// Convert a Vega-Lite specification to a PDF image
     #[command(arg_required_else_help = true)]
-    Vg2svg {
-        /// Path to input Vega file
+    Vl2pdf {
+        /// Path to input Vega-Lite file
         #[arg(short, long)]
         input: String,
 
-        /// Path to output SVG file to be created
+        /// Path to output PDF file to be created
         #[arg(short, long)]
         output: String,
 
+        /// Vega-Lite Version. One of 5.8, 5.14, 5.15, 5.16, 5.17, 5.18, 5.19, 5.20, 5.21
+        #[arg(short, long, default_value = DEFAULT_VL_VERSION)]
+        vl_version: String,
+
+        /// Named theme provided by the vegaThemes package (e.g. "dark")
+        #[arg(long)]
+        theme: Option<String>,
+
+        /// Path to Vega-Lite config file. Defaults to ~/.config/vl-convert/config.json
+        #[arg(short, long)]
+        config: Option<String>,
+
+        /// Whether to show Vega-Lite compilation warnings
+        #[arg(long)]
+        show_warnings: bool,
+
         /// Additional directory to search for fonts
         #[arg(long)]
         font_dir: Option<String>,
@@ -328,26 +718,58 @@ enum Commands {
         /// d3-time-format locale name or file with .json extension
         #[arg(long)]
         time_format_locale: Option<String>,
+
+        /// Write a PDF/A-2b document for archiving, with XMP metadata, an sRGB output intent,
+        /// and a document ID
+        #[arg(long)]
+        pdfa: bool,
+
+        /// Pixels per inch of the image, which sets the size of the PDF page in points. The
+        /// default of 96 matches CSS pixels
+        #[arg(long, default_value = "96.0")]
+        source_dpi: f32,
+
+        /// Path to write the final Vega spec that the image is rendered from, after applying
+        /// the theme, config, and format locales
+        #[arg(long)]
+        dump_final_spec: Option<String>,
+
+        /// Directory to write the rows of the chart's datasets to once it's rendered, as
+        /// <OUTPUT NAME>.<DATASET>.<FORMAT>
+        #[arg(long)]
+        export_data: Option<String>,
+
+        /// Format of the dataset files written with --export-data. One of csv or json
+        #[arg(long, default_value = "csv")]
+        export_data_format: String,
     },
 
-    /// Convert a Vega specification to an PNG image
+    /// Extract the visible text in a Vega-Lite chart as JSON lines
     #[command(arg_required_else_help = true)]
-    Vg2png {
-        /// Path to input Vega file
+    Vl2labels {
+        /// Path to input Vega-Lite file
         #[arg(short, long)]
         input: String,
 
-        /// Path to output PNG file to be created
+        /// Path to output JSON lines file to be created. Defaults to stdout
         #[arg(short, long)]
-        output: String,
+        output: Option<String>,
 
-        /// Image scale factor
-        #[arg(long, default_value = "1.0")]
-        scale: f32,
+        /// Vega-Lite Version. One of 5.8, 5.14, 5.15, 5.16, 5.17, 5.18, 5.19, 5.20, 5.21
+        #[arg(short, long, default_value = DEFAULT_VL_VERSION)]
+        vl_version: String,
 
-        /// Pixels per inch
-        #[arg(short, long, default_value = "72.0")]
-        ppi: f32,
+        /// Named theme provided by the vegaThemes package (e.g. "dark")
+        #[arg(long)]
+        theme: Option<String>,
+
+        /// Path to Vega-Lite config file. Defaults to ~/.config/vl-convert/config.json
+        #[arg(short, long)]
+        config: Option<String>,
+
+        /// Whether to show Vega-Lite compilation warnings
+        #[arg(long)]
+        show_warnings: bool,
 
         /// Additional directory to search for fonts
         #[arg(long)]
@@ -366,53 +788,98 @@ enum Commands {
         time_format_locale: Option<String>,
     },
 
-    /// Convert a Vega specification to an JPEG image
+    /// Freeze a Vega-Lite specification into a self-contained specification with remote data
+    /// inlined and the theme and config merged, so that it renders without network access
     #[command(arg_required_else_help = true)]
-    Vg2jpeg {
-        /// Path to input Vega file
+    Freeze {
+        /// Path to input Vega-Lite file
         #[arg(short, long)]
         input: String,
 
-        /// Path to output JPEG file to be created
+        /// Path to output Vega-Lite file to be created
         #[arg(short, long)]
         output: String,
 
-        /// Image scale factor
-        #[arg(long, default_value = "1.0")]
-        scale: f32,
+        /// Vega-Lite Version. One of 5.8, 5.14, 5.15, 5.16, 5.17, 5.18, 5.19, 5.20, 5.21
+        #[arg(short, long, default_value = DEFAULT_VL_VERSION)]
+        vl_version: String,
 
-        /// JPEG Quality between 0 (worst) and 100 (best)
-        #[arg(short, long, default_value = "90")]
-        quality: u8,
+        /// Named theme provided by the vegaThemes package (e.g. "dark")
+        #[arg(short, long)]
+        theme: Option<String>,
 
-        /// Additional directory to search for fonts
-        #[arg(long)]
-        font_dir: Option<String>,
+        /// Path to Vega-Lite config file. Defaults to ~/.config/vl-convert/config.json
+        #[arg(short, long)]
+        config: Option<String>,
 
         /// Allowed base URL for external data requests. Default allows any base URL
         #[arg(short, long)]
         allowed_base_url: Option<Vec<String>>,
 
-        /// d3-format locale name or file with .json extension
-        #[arg(long)]
-        format_locale: Option<String>,
+        /// Pretty-print JSON in output file
+        #[arg(short, long)]
+        pretty: bool,
 
-        /// d3-time-format locale name or file with .json extension
+        /// Pass integers in inline data that JavaScript numbers can't represent exactly
+        /// (beyond ±2^53) through the conversion as strings, so they're written unchanged
         #[arg(long)]
-        time_format_locale: Option<String>,
+        preserve_int64: bool,
     },
 
-    /// Convert a Vega specification to an PDF image
+    /// Convert a Vega-Lite specification to several image formats, compiling and rendering
+    /// the chart only once
     #[command(arg_required_else_help = true)]
-    Vg2pdf {
-        /// Path to input Vega file
+    Export {
+        /// Path to input Vega-Lite file
         #[arg(short, long)]
         input: String,
 
-        /// Path to output PDF file to be created
+        /// Output path without an extension. Each format is written to <OUTPUT>.<FORMAT>
         #[arg(short, long)]
         output: String,
 
+        /// Comma-separated list of formats to write. Any of svg, png, jpeg, and pdf
+        #[arg(short, long, value_delimiter = ',', required = true)]
+        formats: Vec<String>,
+
+        /// Vega-Lite Version. One of 5.8, 5.14, 5.15, 5.16, 5.17, 5.18, 5.19, 5.20, 5.21
+        #[arg(short, long, default_value = DEFAULT_VL_VERSION)]
+        vl_version: String,
+
+        /// Named theme provided by the vegaThemes package (e.g. "dark")
+        #[arg(long)]
+        theme: Option<String>,
+
+        /// Path to Vega-Lite config file. Defaults to ~/.config/vl-convert/config.json
+        #[arg(short, long)]
+        config: Option<String>,
+
+        /// Image scale factor for PNG and JPEG output
+        #[arg(long, default_value = "1.0")]
+        scale: f32,
+
+        /// Pixels per inch for PNG output
+        #[arg(short, long, default_value = "72.0")]
+        ppi: f32,
+
+        /// JPEG quality between 1 (worst) and 100 (best)
+        #[arg(short, long, default_value = "90")]
+        quality: u8,
+
+        /// Color to composite the JPEG image over, since JPEG images can't be transparent, e.g.
+        /// white or "#f0f0f0"
+        #[arg(long, default_value = "white")]
+        jpeg_background: String,
+
+        /// Color to composite the PNG image over so that it has no transparent pixels, e.g.
+        /// white or "#f0f0f0". By default, transparent parts of the chart are left transparent
+        #[arg(long)]
+        flatten_background: Option<String>,
+
+        /// Whether to show Vega-Lite compilation warnings
+        #[arg(long)]
+        show_warnings: bool,
+
         /// Additional directory to search for fonts
         #[arg(long)]
         font_dir: Option<String>,
@@ -428,31 +895,78 @@ enum Commands {
         /// d3-time-format locale name or file with .json extension
         #[arg(long)]
         time_format_locale: Option<String>,
+
+        /// Directory to write the rows of the chart's datasets to once it's rendered, as
+        /// <OUTPUT NAME>.<DATASET>.<FORMAT>
+        #[arg(long)]
+        export_data: Option<String>,
+
+        /// Format of the dataset files written with --export-data. One of csv or json
+        #[arg(long, default_value = "csv")]
+        export_data_format: String,
     },
 
-    /// Convert a Vega specification to a URL that opens the chart in the Vega editor
+    /// Convert a Vega-Lite specification to a URL that opens the chart in the Vega editor
     #[command(arg_required_else_help = true)]
-    Vg2url {
-        /// Path to input Vega file
+    Vl2url {
+        /// Path to input Vega-Lite file
         #[arg(short, long)]
         input: String,
 
         /// Open chart in fullscreen mode
         #[arg(long, default_value = "false")]
         fullscreen: bool,
-    },
 
-    /// Convert a Vega specification to an HTML file
-    #[command(arg_required_else_help = true)]
-    Vg2html {
-        /// Path to input Vega file
-        #[arg(short, long)]
-        input: String,
+        /// URL shortening service to POST the editor URL to, as {"url": "<editor URL>"}, and
+        /// print the short URL from its JSON response instead. URLs aren't sent anywhere
+        /// unless this is given
+        #[arg(long)]
+        shorten_endpoint: Option<String>,
 
-        /// Path to output HTML file to be created
+        /// Value of the Authorization header of the request to the URL shortening service
+        #[arg(long)]
+        shorten_auth_header: Option<String>,
+
+        /// Field of the URL shortening service's JSON response that holds the short URL
+        #[arg(long, default_value = DEFAULT_SHORTEN_RESPONSE_FIELD)]
+        shorten_response_field: String,
+
+        /// Timeout of the request to the URL shortening service, in seconds
+        #[arg(long, default_value = "10")]
+        shorten_timeout: u64,
+
+        /// Print the unshortened URL, with a warning, if the URL can't be shortened
+        #[arg(long)]
+        shorten_fallback: bool,
+    },
+
+    /// Convert a Vega-Lite specification to an HTML file
+    #[command(arg_required_else_help = true)]
+    Vl2html {
+        /// Path to input Vega-Lite file
+        #[arg(short, long)]
+        input: String,
+
+        /// Path to output HTML file to be created
         #[arg(short, long)]
         output: String,
 
+        /// Vega-Lite Version. One of 5.8, 5.14, 5.15, 5.16, 5.17, 5.18, 5.19, 5.20, 5.21
+        #[arg(short, long, default_value = DEFAULT_VL_VERSION)]
+        vl_version: String,
+
+        /// Named theme provided by the vegaThemes package (e.g. "dark")
+        #[arg(long)]
+        theme: Option<String>,
+
+        /// Path to Vega-Lite config file. Defaults to ~/.config/vl-convert/config.json
+        #[arg(short, long)]
+        config: Option<String>,
+
+        /// Whether to show Vega-Lite compilation warnings
+        #[arg(long)]
+        show_warnings: bool,
+
         /// Whether to bundle JavaScript dependencies in the HTML file
         /// instead of loading them from a CDN
         #[arg(short, long)]
@@ -469,12 +983,180 @@ enum Commands {
         /// Vega renderer. One of 'svg' (default), 'canvas', or 'hybrid'
         #[arg(long)]
         renderer: Option<String>,
+
+        /// Embed a PNG rendering of the chart, which is shown until the chart is rendered
+        /// and in viewers that don't run scripts
+        #[arg(long)]
+        static_fallback: bool,
+
+        /// Scale of the fallback PNG image
+        #[arg(long, default_value = "2.0")]
+        static_fallback_scale: f32,
+
+        /// Allowed base URL for data and images the chart loads. The HTML file is not
+        /// written if the spec loads from other URLs, and it limits requests to the
+        /// origins of the allowed base URLs. Default allows any base URL
+        #[arg(short, long)]
+        allowed_base_url: Option<Vec<String>>,
+
+        /// Print a warning for URLs that aren't allowed instead of failing
+        #[arg(long)]
+        warn_only: bool,
+
+        /// Path to an HTML template file to write the document with, which has {{ scripts }},
+        /// {{ element_id }}, {{ spec }}, and {{ embed_options }} placeholders
+        #[arg(long)]
+        template_file: Option<String>,
     },
 
-    /// Convert an SVG image to a PNG image
+    /// Convert several Vega-Lite specifications to a single HTML report with one section
+    /// per chart
     #[command(arg_required_else_help = true)]
-    Svg2png {
-        /// Path to input SVG file
+    Report {
+        /// Path to input Vega-Lite file. May be repeated, and charts appear in the report in
+        /// the order of the inputs
+        #[arg(short, long, required = true)]
+        input: Vec<String>,
+
+        /// Title of the chart for the corresponding input. May be repeated, and inputs
+        /// without a title are left untitled
+        #[arg(short, long)]
+        title: Vec<String>,
+
+        /// Path to output HTML file to be created
+        #[arg(short, long)]
+        output: String,
+
+        /// Vega-Lite Version. One of 5.8, 5.14, 5.15, 5.16, 5.17, 5.18, 5.19, 5.20, 5.21
+        #[arg(short, long, default_value = DEFAULT_VL_VERSION)]
+        vl_version: String,
+
+        /// Named theme provided by the vegaThemes package (e.g. "dark")
+        #[arg(long)]
+        theme: Option<String>,
+
+        /// Path to Vega-Lite config file. Defaults to ~/.config/vl-convert/config.json
+        #[arg(short, long)]
+        config: Option<String>,
+
+        /// Whether to bundle JavaScript dependencies in the HTML file
+        /// instead of loading them from a CDN
+        #[arg(short, long)]
+        bundle: bool,
+
+        /// d3-format locale name or file with .json extension
+        #[arg(long)]
+        format_locale: Option<String>,
+
+        /// d3-time-format locale name or file with .json extension
+        #[arg(long)]
+        time_format_locale: Option<String>,
+
+        /// Vega renderer. One of 'svg' (default), 'canvas', or 'hybrid'
+        #[arg(long)]
+        renderer: Option<String>,
+    },
+
+    /// Convert a Vega specification to an SVG image
+    #[command(arg_required_else_help = true)]
+    Vg2svg {
+        /// Path to input Vega file
+        #[arg(short, long)]
+        input: String,
+
+        /// Path to output SVG file to be created
+        #[arg(short, long)]
+        output: String,
+
+        /// Named theme provided by the vegaThemes package (e.g. "dark")
+        #[arg(long)]
+        theme: Option<String>,
+
+        /// Path to Vega config file
+        #[arg(short, long)]
+        config: Option<String>,
+
+        /// Additional directory to search for fonts
+        #[arg(long)]
+        font_dir: Option<String>,
+
+        /// Allowed base URL for external data requests. Default allows any base URL
+        #[arg(short, long)]
+        allowed_base_url: Option<Vec<String>>,
+
+        /// d3-format locale name or file with .json extension
+        #[arg(long)]
+        format_locale: Option<String>,
+
+        /// d3-time-format locale name or file with .json extension
+        #[arg(long)]
+        time_format_locale: Option<String>,
+
+        /// Path to write the final Vega spec that the image is rendered from, after applying
+        /// the theme, config, and format locales
+        #[arg(long)]
+        dump_final_spec: Option<String>,
+
+        /// Add textLength attributes to text elements so that applications which substitute
+        /// fonts (e.g. Inkscape or PowerPoint) reproduce the measured text layout
+        #[arg(long)]
+        svg_text_length: bool,
+
+        /// Write the tooltip content of marks as <title> elements, which browsers display as
+        /// native tooltips on hover
+        #[arg(long)]
+        svg_native_tooltips: bool,
+
+        /// Write the smallest SVG that draws the same image, without comments, accessibility
+        /// metadata, class names, or attributes set to their default values
+        #[arg(long)]
+        svg_minify: bool,
+
+        /// Number of decimals that numbers are rounded to in minified SVG
+        #[arg(long, requires = "svg_minify", default_value = "6")]
+        svg_precision: u8,
+    },
+
+    /// Count the groups, items, and scenegraph nodes of a rendered Vega chart as JSON
+    #[command(arg_required_else_help = true)]
+    Vg2stats {
+        /// Path to input Vega file
+        #[arg(short, long)]
+        input: String,
+
+        /// Path to output JSON file to be created. Defaults to stdout
+        #[arg(short, long)]
+        output: Option<String>,
+
+        /// Named theme provided by the vegaThemes package (e.g. "dark")
+        #[arg(long)]
+        theme: Option<String>,
+
+        /// Path to Vega config file
+        #[arg(short, long)]
+        config: Option<String>,
+
+        /// Additional directory to search for fonts
+        #[arg(long)]
+        font_dir: Option<String>,
+
+        /// Allowed base URL for external data requests. Default allows any base URL
+        #[arg(short, long)]
+        allowed_base_url: Option<Vec<String>>,
+
+        /// d3-format locale name or file with .json extension
+        #[arg(long)]
+        format_locale: Option<String>,
+
+        /// d3-time-format locale name or file with .json extension
+        #[arg(long)]
+        time_format_locale: Option<String>,
+    },
+
+    /// Convert a Vega specification to an PNG image
+    #[command(arg_required_else_help = true)]
+    Vg2png {
+        /// Path to input Vega file
         #[arg(short, long)]
         input: String,
 
@@ -482,6 +1164,14 @@ enum Commands {
         #[arg(short, long)]
         output: String,
 
+        /// Named theme provided by the vegaThemes package (e.g. "dark")
+        #[arg(long)]
+        theme: Option<String>,
+
+        /// Path to Vega config file
+        #[arg(short, long)]
+        config: Option<String>,
+
         /// Image scale factor
         #[arg(long, default_value = "1.0")]
         scale: f32,
@@ -490,15 +1180,57 @@ enum Commands {
         #[arg(short, long, default_value = "72.0")]
         ppi: f32,
 
+        /// Write an interlaced (Adam7) PNG for progressive display
+        #[arg(long)]
+        png_interlaced: bool,
+
+        /// Draw shapes without antialiasing, with crisp edges on whole pixels, so that
+        /// adjacent rects (e.g. heatmap cells) don't show seams. Text is still antialiased
+        #[arg(long)]
+        no_antialias: bool,
+
+        /// How the ppi affects the image. One of resize-pixels, which multiplies the number of
+        /// pixels by ppi / 72, or metadata-only, which only writes the ppi to the PNG metadata
+        #[arg(long, default_value = "resize-pixels")]
+        ppi_mode: String,
+
+        /// How embedded raster images are resampled when they're scaled. One of auto, smooth,
+        /// or pixelated, which draws each pixel of an image as a sharp square. Images with an
+        /// image-rendering attribute of their own keep it
+        #[arg(long, default_value = "auto")]
+        image_rendering: String,
+
+        /// Color to composite the image over so that it has no transparent pixels, e.g. white
+        /// or "#f0f0f0". By default, transparent parts of the chart are left transparent
+        #[arg(long)]
+        flatten_background: Option<String>,
+
         /// Additional directory to search for fonts
         #[arg(long)]
         font_dir: Option<String>,
+
+        /// Allowed base URL for external data requests. Default allows any base URL
+        #[arg(short, long)]
+        allowed_base_url: Option<Vec<String>>,
+
+        /// d3-format locale name or file with .json extension
+        #[arg(long)]
+        format_locale: Option<String>,
+
+        /// d3-time-format locale name or file with .json extension
+        #[arg(long)]
+        time_format_locale: Option<String>,
+
+        /// Path to write the final Vega spec that the image is rendered from, after applying
+        /// the theme, config, and format locales
+        #[arg(long)]
+        dump_final_spec: Option<String>,
     },
 
-    /// Convert an SVG image to a JPEG image
+    /// Convert a Vega specification to an JPEG image
     #[command(arg_required_else_help = true)]
-    Svg2jpeg {
-        /// Path to input SVG file
+    Vg2jpeg {
+        /// Path to input Vega file
         #[arg(short, long)]
         input: String,
 
@@ -506,23 +1238,53 @@ enum Commands {
         #[arg(short, long)]
         output: String,
 
+        /// Named theme provided by the vegaThemes package (e.g. "dark")
+        #[arg(long)]
+        theme: Option<String>,
+
+        /// Path to Vega config file
+        #[arg(short, long)]
+        config: Option<String>,
+
         /// Image scale factor
         #[arg(long, default_value = "1.0")]
         scale: f32,
 
-        /// JPEG Quality between 0 (worst) and 100 (best)
+        /// JPEG Quality between 1 (worst) and 100 (best)
         #[arg(short, long, default_value = "90")]
         quality: u8,
 
+        /// Color to composite the image over, since JPEG images can't be transparent, e.g.
+        /// white or "#f0f0f0"
+        #[arg(long, default_value = "white")]
+        jpeg_background: String,
+
         /// Additional directory to search for fonts
         #[arg(long)]
         font_dir: Option<String>,
+
+        /// Allowed base URL for external data requests. Default allows any base URL
+        #[arg(short, long)]
+        allowed_base_url: Option<Vec<String>>,
+
+        /// d3-format locale name or file with .json extension
+        #[arg(long)]
+        format_locale: Option<String>,
+
+        /// d3-time-format locale name or file with .json extension
+        #[arg(long)]
+        time_format_locale: Option<String>,
+
+        /// Path to write the final Vega spec that the image is rendered from, after applying
+        /// the theme, config, and format locales
+        #[arg(long)]
+        dump_final_spec: Option<String>,
     },
 
-    /// Convert an SVG image to a PDF image
+    /// Convert a Vega specification to an PDF image
     #[command(arg_required_else_help = true)]
-    Svg2pdf {
-        /// Path to input SVG file
+    Vg2pdf {
+        /// Path to input Vega file
         #[arg(short, long)]
         input: String,
 
@@ -530,26 +1292,414 @@ enum Commands {
         #[arg(short, long)]
         output: String,
 
+        /// Named theme provided by the vegaThemes package (e.g. "dark")
+        #[arg(long)]
+        theme: Option<String>,
+
+        /// Path to Vega config file
+        #[arg(short, long)]
+        config: Option<String>,
+
         /// Additional directory to search for fonts
         #[arg(long)]
         font_dir: Option<String>,
-    },
 
-    /// List available themes
-    LsThemes,
+        /// Allowed base URL for external data requests. Default allows any base URL
+        #[arg(short, long)]
+        allowed_base_url: Option<Vec<String>>,
 
-    /// Print the config JSON for a theme
-    #[command(arg_required_else_help = true)]
-    CatTheme {
-        /// Name of a theme
-        theme: String,
+        /// d3-format locale name or file with .json extension
+        #[arg(long)]
+        format_locale: Option<String>,
+
+        /// d3-time-format locale name or file with .json extension
+        #[arg(long)]
+        time_format_locale: Option<String>,
+
+        /// Write a PDF/A-2b document for archiving, with XMP metadata, an sRGB output intent,
+        /// and a document ID
+        #[arg(long)]
+        pdfa: bool,
+
+        /// Pixels per inch of the image, which sets the size of the PDF page in points. The
+        /// default of 96 matches CSS pixels
+        #[arg(long, default_value = "96.0")]
+        source_dpi: f32,
+
+        /// Path to write the final Vega spec that the image is rendered from, after applying
+        /// the theme, config, and format locales
+        #[arg(long)]
+        dump_final_spec: Option<String>,
     },
-}
 
-#[tokio::main]
-async fn main() -> Result<(), anyhow::Error> {
-    let args = Cli::parse();
-    use crate::Commands::*;
+    /// Convert a Vega specification to a URL that opens the chart in the Vega editor
+    #[command(arg_required_else_help = true)]
+    Vg2url {
+        /// Path to input Vega file
+        #[arg(short, long)]
+        input: String,
+
+        /// Open chart in fullscreen mode
+        #[arg(long, default_value = "false")]
+        fullscreen: bool,
+
+        /// URL shortening service to POST the editor URL to, as {"url": "<editor URL>"}, and
+        /// print the short URL from its JSON response instead. URLs aren't sent anywhere
+        /// unless this is given
+        #[arg(long)]
+        shorten_endpoint: Option<String>,
+
+        /// Value of the Authorization header of the request to the URL shortening service
+        #[arg(long)]
+        shorten_auth_header: Option<String>,
+
+        /// Field of the URL shortening service's JSON response that holds the short URL
+        #[arg(long, default_value = DEFAULT_SHORTEN_RESPONSE_FIELD)]
+        shorten_response_field: String,
+
+        /// Timeout of the request to the URL shortening service, in seconds
+        #[arg(long, default_value = "10")]
+        shorten_timeout: u64,
+
+        /// Print the unshortened URL, with a warning, if the URL can't be shortened
+        #[arg(long)]
+        shorten_fallback: bool,
+    },
+
+    /// Convert a Vega specification to an HTML file
+    #[command(arg_required_else_help = true)]
+    Vg2html {
+        /// Path to input Vega file
+        #[arg(short, long)]
+        input: String,
+
+        /// Path to output HTML file to be created
+        #[arg(short, long)]
+        output: String,
+
+        /// Named theme provided by the vegaThemes package (e.g. "dark")
+        #[arg(long)]
+        theme: Option<String>,
+
+        /// Path to Vega config file
+        #[arg(short, long)]
+        config: Option<String>,
+
+        /// Whether to bundle JavaScript dependencies in the HTML file
+        /// instead of loading them from a CDN
+        #[arg(short, long)]
+        bundle: bool,
+
+        /// d3-format locale name or file with .json extension
+        #[arg(long)]
+        format_locale: Option<String>,
+
+        /// d3-time-format locale name or file with .json extension
+        #[arg(long)]
+        time_format_locale: Option<String>,
+
+        /// Vega renderer. One of 'svg' (default), 'canvas', or 'hybrid'
+        #[arg(long)]
+        renderer: Option<String>,
+
+        /// Embed a PNG rendering of the chart, which is shown until the chart is rendered
+        /// and in viewers that don't run scripts
+        #[arg(long)]
+        static_fallback: bool,
+
+        /// Scale of the fallback PNG image
+        #[arg(long, default_value = "2.0")]
+        static_fallback_scale: f32,
+
+        /// Allowed base URL for data and images the chart loads. The HTML file is not
+        /// written if the spec loads from other URLs, and it limits requests to the
+        /// origins of the allowed base URLs. Default allows any base URL
+        #[arg(short, long)]
+        allowed_base_url: Option<Vec<String>>,
+
+        /// Print a warning for URLs that aren't allowed instead of failing
+        #[arg(long)]
+        warn_only: bool,
+
+        /// Path to an HTML template file to write the document with, which has {{ scripts }},
+        /// {{ element_id }}, {{ spec }}, and {{ embed_options }} placeholders
+        #[arg(long)]
+        template_file: Option<String>,
+    },
+
+    /// Convert an SVG image to a PNG image
+    #[command(arg_required_else_help = true)]
+    Svg2png {
+        /// Path to input SVG file
+        #[arg(short, long)]
+        input: String,
+
+        /// Path to output PNG file to be created
+        #[arg(short, long)]
+        output: String,
+
+        /// Image scale factor
+        #[arg(long, default_value = "1.0")]
+        scale: f32,
+
+        /// Pixels per inch
+        #[arg(short, long, default_value = "72.0")]
+        ppi: f32,
+
+        /// Write an interlaced (Adam7) PNG for progressive display
+        #[arg(long)]
+        png_interlaced: bool,
+
+        /// Draw shapes without antialiasing, with crisp edges on whole pixels, so that
+        /// adjacent rects (e.g. heatmap cells) don't show seams. Text is still antialiased
+        #[arg(long)]
+        no_antialias: bool,
+
+        /// How the ppi affects the image. One of resize-pixels, which multiplies the number of
+        /// pixels by ppi / 72, or metadata-only, which only writes the ppi to the PNG metadata
+        #[arg(long, default_value = "resize-pixels")]
+        ppi_mode: String,
+
+        /// How embedded raster images are resampled when they're scaled. One of auto, smooth,
+        /// or pixelated, which draws each pixel of an image as a sharp square. Images with an
+        /// image-rendering attribute of their own keep it
+        #[arg(long, default_value = "auto")]
+        image_rendering: String,
+
+        /// Color to composite the image over so that it has no transparent pixels, e.g. white
+        /// or "#f0f0f0". By default, transparent parts of the chart are left transparent
+        #[arg(long)]
+        flatten_background: Option<String>,
+
+        /// Additional directory to search for fonts
+        #[arg(long)]
+        font_dir: Option<String>,
+    },
+
+    /// Convert an SVG image to a JPEG image
+    #[command(arg_required_else_help = true)]
+    Svg2jpeg {
+        /// Path to input SVG file
+        #[arg(short, long)]
+        input: String,
+
+        /// Path to output JPEG file to be created
+        #[arg(short, long)]
+        output: String,
+
+        /// Image scale factor
+        #[arg(long, default_value = "1.0")]
+        scale: f32,
+
+        /// JPEG Quality between 1 (worst) and 100 (best)
+        #[arg(short, long, default_value = "90")]
+        quality: u8,
+
+        /// Color to composite the image over, since JPEG images can't be transparent, e.g.
+        /// white or "#f0f0f0"
+        #[arg(long, default_value = "white")]
+        jpeg_background: String,
+
+        /// Additional directory to search for fonts
+        #[arg(long)]
+        font_dir: Option<String>,
+    },
+
+    /// Convert an SVG image to a PDF image
+    #[command(arg_required_else_help = true)]
+    Svg2pdf {
+        /// Path to input SVG file
+        #[arg(short, long)]
+        input: String,
+
+        /// Path to output PDF file to be created
+        #[arg(short, long)]
+        output: String,
+
+        /// Additional directory to search for fonts
+        #[arg(long)]
+        font_dir: Option<String>,
+
+        /// Write a PDF/A-2b document for archiving, with XMP metadata, an sRGB output intent,
+        /// and a document ID
+        #[arg(long)]
+        pdfa: bool,
+
+        /// Pixels per inch of the image, which sets the size of the PDF page in points. The
+        /// default of 96 matches CSS pixels
+        #[arg(long, default_value = "96.0")]
+        source_dpi: f32,
+    },
+
+    /// Write the vendored JavaScript libraries as a bundle for custom HTML templates. The
+    /// bundle assigns vegaEmbed, vega, vegaLite, and lodashDebounce to the window object
+    #[command(arg_required_else_help = true)]
+    Bundle {
+        /// Path to output JavaScript file to be created
+        #[arg(short, long)]
+        output: String,
+
+        /// Vega-Lite Version. One of 5.8, 5.14, 5.15, 5.16, 5.17, 5.18, 5.19, 5.20, 5.21
+        #[arg(short, long, default_value = DEFAULT_VL_VERSION)]
+        vl_version: String,
+
+        /// Bundle only Vega and Vega-Lite, without vega-embed and lodash.debounce
+        #[arg(long)]
+        vega_only: bool,
+    },
+
+    /// Anonymize the inline data of a Vega or Vega-Lite specification, so that it can be
+    /// shared in a bug report. Strings are replaced with hashes, numbers are perturbed, and
+    /// dates are shifted, while field names, encodings, transforms, and config are kept
+    #[command(arg_required_else_help = true)]
+    Anonymize {
+        /// Path to input Vega or Vega-Lite file
+        #[arg(short, long)]
+        input: String,
+
+        /// Path to output file to be created
+        #[arg(short, long)]
+        output: String,
+
+        /// Salt of the hashes, which also seeds how numbers and dates are changed. The same
+        /// salt anonymizes a spec the same way. Defaults to a random salt
+        #[arg(long)]
+        salt: Option<String>,
+
+        /// Replace strings that hold numbers or dates with hashes too, rather than perturbing
+        /// them like numbers and dates
+        #[arg(long)]
+        no_preserve_types: bool,
+
+        /// Replace every occurrence of a string with a hash of its own, rather than replacing
+        /// equal strings with equal hashes
+        #[arg(long)]
+        no_preserve_cardinality: bool,
+
+        /// Pretty-print JSON in output file
+        #[arg(short, long)]
+        pretty: bool,
+    },
+
+    /// Check a Vega specification for references to undefined scales, signals, and datasets,
+    /// scale domains with fields missing from inline data, unused datasets, and duplicate
+    /// signal names. Prints one line per issue, and exits with an error if any errors are found
+    #[command(arg_required_else_help = true)]
+    Lint {
+        /// Path to input Vega file
+        #[arg(short, long)]
+        input: String,
+
+        /// Exit with an error if any warnings are found, such as unused datasets
+        #[arg(long)]
+        deny_warnings: bool,
+    },
+
+    /// Compare the structure of two SVG images, element by element, and print a report of the
+    /// differences in tag names, attributes, and text. Exits with an error if the images differ
+    #[command(arg_required_else_help = true)]
+    SvgDiff {
+        /// Path to the first SVG file
+        a: String,
+
+        /// Path to the second SVG file
+        b: String,
+
+        /// Largest difference between two numbers in numeric attribute values, like
+        /// coordinates, sizes, path data, and transforms, for them to be considered equal
+        #[arg(long, default_value = "0.0")]
+        tolerance: f64,
+
+        /// Compare the values of id attributes, rather than ignoring the ids that Vega
+        /// generates for clip paths and gradients
+        #[arg(long)]
+        compare_ids: bool,
+    },
+
+    /// Check the embedded Vega, Vega-Lite, and dependency sources against the SHA-256 hashes
+    /// recorded when they were vendored. Exits with an error naming any module that doesn't match
+    VerifyVendor,
+
+    /// List available themes
+    LsThemes,
+
+    /// Print the config JSON for a theme
+    #[command(arg_required_else_help = true)]
+    CatTheme {
+        /// Name of a theme
+        theme: String,
+    },
+
+    /// Check that the JavaScript runtime starts and responds, and print a health report as
+    /// JSON. Exits with an error if the check fails
+    Health,
+
+    /// Print a description of the subcommands and options of this command line interface,
+    /// with their types and defaults, for wrappers that build vl-convert command lines
+    Introspect {
+        /// Format of the description
+        #[arg(long, default_value = "json", value_parser = ["json"])]
+        format: String,
+    },
+}
+
+#[tokio::main]
+async fn main() -> Result<(), anyhow::Error> {
+    let args = Cli::parse_from(args::expand_args::<Cli>(std::env::args())?);
+    let quiet = args.quiet;
+    init_logging(args.log_level, quiet);
+    let start = std::time::Instant::now();
+    set_spec_limits(SpecLimits {
+        max_spec_bytes: args.max_spec_bytes,
+        max_json_depth: args.max_json_depth,
+        max_views: args.max_views,
+    })?;
+    set_ignore_exif_orientation(args.ignore_exif_orientation);
+    set_emoji_font(args.emoji_font)?;
+    let stats = args.stats;
+    let fetch_retries = args.fetch_retries;
+    let fetch_retry_backoff_ms = args.fetch_retry_backoff_ms;
+    let base_url = args.base_url;
+    let allow_local_data = args.allow_local_data;
+    let lenient = args.lenient;
+    let font = args.font;
+    let font_size_scale = args.font_size_scale;
+    let text_color = args.text_color;
+    let config_overrides = (!args.config_overrides.is_empty()).then_some(args.config_overrides);
+    let direction = args
+        .direction
+        .as_deref()
+        .map(Direction::from_str)
+        .transpose()?;
+    let generic_font_mapping = (!args.generic_font_mapping.is_empty())
+        .then(|| HashMap::from_iter(args.generic_font_mapping));
+    let container_width = args.container_width;
+    let container_height = args.container_height;
+    let strict_sizing = args.strict_sizing;
+    let width = args.width;
+    let height = args.height;
+    let resize_mode = ResizeMode::from_str(&args.resize_mode)?;
+    let min_size = args.min_size;
+    let max_rows = args.max_rows;
+    let row_limit_strategy = RowLimitStrategy::from_str(&args.row_limit_strategy)?;
+    let annotate_truncation = args.annotate_truncation;
+    let log_level = args
+        .vega_log_level
+        .as_deref()
+        .map(LogLevel::from_str)
+        .transpose()?;
+    let warning_filters = (!args.suppress_warning.is_empty()).then_some(args.suppress_warning);
+    let suppress_components = (!args.suppress.is_empty()).then_some(args.suppress);
+    let browser_shims = !args.no_browser_shims;
+    let simplify_topojson = args.simplify_topojson;
+    let lenient_dates = args.lenient_dates;
+    let force_iterations = args.force_iterations;
+    let random_seed = args.random_seed;
+    let verbose = args.verbose;
+    let interactions = read_interactions_file(args.interactions_file)?;
+    validate_names(&args.command)?;
+    let mut warnings = ChartWarnings::default();
+    use crate::Commands::*;
     match args.command {
         Vl2vg {
             input: input_vegalite_file,
@@ -559,6 +1709,7 @@ async fn main() -> Result<(), anyhow::Error> {
             config,
             pretty,
             show_warnings,
+            preserve_int64,
         } => {
             vl_2_vg(
                 &input_vegalite_file,
@@ -567,7 +1718,14 @@ async fn main() -> Result<(), anyhow::Error> {
                 theme,
                 config,
                 pretty,
-                show_warnings,
+                show_warnings && !quiet,
+                preserve_int64,
+                stats,
+                font,
+                font_size_scale,
+                text_color,
+                config_overrides,
+                direction,
             )
             .await?
         }
@@ -582,18 +1740,64 @@ async fn main() -> Result<(), anyhow::Error> {
             allowed_base_url,
             format_locale,
             time_format_locale,
+            dump_final_spec,
+            export_data,
+            export_data_format,
+            svg_text_length,
+            svg_native_tooltips,
+            svg_minify,
+            svg_precision,
+            explain_static,
         } => {
             register_font_dir(font_dir)?;
-            vl_2_svg(
+            warnings = vl_2_svg(
                 &input,
                 &output,
                 &vl_version,
                 theme,
                 config,
-                show_warnings,
+                show_warnings && !quiet,
                 allowed_base_url,
                 format_locale,
                 time_format_locale,
+                dump_final_spec,
+                export_data,
+                &export_data_format,
+                svg_text_length,
+                svg_native_tooltips,
+                svg_minify,
+                svg_precision,
+                explain_static && !quiet,
+                stats,
+                fetch_retries,
+                fetch_retry_backoff_ms,
+                base_url,
+                allow_local_data,
+                lenient,
+                interactions,
+                font,
+                font_size_scale,
+                text_color,
+                config_overrides,
+                direction,
+                generic_font_mapping,
+                container_width,
+                container_height,
+                strict_sizing,
+                width,
+                height,
+                resize_mode,
+                min_size,
+                max_rows,
+                row_limit_strategy,
+                annotate_truncation,
+                log_level,
+                warning_filters,
+                suppress_components,
+                browser_shims,
+                simplify_topojson,
+                lenient_dates,
+                verbose,
             )
             .await?
         }
@@ -605,14 +1809,23 @@ async fn main() -> Result<(), anyhow::Error> {
             config,
             scale,
             ppi,
+            png_interlaced,
+            no_antialias,
+            ppi_mode,
+            image_rendering,
+            flatten_background,
             show_warnings,
             font_dir,
             allowed_base_url,
             format_locale,
             time_format_locale,
+            dump_final_spec,
+            export_data,
+            export_data_format,
+            explain_static,
         } => {
             register_font_dir(font_dir)?;
-            vl_2_png(
+            warnings = vl_2_png(
                 &input,
                 &output,
                 &vl_version,
@@ -620,90 +1833,520 @@ async fn main() -> Result<(), anyhow::Error> {
                 config,
                 scale,
                 ppi,
-                show_warnings,
+                png_interlaced,
+                no_antialias,
+                &ppi_mode,
+                &image_rendering,
+                flatten_background,
+                show_warnings && !quiet,
                 allowed_base_url,
                 format_locale,
                 time_format_locale,
+                dump_final_spec,
+                export_data,
+                &export_data_format,
+                explain_static && !quiet,
+                stats,
+                fetch_retries,
+                fetch_retry_backoff_ms,
+                base_url,
+                allow_local_data,
+                lenient,
+                interactions,
+                font,
+                font_size_scale,
+                text_color,
+                config_overrides,
+                direction,
+                generic_font_mapping,
+                container_width,
+                container_height,
+                strict_sizing,
+                width,
+                height,
+                resize_mode,
+                min_size,
+                max_rows,
+                row_limit_strategy,
+                annotate_truncation,
+                log_level,
+                warning_filters,
+                suppress_components,
+                browser_shims,
+                simplify_topojson,
+                lenient_dates,
+                verbose,
             )
             .await?
         }
-        Vl2jpeg {
+        Vl2facets {
             input,
             output,
             vl_version,
             theme,
             config,
             scale,
-            quality,
+            ppi,
             show_warnings,
             font_dir,
             allowed_base_url,
             format_locale,
             time_format_locale,
+            include_guides,
         } => {
             register_font_dir(font_dir)?;
-            vl_2_jpeg(
+            vl_2_facets(
                 &input,
                 &output,
                 &vl_version,
                 theme,
                 config,
                 scale,
-                quality,
-                show_warnings,
+                ppi,
+                show_warnings && !quiet,
                 allowed_base_url,
                 format_locale,
                 time_format_locale,
+                stats,
+                fetch_retries,
+                fetch_retry_backoff_ms,
+                base_url,
+                allow_local_data,
+                lenient,
+                interactions,
+                font,
+                font_size_scale,
+                text_color,
+                config_overrides,
+                direction,
+                generic_font_mapping,
+                container_width,
+                container_height,
+                strict_sizing,
+                width,
+                height,
+                resize_mode,
+                min_size,
+                max_rows,
+                row_limit_strategy,
+                annotate_truncation,
+                log_level,
+                warning_filters,
+                suppress_components,
+                browser_shims,
+                simplify_topojson,
+                lenient_dates,
+                include_guides,
             )
             .await?
         }
-        Vl2pdf {
+        Vl2vgpng {
+            input,
+            output_vega,
+            output_png,
+            vl_version,
+            theme,
+            config,
+            pretty,
+            scale,
+            ppi,
+            show_warnings,
+            font_dir,
+            allowed_base_url,
+            format_locale,
+            time_format_locale,
+        } => {
+            register_font_dir(font_dir)?;
+            warnings = vl_2_vg_png(
+                &input,
+                &output_vega,
+                &output_png,
+                &vl_version,
+                theme,
+                config,
+                pretty,
+                scale,
+                ppi,
+                show_warnings && !quiet,
+                allowed_base_url,
+                format_locale,
+                time_format_locale,
+                stats,
+                fetch_retries,
+                fetch_retry_backoff_ms,
+                base_url,
+                allow_local_data,
+                lenient,
+                interactions,
+                font,
+                font_size_scale,
+                text_color,
+                config_overrides,
+                direction,
+                generic_font_mapping,
+                container_width,
+                container_height,
+                strict_sizing,
+                width,
+                height,
+                resize_mode,
+                min_size,
+                max_rows,
+                row_limit_strategy,
+                annotate_truncation,
+                log_level,
+                warning_filters,
+                suppress_components,
+                browser_shims,
+                simplify_topojson,
+                lenient_dates,
+            )
+            .await?
+        }
+        Vl2jpeg {
             input,
             output,
             vl_version,
             theme,
             config,
+            scale,
+            quality,
+            jpeg_background,
             show_warnings,
             font_dir,
             allowed_base_url,
             format_locale,
             time_format_locale,
+            dump_final_spec,
+            export_data,
+            export_data_format,
         } => {
             register_font_dir(font_dir)?;
-            vl_2_pdf(
+            warnings = vl_2_jpeg(
                 &input,
                 &output,
                 &vl_version,
                 theme,
                 config,
-                show_warnings,
+                scale,
+                quality,
+                &jpeg_background,
+                show_warnings && !quiet,
                 allowed_base_url,
                 format_locale,
                 time_format_locale,
+                dump_final_spec,
+                export_data,
+                &export_data_format,
+                stats,
+                fetch_retries,
+                fetch_retry_backoff_ms,
+                base_url,
+                allow_local_data,
+                lenient,
+                interactions,
+                font,
+                font_size_scale,
+                text_color,
+                config_overrides,
+                direction,
+                generic_font_mapping,
+                container_width,
+                container_height,
+                strict_sizing,
+                width,
+                height,
+                resize_mode,
+                min_size,
+                max_rows,
+                row_limit_strategy,
+                annotate_truncation,
+                log_level,
+                warning_filters,
+                suppress_components,
+                browser_shims,
+                simplify_topojson,
+                lenient_dates,
+                verbose,
             )
             .await?
         }
-        Vl2url { input, fullscreen } => {
-            let vl_str = read_input_string(&input)?;
-            let vl_spec = serde_json::from_str(&vl_str)?;
-            println!("{}", vegalite_to_url(&vl_spec, fullscreen)?)
-        }
-        Vl2html {
+        Vl2pdf {
             input,
             output,
             vl_version,
             theme,
             config,
-            bundle,
+            show_warnings,
+            font_dir,
+            allowed_base_url,
             format_locale,
             time_format_locale,
-            renderer,
+            pdfa,
+            source_dpi,
+            dump_final_spec,
+            export_data,
+            export_data_format,
         } => {
-            // Initialize converter
+            register_font_dir(font_dir)?;
+            warnings = vl_2_pdf(
+                &input,
+                &output,
+                &vl_version,
+                theme,
+                config,
+                show_warnings && !quiet,
+                allowed_base_url,
+                format_locale,
+                time_format_locale,
+                dump_final_spec,
+                export_data,
+                &export_data_format,
+                stats,
+                fetch_retries,
+                fetch_retry_backoff_ms,
+                base_url,
+                allow_local_data,
+                lenient,
+                interactions,
+                font,
+                font_size_scale,
+                text_color,
+                config_overrides,
+                direction,
+                generic_font_mapping,
+                container_width,
+                container_height,
+                strict_sizing,
+                width,
+                height,
+                resize_mode,
+                min_size,
+                max_rows,
+                row_limit_strategy,
+                annotate_truncation,
+                log_level,
+                warning_filters,
+                suppress_components,
+                browser_shims,
+                simplify_topojson,
+                lenient_dates,
+                pdfa,
+                source_dpi,
+            )
+            .await?
+        }
+        Vl2labels {
+            input,
+            output,
+            vl_version,
+            theme,
+            config,
+            show_warnings,
+            font_dir,
+            allowed_base_url,
+            format_locale,
+            time_format_locale,
+        } => {
+            register_font_dir(font_dir)?;
+            warnings = vl_2_labels(
+                &input,
+                output.as_deref(),
+                &vl_version,
+                theme,
+                config,
+                show_warnings && !quiet,
+                allowed_base_url,
+                format_locale,
+                time_format_locale,
+                stats,
+                fetch_retries,
+                fetch_retry_backoff_ms,
+                base_url,
+                allow_local_data,
+                interactions,
+                font,
+                font_size_scale,
+                text_color,
+                config_overrides,
+                direction,
+                generic_font_mapping,
+                container_width,
+                container_height,
+                strict_sizing,
+                width,
+                height,
+                resize_mode,
+                min_size,
+                max_rows,
+                row_limit_strategy,
+                annotate_truncation,
+                log_level,
+                warning_filters,
+                suppress_components,
+                browser_shims,
+                simplify_topojson,
+                lenient_dates,
+            )
+            .await?
+        }
+        Freeze {
+            input,
+            output,
+            vl_version,
+            theme,
+            config,
+            allowed_base_url,
+            pretty,
+            preserve_int64,
+        } => {
+            warnings = vl_freeze(
+                &input,
+                &output,
+                &vl_version,
+                theme,
+                config,
+                allowed_base_url,
+                pretty,
+                preserve_int64,
+                stats,
+                fetch_retries,
+                fetch_retry_backoff_ms,
+                base_url,
+                allow_local_data,
+                font,
+                font_size_scale,
+                text_color,
+                config_overrides,
+                direction,
+            )
+            .await?
+        }
+        Export {
+            input,
+            output,
+            formats,
+            vl_version,
+            theme,
+            config,
+            scale,
+            ppi,
+            quality,
+            jpeg_background,
+            flatten_background,
+            show_warnings,
+            font_dir,
+            allowed_base_url,
+            format_locale,
+            time_format_locale,
+            export_data,
+            export_data_format,
+        } => {
+            register_font_dir(font_dir)?;
+            warnings = vl_export(
+                &input,
+                &output,
+                &formats,
+                &vl_version,
+                theme,
+                config,
+                scale,
+                ppi,
+                quality,
+                &jpeg_background,
+                flatten_background,
+                show_warnings && !quiet,
+                allowed_base_url,
+                format_locale,
+                time_format_locale,
+                export_data,
+                &export_data_format,
+                stats,
+                fetch_retries,
+                fetch_retry_backoff_ms,
+                base_url,
+                allow_local_data,
+                lenient,
+                interactions,
+                font,
+                font_size_scale,
+                text_color,
+                config_overrides,
+                direction,
+                generic_font_mapping,
+                container_width,
+                container_height,
+                strict_sizing,
+                width,
+                height,
+                resize_mode,
+                min_size,
+                max_rows,
+                row_limit_strategy,
+                annotate_truncation,
+                log_level,
+                warning_filters,
+                suppress_components,
+                browser_shims,
+                simplify_topojson,
+                lenient_dates,
+            )
+            .await?
+        }
+        Vl2url {
+            input,
+            fullscreen,
+            shorten_endpoint,
+            shorten_auth_header,
+            shorten_response_field,
+            shorten_timeout,
+            shorten_fallback,
+        } => {
+            let vl_str = read_input_string(&input)?;
+            let vl_spec = parse_as_json(&vl_str)?;
+            let url = vegalite_to_url(&vl_spec, fullscreen)?;
+            let url = match shorten_endpoint {
+                Some(endpoint) => {
+                    let opts = ShortenOptions {
+                        endpoint,
+                        auth_header: shorten_auth_header,
+                        fullscreen,
+                        response_field: shorten_response_field,
+                        timeout: std::time::Duration::from_secs(shorten_timeout),
+                        fallback: shorten_fallback,
+                    };
+                    shorten_url(url, &opts).await?
+                }
+                None => url,
+            };
+            println!("{url}")
+        }
+        Vl2html {
+            input,
+            output,
+            vl_version,
+            theme,
+            config,
+            show_warnings,
+            bundle,
+            format_locale,
+            time_format_locale,
+            renderer,
+            static_fallback,
+            static_fallback_scale,
+            allowed_base_url,
+            warn_only,
+            template_file,
+        } => {
+            // Initialize converter
             let vl_str = read_input_string(&input)?;
-            let vl_spec = serde_json::from_str(&vl_str)?;
+            let vl_spec = parse_as_json(&vl_str)?;
             let config = read_config_json(config)?;
             let vl_version = parse_vl_version(&vl_version)?;
+            let template = template_file
+                .as_deref()
+                .map(read_input_string)
+                .transpose()?;
             let format_locale = match &format_locale {
                 None => None,
                 Some(p) => Some(format_locale_from_str(p)?),
@@ -717,8 +2360,104 @@ async fn main() -> Result<(), anyhow::Error> {
 
             let mut converter = VlConverter::new();
             let html = converter
-                .vegalite_to_html(
+                .vegalite_to_html_with_opts(
                     vl_spec,
+                    VlOpts {
+                        config,
+                        theme,
+                        vl_version,
+                        show_warnings: show_warnings && !quiet,
+                        allowed_base_urls: allowed_base_url,
+                        format_locale,
+                        time_format_locale,
+                        collect_resource_stats: stats,
+                        collect_dataset_summary: false,
+                        fetch_retries: None,
+                        fetch_retry_backoff_ms: None,
+                        interaction_script: None,
+                        preserve_int64: false,
+                        base_url: None,
+                        allow_local_data: false,
+                        lenient: false,
+                        font,
+                        font_size_scale,
+                        text_color,
+                        config_overrides,
+                        direction,
+                        generic_font_mapping: None,
+                        container_width: None,
+                        container_height: None,
+                        strict_sizing: false,
+                        width: None,
+                        height: None,
+                        resize_mode: ResizeMode::default(),
+                        min_size: None,
+                        max_rows: None,
+                        row_limit_strategy: RowLimitStrategy::default(),
+                        annotate_truncation: false,
+                        log_level: None,
+                        warning_filters: None,
+                        suppress_components: None,
+                        browser_shims,
+                        simplify_topojson,
+                        pre_aggregate: false,
+                        lenient_dates: false,
+                        svg_post_processor: None,
+                    },
+                    bundle,
+                    Renderer::from_str(&renderer)?,
+                    HtmlOpts {
+                        static_fallback,
+                        static_fallback_scale: Some(static_fallback_scale),
+                        warn_only,
+                        template,
+                    },
+                )
+                .await?;
+            write_output_string(&output, &html)?;
+        }
+        Report {
+            input,
+            title,
+            output,
+            vl_version,
+            theme,
+            config,
+            bundle,
+            format_locale,
+            time_format_locale,
+            renderer,
+        } => {
+            if title.len() > input.len() {
+                bail!(
+                    "Received {} titles for {} inputs. Each title applies to the input in the same position",
+                    title.len(),
+                    input.len()
+                );
+            }
+            let mut titles = title.into_iter();
+            let mut specs = Vec::new();
+            for input in &input {
+                let vl_str = read_input_string(input)?;
+                specs.push((titles.next(), parse_as_json(&vl_str)?));
+            }
+            let config = read_config_json(config)?;
+            let vl_version = parse_vl_version(&vl_version)?;
+            let format_locale = match &format_locale {
+                None => None,
+                Some(p) => Some(format_locale_from_str(p)?),
+            };
+
+            let time_format_locale = match &time_format_locale {
+                None => None,
+                Some(p) => Some(time_format_locale_from_str(p)?),
+            };
+            let renderer = renderer.unwrap_or_else(|| "svg".to_string());
+
+            let mut converter = VlConverter::new();
+            let html = converter
+                .vegalite_to_html_report(
+                    specs,
                     VlOpts {
                         config,
                         theme,
@@ -727,6 +2466,39 @@ async fn main() -> Result<(), anyhow::Error> {
                         allowed_base_urls: None,
                         format_locale,
                         time_format_locale,
+                        collect_resource_stats: stats,
+                        collect_dataset_summary: false,
+                        fetch_retries: None,
+                        fetch_retry_backoff_ms: None,
+                        interaction_script: None,
+                        preserve_int64: false,
+                        base_url: None,
+                        allow_local_data: false,
+                        lenient: false,
+                        font,
+                        font_size_scale,
+                        text_color,
+                        config_overrides,
+                        direction,
+                        generic_font_mapping: None,
+                        container_width: None,
+                        container_height: None,
+                        strict_sizing: false,
+                        width: None,
+                        height: None,
+                        resize_mode: ResizeMode::default(),
+                        min_size: None,
+                        max_rows: None,
+                        row_limit_strategy: RowLimitStrategy::default(),
+                        annotate_truncation: false,
+                        log_level: None,
+                        warning_filters: None,
+                        suppress_components: None,
+                        browser_shims,
+                        simplify_topojson,
+                        pre_aggregate: false,
+                        lenient_dates: false,
+                        svg_post_processor: None,
                     },
                     bundle,
                     Renderer::from_str(&renderer)?,
@@ -737,99 +2509,332 @@ async fn main() -> Result<(), anyhow::Error> {
         Vg2svg {
             input,
             output,
+            theme,
+            config,
             font_dir,
             allowed_base_url,
             format_locale,
             time_format_locale,
+            dump_final_spec,
+            svg_text_length,
+            svg_native_tooltips,
+            svg_minify,
+            svg_precision,
         } => {
             register_font_dir(font_dir)?;
-            vg_2_svg(
+            warnings = vg_2_svg(
                 &input,
                 &output,
+                theme,
+                config,
+                allowed_base_url,
+                format_locale,
+                time_format_locale,
+                dump_final_spec,
+                svg_text_length,
+                svg_native_tooltips,
+                svg_minify,
+                svg_precision,
+                stats,
+                fetch_retries,
+                fetch_retry_backoff_ms,
+                base_url,
+                allow_local_data,
+                lenient,
+                interactions,
+                generic_font_mapping,
+                container_width,
+                container_height,
+                strict_sizing,
+                width,
+                height,
+                resize_mode,
+                min_size,
+                max_rows,
+                row_limit_strategy,
+                annotate_truncation,
+                log_level,
+                warning_filters,
+                suppress_components,
+                browser_shims,
+                simplify_topojson,
+                force_iterations,
+                random_seed,
+                verbose,
+            )
+            .await?
+        }
+        Vg2stats {
+            input,
+            output,
+            theme,
+            config,
+            font_dir,
+            allowed_base_url,
+            format_locale,
+            time_format_locale,
+        } => {
+            register_font_dir(font_dir)?;
+            warnings = vg_2_stats(
+                &input,
+                output.as_deref(),
+                theme,
+                config,
                 allowed_base_url,
                 format_locale,
                 time_format_locale,
+                stats,
+                fetch_retries,
+                fetch_retry_backoff_ms,
+                base_url,
+                allow_local_data,
+                lenient,
+                interactions,
+                generic_font_mapping,
+                container_width,
+                container_height,
+                strict_sizing,
+                width,
+                height,
+                resize_mode,
+                min_size,
+                max_rows,
+                row_limit_strategy,
+                annotate_truncation,
+                log_level,
+                warning_filters,
+                suppress_components,
+                browser_shims,
+                simplify_topojson,
+                force_iterations,
+                random_seed,
             )
             .await?
         }
         Vg2png {
             input,
             output,
+            theme,
+            config,
             scale,
             ppi,
+            png_interlaced,
+            no_antialias,
+            ppi_mode,
+            image_rendering,
+            flatten_background,
             font_dir,
             allowed_base_url,
             format_locale,
             time_format_locale,
+            dump_final_spec,
         } => {
             register_font_dir(font_dir)?;
-            vg_2_png(
+            warnings = vg_2_png(
                 &input,
                 &output,
+                theme,
+                config,
                 scale,
                 ppi,
+                png_interlaced,
+                no_antialias,
+                &ppi_mode,
+                &image_rendering,
+                flatten_background,
                 allowed_base_url,
                 format_locale,
                 time_format_locale,
+                dump_final_spec,
+                stats,
+                fetch_retries,
+                fetch_retry_backoff_ms,
+                base_url,
+                allow_local_data,
+                lenient,
+                interactions,
+                generic_font_mapping,
+                container_width,
+                container_height,
+                strict_sizing,
+                width,
+                height,
+                resize_mode,
+                min_size,
+                max_rows,
+                row_limit_strategy,
+                annotate_truncation,
+                log_level,
+                warning_filters,
+                suppress_components,
+                browser_shims,
+                simplify_topojson,
+                force_iterations,
+                random_seed,
+                verbose,
             )
             .await?
         }
         Vg2jpeg {
             input,
             output,
+            theme,
+            config,
             scale,
             quality,
+            jpeg_background,
             font_dir,
             allowed_base_url,
             format_locale,
             time_format_locale,
+            dump_final_spec,
         } => {
             register_font_dir(font_dir)?;
-            vg_2_jpeg(
+            warnings = vg_2_jpeg(
                 &input,
                 &output,
+                theme,
+                config,
                 scale,
                 quality,
+                &jpeg_background,
                 allowed_base_url,
                 format_locale,
                 time_format_locale,
+                dump_final_spec,
+                stats,
+                fetch_retries,
+                fetch_retry_backoff_ms,
+                base_url,
+                allow_local_data,
+                lenient,
+                interactions,
+                generic_font_mapping,
+                container_width,
+                container_height,
+                strict_sizing,
+                width,
+                height,
+                resize_mode,
+                min_size,
+                max_rows,
+                row_limit_strategy,
+                annotate_truncation,
+                log_level,
+                warning_filters,
+                suppress_components,
+                browser_shims,
+                simplify_topojson,
+                force_iterations,
+                random_seed,
+                verbose,
             )
             .await?
         }
         Vg2pdf {
             input,
             output,
+            theme,
+            config,
             font_dir,
             allowed_base_url,
             format_locale,
             time_format_locale,
+            pdfa,
+            source_dpi,
+            dump_final_spec,
         } => {
             register_font_dir(font_dir)?;
-            vg_2_pdf(
+            warnings = vg_2_pdf(
                 &input,
                 &output,
+                theme,
+                config,
                 allowed_base_url,
                 format_locale,
                 time_format_locale,
+                dump_final_spec,
+                stats,
+                fetch_retries,
+                fetch_retry_backoff_ms,
+                base_url,
+                allow_local_data,
+                lenient,
+                interactions,
+                generic_font_mapping,
+                container_width,
+                container_height,
+                strict_sizing,
+                width,
+                height,
+                resize_mode,
+                min_size,
+                max_rows,
+                row_limit_strategy,
+                annotate_truncation,
+                log_level,
+                warning_filters,
+                suppress_components,
+                browser_shims,
+                simplify_topojson,
+                force_iterations,
+                random_seed,
+                pdfa,
+                source_dpi,
             )
             .await?
         }
-        Vg2url { input, fullscreen } => {
+        Vg2url {
+            input,
+            fullscreen,
+            shorten_endpoint,
+            shorten_auth_header,
+            shorten_response_field,
+            shorten_timeout,
+            shorten_fallback,
+        } => {
             let vg_str = read_input_string(&input)?;
-            let vg_spec = serde_json::from_str(&vg_str)?;
-            println!("{}", vega_to_url(&vg_spec, fullscreen)?)
+            let vg_spec = parse_as_json(&vg_str)?;
+            let url = vega_to_url(&vg_spec, fullscreen)?;
+            let url = match shorten_endpoint {
+                Some(endpoint) => {
+                    let opts = ShortenOptions {
+                        endpoint,
+                        auth_header: shorten_auth_header,
+                        fullscreen,
+                        response_field: shorten_response_field,
+                        timeout: std::time::Duration::from_secs(shorten_timeout),
+                        fallback: shorten_fallback,
+                    };
+                    shorten_url(url, &opts).await?
+                }
+                None => url,
+            };
+            println!("{url}")
         }
         Vg2html {
             input,
             output,
+            theme,
+            config,
             bundle,
             format_locale,
             time_format_locale,
             renderer,
+            static_fallback,
+            static_fallback_scale,
+            allowed_base_url,
+            warn_only,
+            template_file,
         } => {
             // Initialize converter
             let vg_str = read_input_string(&input)?;
-            let vg_spec = serde_json::from_str(&vg_str)?;
+            let vg_spec = parse_as_json(&vg_str)?;
+            let config = read_vega_config_json(config)?;
+            let template = template_file
+                .as_deref()
+                .map(read_input_string)
+                .transpose()?;
 
             let format_locale = match &format_locale {
                 None => None,
@@ -845,15 +2850,51 @@ async fn main() -> Result<(), anyhow::Error> {
 
             let mut converter = VlConverter::new();
             let html = converter
-                .vega_to_html(
+                .vega_to_html_with_opts(
                     vg_spec,
                     VgOpts {
-                        allowed_base_urls: None,
+                        theme,
+                        config,
+                        allowed_base_urls: allowed_base_url,
                         format_locale,
                         time_format_locale,
+                        collect_resource_stats: stats,
+                        collect_dataset_summary: false,
+                        fetch_retries: None,
+                        fetch_retry_backoff_ms: None,
+                        interaction_script: None,
+                        preserve_int64: false,
+                        base_url: None,
+                        allow_local_data: false,
+                        lenient: false,
+                        generic_font_mapping: None,
+                        container_width: None,
+                        container_height: None,
+                        strict_sizing: false,
+                        width: None,
+                        height: None,
+                        resize_mode: ResizeMode::default(),
+                        min_size: None,
+                        max_rows: None,
+                        row_limit_strategy: RowLimitStrategy::default(),
+                        annotate_truncation: false,
+                        log_level: None,
+                        warning_filters: None,
+                        suppress_components: None,
+                        browser_shims,
+                        simplify_topojson,
+                        force_iterations: None,
+                        random_seed: None,
+                        svg_post_processor: None,
                     },
                     bundle,
                     Renderer::from_str(&renderer)?,
+                    HtmlOpts {
+                        static_fallback,
+                        static_fallback_scale: Some(static_fallback_scale),
+                        warn_only,
+                        template,
+                    },
                 )
                 .await?;
             write_output_string(&output, &html)?;
@@ -863,11 +2904,31 @@ async fn main() -> Result<(), anyhow::Error> {
             output,
             scale,
             ppi,
+            png_interlaced,
+            no_antialias,
+            ppi_mode,
+            image_rendering,
+            flatten_background,
             font_dir,
         } => {
             register_font_dir(font_dir)?;
             let svg = read_input_string(&input)?;
-            let png_data = vl_convert_rs::converter::svg_to_png(&svg, scale, Some(ppi))?;
+            let png_data = vl_convert_rs::converter::svg_to_png_with_opts(
+                &svg,
+                scale,
+                Some(ppi),
+                &PngOpts {
+                    interlaced: png_interlaced,
+                    antialias: !no_antialias,
+                    ppi_mode: PpiMode::from_str(&ppi_mode)?,
+                    image_rendering: ImageRendering::from_str(&image_rendering)?,
+                    flatten_background: flatten_background
+                        .as_deref()
+                        .map(BackgroundColor::from_str)
+                        .transpose()?,
+                    ..Default::default()
+                },
+            )?;
             write_output_binary(&output, &png_data)?;
         }
         Svg2jpeg {
@@ -875,30 +2936,212 @@ async fn main() -> Result<(), anyhow::Error> {
             output,
             scale,
             quality,
+            jpeg_background,
             font_dir,
         } => {
             register_font_dir(font_dir)?;
             let svg = read_input_string(&input)?;
-            let jpeg_data = vl_convert_rs::converter::svg_to_jpeg(&svg, scale, Some(quality))?;
+            let jpeg_data = vl_convert_rs::converter::svg_to_jpeg_with_opts(
+                &svg,
+                scale,
+                Some(quality),
+                &JpegOpts {
+                    background: BackgroundColor::from_str(&jpeg_background)?,
+                },
+            )?;
             write_output_binary(&output, &jpeg_data)?;
         }
         Svg2pdf {
             input,
             output,
             font_dir,
+            pdfa,
+            source_dpi,
         } => {
             register_font_dir(font_dir)?;
             let svg = read_input_string(&input)?;
-            let pdf_data = vl_convert_rs::converter::svg_to_pdf(&svg)?;
+            let pdf_opts = PdfOpts { pdfa, source_dpi };
+            let pdf_data = vl_convert_rs::converter::svg_to_pdf_with_opts(&svg, &pdf_opts)?;
             write_output_binary(&output, &pdf_data)?;
         }
+        Bundle {
+            output,
+            vl_version,
+            vega_only,
+        } => {
+            let vl_version = parse_vl_version(&vl_version)?;
+            let bundle = if vega_only {
+                get_vega_bundle(vl_version).await?
+            } else {
+                get_vegaembed_bundle(vl_version).await?
+            };
+            write_output_string(&output, &bundle)?;
+        }
+        Anonymize {
+            input,
+            output,
+            salt,
+            no_preserve_types,
+            no_preserve_cardinality,
+            pretty,
+        } => {
+            let opts = AnonymizeOptions {
+                preserve_types: !no_preserve_types,
+                preserve_cardinality: !no_preserve_cardinality,
+                salt,
+            };
+            anonymize(&input, &output, &opts, pretty)?
+        }
+        Lint {
+            input,
+            deny_warnings,
+        } => lint(&input, deny_warnings)?,
+        SvgDiff {
+            a,
+            b,
+            tolerance,
+            compare_ids,
+        } => svg_diff(&a, &b, tolerance, compare_ids)?,
+        VerifyVendor => {
+            let modules = verify_vendor_integrity()?;
+            println!("Verified {modules} vendored JavaScript modules against the vendor manifest");
+        }
         LsThemes => list_themes().await?,
         CatTheme { theme } => cat_theme(&theme).await?,
+        Health => health().await?,
+        Introspect { format: _ } => {
+            let schema = introspect::cli_schema(&Cli::command());
+            println!("{}", serde_json::to_string_pretty(&schema)?);
+        }
+    }
+
+    if stats {
+        print_resource_stats(quiet)?;
+    }
+
+    if !quiet {
+        print_dataset_warnings(warnings.dataset_summary.as_ref());
+        print_render_warnings(&warnings.render_warnings);
+    }
+
+    if verbose {
+        eprintln!(
+            "vl-convert: finished in {:.3}s",
+            start.elapsed().as_secs_f64()
+        );
+    }
+
+    Ok(())
+}
+
+/// Warnings about the chart of a conversion command, which are printed once the command
+/// completes unless --quiet is set
+#[derive(Default)]
+struct ChartWarnings {
+    render_warnings: Vec<RenderWarning>,
+    dataset_summary: Option<DatasetSummary>,
+}
+
+fn print_resource_stats(quiet: bool) -> Result<(), anyhow::Error> {
+    match get_last_resource_stats()? {
+        Some(resource_stats) => {
+            eprintln!("{}", serde_json::to_string(&resource_stats)?);
+        }
+        None if !quiet => {
+            eprintln!(
+                "vl-convert: warning: No resource statistics were collected for this command"
+            );
+        }
+        None => {}
+    }
+    Ok(())
+}
+
+/// Warn about datasets without rows that marks are drawn from, which usually means that a
+/// filter removed every row or that a data url returned no data
+fn print_dataset_warnings(summary: Option<&DatasetSummary>) {
+    if let Some(summary) = summary {
+        for dataset in summary.empty_mark_datasets() {
+            eprintln!(
+                "vl-convert: warning: dataset '{}' has 0 rows; chart may be empty",
+                dataset.name
+            );
+        }
     }
+}
 
+/// Report the interactive features of the chart that are lost in the image for --explain-static
+async fn print_static_export_findings(
+    converter: &mut VlConverter,
+    vl_spec: serde_json::Value,
+    vl_opts: VlOpts,
+) -> Result<(), anyhow::Error> {
+    for finding in converter.analyze_static_export(vl_spec, vl_opts).await? {
+        let severity = match finding.severity {
+            StaticExportSeverity::Degraded => "degraded",
+            StaticExportSeverity::Unsupported => "unsupported",
+        };
+        let path = if finding.spec_path.is_empty() {
+            "/"
+        } else {
+            &finding.spec_path
+        };
+        eprintln!(
+            "vl-convert: static export: {severity}[{}] {path}: {}",
+            finding.feature, finding.message
+        );
+    }
     Ok(())
 }
 
+/// Report the render warnings of a conversion, like the parts of the chart that were left out
+/// by --lenient
+fn print_render_warnings(warnings: &[RenderWarning]) {
+    for warning in warnings {
+        match &warning.mark {
+            Some(mark) => eprintln!("vl-convert: warning: mark '{mark}': {}", warning.message),
+            None => eprintln!("vl-convert: warning: {}", warning.message),
+        }
+    }
+}
+
+/// Report the size of a converted SVG image for --verbose
+fn print_svg_size(svg: &SvgResult) {
+    match svg.view_box {
+        Some([x, y, width, height]) => eprintln!(
+            "vl-convert: image is {}x{}, with viewBox {x} {y} {width} {height}",
+            svg.width, svg.height
+        ),
+        None => eprintln!("vl-convert: image is {}x{}", svg.width, svg.height),
+    }
+    print_request_hash(&svg.request_hash);
+}
+
+/// Report the size of a converted PNG image for --verbose
+fn print_png_size(png: &PngResult) {
+    eprintln!(
+        "vl-convert: image is {}x{} pixels, from a {}x{} chart at scale {} and {} ppi",
+        png.width_px, png.height_px, png.css_width, png.css_height, png.scale, png.ppi
+    );
+    print_request_hash(&png.request_hash);
+}
+
+/// Report the size of a converted JPEG image for --verbose
+fn print_jpeg_size(jpeg: &JpegResult) {
+    eprintln!(
+        "vl-convert: image is {}x{} pixels, from a {}x{} chart at scale {}",
+        jpeg.width_px, jpeg.height_px, jpeg.css_width, jpeg.css_height, jpeg.scale
+    );
+    print_request_hash(&jpeg.request_hash);
+}
+
+/// Report the request hash of a converted image for --verbose
+fn print_request_hash(request_hash: &Option<String>) {
+    if let Some(request_hash) = request_hash {
+        eprintln!("vl-convert: request hash {request_hash}");
+    }
+}
+
 fn register_font_dir(dir: Option<String>) -> Result<(), anyhow::Error> {
     if let Some(dir) = dir {
         register_font_directory(&dir)?
@@ -906,6 +3149,22 @@ fn register_font_dir(dir: Option<String>) -> Result<(), anyhow::Error> {
     Ok(())
 }
 
+/// Base URL of the data referenced by the spec in `input`. Local data is resolved relative to
+/// the input file by default
+fn data_base_url(
+    input: &str,
+    base_url: Option<String>,
+    allow_local_data: bool,
+) -> Result<Option<String>, anyhow::Error> {
+    if base_url.is_some() || !allow_local_data {
+        return Ok(base_url);
+    }
+    match directory_base_url(Path::new(input)) {
+        Ok(base_url) => Ok(Some(base_url)),
+        Err(err) => bail!("Failed to determine the directory of {}: {}", input, err),
+    }
+}
+
 fn parse_vl_version(vl_version: &str) -> Result<VlVersion, anyhow::Error> {
     if let Ok(vl_version) = VlVersion::from_str(vl_version) {
         Ok(vl_version)
@@ -924,7 +3183,7 @@ fn read_input_string(input: &str) -> Result<String, anyhow::Error> {
 }
 
 fn parse_as_json(input_str: &str) -> Result<serde_json::Value, anyhow::Error> {
-    match serde_json::from_str::<serde_json::Value>(input_str) {
+    match parse_json_spec(input_str) {
         Ok(input_json) => Ok(input_json),
         Err(err) => {
             bail!("Failed to parse input file as JSON: {}", err);
@@ -950,13 +3209,161 @@ fn time_format_locale_from_str(s: &str) -> Result<TimeFormatLocale, anyhow::Erro
     }
 }
 
-fn write_output_string(output: &str, output_str: &str) -> Result<(), anyhow::Error> {
-    match std::fs::write(output, output_str) {
-        Ok(_) => Ok(()),
-        Err(err) => {
-            bail!("Failed to write converted output to {}\n{}", output, err);
-        }
-    }
+/// Check the theme and locale names of a command before converting anything, so that a
+/// misspelled name fails right away with a suggestion of the closest valid name
+fn validate_names(command: &Commands) -> Result<(), anyhow::Error> {
+    use crate::Commands::*;
+    let (themes, format_locale, time_format_locale): (Vec<&String>, _, _) = match command {
+        Vl2svg {
+            theme,
+            format_locale,
+            time_format_locale,
+            ..
+        }
+        | Vl2png {
+            theme,
+            format_locale,
+            time_format_locale,
+            ..
+        } => (
+            theme.iter().filter(|theme| *theme != "default").collect(),
+            format_locale,
+            time_format_locale,
+        ),
+        Vl2facets {
+            theme,
+            format_locale,
+            time_format_locale,
+            ..
+        }
+        | Vl2vgpng {
+            theme,
+            format_locale,
+            time_format_locale,
+            ..
+        }
+        | Vl2jpeg {
+            theme,
+            format_locale,
+            time_format_locale,
+            ..
+        }
+        | Vl2pdf {
+            theme,
+            format_locale,
+            time_format_locale,
+            ..
+        }
+        | Vl2labels {
+            theme,
+            format_locale,
+            time_format_locale,
+            ..
+        }
+        | Export {
+            theme,
+            format_locale,
+            time_format_locale,
+            ..
+        }
+        | Vl2html {
+            theme,
+            format_locale,
+            time_format_locale,
+            ..
+        }
+        | Report {
+            theme,
+            format_locale,
+            time_format_locale,
+            ..
+        }
+        | Vg2svg {
+            theme,
+            format_locale,
+            time_format_locale,
+            ..
+        }
+        | Vg2stats {
+            theme,
+            format_locale,
+            time_format_locale,
+            ..
+        }
+        | Vg2png {
+            theme,
+            format_locale,
+            time_format_locale,
+            ..
+        }
+        | Vg2jpeg {
+            theme,
+            format_locale,
+            time_format_locale,
+            ..
+        }
+        | Vg2pdf {
+            theme,
+            format_locale,
+            time_format_locale,
+            ..
+        }
+        | Vg2html {
+            theme,
+            format_locale,
+            time_format_locale,
+            ..
+        } => (theme.iter().collect(), format_locale, time_format_locale),
+        Vl2vg { theme, .. } | Freeze { theme, .. } => (theme.iter().collect(), &None, &None),
+        _ => return Ok(()),
+    };
+
+    if !themes.is_empty() {
+        let theme_names = theme_names();
+        for theme in themes {
+            if !theme_names.contains(theme) {
+                bail!(
+                    "{} Run `vl-convert ls-themes` to list available themes.",
+                    unknown_name_message("theme", theme, &theme_names)
+                );
+            }
+        }
+    }
+    if let Some(name) = format_locale
+        .as_ref()
+        .filter(|name| !name.ends_with(".json"))
+    {
+        let names = format_locale_names();
+        if !names.contains(name) {
+            bail!(
+                "{} See https://github.com/d3/d3-format/tree/main/locale for available locales.",
+                unknown_name_message("format locale", name, &names)
+            );
+        }
+    }
+    if let Some(name) = time_format_locale
+        .as_ref()
+        .filter(|name| !name.ends_with(".json"))
+    {
+        let names = time_format_locale_names();
+        if !names.contains(name) {
+            bail!(
+                "{} See https://github.com/d3/d3-time-format/tree/main/locale for available \
+                locales.",
+                unknown_name_message("time format locale", name, &names)
+            );
+        }
+    }
+    Ok(())
+}
+
+fn write_output_string(output: &str, output_str: &str) -> Result<(), anyhow::Error> {
+    match std::fs::write(output, output_str) {
+        Ok(_) => Ok(()),
+        Err(err) => {
+            bail!("Failed to write converted output to {}\n{}", output, err);
+        }
+    }
 }
 
 fn write_output_binary(output: &str, output_data: &[u8]) -> Result<(), anyhow::Error> {
@@ -968,6 +3375,63 @@ fn write_output_binary(output: &str, output_data: &[u8]) -> Result<(), anyhow::E
     }
 }
 
+/// Path that the output for `theme` is written to when a chart is rendered with several
+/// themes: `<OUTPUT STEM>.<THEME>.<EXTENSION>`, in the directory of `output`
+fn themed_output_path(output: &str, theme: &str) -> String {
+    let path = Path::new(output);
+    let mut file_name = path.file_stem().unwrap_or_default().to_os_string();
+    file_name.push(format!(".{theme}"));
+    if let Some(extension) = path.extension() {
+        file_name.push(".");
+        file_name.push(extension);
+    }
+    path.with_file_name(file_name).to_string_lossy().to_string()
+}
+
+/// Render a chart on its own to write the rows of its datasets to `dir`, named after the
+/// output file without its extension
+async fn export_chart_data(
+    converter: &mut VlConverter,
+    vl_spec: serde_json::Value,
+    vl_opts: VlOpts,
+    output: &str,
+    dir: &str,
+    format: DataExportFormat,
+) -> Result<(), anyhow::Error> {
+    let request = ExportRequest {
+        export_data: true,
+        ..Default::default()
+    };
+    let datasets = match converter.vegalite_export(vl_spec, vl_opts, request).await {
+        Ok(result) => result.datasets.unwrap_or_default(),
+        Err(err) => {
+            bail!("Vega-Lite data export failed: {}", err);
+        }
+    };
+    let basename = Path::new(output)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy())
+        .unwrap_or_default();
+    write_datasets(&datasets, dir, &basename, format)
+}
+
+/// Write each dataset to `<dir>/<basename>.<dataset>.<format>`
+fn write_datasets(
+    datasets: &[ExportedDataset],
+    dir: &str,
+    basename: &str,
+    format: DataExportFormat,
+) -> Result<(), anyhow::Error> {
+    if let Err(err) = std::fs::create_dir_all(dir) {
+        bail!("Failed to create data directory {}\n{}", dir, err);
+    }
+    for dataset in datasets {
+        let path = Path::new(dir).join(dataset.file_name(basename, format));
+        write_output_string(&path.to_string_lossy(), &dataset.to_format(format)?)?;
+    }
+    Ok(())
+}
+
 fn normalize_config_path(config: Option<String>) -> Option<String> {
     match config {
         Some(config) => Some(shellexpand::tilde(config.trim()).to_string()),
@@ -982,6 +3446,51 @@ fn normalize_config_path(config: Option<String>) -> Option<String> {
     }
 }
 
+/// Write log messages to stderr so that they never mix with output written to stdout.
+///
+/// Messages are prefixed like `vl-convert: warning: ...`, and only errors are written when
+/// `quiet` is set, regardless of `log_level` and RUST_LOG
+fn init_logging(log_level: Option<log::LevelFilter>, quiet: bool) {
+    let mut builder = if quiet {
+        let mut builder = env_logger::Builder::new();
+        builder.filter_level(log::LevelFilter::Error);
+        builder
+    } else {
+        let mut builder =
+            env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("error"));
+        if let Some(log_level) = log_level {
+            builder.filter_level(log_level);
+        }
+        builder
+    };
+    builder
+        .format(|buf, record| {
+            let level = match record.level() {
+                log::Level::Error => "error",
+                log::Level::Warn => "warning",
+                log::Level::Info => "info",
+                log::Level::Debug => "debug",
+                log::Level::Trace => "trace",
+            };
+            writeln!(buf, "vl-convert: {level}: {}", record.args())
+        })
+        .target(env_logger::Target::Stderr)
+        .init();
+}
+
+fn read_interactions_file(
+    path: Option<String>,
+) -> Result<Option<Vec<InteractionStep>>, anyhow::Error> {
+    let Some(path) = path else {
+        return Ok(None);
+    };
+    let interactions_str = read_input_string(&path)?;
+    match serde_json::from_str(&interactions_str) {
+        Ok(interactions) => Ok(Some(interactions)),
+        Err(err) => bail!("Failed to parse interactions file {}: {}", path, err),
+    }
+}
+
 fn read_config_json(config: Option<String>) -> Result<Option<serde_json::Value>, anyhow::Error> {
     let config = normalize_config_path(config);
     match config {
@@ -999,6 +3508,17 @@ fn read_config_json(config: Option<String>) -> Result<Option<serde_json::Value>,
     }
 }
 
+/// Vega config is only read from an explicit path, since the default config file holds
+/// Vega-Lite config
+fn read_vega_config_json(
+    config: Option<String>,
+) -> Result<Option<serde_json::Value>, anyhow::Error> {
+    match config {
+        None => Ok(None),
+        Some(config) => read_config_json(Some(config)),
+    }
+}
+
 #[allow(clippy::too_many_arguments)]
 async fn vl_2_vg(
     input: &str,
@@ -1008,6 +3528,13 @@ async fn vl_2_vg(
     config: Option<String>,
     pretty: bool,
     show_warnings: bool,
+    preserve_int64: bool,
+    collect_resource_stats: bool,
+    font: Option<String>,
+    font_size_scale: Option<f32>,
+    text_color: Option<String>,
+    config_overrides: Option<Vec<(String, serde_json::Value)>>,
+    direction: Option<Direction>,
 ) -> Result<(), anyhow::Error> {
     // Parse version
     let vl_version = parse_vl_version(vl_version)?;
@@ -1036,6 +3563,39 @@ async fn vl_2_vg(
                 allowed_base_urls: None,
                 format_locale: None,
                 time_format_locale: None,
+                collect_resource_stats,
+                collect_dataset_summary: false,
+                fetch_retries: None,
+                fetch_retry_backoff_ms: None,
+                interaction_script: None,
+                preserve_int64,
+                base_url: None,
+                allow_local_data: false,
+                lenient: false,
+                font,
+                font_size_scale,
+                text_color,
+                config_overrides,
+                direction,
+                generic_font_mapping: None,
+                container_width: None,
+                container_height: None,
+                strict_sizing: false,
+                width: None,
+                height: None,
+                resize_mode: ResizeMode::default(),
+                min_size: None,
+                max_rows: None,
+                row_limit_strategy: RowLimitStrategy::default(),
+                annotate_truncation: false,
+                log_level: None,
+                warning_filters: None,
+                suppress_components: None,
+                browser_shims: true,
+                simplify_topojson: None,
+                pre_aggregate: false,
+                lenient_dates: false,
+                svg_post_processor: None,
             },
         )
         .await
@@ -1066,19 +3626,351 @@ async fn vl_2_vg(
     Ok(())
 }
 
+#[allow(clippy::too_many_arguments)]
+async fn vl_freeze(
+    input: &str,
+    output: &str,
+    vl_version: &str,
+    theme: Option<String>,
+    config: Option<String>,
+    allowed_base_urls: Option<Vec<String>>,
+    pretty: bool,
+    preserve_int64: bool,
+    collect_resource_stats: bool,
+    fetch_retries: Option<u32>,
+    fetch_retry_backoff_ms: Option<u64>,
+    base_url: Option<String>,
+    allow_local_data: bool,
+    font: Option<String>,
+    font_size_scale: Option<f32>,
+    text_color: Option<String>,
+    config_overrides: Option<Vec<(String, serde_json::Value)>>,
+    direction: Option<Direction>,
+) -> Result<ChartWarnings, anyhow::Error> {
+    // Parse version
+    let vl_version = parse_vl_version(vl_version)?;
+
+    // Read input file
+    let vegalite_str = read_input_string(input)?;
+
+    // Parse input as json
+    let vegalite_json = parse_as_json(&vegalite_str)?;
+
+    // Load config from file
+    let config = read_config_json(config)?;
+
+    // Initialize converter
+    let mut converter = VlConverter::new();
+
+    // Perform conversion
+    let frozen_json = match converter
+        .vegalite_freeze(
+            vegalite_json,
+            VlOpts {
+                vl_version,
+                theme,
+                config,
+                show_warnings: false,
+                allowed_base_urls,
+                format_locale: None,
+                time_format_locale: None,
+                collect_resource_stats,
+                collect_dataset_summary: true,
+                fetch_retries,
+                fetch_retry_backoff_ms,
+                interaction_script: None,
+                preserve_int64,
+                base_url: data_base_url(input, base_url, allow_local_data)?,
+                allow_local_data,
+                lenient: false,
+                font,
+                font_size_scale,
+                text_color,
+                config_overrides,
+                direction,
+                generic_font_mapping: None,
+                container_width: None,
+                container_height: None,
+                strict_sizing: false,
+                width: None,
+                height: None,
+                resize_mode: ResizeMode::default(),
+                min_size: None,
+                max_rows: None,
+                row_limit_strategy: RowLimitStrategy::default(),
+                annotate_truncation: false,
+                log_level: None,
+                warning_filters: None,
+                suppress_components: None,
+                browser_shims: true,
+                simplify_topojson: None,
+                pre_aggregate: false,
+                lenient_dates: false,
+                svg_post_processor: None,
+            },
+        )
+        .await
+    {
+        Ok(frozen_json) => frozen_json,
+        Err(err) => {
+            bail!("Vega-Lite freeze failed: {}", err);
+        }
+    };
+    let frozen_str_res = if pretty {
+        serde_json::to_string_pretty(&frozen_json)
+    } else {
+        serde_json::to_string(&frozen_json)
+    };
+    match frozen_str_res {
+        Ok(frozen_str) => {
+            // Write result
+            write_output_string(output, &frozen_str)?;
+        }
+        Err(err) => {
+            bail!(
+                "Failed to serialize Vega-Lite spec to JSON string: {}",
+                err.to_string()
+            )
+        }
+    }
+
+    Ok(ChartWarnings {
+        render_warnings: Vec::new(),
+        dataset_summary: converter.get_last_dataset_summary(),
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn vl_export(
+    input: &str,
+    output: &str,
+    formats: &[String],
+    vl_version: &str,
+    theme: Option<String>,
+    config: Option<String>,
+    scale: f32,
+    ppi: f32,
+    quality: u8,
+    jpeg_background: &str,
+    flatten_background: Option<String>,
+    show_warnings: bool,
+    allowed_base_urls: Option<Vec<String>>,
+    format_locale: Option<String>,
+    time_format_locale: Option<String>,
+    export_data: Option<String>,
+    export_data_format: &str,
+    collect_resource_stats: bool,
+    fetch_retries: Option<u32>,
+    fetch_retry_backoff_ms: Option<u64>,
+    base_url: Option<String>,
+    allow_local_data: bool,
+    lenient: bool,
+    interactions: Option<Vec<InteractionStep>>,
+    font: Option<String>,
+    font_size_scale: Option<f32>,
+    text_color: Option<String>,
+    config_overrides: Option<Vec<(String, serde_json::Value)>>,
+    direction: Option<Direction>,
+    generic_font_mapping: Option<HashMap<String, String>>,
+    container_width: f32,
+    container_height: f32,
+    strict_sizing: bool,
+    width: Option<f32>,
+    height: Option<f32>,
+    resize_mode: ResizeMode,
+    min_size: Option<(u32, u32)>,
+    max_rows: Option<u64>,
+    row_limit_strategy: RowLimitStrategy,
+    annotate_truncation: bool,
+    log_level: Option<LogLevel>,
+    warning_filters: Option<Vec<String>>,
+    suppress_components: Option<Vec<Component>>,
+    browser_shims: bool,
+    simplify_topojson: Option<f64>,
+    lenient_dates: bool,
+) -> Result<ChartWarnings, anyhow::Error> {
+    // Parse version
+    let vl_version = parse_vl_version(vl_version)?;
+
+    // Parse formats
+    let formats = formats
+        .iter()
+        .map(|format| ExportFormat::from_str(format))
+        .collect::<Result<Vec<_>, _>>()?;
+    let data_format = DataExportFormat::from_str(export_data_format)?;
+
+    // Read input file
+    let vegalite_str = read_input_string(input)?;
+
+    // Parse input as json
+    let vl_spec = parse_as_json(&vegalite_str)?;
+
+    // Load config from file
+    let config = read_config_json(config)?;
+
+    let format_locale = match &format_locale {
+        None => None,
+        Some(p) => Some(format_locale_from_str(p)?),
+    };
+
+    let time_format_locale = match &time_format_locale {
+        None => None,
+        Some(p) => Some(time_format_locale_from_str(p)?),
+    };
+
+    // Raster options only apply to raster formats
+    let mut request = ExportRequest::from_formats(&formats);
+    if let Some(png) = &mut request.png {
+        png.scale = Some(scale);
+        png.ppi = Some(ppi);
+        png.png_opts.flatten_background = flatten_background
+            .as_deref()
+            .map(BackgroundColor::from_str)
+            .transpose()?;
+    }
+    if let Some(jpeg) = &mut request.jpeg {
+        jpeg.scale = Some(scale);
+        jpeg.quality = Some(quality);
+        jpeg.jpeg_opts.background = BackgroundColor::from_str(jpeg_background)?;
+    }
+    request.export_data = export_data.is_some();
+
+    // Initialize converter
+    let mut converter = VlConverter::new();
+
+    // Perform conversion
+    let result = match converter
+        .vegalite_export(
+            vl_spec,
+            VlOpts {
+                vl_version,
+                config,
+                theme,
+                show_warnings,
+                allowed_base_urls,
+                format_locale,
+                time_format_locale,
+                collect_resource_stats,
+                collect_dataset_summary: true,
+                fetch_retries,
+                fetch_retry_backoff_ms,
+                interaction_script: interactions,
+                preserve_int64: false,
+                base_url: data_base_url(input, base_url, allow_local_data)?,
+                allow_local_data,
+                lenient,
+                font,
+                font_size_scale,
+                text_color,
+                config_overrides,
+                direction,
+                generic_font_mapping,
+                container_width: Some(container_width),
+                container_height: Some(container_height),
+                strict_sizing,
+                width,
+                height,
+                resize_mode,
+                min_size,
+                max_rows,
+                row_limit_strategy,
+                annotate_truncation,
+                log_level,
+                warning_filters,
+                suppress_components,
+                browser_shims,
+                simplify_topojson,
+                lenient_dates,
+                pre_aggregate: false,
+                svg_post_processor: None,
+            },
+            request,
+        )
+        .await
+    {
+        Ok(result) => result,
+        Err(err) => {
+            bail!("Vega-Lite export failed: {}", err);
+        }
+    };
+
+    // Write results
+    if let Some(svg) = &result.svg {
+        write_output_string(&format!("{output}.svg"), svg)?;
+    }
+    for (format, data) in [
+        (ExportFormat::Png, &result.png),
+        (ExportFormat::Jpeg, &result.jpeg),
+        (ExportFormat::Pdf, &result.pdf),
+    ] {
+        if let Some(data) = data {
+            write_output_binary(&format!("{output}.{format}"), data)?;
+        }
+    }
+    if let (Some(dir), Some(datasets)) = (&export_data, &result.datasets) {
+        let basename = Path::new(output)
+            .file_name()
+            .map(|name| name.to_string_lossy())
+            .unwrap_or_default();
+        write_datasets(datasets, dir, &basename, data_format)?;
+    }
+
+    Ok(ChartWarnings {
+        render_warnings: result.warnings,
+        dataset_summary: converter.get_last_dataset_summary(),
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
 async fn vg_2_svg(
     input: &str,
     output: &str,
+    theme: Option<String>,
+    config: Option<String>,
     allowed_base_urls: Option<Vec<String>>,
     format_locale: Option<String>,
     time_format_locale: Option<String>,
-) -> Result<(), anyhow::Error> {
+    dump_final_spec: Option<String>,
+    svg_text_length: bool,
+    svg_native_tooltips: bool,
+    svg_minify: bool,
+    svg_precision: u8,
+    collect_resource_stats: bool,
+    fetch_retries: Option<u32>,
+    fetch_retry_backoff_ms: Option<u64>,
+    base_url: Option<String>,
+    allow_local_data: bool,
+    lenient: bool,
+    interactions: Option<Vec<InteractionStep>>,
+    generic_font_mapping: Option<HashMap<String, String>>,
+    container_width: f32,
+    container_height: f32,
+    strict_sizing: bool,
+    width: Option<f32>,
+    height: Option<f32>,
+    resize_mode: ResizeMode,
+    min_size: Option<(u32, u32)>,
+    max_rows: Option<u64>,
+    row_limit_strategy: RowLimitStrategy,
+    annotate_truncation: bool,
+    log_level: Option<LogLevel>,
+    warning_filters: Option<Vec<String>>,
+    suppress_components: Option<Vec<Component>>,
+    browser_shims: bool,
+    simplify_topojson: Option<f64>,
+    force_iterations: Option<u32>,
+    random_seed: Option<u32>,
+    verbose: bool,
+) -> Result<ChartWarnings, anyhow::Error> {
     // Read input file
     let vega_str = read_input_string(input)?;
 
     // Parse input as json
     let vg_spec = parse_as_json(&vega_str)?;
 
+    // Load config from file
+    let config = read_vega_config_json(config)?;
+
     let format_locale = match &format_locale {
         None => None,
         Some(p) => Some(format_locale_from_str(p)?),
@@ -1092,44 +3984,664 @@ async fn vg_2_svg(
     // Initialize converter
     let mut converter = VlConverter::new();
 
+    let vg_opts = VgOpts {
+        theme,
+        config,
+        allowed_base_urls,
+        format_locale,
+        time_format_locale,
+        collect_resource_stats,
+        collect_dataset_summary: true,
+        fetch_retries,
+        fetch_retry_backoff_ms,
+        interaction_script: interactions,
+        preserve_int64: false,
+        base_url: data_base_url(input, base_url, allow_local_data)?,
+        allow_local_data,
+        lenient,
+        generic_font_mapping,
+        container_width: Some(container_width),
+        container_height: Some(container_height),
+        strict_sizing,
+        width,
+        height,
+        resize_mode,
+        min_size,
+        max_rows,
+        row_limit_strategy,
+        annotate_truncation,
+        log_level,
+        warning_filters,
+        suppress_components,
+        browser_shims,
+        simplify_topojson,
+        force_iterations,
+        random_seed,
+        svg_post_processor: None,
+    };
+
+    if let Some(dump_final_spec) = &dump_final_spec {
+        let final_spec = converter
+            .get_final_vega_spec_vg(vg_spec.clone(), vg_opts.clone())
+            .await?;
+        write_output_string(dump_final_spec, &serde_json::to_string_pretty(&final_spec)?)?;
+    }
+
     // Perform conversion
+    let svg_opts = SvgOpts {
+        text_length: svg_text_length,
+        native_tooltips: svg_native_tooltips,
+        minify: svg_minify,
+        precision: Some(svg_precision),
+    };
     let svg = match converter
-        .vega_to_svg(
+        .vega_to_svg_info_with_opts(vg_spec, vg_opts, svg_opts)
+        .await
+    {
+        Ok(svg) => svg,
+        Err(err) => {
+            bail!("Vega to SVG conversion failed: {}", err);
+        }
+    };
+
+    // Write result
+    write_output_string(output, &svg.data)?;
+    if verbose {
+        print_svg_size(&svg);
+    }
+
+    Ok(ChartWarnings {
+        render_warnings: svg.warnings,
+        dataset_summary: converter.get_last_dataset_summary(),
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn vg_2_stats(
+    input: &str,
+    output: Option<&str>,
+    theme: Option<String>,
+    config: Option<String>,
+    allowed_base_urls: Option<Vec<String>>,
+    format_locale: Option<String>,
+    time_format_locale: Option<String>,
+    collect_resource_stats: bool,
+    fetch_retries: Option<u32>,
+    fetch_retry_backoff_ms: Option<u64>,
+    base_url: Option<String>,
+    allow_local_data: bool,
+    lenient: bool,
+    interactions: Option<Vec<InteractionStep>>,
+    generic_font_mapping: Option<HashMap<String, String>>,
+    container_width: f32,
+    container_height: f32,
+    strict_sizing: bool,
+    width: Option<f32>,
+    height: Option<f32>,
+    resize_mode: ResizeMode,
+    min_size: Option<(u32, u32)>,
+    max_rows: Option<u64>,
+    row_limit_strategy: RowLimitStrategy,
+    annotate_truncation: bool,
+    log_level: Option<LogLevel>,
+    warning_filters: Option<Vec<String>>,
+    suppress_components: Option<Vec<Component>>,
+    browser_shims: bool,
+    simplify_topojson: Option<f64>,
+    force_iterations: Option<u32>,
+    random_seed: Option<u32>,
+) -> Result<ChartWarnings, anyhow::Error> {
+    // Read input file
+    let vega_str = read_input_string(input)?;
+
+    // Parse input as json
+    let vg_spec = parse_as_json(&vega_str)?;
+
+    // Load config from file
+    let config = read_vega_config_json(config)?;
+
+    let format_locale = match &format_locale {
+        None => None,
+        Some(p) => Some(format_locale_from_str(p)?),
+    };
+
+    let time_format_locale = match &time_format_locale {
+        None => None,
+        Some(p) => Some(time_format_locale_from_str(p)?),
+    };
+
+    // Initialize converter
+    let mut converter = VlConverter::new();
+
+    // Perform conversion
+    let render_stats = match converter
+        .vega_get_render_stats(
             vg_spec,
             VgOpts {
+                theme,
+                config,
                 allowed_base_urls,
                 format_locale,
                 time_format_locale,
+                collect_resource_stats,
+                collect_dataset_summary: true,
+                fetch_retries,
+                fetch_retry_backoff_ms,
+                interaction_script: interactions,
+                preserve_int64: false,
+                base_url: data_base_url(input, base_url, allow_local_data)?,
+                allow_local_data,
+                lenient,
+                generic_font_mapping,
+                container_width: Some(container_width),
+                container_height: Some(container_height),
+                strict_sizing,
+                width,
+                height,
+                resize_mode,
+                min_size,
+                max_rows,
+                row_limit_strategy,
+                annotate_truncation,
+                log_level,
+                warning_filters,
+                suppress_components,
+                browser_shims,
+                simplify_topojson,
+                force_iterations,
+                random_seed,
+                svg_post_processor: None,
             },
         )
         .await
     {
-        Ok(svg) => svg,
+        Ok(render_stats) => render_stats,
+        Err(err) => {
+            bail!("Vega render stats failed: {}", err);
+        }
+    };
+
+    // Write result
+    let json = serde_json::to_string_pretty(&render_stats)?;
+    match output {
+        Some(output) => write_output_string(output, &json)?,
+        None => println!("{}", json),
+    }
+
+    Ok(ChartWarnings {
+        render_warnings: Vec::new(),
+        dataset_summary: converter.get_last_dataset_summary(),
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn vg_2_png(
+    input: &str,
+    output: &str,
+    theme: Option<String>,
+    config: Option<String>,
+    scale: f32,
+    ppi: f32,
+    png_interlaced: bool,
+    no_antialias: bool,
+    ppi_mode: &str,
+    image_rendering: &str,
+    flatten_background: Option<String>,
+    allowed_base_urls: Option<Vec<String>>,
+    format_locale: Option<String>,
+    time_format_locale: Option<String>,
+    dump_final_spec: Option<String>,
+    collect_resource_stats: bool,
+    fetch_retries: Option<u32>,
+    fetch_retry_backoff_ms: Option<u64>,
+    base_url: Option<String>,
+    allow_local_data: bool,
+    lenient: bool,
+    interactions: Option<Vec<InteractionStep>>,
+    generic_font_mapping: Option<HashMap<String, String>>,
+    container_width: f32,
+    container_height: f32,
+    strict_sizing: bool,
+    width: Option<f32>,
+    height: Option<f32>,
+    resize_mode: ResizeMode,
+    min_size: Option<(u32, u32)>,
+    max_rows: Option<u64>,
+    row_limit_strategy: RowLimitStrategy,
+    annotate_truncation: bool,
+    log_level: Option<LogLevel>,
+    warning_filters: Option<Vec<String>>,
+    suppress_components: Option<Vec<Component>>,
+    browser_shims: bool,
+    simplify_topojson: Option<f64>,
+    force_iterations: Option<u32>,
+    random_seed: Option<u32>,
+    verbose: bool,
+) -> Result<ChartWarnings, anyhow::Error> {
+    // Read input file
+    let vega_str = read_input_string(input)?;
+
+    // Parse input as json
+    let vg_spec = parse_as_json(&vega_str)?;
+
+    // Load config from file
+    let config = read_vega_config_json(config)?;
+
+    let format_locale = match &format_locale {
+        None => None,
+        Some(p) => Some(format_locale_from_str(p)?),
+    };
+
+    let time_format_locale = match &time_format_locale {
+        None => None,
+        Some(p) => Some(time_format_locale_from_str(p)?),
+    };
+
+    // Initialize converter
+    let mut converter = VlConverter::new();
+
+    let vg_opts = VgOpts {
+        theme,
+        config,
+        allowed_base_urls,
+        format_locale,
+        time_format_locale,
+        collect_resource_stats,
+        collect_dataset_summary: true,
+        fetch_retries,
+        fetch_retry_backoff_ms,
+        interaction_script: interactions,
+        preserve_int64: false,
+        base_url: data_base_url(input, base_url, allow_local_data)?,
+        allow_local_data,
+        lenient,
+        generic_font_mapping,
+        container_width: Some(container_width),
+        container_height: Some(container_height),
+        strict_sizing,
+        width,
+        height,
+        resize_mode,
+        min_size,
+        max_rows,
+        row_limit_strategy,
+        annotate_truncation,
+        log_level,
+        warning_filters,
+        suppress_components,
+        browser_shims,
+        simplify_topojson,
+        force_iterations,
+        random_seed,
+        svg_post_processor: None,
+    };
+
+    if let Some(dump_final_spec) = &dump_final_spec {
+        let final_spec = converter
+            .get_final_vega_spec_vg(vg_spec.clone(), vg_opts.clone())
+            .await?;
+        write_output_string(dump_final_spec, &serde_json::to_string_pretty(&final_spec)?)?;
+    }
+
+    // Perform conversion
+    let png = match converter
+        .vega_to_png_info_with_opts(
+            vg_spec,
+            vg_opts,
+            Some(scale),
+            Some(ppi),
+            PngOpts {
+                interlaced: png_interlaced,
+                antialias: !no_antialias,
+                ppi_mode: PpiMode::from_str(ppi_mode)?,
+                image_rendering: ImageRendering::from_str(image_rendering)?,
+                flatten_background: flatten_background
+                    .as_deref()
+                    .map(BackgroundColor::from_str)
+                    .transpose()?,
+                ..Default::default()
+            },
+        )
+        .await
+    {
+        Ok(png) => png,
+        Err(err) => {
+            bail!("Vega to PNG conversion failed: {}", err);
+        }
+    };
+
+    // Write result
+    write_output_binary(output, &png.data)?;
+    if verbose {
+        print_png_size(&png);
+    }
+
+    Ok(ChartWarnings {
+        render_warnings: png.warnings,
+        dataset_summary: converter.get_last_dataset_summary(),
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn vg_2_jpeg(
+    input: &str,
+    output: &str,
+    theme: Option<String>,
+    config: Option<String>,
+    scale: f32,
+    quality: u8,
+    jpeg_background: &str,
+    allowed_base_urls: Option<Vec<String>>,
+    format_locale: Option<String>,
+    time_format_locale: Option<String>,
+    dump_final_spec: Option<String>,
+    collect_resource_stats: bool,
+    fetch_retries: Option<u32>,
+    fetch_retry_backoff_ms: Option<u64>,
+    base_url: Option<String>,
+    allow_local_data: bool,
+    lenient: bool,
+    interactions: Option<Vec<InteractionStep>>,
+    generic_font_mapping: Option<HashMap<String, String>>,
+    container_width: f32,
+    container_height: f32,
+    strict_sizing: bool,
+    width: Option<f32>,
+    height: Option<f32>,
+    resize_mode: ResizeMode,
+    min_size: Option<(u32, u32)>,
+    max_rows: Option<u64>,
+    row_limit_strategy: RowLimitStrategy,
+    annotate_truncation: bool,
+    log_level: Option<LogLevel>,
+    warning_filters: Option<Vec<String>>,
+    suppress_components: Option<Vec<Component>>,
+    browser_shims: bool,
+    simplify_topojson: Option<f64>,
+    force_iterations: Option<u32>,
+    random_seed: Option<u32>,
+    verbose: bool,
+) -> Result<ChartWarnings, anyhow::Error> {
+    // Read input file
+    let vega_str = read_input_string(input)?;
+
+    // Parse input as json
+    let vg_spec = parse_as_json(&vega_str)?;
+
+    // Load config from file
+    let config = read_vega_config_json(config)?;
+
+    let format_locale = match &format_locale {
+        None => None,
+        Some(p) => Some(format_locale_from_str(p)?),
+    };
+
+    let time_format_locale = match &time_format_locale {
+        None => None,
+        Some(p) => Some(time_format_locale_from_str(p)?),
+    };
+
+    // Initialize converter
+    let mut converter = VlConverter::new();
+
+    let vg_opts = VgOpts {
+        theme,
+        config,
+        allowed_base_urls,
+        format_locale,
+        time_format_locale,
+        collect_resource_stats,
+        collect_dataset_summary: true,
+        fetch_retries,
+        fetch_retry_backoff_ms,
+        interaction_script: interactions,
+        preserve_int64: false,
+        base_url: data_base_url(input, base_url, allow_local_data)?,
+        allow_local_data,
+        lenient,
+        generic_font_mapping,
+        container_width: Some(container_width),
+        container_height: Some(container_height),
+        strict_sizing,
+        width,
+        height,
+        resize_mode,
+        min_size,
+        max_rows,
+        row_limit_strategy,
+        annotate_truncation,
+        log_level,
+        warning_filters,
+        suppress_components,
+        browser_shims,
+        simplify_topojson,
+        force_iterations,
+        random_seed,
+        svg_post_processor: None,
+    };
+
+    if let Some(dump_final_spec) = &dump_final_spec {
+        let final_spec = converter
+            .get_final_vega_spec_vg(vg_spec.clone(), vg_opts.clone())
+            .await?;
+        write_output_string(dump_final_spec, &serde_json::to_string_pretty(&final_spec)?)?;
+    }
+
+    // Perform conversion
+    let jpeg = match converter
+        .vega_to_jpeg_info_with_opts(
+            vg_spec,
+            vg_opts,
+            Some(scale),
+            Some(quality),
+            JpegOpts {
+                background: BackgroundColor::from_str(jpeg_background)?,
+            },
+        )
+        .await
+    {
+        Ok(jpeg) => jpeg,
+        Err(err) => {
+            bail!("Vega to JPEG conversion failed: {}", err);
+        }
+    };
+
+    // Write result
+    write_output_binary(output, &jpeg.data)?;
+    if verbose {
+        print_jpeg_size(&jpeg);
+    }
+
+    Ok(ChartWarnings {
+        render_warnings: jpeg.warnings,
+        dataset_summary: converter.get_last_dataset_summary(),
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn vg_2_pdf(
+    input: &str,
+    output: &str,
+    theme: Option<String>,
+    config: Option<String>,
+    allowed_base_urls: Option<Vec<String>>,
+    format_locale: Option<String>,
+    time_format_locale: Option<String>,
+    dump_final_spec: Option<String>,
+    collect_resource_stats: bool,
+    fetch_retries: Option<u32>,
+    fetch_retry_backoff_ms: Option<u64>,
+    base_url: Option<String>,
+    allow_local_data: bool,
+    lenient: bool,
+    interactions: Option<Vec<InteractionStep>>,
+    generic_font_mapping: Option<HashMap<String, String>>,
+    container_width: f32,
+    container_height: f32,
+    strict_sizing: bool,
+    width: Option<f32>,
+    height: Option<f32>,
+    resize_mode: ResizeMode,
+    min_size: Option<(u32, u32)>,
+    max_rows: Option<u64>,
+    row_limit_strategy: RowLimitStrategy,
+    annotate_truncation: bool,
+    log_level: Option<LogLevel>,
+    warning_filters: Option<Vec<String>>,
+    suppress_components: Option<Vec<Component>>,
+    browser_shims: bool,
+    simplify_topojson: Option<f64>,
+    force_iterations: Option<u32>,
+    random_seed: Option<u32>,
+    pdfa: bool,
+    source_dpi: f32,
+) -> Result<ChartWarnings, anyhow::Error> {
+    // Read input file
+    let vega_str = read_input_string(input)?;
+
+    // Parse input as json
+    let vg_spec = parse_as_json(&vega_str)?;
+
+    // Load config from file
+    let config = read_vega_config_json(config)?;
+
+    let format_locale = match &format_locale {
+        None => None,
+        Some(p) => Some(format_locale_from_str(p)?),
+    };
+
+    let time_format_locale = match &time_format_locale {
+        None => None,
+        Some(p) => Some(time_format_locale_from_str(p)?),
+    };
+
+    // Initialize converter
+    let mut converter = VlConverter::new();
+
+    let vg_opts = VgOpts {
+        theme,
+        config,
+        allowed_base_urls,
+        format_locale,
+        time_format_locale,
+        collect_resource_stats,
+        collect_dataset_summary: true,
+        fetch_retries,
+        fetch_retry_backoff_ms,
+        interaction_script: interactions,
+        preserve_int64: false,
+        base_url: data_base_url(input, base_url, allow_local_data)?,
+        allow_local_data,
+        lenient,
+        generic_font_mapping,
+        container_width: Some(container_width),
+        container_height: Some(container_height),
+        strict_sizing,
+        width,
+        height,
+        resize_mode,
+        min_size,
+        max_rows,
+        row_limit_strategy,
+        annotate_truncation,
+        log_level,
+        warning_filters,
+        suppress_components,
+        browser_shims,
+        simplify_topojson,
+        force_iterations,
+        random_seed,
+        svg_post_processor: None,
+    };
+
+    if let Some(dump_final_spec) = &dump_final_spec {
+        let final_spec = converter
+            .get_final_vega_spec_vg(vg_spec.clone(), vg_opts.clone())
+            .await?;
+        write_output_string(dump_final_spec, &serde_json::to_string_pretty(&final_spec)?)?;
+    }
+
+    // Perform conversion
+    let pdf = match converter
+        .vega_to_pdf_info_with_opts(vg_spec, vg_opts, PdfOpts { pdfa, source_dpi })
+        .await
+    {
+        Ok(pdf) => pdf,
         Err(err) => {
-            bail!("Vega to SVG conversion failed: {}", err);
+            bail!("Vega to PDF conversion failed: {}", err);
         }
     };
 
     // Write result
-    write_output_string(output, &svg)?;
+    write_output_binary(output, &pdf.data)?;
 
-    Ok(())
+    Ok(ChartWarnings {
+        render_warnings: pdf.warnings,
+        dataset_summary: converter.get_last_dataset_summary(),
+    })
 }
 
-async fn vg_2_png(
+#[allow(clippy::too_many_arguments)]
+async fn vl_2_svg(
     input: &str,
     output: &str,
-    scale: f32,
-    ppi: f32,
+    vl_version: &str,
+    theme: Vec<String>,
+    config: Option<String>,
+    show_warnings: bool,
     allowed_base_urls: Option<Vec<String>>,
     format_locale: Option<String>,
     time_format_locale: Option<String>,
-) -> Result<(), anyhow::Error> {
+    dump_final_spec: Option<String>,
+    export_data: Option<String>,
+    export_data_format: &str,
+    svg_text_length: bool,
+    svg_native_tooltips: bool,
+    svg_minify: bool,
+    svg_precision: u8,
+    explain_static: bool,
+    collect_resource_stats: bool,
+    fetch_retries: Option<u32>,
+    fetch_retry_backoff_ms: Option<u64>,
+    base_url: Option<String>,
+    allow_local_data: bool,
+    lenient: bool,
+    interactions: Option<Vec<InteractionStep>>,
+    font: Option<String>,
+    font_size_scale: Option<f32>,
+    text_color: Option<String>,
+    config_overrides: Option<Vec<(String, serde_json::Value)>>,
+    direction: Option<Direction>,
+    generic_font_mapping: Option<HashMap<String, String>>,
+    container_width: f32,
+    container_height: f32,
+    strict_sizing: bool,
+    width: Option<f32>,
+    height: Option<f32>,
+    resize_mode: ResizeMode,
+    min_size: Option<(u32, u32)>,
+    max_rows: Option<u64>,
+    row_limit_strategy: RowLimitStrategy,
+    annotate_truncation: bool,
+    log_level: Option<LogLevel>,
+    warning_filters: Option<Vec<String>>,
+    suppress_components: Option<Vec<Component>>,
+    browser_shims: bool,
+    simplify_topojson: Option<f64>,
+    lenient_dates: bool,
+    verbose: bool,
+) -> Result<ChartWarnings, anyhow::Error> {
+    // Parse version
+    let vl_version = parse_vl_version(vl_version)?;
+
     // Read input file
-    let vega_str = read_input_string(input)?;
+    let vegalite_str = read_input_string(input)?;
 
     // Parse input as json
-    let vg_spec = parse_as_json(&vega_str)?;
+    let vl_spec = parse_as_json(&vegalite_str)?;
+
+    // Load config from file
+    let config = read_config_json(config)?;
 
     let format_locale = match &format_locale {
         None => None,
@@ -1141,49 +4653,181 @@ async fn vg_2_png(
         Some(p) => Some(time_format_locale_from_str(p)?),
     };
 
+    // With several themes, the chart is rendered once per theme
+    let (theme, themes) = match theme.len() {
+        0 | 1 => (theme.into_iter().next(), None),
+        _ => (None, Some(theme)),
+    };
+    if themes.is_some() && dump_final_spec.is_some() {
+        bail!("--dump-final-spec can't be used with more than one --theme");
+    }
+
     // Initialize converter
     let mut converter = VlConverter::new();
 
-    // Perform conversion
-    let png_data = match converter
-        .vega_to_png(
-            vg_spec,
-            VgOpts {
-                allowed_base_urls,
-                format_locale,
-                time_format_locale,
-            },
-            Some(scale),
-            Some(ppi),
+    let vl_opts = VlOpts {
+        vl_version,
+        config,
+        theme,
+        show_warnings,
+        allowed_base_urls,
+        format_locale,
+        time_format_locale,
+        collect_resource_stats,
+        collect_dataset_summary: true,
+        fetch_retries,
+        fetch_retry_backoff_ms,
+        interaction_script: interactions,
+        preserve_int64: false,
+        base_url: data_base_url(input, base_url, allow_local_data)?,
+        allow_local_data,
+        lenient,
+        font,
+        font_size_scale,
+        text_color,
+        config_overrides,
+        direction,
+        generic_font_mapping,
+        container_width: Some(container_width),
+        container_height: Some(container_height),
+        strict_sizing,
+        width,
+        height,
+        resize_mode,
+        min_size,
+        max_rows,
+        row_limit_strategy,
+        annotate_truncation,
+        log_level,
+        warning_filters,
+        suppress_components,
+        browser_shims,
+        simplify_topojson,
+        lenient_dates,
+        pre_aggregate: false,
+        svg_post_processor: None,
+    };
+
+    if let Some(dump_final_spec) = &dump_final_spec {
+        let final_spec = converter
+            .get_final_vega_spec(vl_spec.clone(), vl_opts.clone())
+            .await?;
+        write_output_string(dump_final_spec, &serde_json::to_string_pretty(&final_spec)?)?;
+    }
+
+    if let Some(export_data) = &export_data {
+        let format = DataExportFormat::from_str(export_data_format)?;
+        export_chart_data(
+            &mut converter,
+            vl_spec.clone(),
+            vl_opts.clone(),
+            output,
+            export_data,
+            format,
         )
+        .await?;
+    }
+
+    if explain_static {
+        print_static_export_findings(&mut converter, vl_spec.clone(), vl_opts.clone()).await?;
+    }
+
+    // Perform conversion
+    let svg_opts = SvgOpts {
+        text_length: svg_text_length,
+        native_tooltips: svg_native_tooltips,
+        minify: svg_minify,
+        precision: Some(svg_precision),
+    };
+    if let Some(themes) = themes {
+        let themes: Vec<&str> = themes.iter().map(String::as_str).collect();
+        let svgs = match converter
+            .vegalite_to_svg_themed_with_opts(vl_spec, vl_opts, &themes, svg_opts)
+            .await
+        {
+            Ok(svgs) => svgs,
+            Err(err) => {
+                bail!("Vega-Lite to SVG conversion failed: {}", err);
+            }
+        };
+        for theme in themes {
+            write_output_string(&themed_output_path(output, theme), &svgs[theme])?;
+        }
+        return Ok(ChartWarnings::default());
+    }
+    let svg = match converter
+        .vegalite_to_svg_info_with_opts(vl_spec, vl_opts, svg_opts)
         .await
     {
-        Ok(png_data) => png_data,
+        Ok(svg) => svg,
         Err(err) => {
-            bail!("Vega to PNG conversion failed: {}", err);
+            bail!("Vega-Lite to SVG conversion failed: {}", err);
         }
     };
 
     // Write result
-    write_output_binary(output, &png_data)?;
+    write_output_string(output, &svg.data)?;
+    if verbose {
+        print_svg_size(&svg);
+    }
 
-    Ok(())
+    Ok(ChartWarnings {
+        render_warnings: svg.warnings,
+        dataset_summary: converter.get_last_dataset_summary(),
+    })
 }
 
-async fn vg_2_jpeg(
+#[allow(clippy::too_many_arguments)]
+async fn vl_2_labels(
     input: &str,
-    output: &str,
-    scale: f32,
-    quality: u8,
+    output: Option<&str>,
+    vl_version: &str,
+    theme: Option<String>,
+    config: Option<String>,
+    show_warnings: bool,
     allowed_base_urls: Option<Vec<String>>,
     format_locale: Option<String>,
     time_format_locale: Option<String>,
-) -> Result<(), anyhow::Error> {
+    collect_resource_stats: bool,
+    fetch_retries: Option<u32>,
+    fetch_retry_backoff_ms: Option<u64>,
+    base_url: Option<String>,
+    allow_local_data: bool,
+    interactions: Option<Vec<InteractionStep>>,
+    font: Option<String>,
+    font_size_scale: Option<f32>,
+    text_color: Option<String>,
+    config_overrides: Option<Vec<(String, serde_json::Value)>>,
+    direction: Option<Direction>,
+    generic_font_mapping: Option<HashMap<String, String>>,
+    container_width: f32,
+    container_height: f32,
+    strict_sizing: bool,
+    width: Option<f32>,
+    height: Option<f32>,
+    resize_mode: ResizeMode,
+    min_size: Option<(u32, u32)>,
+    max_rows: Option<u64>,
+    row_limit_strategy: RowLimitStrategy,
+    annotate_truncation: bool,
+    log_level: Option<LogLevel>,
+    warning_filters: Option<Vec<String>>,
+    suppress_components: Option<Vec<Component>>,
+    browser_shims: bool,
+    simplify_topojson: Option<f64>,
+    lenient_dates: bool,
+) -> Result<ChartWarnings, anyhow::Error> {
+    // Parse version
+    let vl_version = parse_vl_version(vl_version)?;
+
     // Read input file
-    let vega_str = read_input_string(input)?;
+    let vegalite_str = read_input_string(input)?;
 
     // Parse input as json
-    let vg_spec = parse_as_json(&vega_str)?;
+    let vl_spec = parse_as_json(&vegalite_str)?;
+
+    // Load config from file
+    let config = read_config_json(config)?;
 
     let format_locale = match &format_locale {
         None => None,
@@ -1199,43 +4843,141 @@ async fn vg_2_jpeg(
     let mut converter = VlConverter::new();
 
     // Perform conversion
-    let jpeg_data = match converter
-        .vega_to_jpeg(
-            vg_spec,
-            VgOpts {
+    let text_items = match converter
+        .vegalite_get_text_items(
+            vl_spec,
+            VlOpts {
+                vl_version,
+                config,
+                theme,
+                show_warnings,
                 allowed_base_urls,
                 format_locale,
                 time_format_locale,
+                collect_resource_stats,
+                collect_dataset_summary: true,
+                fetch_retries,
+                fetch_retry_backoff_ms,
+                interaction_script: interactions,
+                preserve_int64: false,
+                base_url: data_base_url(input, base_url, allow_local_data)?,
+                allow_local_data,
+                lenient: false,
+                font,
+                font_size_scale,
+                text_color,
+                config_overrides,
+                direction,
+                generic_font_mapping,
+                container_width: Some(container_width),
+                container_height: Some(container_height),
+                strict_sizing,
+                width,
+                height,
+                resize_mode,
+                min_size,
+                max_rows,
+                row_limit_strategy,
+                annotate_truncation,
+                log_level,
+                warning_filters,
+                suppress_components,
+                browser_shims,
+                simplify_topojson,
+                lenient_dates,
+                pre_aggregate: false,
+                svg_post_processor: None,
             },
-            Some(scale),
-            Some(quality),
         )
         .await
     {
-        Ok(jpeg_data) => jpeg_data,
+        Ok(text_items) => text_items,
         Err(err) => {
-            bail!("Vega to JPEG conversion failed: {}", err);
+            bail!("Vega-Lite text item extraction failed: {}", err);
         }
     };
 
-    // Write result
-    write_output_binary(output, &jpeg_data)?;
+    // Write one JSON object per line
+    let mut lines = String::new();
+    for text_item in &text_items {
+        lines.push_str(&serde_json::to_string(text_item)?);
+        lines.push('\n');
+    }
+    match output {
+        Some(output) => write_output_string(output, &lines)?,
+        None => print!("{}", lines),
+    }
 
-    Ok(())
+    Ok(ChartWarnings {
+        render_warnings: Vec::new(),
+        dataset_summary: converter.get_last_dataset_summary(),
+    })
 }
 
-async fn vg_2_pdf(
+#[allow(clippy::too_many_arguments)]
+async fn vl_2_png(
     input: &str,
     output: &str,
+    vl_version: &str,
+    theme: Vec<String>,
+    config: Option<String>,
+    scale: f32,
+    ppi: f32,
+    png_interlaced: bool,
+    no_antialias: bool,
+    ppi_mode: &str,
+    image_rendering: &str,
+    flatten_background: Option<String>,
+    show_warnings: bool,
     allowed_base_urls: Option<Vec<String>>,
     format_locale: Option<String>,
     time_format_locale: Option<String>,
-) -> Result<(), anyhow::Error> {
+    dump_final_spec: Option<String>,
+    export_data: Option<String>,
+    export_data_format: &str,
+    explain_static: bool,
+    collect_resource_stats: bool,
+    fetch_retries: Option<u32>,
+    fetch_retry_backoff_ms: Option<u64>,
+    base_url: Option<String>,
+    allow_local_data: bool,
+    lenient: bool,
+    interactions: Option<Vec<InteractionStep>>,
+    font: Option<String>,
+    font_size_scale: Option<f32>,
+    text_color: Option<String>,
+    config_overrides: Option<Vec<(String, serde_json::Value)>>,
+    direction: Option<Direction>,
+    generic_font_mapping: Option<HashMap<String, String>>,
+    container_width: f32,
+    container_height: f32,
+    strict_sizing: bool,
+    width: Option<f32>,
+    height: Option<f32>,
+    resize_mode: ResizeMode,
+    min_size: Option<(u32, u32)>,
+    max_rows: Option<u64>,
+    row_limit_strategy: RowLimitStrategy,
+    annotate_truncation: bool,
+    log_level: Option<LogLevel>,
+    warning_filters: Option<Vec<String>>,
+    suppress_components: Option<Vec<Component>>,
+    browser_shims: bool,
+    simplify_topojson: Option<f64>,
+    lenient_dates: bool,
+    verbose: bool,
+) -> Result<ChartWarnings, anyhow::Error> {
+    // Parse version
+    let vl_version = parse_vl_version(vl_version)?;
+
     // Read input file
-    let vega_str = read_input_string(input)?;
+    let vegalite_str = read_input_string(input)?;
 
     // Parse input as json
-    let vg_spec = parse_as_json(&vega_str)?;
+    let vl_spec = parse_as_json(&vegalite_str)?;
+
+    // Load config from file
+    let config = read_config_json(config)?;
 
     let format_locale = match &format_locale {
         None => None,
@@ -1247,45 +4989,190 @@ async fn vg_2_pdf(
         Some(p) => Some(time_format_locale_from_str(p)?),
     };
 
+    // With several themes, the chart is rendered once per theme
+    let (theme, themes) = match theme.len() {
+        0 | 1 => (theme.into_iter().next(), None),
+        _ => (None, Some(theme)),
+    };
+    if themes.is_some() && dump_final_spec.is_some() {
+        bail!("--dump-final-spec can't be used with more than one --theme");
+    }
+
     // Initialize converter
     let mut converter = VlConverter::new();
 
-    // Perform conversion
-    let pdf_data = match converter
-        .vega_to_pdf(
-            vg_spec,
-            VgOpts {
-                allowed_base_urls,
-                format_locale,
-                time_format_locale,
-            },
+    let vl_opts = VlOpts {
+        vl_version,
+        config,
+        theme,
+        show_warnings,
+        allowed_base_urls,
+        format_locale,
+        time_format_locale,
+        collect_resource_stats,
+        collect_dataset_summary: true,
+        fetch_retries,
+        fetch_retry_backoff_ms,
+        interaction_script: interactions,
+        preserve_int64: false,
+        base_url: data_base_url(input, base_url, allow_local_data)?,
+        allow_local_data,
+        lenient,
+        font,
+        font_size_scale,
+        text_color,
+        config_overrides,
+        direction,
+        generic_font_mapping,
+        container_width: Some(container_width),
+        container_height: Some(container_height),
+        strict_sizing,
+        width,
+        height,
+        resize_mode,
+        min_size,
+        max_rows,
+        row_limit_strategy,
+        annotate_truncation,
+        log_level,
+        warning_filters,
+        suppress_components,
+        browser_shims,
+        simplify_topojson,
+        lenient_dates,
+        pre_aggregate: false,
+        svg_post_processor: None,
+    };
+
+    if let Some(dump_final_spec) = &dump_final_spec {
+        let final_spec = converter
+            .get_final_vega_spec(vl_spec.clone(), vl_opts.clone())
+            .await?;
+        write_output_string(dump_final_spec, &serde_json::to_string_pretty(&final_spec)?)?;
+    }
+
+    if let Some(export_data) = &export_data {
+        let format = DataExportFormat::from_str(export_data_format)?;
+        export_chart_data(
+            &mut converter,
+            vl_spec.clone(),
+            vl_opts.clone(),
+            output,
+            export_data,
+            format,
         )
+        .await?;
+    }
+
+    if explain_static {
+        print_static_export_findings(&mut converter, vl_spec.clone(), vl_opts.clone()).await?;
+    }
+
+    // Perform conversion
+    let png_opts = PngOpts {
+        interlaced: png_interlaced,
+        antialias: !no_antialias,
+        ppi_mode: PpiMode::from_str(ppi_mode)?,
+        image_rendering: ImageRendering::from_str(image_rendering)?,
+        flatten_background: flatten_background
+            .as_deref()
+            .map(BackgroundColor::from_str)
+            .transpose()?,
+        ..Default::default()
+    };
+    if let Some(themes) = themes {
+        let themes: Vec<&str> = themes.iter().map(String::as_str).collect();
+        let pngs = match converter
+            .vegalite_to_png_themed_with_opts(
+                vl_spec,
+                vl_opts,
+                &themes,
+                Some(scale),
+                Some(ppi),
+                png_opts,
+            )
+            .await
+        {
+            Ok(pngs) => pngs,
+            Err(err) => {
+                bail!("Vega-Lite to PNG conversion failed: {}", err);
+            }
+        };
+        for theme in themes {
+            write_output_binary(&themed_output_path(output, theme), &pngs[theme])?;
+        }
+        return Ok(ChartWarnings::default());
+    }
+    let png = match converter
+        .vegalite_to_png_info_with_opts(vl_spec, vl_opts, Some(scale), Some(ppi), png_opts)
         .await
     {
-        Ok(pdf_data) => pdf_data,
+        Ok(png) => png,
         Err(err) => {
-            bail!("Vega to PDF conversion failed: {}", err);
+            bail!("Vega-Lite to PNG conversion failed: {}", err);
         }
     };
 
     // Write result
-    write_output_binary(output, &pdf_data)?;
+    write_output_binary(output, &png.data)?;
+    if verbose {
+        print_png_size(&png);
+    }
 
-    Ok(())
+    Ok(ChartWarnings {
+        render_warnings: png.warnings,
+        dataset_summary: converter.get_last_dataset_summary(),
+    })
 }
 
 #[allow(clippy::too_many_arguments)]
-async fn vl_2_svg(
+async fn vl_2_facets(
     input: &str,
     output: &str,
     vl_version: &str,
     theme: Option<String>,
     config: Option<String>,
+    scale: f32,
+    ppi: f32,
     show_warnings: bool,
     allowed_base_urls: Option<Vec<String>>,
     format_locale: Option<String>,
     time_format_locale: Option<String>,
+    collect_resource_stats: bool,
+    fetch_retries: Option<u32>,
+    fetch_retry_backoff_ms: Option<u64>,
+    base_url: Option<String>,
+    allow_local_data: bool,
+    lenient: bool,
+    interactions: Option<Vec<InteractionStep>>,
+    font: Option<String>,
+    font_size_scale: Option<f32>,
+    text_color: Option<String>,
+    config_overrides: Option<Vec<(String, serde_json::Value)>>,
+    direction: Option<Direction>,
+    generic_font_mapping: Option<HashMap<String, String>>,
+    container_width: f32,
+    container_height: f32,
+    strict_sizing: bool,
+    width: Option<f32>,
+    height: Option<f32>,
+    resize_mode: ResizeMode,
+    min_size: Option<(u32, u32)>,
+    max_rows: Option<u64>,
+    row_limit_strategy: RowLimitStrategy,
+    annotate_truncation: bool,
+    log_level: Option<LogLevel>,
+    warning_filters: Option<Vec<String>>,
+    suppress_components: Option<Vec<Component>>,
+    browser_shims: bool,
+    simplify_topojson: Option<f64>,
+    lenient_dates: bool,
+    include_guides: bool,
 ) -> Result<(), anyhow::Error> {
+    if !output.contains("{row}") && !output.contains("{column}") {
+        bail!("Output path must contain {{row}} or {{column}}, to name the image of each cell");
+    }
+
     // Parse version
     let vl_version = parse_vl_version(vl_version)?;
 
@@ -1311,48 +5198,156 @@ async fn vl_2_svg(
     // Initialize converter
     let mut converter = VlConverter::new();
 
+    let vl_opts = VlOpts {
+        vl_version,
+        config,
+        theme,
+        show_warnings,
+        allowed_base_urls,
+        format_locale,
+        time_format_locale,
+        collect_resource_stats,
+        collect_dataset_summary: false,
+        fetch_retries,
+        fetch_retry_backoff_ms,
+        interaction_script: interactions,
+        preserve_int64: false,
+        base_url: data_base_url(input, base_url, allow_local_data)?,
+        allow_local_data,
+        lenient,
+        font,
+        font_size_scale,
+        text_color,
+        config_overrides,
+        direction,
+        generic_font_mapping,
+        container_width: Some(container_width),
+        container_height: Some(container_height),
+        strict_sizing,
+        width,
+        height,
+        resize_mode,
+        min_size,
+        max_rows,
+        row_limit_strategy,
+        annotate_truncation,
+        log_level,
+        warning_filters,
+        suppress_components,
+        browser_shims,
+        simplify_topojson,
+        lenient_dates,
+        pre_aggregate: false,
+        svg_post_processor: None,
+    };
+
     // Perform conversion
-    let svg = match converter
-        .vegalite_to_svg(
+    let images = match converter
+        .vegalite_facets_to_pngs_with_opts(
             vl_spec,
-            VlOpts {
-                vl_version,
-                config,
-                theme,
-                show_warnings,
-                allowed_base_urls,
-                format_locale,
-                time_format_locale,
-            },
+            vl_opts,
+            Some(scale),
+            Some(ppi),
+            FacetOpts { include_guides },
         )
         .await
     {
-        Ok(svg) => svg,
+        Ok(images) => images,
         Err(err) => {
-            bail!("Vega-Lite to SVG conversion failed: {}", err);
+            bail!("Vega-Lite facets to PNG conversion failed: {}", err);
         }
     };
 
-    // Write result
-    write_output_string(output, &svg)?;
+    // Make sure that no two cells are written to the same file before writing any of them
+    let paths: Vec<_> = images
+        .iter()
+        .map(|image| facet_output_path(output, &image.row_value, &image.column_value))
+        .collect();
+    let mut seen = HashSet::new();
+    for path in &paths {
+        if !seen.insert(path) {
+            bail!(
+                "Several facet cells would be written to {path}. Add {{row}} or {{column}} to \
+                the output path to tell them apart"
+            );
+        }
+    }
+
+    // Write results
+    for (path, image) in paths.iter().zip(&images) {
+        write_output_binary(path, &image.data)?;
+    }
 
     Ok(())
 }
 
+/// Path that the image of a facet cell is written to, with {row} and {column} in `output`
+/// replaced by the cell's facet values. String values are used as they are, other values as
+/// JSON, and missing values as an empty string. Path separators in the values are replaced
+/// by underscores, so that each image is written to the directory of `output`
+fn facet_output_path(
+    output: &str,
+    row_value: &Option<serde_json::Value>,
+    column_value: &Option<serde_json::Value>,
+) -> String {
+    let name = |value: &Option<serde_json::Value>| {
+        let name = match value {
+            None | Some(serde_json::Value::Null) => String::new(),
+            Some(serde_json::Value::String(s)) => s.clone(),
+            Some(value) => value.to_string(),
+        };
+        name.replace(['/', '\\'], "_")
+    };
+    output
+        .replace("{row}", &name(row_value))
+        .replace("{column}", &name(column_value))
+}
+
 #[allow(clippy::too_many_arguments)]
-async fn vl_2_png(
+async fn vl_2_vg_png(
     input: &str,
-    output: &str,
+    output_vega: &str,
+    output_png: &str,
     vl_version: &str,
     theme: Option<String>,
     config: Option<String>,
+    pretty: bool,
     scale: f32,
     ppi: f32,
     show_warnings: bool,
     allowed_base_urls: Option<Vec<String>>,
     format_locale: Option<String>,
     time_format_locale: Option<String>,
-) -> Result<(), anyhow::Error> {
+    collect_resource_stats: bool,
+    fetch_retries: Option<u32>,
+    fetch_retry_backoff_ms: Option<u64>,
+    base_url: Option<String>,
+    allow_local_data: bool,
+    lenient: bool,
+    interactions: Option<Vec<InteractionStep>>,
+    font: Option<String>,
+    font_size_scale: Option<f32>,
+    text_color: Option<String>,
+    config_overrides: Option<Vec<(String, serde_json::Value)>>,
+    direction: Option<Direction>,
+    generic_font_mapping: Option<HashMap<String, String>>,
+    container_width: f32,
+    container_height: f32,
+    strict_sizing: bool,
+    width: Option<f32>,
+    height: Option<f32>,
+    resize_mode: ResizeMode,
+    min_size: Option<(u32, u32)>,
+    max_rows: Option<u64>,
+    row_limit_strategy: RowLimitStrategy,
+    annotate_truncation: bool,
+    log_level: Option<LogLevel>,
+    warning_filters: Option<Vec<String>>,
+    suppress_components: Option<Vec<Component>>,
+    browser_shims: bool,
+    simplify_topojson: Option<f64>,
+    lenient_dates: bool,
+) -> Result<ChartWarnings, anyhow::Error> {
     // Parse version
     let vl_version = parse_vl_version(vl_version)?;
 
@@ -1379,8 +5374,8 @@ async fn vl_2_png(
     let mut converter = VlConverter::new();
 
     // Perform conversion
-    let png_data = match converter
-        .vegalite_to_png(
+    let (vega_json, png_data) = match converter
+        .vegalite_to_vega_and_png(
             vl_spec,
             VlOpts {
                 vl_version,
@@ -1390,22 +5385,65 @@ async fn vl_2_png(
                 allowed_base_urls,
                 format_locale,
                 time_format_locale,
+                collect_resource_stats,
+                collect_dataset_summary: true,
+                fetch_retries,
+                fetch_retry_backoff_ms,
+                interaction_script: interactions,
+                preserve_int64: false,
+                base_url: data_base_url(input, base_url, allow_local_data)?,
+                allow_local_data,
+                lenient,
+                font,
+                font_size_scale,
+                text_color,
+                config_overrides,
+                direction,
+                generic_font_mapping,
+                container_width: Some(container_width),
+                container_height: Some(container_height),
+                strict_sizing,
+                width,
+                height,
+                resize_mode,
+                min_size,
+                max_rows,
+                row_limit_strategy,
+                annotate_truncation,
+                log_level,
+                warning_filters,
+                suppress_components,
+                browser_shims,
+                simplify_topojson,
+                lenient_dates,
+                pre_aggregate: false,
+                svg_post_processor: None,
             },
             Some(scale),
             Some(ppi),
         )
         .await
     {
-        Ok(png_data) => png_data,
+        Ok(result) => result,
         Err(err) => {
-            bail!("Vega-Lite to PNG conversion failed: {}", err);
+            bail!("Vega-Lite to Vega and PNG conversion failed: {}", err);
         }
     };
 
-    // Write result
-    write_output_binary(output, &png_data)?;
+    let vega_str = if pretty {
+        serde_json::to_string_pretty(&vega_json)?
+    } else {
+        serde_json::to_string(&vega_json)?
+    };
 
-    Ok(())
+    // Write results
+    write_output_string(output_vega, &vega_str)?;
+    write_output_binary(output_png, &png_data)?;
+
+    Ok(ChartWarnings {
+        render_warnings: Vec::new(),
+        dataset_summary: converter.get_last_dataset_summary(),
+    })
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -1417,11 +5455,45 @@ async fn vl_2_jpeg(
     config: Option<String>,
     scale: f32,
     quality: u8,
+    jpeg_background: &str,
     show_warnings: bool,
     allowed_base_urls: Option<Vec<String>>,
     format_locale: Option<String>,
     time_format_locale: Option<String>,
-) -> Result<(), anyhow::Error> {
+    dump_final_spec: Option<String>,
+    export_data: Option<String>,
+    export_data_format: &str,
+    collect_resource_stats: bool,
+    fetch_retries: Option<u32>,
+    fetch_retry_backoff_ms: Option<u64>,
+    base_url: Option<String>,
+    allow_local_data: bool,
+    lenient: bool,
+    interactions: Option<Vec<InteractionStep>>,
+    font: Option<String>,
+    font_size_scale: Option<f32>,
+    text_color: Option<String>,
+    config_overrides: Option<Vec<(String, serde_json::Value)>>,
+    direction: Option<Direction>,
+    generic_font_mapping: Option<HashMap<String, String>>,
+    container_width: f32,
+    container_height: f32,
+    strict_sizing: bool,
+    width: Option<f32>,
+    height: Option<f32>,
+    resize_mode: ResizeMode,
+    min_size: Option<(u32, u32)>,
+    max_rows: Option<u64>,
+    row_limit_strategy: RowLimitStrategy,
+    annotate_truncation: bool,
+    log_level: Option<LogLevel>,
+    warning_filters: Option<Vec<String>>,
+    suppress_components: Option<Vec<Component>>,
+    browser_shims: bool,
+    simplify_topojson: Option<f64>,
+    lenient_dates: bool,
+    verbose: bool,
+) -> Result<ChartWarnings, anyhow::Error> {
     // Parse version
     let vl_version = parse_vl_version(vl_version)?;
 
@@ -1447,34 +5519,98 @@ async fn vl_2_jpeg(
     // Initialize converter
     let mut converter = VlConverter::new();
 
+    let vl_opts = VlOpts {
+        vl_version,
+        config,
+        theme,
+        show_warnings,
+        allowed_base_urls,
+        format_locale,
+        time_format_locale,
+        collect_resource_stats,
+        collect_dataset_summary: true,
+        fetch_retries,
+        fetch_retry_backoff_ms,
+        interaction_script: interactions,
+        preserve_int64: false,
+        base_url: data_base_url(input, base_url, allow_local_data)?,
+        allow_local_data,
+        lenient,
+        font,
+        font_size_scale,
+        text_color,
+        config_overrides,
+        direction,
+        generic_font_mapping,
+        container_width: Some(container_width),
+        container_height: Some(container_height),
+        strict_sizing,
+        width,
+        height,
+        resize_mode,
+        min_size,
+        max_rows,
+        row_limit_strategy,
+        annotate_truncation,
+        log_level,
+        warning_filters,
+        suppress_components,
+        browser_shims,
+        simplify_topojson,
+        lenient_dates,
+        pre_aggregate: false,
+        svg_post_processor: None,
+    };
+
+    if let Some(dump_final_spec) = &dump_final_spec {
+        let final_spec = converter
+            .get_final_vega_spec(vl_spec.clone(), vl_opts.clone())
+            .await?;
+        write_output_string(dump_final_spec, &serde_json::to_string_pretty(&final_spec)?)?;
+    }
+
+    if let Some(export_data) = &export_data {
+        let format = DataExportFormat::from_str(export_data_format)?;
+        export_chart_data(
+            &mut converter,
+            vl_spec.clone(),
+            vl_opts.clone(),
+            output,
+            export_data,
+            format,
+        )
+        .await?;
+    }
+
     // Perform conversion
-    let jpeg_data = match converter
-        .vegalite_to_jpeg(
+    let jpeg = match converter
+        .vegalite_to_jpeg_info_with_opts(
             vl_spec,
-            VlOpts {
-                vl_version,
-                config,
-                theme,
-                show_warnings,
-                allowed_base_urls,
-                format_locale,
-                time_format_locale,
-            },
+            vl_opts,
             Some(scale),
             Some(quality),
+            JpegOpts {
+                background: BackgroundColor::from_str(jpeg_background)?,
+            },
         )
         .await
     {
-        Ok(jpeg_data) => jpeg_data,
+        Ok(jpeg) => jpeg,
         Err(err) => {
             bail!("Vega-Lite to JPEG conversion failed: {}", err);
         }
     };
 
     // Write result
-    write_output_binary(output, &jpeg_data)?;
+    write_output_binary(output, &jpeg.data)?;
+    if verbose {
+        print_jpeg_size(&jpeg);
+    }
 
-    Ok(())
+    Ok(ChartWarnings {
+        render_warnings: jpeg.warnings,
+        dataset_summary: converter.get_last_dataset_summary(),
+    })
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -1488,7 +5624,41 @@ async fn vl_2_pdf(
     allowed_base_urls: Option<Vec<String>>,
     format_locale: Option<String>,
     time_format_locale: Option<String>,
-) -> Result<(), anyhow::Error> {
+    dump_final_spec: Option<String>,
+    export_data: Option<String>,
+    export_data_format: &str,
+    collect_resource_stats: bool,
+    fetch_retries: Option<u32>,
+    fetch_retry_backoff_ms: Option<u64>,
+    base_url: Option<String>,
+    allow_local_data: bool,
+    lenient: bool,
+    interactions: Option<Vec<InteractionStep>>,
+    font: Option<String>,
+    font_size_scale: Option<f32>,
+    text_color: Option<String>,
+    config_overrides: Option<Vec<(String, serde_json::Value)>>,
+    direction: Option<Direction>,
+    generic_font_mapping: Option<HashMap<String, String>>,
+    container_width: f32,
+    container_height: f32,
+    strict_sizing: bool,
+    width: Option<f32>,
+    height: Option<f32>,
+    resize_mode: ResizeMode,
+    min_size: Option<(u32, u32)>,
+    max_rows: Option<u64>,
+    row_limit_strategy: RowLimitStrategy,
+    annotate_truncation: bool,
+    log_level: Option<LogLevel>,
+    warning_filters: Option<Vec<String>>,
+    suppress_components: Option<Vec<Component>>,
+    browser_shims: bool,
+    simplify_topojson: Option<f64>,
+    lenient_dates: bool,
+    pdfa: bool,
+    source_dpi: f32,
+) -> Result<ChartWarnings, anyhow::Error> {
     // Parse version
     let vl_version = parse_vl_version(vl_version)?;
 
@@ -1514,46 +5684,178 @@ async fn vl_2_pdf(
     // Initialize converter
     let mut converter = VlConverter::new();
 
-    // Perform conversion
-    let pdf_data = match converter
-        .vegalite_to_pdf(
-            vl_spec,
-            VlOpts {
-                vl_version,
-                config,
-                theme,
-                show_warnings,
-                allowed_base_urls,
-                format_locale,
-                time_format_locale,
-            },
+    let vl_opts = VlOpts {
+        vl_version,
+        config,
+        theme,
+        show_warnings,
+        allowed_base_urls,
+        format_locale,
+        time_format_locale,
+        collect_resource_stats,
+        collect_dataset_summary: true,
+        fetch_retries,
+        fetch_retry_backoff_ms,
+        interaction_script: interactions,
+        preserve_int64: false,
+        base_url: data_base_url(input, base_url, allow_local_data)?,
+        allow_local_data,
+        lenient,
+        font,
+        font_size_scale,
+        text_color,
+        config_overrides,
+        direction,
+        generic_font_mapping,
+        container_width: Some(container_width),
+        container_height: Some(container_height),
+        strict_sizing,
+        width,
+        height,
+        resize_mode,
+        min_size,
+        max_rows,
+        row_limit_strategy,
+        annotate_truncation,
+        log_level,
+        warning_filters,
+        suppress_components,
+        browser_shims,
+        simplify_topojson,
+        lenient_dates,
+        pre_aggregate: false,
+        svg_post_processor: None,
+    };
+
+    if let Some(dump_final_spec) = &dump_final_spec {
+        let final_spec = converter
+            .get_final_vega_spec(vl_spec.clone(), vl_opts.clone())
+            .await?;
+        write_output_string(dump_final_spec, &serde_json::to_string_pretty(&final_spec)?)?;
+    }
+
+    if let Some(export_data) = &export_data {
+        let format = DataExportFormat::from_str(export_data_format)?;
+        export_chart_data(
+            &mut converter,
+            vl_spec.clone(),
+            vl_opts.clone(),
+            output,
+            export_data,
+            format,
         )
+        .await?;
+    }
+
+    // Perform conversion
+    let pdf = match converter
+        .vegalite_to_pdf_info_with_opts(vl_spec, vl_opts, PdfOpts { pdfa, source_dpi })
         .await
     {
-        Ok(pdf_data) => pdf_data,
+        Ok(pdf) => pdf,
         Err(err) => {
             bail!("Vega-Lite to PDF conversion failed: {}", err);
         }
     };
 
     // Write result
-    write_output_binary(output, &pdf_data)?;
+    write_output_binary(output, &pdf.data)?;
 
-    Ok(())
+    Ok(ChartWarnings {
+        render_warnings: pdf.warnings,
+        dataset_summary: converter.get_last_dataset_summary(),
+    })
 }
 
 async fn list_themes() -> Result<(), anyhow::Error> {
     // Initialize converter
     let mut converter = VlConverter::new();
 
-    if let serde_json::Value::Object(themes) = converter.get_themes().await? {
-        for theme in themes.keys().sorted() {
-            println!("{}", theme)
-        }
+    for theme in converter.get_theme_names().await? {
+        println!("{}", theme)
+    }
+
+    Ok(())
+}
+
+async fn health() -> Result<(), anyhow::Error> {
+    // Initialize converter
+    let mut converter = VlConverter::new();
+
+    let report = converter.health_check().await?;
+    println!("{}", serde_json::to_string_pretty(&report)?);
+    Ok(())
+}
+
+fn anonymize(
+    input: &str,
+    output: &str,
+    opts: &AnonymizeOptions,
+    pretty: bool,
+) -> Result<(), anyhow::Error> {
+    let spec = parse_as_json(&read_input_string(input)?)?;
+    let anonymized = anonymize_spec(&spec, opts);
+    let anonymized_str = if pretty {
+        serde_json::to_string_pretty(&anonymized)?
     } else {
-        bail!("Failed to load themes")
+        serde_json::to_string(&anonymized)?
+    };
+    write_output_string(output, &anonymized_str)
+}
+
+fn lint(input: &str, deny_warnings: bool) -> Result<(), anyhow::Error> {
+    let vg_spec = parse_as_json(&read_input_string(input)?)?;
+    let issues = lint_vega(&vg_spec);
+    for issue in &issues {
+        let severity = match issue.severity {
+            LintSeverity::Error => "error",
+            LintSeverity::Warning => "warning",
+        };
+        println!(
+            "{}[{}] {}: {}",
+            severity, issue.code, issue.path, issue.message
+        );
+    }
+
+    let errors = issues
+        .iter()
+        .filter(|issue| issue.severity == LintSeverity::Error)
+        .count();
+    let warnings = issues.len() - errors;
+    if errors > 0 || (deny_warnings && warnings > 0) {
+        bail!("Found {} error(s) and {} warning(s)", errors, warnings)
+    }
+    Ok(())
+}
+
+fn svg_diff(a: &str, b: &str, tolerance: f64, compare_ids: bool) -> Result<(), anyhow::Error> {
+    let svg_a = read_input_string(a)?;
+    let svg_b = read_input_string(b)?;
+    let opts = SvgCompareOptions {
+        tolerance,
+        ignore_ids: !compare_ids,
+    };
+    let diff = compare_svgs(&svg_a, &svg_b, &opts)?;
+    for difference in &diff.differences {
+        let kind = match difference.kind {
+            SvgDifferenceKind::Tag => "tag",
+            SvgDifferenceKind::Attribute => "attribute",
+            SvgDifferenceKind::Text => "text",
+            SvgDifferenceKind::Added => "added",
+            SvgDifferenceKind::Removed => "removed",
+        };
+        println!(
+            "{} {}: {} -> {}",
+            kind,
+            difference.path,
+            difference.a.as_deref().unwrap_or("(none)"),
+            difference.b.as_deref().unwrap_or("(none)"),
+        );
     }
 
+    if !diff.equal {
+        bail!("Found {} difference(s)", diff.differences.len())
+    }
     Ok(())
 }
 
@@ -1566,7 +5868,11 @@ async fn cat_theme(theme: &str) -> Result<(), anyhow::Error> {
             let theme_config_str = serde_json::to_string_pretty(theme_config).unwrap();
             println!("{}", theme_config_str);
         } else {
-            bail!("No theme named '{}'", theme)
+            let names: Vec<String> = themes.keys().cloned().collect();
+            bail!(
+                "{} Run `vl-convert ls-themes` to list available themes.",
+                unknown_name_message("theme", theme, &names)
+            )
         }
     } else {
         bail!("Failed to load themes")
@@ -412,6 +412,58 @@ mod test_vl2png {
     }
 }
 
+#[rustfmt::skip]
+mod test_vl2vgpng {
+    use std::fs;
+    use std::process::Command;
+    use crate::*;
+
+    #[rstest]
+    fn test(
+        #[values("circle_binned")]
+        name: &str,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        initialize();
+
+        let vl_version = "5_8";
+        let output_vega = output_path(&format!("{}_{}.vgpng.vg.json", vl_version, name));
+        let output_png = output_path(&format!("{}_{}.vgpng.png", vl_version, name));
+
+        let vl_path = vl_spec_path(name);
+
+        let mut cmd = Command::cargo_bin("vl-convert")?;
+        let cmd = cmd.arg("vl2vgpng")
+            .arg("-i").arg(vl_path)
+            .arg("--output-vega").arg(&output_vega)
+            .arg("--output-png").arg(&output_png)
+            .arg("--vl-version").arg(vl_version)
+            .arg("--font-dir").arg(test_font_dir());
+
+        cmd.assert().success();
+
+        // Check written Vega spec
+        let expected_str = load_expected_vg_spec(name, vl_version, false).unwrap();
+        let output_str = fs::read_to_string(&output_vega).unwrap();
+        assert_eq!(expected_str, output_str);
+
+        // Check written image
+        let expected_png = load_expected_png(name, vl_version, None).unwrap();
+        let output_png = dssim::load_image(&Dssim::new(), &output_png).unwrap();
+
+        let attr = Dssim::new();
+        let (diff, _) = attr.compare(&expected_png, output_png);
+
+        if diff > 0.0001 {
+            panic!(
+                "Images don't match for {}.png with diff {}",
+                name, diff
+            )
+        }
+
+        Ok(())
+    }
+}
+
 #[rustfmt::skip]
 mod test_vl2png_theme_config {
     use std::process::Command;
@@ -620,3 +672,1936 @@ fn test_cat_theme() -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
+
+#[test]
+fn test_misspelled_names() -> Result<(), Box<dyn std::error::Error>> {
+    initialize();
+
+    // Names are checked before the input file is read
+    let output = output_path("misspelled_names.svg");
+    let mut cmd = Command::cargo_bin("vl-convert")?;
+    cmd.arg("vl2svg")
+        .arg("-i")
+        .arg("does_not_exist.vl.json")
+        .arg("-o")
+        .arg(&output)
+        .arg("--theme")
+        .arg("drk")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Unknown theme 'drk'. Did you mean 'dark'? \
+            Run `vl-convert ls-themes` to list available themes.",
+        ));
+
+    let mut cmd = Command::cargo_bin("vl-convert")?;
+    cmd.arg("vl2svg")
+        .arg("-i")
+        .arg("does_not_exist.vl.json")
+        .arg("-o")
+        .arg(&output)
+        .arg("--format-locale")
+        .arg("it_IT")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Unknown format locale 'it_IT'. Did you mean 'it-IT'?",
+        ));
+
+    let mut cmd = Command::cargo_bin("vl-convert")?;
+    cmd.arg("cat-theme")
+        .arg("exel")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Did you mean 'excel'?"));
+
+    // Valid names pass through
+    let mut cmd = Command::cargo_bin("vl-convert")?;
+    cmd.arg("vl2svg")
+        .arg("-i")
+        .arg(vl_spec_path("circle_binned"))
+        .arg("-o")
+        .arg(&output)
+        .arg("--theme")
+        .arg("dark")
+        .arg("--format-locale")
+        .arg("it-IT")
+        .arg("--time-format-locale")
+        .arg("it-IT")
+        .assert()
+        .success();
+
+    Ok(())
+}
+
+#[test]
+fn test_bundle() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::tempdir()?;
+    let output = dir.path().join("vega-bundle.js");
+    let mut cmd = Command::cargo_bin("vl-convert")?;
+    cmd.arg("bundle")
+        .arg("--vl-version")
+        .arg("5.20")
+        .arg("-o")
+        .arg(&output)
+        .assert()
+        .success();
+
+    let bundle = fs::read_to_string(&output)?;
+    assert!(bundle.contains("5.20.1"));
+    assert!(bundle.contains("window.lodashDebounce"));
+
+    Ok(())
+}
+
+#[test]
+fn test_health() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("vl-convert")?;
+    let output = cmd.arg("health").output()?;
+    assert!(output.status.success());
+
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+    assert_eq!(report["vega_version"], "5.30.0");
+    assert_eq!(report["conversions_completed"], 0);
+    assert!(report["last_conversion_at"].is_null());
+
+    Ok(())
+}
+
+#[test]
+fn test_introspect() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("vl-convert")?;
+    let output = cmd.arg("introspect").arg("--format").arg("json").output()?;
+    assert!(output.status.success());
+
+    let schema: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+    let vl2png = schema["subcommands"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|subcommand| subcommand["name"] == "vl2png")
+        .unwrap();
+    let ppi = vl2png["options"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|option| option["name"] == "ppi")
+        .unwrap();
+    assert_eq!(ppi["long"], "--ppi");
+    assert_eq!(ppi["type"], "number");
+    assert_eq!(ppi["default"].as_f64(), Some(72.0));
+
+    // Subcommands and options are annotated with the version that added them
+    assert_eq!(vl2png["since"], "1.7.0");
+    assert_eq!(ppi["since"], "1.7.0");
+    let flatten_background = vl2png["options"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|option| option["name"] == "flatten_background")
+        .unwrap();
+    assert_eq!(flatten_background["since"], "1.8.0");
+    assert!(schema["global_options"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .all(|option| option["since"] == "1.8.0"));
+
+    // The schema embedded in vl-convert-rs is kept up to date with the CLI
+    assert_eq!(
+        schema,
+        vl_convert_rs::cli_schema::get_cli_schema(),
+        "Regenerate vl-convert-rs/src/cli_schema.json with vl-convert introspect"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_max_spec_bytes() -> Result<(), Box<dyn std::error::Error>> {
+    initialize();
+
+    let output = output_path("max_spec_bytes.vg.json");
+    let mut cmd = Command::cargo_bin("vl-convert")?;
+    let cmd = cmd
+        .arg("vl2vg")
+        .arg("-i")
+        .arg(vl_spec_path("circle_binned"))
+        .arg("-o")
+        .arg(&output)
+        .arg("--max-spec-bytes")
+        .arg("64");
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("max_spec_bytes limit of 64 bytes"));
+
+    Ok(())
+}
+
+#[test]
+fn test_zero_size_chart() -> Result<(), Box<dyn std::error::Error>> {
+    initialize();
+
+    let input = output_path("zero_width.vg.json");
+    fs::write(
+        &input,
+        r#"{"width": 0, "height": 100, "padding": 0, "autosize": "none", "marks": []}"#,
+    )?;
+    let output = output_path("zero_width.png");
+    let mut cmd = Command::cargo_bin("vl-convert")?;
+    cmd.arg("vg2png")
+        .arg("-i")
+        .arg(&input)
+        .arg("-o")
+        .arg(&output)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Chart resolved to 0×100; check data and width/height settings",
+        ));
+
+    let mut cmd = Command::cargo_bin("vl-convert")?;
+    cmd.arg("vg2png")
+        .arg("-i")
+        .arg(&input)
+        .arg("-o")
+        .arg(&output)
+        .arg("--min-size")
+        .arg("40x30")
+        .assert()
+        .success();
+    // The width and height are the first fields of the IHDR chunk
+    let png = fs::read(&output)?;
+    let width = u32::from_be_bytes(png[16..20].try_into()?);
+    let height = u32::from_be_bytes(png[20..24].try_into()?);
+    assert_eq!((width, height), (40, 100));
+
+    Ok(())
+}
+
+#[test]
+fn test_max_rows() -> Result<(), Box<dyn std::error::Error>> {
+    initialize();
+
+    let rows: Vec<_> = (0..100).map(|i| format!(r#"{{"i": {i}}}"#)).collect();
+    let input = output_path("max_rows.vl.json");
+    fs::write(
+        &input,
+        format!(
+            r#"{{"data": {{"values": [{}]}}, "mark": "point", "encoding": {{"x": {{"field": "i", "type": "quantitative"}}}}}}"#,
+            rows.join(", ")
+        ),
+    )?;
+    let output = output_path("max_rows.svg");
+    let mut cmd = Command::cargo_bin("vl-convert")?;
+    cmd.arg("vl2svg")
+        .arg("-i")
+        .arg(&input)
+        .arg("-o")
+        .arg(&output)
+        .arg("--max-rows")
+        .arg("10")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "A dataset has 100 rows, more than max_rows (10)",
+        ));
+
+    let mut cmd = Command::cargo_bin("vl-convert")?;
+    cmd.arg("vl2svg")
+        .arg("-i")
+        .arg(&input)
+        .arg("-o")
+        .arg(&output)
+        .arg("--max-rows")
+        .arg("10")
+        .arg("--row-limit-strategy")
+        .arg("head")
+        .arg("--annotate-truncation")
+        .assert()
+        .success();
+    let svg = fs::read_to_string(&output)?;
+    assert!(svg.contains("Data truncated to 10 rows"));
+
+    Ok(())
+}
+
+#[test]
+fn test_vega_log_level_and_suppress_warning() -> Result<(), Box<dyn std::error::Error>> {
+    initialize();
+
+    // Vega warns that the extents of the empty dataset are infinite
+    let input = output_path("infinite_extent.vg.json");
+    fs::write(
+        &input,
+        r#"{
+            "width": 100,
+            "height": 100,
+            "data": [{
+                "name": "table",
+                "values": [],
+                "transform": [
+                    {"type": "extent", "field": "v", "signal": "v_extent"},
+                    {"type": "extent", "field": "w", "signal": "w_extent"}
+                ]
+            }],
+            "marks": [{"type": "rect", "encode": {"enter": {"width": {"value": 100}}}}]
+        }"#,
+    )?;
+    let output = output_path("infinite_extent.svg");
+    let mut cmd = Command::cargo_bin("vl-convert")?;
+    cmd.arg("vg2svg")
+        .arg("-i")
+        .arg(&input)
+        .arg("-o")
+        .arg(&output)
+        .arg("--vega-log-level")
+        .arg("warn")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "vl-convert: warning: Infinite extent for field \"w\"",
+        ));
+
+    let mut cmd = Command::cargo_bin("vl-convert")?;
+    cmd.arg("vg2svg")
+        .arg("-i")
+        .arg(&input)
+        .arg("-o")
+        .arg(&output)
+        .arg("--vega-log-level")
+        .arg("warn")
+        .arg("--suppress-warning")
+        .arg("Infinite extent")
+        .assert()
+        .success()
+        .stderr(
+            predicate::str::contains("Infinite extent for field")
+                .not()
+                .and(predicate::str::contains(
+                    "vl-convert: warning: Suppressed 2 warnings matching \"Infinite extent\"",
+                )),
+        );
+
+    let mut cmd = Command::cargo_bin("vl-convert")?;
+    cmd.arg("vg2svg")
+        .arg("-i")
+        .arg(&input)
+        .arg("-o")
+        .arg(&output)
+        .arg("--vega-log-level")
+        .arg("verbose")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Unsupported log level: verbose"));
+
+    Ok(())
+}
+
+#[test]
+fn test_suppress_components() -> Result<(), Box<dyn std::error::Error>> {
+    initialize();
+
+    let input = output_path("suppress_components.vl.json");
+    fs::write(
+        &input,
+        r#"{
+            "title": "Fruit",
+            "data": {"values": [{"a": "apple", "b": 3}, {"a": "pear", "b": 5}]},
+            "mark": "bar",
+            "encoding": {
+                "x": {"field": "a", "type": "nominal"},
+                "y": {"field": "b", "type": "quantitative"},
+                "color": {"field": "a", "type": "nominal"}
+            }
+        }"#,
+    )?;
+    let output = output_path("suppress_components.svg");
+    let mut cmd = Command::cargo_bin("vl-convert")?;
+    cmd.arg("vl2svg")
+        .arg("-i")
+        .arg(&input)
+        .arg("-o")
+        .arg(&output)
+        .arg("--suppress")
+        .arg("legend")
+        .arg("--suppress")
+        .arg("title")
+        .assert()
+        .success();
+
+    let svg = fs::read_to_string(&output)?;
+    assert!(!svg.contains("role-legend"));
+    assert!(!svg.contains("role-title"));
+    assert!(svg.contains("role-axis"));
+
+    let mut cmd = Command::cargo_bin("vl-convert")?;
+    cmd.arg("vl2svg")
+        .arg("-i")
+        .arg(&input)
+        .arg("-o")
+        .arg(&output)
+        .arg("--suppress")
+        .arg("axes")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Unsupported component: axes"));
+
+    Ok(())
+}
+
+#[test]
+fn test_lenient_dates() -> Result<(), Box<dyn std::error::Error>> {
+    initialize();
+
+    let input = output_path("lenient_dates.vl.json");
+    fs::write(
+        &input,
+        r#"{
+            "data": {"values": [{"date": "03/27/2024 5:00 PM", "v": 1}, {"date": "03/28/2024 9:15 AM", "v": 2}]},
+            "mark": "line",
+            "encoding": {
+                "x": {"field": "date", "type": "temporal"},
+                "y": {"field": "v", "type": "quantitative"}
+            }
+        }"#,
+    )?;
+    let output = output_path("lenient_dates.svg");
+    let mut cmd = Command::cargo_bin("vl-convert")?;
+    cmd.arg("vl2svg")
+        .arg("-i")
+        .arg(&input)
+        .arg("-o")
+        .arg(&output)
+        .arg("--lenient-dates")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "vl-convert: warning: Rewrote the non-ISO 8601 dates of fields \"date\" to ISO 8601",
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn test_browser_shims() -> Result<(), Box<dyn std::error::Error>> {
+    initialize();
+
+    let input = output_path("browser_shims.vl.json");
+    fs::write(
+        &input,
+        r#"{
+            "data": {"values": [{}]},
+            "transform": [{"calculate": "navigator.language", "as": "language"}],
+            "mark": "text",
+            "encoding": {"text": {"field": "language", "type": "nominal"}}
+        }"#,
+    )?;
+    let output = output_path("browser_shims.svg");
+    let mut cmd = Command::cargo_bin("vl-convert")?;
+    cmd.arg("vl2svg")
+        .arg("-i")
+        .arg(&input)
+        .arg("-o")
+        .arg(&output)
+        .assert()
+        .success();
+    assert!(fs::read_to_string(&output)?.contains("en-US"));
+
+    let mut cmd = Command::cargo_bin("vl-convert")?;
+    cmd.arg("vl2svg")
+        .arg("-i")
+        .arg(&input)
+        .arg("-o")
+        .arg(&output)
+        .arg("--no-browser-shims")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "An expression reads the browser API \"navigator\"",
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn test_simplify_topojson() -> Result<(), Box<dyn std::error::Error>> {
+    initialize();
+
+    let output = output_path("topojson_regions.svg");
+    let mut cmd = Command::cargo_bin("vl-convert")?;
+    cmd.arg("vl2svg")
+        .arg("-i")
+        .arg(vl_spec_path("topojson_regions"))
+        .arg("-o")
+        .arg(&output)
+        .arg("--simplify-topojson")
+        .arg("1")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "Simplified the arcs of a TopoJSON dataset from 126 to 18 vertices (85.7% fewer)",
+        ));
+    let svg = fs::read_to_string(&output)?;
+    assert_eq!(svg.matches(r#"aria-roledescription="geoshape""#).count(), 3);
+
+    Ok(())
+}
+
+#[test]
+fn test_config_overrides() -> Result<(), Box<dyn std::error::Error>> {
+    initialize();
+
+    let output = output_path("config_overrides.vg.json");
+    let mut cmd = Command::cargo_bin("vl-convert")?;
+    cmd.arg("vl2vg")
+        .arg("-i")
+        .arg(vl_spec_path("circle_binned"))
+        .arg("-o")
+        .arg(&output)
+        .arg("--theme")
+        .arg("dark")
+        .arg("--set")
+        .arg("axis.labelFontSize=14")
+        .arg("--set")
+        .arg(r##"range.category=["#111","#222"]"##)
+        .arg("--set")
+        .arg("background=white")
+        .arg("--set")
+        .arg("background=#123456")
+        .assert()
+        .success();
+
+    let vg_spec: serde_json::Value = serde_json::from_str(&fs::read_to_string(&output)?)?;
+    assert_eq!(vg_spec["config"]["axis"]["labelFontSize"], 14);
+    assert_eq!(
+        vg_spec["config"]["range"]["category"],
+        serde_json::json!(["#111", "#222"])
+    );
+    // Later overrides replace earlier ones, and the rest of the theme still applies
+    assert_eq!(vg_spec["background"], "#123456");
+    assert_eq!(vg_spec["config"]["axis"]["domainColor"], "#fff");
+
+    let mut cmd = Command::cargo_bin("vl-convert")?;
+    cmd.arg("vl2vg")
+        .arg("-i")
+        .arg(vl_spec_path("circle_binned"))
+        .arg("-o")
+        .arg(&output)
+        .arg("--set")
+        .arg("axis.labelFontSize")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("expected path=value"));
+    Ok(())
+}
+
+#[test]
+fn test_map_font() -> Result<(), Box<dyn std::error::Error>> {
+    initialize();
+
+    let output = output_path("map_font.svg");
+    let mut cmd = Command::cargo_bin("vl-convert")?;
+    cmd.arg("vl2svg")
+        .arg("-i")
+        .arg(vl_spec_path("circle_binned"))
+        .arg("-o")
+        .arg(&output)
+        .arg("--font-dir")
+        .arg(test_font_dir())
+        .arg("--map-font")
+        .arg("sans-serif=Caveat")
+        .assert()
+        .success();
+
+    let svg = fs::read_to_string(&output)?;
+    assert!(svg.contains(r#"font-family="Caveat""#));
+    assert!(!svg.contains(r#"font-family="sans-serif""#));
+
+    let mut cmd = Command::cargo_bin("vl-convert")?;
+    cmd.arg("vl2svg")
+        .arg("-i")
+        .arg(vl_spec_path("circle_binned"))
+        .arg("-o")
+        .arg(&output)
+        .arg("--map-font")
+        .arg("fantasy=Caveat")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Unsupported generic font family"));
+    Ok(())
+}
+
+#[test]
+fn test_dataflow_error_location() -> Result<(), Box<dyn std::error::Error>> {
+    initialize();
+
+    let output = output_path("broken_calculate.svg");
+    let mut cmd = Command::cargo_bin("vl-convert")?;
+    cmd.arg("vl2svg")
+        .arg("-i")
+        .arg(vl_spec_path("broken_calculate"))
+        .arg("-o")
+        .arg(&output)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Vega dataflow error in transform[2] (calculate) of layer[1]",
+        ));
+    Ok(())
+}
+
+#[test]
+fn test_lenient() -> Result<(), Box<dyn std::error::Error>> {
+    initialize();
+
+    let output = output_path("missing_local_image.png");
+    let mut cmd = Command::cargo_bin("vl-convert")?;
+    cmd.arg("vl2png")
+        .arg("-i")
+        .arg(vl_spec_path("missing_local_image"))
+        .arg("-o")
+        .arg(&output)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Local file access is not allowed"));
+
+    // The chart is written without the image
+    let mut cmd = Command::cargo_bin("vl-convert")?;
+    cmd.arg("vl2png")
+        .arg("-i")
+        .arg(vl_spec_path("missing_local_image"))
+        .arg("-o")
+        .arg(&output)
+        .arg("--lenient")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "vl-convert: warning: mark 'layer_1_marks': Local file access is not allowed",
+        ));
+    assert!(fs::read(&output)?.starts_with(b"\x89PNG"));
+
+    Ok(())
+}
+
+#[test]
+fn test_max_json_depth() -> Result<(), Box<dyn std::error::Error>> {
+    initialize();
+
+    // Generate a spec with deeply nested inline data
+    let depth = 100_000;
+    let mut spec_file = NamedTempFile::new()?;
+    write!(
+        spec_file,
+        r#"{{"mark": "point", "data": {{"values": {}{}}}}}"#,
+        "[".repeat(depth),
+        "]".repeat(depth)
+    )?;
+
+    let output = output_path("max_json_depth.vg.json");
+    let mut cmd = Command::cargo_bin("vl-convert")?;
+    let cmd = cmd
+        .arg("vl2vg")
+        .arg("-i")
+        .arg(spec_file.path())
+        .arg("-o")
+        .arg(&output);
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("max_json_depth limit of 1000"));
+
+    Ok(())
+}
+
+#[test]
+fn test_max_views() -> Result<(), Box<dyn std::error::Error>> {
+    initialize();
+
+    // 4 repeated fields, each faceted into 5 rows
+    let values: Vec<_> = (0..20)
+        .map(|i| serde_json::json!({"row": i % 5, "w": i, "x": i, "y": i, "z": i}))
+        .collect();
+    let spec = serde_json::json!({
+        "data": {"values": values},
+        "repeat": ["w", "x", "y", "z"],
+        "spec": {
+            "facet": {"row": {"field": "row"}},
+            "spec": {
+                "mark": "point",
+                "encoding": {"x": {"field": {"repeat": "repeat"}, "type": "quantitative"}}
+            }
+        }
+    });
+    let mut spec_file = NamedTempFile::new()?;
+    write!(spec_file, "{spec}")?;
+
+    let output = output_path("max_views.vg.json");
+    let mut cmd = Command::cargo_bin("vl-convert")?;
+    cmd.arg("vl2vg")
+        .arg("-i")
+        .arg(spec_file.path())
+        .arg("-o")
+        .arg(&output)
+        .arg("--max-views")
+        .arg("20")
+        .assert()
+        .success();
+
+    let mut cmd = Command::cargo_bin("vl-convert")?;
+    cmd.arg("vl2vg")
+        .arg("-i")
+        .arg(spec_file.path())
+        .arg("-o")
+        .arg(&output)
+        .arg("--max-views")
+        .arg("19")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "estimated 20 views, which exceeds the max_views limit of 19",
+        ));
+
+    Ok(())
+}
+
+#[rstest]
+#[case::zero_scale("svg2png", "--scale=0", "Image scale must be a finite number")]
+#[case::nan_scale("svg2png", "--scale=NaN", "Image scale must be a finite number")]
+#[case::negative_ppi("svg2png", "--ppi=-72", "Image ppi must be a finite number")]
+#[case::zero_quality("svg2jpeg", "--quality=0", "must be between 1 and 100")]
+#[case::large_quality("svg2jpeg", "--quality=101", "must be between 1 and 100")]
+#[case::invalid_jpeg_background(
+    "svg2jpeg",
+    "--jpeg-background=whiteish",
+    "Invalid background color"
+)]
+#[case::invalid_flatten_background(
+    "svg2png",
+    "--flatten-background=#ab",
+    "Invalid background color"
+)]
+fn test_invalid_raster_options(
+    #[case] subcommand: &str,
+    #[case] option: &str,
+    #[case] message: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    initialize();
+
+    let mut svg_file = NamedTempFile::new()?;
+    write!(
+        svg_file,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="20" height="10"/>"#
+    )?;
+
+    let output = output_path(&format!("invalid_raster_options.{subcommand}"));
+    let mut cmd = Command::cargo_bin("vl-convert")?;
+    let cmd = cmd
+        .arg(subcommand)
+        .arg("-i")
+        .arg(svg_file.path())
+        .arg("-o")
+        .arg(&output)
+        .arg(option);
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains(message));
+
+    Ok(())
+}
+
+#[test]
+fn test_jpeg_and_flatten_background() -> Result<(), Box<dyn std::error::Error>> {
+    initialize();
+
+    let mut transparent_svg = NamedTempFile::new()?;
+    write!(
+        transparent_svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="20" height="10"/>"#
+    )?;
+    let mut red_svg = NamedTempFile::new()?;
+    write!(
+        red_svg,
+        r##"<svg xmlns="http://www.w3.org/2000/svg" width="20" height="10"><rect width="20" height="10" fill="#ff0000"/></svg>"##
+    )?;
+
+    let convert = |subcommand: &str, svg: &Path, output: &str, args: &[&str]| {
+        let output = output_path(output);
+        Command::cargo_bin("vl-convert")
+            .unwrap()
+            .arg(subcommand)
+            .arg("-i")
+            .arg(svg)
+            .arg("-o")
+            .arg(&output)
+            .args(args)
+            .assert()
+            .success();
+        fs::read(&output).unwrap()
+    };
+
+    // A transparent image composited over red is encoded the same as an opaque red image
+    let red = convert("svg2jpeg", red_svg.path(), "background_red.jpg", &[]);
+    let composited = convert(
+        "svg2jpeg",
+        transparent_svg.path(),
+        "background_composited.jpg",
+        &["--jpeg-background", "red"],
+    );
+    let white = convert(
+        "svg2jpeg",
+        transparent_svg.path(),
+        "background_white.jpg",
+        &[],
+    );
+    assert_eq!(composited, red);
+    assert_ne!(white, red);
+
+    let red = convert("svg2png", red_svg.path(), "background_red.png", &[]);
+    let flattened = convert(
+        "svg2png",
+        transparent_svg.path(),
+        "background_flattened.png",
+        &["--flatten-background", "#f00"],
+    );
+    let transparent = convert(
+        "svg2png",
+        transparent_svg.path(),
+        "background_none.png",
+        &[],
+    );
+    assert_eq!(flattened, red);
+    assert_ne!(transparent, red);
+
+    Ok(())
+}
+
+#[test]
+fn test_explain_static() -> Result<(), Box<dyn std::error::Error>> {
+    initialize();
+
+    let mut brush_file = NamedTempFile::new()?;
+    write!(
+        brush_file,
+        r#"{{
+            "data": {{"values": [{{"a": 1, "b": 2}}]}},
+            "params": [{{"name": "brush", "select": "interval"}}],
+            "mark": "point",
+            "encoding": {{
+                "x": {{"field": "a", "type": "quantitative"}},
+                "y": {{"field": "b", "type": "quantitative"}}
+            }}
+        }}"#
+    )?;
+
+    let explain = |subcommand: &str, input: &str, output: &str, args: &[&str]| {
+        let result = Command::cargo_bin("vl-convert")
+            .unwrap()
+            .arg(subcommand)
+            .arg("-i")
+            .arg(input)
+            .arg("-o")
+            .arg(output_path(output))
+            .arg("--font-dir")
+            .arg(test_font_dir())
+            .arg("--explain-static")
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(result.status.success());
+        String::from_utf8(result.stderr).unwrap()
+    };
+
+    let brush_path = brush_file.path().to_str().unwrap();
+    let expected = "vl-convert: static export: unsupported[selection] /params/0: \
+        Selection \"brush\" has no effect in static output\n";
+    assert_eq!(explain("vl2svg", brush_path, "brush.svg", &[]), expected);
+    assert_eq!(explain("vl2png", brush_path, "brush.png", &[]), expected);
+    assert_eq!(explain("vl2png", brush_path, "brush.png", &["--quiet"]), "");
+
+    // A chart without interactive features has nothing to explain
+    let plain_path = vl_spec_path("circle_binned");
+    assert_eq!(explain("vl2svg", &plain_path, "plain.svg", &[]), "");
+
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn test_only_artifact_on_stdout() -> Result<(), Box<dyn std::error::Error>> {
+    initialize();
+
+    let vl_file = write_spec_with_warning()?;
+
+    let mut cmd = Command::cargo_bin("vl-convert")?;
+    let cmd = cmd
+        .arg("vl2svg")
+        .arg("-i")
+        .arg(vl_file.path())
+        .arg("-o")
+        .arg("/dev/stdout")
+        .arg("--show-warnings")
+        .arg("--log-level=trace")
+        .arg("--font-dir")
+        .arg(test_font_dir());
+    cmd.assert()
+        .success()
+        .stderr(predicate::str::contains("shape dropped"));
+
+    let output = cmd.output()?;
+    let stdout = String::from_utf8(output.stdout)?;
+    assert!(stdout.starts_with("<svg"), "{stdout}");
+    assert!(stdout.trim_end().ends_with("</svg>"), "{stdout}");
+    assert_eq!(stdout.matches("<svg").count(), 1, "{stdout}");
+
+    Ok(())
+}
+
+#[test]
+fn test_svg_minify() -> Result<(), Box<dyn std::error::Error>> {
+    initialize();
+
+    let output = output_path("5_8_circle_binned_minified.svg");
+    let mut cmd = Command::cargo_bin("vl-convert")?;
+    let cmd = cmd
+        .arg("vl2svg")
+        .arg("-i")
+        .arg(vl_spec_path("circle_binned"))
+        .arg("-o")
+        .arg(&output)
+        .arg("--vl-version")
+        .arg("5_8")
+        .arg("--font-dir")
+        .arg(test_font_dir())
+        .arg("--svg-minify")
+        .arg("--svg-precision")
+        .arg("2");
+    cmd.assert().success();
+
+    let minified = fs::read_to_string(&output)?;
+    let expected = load_expected_svg("circle_binned", "5_8");
+    assert!(minified.len() < expected.len());
+    assert!(minified.starts_with("<svg"), "{minified}");
+    assert!(!minified.contains("aria-"), "{minified}");
+    assert!(!minified.contains(" role="), "{minified}");
+
+    // The precision only applies to minified SVG
+    let mut cmd = Command::cargo_bin("vl-convert")?;
+    let cmd = cmd
+        .arg("vl2svg")
+        .arg("-i")
+        .arg(vl_spec_path("circle_binned"))
+        .arg("-o")
+        .arg(&output)
+        .arg("--svg-precision")
+        .arg("2");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("--svg-minify"));
+
+    Ok(())
+}
+
+/// Write a Vega-Lite spec that generates a compilation warning, because the shape channel
+/// isn't supported by bar marks
+fn write_spec_with_warning() -> Result<NamedTempFile, Box<dyn std::error::Error>> {
+    let mut vl_file = NamedTempFile::new()?;
+    write!(
+        vl_file,
+        r#"{{
+            "data": {{"values": [{{"a": 1}}]}},
+            "mark": "bar",
+            "encoding": {{
+                "x": {{"field": "a", "type": "quantitative"}},
+                "shape": {{"field": "a", "type": "nominal"}}
+            }}
+        }}"#
+    )?;
+    Ok(vl_file)
+}
+
+const SHAPE_WARNING: &str =
+    "vl-convert: warning: shape dropped as it is incompatible with \"bar\".\n";
+
+#[rstest]
+#[case::default(&[], "")]
+#[case::show_warnings(&["--show-warnings"], SHAPE_WARNING)]
+#[case::log_level(&["--log-level=warn"], SHAPE_WARNING)]
+#[case::quiet(&["--quiet"], "")]
+#[case::quiet_show_warnings(&["--quiet", "--show-warnings"], "")]
+#[case::quiet_log_level(&["--quiet", "--log-level=warn"], "")]
+#[case::quiet_all(&["--quiet", "--show-warnings", "--log-level=trace"], "")]
+fn test_stderr_output(
+    flags: &[&str],
+    expected_stderr: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    initialize();
+    let vl_file = write_spec_with_warning()?;
+    let output = NamedTempFile::new()?;
+
+    let mut cmd = Command::cargo_bin("vl-convert")?;
+    let cmd = cmd
+        .env_remove("RUST_LOG")
+        .arg("vl2vg")
+        .arg("-i")
+        .arg(vl_file.path())
+        .arg("-o")
+        .arg(output.path())
+        .args(flags);
+    let result = cmd.output()?;
+    assert!(result.status.success());
+    assert_eq!(String::from_utf8(result.stdout)?, "");
+    assert_eq!(String::from_utf8(result.stderr)?, expected_stderr);
+    Ok(())
+}
+
+#[rstest]
+#[case::default(&[], "")]
+#[case::show_warnings(&["--show-warnings"], SHAPE_WARNING)]
+#[case::quiet_show_warnings(&["--quiet", "--show-warnings"], "")]
+fn test_vl2html_stderr_output(
+    flags: &[&str],
+    expected_stderr: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    initialize();
+    let vl_file = write_spec_with_warning()?;
+    let output = NamedTempFile::new()?;
+
+    let mut cmd = Command::cargo_bin("vl-convert")?;
+    let cmd = cmd
+        .env_remove("RUST_LOG")
+        .arg("vl2html")
+        .arg("-i")
+        .arg(vl_file.path())
+        .arg("-o")
+        .arg(output.path())
+        .args(flags);
+    let result = cmd.output()?;
+    assert!(result.status.success());
+    assert_eq!(String::from_utf8(result.stderr)?, expected_stderr);
+    assert!(fs::read_to_string(output.path())?.starts_with("<!DOCTYPE html>"));
+    Ok(())
+}
+
+#[test]
+fn test_vl2html_static_fallback() -> Result<(), Box<dyn std::error::Error>> {
+    initialize();
+
+    let output = output_path("circle_binned_static_fallback.html");
+    let mut cmd = Command::cargo_bin("vl-convert")?;
+    let cmd = cmd
+        .arg("vl2html")
+        .arg("-i")
+        .arg(vl_spec_path("circle_binned"))
+        .arg("-o")
+        .arg(&output)
+        .arg("--static-fallback")
+        .arg("--static-fallback-scale")
+        .arg("1");
+    cmd.assert().success();
+
+    // The base64 encoded PNG signature starts the data URI in the chart div and in noscript
+    let html = fs::read_to_string(&output)?;
+    assert_eq!(html.matches("<noscript>").count(), 1);
+    assert_eq!(
+        html.matches(r#"<img src="data:image/png;base64,iVBORw0KGgo"#)
+            .count(),
+        2
+    );
+    Ok(())
+}
+
+#[test]
+fn test_verbose_image_size() -> Result<(), Box<dyn std::error::Error>> {
+    initialize();
+
+    for (scale, ppi) in [("1", "72"), ("2", "72"), ("1.5", "144")] {
+        let output = output_path(&format!("image_size_{scale}_{ppi}.png"));
+        let mut cmd = Command::cargo_bin("vl-convert")?;
+        let result = cmd
+            .arg("vl2png")
+            .arg("-i")
+            .arg(vl_spec_path("circle_binned"))
+            .arg("-o")
+            .arg(&output)
+            .arg("--scale")
+            .arg(scale)
+            .arg("--ppi")
+            .arg(ppi)
+            .arg("--font-dir")
+            .arg(test_font_dir())
+            .arg("--verbose")
+            .output()?;
+        assert!(result.status.success());
+
+        // The width and height are the first fields of the IHDR chunk
+        let png = fs::read(&output)?;
+        let width = u32::from_be_bytes(png[16..20].try_into()?);
+        let height = u32::from_be_bytes(png[20..24].try_into()?);
+        let stderr = String::from_utf8(result.stderr)?;
+        assert!(
+            stderr.starts_with(&format!("vl-convert: image is {width}x{height} pixels, ")),
+            "{stderr}"
+        );
+        assert!(
+            stderr.contains(&format!(" at scale {scale} and {ppi} ppi\n")),
+            "{stderr}"
+        );
+    }
+
+    let output = output_path("image_size.svg");
+    let mut cmd = Command::cargo_bin("vl-convert")?;
+    cmd.arg("vl2svg")
+        .arg("-i")
+        .arg(vl_spec_path("circle_binned"))
+        .arg("-o")
+        .arg(&output)
+        .arg("--font-dir")
+        .arg(test_font_dir())
+        .arg("--verbose")
+        .assert()
+        .success()
+        .stderr(predicate::str::is_match(
+            r"^vl-convert: image is [0-9.]+x[0-9.]+, with viewBox 0 0 [0-9.]+ [0-9.]+\n",
+        )?);
+    Ok(())
+}
+
+#[test]
+fn test_stderr_output_verbose() -> Result<(), Box<dyn std::error::Error>> {
+    initialize();
+    let vl_file = write_spec_with_warning()?;
+    let output = NamedTempFile::new()?;
+
+    let mut cmd = Command::cargo_bin("vl-convert")?;
+    let cmd = cmd
+        .env_remove("RUST_LOG")
+        .arg("vl2vg")
+        .arg("-i")
+        .arg(vl_file.path())
+        .arg("-o")
+        .arg(output.path())
+        .arg("--show-warnings")
+        .arg("--verbose");
+    let result = cmd.output()?;
+    assert!(result.status.success());
+    assert_eq!(String::from_utf8(result.stdout)?, "");
+
+    // Timing is written after any warnings
+    let stderr = String::from_utf8(result.stderr)?;
+    let seconds = stderr
+        .strip_prefix(SHAPE_WARNING)
+        .and_then(|rest| rest.strip_prefix("vl-convert: finished in "))
+        .and_then(|rest| rest.strip_suffix("s\n"));
+    assert!(
+        seconds.is_some_and(|seconds| f64::from_str(seconds).is_ok()),
+        "{stderr}"
+    );
+
+    // Errors are written even when quiet
+    let mut cmd = Command::cargo_bin("vl-convert")?;
+    let cmd = cmd
+        .arg("vl2vg")
+        .arg("-i")
+        .arg(vl_spec_path("does_not_exist"))
+        .arg("-o")
+        .arg(output.path())
+        .arg("--quiet");
+    cmd.assert()
+        .failure()
+        .stdout("")
+        .stderr(predicate::str::contains("Failed to read input file"));
+
+    // Quiet and verbose can't be combined
+    let mut cmd = Command::cargo_bin("vl-convert")?;
+    let cmd = cmd
+        .arg("vl2vg")
+        .arg("-i")
+        .arg(vl_file.path())
+        .arg("-o")
+        .arg(output.path())
+        .arg("--quiet")
+        .arg("--verbose");
+    cmd.assert().failure();
+    Ok(())
+}
+
+#[test]
+fn test_empty_dataset_warning() -> Result<(), Box<dyn std::error::Error>> {
+    initialize();
+
+    // The filter removes every row, so the chart is empty
+    let mut vl_file = NamedTempFile::new()?;
+    write!(
+        vl_file,
+        r#"{{
+            "data": {{"values": [{{"a": "A", "b": 28}}, {{"a": "B", "b": 55}}]}},
+            "transform": [{{"filter": "datum.b > 100"}}],
+            "mark": "bar",
+            "encoding": {{
+                "x": {{"field": "a", "type": "nominal"}},
+                "y": {{"field": "b", "type": "quantitative"}}
+            }}
+        }}"#
+    )?;
+    let output = output_path("empty_dataset.svg");
+    let warning = "vl-convert: warning: dataset 'data_0' has 0 rows; chart may be empty\n";
+
+    let mut cmd = Command::cargo_bin("vl-convert")?;
+    let cmd = cmd
+        .env_remove("RUST_LOG")
+        .arg("vl2svg")
+        .arg("-i")
+        .arg(vl_file.path())
+        .arg("-o")
+        .arg(&output)
+        .arg("--font-dir")
+        .arg(test_font_dir());
+    cmd.assert().success().stdout("").stderr(warning);
+
+    // The warning is suppressed with --quiet
+    cmd.arg("--quiet");
+    cmd.assert().success().stdout("").stderr("");
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn test_local_data() -> Result<(), Box<dyn std::error::Error>> {
+    initialize();
+
+    // A spec next to a data/ folder, converted from another working directory
+    let dir = tempfile::tempdir()?;
+    let charts_dir = dir.path().join("charts");
+    fs::create_dir_all(charts_dir.join("data"))?;
+    fs::write(
+        charts_dir.join("data").join("local.csv"),
+        "category,amount\nLocal A,28\nLocal B,55\n",
+    )?;
+    fs::write(
+        charts_dir.join("chart.vl.json"),
+        r#"{
+            "data": {"url": "data/local.csv"},
+            "mark": "bar",
+            "encoding": {
+                "x": {"field": "category", "type": "nominal"},
+                "y": {"field": "amount", "type": "quantitative"}
+            }
+        }"#,
+    )?;
+    let output = output_path("local_data.svg");
+
+    let mut cmd = Command::cargo_bin("vl-convert")?;
+    let cmd = cmd
+        .current_dir(dir.path())
+        .arg("vl2svg")
+        .arg("-i")
+        .arg(Path::new("charts").join("chart.vl.json"))
+        .arg("-o")
+        .arg(&output)
+        .arg("--allow-local-data")
+        .arg("--font-dir")
+        .arg(test_font_dir());
+    cmd.assert().success();
+    let svg = fs::read_to_string(&output)?;
+    assert!(svg.contains("Local A"), "{svg}");
+
+    // Local files can't be read without --allow-local-data, even with a file base URL
+    let mut cmd = Command::cargo_bin("vl-convert")?;
+    let cmd = cmd
+        .current_dir(dir.path())
+        .arg("vl2svg")
+        .arg("-i")
+        .arg(Path::new("charts").join("chart.vl.json"))
+        .arg("-o")
+        .arg(&output)
+        .arg("--base-url")
+        .arg(format!("file://{}/", charts_dir.display()))
+        .arg("--font-dir")
+        .arg(test_font_dir());
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Local file access is not allowed"));
+    Ok(())
+}
+
+#[test]
+fn test_vl2labels() -> Result<(), Box<dyn std::error::Error>> {
+    initialize();
+
+    let mut cmd = Command::cargo_bin("vl-convert")?;
+    let cmd = cmd
+        .arg("vl2labels")
+        .arg("-i")
+        .arg(vl_spec_path("circle_binned"))
+        .arg("--font-dir")
+        .arg(test_font_dir());
+    cmd.assert().success();
+
+    // Each line is a JSON text item
+    let output = cmd.output().unwrap();
+    let output_str = String::from_utf8(output.stdout).unwrap();
+    let text_items = output_str
+        .lines()
+        .map(serde_json::Value::from_str)
+        .collect::<Result<Vec<_>, _>>()?;
+    assert!(text_items
+        .iter()
+        .any(|item| item["role"] == "axis-title" && item["text"] == "IMDB Rating (binned)"));
+
+    Ok(())
+}
+
+#[test]
+fn test_vg2stats() -> Result<(), Box<dyn std::error::Error>> {
+    initialize();
+
+    let input = output_path("three_rects.vg.json");
+    fs::write(
+        &input,
+        r#"{
+            "width": 100,
+            "height": 100,
+            "data": [{"name": "table", "values": [{"x": 0}, {"x": 30}, {"x": 60}]}],
+            "marks": [{
+                "type": "rect",
+                "from": {"data": "table"},
+                "encode": {"enter": {
+                    "x": {"field": "x"}, "width": {"value": 20}, "height": {"value": 20}
+                }}
+            }]
+        }"#,
+    )?;
+    let mut cmd = Command::cargo_bin("vl-convert")?;
+    let cmd = cmd.arg("vg2stats").arg("-i").arg(&input);
+    cmd.assert().success();
+
+    let output = cmd.output().unwrap();
+    let stats = serde_json::Value::from_str(&String::from_utf8(output.stdout)?)?;
+    assert_eq!(stats["item_counts_by_marktype"]["rect"], 3);
+    assert_eq!(stats["group_count"], 1);
+    assert_eq!(stats["text_item_count"], 0);
+    // The root group mark and item, and the rect mark and its items
+    assert_eq!(stats["total_scenegraph_nodes"], 6);
+
+    Ok(())
+}
+
+#[test]
+fn test_vl2facets() -> Result<(), Box<dyn std::error::Error>> {
+    initialize();
+
+    // The chart has a row facet on a, with values a1 to a3, and a column facet on b, with
+    // values b1 to b3
+    let input = vl_spec_path("bar_chart_trellis_compact");
+    let output = output_path("trellis_{row}_{column}.png");
+    let mut cmd = Command::cargo_bin("vl-convert")?;
+    let cmd = cmd
+        .arg("vl2facets")
+        .arg("-i")
+        .arg(&input)
+        .arg("-o")
+        .arg(&output);
+    cmd.assert().success();
+
+    let mut sizes = Vec::new();
+    for row in ["a1", "a2", "a3"] {
+        for column in ["b1", "b2", "b3"] {
+            let png = fs::read(output_path(&format!("trellis_{row}_{column}.png")))?;
+            assert!(png.starts_with(b"\x89PNG"));
+            // Width and height of the IHDR chunk
+            sizes.push(png[16..24].to_vec());
+        }
+    }
+    assert!(sizes.iter().all(|size| size == &sizes[0]));
+
+    // Output paths without a placeholder would write every cell to the same file
+    let mut cmd = Command::cargo_bin("vl-convert")?;
+    let cmd = cmd
+        .arg("vl2facets")
+        .arg("-i")
+        .arg(&input)
+        .arg("-o")
+        .arg(output_path("trellis.png"));
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("must contain {row} or {column}"));
+
+    Ok(())
+}
+
+#[test]
+fn test_report() -> Result<(), Box<dyn std::error::Error>> {
+    initialize();
+
+    let output = output_path("report.html");
+    let mut cmd = Command::cargo_bin("vl-convert")?;
+    let cmd = cmd
+        .arg("report")
+        .arg("--input")
+        .arg(vl_spec_path("circle_binned"))
+        .arg("--input")
+        .arg(vl_spec_path("stacked_bar_h"))
+        .arg("--title")
+        .arg("A")
+        .arg("--title")
+        .arg("B")
+        .arg("-o")
+        .arg(&output);
+    cmd.assert().success();
+
+    let html = fs::read_to_string(&output)?;
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert_eq!(html.matches("cdn.jsdelivr.net/npm/vega-embed@6").count(), 1);
+    assert!(html.contains("<h2>A</h2>"));
+    assert!(html.contains("<h2>B</h2>"));
+    assert!(html.contains("id=\"vega-chart-0\""));
+    assert!(html.contains("id=\"vega-chart-1\""));
+
+    // Every title must correspond to an input
+    let mut cmd = Command::cargo_bin("vl-convert")?;
+    let cmd = cmd
+        .arg("report")
+        .arg("--input")
+        .arg(vl_spec_path("circle_binned"))
+        .arg("--title")
+        .arg("A")
+        .arg("--title")
+        .arg("B")
+        .arg("-o")
+        .arg(&output);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Received 2 titles for 1 inputs"));
+
+    Ok(())
+}
+
+#[test]
+fn test_vl2html_template_file() -> Result<(), Box<dyn std::error::Error>> {
+    initialize();
+
+    let template = output_path("template.html");
+    fs::write(
+        &template,
+        "<head>{{ scripts }}</head><div id=\"{{ element_id }}\"></div><script>\
+         vegaEmbed(\"#{{ element_id }}\", {{ spec }}, {{ embed_options }});</script>\
+         <footer>{{ footer }}</footer>",
+    )?;
+    let output = output_path("template_circle_binned.html");
+    let mut cmd = Command::cargo_bin("vl-convert")?;
+    let cmd = cmd
+        .arg("vl2html")
+        .arg("-i")
+        .arg(vl_spec_path("circle_binned"))
+        .arg("-o")
+        .arg(&output)
+        .arg("--template-file")
+        .arg(&template);
+    cmd.assert().success();
+
+    let html = fs::read_to_string(&output)?;
+    assert!(html.starts_with("<head><script src=\"https://cdn.jsdelivr.net/npm/vega@5\">"));
+    assert!(html.contains("vegaEmbed(\"#vega-chart\", {\"$schema\""));
+    assert!(html.ends_with("<footer>{{ footer }}</footer>"));
+
+    // Templates must have the required placeholders
+    fs::write(&template, "<div>{{ spec }}</div>")?;
+    let mut cmd = Command::cargo_bin("vl-convert")?;
+    let cmd = cmd
+        .arg("vl2html")
+        .arg("-i")
+        .arg(vl_spec_path("circle_binned"))
+        .arg("-o")
+        .arg(&output)
+        .arg("--template-file")
+        .arg(&template);
+    cmd.assert().failure().stderr(predicate::str::contains(
+        "missing required placeholders: {{ scripts }}, {{ element_id }}, {{ embed_options }}",
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_stats() -> Result<(), Box<dyn std::error::Error>> {
+    initialize();
+
+    let output = output_path("stats_circle_binned.png");
+    let mut cmd = Command::cargo_bin("vl-convert")?;
+    let cmd = cmd
+        .arg("vl2png")
+        .arg("-i")
+        .arg(vl_spec_path("circle_binned"))
+        .arg("-o")
+        .arg(&output)
+        .arg("--font-dir")
+        .arg(test_font_dir())
+        .arg("--stats");
+    cmd.assert().success();
+
+    // Stats are printed to stderr as a JSON object
+    let output = cmd.output().unwrap();
+    let stderr_str = String::from_utf8(output.stderr).unwrap();
+    let stats = serde_json::Value::from_str(stderr_str.trim())?;
+    assert!(stats["v8_heap_used_bytes"].as_u64().unwrap() > 0);
+    assert!(stats["svg_bytes"].as_u64().unwrap() > 0);
+    assert!(stats["pixmap_bytes"].as_u64().unwrap() > 0);
+    assert!(stats["peak_rss_delta_bytes"].is_u64());
+
+    Ok(())
+}
+
+#[test]
+fn test_dump_final_spec() -> Result<(), Box<dyn std::error::Error>> {
+    initialize();
+
+    let name = "stacked_bar_h";
+    let output_png = output_path(&format!("{name}_final.png"));
+    let output_spec = output_path(&format!("{name}_final.vg.json"));
+    let mut cmd = Command::cargo_bin("vl-convert")?;
+    let cmd = cmd
+        .arg("vl2png")
+        .arg("-i")
+        .arg(vl_spec_path(name))
+        .arg("-o")
+        .arg(&output_png)
+        .arg("--theme")
+        .arg("dark")
+        .arg("--format-locale")
+        .arg("de-DE")
+        .arg("--font-dir")
+        .arg(test_font_dir())
+        .arg("--dump-final-spec")
+        .arg(&output_spec);
+    cmd.assert().success();
+
+    // The dumped spec renders the same image without the theme and locale options
+    let output_vg_png = output_path(&format!("{name}_final.vg.png"));
+    let mut cmd = Command::cargo_bin("vl-convert")?;
+    let cmd = cmd
+        .arg("vg2png")
+        .arg("-i")
+        .arg(&output_spec)
+        .arg("-o")
+        .arg(&output_vg_png)
+        .arg("--font-dir")
+        .arg(test_font_dir());
+    cmd.assert().success();
+
+    assert_eq!(fs::read(&output_png)?, fs::read(&output_vg_png)?);
+    Ok(())
+}
+
+#[test]
+fn test_export_data() -> Result<(), Box<dyn std::error::Error>> {
+    initialize();
+
+    let dir = tempfile::tempdir()?;
+    let mut cmd = Command::cargo_bin("vl-convert")?;
+    cmd.arg("vl2png")
+        .arg("-i")
+        .arg(vl_spec_path("aggregated_totals"))
+        .arg("-o")
+        .arg(output_path("aggregated_totals.png"))
+        .arg("--export-data")
+        .arg(dir.path())
+        .assert()
+        .success();
+
+    // The aggregated rows that the bars are drawn from, rather than the input rows
+    assert_eq!(
+        fs::read_to_string(dir.path().join("aggregated_totals.data_0.csv"))?,
+        "category,date,total,total_start,total_end\n\
+         A,2024-01-15T00:00:00.000Z,30,0,30\n\
+         B,2024-06-15T00:00:00.000Z,55,0,55\n"
+    );
+    assert!(dir.path().join("aggregated_totals.source_0.csv").exists());
+
+    let mut cmd = Command::cargo_bin("vl-convert")?;
+    cmd.arg("export")
+        .arg("-i")
+        .arg(vl_spec_path("aggregated_totals"))
+        .arg("-o")
+        .arg(output_path("aggregated_totals_export"))
+        .arg("--formats")
+        .arg("svg")
+        .arg("--export-data")
+        .arg(dir.path())
+        .arg("--export-data-format")
+        .arg("json")
+        .assert()
+        .success();
+
+    let rows: serde_json::Value = serde_json::from_str(&fs::read_to_string(
+        dir.path().join("aggregated_totals_export.data_0.json"),
+    )?)?;
+    assert_eq!(
+        rows[1],
+        serde_json::json!({
+            "category": "B",
+            "date": "2024-06-15T00:00:00.000Z",
+            "total": 55,
+            "total_start": 0,
+            "total_end": 55
+        })
+    );
+    Ok(())
+}
+
+#[test]
+fn test_multiple_themes() -> Result<(), Box<dyn std::error::Error>> {
+    initialize();
+
+    let dir = tempfile::tempdir()?;
+    let mut cmd = Command::cargo_bin("vl-convert")?;
+    cmd.arg("vl2png")
+        .arg("-i")
+        .arg(vl_spec_path("stacked_bar_h"))
+        .arg("-o")
+        .arg(dir.path().join("chart.png"))
+        .arg("--theme")
+        .arg("default")
+        .arg("--theme")
+        .arg("dark")
+        .arg("--font-dir")
+        .arg(test_font_dir())
+        .assert()
+        .success();
+    assert!(!dir.path().join("chart.png").exists());
+    let default_png = fs::read(dir.path().join("chart.default.png"))?;
+    let dark_png = fs::read(dir.path().join("chart.dark.png"))?;
+    assert_ne!(default_png, dark_png);
+
+    // Each image matches the image that's converted with its theme alone
+    let mut cmd = Command::cargo_bin("vl-convert")?;
+    cmd.arg("vl2png")
+        .arg("-i")
+        .arg(vl_spec_path("stacked_bar_h"))
+        .arg("-o")
+        .arg(dir.path().join("dark.png"))
+        .arg("--theme")
+        .arg("dark")
+        .arg("--font-dir")
+        .arg(test_font_dir())
+        .assert()
+        .success();
+    assert_eq!(dark_png, fs::read(dir.path().join("dark.png"))?);
+
+    let mut cmd = Command::cargo_bin("vl-convert")?;
+    cmd.arg("vl2svg")
+        .arg("-i")
+        .arg(vl_spec_path("stacked_bar_h"))
+        .arg("-o")
+        .arg(dir.path().join("chart.svg"))
+        .arg("--theme")
+        .arg("default")
+        .arg("--theme")
+        .arg("dark")
+        .assert()
+        .success();
+    assert!(dir.path().join("chart.default.svg").exists());
+    assert!(dir.path().join("chart.dark.svg").exists());
+    Ok(())
+}
+
+#[test]
+fn test_container_size() -> Result<(), Box<dyn std::error::Error>> {
+    initialize();
+
+    let dir = tempfile::tempdir()?;
+    let input = dir.path().join("container.vl.json");
+    fs::write(
+        &input,
+        r#"{
+            "data": {"values": [{"a": "A", "b": 28}, {"a": "B", "b": 55}]},
+            "mark": "bar",
+            "width": "container",
+            "height": "container",
+            "encoding": {
+                "x": {"field": "a", "type": "nominal"},
+                "y": {"field": "b", "type": "quantitative"}
+            }
+        }"#,
+    )?;
+    let output = output_path("container_size.svg");
+
+    let mut cmd = Command::cargo_bin("vl-convert")?;
+    cmd.arg("vl2svg")
+        .arg("-i")
+        .arg(&input)
+        .arg("-o")
+        .arg(&output)
+        .arg("--container-width")
+        .arg("500")
+        .arg("--container-height")
+        .arg("300")
+        .arg("--font-dir")
+        .arg(test_font_dir())
+        .assert()
+        .success();
+    let svg = fs::read_to_string(&output)?;
+    assert!(svg.contains(r#"width="500""#), "{svg}");
+    assert!(svg.contains(r#"height="300""#), "{svg}");
+
+    let mut cmd = Command::cargo_bin("vl-convert")?;
+    cmd.arg("vl2svg")
+        .arg("-i")
+        .arg(&input)
+        .arg("-o")
+        .arg(&output)
+        .arg("--strict-sizing")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("sized to its container"));
+    Ok(())
+}
+
+#[test]
+fn test_anonymize() -> Result<(), Box<dyn std::error::Error>> {
+    initialize();
+
+    let dir = tempfile::tempdir()?;
+    let input = dir.path().join("confidential.vl.json");
+    fs::write(
+        &input,
+        r#"{
+            "data": {"values": [
+                {"customer": "Acme Corp", "region": "North", "revenue": 1200},
+                {"customer": "Globex", "region": "North", "revenue": 800},
+                {"customer": "Initech", "region": "South", "revenue": 450}
+            ]},
+            "mark": "bar",
+            "encoding": {
+                "x": {"field": "region", "type": "nominal"},
+                "y": {"field": "revenue", "aggregate": "sum", "type": "quantitative"}
+            }
+        }"#,
+    )?;
+    let output = dir.path().join("anon.vl.json");
+    let mut cmd = Command::cargo_bin("vl-convert")?;
+    cmd.arg("anonymize")
+        .arg("-i")
+        .arg(&input)
+        .arg("-o")
+        .arg(&output)
+        .arg("--salt")
+        .arg("bug-report")
+        .assert()
+        .success();
+
+    let anonymized = fs::read_to_string(&output)?;
+    for value in ["Acme Corp", "Globex", "Initech", "North", "South"] {
+        assert!(!anonymized.contains(value), "{anonymized}");
+    }
+    let spec: serde_json::Value = serde_json::from_str(&anonymized)?;
+    let values = spec["data"]["values"].as_array().unwrap();
+    assert_eq!(values[0]["region"], values[1]["region"]);
+    assert!(values[0].get("customer").is_some() && values[0].get("revenue").is_some());
+
+    // The anonymized spec still converts
+    let vega_output = dir.path().join("anon.vg.json");
+    let mut cmd = Command::cargo_bin("vl-convert")?;
+    cmd.arg("vl2vg")
+        .arg("-i")
+        .arg(&output)
+        .arg("-o")
+        .arg(&vega_output)
+        .assert()
+        .success();
+
+    Ok(())
+}
+
+#[test]
+fn test_lint() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::tempdir()?;
+    let input = dir.path().join("lint.vg.json");
+    fs::write(
+        &input,
+        r#"{
+            "data": [
+                {"name": "table", "values": [{"a": 1}]},
+                {"name": "unused", "values": [{"a": 1}]}
+            ],
+            "scales": [{"name": "xscale", "domain": {"data": "table", "field": "a"}}],
+            "marks": [{
+                "type": "rect",
+                "from": {"data": "table"},
+                "encode": {"update": {"x": {"scale": "x_scale", "field": "a"}}}
+            }]
+        }"#,
+    )?;
+
+    let mut cmd = Command::cargo_bin("vl-convert")?;
+    cmd.arg("lint")
+        .arg("-i")
+        .arg(&input)
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains(
+            "error[undefined-scale] /marks/0/encode/update/x/scale: Scale \"x_scale\" is not defined",
+        ))
+        .stdout(predicate::str::contains(
+            "warning[unused-dataset] /data/1/name: Dataset \"unused\" is never used",
+        ))
+        .stderr(predicate::str::contains("Found 1 error(s) and 1 warning(s)"));
+
+    // Warnings alone only fail with --deny-warnings
+    let spec = fs::read_to_string(&input)?.replace("x_scale", "xscale");
+    fs::write(&input, spec)?;
+    let mut cmd = Command::cargo_bin("vl-convert")?;
+    cmd.arg("lint").arg("-i").arg(&input).assert().success();
+    let mut cmd = Command::cargo_bin("vl-convert")?;
+    cmd.arg("lint")
+        .arg("-i")
+        .arg(&input)
+        .arg("--deny-warnings")
+        .assert()
+        .failure();
+    Ok(())
+}
+
+#[test]
+fn test_svg_diff() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::tempdir()?;
+    let a = dir.path().join("a.svg");
+    let b = dir.path().join("b.svg");
+    fs::write(
+        &a,
+        r#"<svg><g><rect x="1" y="2"/><text x="0">Sales</text></g></svg>"#,
+    )?;
+    fs::write(
+        &b,
+        r#"<svg><g><rect x="1.2" y="2"/><text x="0">Sales</text></g></svg>"#,
+    )?;
+
+    let mut cmd = Command::cargo_bin("vl-convert")?;
+    cmd.arg("svg-diff")
+        .arg(&a)
+        .arg(&b)
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains(
+            "attribute /svg[0]/g[0]/rect[0]/@x: 1 -> 1.2",
+        ))
+        .stderr(predicate::str::contains("Found 1 difference(s)"));
+
+    let mut cmd = Command::cargo_bin("vl-convert")?;
+    cmd.arg("svg-diff")
+        .arg(&a)
+        .arg(&b)
+        .arg("--tolerance")
+        .arg("0.5")
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_verify_vendor() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("vl-convert")?;
+    cmd.arg("verify-vendor")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "vendored JavaScript modules against the vendor manifest",
+        ));
+    Ok(())
+}
+
+mod test_response_files {
+    use crate::*;
+
+    #[test]
+    fn test_expansion() -> Result<(), Box<dyn std::error::Error>> {
+        initialize();
+
+        let output = output_path("response_file.vg.json");
+        let mut args_file = NamedTempFile::new()?;
+        writeln!(args_file, "# Convert circle_binned")?;
+        writeln!(args_file, "vl2vg")?;
+        writeln!(args_file, "--vl-version=5.8")?;
+        writeln!(args_file, "--input\n{}", vl_spec_path("circle_binned"))?;
+        writeln!(args_file, "\n--output\n\"{}\"", output)?;
+
+        let mut cmd = Command::cargo_bin("vl-convert")?;
+        let cmd = cmd.arg(format!("@{}", args_file.path().display()));
+        cmd.assert().success();
+
+        let expected = load_expected_vg_spec("circle_binned", "5.8", false).unwrap();
+        assert_eq!(expected, fs::read_to_string(&output)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_nested() -> Result<(), Box<dyn std::error::Error>> {
+        initialize();
+
+        let dir = tempfile::tempdir()?;
+        let output = output_path("nested_response_file.vg.json");
+        fs::write(
+            dir.path().join("input.txt"),
+            format!("--input\n{}\n", vl_spec_path("circle_binned")),
+        )?;
+        fs::write(
+            dir.path().join("args.txt"),
+            format!("vl2vg\n@input.txt\n--output\n{}\n", output),
+        )?;
+
+        let mut cmd = Command::cargo_bin("vl-convert")?;
+        let cmd = cmd.arg(format!("@{}", dir.path().join("args.txt").display()));
+        cmd.assert().success();
+        assert!(Path::new(&output).exists());
+        Ok(())
+    }
+
+    #[test]
+    fn test_missing_file() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("vl-convert")?;
+        let cmd = cmd.arg("@does-not-exist.txt");
+        cmd.assert().failure().stderr(predicate::str::contains(
+            "Failed to read response file does-not-exist.txt",
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_options_json_precedence() -> Result<(), Box<dyn std::error::Error>> {
+        initialize();
+
+        let json_output = output_path("options_json.vg.json");
+        let flag_output = output_path("options_flag.vg.json");
+        let _ = fs::remove_file(&json_output);
+
+        let mut options_file = NamedTempFile::new()?;
+        write!(
+            options_file,
+            "{}",
+            serde_json::json!({
+                "input": vl_spec_path("circle_binned"),
+                "output": json_output,
+                "vl_version": "5.8",
+                "pretty": true,
+            })
+        )?;
+
+        // The --output flag takes precedence over the output option
+        let mut cmd = Command::cargo_bin("vl-convert")?;
+        let cmd = cmd
+            .arg("vl2vg")
+            .arg("--output")
+            .arg(&flag_output)
+            .arg("--options-json")
+            .arg(options_file.path());
+        cmd.assert().success();
+
+        let expected = load_expected_vg_spec("circle_binned", "5.8", true).unwrap();
+        assert_eq!(expected, fs::read_to_string(&flag_output)?);
+        assert!(!Path::new(&json_output).exists());
+        Ok(())
+    }
+}